@@ -3,16 +3,35 @@
 //! Provides a unified context that combines CLI arguments with
 //! lazily-loaded security configuration.
 
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
 use once_cell::unsync::OnceCell;
 
-use ledger_core::storage::AgeSqliteStorage;
+use ledger_core::fs::path_risks;
+use ledger_core::storage::{AgeSqliteStorage, EntryOrigin, SearchTokenizer, StorageEngine};
+use ledger_core::VERSION;
 
 use crate::cli::Cli;
+use crate::collectors::expand_tilde;
+use crate::config::{
+    config_version, migrate_config_file, read_config, unknown_config_keys, LedgerConfig,
+    ThemeConfig, CONFIG_VERSION,
+};
+use crate::errors::warn;
+use crate::helpers::{parse_search_tokenizer, user_prompts_path, PromptSet};
+use crate::output::{Locale, SUPPORTED_LOCALES};
+use crate::replica::spawn_replica_sync;
+use crate::ui::theme::{Palette, ThemeColor, ThemeName};
 use crate::ui::UiContext;
 
-use super::passphrase::open_storage_with_retry;
+use super::passphrase::{open_storage_with_retry, resolve_unlock_passphrase};
+use super::resolver::{resolve_config_path, resolve_ledger_path};
 use super::security_config::{load_security_config, SecurityConfig};
 
+/// Tags suggested at once unless `[tags] suggest_limit` says otherwise.
+const DEFAULT_TAG_SUGGEST_LIMIT: usize = 3;
+
 /// Application context that bundles CLI args with security configuration.
 ///
 /// This avoids repeatedly loading config and threading multiple parameters
@@ -20,6 +39,12 @@ use super::security_config::{load_security_config, SecurityConfig};
 pub struct AppContext<'a> {
     cli: &'a Cli,
     security_config: OnceCell<SecurityConfig>,
+    config: OnceCell<Option<LedgerConfig>>,
+    passphrase: OnceCell<String>,
+    location_checked: OnceCell<()>,
+    theme: OnceCell<(ThemeName, Palette)>,
+    command: Option<String>,
+    read_only: bool,
 }
 
 impl<'a> AppContext<'a> {
@@ -28,9 +53,42 @@ impl<'a> AppContext<'a> {
         Self {
             cli,
             security_config: OnceCell::new(),
+            config: OnceCell::new(),
+            passphrase: OnceCell::new(),
+            location_checked: OnceCell::new(),
+            theme: OnceCell::new(),
+            command: None,
+            read_only: false,
         }
     }
 
+    /// Record the subcommand being run (e.g. `secret set`) as the origin
+    /// of entries it writes.
+    pub fn with_command(mut self, command: Option<String>) -> Self {
+        self.command = command;
+        self
+    }
+
+    /// Never write: `save_storage` drops changes and unlocking leaves the
+    /// session cache, keychain and lockout counter alone, as for the
+    /// read-only `ledger-viewer` binary.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Origin recorded on entries written by this invocation.
+    pub fn entry_origin(&self) -> EntryOrigin {
+        let mut origin = EntryOrigin::new("ledger-cli").with_version(VERSION);
+        if let Some(ref command) = self.command {
+            origin = origin.with_command(command.clone());
+        }
+        if let Some(hostname) = hostname() {
+            origin = origin.with_hostname(hostname);
+        }
+        origin
+    }
+
     /// Get the CLI arguments.
     pub fn cli(&self) -> &Cli {
         self.cli
@@ -47,17 +105,333 @@ impl<'a> AppContext<'a> {
             .get_or_try_init(|| load_security_config(self.cli))
     }
 
+    /// Get the full config file, loading it lazily if needed.
+    ///
+    /// `secret://name` values are resolved from the ledger's secret store,
+    /// unlocking the ledger if the config references any secrets.
+    /// Returns `None` when no config file exists yet.
+    pub fn config(&self) -> anyhow::Result<Option<&LedgerConfig>> {
+        let config = self.config.get_or_try_init(|| {
+            let config_path = resolve_config_path()?;
+            if !config_path.exists() {
+                return Ok(None);
+            }
+            let config = read_config(&config_path)?;
+            if config.secret_refs()?.is_empty() {
+                return Ok(Some(config));
+            }
+            let (storage, _passphrase) = self.open_storage(false)?;
+            config
+                .resolve_secrets(|name| Ok(storage.get_secret(name)?))
+                .map(Some)
+        })?;
+        Ok(config.as_ref())
+    }
+
+    /// Warn about config keys that would otherwise be silently ignored.
+    pub fn check_config_keys(&self) -> anyhow::Result<()> {
+        let config_path = resolve_config_path()?;
+        if !config_path.exists() {
+            return Ok(());
+        }
+        // A config that fails to parse is reported by whatever reads it.
+        // The viewer never writes, so it only reports what `ledger` would do.
+        let write = !self.dry_run() && !self.read_only;
+        if let Ok(Some(migration)) = migrate_config_file(&config_path, write) {
+            if !self.quiet() {
+                let verb = if !write { "Would migrate" } else { "Migrated" };
+                eprintln!(
+                    "{} config {} from version {} to {} (backup: {})",
+                    verb,
+                    config_path.display(),
+                    migration.from,
+                    migration.to,
+                    migration.backup.display()
+                );
+                for (old, new) in &migration.renamed {
+                    eprintln!("  {} -> {}", old, new);
+                }
+            }
+        }
+        let Ok(contents) = std::fs::read_to_string(&config_path) else {
+            return Ok(());
+        };
+        let Ok(table) = toml::from_str::<toml::Table>(&contents) else {
+            return Ok(());
+        };
+        let version = config_version(&table);
+        if version > CONFIG_VERSION {
+            self.warn(&format!(
+                "Config {} has config_version {}, newer than this ledger supports ({})\n\
+                 Hint: Upgrade ledger; settings it does not know are ignored.",
+                config_path.display(),
+                version,
+                CONFIG_VERSION
+            ))?;
+        }
+        let Ok(keys) = unknown_config_keys(&contents) else {
+            return Ok(());
+        };
+        for key in keys {
+            self.warn(&format!(
+                "Unknown config key in {}: {}",
+                config_path.display(),
+                key
+            ))?;
+        }
+        let Some(theme) = configured_theme() else {
+            return Ok(());
+        };
+        if let Some(name) = theme
+            .preset()
+            .filter(|name| ThemeName::parse(name).is_none())
+        {
+            self.warn(&format!(
+                "Unsupported theme in config: {}\nHint: Use one of {}.",
+                name,
+                ThemeName::ALL.map(|t| t.as_str()).join(", ")
+            ))?;
+        }
+        for (key, value) in theme.colors() {
+            if ThemeColor::parse(&value).is_none() {
+                self.warn(&format!(
+                    "Unsupported color in config: ui.theme.{} = {}\n\
+                     Hint: Use a color name like \"cyan\", an ANSI index 0-255 or \"#rrggbb\".",
+                    key, value
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The `[ui] theme` from config, or the default if unset or unsupported.
+    ///
+    /// Read straight from the file: a theme never needs secrets resolved, and
+    /// rendering must not prompt for a passphrase.
+    pub fn theme(&self) -> ThemeName {
+        self.theme_and_palette().0
+    }
+
+    /// The theme's preset palette with any `[ui.theme]` colors applied;
+    /// colors that don't parse keep the preset's.
+    pub fn palette(&self) -> Palette {
+        self.theme_and_palette().1
+    }
+
+    fn theme_and_palette(&self) -> (ThemeName, Palette) {
+        *self.theme.get_or_init(|| {
+            let Some(config) = configured_theme() else {
+                return (ThemeName::default(), Palette::default());
+            };
+            let theme = config
+                .preset()
+                .and_then(ThemeName::parse)
+                .unwrap_or_default();
+            let mut palette = Palette::preset(theme);
+            for (key, value) in config.colors() {
+                if let Some(color) = ThemeColor::parse(&value) {
+                    palette.set(key, color);
+                }
+            }
+            (theme, palette)
+        })
+    }
+
     /// Get the configured editor override, if any.
     pub fn editor(&self) -> anyhow::Result<Option<&str>> {
-        Ok(self.security_config()?.editor.as_deref())
+        Ok(self
+            .config()?
+            .and_then(|config| config.ui.editor.as_deref()))
+    }
+
+    /// Get the configured `[ui] locale`, or ISO-style formatting if unset.
+    pub fn locale(&self) -> anyhow::Result<Locale> {
+        match self
+            .config()?
+            .and_then(|config| config.ui.locale.as_deref())
+        {
+            None => Ok(Locale::default()),
+            Some(tag) => Locale::parse(tag).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unsupported locale in config: {}\nHint: Use one of {}.",
+                    tag,
+                    SUPPORTED_LOCALES.join(", ")
+                )
+            }),
+        }
+    }
+
+    /// Whether `[privacy] track_access` asks for entry views to be recorded.
+    pub fn track_access(&self) -> anyhow::Result<bool> {
+        Ok(self
+            .config()?
+            .is_some_and(|config| config.privacy.track_access))
+    }
+
+    /// The `[search] tokenizer` from config, if set.
+    pub fn search_tokenizer(&self) -> anyhow::Result<Option<SearchTokenizer>> {
+        let Some(name) = self
+            .config()?
+            .and_then(|config| config.search.tokenizer.as_deref())
+        else {
+            return Ok(None);
+        };
+        parse_search_tokenizer(name).map(Some)
+    }
+
+    /// Whether `[tags] auto_suggest` turns on tag suggestions for `add`.
+    pub fn auto_suggest_tags(&self) -> anyhow::Result<bool> {
+        Ok(self
+            .config()?
+            .is_some_and(|config| config.tags.auto_suggest))
+    }
+
+    /// Most tags to suggest at once, from `[tags] suggest_limit`.
+    pub fn tag_suggest_limit(&self) -> anyhow::Result<usize> {
+        Ok(self
+            .config()?
+            .and_then(|config| config.tags.suggest_limit)
+            .unwrap_or(DEFAULT_TAG_SUGGEST_LIMIT))
+    }
+
+    /// Whether `[prompts] enabled` asks for a prompt of the day.
+    pub fn prompts_enabled(&self) -> anyhow::Result<bool> {
+        Ok(self.config()?.is_some_and(|config| config.prompts.enabled))
+    }
+
+    /// The prompts file from `[prompts] file`, or `prompts.txt` next to the
+    /// config.
+    pub fn prompts_file(&self) -> anyhow::Result<PathBuf> {
+        match self
+            .config()?
+            .and_then(|config| config.prompts.file.as_deref())
+        {
+            Some(path) => Ok(PathBuf::from(path)),
+            None => user_prompts_path(),
+        }
+    }
+
+    /// Every prompt: the built-ins unless `[prompts] builtin = false`, then
+    /// the prompts file.
+    pub fn prompts(&self) -> anyhow::Result<PromptSet> {
+        let builtin = self
+            .config()?
+            .and_then(|config| config.prompts.builtin)
+            .unwrap_or(true);
+        PromptSet::load(&self.prompts_file()?, builtin)
+    }
+
+    /// The prompt for `day` when prompts are enabled.
+    pub fn prompt_of_the_day(&self, day: NaiveDate) -> anyhow::Result<Option<String>> {
+        if !self.prompts_enabled()? {
+            return Ok(None);
+        }
+        Ok(self
+            .prompts()?
+            .for_day(day)
+            .map(|prompt| prompt.text.clone()))
+    }
+
+    /// Get the configured audio transcription command, if any.
+    pub fn transcriber(&self) -> anyhow::Result<Option<&str>> {
+        Ok(self
+            .config()?
+            .and_then(|config| config.capture.transcriber.as_deref()))
     }
 
     /// Open storage with passphrase handling and retry logic.
     ///
-    /// This is a convenience method that delegates to the underlying
-    /// `open_storage_with_retry` function.
+    /// Delegates to `open_storage_with_retry` the first time; later calls in
+    /// the same invocation reuse the passphrase instead of prompting again.
+    /// Honors `[storage] verify_after_write` and `timestamp_precision` for the
+    /// returned storage, and warns once if the ledger sits on a network
+    /// filesystem or synced folder.
     pub fn open_storage(&self, no_input: bool) -> anyhow::Result<(AgeSqliteStorage, String)> {
-        open_storage_with_retry(self.cli, no_input)
+        if self.location_checked.set(()).is_ok() {
+            let target = resolve_ledger_path(self.cli)?;
+            for risk in path_risks(Path::new(&target)) {
+                if risk.is_concurrent_write_risk() {
+                    self.warn(&format!(
+                        "{}\nHint: Keep the ledger on a local disk and sync backups instead.",
+                        risk
+                    ))?;
+                }
+            }
+        }
+        let (mut storage, passphrase) = match self.passphrase.get() {
+            Some(passphrase) => {
+                let target = resolve_ledger_path(self.cli)?;
+                let storage = AgeSqliteStorage::open(Path::new(&target), passphrase)?;
+                (storage, passphrase.clone())
+            }
+            None => {
+                let (storage, passphrase) =
+                    open_storage_with_retry(self.cli, no_input, self.read_only)?;
+                let _ = self.passphrase.set(passphrase.clone());
+                (storage, passphrase)
+            }
+        };
+        let security = self.security_config()?;
+        storage.set_verify_after_write(security.verify_after_write);
+        storage.set_timestamp_precision(security.timestamp_precision);
+        storage.set_origin(Some(self.entry_origin()));
+        Ok((storage, passphrase))
+    }
+
+    /// Print a warning, or fail with exit code 7 under `--strict`.
+    pub fn warn(&self, message: &str) -> anyhow::Result<()> {
+        Ok(warn(self.cli.strict, self.quiet(), message)?)
+    }
+
+    /// Check if `--yes` was passed.
+    pub fn assume_yes(&self) -> bool {
+        self.cli.yes
+    }
+
+    /// Check if `--strict` was passed.
+    pub fn strict(&self) -> bool {
+        self.cli.strict
+    }
+
+    /// Check if `--dry-run` was passed.
+    pub fn dry_run(&self) -> bool {
+        self.cli.dry_run
+    }
+
+    /// Encrypt and write storage back to the ledger file.
+    ///
+    /// Under `--dry-run` the changes only ever lived in the in-memory
+    /// database, so they are dropped here and the file is left untouched.
+    /// Read-only contexts drop them the same way, without a notice. With
+    /// `[replica] path` set, saving the configured ledger is followed by a
+    /// background copy to the replica.
+    pub fn save_storage(&self, storage: AgeSqliteStorage, passphrase: &str) -> anyhow::Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        if self.dry_run() {
+            if !self.quiet() {
+                let target = resolve_ledger_path(self.cli)?;
+                eprintln!("Dry run: no changes written to {}", target);
+            }
+            return Ok(());
+        }
+        storage.close(passphrase)?;
+        let target = resolve_ledger_path(self.cli)?;
+        if let Some(replica) = configured_replica(Path::new(&target)) {
+            if let Err(err) = spawn_replica_sync(Path::new(&target), &replica) {
+                self.warn(&format!("Saved, but the replica was not updated: {}", err))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve the unlock passphrase without opening the ledger.
+    pub fn unlock_passphrase(&self, no_input: bool) -> anyhow::Result<String> {
+        if let Some(passphrase) = self.passphrase.get() {
+            return Ok(passphrase.clone());
+        }
+        resolve_unlock_passphrase(self.cli, no_input)
     }
 
     /// Create a UI context for the current environment.
@@ -71,5 +445,58 @@ impl<'a> AppContext<'a> {
     #[allow(dead_code)] // Will be used during command migration
     pub fn ui_context(&self, json_flag: bool, format_flag: Option<&str>) -> UiContext {
         UiContext::from_env(json_flag, format_flag, self.cli.no_color, self.cli.ascii)
+            .with_theme(self.theme())
+            .with_palette(self.palette())
+    }
+}
+
+/// Raw `[ui] theme` value from the config file, if any.
+fn configured_theme() -> Option<ThemeConfig> {
+    let config_path = resolve_config_path().ok()?;
+    if !config_path.exists() {
+        return None;
+    }
+    read_config(&config_path).ok()?.ui.theme
+}
+
+/// Raw `[replica] path` from the config file, if `ledger` is the configured
+/// ledger it mirrors.
+///
+/// A ledger opened with `--ledger` elsewhere has no replica: copying it over
+/// the configured ledger's replica would replace that with an unrelated file.
+/// Read without resolving secret references: the save that triggers the
+/// replica may have just removed a secret the config still names.
+fn configured_replica(ledger: &Path) -> Option<PathBuf> {
+    let config_path = resolve_config_path().ok()?;
+    if !config_path.exists() {
+        return None;
+    }
+    let config = read_config(&config_path).ok()?;
+    let replica = config.replica.path?;
+    let configured = std::fs::canonicalize(expand_tilde(&config.ledger.path)).ok()?;
+    if std::fs::canonicalize(ledger).ok()? != configured {
+        return None;
+    }
+    Some(expand_tilde(&replica))
+}
+
+/// Name of this machine, if it has one.
+fn hostname() -> Option<String> {
+    #[cfg(unix)]
+    {
+        let mut buf = [0u8; 256];
+        let rc = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+        if rc != 0 {
+            return None;
+        }
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        let name = String::from_utf8_lossy(&buf[..len]).trim().to_string();
+        (!name.is_empty()).then_some(name)
+    }
+    #[cfg(not(unix))]
+    {
+        std::env::var("COMPUTERNAME")
+            .ok()
+            .filter(|name| !name.trim().is_empty())
     }
 }
@@ -0,0 +1,204 @@
+//! Persistent failed-unlock counter with exponential backoff.
+//!
+//! State lives in `<ledger>.lockout` next to the ledger. It only slows down
+//! casual guessing through the CLI; it does not protect against offline
+//! attacks on the encrypted file.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::CliError;
+
+/// Failures allowed before backoff starts.
+const FREE_ATTEMPTS: u32 = 3;
+
+/// Delay after the first failure past the free attempts.
+const BASE_DELAY_SECS: i64 = 30;
+
+/// Upper bound on the backoff delay.
+const MAX_DELAY_SECS: i64 = 60 * 60;
+
+/// Failed-unlock state stored next to the ledger.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockoutState {
+    pub failed_attempts: u32,
+    pub last_failure: Option<DateTime<Utc>>,
+}
+
+impl LockoutState {
+    /// Time until which unlock attempts are refused, if any.
+    pub fn locked_until(&self) -> Option<DateTime<Utc>> {
+        let delay = backoff_delay(self.failed_attempts)?;
+        self.last_failure.map(|at| at + delay)
+    }
+
+    /// Remaining lockout at `now`, if still locked.
+    pub fn remaining(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.locked_until()
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+}
+
+/// Backoff after `failed_attempts` failures: none for the first few, then
+/// doubling from `BASE_DELAY_SECS` up to `MAX_DELAY_SECS`.
+pub fn backoff_delay(failed_attempts: u32) -> Option<Duration> {
+    if failed_attempts < FREE_ATTEMPTS {
+        return None;
+    }
+    let exponent = (failed_attempts - FREE_ATTEMPTS).min(16);
+    let secs = (BASE_DELAY_SECS << exponent).min(MAX_DELAY_SECS);
+    Some(Duration::seconds(secs))
+}
+
+/// Path of the lockout state file for a ledger.
+pub fn lockout_path(ledger_path: &Path) -> PathBuf {
+    let mut name = ledger_path.as_os_str().to_os_string();
+    name.push(".lockout");
+    PathBuf::from(name)
+}
+
+/// Read lockout state; a missing or unreadable file counts as no failures.
+pub fn read_lockout(ledger_path: &Path) -> LockoutState {
+    std::fs::read_to_string(lockout_path(ledger_path))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Enforces and records unlock attempts for one ledger.
+pub struct UnlockGuard {
+    ledger_path: PathBuf,
+    enabled: bool,
+    enforce: bool,
+    record: bool,
+}
+
+impl UnlockGuard {
+    /// `enabled` turns tracking on; `enforce` is false with `--no-lockout`,
+    /// which skips the wait but still records failures.
+    pub fn new(ledger_path: &Path, enabled: bool, enforce: bool) -> Self {
+        Self {
+            ledger_path: ledger_path.to_path_buf(),
+            enabled,
+            enforce,
+            record: true,
+        }
+    }
+
+    /// Still enforce the lockout, but never write the counter.
+    pub fn read_only(mut self) -> Self {
+        self.record = false;
+        self
+    }
+
+    /// Fail with an auth error if the ledger is locked out.
+    pub fn check(&self) -> Result<(), CliError> {
+        if !self.enabled || !self.enforce {
+            return Ok(());
+        }
+        let state = read_lockout(&self.ledger_path);
+        if let Some(remaining) = state.remaining(Utc::now()) {
+            return Err(CliError::auth_failed_with_hint(
+                format!(
+                    "Too many failed unlock attempts ({}). Try again in {}.",
+                    state.failed_attempts,
+                    format_wait(remaining)
+                ),
+                "Hint: Wait for the lockout to expire, or pass --no-lockout if this is your ledger.",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Record a failed attempt.
+    pub fn failed(&self) {
+        if !self.enabled || !self.record {
+            return;
+        }
+        let mut state = read_lockout(&self.ledger_path);
+        state.failed_attempts = state.failed_attempts.saturating_add(1);
+        state.last_failure = Some(Utc::now());
+        if let Err(err) = write_lockout(&self.ledger_path, &state) {
+            eprintln!("Warning: Failed to record unlock attempt: {}", err);
+        }
+    }
+
+    /// Clear the counter after a successful unlock.
+    pub fn succeeded(&self) {
+        if !self.enabled || !self.record {
+            return;
+        }
+        let path = lockout_path(&self.ledger_path);
+        if path.exists() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn write_lockout(ledger_path: &Path, state: &LockoutState) -> anyhow::Result<()> {
+    let path = lockout_path(ledger_path);
+    std::fs::write(&path, serde_json::to_string(state)?)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+/// Format a wait as "45s", "3m 20s", or "1h 0m".
+pub fn format_wait(wait: Duration) -> String {
+    let secs = wait.num_seconds().max(1);
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay_doubles_and_caps() {
+        assert_eq!(backoff_delay(0), None);
+        assert_eq!(backoff_delay(2), None);
+        assert_eq!(backoff_delay(3), Some(Duration::seconds(30)));
+        assert_eq!(backoff_delay(4), Some(Duration::seconds(60)));
+        assert_eq!(backoff_delay(5), Some(Duration::seconds(120)));
+        assert_eq!(backoff_delay(40), Some(Duration::seconds(3600)));
+    }
+
+    #[test]
+    fn test_remaining_lockout() {
+        let now = Utc::now();
+        let state = LockoutState {
+            failed_attempts: 4,
+            last_failure: Some(now - Duration::seconds(20)),
+        };
+        assert_eq!(state.remaining(now), Some(Duration::seconds(40)));
+        assert_eq!(state.remaining(now + Duration::seconds(40)), None);
+        assert_eq!(LockoutState::default().remaining(now), None);
+    }
+
+    #[test]
+    fn test_lockout_path_appends_suffix() {
+        assert_eq!(
+            lockout_path(Path::new("/data/my.ledger")),
+            PathBuf::from("/data/my.ledger.lockout")
+        );
+    }
+
+    #[test]
+    fn test_format_wait() {
+        assert_eq!(format_wait(Duration::seconds(45)), "45s");
+        assert_eq!(format_wait(Duration::seconds(200)), "3m 20s");
+        assert_eq!(format_wait(Duration::seconds(3600)), "1h 0m");
+    }
+}
@@ -5,16 +5,19 @@
 //! - Path resolution for config and ledger files
 //! - Security configuration loading
 //! - Passphrase handling with retry logic
+//! - Failed-unlock lockout state
 
 mod context;
+mod lockout;
 mod passphrase;
 mod resolver;
 mod security_config;
 
 // Re-export public API
 pub use context::AppContext;
+pub use lockout::{format_wait, lockout_path, read_lockout};
 pub use resolver::{
-    exit_not_found_with_hint, missing_config_message, missing_ledger_message, resolve_config_path,
+    missing_config_message, missing_ledger_message, not_found_with_hint, resolve_config_path,
     resolve_ledger_path,
 };
 pub use security_config::device_keyfile_warning;
@@ -9,26 +9,39 @@ use ledger_core::StorageEngine;
 use crate::cache::{cache_clear, cache_config, cache_get, cache_store, ledger_hash, CacheConfig};
 use crate::cli::Cli;
 use crate::config::SecurityTier;
-use crate::errors::CliError;
+use crate::errors::{warn, CliError};
 use crate::helpers::prompt_passphrase;
 use crate::security::{
     key_bytes_to_passphrase, keychain_clear, keychain_get, keychain_set, read_keyfile_encrypted,
     read_keyfile_plain,
 };
 
+use super::lockout::UnlockGuard;
 use super::resolver::{missing_ledger_message, resolve_ledger_path};
 use super::security_config::{load_security_config, SecurityConfig};
 
 /// Open storage with passphrase retry logic based on security tier.
+///
+/// With `read_only` nothing is written on the way: the session cache is
+/// read but never filled or cleared, the keychain is left as it is and the
+/// lockout is enforced without recording attempts.
 pub fn open_storage_with_retry(
     cli: &Cli,
     no_input: bool,
+    read_only: bool,
 ) -> anyhow::Result<(AgeSqliteStorage, String)> {
     let target = resolve_ledger_path(cli)?;
     let interactive = std::io::stdin().is_terminal() && !no_input;
     let target_path = Path::new(&target);
     let security = load_security_config(cli)?;
     let cache_config = cache_config(target_path, security.cache_ttl_seconds).unwrap_or(None);
+    let store_cache = cache_config.as_ref().filter(|_| !read_only);
+    let mut guard = UnlockGuard::new(target_path, security.lockout, !cli.no_lockout);
+    if read_only {
+        guard = guard.read_only();
+    }
+    let keychain =
+        matches!(security.tier, SecurityTier::PassphraseKeychain) && security.keychain_enabled;
 
     // Try cache first
     if let Some(config) = cache_config.as_ref() {
@@ -41,7 +54,9 @@ pub fn open_storage_with_retry(
                     return Ok((storage, passphrase));
                 }
                 Err(err) if is_incorrect_passphrase_error(&err) => {
-                    let _ = cache_clear(&config.socket_path);
+                    if !read_only {
+                        let _ = cache_clear(&config.socket_path);
+                    }
                 }
                 Err(err) => return Err(err.into()),
             }
@@ -50,7 +65,7 @@ pub fn open_storage_with_retry(
 
     // Device keyfile: no passphrase needed
     if matches!(security.tier, SecurityTier::DeviceKeyfile) {
-        return open_with_device_keyfile(cli, target_path, &security, cache_config.as_ref());
+        return open_with_device_keyfile(cli, target_path, &security, store_cache, &guard);
     }
 
     // Passphrase keyfile: decrypt keyfile with passphrase
@@ -60,13 +75,14 @@ pub fn open_storage_with_retry(
             target_path,
             &security,
             interactive,
-            cache_config.as_ref(),
+            store_cache,
+            &guard,
         );
     }
 
     // Passphrase + keychain: try keychain first
-    if matches!(security.tier, SecurityTier::PassphraseKeychain) && security.keychain_enabled {
-        if let Some(result) = try_keychain_passphrase(target_path) {
+    if keychain {
+        if let Some(result) = try_keychain_passphrase(cli, target_path, read_only)? {
             return Ok(result);
         }
     }
@@ -77,8 +93,8 @@ pub fn open_storage_with_retry(
         .filter(|v| !v.trim().is_empty());
     if let Some(passphrase) = env_passphrase {
         let (storage, passphrase) =
-            open_with_passphrase_and_cache(cli, target_path, &passphrase, cache_config.as_ref())?;
-        if matches!(security.tier, SecurityTier::PassphraseKeychain) && security.keychain_enabled {
+            open_with_passphrase_and_cache(cli, target_path, &passphrase, store_cache, &guard)?;
+        if keychain && !read_only {
             let account = ledger_hash(target_path);
             let _ = keychain_set(&account, &passphrase);
         }
@@ -87,19 +103,63 @@ pub fn open_storage_with_retry(
 
     // Prompt for passphrase
     let (storage, passphrase) =
-        open_with_retry_prompt(cli, target_path, interactive, cache_config.as_ref())?;
-    if matches!(security.tier, SecurityTier::PassphraseKeychain) && security.keychain_enabled {
+        open_with_retry_prompt(cli, target_path, interactive, store_cache, &guard)?;
+    if keychain && !read_only {
         let account = ledger_hash(target_path);
         let _ = keychain_set(&account, &passphrase);
     }
     Ok((storage, passphrase))
 }
 
+/// Resolve the unlock passphrase for the ledger without opening it.
+///
+/// Used where the ledger may be too damaged to open (e.g. `recover`): keyfile
+/// tiers derive it from the keyfile, otherwise the keychain, environment or a
+/// single prompt supplies it. The caller reports a wrong passphrase.
+pub fn resolve_unlock_passphrase(cli: &Cli, no_input: bool) -> anyhow::Result<String> {
+    let target = resolve_ledger_path(cli)?;
+    let target_path = Path::new(&target);
+    let interactive = std::io::stdin().is_terminal() && !no_input;
+    let security = load_security_config(cli)?;
+    let guard = UnlockGuard::new(target_path, security.lockout, !cli.no_lockout);
+
+    match security.tier {
+        SecurityTier::DeviceKeyfile | SecurityTier::PassphraseKeyfile => {
+            let keyfile_path = security
+                .keyfile_path
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Keyfile path is required for this tier"))?;
+            let key_bytes = if matches!(security.tier, SecurityTier::DeviceKeyfile) {
+                read_keyfile_plain(keyfile_path)?
+            } else {
+                let env_passphrase = std::env::var("LEDGER_PASSPHRASE")
+                    .ok()
+                    .filter(|v| !v.trim().is_empty());
+                decrypt_keyfile_with_retry(
+                    keyfile_path,
+                    env_passphrase.as_deref(),
+                    interactive,
+                    &guard,
+                )?
+            };
+            Ok(key_bytes_to_passphrase(&key_bytes))
+        }
+        SecurityTier::PassphraseKeychain if security.keychain_enabled => {
+            match keychain_get(&ledger_hash(target_path)) {
+                Ok(Some(passphrase)) => Ok(passphrase),
+                _ => prompt_passphrase(interactive),
+            }
+        }
+        _ => prompt_passphrase(interactive),
+    }
+}
+
 fn open_with_device_keyfile(
     cli: &Cli,
     target_path: &Path,
     security: &SecurityConfig,
     cache_config: Option<&CacheConfig>,
+    guard: &UnlockGuard,
 ) -> anyhow::Result<(AgeSqliteStorage, String)> {
     let keyfile_path = security
         .keyfile_path
@@ -107,7 +167,7 @@ fn open_with_device_keyfile(
         .ok_or_else(|| anyhow::anyhow!("Keyfile path is required for device_keyfile"))?;
     let key_bytes = read_keyfile_plain(keyfile_path)?;
     let passphrase = key_bytes_to_passphrase(&key_bytes);
-    open_with_passphrase_and_cache(cli, target_path, &passphrase, cache_config)
+    open_with_passphrase_and_cache(cli, target_path, &passphrase, cache_config, guard)
 }
 
 fn open_with_passphrase_keyfile(
@@ -116,6 +176,7 @@ fn open_with_passphrase_keyfile(
     security: &SecurityConfig,
     interactive: bool,
     cache_config: Option<&CacheConfig>,
+    guard: &UnlockGuard,
 ) -> anyhow::Result<(AgeSqliteStorage, String)> {
     let keyfile_path = security
         .keyfile_path
@@ -125,25 +186,31 @@ fn open_with_passphrase_keyfile(
         .ok()
         .filter(|v| !v.trim().is_empty());
     let key_bytes =
-        decrypt_keyfile_with_retry(keyfile_path, env_passphrase.as_deref(), interactive)?;
+        decrypt_keyfile_with_retry(keyfile_path, env_passphrase.as_deref(), interactive, guard)?;
     let passphrase = key_bytes_to_passphrase(&key_bytes);
-    open_with_passphrase_and_cache(cli, target_path, &passphrase, cache_config)
+    open_with_passphrase_and_cache(cli, target_path, &passphrase, cache_config, guard)
 }
 
-fn try_keychain_passphrase(target_path: &Path) -> Option<(AgeSqliteStorage, String)> {
+fn try_keychain_passphrase(
+    cli: &Cli,
+    target_path: &Path,
+    read_only: bool,
+) -> anyhow::Result<Option<(AgeSqliteStorage, String)>> {
     let account = ledger_hash(target_path);
     match keychain_get(&account) {
         Ok(Some(passphrase)) => {
             if let Ok(storage) = AgeSqliteStorage::open(target_path, &passphrase) {
-                return Some((storage, passphrase));
+                return Ok(Some((storage, passphrase)));
+            }
+            if !read_only {
+                let _ = keychain_clear(&account);
             }
-            let _ = keychain_clear(&account);
-            None
+            Ok(None)
         }
-        Ok(None) => None,
+        Ok(None) => Ok(None),
         Err(err) => {
-            eprintln!("Warning: {}", err);
-            None
+            warn(cli.strict, cli.quiet, &err.to_string())?;
+            Ok(None)
         }
     }
 }
@@ -153,13 +220,16 @@ fn open_with_passphrase_and_cache(
     path: &Path,
     passphrase: &str,
     cache_config: Option<&CacheConfig>,
+    guard: &UnlockGuard,
 ) -> anyhow::Result<(AgeSqliteStorage, String)> {
+    guard.check()?;
     match AgeSqliteStorage::open(path, passphrase) {
         Ok(storage) => {
+            guard.succeeded();
             if let Some(config) = cache_config {
                 if !cli.quiet {
                     println!(
-                        "Note: Passphrase caching keeps your passphrase in memory for {} seconds.",
+                        "Note: Passphrase caching keeps this ledger unlocked for {} seconds.",
                         config.ttl.as_secs()
                     );
                 }
@@ -168,7 +238,8 @@ fn open_with_passphrase_and_cache(
             Ok((storage, passphrase.to_string()))
         }
         Err(err) if is_incorrect_passphrase_error(&err) => {
-            CliError::auth_failed("Incorrect passphrase.").exit()
+            guard.failed();
+            Err(CliError::auth_failed("Incorrect passphrase.").into())
         }
         Err(err) if is_missing_ledger_error(&err) => {
             Err(anyhow::anyhow!(missing_ledger_message(path)))
@@ -182,6 +253,7 @@ fn open_with_retry_prompt(
     path: &Path,
     interactive: bool,
     cache_config: Option<&CacheConfig>,
+    guard: &UnlockGuard,
 ) -> anyhow::Result<(AgeSqliteStorage, String)> {
     let test_attempts = if !interactive && cfg!(feature = "test-support") {
         std::env::var("LEDGER_TEST_PASSPHRASE_ATTEMPTS")
@@ -205,6 +277,7 @@ fn open_with_retry_prompt(
 
     loop {
         attempts += 1;
+        guard.check()?;
         let passphrase = if let Some(values) = test_attempts.as_ref() {
             values
                 .get((attempts - 1) as usize)
@@ -215,10 +288,11 @@ fn open_with_retry_prompt(
         };
         match AgeSqliteStorage::open(path, &passphrase) {
             Ok(storage) => {
+                guard.succeeded();
                 if let Some(config) = cache_config {
                     if !cli.quiet {
                         println!(
-                            "Note: Passphrase caching keeps your passphrase in memory for {} seconds.",
+                            "Note: Passphrase caching keeps this ledger unlocked for {} seconds.",
                             config.ttl.as_secs()
                         );
                     }
@@ -227,13 +301,14 @@ fn open_with_retry_prompt(
                 return Ok((storage, passphrase));
             }
             Err(err) if is_incorrect_passphrase_error(&err) => {
+                guard.failed();
                 let remaining = max_attempts.saturating_sub(attempts);
                 if remaining == 0 {
-                    CliError::auth_failed_with_hint(
+                    return Err(CliError::auth_failed_with_hint(
                         "Too many failed passphrase attempts.",
                         "Hint: If you forgot your passphrase, the ledger cannot be recovered.\n      Backups use the same passphrase.",
                     )
-                    .exit()
+                    .into());
                 }
                 eprintln!(
                     "Incorrect passphrase. {} attempt{} remaining.",
@@ -254,12 +329,19 @@ fn decrypt_keyfile_with_retry(
     path: &Path,
     passphrase_env: Option<&str>,
     interactive: bool,
+    guard: &UnlockGuard,
 ) -> anyhow::Result<zeroize::Zeroizing<Vec<u8>>> {
     if let Some(passphrase) = passphrase_env {
+        guard.check()?;
         return match read_keyfile_encrypted(path, passphrase) {
             Ok(bytes) => Ok(bytes),
-            Err(err) if err.to_string().contains("Incorrect passphrase") => {
-                CliError::auth_failed("Incorrect passphrase.").exit()
+            Err(err)
+                if err
+                    .downcast_ref::<ledger_core::error::LedgerError>()
+                    .is_some_and(is_incorrect_passphrase_error) =>
+            {
+                guard.failed();
+                Err(CliError::auth_failed("Incorrect passphrase.").into())
             }
             Err(err) => Err(err),
         };
@@ -270,17 +352,23 @@ fn decrypt_keyfile_with_retry(
 
     loop {
         attempts += 1;
+        guard.check()?;
         let passphrase = prompt_passphrase(interactive)?;
         match read_keyfile_encrypted(path, &passphrase) {
             Ok(bytes) => return Ok(bytes),
-            Err(err) if err.to_string().contains("Incorrect passphrase") => {
+            Err(err)
+                if err
+                    .downcast_ref::<ledger_core::error::LedgerError>()
+                    .is_some_and(is_incorrect_passphrase_error) =>
+            {
+                guard.failed();
                 let remaining = max_attempts.saturating_sub(attempts);
                 if remaining == 0 {
-                    CliError::auth_failed_with_hint(
+                    return Err(CliError::auth_failed_with_hint(
                         "Too many failed passphrase attempts.",
                         "Hint: If you forgot your passphrase, the ledger cannot be recovered.\n      Backups use the same passphrase.",
                     )
-                    .exit()
+                    .into());
                 }
                 eprintln!(
                     "Incorrect passphrase. {} attempt{} remaining.",
@@ -42,15 +42,12 @@ pub fn missing_ledger_message(path: &Path) -> String {
 /// Error message when config file is missing.
 pub fn missing_config_message(config_path: &Path) -> String {
     format!(
-        "Config file not found: {}\n\nRun:\n  ledger init\n\nOr set LEDGER_CONFIG to specify a different config location.",
+        "Config file not found: {}\n\nRun:\n  ledger init\n\nOr, for an existing ledger file:\n  ledger adopt /path/to/my.ledger\n\nOr set LEDGER_CONFIG to specify a different config location.",
         config_path.display()
     )
 }
 
-/// Exit with error code for not found errors.
-///
-/// This function prints the error and exits immediately.
-/// Use `CliError::not_found` if you need to return an error instead.
-pub fn exit_not_found_with_hint(message: &str, hint: &str) -> ! {
-    CliError::not_found(message, hint).exit()
+/// Not-found error with a hint, exiting with `exit_codes::NOT_FOUND`.
+pub fn not_found_with_hint(message: &str, hint: &str) -> anyhow::Error {
+    CliError::not_found(message, hint).into()
 }
@@ -2,8 +2,12 @@
 
 use std::path::PathBuf;
 
+use ledger_core::storage::TimestampPrecision;
+
 use crate::cli::Cli;
-use crate::config::{default_keyfile_path, read_config, KeyfileMode, SecurityTier};
+use crate::config::{
+    default_keyfile_path, read_config, secret_ref_name, KeyfileMode, SecurityTier,
+};
 
 use super::resolver::resolve_config_path;
 
@@ -14,7 +18,9 @@ pub struct SecurityConfig {
     pub keyfile_mode: KeyfileMode,
     pub keyfile_path: Option<PathBuf>,
     pub cache_ttl_seconds: u64,
-    pub editor: Option<String>,
+    pub lockout: bool,
+    pub verify_after_write: bool,
+    pub timestamp_precision: TimestampPrecision,
 }
 
 /// Load security configuration from the config file.
@@ -22,14 +28,38 @@ pub fn load_security_config(_cli: &Cli) -> anyhow::Result<SecurityConfig> {
     let config_path = resolve_config_path()?;
     if config_path.exists() {
         let config = read_config(&config_path)?;
+        if config
+            .keyfile
+            .path
+            .as_deref()
+            .and_then(secret_ref_name)
+            .is_some()
+        {
+            // The keyfile is needed to unlock the ledger that holds the secrets
+            return Err(anyhow::anyhow!(
+                "keyfile.path cannot reference a secret (the keyfile unlocks the secret store)"
+            ));
+        }
         let keyfile_path = config.keyfile.path.as_ref().map(PathBuf::from);
+        let timestamp_precision = match config.storage.timestamp_precision.as_deref() {
+            None => TimestampPrecision::default(),
+            Some(value) => TimestampPrecision::parse(value).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unsupported storage.timestamp_precision in config: {}\nHint: Use one of {}.",
+                    value,
+                    TimestampPrecision::ALL.map(|p| p.as_str()).join(", ")
+                )
+            })?,
+        };
         let security = SecurityConfig {
             tier: config.security.tier,
             keychain_enabled: config.keychain.enabled,
             keyfile_mode: config.keyfile.mode,
             keyfile_path,
             cache_ttl_seconds: config.security.passphrase_cache_ttl_seconds,
-            editor: config.ui.editor,
+            lockout: config.security.lockout,
+            verify_after_write: config.storage.verify_after_write,
+            timestamp_precision,
         };
         validate_security_config(&security)?;
         return Ok(security);
@@ -41,7 +71,9 @@ pub fn load_security_config(_cli: &Cli) -> anyhow::Result<SecurityConfig> {
         keyfile_mode: KeyfileMode::None,
         keyfile_path: Some(default_keyfile_path()?),
         cache_ttl_seconds: 0,
-        editor: None,
+        lockout: false,
+        verify_after_write: false,
+        timestamp_precision: TimestampPrecision::default(),
     })
 }
 
@@ -1,3 +1,26 @@
+//! Passphrase cache daemon and client.
+//!
+//! The passphrase crosses the socket exactly once, on `STORE`. The daemon
+//! seals it under an ephemeral session key that only ever lives in its memory
+//! and answers with a capability token, which the CLI keeps in a `0600` file
+//! next to the socket. Later processes prove they hold the token with a MAC
+//! over a fresh nonce on `GET` and receive the unlock secret sealed for that
+//! token and nonce, so neither the secret nor the token crosses the socket
+//! again. Entries and token keys are zeroized on TTL expiry and `CLEAR`.
+//!
+//! One daemon per user serves every ledger: entries are keyed by a hash of the
+//! ledger path and each carries its own TTL, so ledgers expire and lock
+//! independently.
+//!
+//! Every connection carries one request. The client sends two length-prefixed
+//! frames, `HELLO ledger-cache <version>` and the request; the daemon answers
+//! with its own hello and the response. Both sides check the peer's uid
+//! against their own before exchanging anything, so neither a different user
+//! nor a daemon impersonating the socket sees a secret. A daemon speaking
+//! another protocol version (including the unversioned line format it
+//! replaced) refuses everything but `STOP`, and the client stops it and
+//! starts a fresh one instead of guessing at its cache.
+
 use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
@@ -5,17 +28,105 @@ use std::time::{Duration, Instant};
 
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
 use zeroize::Zeroizing;
 
+/// Key-derivation context for wrapping unlock secrets with a capability token.
+const TOKEN_WRAP_CONTEXT: &str = "ledger cache token wrap v1";
+
+/// Key-derivation context for the key that authenticates `GET` requests.
+const TOKEN_AUTH_CONTEXT: &str = "ledger cache token auth v1";
+
+/// Protocol name sent in both hellos.
+const PROTOCOL_NAME: &str = "ledger-cache";
+
+/// Wire protocol version. Version 1 was the unframed line format; version 2
+/// sent the raw token on `GET`.
+const PROTOCOL_VERSION: u32 = 3;
+
+/// Largest frame either side accepts, far above any real request.
+const MAX_FRAME_LEN: usize = 64 * 1024;
+
+/// How long the daemon waits on a client before dropping it.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+const REQUEST_NONCE_LEN: usize = 32;
+
 pub struct CacheConfig {
     pub ttl: Duration,
     pub socket_path: PathBuf,
     pub key: String,
+    pub ledger_path: PathBuf,
+}
+
+/// The daemon at the socket speaks a different protocol version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProtocolMismatch {
+    daemon: u32,
+}
+
+impl std::fmt::Display for ProtocolMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cache daemon speaks protocol v{}, this ledger speaks v{}\nHint: Run `ledger lock --now` to stop it; the next unlock starts a new one.",
+            self.daemon, PROTOCOL_VERSION
+        )
+    }
+}
+
+impl std::error::Error for ProtocolMismatch {}
+
+/// An unlocked ledger as reported by the daemon's `LIST` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheSession {
+    pub key: String,
+    pub ledger_path: Option<String>,
+    pub ttl: Duration,
+    pub remaining: Duration,
 }
 
 struct CacheEntry {
-    passphrase: Zeroizing<Vec<u8>>,
+    /// Unlock secret sealed under the daemon session key.
+    sealed: Zeroizing<Vec<u8>>,
+    /// Keys derived from the capability token issued for this entry; the
+    /// token itself is never kept.
+    auth_key: Zeroizing<[u8; KEY_LEN]>,
+    wrap_key: Zeroizing<[u8; KEY_LEN]>,
     stored_at: Instant,
+    ttl: Duration,
+    ledger_path: Option<String>,
+}
+
+impl CacheEntry {
+    fn remaining(&self) -> Option<Duration> {
+        self.ttl.checked_sub(self.stored_at.elapsed())
+    }
+}
+
+/// In-memory daemon state: the session key and sealed entries.
+struct CacheState {
+    session_key: Zeroizing<[u8; KEY_LEN]>,
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl CacheState {
+    fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            session_key: random_key()?,
+            entries: HashMap::new(),
+        })
+    }
+
+    /// Drop every entry and rotate the session key.
+    fn clear(&mut self) -> anyhow::Result<()> {
+        self.entries.clear();
+        self.session_key = random_key()?;
+        Ok(())
+    }
 }
 
 pub fn cache_config(path: &Path, ttl_seconds: u64) -> anyhow::Result<Option<CacheConfig>> {
@@ -28,26 +139,40 @@ pub fn cache_config(path: &Path, ttl_seconds: u64) -> anyhow::Result<Option<Cach
         ttl: Duration::from_secs(ttl_seconds),
         socket_path,
         key,
+        ledger_path: path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
     }))
 }
 
 pub fn cache_get(config: &CacheConfig) -> anyhow::Result<Option<String>> {
+    let token_path = token_path(&config.socket_path, &config.key);
+    let token = match std::fs::read_to_string(&token_path) {
+        Ok(contents) => Zeroizing::new(
+            STANDARD
+                .decode(contents.trim().as_bytes())
+                .map_err(|e| anyhow::anyhow!("Cache token decode failed: {}", e))?,
+        ),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(anyhow::anyhow!("Cache token read failed: {}", err)),
+    };
     let mut stream = match std::os::unix::net::UnixStream::connect(&config.socket_path) {
         Ok(stream) => stream,
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
         Err(err) => return Err(anyhow::anyhow!("Cache connect failed: {}", err)),
     };
-    send_line(&mut stream, &format!("GET {}", config.key))?;
-    let response = read_response(&mut stream)?;
-    if response.starts_with("PASSPHRASE ") {
-        let encoded = response.trim_start_matches("PASSPHRASE ").trim();
-        let decoded = STANDARD
-            .decode(encoded.as_bytes())
+    let (request, nonce) = get_request(&config.key, &token)?;
+    let response = exchange(&mut stream, &request)?;
+    if let Some(encoded) = response.strip_prefix("SEALED ") {
+        let sealed = STANDARD
+            .decode(encoded.trim().as_bytes())
             .map_err(|e| anyhow::anyhow!("Cache decode failed: {}", e))?;
-        let passphrase = String::from_utf8(decoded)
+        let wrap_key = token_wrap_key(&token);
+        let secret = open_sealed(&wrap_key, &sealed, &reply_aad(&config.key, &nonce))?;
+        let passphrase = String::from_utf8(secret.to_vec())
             .map_err(|_| anyhow::anyhow!("Cache entry is not valid UTF-8"))?;
         return Ok(Some(passphrase));
     }
+    // Stale token: the daemon restarted, expired, or was locked.
+    let _ = std::fs::remove_file(&token_path);
     Ok(None)
 }
 
@@ -55,10 +180,20 @@ pub fn cache_store(config: &CacheConfig, passphrase: &str) -> anyhow::Result<()>
     ensure_daemon_running(config)?;
     let mut stream = std::os::unix::net::UnixStream::connect(&config.socket_path)
         .map_err(|e| anyhow::anyhow!("Cache connect failed: {}", e))?;
-    let encoded = STANDARD.encode(passphrase.as_bytes());
-    send_line(&mut stream, &format!("STORE {} {}", config.key, encoded))?;
-    let _ = read_response(&mut stream)?;
-    Ok(())
+    let encoded = Zeroizing::new(STANDARD.encode(passphrase.as_bytes()));
+    let label = STANDARD.encode(config.ledger_path.to_string_lossy().as_bytes());
+    let request = Zeroizing::new(format!(
+        "STORE {} {} {} {}",
+        config.key,
+        *encoded,
+        config.ttl.as_secs(),
+        label
+    ));
+    let response = exchange(&mut stream, &request)?;
+    let token = response
+        .strip_prefix("OK ")
+        .ok_or_else(|| anyhow::anyhow!("Cache store failed: {}", response))?;
+    write_token(&token_path(&config.socket_path, &config.key), token.trim())
 }
 
 pub fn cache_clear(socket_path: &Path) -> anyhow::Result<()> {
@@ -67,29 +202,103 @@ pub fn cache_clear(socket_path: &Path) -> anyhow::Result<()> {
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
         Err(err) => return Err(anyhow::anyhow!("Cache connect failed: {}", err)),
     };
-    send_line(&mut stream, "CLEAR")?;
-    let _ = read_response(&mut stream)?;
+    let _ = exchange(&mut stream, "CLEAR")?;
+    remove_token_files(socket_path);
     Ok(())
 }
 
+/// Lock a single ledger, leaving other sessions untouched.
+///
+/// Returns `false` when the ledger had no active session.
+pub fn cache_clear_ledger(socket_path: &Path, key: &str) -> anyhow::Result<bool> {
+    let _ = std::fs::remove_file(token_path(socket_path, key));
+    let mut stream = match std::os::unix::net::UnixStream::connect(socket_path) {
+        Ok(stream) => stream,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(anyhow::anyhow!("Cache connect failed: {}", err)),
+    };
+    Ok(exchange(&mut stream, &format!("CLEAR {}", key))? == "OK")
+}
+
+/// Wipe every session and shut the daemon down.
+///
+/// Returns `false` when no daemon was running.
+pub fn cache_stop(socket_path: &Path) -> anyhow::Result<bool> {
+    let mut stream = match std::os::unix::net::UnixStream::connect(socket_path) {
+        Ok(stream) => stream,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) if err.kind() == std::io::ErrorKind::ConnectionRefused => {
+            // Stale socket from a daemon that died without cleaning up
+            let _ = std::fs::remove_file(socket_path);
+            remove_token_files(socket_path);
+            return Ok(false);
+        }
+        Err(err) => return Err(anyhow::anyhow!("Cache connect failed: {}", err)),
+    };
+    match exchange(&mut stream, "STOP") {
+        Ok(_) => {}
+        Err(err) => match err.downcast_ref::<ProtocolMismatch>() {
+            Some(mismatch) => stop_mismatched(socket_path, mismatch.daemon)?,
+            None => return Err(err),
+        },
+    }
+    remove_token_files(socket_path);
+    Ok(true)
+}
+
+/// List the ledgers currently unlocked in the daemon.
+pub fn cache_sessions(socket_path: &Path) -> anyhow::Result<Vec<CacheSession>> {
+    let mut stream = match std::os::unix::net::UnixStream::connect(socket_path) {
+        Ok(stream) => stream,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(anyhow::anyhow!("Cache connect failed: {}", err)),
+    };
+    let response = exchange(&mut stream, "LIST")?;
+    Ok(response.lines().filter_map(parse_session_line).collect())
+}
+
+fn parse_session_line(line: &str) -> Option<CacheSession> {
+    let mut parts = line.split_whitespace();
+    if parts.next()? != "SESSION" {
+        return None;
+    }
+    let key = parts.next()?.to_string();
+    let ttl = Duration::from_secs(parts.next()?.parse().ok()?);
+    let remaining = Duration::from_secs(parts.next()?.parse().ok()?);
+    let ledger_path = parts
+        .next()
+        .and_then(|label| STANDARD.decode(label.as_bytes()).ok())
+        .and_then(|bytes| String::from_utf8(bytes).ok());
+    Some(CacheSession {
+        key,
+        ledger_path,
+        ttl,
+        remaining,
+    })
+}
+
 pub fn cache_ping(socket_path: &Path) -> anyhow::Result<bool> {
     let mut stream = match std::os::unix::net::UnixStream::connect(socket_path) {
         Ok(stream) => stream,
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
         Err(err) => return Err(anyhow::anyhow!("Cache connect failed: {}", err)),
     };
-    send_line(&mut stream, "PING")?;
-    let response = read_response(&mut stream)?;
-    Ok(response.trim() == "PONG")
+    Ok(exchange(&mut stream, "PING")? == "PONG")
 }
 
-pub fn run_cache_daemon(ttl: Duration, socket_path: &Path) -> anyhow::Result<()> {
+/// Serve the cache until `STOP`, or until it has been empty and idle for a
+/// minute unless `keep_alive` is set (for daemons run by a service manager).
+pub fn run_cache_daemon(ttl: Duration, socket_path: &Path, keep_alive: bool) -> anyhow::Result<()> {
     let parent = socket_path.parent().ok_or_else(|| {
         anyhow::anyhow!(
             "Cache socket path has no parent directory: {}",
             socket_path.display()
         )
     })?;
+    // macOS keeps the socket directly in the per-user $TMPDIR; elsewhere the
+    // directory is ours alone, keeping the socket and token files out of
+    // reach of other users
+    #[cfg(target_os = "macos")]
     std::fs::create_dir_all(parent).map_err(|e| {
         anyhow::anyhow!(
             "Failed to create cache socket directory {}: {}",
@@ -97,6 +306,9 @@ pub fn run_cache_daemon(ttl: Duration, socket_path: &Path) -> anyhow::Result<()>
             e
         )
     })?;
+    #[cfg(not(target_os = "macos"))]
+    crate::helpers::create_private_dir(parent)
+        .map_err(|e| anyhow::anyhow!("Failed to create cache socket directory: {}", e))?;
     if socket_path.exists() {
         let _ = std::fs::remove_file(socket_path);
     }
@@ -106,30 +318,34 @@ pub fn run_cache_daemon(ttl: Duration, socket_path: &Path) -> anyhow::Result<()>
     set_socket_permissions(socket_path)?;
     listener.set_nonblocking(true)?;
 
-    let mut cache: HashMap<String, CacheEntry> = HashMap::new();
+    let mut state = CacheState::new()?;
     let mut last_activity = Instant::now();
 
     loop {
         match listener.accept() {
             Ok((mut stream, _addr)) => {
                 last_activity = Instant::now();
-                let mut buffer = String::new();
-                stream.read_to_string(&mut buffer)?;
-                let response = handle_request(buffer.trim(), &mut cache, ttl);
-                stream.write_all(response.as_bytes())?;
+                // A misbehaving client only loses its own connection
+                if let Ok(true) = serve_connection(&mut stream, &mut state, ttl) {
+                    break;
+                }
             }
             Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {}
             Err(err) => return Err(anyhow::anyhow!("Cache accept failed: {}", err)),
         }
 
-        expire_entries(&mut cache, ttl);
-        if cache.is_empty() && last_activity.elapsed() >= Duration::from_secs(60) {
+        expire_entries(&mut state.entries);
+        if !keep_alive
+            && state.entries.is_empty()
+            && last_activity.elapsed() >= Duration::from_secs(60)
+        {
             break;
         }
         std::thread::sleep(Duration::from_millis(50));
     }
 
     let _ = std::fs::remove_file(socket_path);
+    remove_token_files(socket_path);
     Ok(())
 }
 
@@ -152,38 +368,113 @@ pub fn cache_socket_path() -> anyhow::Result<PathBuf> {
     }
 }
 
-fn handle_request(request: &str, cache: &mut HashMap<String, CacheEntry>, ttl: Duration) -> String {
-    let mut parts = request.splitn(3, ' ');
+fn handle_request(request: &str, state: &mut CacheState, ttl: Duration) -> String {
+    let mut parts = request.split_whitespace();
     let command = parts.next().unwrap_or("");
     match command {
         "PING" => "PONG\n".to_string(),
-        "CLEAR" => {
-            cache.clear();
-            "OK\n".to_string()
+        "CLEAR" => match parts.next() {
+            // Dropping the entry zeroizes its sealed secret and revokes its token
+            Some(key) => match state.entries.remove(key) {
+                Some(_) => "OK\n".to_string(),
+                None => "NOT_FOUND\n".to_string(),
+            },
+            None => match state.clear() {
+                Ok(()) => "OK\n".to_string(),
+                Err(_) => "ERROR\n".to_string(),
+            },
+        },
+        // The daemon loop exits after answering
+        "STOP" => match state.clear() {
+            Ok(()) => "OK\n".to_string(),
+            Err(_) => "ERROR\n".to_string(),
+        },
+        "LIST" => {
+            let mut keys: Vec<&String> = state.entries.keys().collect();
+            keys.sort();
+            let mut response = String::new();
+            for key in keys {
+                let entry = &state.entries[key];
+                let Some(remaining) = entry.remaining() else {
+                    continue;
+                };
+                let label = entry
+                    .ledger_path
+                    .as_ref()
+                    .map(|path| STANDARD.encode(path.as_bytes()))
+                    .unwrap_or_else(|| "-".to_string());
+                response.push_str(&format!(
+                    "SESSION {} {} {} {}\n",
+                    key,
+                    entry.ttl.as_secs(),
+                    remaining.as_secs(),
+                    label
+                ));
+            }
+            response.push_str("END\n");
+            response
         }
         "GET" => {
             let key = parts.next().unwrap_or("");
-            if let Some(entry) = cache.get(key) {
-                if entry.stored_at.elapsed() <= ttl {
-                    let encoded = STANDARD.encode(entry.passphrase.as_slice());
-                    return format!("PASSPHRASE {}\n", encoded);
-                }
+            let nonce = match STANDARD.decode(parts.next().unwrap_or("").as_bytes()) {
+                Ok(nonce) if nonce.len() == REQUEST_NONCE_LEN => nonce,
+                _ => return "ERROR\n".to_string(),
+            };
+            let mac = match STANDARD
+                .decode(parts.next().unwrap_or("").as_bytes())
+                .map(<[u8; KEY_LEN]>::try_from)
+            {
+                Ok(Ok(mac)) => blake3::Hash::from(mac),
+                _ => return "ERROR\n".to_string(),
+            };
+            let Some(entry) = state.entries.get(key) else {
+                return "NOT_FOUND\n".to_string();
+            };
+            // blake3::Hash compares in constant time
+            if entry.remaining().is_none() || request_mac(&entry.auth_key, key, &nonce) != mac {
+                return "NOT_FOUND\n".to_string();
+            }
+            let sealed = open_sealed(&state.session_key, &entry.sealed, key.as_bytes())
+                .and_then(|secret| seal(&entry.wrap_key, &secret, &reply_aad(key, &nonce)));
+            match sealed {
+                Ok(sealed) => format!("SEALED {}\n", STANDARD.encode(sealed.as_slice())),
+                Err(_) => "ERROR\n".to_string(),
             }
-            "NOT_FOUND\n".to_string()
         }
         "STORE" => {
             let key = parts.next().unwrap_or("");
-            let encoded = parts.next().unwrap_or("");
-            match STANDARD.decode(encoded.as_bytes()) {
-                Ok(decoded) => {
-                    cache.insert(
+            let secret = match STANDARD.decode(parts.next().unwrap_or("").as_bytes()) {
+                Ok(decoded) => Zeroizing::new(decoded),
+                Err(_) => return "ERROR\n".to_string(),
+            };
+            // Optional per-ledger TTL and path label; the daemon TTL is the default
+            let entry_ttl = match parts.next().map(str::parse::<u64>) {
+                Some(Ok(seconds)) => Duration::from_secs(seconds),
+                Some(Err(_)) => return "ERROR\n".to_string(),
+                None => ttl,
+            };
+            let ledger_path = parts
+                .next()
+                .and_then(|label| STANDARD.decode(label.as_bytes()).ok())
+                .and_then(|bytes| String::from_utf8(bytes).ok());
+            let token = match random_key() {
+                Ok(token) => token,
+                Err(_) => return "ERROR\n".to_string(),
+            };
+            match seal(&state.session_key, &secret, key.as_bytes()) {
+                Ok(sealed) => {
+                    state.entries.insert(
                         key.to_string(),
                         CacheEntry {
-                            passphrase: Zeroizing::new(decoded),
+                            sealed,
+                            auth_key: token_auth_key(token.as_slice()),
+                            wrap_key: token_wrap_key(token.as_slice()),
                             stored_at: Instant::now(),
+                            ttl: entry_ttl,
+                            ledger_path,
                         },
                     );
-                    "OK\n".to_string()
+                    format!("OK {}\n", STANDARD.encode(token.as_slice()))
                 }
                 Err(_) => "ERROR\n".to_string(),
             }
@@ -192,47 +483,231 @@ fn handle_request(request: &str, cache: &mut HashMap<String, CacheEntry>, ttl: D
     }
 }
 
-fn expire_entries(cache: &mut HashMap<String, CacheEntry>, ttl: Duration) {
-    if ttl == Duration::from_secs(0) {
-        cache.clear();
-        return;
-    }
-    let expired: Vec<String> = cache
-        .iter()
-        .filter_map(|(key, entry)| {
-            if entry.stored_at.elapsed() > ttl {
-                Some(key.clone())
-            } else {
-                None
-            }
-        })
-        .collect();
-    for key in expired {
-        cache.remove(&key);
+fn expire_entries(cache: &mut HashMap<String, CacheEntry>) {
+    cache.retain(|_, entry| entry.remaining().is_some());
+}
+
+/// Answer one client connection. Returns `true` when it asked the daemon to
+/// stop.
+fn serve_connection(
+    stream: &mut std::os::unix::net::UnixStream,
+    state: &mut CacheState,
+    ttl: Duration,
+) -> anyhow::Result<bool> {
+    // Accepted sockets inherit the listener's non-blocking mode on some platforms
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    if peer_uid(stream)? != current_uid() {
+        return Ok(false);
     }
+
+    let hello = match read_frame(stream) {
+        Ok(hello) => hello,
+        Err(_) => {
+            // An unframed (version 1) client reads this as an error line; its
+            // request is drained first so closing does not reset the socket
+            let _ = std::io::copy(
+                &mut (&*stream).take(MAX_FRAME_LEN as u64),
+                &mut std::io::sink(),
+            );
+            stream.write_all(b"ERROR protocol\n")?;
+            return Ok(false);
+        }
+    };
+    let client_version = parse_hello(&hello);
+    let request = Zeroizing::new(
+        String::from_utf8(read_frame(stream)?.to_vec())
+            .map_err(|_| anyhow::anyhow!("Cache request is not valid UTF-8"))?,
+    );
+    let request = request.trim();
+    let stop = request == "STOP";
+
+    let response = if client_version == Some(PROTOCOL_VERSION) || stop {
+        Zeroizing::new(handle_request(request, state, ttl))
+    } else {
+        Zeroizing::new("MISMATCH\n".to_string())
+    };
+    write_frame(stream, hello_line().as_bytes())?;
+    write_frame(stream, response.trim_end().as_bytes())?;
+    Ok(stop)
 }
 
-fn send_line(stream: &mut std::os::unix::net::UnixStream, line: &str) -> anyhow::Result<()> {
+/// Send one request and return the daemon's response.
+///
+/// Fails with [`ProtocolMismatch`] when the daemon speaks another version.
+fn exchange(stream: &mut std::os::unix::net::UnixStream, request: &str) -> anyhow::Result<String> {
     use std::net::Shutdown;
+    let uid = peer_uid(stream).map_err(|e| anyhow::anyhow!("Cache peer check failed: {}", e))?;
+    if uid != current_uid() {
+        return Err(anyhow::anyhow!(
+            "Cache socket is served by uid {}, not you; refusing to use it",
+            uid
+        ));
+    }
+
+    let mut frames = Vec::new();
+    encode_frame(&mut frames, hello_line().as_bytes())?;
+    encode_frame(&mut frames, request.as_bytes())?;
+    let frames = Zeroizing::new(frames);
     stream
-        .write_all(format!("{}\n", line).as_bytes())
+        .write_all(&frames)
+        .and_then(|()| stream.shutdown(Shutdown::Write))
         .map_err(|e| anyhow::anyhow!("Cache write failed: {}", e))?;
+
+    let mut reply = Zeroizing::new(Vec::new());
+    stream
+        .read_to_end(&mut reply)
+        .map_err(|e| anyhow::anyhow!("Cache read failed: {}", e))?;
+    let mut reader = reply.as_slice();
+    // Anything that is not a framed hello comes from an unversioned daemon
+    let daemon_version = read_frame(&mut reader)
+        .ok()
+        .and_then(|hello| parse_hello(&hello))
+        .unwrap_or(1);
+    if daemon_version != PROTOCOL_VERSION {
+        return Err(ProtocolMismatch {
+            daemon: daemon_version,
+        }
+        .into());
+    }
+    let response = read_frame(&mut reader)?;
+    let response = std::str::from_utf8(&response)
+        .map_err(|_| anyhow::anyhow!("Cache response is not valid UTF-8"))?;
+    if response == "MISMATCH" {
+        return Err(ProtocolMismatch {
+            daemon: daemon_version,
+        }
+        .into());
+    }
+    Ok(response.trim().to_string())
+}
+
+/// Stop a daemon speaking another protocol version, wiping its sessions.
+fn stop_mismatched(socket_path: &Path, daemon_version: u32) -> anyhow::Result<()> {
+    let mut stream = std::os::unix::net::UnixStream::connect(socket_path)
+        .map_err(|e| anyhow::anyhow!("Cache connect failed: {}", e))?;
+    if peer_uid(&stream)? != current_uid() {
+        return Err(anyhow::anyhow!(
+            "Cache socket is served by another user; refusing to use it"
+        ));
+    }
+    if daemon_version == 1 {
+        // The unframed protocol: one line, then end of input
+        stream
+            .write_all(b"STOP\n")
+            .and_then(|()| stream.shutdown(std::net::Shutdown::Write))
+            .map_err(|e| anyhow::anyhow!("Cache write failed: {}", e))?;
+        let _ = stream.read_to_end(&mut Vec::new());
+    } else {
+        // Every version honours a framed STOP, whatever its hello says
+        let _ = exchange(&mut stream, "STOP");
+    }
+    Ok(())
+}
+
+fn hello_line() -> String {
+    format!("HELLO {} {}", PROTOCOL_NAME, PROTOCOL_VERSION)
+}
+
+/// Protocol version from a hello frame, if it is one.
+fn parse_hello(frame: &[u8]) -> Option<u32> {
+    let hello = std::str::from_utf8(frame).ok()?;
+    let mut parts = hello.split_whitespace();
+    if parts.next()? != "HELLO" || parts.next()? != PROTOCOL_NAME {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+/// Append `payload` to `out` behind a 4-byte big-endian length.
+fn encode_frame(out: &mut Vec<u8>, payload: &[u8]) -> anyhow::Result<()> {
+    if payload.len() > MAX_FRAME_LEN {
+        return Err(anyhow::anyhow!("Cache frame is too large"));
+    }
+    out.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    out.extend_from_slice(payload);
+    Ok(())
+}
+
+fn write_frame(stream: &mut impl Write, payload: &[u8]) -> anyhow::Result<()> {
+    let mut frame = Zeroizing::new(Vec::with_capacity(payload.len() + 4));
+    encode_frame(&mut frame, payload)?;
     stream
-        .shutdown(Shutdown::Write)
-        .map_err(|e| anyhow::anyhow!("Cache shutdown failed: {}", e))
+        .write_all(&frame)
+        .map_err(|e| anyhow::anyhow!("Cache write failed: {}", e))
 }
 
-fn read_response(stream: &mut std::os::unix::net::UnixStream) -> anyhow::Result<String> {
-    let mut response = String::new();
+fn read_frame(stream: &mut impl Read) -> anyhow::Result<Zeroizing<Vec<u8>>> {
+    let mut len = [0u8; 4];
     stream
-        .read_to_string(&mut response)
+        .read_exact(&mut len)
         .map_err(|e| anyhow::anyhow!("Cache read failed: {}", e))?;
-    Ok(response.trim().to_string())
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(anyhow::anyhow!("Cache frame is too large"));
+    }
+    let mut payload = Zeroizing::new(vec![0u8; len]);
+    stream
+        .read_exact(&mut payload)
+        .map_err(|e| anyhow::anyhow!("Cache read failed: {}", e))?;
+    Ok(payload)
+}
+
+fn current_uid() -> u32 {
+    unsafe { libc::geteuid() }
+}
+
+/// Uid of the process on the other end of a Unix socket.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer_uid(stream: &std::os::unix::net::UnixStream) -> std::io::Result<u32> {
+    use std::os::unix::io::AsRawFd;
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            (&mut cred as *mut libc::ucred).cast(),
+            &mut len,
+        )
+    };
+    if rc != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(cred.uid)
+}
+
+/// Uid of the process on the other end of a Unix socket.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn peer_uid(stream: &std::os::unix::net::UnixStream) -> std::io::Result<u32> {
+    use std::os::unix::io::AsRawFd;
+    let mut uid = 0;
+    let mut gid = 0;
+    if unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(uid)
 }
 
 fn ensure_daemon_running(config: &CacheConfig) -> anyhow::Result<()> {
-    if cache_ping(&config.socket_path)? {
-        return Ok(());
+    match cache_ping(&config.socket_path) {
+        Ok(true) => return Ok(()),
+        Ok(false) => {}
+        // Replace a daemon from another version rather than misread its cache
+        Err(err) => match err.downcast_ref::<ProtocolMismatch>() {
+            Some(mismatch) => {
+                stop_mismatched(&config.socket_path, mismatch.daemon)?;
+                remove_token_files(&config.socket_path);
+                wait_for_socket_removal(&config.socket_path);
+            }
+            None => return Err(err),
+        },
     }
 
     let exe = std::env::current_exe().map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -258,6 +733,136 @@ fn ensure_daemon_running(config: &CacheConfig) -> anyhow::Result<()> {
     Err(anyhow::anyhow!("Cache daemon did not become ready in time"))
 }
 
+/// Give a stopping daemon a moment to remove its socket, so the new one
+/// does not race it for the path.
+fn wait_for_socket_removal(socket_path: &Path) {
+    for _ in 0..20 {
+        if !socket_path.exists() {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn random_key() -> anyhow::Result<Zeroizing<[u8; KEY_LEN]>> {
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    getrandom::getrandom(key.as_mut_slice())
+        .map_err(|e| anyhow::anyhow!("Failed to generate cache key: {}", e))?;
+    Ok(key)
+}
+
+fn token_wrap_key(token: &[u8]) -> Zeroizing<[u8; KEY_LEN]> {
+    Zeroizing::new(blake3::derive_key(TOKEN_WRAP_CONTEXT, token))
+}
+
+fn token_auth_key(token: &[u8]) -> Zeroizing<[u8; KEY_LEN]> {
+    Zeroizing::new(blake3::derive_key(TOKEN_AUTH_CONTEXT, token))
+}
+
+/// MAC proving possession of a token for one `GET`.
+fn request_mac(auth_key: &[u8; KEY_LEN], key: &str, nonce: &[u8]) -> blake3::Hash {
+    let mut hasher = blake3::Hasher::new_keyed(auth_key);
+    hasher.update(b"GET ");
+    hasher.update(key.as_bytes());
+    hasher.update(b" ");
+    hasher.update(nonce);
+    hasher.finalize()
+}
+
+/// A `GET` for `key` that proves possession of `token` without sending it.
+///
+/// Returns the request and its nonce, which the reply is sealed to, so a
+/// replayed request only yields a reply the token holder can open.
+fn get_request(key: &str, token: &[u8]) -> anyhow::Result<(String, [u8; REQUEST_NONCE_LEN])> {
+    let mut nonce = [0u8; REQUEST_NONCE_LEN];
+    getrandom::getrandom(&mut nonce)
+        .map_err(|e| anyhow::anyhow!("Failed to generate cache nonce: {}", e))?;
+    let mac = request_mac(&token_auth_key(token), key, &nonce);
+    let request = format!(
+        "GET {} {} {}",
+        key,
+        STANDARD.encode(nonce),
+        STANDARD.encode(mac.as_bytes())
+    );
+    Ok((request, nonce))
+}
+
+/// Associated data for a `GET` reply: the ledger key and request nonce.
+fn reply_aad(key: &str, nonce: &[u8]) -> Vec<u8> {
+    let mut aad = key.as_bytes().to_vec();
+    aad.extend_from_slice(nonce);
+    aad
+}
+
+/// Seal `secret` as `nonce || ciphertext`, bound to the ledger key.
+fn seal(key: &[u8; KEY_LEN], secret: &[u8], aad: &[u8]) -> anyhow::Result<Zeroizing<Vec<u8>>> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut nonce)
+        .map_err(|e| anyhow::anyhow!("Failed to generate cache nonce: {}", e))?;
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: secret, aad })
+        .map_err(|_| anyhow::anyhow!("Cache seal failed"))?;
+    let mut sealed = Zeroizing::new(nonce.to_vec());
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+fn open_sealed(
+    key: &[u8; KEY_LEN],
+    sealed: &[u8],
+    aad: &[u8],
+) -> anyhow::Result<Zeroizing<Vec<u8>>> {
+    if sealed.len() < NONCE_LEN {
+        return Err(anyhow::anyhow!("Cache entry is truncated"));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let cipher = XChaCha20Poly1305::new(key.into());
+    cipher
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| anyhow::anyhow!("Cache entry could not be unsealed"))
+}
+
+/// Capability token file for a ledger, stored beside the cache socket.
+fn token_path(socket_path: &Path, key: &str) -> PathBuf {
+    socket_path.with_file_name(format!("cache-{}.token", key))
+}
+
+fn write_token(path: &Path, token: &str) -> anyhow::Result<()> {
+    use std::os::unix::fs::OpenOptionsExt;
+    let _ = std::fs::remove_file(path);
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| anyhow::anyhow!("Cache token write failed: {}", e))?;
+    file.write_all(token.as_bytes())
+        .map_err(|e| anyhow::anyhow!("Cache token write failed: {}", e))
+}
+
+fn remove_token_files(socket_path: &Path) {
+    let Some(dir) = socket_path.parent() else {
+        return;
+    };
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("cache-") && name.ends_with(".token") {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
 pub fn ledger_hash(path: &Path) -> String {
     let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
     let hash = blake3::hash(canonical.to_string_lossy().as_bytes());
@@ -271,3 +876,233 @@ fn set_socket_permissions(path: &Path) -> anyhow::Result<()> {
     std::fs::set_permissions(path, perms)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(state: &mut CacheState, key: &str, secret: &str) -> Vec<u8> {
+        let response = handle_request(
+            &format!("STORE {} {}", key, STANDARD.encode(secret)),
+            state,
+            Duration::from_secs(60),
+        );
+        let token = response.trim().strip_prefix("OK ").expect("token issued");
+        STANDARD.decode(token).expect("token is base64")
+    }
+
+    fn get(state: &mut CacheState, key: &str, token: &[u8]) -> String {
+        let (request, _) = get_request(key, token).unwrap();
+        handle_request(&request, state, Duration::from_secs(60))
+    }
+
+    #[test]
+    fn test_get_returns_secret_sealed_for_token() {
+        let mut state = CacheState::new().unwrap();
+        let token = store(&mut state, "abc", "hunter2-passphrase");
+
+        let (request, nonce) = get_request("abc", &token).unwrap();
+        assert!(!request.contains(&STANDARD.encode(&token)));
+        let response = handle_request(&request, &mut state, Duration::from_secs(60));
+        assert!(!response.contains(&STANDARD.encode("hunter2-passphrase")));
+        let sealed = STANDARD
+            .decode(response.trim().strip_prefix("SEALED ").unwrap())
+            .unwrap();
+        let secret = open_sealed(&token_wrap_key(&token), &sealed, &reply_aad("abc", &nonce));
+        assert_eq!(secret.unwrap().as_slice(), b"hunter2-passphrase");
+        // The reply is bound to its request's nonce
+        assert!(open_sealed(&token_wrap_key(&token), &sealed, b"abc").is_err());
+    }
+
+    #[test]
+    fn test_get_requires_a_mac_over_its_nonce() {
+        let mut state = CacheState::new().unwrap();
+        let token = store(&mut state, "abc", "hunter2-passphrase");
+        let (request, _) = get_request("abc", &token).unwrap();
+        let fields: Vec<&str> = request.split_whitespace().collect();
+
+        // A MAC lifted onto another nonce does not verify
+        let other_nonce = STANDARD.encode([7u8; REQUEST_NONCE_LEN]);
+        let forged = format!("GET abc {} {}", other_nonce, fields[3]);
+        assert_eq!(
+            handle_request(&forged, &mut state, Duration::from_secs(60)),
+            "NOT_FOUND\n"
+        );
+        // Sending the raw token, as protocol v2 did, is not accepted
+        let raw = format!("GET abc {}", STANDARD.encode(&token));
+        assert_eq!(
+            handle_request(&raw, &mut state, Duration::from_secs(60)),
+            "ERROR\n"
+        );
+        assert!(
+            handle_request(&request, &mut state, Duration::from_secs(60)).starts_with("SEALED ")
+        );
+    }
+
+    #[test]
+    fn test_get_rejects_wrong_token_and_ledger() {
+        let mut state = CacheState::new().unwrap();
+        let token = store(&mut state, "abc", "hunter2-passphrase");
+
+        assert_eq!(get(&mut state, "abc", &[0u8; KEY_LEN]), "NOT_FOUND\n");
+        assert_eq!(get(&mut state, "other", &token), "NOT_FOUND\n");
+    }
+
+    #[test]
+    fn test_restore_and_clear_revoke_tokens() {
+        let mut state = CacheState::new().unwrap();
+        let first = store(&mut state, "abc", "hunter2-passphrase");
+        let second = store(&mut state, "abc", "hunter2-passphrase");
+        assert_eq!(get(&mut state, "abc", &first), "NOT_FOUND\n");
+        assert!(get(&mut state, "abc", &second).starts_with("SEALED "));
+
+        assert_eq!(
+            handle_request("CLEAR", &mut state, Duration::from_secs(60)),
+            "OK\n"
+        );
+        assert_eq!(get(&mut state, "abc", &second), "NOT_FOUND\n");
+    }
+
+    #[test]
+    fn test_sessions_have_independent_ttls_and_clear() {
+        let mut state = CacheState::new().unwrap();
+        let label = STANDARD.encode("/tmp/a.ledger");
+        let response = handle_request(
+            &format!("STORE a {} 0 {}", STANDARD.encode("pass-a"), label),
+            &mut state,
+            Duration::from_secs(60),
+        );
+        assert!(response.starts_with("OK "));
+        let token_b = store(&mut state, "b", "pass-b");
+
+        // "a" expired immediately, "b" uses the daemon default
+        let listing = handle_request("LIST", &mut state, Duration::from_secs(60));
+        let sessions: Vec<CacheSession> = listing.lines().filter_map(parse_session_line).collect();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].key, "b");
+        assert_eq!(sessions[0].ttl, Duration::from_secs(60));
+
+        expire_entries(&mut state.entries);
+        assert!(!state.entries.contains_key("a"));
+
+        store(&mut state, "c", "pass-c");
+        assert_eq!(
+            handle_request("CLEAR b", &mut state, Duration::from_secs(60)),
+            "OK\n"
+        );
+        assert_eq!(get(&mut state, "b", &token_b), "NOT_FOUND\n");
+        assert!(state.entries.contains_key("c"));
+        assert_eq!(
+            handle_request("CLEAR b", &mut state, Duration::from_secs(60)),
+            "NOT_FOUND\n"
+        );
+    }
+
+    fn serve_pair(
+        state: CacheState,
+    ) -> (
+        std::os::unix::net::UnixStream,
+        std::thread::JoinHandle<(CacheState, bool)>,
+    ) {
+        let (client, mut server) = std::os::unix::net::UnixStream::pair().unwrap();
+        let handle = std::thread::spawn(move || {
+            let mut state = state;
+            let stop = serve_connection(&mut server, &mut state, Duration::from_secs(60)).unwrap();
+            (state, stop)
+        });
+        (client, handle)
+    }
+
+    #[test]
+    fn test_framed_exchange_checks_peer_and_version() {
+        let (mut client, handle) = serve_pair(CacheState::new().unwrap());
+        assert_eq!(exchange(&mut client, "PING").unwrap(), "PONG");
+        let (_, stop) = handle.join().unwrap();
+        assert!(!stop);
+    }
+
+    #[test]
+    fn test_other_versions_are_refused_except_stop() {
+        let mut state = CacheState::new().unwrap();
+        let token = store(&mut state, "abc", "hunter2-passphrase");
+        let future_hello = format!("HELLO {} {}", PROTOCOL_NAME, PROTOCOL_VERSION + 1);
+        let send = |state: CacheState, request: &str| {
+            let (mut client, handle) = serve_pair(state);
+            let mut frames = Vec::new();
+            encode_frame(&mut frames, future_hello.as_bytes()).unwrap();
+            encode_frame(&mut frames, request.as_bytes()).unwrap();
+            client.write_all(&frames).unwrap();
+            client.shutdown(std::net::Shutdown::Write).unwrap();
+            let mut reply = Vec::new();
+            client.read_to_end(&mut reply).unwrap();
+            let mut reader = reply.as_slice();
+            assert_eq!(
+                parse_hello(&read_frame(&mut reader).unwrap()),
+                Some(PROTOCOL_VERSION)
+            );
+            let response = String::from_utf8(read_frame(&mut reader).unwrap().to_vec()).unwrap();
+            let (state, stop) = handle.join().unwrap();
+            (state, response, stop)
+        };
+
+        let (request, _) = get_request("abc", &token).unwrap();
+        let (state, response, stop) = send(state, &request);
+        assert_eq!(response, "MISMATCH");
+        assert!(!stop);
+        assert!(state.entries.contains_key("abc"));
+
+        let (state, response, stop) = send(state, "STOP");
+        assert_eq!(response, "OK");
+        assert!(stop);
+        assert!(state.entries.is_empty());
+    }
+
+    #[test]
+    fn test_unframed_peers_get_protocol_errors() {
+        // An old client's line gets a line back
+        let (mut client, handle) = serve_pair(CacheState::new().unwrap());
+        client.write_all(b"PING\n").unwrap();
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+        let mut reply = String::new();
+        client.read_to_string(&mut reply).unwrap();
+        assert_eq!(reply, "ERROR protocol\n");
+        handle.join().unwrap();
+
+        // An old daemon's line reads as protocol version 1
+        let (mut client, mut server) = std::os::unix::net::UnixStream::pair().unwrap();
+        let handle = std::thread::spawn(move || {
+            let mut request = Vec::new();
+            server.read_to_end(&mut request).unwrap();
+            server.write_all(b"ERROR\n").unwrap();
+        });
+        let err = exchange(&mut client, "PING").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ProtocolMismatch>(),
+            Some(&ProtocolMismatch { daemon: 1 })
+        );
+        assert!(err.to_string().contains("ledger lock --now"));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_oversized_frames_are_rejected() {
+        let mut frame = Vec::new();
+        assert!(encode_frame(&mut frame, &vec![0u8; MAX_FRAME_LEN + 1]).is_err());
+        let huge = ((MAX_FRAME_LEN + 1) as u32).to_be_bytes();
+        let err = read_frame(&mut huge.as_slice()).unwrap_err();
+        assert!(err.to_string().contains("too large"));
+    }
+
+    #[test]
+    fn test_stop_wipes_every_session() {
+        let mut state = CacheState::new().unwrap();
+        let token = store(&mut state, "a", "pass-a");
+        store(&mut state, "b", "pass-b");
+        assert_eq!(
+            handle_request("STOP", &mut state, Duration::from_secs(60)),
+            "OK\n"
+        );
+        assert!(state.entries.is_empty());
+        assert_eq!(get(&mut state, "a", &token), "NOT_FOUND\n");
+    }
+}
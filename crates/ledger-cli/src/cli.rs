@@ -21,12 +21,33 @@ pub struct Cli {
     pub quiet: bool,
 
     /// Disable colored output
-    #[arg(long, global = true, env = "NO_COLOR")]
+    #[arg(
+        long,
+        global = true,
+        env = "NO_COLOR",
+        value_parser = clap::builder::FalseyValueParser::new()
+    )]
     pub no_color: bool,
 
     /// Use ASCII-only symbols (no Unicode)
     #[arg(long, global = true)]
     pub ascii: bool,
+
+    /// Skip the failed-unlock lockout wait (attempts are still recorded)
+    #[arg(long, global = true)]
+    pub no_lockout: bool,
+
+    /// Show what a command would change without writing the ledger
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Fail on warnings (unknown config keys, skipped steps) with exit code 7
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// Answer yes to confirmations before destructive changes
+    #[arg(short = 'y', long, global = true)]
+    pub yes: bool,
 }
 
 /// Arguments for the `init` command
@@ -55,6 +76,50 @@ pub struct InitArgs {
     /// Keyfile path override
     #[arg(long)]
     pub keyfile_path: Option<String>,
+
+    /// Search index tokenizer: unicode61, porter (English stemming), or trigram
+    #[arg(long, value_name = "NAME")]
+    pub search_tokenizer: Option<String>,
+}
+
+/// Arguments for the `adopt` command
+#[derive(Args)]
+pub struct AdoptArgs {
+    /// Existing ledger file to write a config for
+    #[arg(value_name = "PATH")]
+    pub path: String,
+
+    /// Security tier: passphrase, passphrase_keychain, passphrase_keyfile or device_keyfile
+    #[arg(long, value_name = "TIER", default_value = "passphrase")]
+    pub tier: String,
+
+    /// Keyfile that unlocks the ledger (keyfile tiers; defaults to the standard location)
+    #[arg(long, value_name = "PATH")]
+    pub keyfile_path: Option<String>,
+
+    /// Passphrase cache TTL seconds
+    #[arg(long)]
+    pub passphrase_cache_ttl_seconds: Option<u64>,
+
+    /// Set timezone
+    #[arg(long)]
+    pub timezone: Option<String>,
+
+    /// Set default editor
+    #[arg(long)]
+    pub editor: Option<String>,
+
+    /// Replace an existing config
+    #[arg(long)]
+    pub force: bool,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for the `add` command
@@ -76,6 +141,10 @@ pub struct AddArgs {
     #[arg(long)]
     pub date: Option<String>,
 
+    /// Editor command for this entry, e.g. "code --wait" (overrides [ui] editor, $VISUAL, $EDITOR)
+    #[arg(long, value_name = "COMMAND")]
+    pub editor: Option<String>,
+
     /// Disable interactive prompts
     #[arg(long)]
     pub no_input: bool,
@@ -95,12 +164,72 @@ pub struct AddArgs {
     /// Set field values (format: field=value, can be repeated)
     #[arg(long = "field", short = 'f', value_name = "FIELD=VALUE")]
     pub fields: Vec<String>,
+
+    /// Bookmark URL (shorthand for --field url=<URL>)
+    #[arg(long, value_name = "URL")]
+    pub url: Option<String>,
+
+    /// Fetch the page title for a bookmark when none is given
+    #[arg(long)]
+    pub fetch_title: bool,
+
+    /// Attach an audio memo and use its transcript as the body (journal only)
+    #[arg(long, value_name = "FILE")]
+    pub from_audio: Option<String>,
+
+    /// Image to attach; EXIF time and GPS fill the entry (photo only)
+    #[arg(long, value_name = "FILE")]
+    pub file: Option<String>,
+
+    /// Spell check the body before saving and offer corrections (warnings only with --no-input)
+    #[arg(long)]
+    pub lint: bool,
+
+    /// Suggest tags from similar past entries and confirm them (listed only with --no-input)
+    #[arg(long)]
+    pub suggest_tags: bool,
+
+    /// Record the add under this key; replaying it returns the existing entry
+    #[arg(long, value_name = "KEY")]
+    pub idempotency_key: Option<String>,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `backfill` command
+#[derive(Args)]
+pub struct BackfillArgs {
+    /// Days to fill in, inclusive (YYYY-MM-DD..YYYY-MM-DD, or one day)
+    #[arg(long, value_name = "START..END")]
+    pub date: String,
+
+    /// Template for each entry (defaults to the entry type's default template)
+    #[arg(long, value_name = "TEMPLATE")]
+    pub template: Option<String>,
+
+    /// Entry type to fill in (defaults to the template's type, else journal)
+    #[arg(long = "type", value_name = "TYPE")]
+    pub entry_type: Option<String>,
+
+    /// Add tags to each entry (replaces the template's default tags)
+    #[arg(short, long, value_name = "TAG")]
+    pub tag: Vec<String>,
+
+    /// Insert template defaults without prompting or opening the editor
+    #[arg(long)]
+    pub no_input: bool,
+
+    /// Output a JSON summary
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for the `edit` command
 #[derive(Args)]
 pub struct EditArgs {
-    /// Entry ID (full UUID)
+    /// Entry ID (full UUID, or a day number like 2025-02-03#4)
     #[arg(value_name = "ID")]
     pub id: String,
 
@@ -108,9 +237,17 @@ pub struct EditArgs {
     #[arg(long)]
     pub body: Option<String>,
 
+    /// Editor command for this entry, e.g. "code --wait" (overrides [ui] editor, $VISUAL, $EDITOR)
+    #[arg(long, value_name = "COMMAND")]
+    pub editor: Option<String>,
+
     /// Disable interactive prompts
     #[arg(long)]
     pub no_input: bool,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for the `list` command
@@ -120,7 +257,7 @@ pub struct ListArgs {
     #[arg(value_name = "TYPE")]
     pub entry_type: Option<String>,
 
-    /// Filter by tag
+    /// Filter by tag; a trailing '/' (e.g. work/) includes nested tags
     #[arg(long)]
     pub tag: Option<String>,
 
@@ -128,6 +265,10 @@ pub struct ListArgs {
     #[arg(long)]
     pub last: Option<String>,
 
+    /// Only entries written by this app, command or host (see `show --verbose`)
+    #[arg(long, value_name = "ORIGIN")]
+    pub origin: Option<String>,
+
     /// Start date (ISO-8601)
     #[arg(long)]
     pub since: Option<String>,
@@ -151,6 +292,14 @@ pub struct ListArgs {
     /// Include superseded revisions
     #[arg(long)]
     pub history: bool,
+
+    /// Group bookmark entries by URL domain
+    #[arg(long)]
+    pub by_domain: bool,
+
+    /// Sort order (created, last-viewed)
+    #[arg(long, value_name = "ORDER")]
+    pub sort: Option<String>,
 }
 
 /// Arguments for the `search` command
@@ -164,6 +313,10 @@ pub struct SearchArgs {
     #[arg(long)]
     pub r#type: Option<String>,
 
+    /// Filter by tag; a trailing '/' (e.g. work/) includes nested tags
+    #[arg(long)]
+    pub tag: Option<String>,
+
     /// Time window (e.g., "7d", "30d")
     #[arg(long)]
     pub last: Option<String>,
@@ -188,29 +341,160 @@ pub struct SearchArgs {
 /// Arguments for the `show` command
 #[derive(Args)]
 pub struct ShowArgs {
-    /// Entry ID (full UUID)
+    /// Entry ID (full UUID, or a day number like 2025-02-03#4)
     #[arg(value_name = "ID")]
     pub id: String,
 
+    /// Wrap the body at this many columns (default: terminal width, 0 disables)
+    #[arg(long, value_name = "COLUMNS")]
+    pub width: Option<usize>,
+
+    /// Related entries to list: shared rare tags, compositions or similar text (0 hides them)
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub related: usize,
+
+    /// Also show where the entry came from: app, version, command and host
+    #[arg(long)]
+    pub verbose: bool,
+
     /// Output as JSON
     #[arg(long)]
     pub json: bool,
+
+    /// Print the entry as a json, yaml or toml document
+    #[arg(long, value_name = "FORMAT", conflicts_with = "json")]
+    pub format: Option<String>,
 }
 
-/// Arguments for the `export` command
+/// Arguments for the `check` command
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Output issues as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Monitoring mode: never prompt, also check the last backup's age against
+    /// `[backup] max_age_hours`, and print one status line
+    #[arg(long)]
+    pub healthcheck: bool,
+}
+
+/// Arguments for the `status` command
 #[derive(Args)]
+pub struct StatusArgs {
+    /// Output status as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `export` command
+#[derive(Args, Clone)]
 pub struct ExportArgs {
     /// Filter by entry type
     #[arg(value_name = "TYPE")]
     pub entry_type: Option<String>,
 
-    /// Output format
-    #[arg(long, default_value = "json")]
-    pub format: String,
+    /// Filter by entry type (same as the positional TYPE)
+    #[arg(long = "type", value_name = "TYPE", conflicts_with = "entry_type")]
+    pub r#type: Option<String>,
+
+    /// Output format (json, jsonl, yaml, toml, atom, markdown, org, or todotxt for tasks);
+    /// only jsonl streams, the others are built in memory first [default: json]
+    #[arg(long)]
+    pub format: Option<String>,
 
     /// Start date (ISO-8601)
     #[arg(long)]
     pub since: Option<String>,
+
+    /// Only export entries with this tag; a trailing '/' includes nested tags
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+
+    /// Write to a file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    pub out: Option<String>,
+
+    /// Encrypt the --out file with age under the ledger passphrase (`age -d`
+    /// decrypts it); the export is built in memory, so nothing plaintext is
+    /// written
+    #[arg(long, requires = "out")]
+    pub encrypt_output: bool,
+
+    /// Also export structure: compositions, templates, types (comma-separated)
+    #[arg(long, value_delimiter = ',', value_name = "SECTIONS")]
+    pub include: Vec<String>,
+
+    /// Write one file per month or year (UTC) into --out-dir, with a manifest
+    #[arg(long, value_name = "PERIOD", requires = "out_dir")]
+    pub chunk_by: Option<String>,
+
+    /// Directory for chunk files and manifest.json
+    #[arg(
+        long,
+        value_name = "DIR",
+        requires = "chunk_by",
+        conflicts_with = "out"
+    )]
+    pub out_dir: Option<String>,
+
+    /// Continue a chunked export, skipping chunks already in the manifest
+    #[arg(long, requires = "chunk_by")]
+    pub resume: bool,
+
+    /// Leave out entries with this tag or tags nested under it (repeatable)
+    #[arg(long, value_name = "TAG")]
+    pub redact: Vec<String>,
+
+    /// Use the settings of [export.profiles.<NAME>] in config; flags given
+    /// here override them, and --redact adds to the profile's tags
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+}
+
+impl ExportArgs {
+    /// The output format, `json` unless one was given.
+    pub fn format(&self) -> &str {
+        self.format.as_deref().unwrap_or("json")
+    }
+}
+
+/// Arguments for the `export-diff` command
+#[derive(Args)]
+pub struct ExportDiffArgs {
+    /// Previous JSON or JSONL export to compare against (`-` for stdin)
+    #[arg(value_name = "OLD_EXPORT")]
+    pub old_export: String,
+
+    /// Output format (jsonl or json)
+    #[arg(long, default_value = "jsonl")]
+    pub format: String,
+
+    /// Write to a file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    pub out: Option<String>,
+}
+
+/// Arguments for the `import` command
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Export file to import (`-` for stdin)
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    /// Source format: ledger (JSON/JSONL export), yaml (YAML export), org (edited
+    /// headlines become revisions) or todotxt
+    #[arg(long, default_value = "ledger", value_name = "FORMAT")]
+    pub from: String,
+
+    /// Record the import under this key; replaying it reports the first
+    /// import instead of importing again
+    #[arg(long, value_name = "KEY")]
+    pub idempotency_key: Option<String>,
+
+    /// Output the import summary as JSON
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for the `backup` command
@@ -221,6 +505,168 @@ pub struct BackupArgs {
     pub destination: String,
 }
 
+/// Arguments for the `systemd` command
+#[derive(Args)]
+pub struct SystemdArgs {
+    #[command(subcommand)]
+    pub command: SystemdSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum SystemdSubcommand {
+    /// Write and enable units for the cache daemon, backups and healthchecks
+    Install(SystemdInstallArgs),
+}
+
+/// Arguments for `systemd install`
+#[derive(Args)]
+pub struct SystemdInstallArgs {
+    /// Install user units (~/.config/systemd/user) instead of system units
+    #[arg(long)]
+    pub user: bool,
+
+    /// Write the units to this directory instead
+    #[arg(long, value_name = "DIR")]
+    pub dir: Option<String>,
+
+    /// Backup destination [default: <data dir>/backups/<ledger name>.bak]
+    #[arg(long, value_name = "PATH")]
+    pub backup_to: Option<String>,
+
+    /// When to back up (systemd OnCalendar expression)
+    #[arg(long, value_name = "CALENDAR", default_value = "daily")]
+    pub backup_schedule: String,
+
+    /// When to run `check --healthcheck` (systemd OnCalendar expression)
+    #[arg(long, value_name = "CALENDAR", default_value = "hourly")]
+    pub healthcheck_schedule: String,
+
+    /// Only write the unit files; do not run systemctl
+    #[arg(long)]
+    pub no_enable: bool,
+
+    /// Replace unit files that were not generated by ledger
+    #[arg(long)]
+    pub force: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `launchd` command
+#[derive(Args)]
+pub struct LaunchdArgs {
+    #[command(subcommand)]
+    pub command: LaunchdSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum LaunchdSubcommand {
+    /// Write and load agents for the cache daemon, backups and healthchecks
+    Install(LaunchdInstallArgs),
+}
+
+/// Arguments for `launchd install`
+#[derive(Args)]
+pub struct LaunchdInstallArgs {
+    /// Write the agents to this directory [default: ~/Library/LaunchAgents]
+    #[arg(long, value_name = "DIR")]
+    pub dir: Option<String>,
+
+    /// Backup destination [default: <data dir>/backups/<ledger name>.bak]
+    #[arg(long, value_name = "PATH")]
+    pub backup_to: Option<String>,
+
+    /// Hour of the day (0-23) to back up
+    #[arg(long, value_name = "HOUR", default_value_t = 3)]
+    pub backup_hour: u32,
+
+    /// How often to run `check --healthcheck` (e.g. 30m, 1h)
+    #[arg(long, value_name = "DURATION", default_value = "1h")]
+    pub healthcheck_every: String,
+
+    /// Only write the plist files; do not run launchctl
+    #[arg(long)]
+    pub no_load: bool,
+
+    /// Replace plist files that were not generated by ledger
+    #[arg(long)]
+    pub force: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `move` command
+#[derive(Args)]
+pub struct MoveArgs {
+    /// New path for the ledger file (must not exist)
+    #[arg(long, value_name = "PATH")]
+    pub to: String,
+
+    /// Remove the old ledger without asking
+    #[arg(long)]
+    pub force: bool,
+
+    /// Disable interactive prompts (the old ledger is kept unless --force)
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for the `recover` command
+#[derive(Args)]
+pub struct RecoverArgs {
+    /// New ledger file to write salvaged data to (must not exist)
+    #[arg(long, value_name = "PATH")]
+    pub out: String,
+
+    /// Output the recovery report as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for the `reindex` command
+#[derive(Args)]
+pub struct ReindexArgs {
+    /// Tokenizer to rebuild with (defaults to [search] tokenizer, then the current one)
+    #[arg(long, value_name = "NAME")]
+    pub tokenizer: Option<String>,
+
+    /// Entries to index per batch
+    #[arg(long, value_name = "N", default_value_t = 500)]
+    pub batch_size: usize,
+
+    /// Pause between batches, to keep a busy machine responsive
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    pub pause_ms: u64,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for the `reencrypt` command
+#[derive(Args)]
+pub struct ReencryptArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
 /// Arguments for the `doctor` command
 #[derive(Args)]
 pub struct DoctorArgs {
@@ -229,6 +675,34 @@ pub struct DoctorArgs {
     pub no_input: bool,
 }
 
+/// Arguments for the `audit-security` command
+#[derive(Args)]
+pub struct AuditSecurityArgs {
+    /// Extra backup file or directory to check (repeatable)
+    #[arg(long, value_name = "PATH")]
+    pub backup: Vec<String>,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `lock` command
+#[derive(Args)]
+pub struct LockArgs {
+    /// List unlocked ledgers and their remaining time instead of locking
+    #[arg(long)]
+    pub status: bool,
+
+    /// Panic lock: clear every session, stop the cache daemon, wipe editor drafts and the screen
+    #[arg(long, conflicts_with = "status")]
+    pub now: bool,
+
+    /// Output as JSON (with --status)
+    #[arg(long, requires = "status")]
+    pub json: bool,
+}
+
 /// Arguments for the `completions` command
 #[derive(Args)]
 pub struct CompletionsArgs {
@@ -237,6 +711,25 @@ pub struct CompletionsArgs {
     pub shell: Shell,
 }
 
+/// Arguments for the `schema` command
+#[derive(Args)]
+pub struct SchemaArgs {
+    /// View to describe; all views when omitted
+    #[arg(
+        value_name = "VIEW",
+        value_parser = clap::builder::PossibleValuesParser::new(ledger_core::views::VIEW_NAMES)
+    )]
+    pub view: Option<String>,
+}
+
+/// Arguments for the `api` command
+#[derive(Args)]
+pub struct ApiArgs {
+    /// Read one JSON request from stdin and print one JSON response
+    #[arg(long, required = true)]
+    pub stdin_json: bool,
+}
+
 /// Arguments for the internal cache daemon command
 #[derive(Args)]
 pub struct InternalCacheDaemonArgs {
@@ -244,6 +737,47 @@ pub struct InternalCacheDaemonArgs {
     pub ttl: u64,
     #[arg(long)]
     pub socket: String,
+    /// Keep running when idle (set by generated service units)
+    #[arg(long)]
+    pub keep_alive: bool,
+}
+
+/// Arguments for the internal replicator command
+#[derive(Args)]
+pub struct InternalReplicateArgs {
+    #[arg(long)]
+    pub source: String,
+    #[arg(long)]
+    pub dest: String,
+}
+
+/// Arguments for the internal data generator (test-support builds only)
+#[cfg(feature = "test-support")]
+#[derive(Args)]
+pub struct InternalGenerateArgs {
+    /// Number of entries to add
+    #[arg(long, default_value_t = 1000)]
+    pub entries: usize,
+
+    /// Number of entry types to spread them over
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub types: u32,
+
+    /// Size of the tag vocabulary
+    #[arg(long, default_value_t = 50)]
+    pub tags: usize,
+
+    /// Days back from now the entries are spread over
+    #[arg(long, default_value_t = 730, value_parser = clap::value_parser!(u32).range(1..))]
+    pub days: u32,
+
+    /// Seed for the generated data; the same seed gives the same content
+    #[arg(long, default_value_t = 1)]
+    pub seed: u64,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
 }
 
 // ============================================================================
@@ -265,6 +799,8 @@ pub enum CompositionsSubcommand {
     List(CompositionListArgs),
     /// Show composition details
     Show(CompositionShowArgs),
+    /// Edit a composition's overview page in the editor
+    Edit(CompositionEditArgs),
     /// Rename a composition
     Rename(CompositionRenameArgs),
     /// Delete a composition
@@ -281,6 +817,10 @@ pub struct CompositionCreateArgs {
     /// Optional description
     #[arg(long, short)]
     pub description: Option<String>,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for listing compositions
@@ -302,21 +842,57 @@ pub struct CompositionShowArgs {
     #[arg(value_name = "NAME_OR_ID")]
     pub name_or_id: String,
 
+    /// List every membership, including detached entries
+    #[arg(long)]
+    pub history: bool,
+
     /// Output as JSON
     #[arg(long)]
     pub json: bool,
 }
 
-/// Arguments for renaming a composition
+/// Arguments for editing a composition's overview
 #[derive(Args)]
-pub struct CompositionRenameArgs {
-    /// Current composition name or ID
+pub struct CompositionEditArgs {
+    /// Composition name or ID
     #[arg(value_name = "NAME_OR_ID")]
     pub name_or_id: String,
 
-    /// New name
+    /// Replace the overview instead of opening the editor
+    #[arg(long, conflicts_with = "clear")]
+    pub body: Option<String>,
+
+    /// Remove the overview
+    #[arg(long)]
+    pub clear: bool,
+
+    /// Editor command, e.g. "code --wait" (overrides [ui] editor, $VISUAL, $EDITOR)
+    #[arg(long, value_name = "COMMAND")]
+    pub editor: Option<String>,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for renaming a composition
+#[derive(Args)]
+pub struct CompositionRenameArgs {
+    /// Current composition name or ID
+    #[arg(value_name = "NAME_OR_ID")]
+    pub name_or_id: String,
+
+    /// New name
     #[arg(value_name = "NEW_NAME")]
     pub new_name: String,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for deleting a composition
@@ -329,6 +905,10 @@ pub struct CompositionDeleteArgs {
     /// Skip confirmation prompt
     #[arg(long)]
     pub force: bool,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
 }
 
 // ============================================================================
@@ -358,6 +938,8 @@ pub enum TemplatesSubcommand {
     SetDefault(TemplateSetDefaultArgs),
     /// Clear the default template for an entry type
     ClearDefault(TemplateClearDefaultArgs),
+    /// Show how many entries each template created, by version
+    Stats(TemplateStatsArgs),
 }
 
 /// Arguments for creating a template
@@ -379,9 +961,17 @@ pub struct TemplateCreateArgs {
     #[arg(long, value_name = "JSON")]
     pub defaults: Option<String>,
 
+    /// Weekday-specific defaults as JSON, e.g. '{"fri": {"body": "## Week retro"}}'
+    #[arg(long, value_name = "JSON")]
+    pub by_weekday: Option<String>,
+
     /// Set as default template for the entry type
     #[arg(long)]
     pub set_default: bool,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for listing templates
@@ -416,8 +1006,16 @@ pub struct TemplateUpdateArgs {
     pub name_or_id: String,
 
     /// New template defaults as JSON string
+    #[arg(long, value_name = "JSON", required_unless_present = "by_weekday")]
+    pub defaults: Option<String>,
+
+    /// New weekday-specific defaults as JSON ('{}' removes them)
     #[arg(long, value_name = "JSON")]
-    pub defaults: String,
+    pub by_weekday: Option<String>,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for deleting a template
@@ -430,6 +1028,10 @@ pub struct TemplateDeleteArgs {
     /// Skip confirmation prompt
     #[arg(long)]
     pub force: bool,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for setting default template
@@ -442,6 +1044,10 @@ pub struct TemplateSetDefaultArgs {
     /// Template name or ID
     #[arg(value_name = "TEMPLATE")]
     pub template: String,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for clearing default template
@@ -450,6 +1056,22 @@ pub struct TemplateClearDefaultArgs {
     /// Entry type name
     #[arg(value_name = "TYPE")]
     pub entry_type: String,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for template usage stats
+#[derive(Args)]
+pub struct TemplateStatsArgs {
+    /// Filter by entry type
+    #[arg(long, short = 't', value_name = "TYPE")]
+    pub entry_type: Option<String>,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
 }
 
 // ============================================================================
@@ -466,6 +1088,10 @@ pub struct AttachArgs {
     /// Composition name or ID to attach to
     #[arg(value_name = "COMPOSITION")]
     pub composition: String,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
 }
 
 /// Arguments for the `detach` command
@@ -478,59 +1104,1146 @@ pub struct DetachArgs {
     /// Composition name or ID to detach from
     #[arg(value_name = "COMPOSITION")]
     pub composition: String,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `collect` command
+#[derive(Args)]
+pub struct CollectArgs {
+    /// Day to collect (YYYY-MM-DD, default: today)
+    #[arg(long)]
+    pub date: Option<String>,
+
+    /// Only run these collectors (repeatable)
+    #[arg(long, value_name = "NAME")]
+    pub only: Vec<String>,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Do not prompt for input
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for the `report` command
+#[derive(Args)]
+pub struct ReportArgs {
+    #[command(subcommand)]
+    pub command: ReportSubcommand,
 }
 
 #[derive(Subcommand)]
-pub enum Commands {
-    /// Initialize a new encrypted ledger
-    Init(InitArgs),
+pub enum ReportSubcommand {
+    /// Yesterday / Today / Blockers skeleton for a standup update
+    Standup(StandupArgs),
+}
 
-    /// Add a new entry to the ledger
-    Add(AddArgs),
+/// Arguments for `report standup`
+#[derive(Args)]
+pub struct StandupArgs {
+    /// Time window to pull entries from (e.g., "1d", "3d")
+    #[arg(long, default_value = "1d")]
+    pub last: String,
 
-    /// Edit an existing entry (creates a new revision)
-    Edit(EditArgs),
+    /// Only include entries with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
 
-    /// List entries
-    List(ListArgs),
+    /// Copy the report to the clipboard instead of printing it
+    #[arg(long)]
+    pub copy: bool,
 
-    /// Search entries using full-text search
-    Search(SearchArgs),
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
 
-    /// Show a specific entry by ID
-    Show(ShowArgs),
+/// Arguments for the `upcoming` command
+#[derive(Args)]
+pub struct UpcomingArgs {
+    /// How far ahead to look (e.g., "14d")
+    #[arg(long, default_value = "14d")]
+    pub within: String,
 
-    /// Export entries (portable formats, you own your data)
-    Export(ExportArgs),
+    /// Only include entries of this type
+    #[arg(long = "type")]
+    pub entry_type: Option<String>,
 
-    /// Check ledger integrity
-    Check,
+    /// Also send a desktop notification for each reminder due today, once a
+    /// day (see [reminders].notify_command)
+    #[arg(long)]
+    pub notify: bool,
 
-    /// Backup the ledger
-    Backup(BackupArgs),
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
 
-    /// Clear cached passphrase (if enabled)
-    Lock,
+/// Arguments for the `today` command
+#[derive(Args)]
+pub struct TodayArgs {
+    /// How far ahead to list recurring dates (e.g., "7d")
+    #[arg(long, default_value = "7d")]
+    pub within: String,
 
-    /// Run onboarding diagnostics
-    Doctor(DoctorArgs),
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
 
-    /// Generate shell completions
-    Completions(CompletionsArgs),
+/// Arguments for the `week` command
+#[derive(Args)]
+pub struct WeekArgs {
+    /// Weeks from the current one (e.g., -1 for last week)
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub offset: i64,
 
-    /// Internal cache daemon (not user-facing)
-    #[command(hide = true, name = "internal-cache-daemon")]
-    InternalCacheDaemon(InternalCacheDaemonArgs),
+    /// Show the week containing this date (YYYY-MM-DD) instead of today
+    #[arg(long)]
+    pub date: Option<String>,
 
-    /// Manage compositions (semantic groupings of entries)
-    Compositions(CompositionsArgs),
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
 
-    /// Manage templates (reusable defaults for entries)
-    Templates(TemplatesArgs),
+/// Arguments for the `stats` command
+#[derive(Args)]
+pub struct StatsArgs {
+    /// List entries that have never been viewed (needs [privacy] track_access)
+    #[arg(long)]
+    pub unread: bool,
 
-    /// Attach an entry to a composition
-    Attach(AttachArgs),
+    /// Limit the --unread list
+    #[arg(long)]
+    pub limit: Option<usize>,
 
-    /// Detach an entry from a composition
-    Detach(DetachArgs),
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `digest` command
+#[derive(Args)]
+pub struct DigestArgs {
+    /// Period to summarize, ending today (week or month)
+    #[arg(long, default_value = "week")]
+    pub period: String,
+
+    /// Output format (md or html)
+    #[arg(long, default_value = "md")]
+    pub format: String,
+
+    /// Add a section of entries with this tag (repeatable)
+    #[arg(long, value_name = "TAG")]
+    pub tag: Vec<String>,
+
+    /// Most entries listed per section
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+
+    /// Write to a file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    pub out: Option<String>,
+}
+
+/// Arguments for the `correlate` command
+#[derive(Args)]
+pub struct CorrelateArgs {
+    /// First series: metric:<type>[.<field>] or habit:<name>
+    #[arg(value_name = "SERIES")]
+    pub first: String,
+
+    /// Second series, in the same form
+    #[arg(value_name = "SERIES")]
+    pub second: String,
+
+    /// Window to analyze (e.g., "90d")
+    #[arg(long, default_value = "90d")]
+    pub last: String,
+
+    /// Pair each day of the first series with the second N days later
+    #[arg(long, default_value_t = 0, value_name = "DAYS")]
+    pub lag: u32,
+
+    /// Report every lag from 0 up to N days
+    #[arg(long, conflicts_with = "lag", value_name = "DAYS")]
+    pub max_lag: Option<u32>,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `metrics` command
+#[derive(Args)]
+pub struct MetricsArgs {
+    #[command(subcommand)]
+    pub command: MetricsSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum MetricsSubcommand {
+    /// Flag values far from their recent average, or sudden jumps
+    Anomalies(MetricAnomaliesArgs),
+}
+
+/// Arguments for flagging unusual metric values
+#[derive(Args)]
+pub struct MetricAnomaliesArgs {
+    /// Metric to check: an entry type, with .<field> if it has several
+    #[arg(long, value_name = "TYPE[.FIELD]")]
+    pub metric: String,
+
+    /// Window to check, also used as the baseline for each value (e.g., "30d")
+    #[arg(long, default_value = "30d")]
+    pub window: String,
+
+    /// Flag values more than N standard deviations from the baseline
+    #[arg(long, default_value_t = 3.0, value_name = "N")]
+    pub sigma: f64,
+
+    /// Flag values N or more times larger or smaller than the previous one
+    #[arg(long, default_value_t = 5.0, value_name = "N")]
+    pub max_ratio: f64,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `expenses` command
+#[derive(Args)]
+pub struct ExpensesArgs {
+    #[command(subcommand)]
+    pub command: ExpensesSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum ExpensesSubcommand {
+    /// Category totals for a month, compared against `[budgets]`
+    Report(ExpensesReportArgs),
+}
+
+/// Arguments for the monthly expenses report
+#[derive(Args)]
+pub struct ExpensesReportArgs {
+    /// Month to report on (YYYY-MM, default: current month)
+    #[arg(long, value_name = "YYYY-MM")]
+    pub month: Option<String>,
+
+    /// Currency that budgets are in (needed when a month mixes currencies)
+    #[arg(long)]
+    pub currency: Option<String>,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `share` command
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct ShareArgs {
+    #[command(subcommand)]
+    pub command: Option<ShareSubcommand>,
+
+    /// Select entries: composition=NAME, tag=TAG, type=TYPE or since=DATE (repeatable, all must match)
+    #[arg(long = "where", value_name = "KEY=VALUE")]
+    pub filters: Vec<String>,
+
+    /// age recipient (age1...) who can open the bundle (repeatable)
+    #[arg(long = "to-age-recipient", value_name = "RECIPIENT")]
+    pub recipients: Vec<String>,
+
+    /// Bundle file to write
+    #[arg(long, value_name = "PATH")]
+    pub out: Option<String>,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+#[derive(Subcommand)]
+pub enum ShareSubcommand {
+    /// View a share bundle read-only
+    Open(ShareOpenArgs),
+}
+
+/// Arguments for opening a share bundle
+#[derive(Args)]
+pub struct ShareOpenArgs {
+    /// Bundle file to open
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    /// age identity file (AGE-SECRET-KEY-1...) matching a bundle recipient
+    #[arg(long, value_name = "PATH")]
+    pub identity: String,
+
+    /// Output entries as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `sync` command
+#[derive(Args)]
+pub struct SyncArgs {
+    #[command(subcommand)]
+    pub command: SyncSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum SyncSubcommand {
+    /// Write a change bundle for another ledger to apply with `ledger import`
+    Export(SyncExportArgs),
+
+    /// Apply a change bundle exported by a peer
+    Import(SyncImportArgs),
+
+    /// Manage the ledgers this one exchanges change bundles with
+    Peers(SyncPeersArgs),
+}
+
+/// Arguments for exporting a change bundle
+#[derive(Args)]
+pub struct SyncExportArgs {
+    /// Peer to export for; its `[sync.peers.<NAME>]` rules choose the entries to send
+    #[arg(long, value_name = "NAME")]
+    pub peer: Option<String>,
+
+    /// Only entries created at or after this time (ISO-8601) [default: entries added since the peer's last export]
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Write to a file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    pub out: Option<String>,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for importing a peer's change bundle
+#[derive(Args)]
+pub struct SyncImportArgs {
+    /// Change bundle to apply (`-` for stdin)
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    /// Peer the bundle must come from
+    #[arg(long, value_name = "NAME")]
+    pub peer: String,
+
+    /// Output the import summary as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for `sync peers`
+#[derive(Args)]
+pub struct SyncPeersArgs {
+    #[command(subcommand)]
+    pub command: SyncPeersSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum SyncPeersSubcommand {
+    /// List peers and this ledger's own fingerprint
+    List(SyncPeerListArgs),
+
+    /// Add a peer by name and device fingerprint
+    Add(SyncPeerAddArgs),
+
+    /// Forget a peer
+    Remove(SyncPeerRemoveArgs),
+}
+
+/// Arguments for listing sync peers
+#[derive(Args)]
+pub struct SyncPeerListArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for adding a sync peer
+#[derive(Args)]
+pub struct SyncPeerAddArgs {
+    /// Peer name (lowercase letters, digits, '-', '_')
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Device fingerprint shown by `ledger sync peers list` on the peer
+    #[arg(long, value_name = "FINGERPRINT")]
+    pub fingerprint: String,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for removing a sync peer
+#[derive(Args)]
+pub struct SyncPeerRemoveArgs {
+    /// Peer name
+    #[arg(value_name = "NAME")]
+    pub name: String,
+}
+
+/// Arguments for the `vault` command
+#[derive(Args)]
+pub struct VaultArgs {
+    #[command(subcommand)]
+    pub command: VaultSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum VaultSubcommand {
+    /// Mirror entries to a Markdown folder and pull edits back as revisions
+    Sync(VaultSyncArgs),
+}
+
+/// Arguments for syncing a Markdown vault
+#[derive(Args)]
+pub struct VaultSyncArgs {
+    /// Vault directory (created if missing)
+    #[arg(value_name = "DIR")]
+    pub dir: String,
+
+    /// Write plaintext Markdown notes (readable by anything with folder access)
+    #[arg(long, conflicts_with = "encrypt")]
+    pub plaintext: bool,
+
+    /// Age-encrypt each note with the ledger passphrase (`.md.age`)
+    #[arg(long)]
+    pub encrypt: bool,
+
+    /// Keep syncing until interrupted
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Seconds between syncs in --watch mode
+    #[arg(long, default_value_t = 5, value_name = "SECS")]
+    pub interval: u64,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for the `habit` command
+#[derive(Args)]
+pub struct HabitArgs {
+    #[command(subcommand)]
+    pub command: HabitSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum HabitSubcommand {
+    /// Define a habit and how often it is due
+    Add(HabitAddArgs),
+
+    /// Record that a habit was done
+    Done(HabitDoneArgs),
+
+    /// Show a completion matrix with streaks for every habit
+    Status(HabitStatusArgs),
+
+    /// Remove a habit definition (completions are kept)
+    Remove(HabitRemoveArgs),
+}
+
+/// Arguments for defining a habit
+#[derive(Args)]
+pub struct HabitAddArgs {
+    /// Habit name (lowercase letters, digits, '-', '_')
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// How often: daily, weekdays, weekends, mon,wed,fri, or 3/week
+    #[arg(long, default_value = "daily")]
+    pub schedule: String,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for marking a habit done
+#[derive(Args)]
+pub struct HabitDoneArgs {
+    /// Habit name
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Day it was done (YYYY-MM-DD, default: today)
+    #[arg(long)]
+    pub date: Option<String>,
+
+    /// Optional note stored with the completion
+    #[arg(long)]
+    pub note: Option<String>,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for the habit status matrix
+#[derive(Args)]
+pub struct HabitStatusArgs {
+    /// Number of days shown in the matrix
+    #[arg(long, default_value_t = 30)]
+    pub days: u32,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for removing a habit
+#[derive(Args)]
+pub struct HabitRemoveArgs {
+    /// Habit name
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for the `secret` command
+#[derive(Args)]
+pub struct SecretArgs {
+    #[command(subcommand)]
+    pub command: SecretSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum SecretSubcommand {
+    /// Store a secret (value read from stdin or a hidden prompt)
+    Set(SecretSetArgs),
+    /// Print a secret value
+    Get(SecretGetArgs),
+    /// List secret names
+    List(SecretListArgs),
+    /// Delete a secret
+    Delete(SecretDeleteArgs),
+}
+
+/// Arguments for storing a secret
+#[derive(Args)]
+pub struct SecretSetArgs {
+    /// Secret name (lowercase letters, digits, '-', '_', '.')
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Secret value (visible in shell history; prefer stdin or the prompt)
+    #[arg(long)]
+    pub value: Option<String>,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for printing a secret
+#[derive(Args)]
+pub struct SecretGetArgs {
+    /// Secret name
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for listing secrets
+#[derive(Args)]
+pub struct SecretListArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for deleting a secret
+#[derive(Args)]
+pub struct SecretDeleteArgs {
+    /// Secret name
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Skip confirmation prompt
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments for the `scratch` command
+#[derive(Args)]
+pub struct ScratchArgs {
+    #[command(subcommand)]
+    pub command: ScratchSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum ScratchSubcommand {
+    /// Edit the scratchpad in $EDITOR (or set it with --body)
+    Edit(ScratchEditArgs),
+    /// Print the scratchpad
+    Show(ScratchShowArgs),
+    /// Empty the scratchpad
+    Clear(ScratchClearArgs),
+    /// Turn the scratchpad into a journal entry and clear it
+    Promote(ScratchPromoteArgs),
+}
+
+/// Arguments for editing the scratchpad
+#[derive(Args)]
+pub struct ScratchEditArgs {
+    /// Replace the text instead of opening the editor
+    #[arg(long)]
+    pub body: Option<String>,
+
+    /// Add to the end of the text instead of replacing it
+    #[arg(long)]
+    pub append: bool,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for printing the scratchpad
+#[derive(Args)]
+pub struct ScratchShowArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for clearing the scratchpad
+#[derive(Args)]
+pub struct ScratchClearArgs {
+    /// Skip confirmation prompt
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments for promoting the scratchpad to an entry
+#[derive(Args)]
+pub struct ScratchPromoteArgs {
+    /// Tag for the new entry (repeatable)
+    #[arg(short = 't', long = "tag", value_name = "TAG")]
+    pub tag: Vec<String>,
+
+    /// Keep the scratchpad text after promoting it
+    #[arg(long)]
+    pub keep: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `draft` command
+#[derive(Args)]
+pub struct DraftArgs {
+    #[command(subcommand)]
+    pub command: DraftSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum DraftSubcommand {
+    /// Save a new draft from $EDITOR, --body, --field or stdin
+    Save(DraftSaveArgs),
+    /// List drafts, most recently saved first
+    List(DraftListArgs),
+    /// Edit a draft's body in $EDITOR (or replace it with --body)
+    Edit(DraftEditArgs),
+    /// Publish a draft as an entry
+    Publish(DraftPublishArgs),
+    /// Delete a draft without publishing it
+    Discard(DraftDiscardArgs),
+}
+
+/// Arguments for saving a draft
+#[derive(Args)]
+pub struct DraftSaveArgs {
+    /// Entry type the draft is published as
+    #[arg(value_name = "TYPE", default_value = "journal")]
+    pub entry_type: String,
+
+    /// Draft body (overrides stdin/editor)
+    #[arg(long)]
+    pub body: Option<String>,
+
+    /// Set field values (format: field=value, can be repeated)
+    #[arg(long = "field", short = 'f', value_name = "FIELD=VALUE")]
+    pub fields: Vec<String>,
+
+    /// Tags for the published entry
+    #[arg(short, long, value_name = "TAG")]
+    pub tag: Vec<String>,
+
+    /// Editor command for this draft (overrides [ui] editor, $VISUAL, $EDITOR)
+    #[arg(long, value_name = "COMMAND")]
+    pub editor: Option<String>,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for listing drafts
+#[derive(Args)]
+pub struct DraftListArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for editing a draft
+#[derive(Args)]
+pub struct DraftEditArgs {
+    /// Draft ID (or a unique prefix)
+    pub id: String,
+
+    /// Replace the body instead of opening the editor
+    #[arg(long)]
+    pub body: Option<String>,
+
+    /// Set field values (format: field=value, can be repeated)
+    #[arg(long = "field", short = 'f', value_name = "FIELD=VALUE")]
+    pub fields: Vec<String>,
+
+    /// Replace the draft's tags (repeatable)
+    #[arg(short, long, value_name = "TAG")]
+    pub tag: Vec<String>,
+
+    /// Editor command for this draft (overrides [ui] editor, $VISUAL, $EDITOR)
+    #[arg(long, value_name = "COMMAND")]
+    pub editor: Option<String>,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for publishing a draft
+#[derive(Args)]
+pub struct DraftPublishArgs {
+    /// Draft ID (or a unique prefix)
+    pub id: String,
+
+    /// Date the entry now instead of when the draft was first saved
+    #[arg(long)]
+    pub now: bool,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for discarding a draft
+#[derive(Args)]
+pub struct DraftDiscardArgs {
+    /// Draft ID (or a unique prefix)
+    pub id: String,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `tags` command
+#[derive(Args)]
+pub struct TagsArgs {
+    #[command(subcommand)]
+    pub command: TagsSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum TagsSubcommand {
+    /// List tags with entry counts
+    List(TagListArgs),
+}
+
+/// Arguments for listing tags
+#[derive(Args)]
+pub struct TagListArgs {
+    /// Nest hierarchical tags (work/project-x) under their parents
+    #[arg(long)]
+    pub tree: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `types` command
+#[derive(Args)]
+pub struct TypesArgs {
+    #[command(subcommand)]
+    pub command: TypesSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum TypesSubcommand {
+    /// List entry types, retired ones marked
+    List(TypeListArgs),
+    /// Show an entry type's description and fields
+    Show(TypeShowArgs),
+    /// Stop an entry type taking new entries, keeping its history
+    Retire(TypeRetireArgs),
+}
+
+/// Arguments for listing entry types
+#[derive(Args)]
+pub struct TypeListArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for showing an entry type
+#[derive(Args)]
+pub struct TypeShowArgs {
+    /// Entry type name
+    #[arg(value_name = "TYPE")]
+    pub name: String,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for retiring an entry type
+#[derive(Args)]
+pub struct TypeRetireArgs {
+    /// Entry type name
+    #[arg(value_name = "TYPE")]
+    pub name: String,
+
+    /// Accept new entries of the type again
+    #[arg(long)]
+    pub undo: bool,
+
+    /// Output a JSON receipt
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `attachments` command
+#[derive(Args)]
+pub struct AttachmentsArgs {
+    #[command(subcommand)]
+    pub command: AttachmentsSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum AttachmentsSubcommand {
+    /// Delete stored blobs that no attachment refers to any more
+    Gc(AttachmentGcArgs),
+}
+
+/// Arguments for attachment garbage collection
+#[derive(Args)]
+pub struct AttachmentGcArgs {
+    /// Report what would be removed without deleting anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for the `packs` command
+#[derive(Args)]
+pub struct PacksArgs {
+    #[command(subcommand)]
+    pub command: PacksSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum PacksSubcommand {
+    /// Install a pack from a JSON manifest or by built-in name
+    Install(PackInstallArgs),
+    /// List installed packs and their versions
+    List(PackListArgs),
+}
+
+/// Arguments for installing a pack
+#[derive(Args)]
+pub struct PackInstallArgs {
+    /// Manifest file, or the name of a built-in pack (fitness, gardening)
+    #[arg(value_name = "FILE|NAME")]
+    pub source: String,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Disable interactive prompts
+    #[arg(long)]
+    pub no_input: bool,
+}
+
+/// Arguments for listing packs
+#[derive(Args)]
+pub struct PackListArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `prompts` command
+#[derive(Args)]
+pub struct PromptsArgs {
+    #[command(subcommand)]
+    pub command: PromptsSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum PromptsSubcommand {
+    /// List journaling prompts, today's and disabled ones marked
+    List(PromptListArgs),
+    /// Add a prompt to the prompts file
+    Add(PromptAddArgs),
+    /// Take a prompt out of the rotation
+    Disable(PromptDisableArgs),
+}
+
+/// Arguments for listing prompts
+#[derive(Args)]
+pub struct PromptListArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for adding a prompt
+#[derive(Args)]
+pub struct PromptAddArgs {
+    /// Prompt text (one line)
+    #[arg(value_name = "TEXT")]
+    pub text: String,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for disabling a prompt
+#[derive(Args)]
+pub struct PromptDisableArgs {
+    /// Prompt number from `prompts list`, or its exact text
+    #[arg(value_name = "PROMPT")]
+    pub prompt: String,
+
+    /// Put the prompt back into the rotation
+    #[arg(long)]
+    pub undo: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Initialize a new encrypted ledger
+    Init(InitArgs),
+
+    /// Write a config for an existing ledger file (e.g. on a restored machine)
+    Adopt(AdoptArgs),
+
+    /// Add a new entry to the ledger
+    Add(AddArgs),
+
+    /// Catch up on missed days: one entry per day without one
+    Backfill(BackfillArgs),
+
+    /// Edit an existing entry (creates a new revision)
+    Edit(EditArgs),
+
+    /// List entries
+    List(ListArgs),
+
+    /// Search entries using full-text search
+    Search(SearchArgs),
+
+    /// Show a specific entry by ID
+    Show(ShowArgs),
+
+    /// Export entries (portable formats, you own your data)
+    Export(ExportArgs),
+
+    /// Export only the entries a previous export does not contain
+    ExportDiff(ExportDiffArgs),
+
+    /// Import entries and structure from an export file
+    Import(ImportArgs),
+
+    /// Check ledger integrity
+    Check(CheckArgs),
+
+    /// Show lock state, pending drafts, last entry, backup and check at a glance
+    Status(StatusArgs),
+
+    /// Backup the ledger
+    Backup(BackupArgs),
+
+    /// Relocate the ledger file, updating config and stored credentials
+    Move(MoveArgs),
+
+    /// Generate systemd units for the cache daemon, backups and healthchecks
+    Systemd(SystemdArgs),
+
+    /// Generate launchd agents (macOS) for the cache daemon, backups and healthchecks
+    Launchd(LaunchdArgs),
+
+    /// Salvage readable data from a damaged ledger into a new file
+    Recover(RecoverArgs),
+
+    /// Rebuild the full-text search index, optionally with a new tokenizer
+    Reindex(ReindexArgs),
+
+    /// Rewrite the ledger with a fresh salt, nonce and KDF work factor (same passphrase)
+    Reencrypt(ReencryptArgs),
+
+    /// Clear cached passphrase (if enabled)
+    Lock(LockArgs),
+
+    /// Run onboarding diagnostics
+    Doctor(DoctorArgs),
+
+    /// Check permissions and passphrase exposure, with remediation steps
+    AuditSecurity(AuditSecurityArgs),
+
+    /// Generate shell completions
+    Completions(CompletionsArgs),
+
+    /// Print the JSON Schema of entries, compositions, templates and receipts printed by --json
+    Schema(SchemaArgs),
+
+    /// JSON request/response backend for editor plugins
+    Api(ApiArgs),
+
+    /// Internal cache daemon (not user-facing)
+    #[command(hide = true, name = "internal-cache-daemon")]
+    InternalCacheDaemon(InternalCacheDaemonArgs),
+
+    /// Internal replica sync, spawned after each save (not user-facing)
+    #[command(hide = true, name = "internal-replicate")]
+    InternalReplicate(InternalReplicateArgs),
+
+    /// Fill the ledger with generated entries for performance testing
+    #[cfg(feature = "test-support")]
+    #[command(hide = true, name = "internal-generate")]
+    InternalGenerate(InternalGenerateArgs),
+
+    /// Manage compositions (semantic groupings of entries)
+    Compositions(CompositionsArgs),
+
+    /// Manage templates (reusable defaults for entries)
+    Templates(TemplatesArgs),
+
+    /// Attach an entry to a composition
+    Attach(AttachArgs),
+
+    /// Detach an entry from a composition
+    Detach(DetachArgs),
+
+    /// Collect system activity (shell, git, calendar) into activity entries
+    Collect(CollectArgs),
+
+    /// Generate reports from recent entries
+    Report(ReportArgs),
+
+    /// List anniversaries and other recurring dates that fall due soon
+    Upcoming(UpcomingArgs),
+
+    /// Today's entries, due tasks, scheduled habits and reminders
+    Today(TodayArgs),
+
+    /// Seven-day overview of entries, due tasks and habit completion
+    Week(WeekArgs),
+
+    /// Entry counts by type, and entries never viewed
+    Stats(StatsArgs),
+
+    /// Render a week or month digest (counts, streaks, tagged entries) for mailing
+    Digest(DigestArgs),
+
+    /// Correlate two daily series (metric fields or habits) over a window
+    Correlate(CorrelateArgs),
+
+    /// Check numeric metric fields for outliers and typos
+    Metrics(MetricsArgs),
+
+    /// Summarize expense entries by category against monthly budgets
+    Expenses(ExpensesArgs),
+
+    /// Track habits: schedules, completions and streaks
+    Habit(HabitArgs),
+
+    /// Manage encrypted secrets referenced from config as secret://name
+    Secret(SecretArgs),
+
+    /// Jot in the encrypted scratchpad, then promote it to an entry
+    Scratch(ScratchArgs),
+
+    /// Save unfinished entries as drafts and publish them later
+    Draft(DraftArgs),
+
+    /// List tags and how many entries use them
+    Tags(TagsArgs),
+
+    /// List entry types and retire unused ones
+    Types(TypesArgs),
+
+    /// Install packs of related entry types and templates
+    Packs(PacksArgs),
+
+    /// Manage attachment storage
+    Attachments(AttachmentsArgs),
+
+    /// Manage journaling prompts for `add journal` and `today`
+    Prompts(PromptsArgs),
+
+    /// Exchange change bundles with other ledgers
+    Sync(SyncArgs),
+
+    /// Sync entries with an Obsidian-compatible Markdown folder
+    Vault(VaultArgs),
+
+    /// Share selected entries as an age-encrypted bundle, or open one
+    Share(ShareArgs),
 }
@@ -0,0 +1,257 @@
+//! Calendar collector reading events from local `.ics` files.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use super::{local_day_bounds, Activity, Collector};
+
+/// Lists the completed events of the day from one or more `.ics` files.
+pub struct CalendarCollector {
+    files: Vec<PathBuf>,
+}
+
+impl CalendarCollector {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        Self { files }
+    }
+}
+
+/// Start or end of a calendar event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EventTime {
+    /// A specific instant
+    At(DateTime<Utc>),
+    /// An all-day date
+    Date(NaiveDate),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Event {
+    summary: String,
+    start: EventTime,
+    end: Option<EventTime>,
+}
+
+impl Collector for CalendarCollector {
+    fn name(&self) -> &'static str {
+        "calendar"
+    }
+
+    fn collect(&self, day: NaiveDate) -> anyhow::Result<Vec<Activity>> {
+        if self.files.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No calendar files configured (calendar_files)"
+            ));
+        }
+        let mut events = Vec::new();
+        for path in &self.files {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+            events.extend(parse_events(&contents));
+        }
+        Ok(summarize(&events, day, Utc::now()).into_iter().collect())
+    }
+}
+
+/// Parse VEVENT blocks. Recurrence rules are not expanded.
+fn parse_events(contents: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut current: Option<(Option<String>, Option<EventTime>, Option<EventTime>)> = None;
+
+    for line in unfold_lines(contents) {
+        let Some((name_params, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parts = name_params.split(';');
+        let name = parts.next().unwrap_or("").to_ascii_uppercase();
+        let params: Vec<&str> = parts.collect();
+
+        match (name.as_str(), value.trim()) {
+            ("BEGIN", "VEVENT") => current = Some((None, None, None)),
+            ("END", "VEVENT") => {
+                if let Some((summary, Some(start), end)) = current.take() {
+                    events.push(Event {
+                        summary: summary.unwrap_or_else(|| "(untitled event)".to_string()),
+                        start,
+                        end,
+                    });
+                }
+            }
+            ("SUMMARY", value) => {
+                if let Some(ref mut event) = current {
+                    event.0 = Some(unescape_text(value));
+                }
+            }
+            ("DTSTART", value) => {
+                if let Some(ref mut event) = current {
+                    event.1 = parse_event_time(&params, value);
+                }
+            }
+            ("DTEND", value) => {
+                if let Some(ref mut event) = current {
+                    event.2 = parse_event_time(&params, value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    events
+}
+
+/// Join RFC 5545 folded lines (continuations start with a space or tab).
+fn unfold_lines(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in contents.lines() {
+        let raw = raw.trim_end_matches('\r');
+        if let Some(rest) = raw.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        lines.push(raw.to_string());
+    }
+    lines
+}
+
+fn unescape_text(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+fn parse_event_time(params: &[&str], value: &str) -> Option<EventTime> {
+    let is_date = params.iter().any(|p| p.eq_ignore_ascii_case("VALUE=DATE")) || value.len() == 8;
+    if is_date {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(EventTime::Date);
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(EventTime::At(Utc.from_utc_datetime(&naive)));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let tzid = params
+        .iter()
+        .find_map(|p| p.strip_prefix("TZID="))
+        .map(|tz| tz.trim_matches('"'));
+    let at = match tzid.and_then(|tz| tz.parse::<chrono_tz::Tz>().ok()) {
+        Some(tz) => tz
+            .from_local_datetime(&naive)
+            .earliest()?
+            .with_timezone(&Utc),
+        // Floating times (and unknown zones) are read as local time
+        None => Local
+            .from_local_datetime(&naive)
+            .earliest()?
+            .with_timezone(&Utc),
+    };
+    Some(EventTime::At(at))
+}
+
+/// Summarize events that fall on `day` and have ended by `now`.
+fn summarize(events: &[Event], day: NaiveDate, now: DateTime<Utc>) -> Option<Activity> {
+    let (day_start, day_end) = local_day_bounds(day)?;
+
+    let mut matching: Vec<(Option<DateTime<Utc>>, &Event)> = events
+        .iter()
+        .filter_map(|event| match event.start {
+            EventTime::At(start) => {
+                let end = match event.end {
+                    Some(EventTime::At(end)) => end,
+                    _ => start,
+                };
+                (start >= day_start && start < day_end && end <= now)
+                    .then_some((Some(start), event))
+            }
+            EventTime::Date(date) => (date == day && day_end <= now).then_some((None, event)),
+        })
+        .collect();
+    if matching.is_empty() {
+        return None;
+    }
+    matching.sort_by_key(|(start, _)| *start);
+
+    let details = matching
+        .iter()
+        .map(|(start, event)| match start {
+            Some(start) => format!(
+                "{} {}",
+                start.with_timezone(&Local).format("%H:%M"),
+                event.summary
+            ),
+            None => format!("all day {}", event.summary),
+        })
+        .collect::<Vec<_>>();
+    let noun = if details.len() == 1 {
+        "event"
+    } else {
+        "events"
+    };
+    Some(Activity {
+        source: "calendar".to_string(),
+        subject: None,
+        summary: format!("{} calendar {}", details.len(), noun),
+        count: details.len() as i64,
+        details,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICS: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Design review\\, round 2\r\n\
+DTSTART:20240305T150000Z\r\n\
+DTEND:20240305T160000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Long meeting \r\n\
+\x20name\r\n\
+DTSTART;TZID=Europe/Berlin:20240305T100000\r\n\
+DTEND;TZID=Europe/Berlin:20240305T110000\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Holiday\r\n\
+DTSTART;VALUE=DATE:20240306\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_events_handles_folding_and_timezones() {
+        let events = parse_events(ICS);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].summary, "Design review, round 2");
+        assert_eq!(events[1].summary, "Long meeting name");
+        assert_eq!(
+            events[1].start,
+            EventTime::At(Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap())
+        );
+        assert_eq!(
+            events[2].start,
+            EventTime::Date(NaiveDate::from_ymd_opt(2024, 3, 6).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_summarize_skips_events_not_yet_ended() {
+        let events = parse_events(ICS);
+        let day = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let before = Utc.with_ymd_and_hms(2024, 3, 6, 12, 0, 0).unwrap();
+        assert!(summarize(&events, day, before).is_none());
+
+        let after = Utc.with_ymd_and_hms(2024, 3, 10, 0, 0, 0).unwrap();
+        let activity = summarize(&events, day, after).unwrap();
+        assert_eq!(activity.details, vec!["all day Holiday"]);
+    }
+}
@@ -0,0 +1,119 @@
+//! Git commit collector.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use chrono::NaiveDate;
+
+use super::{local_day_bounds, Activity, Collector};
+
+/// Lists the day's commits in each configured repository.
+pub struct GitCollector {
+    repos: Vec<PathBuf>,
+    author: Option<String>,
+}
+
+impl GitCollector {
+    pub fn new(repos: Vec<PathBuf>, author: Option<String>) -> Self {
+        Self { repos, author }
+    }
+}
+
+impl Collector for GitCollector {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+
+    fn collect(&self, day: NaiveDate) -> anyhow::Result<Vec<Activity>> {
+        if self.repos.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No git repositories configured (git_repos)"
+            ));
+        }
+        let (start, end) =
+            local_day_bounds(day).ok_or_else(|| anyhow::anyhow!("Invalid day: {}", day))?;
+
+        let mut activities = Vec::new();
+        for repo in &self.repos {
+            let mut command = Command::new("git");
+            command
+                .arg("-C")
+                .arg(repo)
+                .arg("log")
+                .arg("--all")
+                .arg("--no-merges")
+                .arg(format!("--since={}", start.timestamp()))
+                .arg(format!("--until={}", end.timestamp() - 1))
+                .arg("--pretty=format:%h %s");
+            if let Some(ref author) = self.author {
+                command.arg(format!("--author={}", author));
+            }
+            let output = command
+                .output()
+                .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(anyhow::anyhow!(
+                    "git log failed in {}: {}",
+                    repo.display(),
+                    stderr.trim()
+                ));
+            }
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if let Some(activity) = summarize_repo(repo, &stdout) {
+                activities.push(activity);
+            }
+        }
+        Ok(activities)
+    }
+}
+
+fn repo_name(repo: &std::path::Path) -> String {
+    repo.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| repo.display().to_string())
+}
+
+fn summarize_repo(repo: &std::path::Path, log: &str) -> Option<Activity> {
+    let details: Vec<String> = log
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    if details.is_empty() {
+        return None;
+    }
+    let noun = if details.len() == 1 {
+        "commit"
+    } else {
+        "commits"
+    };
+    let name = repo_name(repo);
+    Some(Activity {
+        source: "git".to_string(),
+        summary: format!("{} {} in {}", details.len(), noun, name),
+        subject: Some(name),
+        count: details.len() as i64,
+        details,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_summarize_repo() {
+        let activity = summarize_repo(
+            Path::new("/src/ledger"),
+            "abc123 Fix list\ndef456 Add collect\n",
+        )
+        .unwrap();
+        assert_eq!(activity.summary, "2 commits in ledger");
+        assert_eq!(activity.count, 2);
+        assert_eq!(activity.details[0], "abc123 Fix list");
+        assert!(summarize_repo(Path::new("/src/ledger"), "\n").is_none());
+    }
+}
@@ -0,0 +1,151 @@
+//! Activity collectors that turn local system activity into entries.
+//!
+//! Each collector implements [`Collector`] and summarizes one local day of
+//! activity from a single source. `ledger collect` runs the collectors
+//! enabled in `[collectors]` and stores the results as `activity` entries.
+
+mod calendar;
+mod git;
+mod shell_history;
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+
+use crate::config::CollectorsSection;
+
+pub use calendar::CalendarCollector;
+pub use git::GitCollector;
+pub use shell_history::ShellHistoryCollector;
+
+/// Names accepted in `[collectors].enabled`.
+pub const COLLECTOR_NAMES: &[&str] = &["shell_history", "git", "calendar"];
+
+/// One summarized piece of activity, stored as a single entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Activity {
+    /// Collector name (stored as the entry's `source` field)
+    pub source: String,
+    /// What the activity is about within its source (e.g. a repository),
+    /// for collectors that report more than one activity per day
+    pub subject: Option<String>,
+    /// One-line summary
+    pub summary: String,
+    /// Number of underlying events (commands, commits, meetings)
+    pub count: i64,
+    /// Detail lines (one per event or group)
+    pub details: Vec<String>,
+}
+
+/// A source of activity for a given day.
+pub trait Collector {
+    /// Stable collector name, as used in config.
+    fn name(&self) -> &'static str;
+
+    /// Summarize activity for a local calendar day.
+    ///
+    /// Returns an empty list when there was no activity.
+    fn collect(&self, day: NaiveDate) -> anyhow::Result<Vec<Activity>>;
+}
+
+/// Build the collectors enabled in config, optionally restricted to `only`.
+pub fn build_collectors(
+    section: &CollectorsSection,
+    only: &[String],
+) -> anyhow::Result<Vec<Box<dyn Collector>>> {
+    for name in section.enabled.iter().chain(only) {
+        if !COLLECTOR_NAMES.contains(&name.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Unknown collector: {} (available: {})",
+                name,
+                COLLECTOR_NAMES.join(", ")
+            ));
+        }
+    }
+
+    let mut collectors: Vec<Box<dyn Collector>> = Vec::new();
+    for name in &section.enabled {
+        if !only.is_empty() && !only.contains(name) {
+            continue;
+        }
+        match name.as_str() {
+            "shell_history" => collectors.push(Box::new(ShellHistoryCollector::new(
+                section.history_file.as_deref().map(expand_tilde),
+            ))),
+            "git" => collectors.push(Box::new(GitCollector::new(
+                section.git_repos.iter().map(|r| expand_tilde(r)).collect(),
+                section.git_author.clone(),
+            ))),
+            "calendar" => collectors.push(Box::new(CalendarCollector::new(
+                section
+                    .calendar_files
+                    .iter()
+                    .map(|f| expand_tilde(f))
+                    .collect(),
+            ))),
+            _ => {}
+        }
+    }
+    Ok(collectors)
+}
+
+/// Expand a leading `~` to $HOME.
+pub fn expand_tilde(path: &str) -> PathBuf {
+    if let Ok(home) = std::env::var("HOME") {
+        if path == "~" {
+            return PathBuf::from(home);
+        }
+        if let Some(rest) = path.strip_prefix("~/") {
+            return PathBuf::from(home).join(rest);
+        }
+    }
+    PathBuf::from(path)
+}
+
+/// UTC bounds `[start, end)` of a local calendar day.
+pub fn local_day_bounds(day: NaiveDate) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = Local
+        .from_local_datetime(&day.and_hms_opt(0, 0, 0)?)
+        .earliest()?;
+    let end = Local
+        .from_local_datetime(&day.succ_opt()?.and_hms_opt(0, 0, 0)?)
+        .earliest()?;
+    Some((start.with_timezone(&Utc), end.with_timezone(&Utc)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_collectors_respects_enabled_and_only() {
+        let section = CollectorsSection {
+            enabled: vec!["git".to_string(), "calendar".to_string()],
+            ..Default::default()
+        };
+        let all = build_collectors(&section, &[]).unwrap();
+        let names: Vec<&str> = all.iter().map(|c| c.name()).collect();
+        assert_eq!(names, vec!["git", "calendar"]);
+
+        let only = build_collectors(&section, &["calendar".to_string()]).unwrap();
+        assert_eq!(only.len(), 1);
+        assert_eq!(only[0].name(), "calendar");
+    }
+
+    #[test]
+    fn test_build_collectors_rejects_unknown_names() {
+        let section = CollectorsSection {
+            enabled: vec!["browser".to_string()],
+            ..Default::default()
+        };
+        assert!(build_collectors(&section, &[]).is_err());
+    }
+
+    #[test]
+    fn test_local_day_bounds_span_one_day() {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let (start, end) = local_day_bounds(day).unwrap();
+        let hours = (end - start).num_hours();
+        assert!((23..=25).contains(&hours), "hours={}", hours);
+    }
+}
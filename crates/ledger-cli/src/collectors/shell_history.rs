@@ -0,0 +1,167 @@
+//! Shell history collector.
+//!
+//! Only program names and counts are stored; full command lines can contain
+//! secrets and never leave the history file.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use super::{local_day_bounds, Activity, Collector};
+
+/// Number of programs listed in the activity details.
+const TOP_PROGRAMS: usize = 10;
+
+/// Summarizes timestamped zsh (`EXTENDED_HISTORY`) or bash (`HISTTIMEFORMAT`) history.
+pub struct ShellHistoryCollector {
+    history_file: Option<PathBuf>,
+}
+
+impl ShellHistoryCollector {
+    pub fn new(history_file: Option<PathBuf>) -> Self {
+        Self { history_file }
+    }
+
+    fn resolve_history_file(&self) -> Option<PathBuf> {
+        if let Some(ref path) = self.history_file {
+            return Some(path.clone());
+        }
+        if let Ok(value) = std::env::var("HISTFILE") {
+            if !value.trim().is_empty() {
+                return Some(PathBuf::from(value));
+            }
+        }
+        let home = PathBuf::from(std::env::var("HOME").ok()?);
+        [".zsh_history", ".bash_history"]
+            .iter()
+            .map(|name| home.join(name))
+            .find(|path| path.exists())
+    }
+}
+
+impl Collector for ShellHistoryCollector {
+    fn name(&self) -> &'static str {
+        "shell_history"
+    }
+
+    fn collect(&self, day: NaiveDate) -> anyhow::Result<Vec<Activity>> {
+        let path = self
+            .resolve_history_file()
+            .ok_or_else(|| anyhow::anyhow!("No shell history file found (set history_file)"))?;
+        let bytes = std::fs::read(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let contents = String::from_utf8_lossy(&bytes);
+
+        let (start, end) =
+            local_day_bounds(day).ok_or_else(|| anyhow::anyhow!("Invalid day: {}", day))?;
+        let commands: Vec<String> = parse_history(&contents)
+            .into_iter()
+            .filter(|(at, _)| *at >= start && *at < end)
+            .map(|(_, command)| command)
+            .collect();
+        Ok(summarize(&commands).into_iter().collect())
+    }
+}
+
+/// Parse timestamped history lines into `(time, command)` pairs.
+///
+/// Lines without a timestamp are skipped since they cannot be placed on a day.
+fn parse_history(contents: &str) -> Vec<(DateTime<Utc>, String)> {
+    let mut result = Vec::new();
+    let mut pending_bash_ts: Option<DateTime<Utc>> = None;
+
+    for line in contents.lines() {
+        // zsh extended history: ": 1700000000:0;git status"
+        if let Some(rest) = line.strip_prefix(": ") {
+            if let Some((meta, command)) = rest.split_once(';') {
+                let ts = meta.split(':').next().and_then(|t| t.trim().parse().ok());
+                if let Some(at) = ts.and_then(|t| DateTime::from_timestamp(t, 0)) {
+                    result.push((at, command.to_string()));
+                }
+                continue;
+            }
+        }
+        // bash with HISTTIMEFORMAT: "#1700000000" followed by the command
+        if let Some(ts) = line.strip_prefix('#').and_then(|t| t.parse::<i64>().ok()) {
+            pending_bash_ts = DateTime::from_timestamp(ts, 0);
+            continue;
+        }
+        if let Some(at) = pending_bash_ts.take() {
+            result.push((at, line.to_string()));
+        }
+    }
+
+    result
+}
+
+fn program_name(command: &str) -> Option<String> {
+    command
+        .split_whitespace()
+        .find(|word| !word.contains('=') && *word != "sudo")
+        .map(|word| word.rsplit('/').next().unwrap_or(word).to_string())
+}
+
+fn summarize(commands: &[String]) -> Option<Activity> {
+    if commands.is_empty() {
+        return None;
+    }
+    let mut counts: HashMap<String, i64> = HashMap::new();
+    for command in commands {
+        if let Some(program) = program_name(command) {
+            *counts.entry(program).or_default() += 1;
+        }
+    }
+    let mut ranked: Vec<(String, i64)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let details = ranked
+        .iter()
+        .take(TOP_PROGRAMS)
+        .map(|(program, count)| format!("{} ({})", program, count))
+        .collect();
+    Some(Activity {
+        source: "shell_history".to_string(),
+        subject: None,
+        summary: format!(
+            "{} shell commands across {} programs",
+            commands.len(),
+            ranked.len()
+        ),
+        count: commands.len() as i64,
+        details,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zsh_and_bash_formats() {
+        let contents = ": 1700000000:0;git status\n#1700000100\ncargo test\nplain-line\n";
+        let parsed = parse_history(contents);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].1, "git status");
+        assert_eq!(parsed[1].0.timestamp(), 1700000100);
+        assert_eq!(parsed[1].1, "cargo test");
+    }
+
+    #[test]
+    fn test_summarize_counts_programs_only() {
+        let commands = vec![
+            "git commit -m 'secret token'".to_string(),
+            "git push".to_string(),
+            "FOO=1 sudo /usr/bin/cargo build".to_string(),
+        ];
+        let activity = summarize(&commands).unwrap();
+        assert_eq!(activity.count, 3);
+        assert_eq!(activity.details, vec!["git (2)", "cargo (1)"]);
+        assert!(!activity.details.iter().any(|d| d.contains("secret")));
+    }
+
+    #[test]
+    fn test_summarize_empty_is_none() {
+        assert!(summarize(&[]).is_none());
+    }
+}
@@ -0,0 +1,219 @@
+//! Write a config for a ledger file that already exists, e.g. one restored
+//! onto a new machine, after checking that it unlocks.
+
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+use dialoguer::Confirm;
+use ledger_core::error::LedgerError;
+use ledger_core::storage::{AgeSqliteStorage, EntryFilter, SearchTokenizer, StorageEngine};
+
+use crate::app::{device_keyfile_warning, not_found_with_hint, resolve_config_path, AppContext};
+use crate::cache::ledger_hash;
+use crate::cli::AdoptArgs;
+use crate::commands::init::parse_timezone;
+use crate::config::{default_keyfile_path, write_config, KeyfileMode, LedgerConfig, SecurityTier};
+use crate::errors::CliError;
+use crate::helpers::prompt_passphrase;
+use crate::security::{
+    key_bytes_to_passphrase, keychain_set, read_keyfile_encrypted, read_keyfile_plain,
+};
+use crate::ui::{badge, blank_line, header, hint, kv, print, Badge, OutputMode};
+
+pub fn handle_adopt(ctx: &AppContext, args: &AdoptArgs) -> anyhow::Result<()> {
+    let ledger_path = PathBuf::from(&args.path);
+    if !ledger_path.is_file() {
+        return Err(not_found_with_hint(
+            &format!("Ledger file not found: {}", ledger_path.display()),
+            "Hint: Run `ledger init` to create a new ledger.",
+        ));
+    }
+    let ledger_path = std::fs::canonicalize(&ledger_path).map_err(|e| {
+        anyhow::anyhow!("Failed to resolve ledger {}: {}", ledger_path.display(), e)
+    })?;
+
+    let config_path = resolve_config_path()?;
+    if config_path.exists() && !args.force {
+        return Err(anyhow::anyhow!(
+            "Config already exists: {}\nHint: Use --force to replace it, or `ledger move` to relocate the configured ledger.",
+            config_path.display()
+        ));
+    }
+
+    let tier = parse_tier(&args.tier)?;
+    let timezone = parse_timezone(args.timezone.as_deref().unwrap_or(""))?;
+    let interactive = std::io::stdin().is_terminal() && !args.no_input;
+
+    let keyfile_path = match tier {
+        SecurityTier::PassphraseKeyfile | SecurityTier::DeviceKeyfile => {
+            let path = match args.keyfile_path {
+                Some(ref value) => PathBuf::from(value),
+                None => default_keyfile_path()?,
+            };
+            if !path.is_file() {
+                return Err(not_found_with_hint(
+                    &format!("Keyfile not found: {}", path.display()),
+                    "Hint: Pass --keyfile-path, or adopt with --tier passphrase.",
+                ));
+            }
+            Some(path)
+        }
+        SecurityTier::Passphrase | SecurityTier::PassphraseKeychain => None,
+    };
+
+    if matches!(tier, SecurityTier::DeviceKeyfile) && interactive {
+        let proceed = Confirm::new()
+            .with_prompt(device_keyfile_warning())
+            .default(false)
+            .interact()?;
+        if !proceed {
+            return Err(anyhow::anyhow!("Adopt cancelled"));
+        }
+    }
+
+    let (unlock, passphrase, keyfile_mode) = match (tier, keyfile_path.as_ref()) {
+        (SecurityTier::DeviceKeyfile, Some(path)) => (
+            key_bytes_to_passphrase(&read_keyfile_plain(path)?),
+            None,
+            KeyfileMode::Plain,
+        ),
+        (SecurityTier::PassphraseKeyfile, Some(path)) => {
+            let passphrase = prompt_passphrase(interactive)?;
+            let key_bytes = read_keyfile_encrypted(path, &passphrase).map_err(|_| {
+                CliError::auth_failed_with_hint(
+                    format!("Could not decrypt keyfile {}", path.display()),
+                    "Hint: Check the passphrase, or that this is the ledger's keyfile.",
+                )
+            })?;
+            (
+                key_bytes_to_passphrase(&key_bytes),
+                Some(passphrase),
+                KeyfileMode::Encrypted,
+            )
+        }
+        _ => {
+            let passphrase = prompt_passphrase(interactive)?;
+            (passphrase.clone(), Some(passphrase), KeyfileMode::None)
+        }
+    };
+
+    let storage = AgeSqliteStorage::open(&ledger_path, &unlock).map_err(|err| match err {
+        LedgerError::IncorrectPassphrase => anyhow::Error::from(CliError::auth_failed_with_hint(
+            format!("Incorrect passphrase for {}", ledger_path.display()),
+            "Hint: Use the passphrase (or keyfile) the ledger was created with.",
+        )),
+        err => err.into(),
+    })?;
+    let metadata = storage.metadata()?;
+    let entries = storage.count_entries(&EntryFilter::new().latest_only())?;
+    let search_tokenizer = storage.search_tokenizer()?;
+    drop(storage);
+
+    let ttl = args.passphrase_cache_ttl_seconds.unwrap_or(0);
+    let mut config = LedgerConfig::new(
+        ledger_path.clone(),
+        tier,
+        ttl,
+        keyfile_mode,
+        keyfile_path.clone(),
+        timezone,
+        args.editor.clone(),
+    );
+    // Keep a non-default index tokenizer so `ledger reindex` does not reset it
+    if search_tokenizer != SearchTokenizer::default() {
+        config.search.tokenizer = Some(search_tokenizer.to_string());
+    }
+    write_config(&config_path, &config)?;
+
+    if let (SecurityTier::PassphraseKeychain, Some(passphrase)) = (tier, passphrase.as_ref()) {
+        if let Err(err) = keychain_set(&ledger_hash(&ledger_path), passphrase) {
+            ctx.warn(&format!(
+                "Could not store the passphrase in the keychain: {}\nHint: Ledger will prompt instead.",
+                err
+            ))?;
+        }
+    }
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "status": "ok",
+                "ledger_path": ledger_path,
+                "config_path": config_path,
+                "tier": config.security.tier,
+                "keyfile_path": keyfile_path,
+                "format_version": metadata.format_version,
+                "device_id": metadata.device_id,
+                "created_at": metadata.created_at,
+                "last_modified": metadata.last_modified,
+                "entries": entries,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Plain => {
+            if !ctx.quiet() {
+                println!("status=ok");
+                println!("ledger_path={}", ledger_path.display());
+                println!("config_path={}", config_path.display());
+                println!("tier={}", args.tier);
+                println!("created_at={}", metadata.created_at.to_rfc3339());
+                println!("entries={}", entries);
+            }
+        }
+        OutputMode::Pretty => {
+            if !ctx.quiet() {
+                print(&ui_ctx, &header(&ui_ctx, "adopt", None));
+                blank_line(&ui_ctx);
+                print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, "Ledger unlocked"));
+                print(
+                    &ui_ctx,
+                    &kv(&ui_ctx, "Ledger", &ledger_path.display().to_string()),
+                );
+                print(
+                    &ui_ctx,
+                    &kv(
+                        &ui_ctx,
+                        "Created",
+                        &metadata.created_at.format("%Y-%m-%d").to_string(),
+                    ),
+                );
+                print(
+                    &ui_ctx,
+                    &kv(
+                        &ui_ctx,
+                        "Modified",
+                        &metadata.last_modified.format("%Y-%m-%d %H:%M").to_string(),
+                    ),
+                );
+                print(&ui_ctx, &kv(&ui_ctx, "Entries", &entries.to_string()));
+                print(&ui_ctx, &kv(&ui_ctx, "Security", &args.tier));
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Config written to {}", config_path.display()),
+                    ),
+                );
+                blank_line(&ui_ctx);
+                print(&ui_ctx, &hint(&ui_ctx, "ledger list  |  ledger doctor"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_tier(value: &str) -> anyhow::Result<SecurityTier> {
+    match value {
+        "passphrase" => Ok(SecurityTier::Passphrase),
+        "passphrase_keychain" => Ok(SecurityTier::PassphraseKeychain),
+        "passphrase_keyfile" => Ok(SecurityTier::PassphraseKeyfile),
+        "device_keyfile" => Ok(SecurityTier::DeviceKeyfile),
+        other => Err(CliError::invalid_input(format!(
+            "Unknown security tier: {}\nHint: Use passphrase, passphrase_keychain, passphrase_keyfile or device_keyfile.",
+            other
+        ))
+        .into()),
+    }
+}
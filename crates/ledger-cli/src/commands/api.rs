@@ -0,0 +1,351 @@
+//! Single-shot JSON API for editor plugins.
+//!
+//! `ledger api --stdin-json` reads one request from stdin and prints one
+//! response on stdout, so vim, neovim or VS Code plugins can talk to the
+//! ledger without driving many subcommands and parsing their output.
+//!
+//! Request: `{"op": "...", "params": {...}, "id": <any>, "app": "..."}`.
+//! `params`, `id` and `app` are optional; `id` is echoed back unchanged, and
+//! `app` names the plugin in the origin recorded on entries it writes.
+//!
+//! Response: `{"ok": true, "id": ..., "result": ...}`, or
+//! `{"ok": false, "id": ..., "error": {"code": "...", "exit_code": N, "message": "..."}}`
+//! with the process exiting with the same code as the matching subcommand.
+
+use std::io::Read;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use ledger_core::storage::{AgeSqliteStorage, EntryFilter, NewEntry, StorageEngine};
+use ledger_core::VERSION;
+
+use crate::app::{not_found_with_hint, AppContext};
+use crate::cli::ApiArgs;
+use crate::constants::exit_codes;
+use crate::errors::{exit_code, CliError};
+use crate::helpers::{ensure_builtin_entry_type, parse_datetime, require_entry_type};
+use crate::output::{entries_json, entry_type_name_map, stored_entry_json};
+
+/// Version of the request and response shapes; bumped on breaking changes.
+const API_VERSION: u32 = 1;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ApiRequest {
+    op: String,
+    #[serde(default)]
+    params: serde_json::Value,
+    #[serde(default)]
+    id: serde_json::Value,
+    app: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QueryParams {
+    /// Required by `search`, rejected by `list`.
+    query: Option<String>,
+    #[serde(rename = "type")]
+    entry_type: Option<String>,
+    tag: Option<String>,
+    origin: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+    #[serde(default)]
+    history: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShowParams {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AddParams {
+    #[serde(rename = "type", default = "default_entry_type")]
+    entry_type: String,
+    body: Option<String>,
+    data: Option<serde_json::Map<String, serde_json::Value>>,
+    #[serde(default)]
+    tags: Vec<String>,
+    created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EditParams {
+    id: String,
+    body: Option<String>,
+    data: Option<serde_json::Map<String, serde_json::Value>>,
+    tags: Option<Vec<String>>,
+}
+
+fn default_entry_type() -> String {
+    "journal".to_string()
+}
+
+pub fn handle_api(ctx: &AppContext, _args: &ApiArgs) -> anyhow::Result<()> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+
+    let (id, outcome) = match serde_json::from_str::<ApiRequest>(&input) {
+        Ok(request) => (request.id.clone(), dispatch(ctx, request)),
+        Err(err) => (
+            serde_json::Value::Null,
+            Err(CliError::invalid_input(format!("Invalid API request: {}", err)).into()),
+        ),
+    };
+
+    match outcome {
+        Ok(result) => {
+            let response = serde_json::json!({ "ok": true, "id": id, "result": result });
+            println!("{}", serde_json::to_string(&response)?);
+            Ok(())
+        }
+        Err(err) => {
+            let code = exit_code(&err);
+            let response = serde_json::json!({
+                "ok": false,
+                "id": id,
+                "error": {
+                    "code": error_code(code),
+                    "exit_code": code,
+                    "message": err.to_string(),
+                },
+            });
+            println!("{}", serde_json::to_string(&response)?);
+            Err(err)
+        }
+    }
+}
+
+fn dispatch(ctx: &AppContext, request: ApiRequest) -> anyhow::Result<serde_json::Value> {
+    let op = request.op.as_str();
+    if op == "version" {
+        return Ok(serde_json::json!({ "version": VERSION, "api": API_VERSION }));
+    }
+    if !matches!(
+        op,
+        "types" | "tags" | "list" | "search" | "show" | "add" | "edit"
+    ) {
+        return Err(CliError::invalid_input(format!(
+            "Unknown API op: {} (use version, types, tags, list, search, show, add or edit)",
+            op
+        ))
+        .into());
+    }
+
+    // Editor plugins have no terminal to prompt on
+    let (mut storage, passphrase) = ctx.open_storage(true)?;
+    let mut origin = ctx.entry_origin().with_command(format!("api {}", op));
+    if let Some(app) = request.app {
+        origin.app = app;
+        origin.version = None;
+    }
+    storage.set_origin(Some(origin));
+    let result = match op {
+        "types" => types(&storage)?,
+        "tags" => serde_json::to_value(storage.tag_counts()?)?,
+        "list" => {
+            let params: QueryParams = params(op, request.params)?;
+            if params.query.is_some() {
+                return Err(CliError::invalid_input(
+                    "Invalid params for list: query is only used by search",
+                )
+                .into());
+            }
+            let filter = entry_filter(&storage, &params)?;
+            let entries = storage.list_entries(&filter)?;
+            serde_json::json!(entries_json(&entries, &entry_type_name_map(&storage)?))
+        }
+        "search" => {
+            let params: QueryParams = params(op, request.params)?;
+            let Some(ref query) = params.query else {
+                return Err(CliError::invalid_input(
+                    "Invalid params for search: missing field `query`",
+                )
+                .into());
+            };
+            let filter = entry_filter(&storage, &params)?;
+            let entries = storage.search_entries_filtered(query, &filter)?;
+            serde_json::json!(entries_json(&entries, &entry_type_name_map(&storage)?))
+        }
+        "show" => {
+            let params: ShowParams = params(op, request.params)?;
+            let entry_id = parse_entry_id(&storage, &params.id)?;
+            stored_entry_json(&storage, &entry_id)?
+        }
+        "add" => {
+            let params: AddParams = params(op, request.params)?;
+            let entry_id = add(&mut storage, params)?;
+            let result = stored_entry_json(&storage, &entry_id)?;
+            ctx.save_storage(storage, &passphrase)?;
+            result
+        }
+        _ => {
+            let params: EditParams = params(op, request.params)?;
+            let entry_id = edit(&mut storage, params)?;
+            let result = stored_entry_json(&storage, &entry_id)?;
+            ctx.save_storage(storage, &passphrase)?;
+            result
+        }
+    };
+    Ok(result)
+}
+
+/// Parse `params` for `op`; a missing `params` counts as an empty object.
+fn params<T: DeserializeOwned>(op: &str, value: serde_json::Value) -> anyhow::Result<T> {
+    let value = if value.is_null() {
+        serde_json::json!({})
+    } else {
+        value
+    };
+    serde_json::from_value(value).map_err(|err| {
+        CliError::invalid_input(format!("Invalid params for {}: {}", op, err)).into()
+    })
+}
+
+fn types(storage: &AgeSqliteStorage) -> anyhow::Result<serde_json::Value> {
+    Ok(serde_json::json!(storage
+        .list_entry_types()?
+        .iter()
+        .map(|entry_type| serde_json::json!({
+            "name": entry_type.name,
+            "version": entry_type.version,
+            "fields": entry_type.schema_json.get("fields").cloned().unwrap_or_default(),
+        }))
+        .collect::<Vec<_>>()))
+}
+
+fn entry_filter(storage: &AgeSqliteStorage, params: &QueryParams) -> anyhow::Result<EntryFilter> {
+    let mut filter = EntryFilter::new();
+    if let Some(ref name) = params.entry_type {
+        filter = filter.entry_type(require_entry_type(storage, name)?.id);
+    }
+    if let Some(ref tag) = params.tag {
+        filter = filter.tag(tag.clone());
+    }
+    if let Some(ref origin) = params.origin {
+        filter = filter.origin(origin.clone());
+    }
+    if let Some(ref since) = params.since {
+        filter = filter.since(parse_datetime(since)?);
+    }
+    if let Some(ref until) = params.until {
+        filter = filter.until(parse_datetime(until)?);
+    }
+    if !params.history {
+        filter = filter.latest_only();
+    }
+    if let Some(limit) = params.limit {
+        filter = filter.limit(limit);
+    }
+    Ok(filter)
+}
+
+fn parse_entry_id(storage: &AgeSqliteStorage, id: &str) -> anyhow::Result<Uuid> {
+    let parsed = Uuid::parse_str(id)
+        .map_err(|e| CliError::invalid_input(format!("Invalid entry ID: {}", e)))?;
+    if storage.get_entry(&parsed)?.is_none() {
+        return Err(not_found_with_hint(
+            "Entry not found",
+            "Hint: Use the list or search op to find entry IDs.",
+        ));
+    }
+    Ok(parsed)
+}
+
+fn add(storage: &mut AgeSqliteStorage, params: AddParams) -> anyhow::Result<Uuid> {
+    let mut data = params.data.unwrap_or_default();
+    if let Some(body) = params.body {
+        data.insert("body".to_string(), serde_json::Value::String(body));
+    }
+    let device_id = storage.metadata()?.device_id;
+    ensure_builtin_entry_type(storage, &params.entry_type, device_id)?;
+    let entry_type = require_entry_type(storage, &params.entry_type)?;
+
+    let mut new_entry = NewEntry::new(
+        entry_type.id,
+        entry_type.version,
+        serde_json::Value::Object(data),
+        device_id,
+    )
+    .with_tags(params.tags);
+    if let Some(ref value) = params.created_at {
+        new_entry = new_entry.with_created_at(parse_datetime(value)?);
+    }
+    Ok(storage.insert_entry(&new_entry)?)
+}
+
+/// Write a revision superseding the entry. `data` replaces the fields,
+/// `body` replaces just the body, and tags are kept unless given.
+fn edit(storage: &mut AgeSqliteStorage, params: EditParams) -> anyhow::Result<Uuid> {
+    let entry_id = parse_entry_id(storage, &params.id)?;
+    let entry = storage
+        .get_entry(&entry_id)?
+        .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", entry_id))?;
+    if params.body.is_none() && params.data.is_none() && params.tags.is_none() {
+        return Err(CliError::invalid_input("Nothing to edit: pass body, data or tags").into());
+    }
+
+    let mut data = match params.data {
+        Some(data) => serde_json::Value::Object(data),
+        None => entry.data.clone(),
+    };
+    if let Some(body) = params.body {
+        data["body"] = serde_json::Value::String(body);
+    }
+    let device_id = storage.metadata()?.device_id;
+    let new_entry = NewEntry::new(entry.entry_type_id, entry.schema_version, data, device_id)
+        .with_tags(params.tags.unwrap_or(entry.tags))
+        .with_supersedes(entry.id);
+    Ok(storage.insert_entry(&new_entry)?)
+}
+
+/// Stable error name for an exit code, for plugins that branch on it.
+fn error_code(exit_code: i32) -> &'static str {
+    match exit_code {
+        exit_codes::NOT_FOUND => "not_found",
+        exit_codes::INVALID_INPUT => "invalid_input",
+        exit_codes::AUTH_FAILED => "auth_failed",
+        exit_codes::STRICT_WARNING => "strict_warning",
+        _ => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_default_to_empty_object() {
+        let parsed: QueryParams = params("list", serde_json::Value::Null).unwrap();
+        assert!(parsed.limit.is_none());
+        assert!(!parsed.history);
+    }
+
+    #[test]
+    fn test_params_reject_unknown_fields() {
+        let err =
+            params::<ShowParams>("show", serde_json::json!({"id": "x", "idd": "y"})).unwrap_err();
+        assert_eq!(exit_code(&err), exit_codes::INVALID_INPUT);
+        assert!(err.to_string().contains("Invalid params for show"));
+    }
+
+    #[test]
+    fn test_query_params_rename_type() {
+        let parsed: QueryParams = params(
+            "search",
+            serde_json::json!({"query": "coffee", "type": "journal", "limit": 5}),
+        )
+        .unwrap();
+        assert_eq!(parsed.query.as_deref(), Some("coffee"));
+        assert_eq!(parsed.entry_type.as_deref(), Some("journal"));
+        assert_eq!(parsed.limit, Some(5));
+    }
+}
@@ -4,7 +4,8 @@ use ledger_core::storage::StorageEngine;
 
 use crate::app::AppContext;
 use crate::cli::DetachArgs;
-use crate::ui::theme::{styled, styles};
+use crate::output::Receipt;
+use crate::ui::theme::{styled, styles, symbols};
 use crate::ui::{badge, blank_line, hint, print, short_id, Badge, OutputMode};
 
 pub fn handle_detach(ctx: &AppContext, args: &DetachArgs) -> anyhow::Result<()> {
@@ -31,19 +32,20 @@ pub fn handle_detach(ctx: &AppContext, args: &DetachArgs) -> anyhow::Result<()>
         .ok_or_else(|| anyhow::anyhow!("Composition '{}' not found", args.composition))?;
 
     storage.detach_entry_from_composition(&entry_id, &composition.id)?;
-    storage.close(&passphrase)?;
+    ctx.save_storage(storage, &passphrase)?;
 
     if !ctx.quiet() {
-        let ui_ctx = ctx.ui_context(false, None);
+        let ui_ctx = ctx.ui_context(args.json, None);
         match ui_ctx.mode {
             OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
                 print(
                     &ui_ctx,
                     &badge(&ui_ctx, Badge::Ok, "Detached entry from composition"),
                 );
                 // Context line with entry ID and composition name
                 let context = format!(
-                    "Entry: {}  \u{00B7}  Composition: {}",
+                    "Entry: {}  {sep}  Composition: {}",
                     short_id(&entry_id),
                     composition.name
                 );
@@ -55,11 +57,16 @@ pub fn handle_detach(ctx: &AppContext, args: &DetachArgs) -> anyhow::Result<()>
                     &ui_ctx,
                     &hint(
                         &ui_ctx,
-                        &format!("ledger show {}  \u{00B7}  ledger list", short_id(&entry_id)),
+                        &format!("ledger show {}  {sep}  ledger list", short_id(&entry_id)),
                     ),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                Receipt::new("detach", "entry", entry_id, None)
+                    .affects("composition", composition.id, Some(&composition.name))
+                    .print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("entry_id={}", entry_id);
                 println!("composition={}", composition.name);
@@ -0,0 +1,78 @@
+//! `ledger attachments gc`: drop blobs no attachment refers to.
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::AttachmentGcArgs;
+use crate::ui::{badge, blank_line, format_bytes, header, kv, print, Badge, OutputMode};
+
+pub fn handle_gc(ctx: &AppContext, args: &AttachmentGcArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    // The ledger is only written on save, so a dry run collects in memory
+    // and drops the result
+    let gc = storage.gc_attachment_blobs()?;
+    let usage = storage.attachment_usage()?;
+    if !args.dry_run && gc.blobs_removed > 0 {
+        ctx.save_storage(storage, &passphrase)?;
+    }
+
+    if ctx.quiet() && !args.json {
+        return Ok(());
+    }
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "status": "ok",
+                "dry_run": args.dry_run,
+                "blobs_removed": gc.blobs_removed,
+                "bytes_freed": gc.bytes_freed,
+                "usage": usage,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            print(
+                &ui_ctx,
+                &header(&ui_ctx, "attachments gc", ledger_path.as_deref()),
+            );
+            blank_line(&ui_ctx);
+            let verb = if args.dry_run {
+                "Would remove"
+            } else {
+                "Removed"
+            };
+            let message = if gc.blobs_removed == 0 {
+                "No unreferenced blobs".to_string()
+            } else {
+                format!(
+                    "{} {} unreferenced blob(s), {}",
+                    verb,
+                    gc.blobs_removed,
+                    format_bytes(gc.bytes_freed)
+                )
+            };
+            print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, &message));
+            print(
+                &ui_ctx,
+                &kv(
+                    &ui_ctx,
+                    "Stored",
+                    &format!(
+                        "{} in {} blob(s) for {} attachment(s)",
+                        format_bytes(usage.stored_bytes),
+                        usage.blobs,
+                        usage.attachments
+                    ),
+                ),
+            );
+        }
+        OutputMode::Plain => {
+            println!("status=ok");
+            println!("dry_run={}", args.dry_run);
+            println!("blobs_removed={}", gc.blobs_removed);
+            println!("bytes_freed={}", gc.bytes_freed);
+            println!("stored_bytes={}", usage.stored_bytes);
+        }
+    }
+    Ok(())
+}
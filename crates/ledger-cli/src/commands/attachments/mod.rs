@@ -0,0 +1,3 @@
+pub mod gc;
+
+pub use gc::handle_gc;
@@ -0,0 +1,224 @@
+//! Collect command handler: turns local system activity into entries.
+
+use chrono::{Local, NaiveDate, Utc};
+use uuid::Uuid;
+
+use ledger_core::storage::{Entry, EntryFilter, NewEntry, StorageEngine};
+
+use crate::app::AppContext;
+use crate::cli::CollectArgs;
+use crate::collectors::{build_collectors, local_day_bounds, Activity};
+use crate::helpers::{ensure_builtin_entry_type, require_entry_type};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, blank_line, print, short_id, Badge, OutputMode};
+
+/// What happened to a collected activity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Outcome {
+    Added,
+    Updated,
+    Unchanged,
+    Preview,
+}
+
+impl Outcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Added => "added",
+            Outcome::Updated => "updated",
+            Outcome::Unchanged => "unchanged",
+            Outcome::Preview => "preview",
+        }
+    }
+}
+
+pub fn handle_collect(ctx: &AppContext, args: &CollectArgs) -> anyhow::Result<()> {
+    let day = match args.date {
+        Some(ref value) => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| anyhow::anyhow!("Invalid date: {} (expected YYYY-MM-DD)", value))?,
+        None => Local::now().date_naive(),
+    };
+
+    let section = ctx
+        .config()?
+        .map(|config| config.collectors.clone())
+        .unwrap_or_default();
+    let collectors = build_collectors(&section, &args.only)?;
+    if collectors.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No collectors enabled\nHint: Add `enabled = [\"git\"]` (or shell_history, calendar) under [collectors] in your config."
+        ));
+    }
+
+    // A failing collector is reported but does not block the others
+    let mut activities: Vec<Activity> = Vec::new();
+    let mut failures: Vec<(&'static str, String)> = Vec::new();
+    for collector in &collectors {
+        match collector.collect(day) {
+            Ok(found) => activities.extend(found),
+            Err(e) => failures.push((collector.name(), e.to_string())),
+        }
+    }
+    for (name, error) in &failures {
+        ctx.warn(&format!("collector {} failed: {}", name, error))?;
+    }
+
+    let mut results: Vec<(Activity, Outcome, Option<Uuid>)> = Vec::new();
+    if ctx.dry_run() {
+        results.extend(activities.into_iter().map(|a| (a, Outcome::Preview, None)));
+    } else if !activities.is_empty() {
+        let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+        let metadata = storage.metadata()?;
+        ensure_builtin_entry_type(&mut storage, "activity", metadata.device_id)?;
+        let entry_type = require_entry_type(&storage, "activity")?;
+
+        let superseded = storage.superseded_entry_ids()?;
+        let existing: Vec<Entry> = storage
+            .list_entries(&EntryFilter::new().entry_type(entry_type.id))?
+            .into_iter()
+            .filter(|entry| !superseded.contains(&entry.id))
+            .collect();
+        let date = day.format("%Y-%m-%d").to_string();
+
+        storage.batch(|storage| {
+            for activity in activities {
+                let data = activity_data(&activity, &date);
+                let previous = existing.iter().find(|entry| {
+                    entry.data.get("source") == data.get("source")
+                        && entry.data.get("date") == data.get("date")
+                        && entry.data.get("subject") == data.get("subject")
+                });
+                if let Some(previous) = previous {
+                    if previous.data == data {
+                        results.push((activity, Outcome::Unchanged, Some(previous.id)));
+                        continue;
+                    }
+                }
+
+                let mut new_entry =
+                    NewEntry::new(entry_type.id, entry_type.version, data, metadata.device_id)
+                        .with_tags(vec![format!("collector:{}", activity.source)]);
+                if let Some(created_at) = entry_time(day) {
+                    new_entry = new_entry.with_created_at(created_at);
+                }
+                let outcome = match previous {
+                    Some(previous) => {
+                        new_entry = new_entry.with_supersedes(previous.id);
+                        Outcome::Updated
+                    }
+                    None => Outcome::Added,
+                };
+                let entry_id = storage.insert_entry(&new_entry)?;
+                results.push((activity, outcome, Some(entry_id)));
+            }
+            Ok(())
+        })?;
+
+        ctx.save_storage(storage, &passphrase)?;
+    }
+
+    if ctx.quiet() {
+        return Ok(());
+    }
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "date": day.format("%Y-%m-%d").to_string(),
+                "dry_run": ctx.dry_run(),
+                "activities": results.iter().map(|(activity, outcome, id)| {
+                    serde_json::json!({
+                        "id": id.map(|id| id.to_string()),
+                        "source": activity.source,
+                        "subject": activity.subject,
+                        "summary": activity.summary,
+                        "count": activity.count,
+                        "details": activity.details,
+                        "status": outcome.as_str(),
+                    })
+                }).collect::<Vec<_>>(),
+                "failures": failures.iter().map(|(name, error)| {
+                    serde_json::json!({"collector": name, "error": error})
+                }).collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Plain => {
+            println!("date={}", day.format("%Y-%m-%d"));
+            for (activity, outcome, id) in &results {
+                let id = id.map(|id| id.to_string()).unwrap_or_default();
+                println!(
+                    "activity={} status={} source={} count={} summary={}",
+                    id,
+                    outcome.as_str(),
+                    activity.source,
+                    activity.count,
+                    activity.summary
+                );
+            }
+        }
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            let written = results
+                .iter()
+                .filter(|(_, outcome, _)| matches!(outcome, Outcome::Added | Outcome::Updated))
+                .count();
+            let message = if ctx.dry_run() {
+                format!("Found {} activities for {}", results.len(), day)
+            } else if results.is_empty() {
+                format!("No activity found for {}", day)
+            } else {
+                format!("Collected {} activities for {}", written, day)
+            };
+            let kind = if failures.is_empty() {
+                Badge::Ok
+            } else {
+                Badge::Warn
+            };
+            print(&ui_ctx, &badge(&ui_ctx, kind, &message));
+            for (activity, outcome, id) in &results {
+                blank_line(&ui_ctx);
+                let mut context = format!("{}  {sep}  {}", activity.source, outcome.as_str());
+                if let Some(id) = id {
+                    context.push_str(&format!("  {sep}  ID: {}", short_id(id)));
+                }
+                println!(
+                    "{}  {}",
+                    styled(&activity.summary, styles::bold(), ui_ctx.color),
+                    styled(&context, styles::dim(), ui_ctx.color)
+                );
+                for detail in &activity.details {
+                    println!("  {}", detail);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn activity_data(activity: &Activity, date: &str) -> serde_json::Value {
+    let mut data = serde_json::json!({
+        "source": activity.source,
+        "date": date,
+        "summary": activity.summary,
+        "count": activity.count,
+    });
+    if let Some(ref subject) = activity.subject {
+        data["subject"] = serde_json::Value::String(subject.clone());
+    }
+    if !activity.details.is_empty() {
+        data["details"] = serde_json::Value::String(activity.details.join("\n"));
+    }
+    data
+}
+
+/// Timestamp for an activity entry: now for today, otherwise the end of the day.
+fn entry_time(day: NaiveDate) -> Option<chrono::DateTime<Utc>> {
+    if day == Local::now().date_naive() {
+        return None;
+    }
+    let (_, end) = local_day_bounds(day)?;
+    Some(end - chrono::Duration::seconds(1))
+}
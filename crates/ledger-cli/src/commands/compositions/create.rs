@@ -4,7 +4,8 @@ use ledger_core::storage::{NewComposition, StorageEngine};
 
 use crate::app::AppContext;
 use crate::cli::CompositionCreateArgs;
-use crate::ui::theme::{styled, styles};
+use crate::output::Receipt;
+use crate::ui::theme::{styled, styles, symbols};
 use crate::ui::{badge, blank_line, hint, print, short_id, Badge, OutputMode};
 
 pub fn handle_create(ctx: &AppContext, args: &CompositionCreateArgs) -> anyhow::Result<()> {
@@ -17,18 +18,19 @@ pub fn handle_create(ctx: &AppContext, args: &CompositionCreateArgs) -> anyhow::
     }
 
     let composition_id = storage.create_composition(&new_composition)?;
-    storage.close(&passphrase)?;
+    ctx.save_storage(storage, &passphrase)?;
 
     if !ctx.quiet() {
-        let ui_ctx = ctx.ui_context(false, None);
+        let ui_ctx = ctx.ui_context(args.json, None);
         let created_at = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
 
         match ui_ctx.mode {
             OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
                 print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, "Created composition"));
                 // Context line with name, ID, and timestamp
                 let context = format!(
-                    "Name: {}  \u{00B7}  ID: {}  \u{00B7}  {}",
+                    "Name: {}  {sep}  ID: {}  {sep}  {}",
                     args.name,
                     short_id(&composition_id),
                     created_at
@@ -42,13 +44,22 @@ pub fn handle_create(ctx: &AppContext, args: &CompositionCreateArgs) -> anyhow::
                     &hint(
                         &ui_ctx,
                         &format!(
-                            "ledger attach <entry-id> {}  \u{00B7}  ledger composition list",
+                            "ledger attach <entry-id> {}  {sep}  ledger composition list",
                             args.name
                         ),
                     ),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                Receipt::new(
+                    "composition.create",
+                    "composition",
+                    composition_id,
+                    Some(&args.name),
+                )
+                .print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("composition_id={}", composition_id);
                 println!("name={}", args.name);
@@ -1,10 +1,11 @@
-use dialoguer::Confirm;
 use uuid::Uuid;
 
 use ledger_core::storage::StorageEngine;
 
 use crate::app::AppContext;
 use crate::cli::CompositionDeleteArgs;
+use crate::output::Receipt;
+use crate::ui::prompt::confirm_destructive;
 use crate::ui::{badge, print, Badge, OutputMode};
 
 pub fn handle_delete(ctx: &AppContext, args: &CompositionDeleteArgs) -> anyhow::Result<()> {
@@ -24,43 +25,37 @@ pub fn handle_delete(ctx: &AppContext, args: &CompositionDeleteArgs) -> anyhow::
     let entries = storage.get_composition_entries(&composition.id)?;
     let entry_count = entries.len();
 
-    if !args.force {
-        let confirm_msg = if entry_count > 0 {
-            format!(
-                "Delete composition '{}' with {} attached entries? (entries will NOT be deleted)",
-                composition.name, entry_count
-            )
-        } else {
-            format!("Delete composition '{}'?", composition.name)
-        };
-
-        let confirmed = Confirm::new()
-            .with_prompt(confirm_msg)
-            .default(false)
-            .interact()?;
-
-        if !confirmed {
-            if !ctx.quiet() {
-                let ui_ctx = ctx.ui_context(false, None);
-                match ui_ctx.mode {
-                    OutputMode::Pretty => {
-                        print(&ui_ctx, &badge(&ui_ctx, Badge::Info, "Cancelled"));
-                    }
-                    OutputMode::Plain | OutputMode::Json => {
-                        println!("status=cancelled");
-                    }
+    let confirm_msg = if entry_count > 0 {
+        format!(
+            "Delete composition '{}' with {} attached entries? (entries will NOT be deleted)",
+            composition.name, entry_count
+        )
+    } else {
+        format!("Delete composition '{}'?", composition.name)
+    };
+    let ui_ctx = ctx.ui_context(args.json, None);
+    if !confirm_destructive(&ui_ctx, &confirm_msg, args.force || ctx.assume_yes(), false)? {
+        if !ctx.quiet() {
+            match ui_ctx.mode {
+                OutputMode::Pretty => {
+                    print(&ui_ctx, &badge(&ui_ctx, Badge::Info, "Cancelled"));
+                }
+                OutputMode::Json => {
+                    println!("{}", serde_json::json!({ "status": "cancelled" }));
+                }
+                OutputMode::Plain => {
+                    println!("status=cancelled");
                 }
             }
-            return Ok(());
         }
+        return Ok(());
     }
 
     let name = composition.name.clone();
     storage.delete_composition(&composition.id)?;
-    storage.close(&passphrase)?;
+    ctx.save_storage(storage, &passphrase)?;
 
     if !ctx.quiet() {
-        let ui_ctx = ctx.ui_context(false, None);
         match ui_ctx.mode {
             OutputMode::Pretty => {
                 print(
@@ -72,7 +67,16 @@ pub fn handle_delete(ctx: &AppContext, args: &CompositionDeleteArgs) -> anyhow::
                     ),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                Receipt::new(
+                    "composition.delete",
+                    "composition",
+                    composition.id,
+                    Some(&name),
+                )
+                .print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("deleted={}", name);
             }
@@ -0,0 +1,79 @@
+use uuid::Uuid;
+
+use ledger_core::storage::StorageEngine;
+
+use crate::app::AppContext;
+use crate::cli::CompositionEditArgs;
+use crate::helpers::read_entry_body;
+use crate::output::Receipt;
+use crate::ui::theme::{styled, styles};
+use crate::ui::{badge, print, Badge, OutputMode};
+
+pub fn handle_edit(ctx: &AppContext, args: &CompositionEditArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+
+    // Try to find by name first, then by ID
+    let composition = if let Ok(uuid) = Uuid::parse_str(&args.name_or_id) {
+        storage.get_composition_by_id(&uuid)?
+    } else {
+        storage.get_composition(&args.name_or_id)?
+    };
+
+    let composition = composition
+        .ok_or_else(|| anyhow::anyhow!("Composition '{}' not found", args.name_or_id))?;
+
+    let body = if args.clear {
+        String::new()
+    } else {
+        let current = storage.composition_overview(&composition.id)?;
+        let editor_override = match args.editor.as_deref() {
+            Some(editor) => Some(editor),
+            None => ctx.editor()?,
+        };
+        read_entry_body(
+            args.no_input,
+            args.body.clone(),
+            editor_override.into(),
+            current.as_ref().map(|overview| overview.body.as_str()),
+        )?
+    };
+    storage.set_composition_overview(&composition.id, &body)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(args.json, None);
+        let lines = body.lines().count();
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let message = if args.clear {
+                    format!("Cleared overview of '{}'", composition.name)
+                } else {
+                    format!("Saved overview of '{}'", composition.name)
+                };
+                print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, &message));
+                if !args.clear {
+                    let context = format!(
+                        "{} lines  ·  ledger compositions show {}",
+                        lines, composition.name
+                    );
+                    println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+                }
+            }
+            OutputMode::Json => {
+                Receipt::new(
+                    "composition.edit",
+                    "composition",
+                    composition.id,
+                    Some(&composition.name),
+                )
+                .print()?;
+            }
+            OutputMode::Plain => {
+                println!("status=ok");
+                println!("name={}", composition.name);
+                println!("lines={}", lines);
+            }
+        }
+    }
+    Ok(())
+}
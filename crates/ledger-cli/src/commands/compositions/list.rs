@@ -1,4 +1,5 @@
 use ledger_core::storage::{CompositionFilter, StorageEngine};
+use ledger_core::views::CompositionView;
 
 use crate::app::AppContext;
 use crate::cli::CompositionListArgs;
@@ -13,6 +14,8 @@ pub fn handle_list(ctx: &AppContext, args: &CompositionListArgs) -> anyhow::Resu
     }
 
     let compositions = storage.list_compositions(&filter)?;
+    let stats = storage.composition_stats()?;
+    let stats_for = |id| stats.get(id).cloned().unwrap_or_default();
 
     // Create UI context
     let ui_ctx = ctx.ui_context(args.json, None);
@@ -21,14 +24,7 @@ pub fn handle_list(ctx: &AppContext, args: &CompositionListArgs) -> anyhow::Resu
     if ui_ctx.mode.is_json() {
         let json_output: Vec<_> = compositions
             .iter()
-            .map(|c| {
-                serde_json::json!({
-                    "id": c.id.to_string(),
-                    "name": c.name,
-                    "description": c.description,
-                    "created_at": c.created_at.to_rfc3339(),
-                })
-            })
+            .map(|c| CompositionView::new(c, &stats_for(&c.id)))
             .collect();
         println!("{}", serde_json::to_string_pretty(&json_output)?);
         return Ok(());
@@ -59,6 +55,8 @@ pub fn handle_list(ctx: &AppContext, args: &CompositionListArgs) -> anyhow::Resu
 
             let columns = [
                 Column::new("Name"),
+                Column::new("Entries"),
+                Column::new("Last activity"),
                 Column::new("Description"),
                 Column::new("ID"),
             ];
@@ -66,8 +64,14 @@ pub fn handle_list(ctx: &AppContext, args: &CompositionListArgs) -> anyhow::Resu
             let rows: Vec<Vec<String>> = compositions
                 .iter()
                 .map(|c| {
+                    let stats = stats_for(&c.id);
                     vec![
                         c.name.clone(),
+                        stats.entry_count.to_string(),
+                        stats
+                            .last_activity
+                            .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+                            .unwrap_or_else(|| "-".to_string()),
                         c.description.clone().unwrap_or_default(),
                         short_id(&c.id),
                     ]
@@ -83,8 +87,16 @@ pub fn handle_list(ctx: &AppContext, args: &CompositionListArgs) -> anyhow::Resu
         }
         OutputMode::Plain | OutputMode::Json => {
             for comp in &compositions {
+                let stats = stats_for(&comp.id);
+                let last = stats
+                    .last_activity
+                    .map(|at| at.to_rfc3339())
+                    .unwrap_or_else(|| "-".to_string());
                 let desc = comp.description.as_deref().unwrap_or("");
-                println!("{} {} {}", comp.id, comp.name, desc);
+                println!(
+                    "{} {} {} {} {}",
+                    comp.id, comp.name, stats.entry_count, last, desc
+                );
             }
         }
     }
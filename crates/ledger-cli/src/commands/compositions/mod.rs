@@ -1,11 +1,13 @@
 pub mod create;
 pub mod delete;
+pub mod edit;
 pub mod list;
 pub mod rename;
 pub mod show;
 
 pub use create::handle_create;
 pub use delete::handle_delete;
+pub use edit::handle_edit;
 pub use list::handle_list;
 pub use rename::handle_rename;
 pub use show::handle_show;
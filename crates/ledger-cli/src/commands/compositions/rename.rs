@@ -4,6 +4,7 @@ use ledger_core::storage::StorageEngine;
 
 use crate::app::AppContext;
 use crate::cli::CompositionRenameArgs;
+use crate::output::Receipt;
 use crate::ui::{badge, print, Badge, OutputMode};
 
 pub fn handle_rename(ctx: &AppContext, args: &CompositionRenameArgs) -> anyhow::Result<()> {
@@ -21,10 +22,10 @@ pub fn handle_rename(ctx: &AppContext, args: &CompositionRenameArgs) -> anyhow::
 
     let old_name = composition.name.clone();
     storage.rename_composition(&composition.id, &args.new_name)?;
-    storage.close(&passphrase)?;
+    ctx.save_storage(storage, &passphrase)?;
 
     if !ctx.quiet() {
-        let ui_ctx = ctx.ui_context(false, None);
+        let ui_ctx = ctx.ui_context(args.json, None);
         match ui_ctx.mode {
             OutputMode::Pretty => {
                 print(
@@ -36,7 +37,16 @@ pub fn handle_rename(ctx: &AppContext, args: &CompositionRenameArgs) -> anyhow::
                     ),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                Receipt::new(
+                    "composition.rename",
+                    "composition",
+                    composition.id,
+                    Some(&args.new_name),
+                )
+                .print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("old_name={}", old_name);
                 println!("new_name={}", args.new_name);
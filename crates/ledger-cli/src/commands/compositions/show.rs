@@ -1,10 +1,13 @@
 use uuid::Uuid;
 
 use ledger_core::storage::StorageEngine;
+use ledger_core::views::CompositionView;
 
 use crate::app::AppContext;
 use crate::cli::CompositionShowArgs;
-use crate::ui::{blank_line, header, kv, print, OutputMode};
+use crate::ui::{
+    blank_line, divider, header, kv, print, short_id, simple_table, Column, OutputMode,
+};
 
 pub fn handle_show(ctx: &AppContext, args: &CompositionShowArgs) -> anyhow::Result<()> {
     let (storage, _passphrase) = ctx.open_storage(false)?;
@@ -19,21 +22,27 @@ pub fn handle_show(ctx: &AppContext, args: &CompositionShowArgs) -> anyhow::Resu
     let composition = composition
         .ok_or_else(|| anyhow::anyhow!("Composition '{}' not found", args.name_or_id))?;
 
+    let overview = storage.composition_overview(&composition.id)?;
+    let history = if args.history {
+        Some(storage.composition_history(&composition.id)?)
+    } else {
+        None
+    };
+
     // Create UI context
     let ui_ctx = ctx.ui_context(args.json, None);
 
     // Handle JSON output
     if ui_ctx.mode.is_json() {
-        let entries = storage.get_composition_entries(&composition.id)?;
-        let json_output = serde_json::json!({
-            "id": composition.id.to_string(),
-            "name": composition.name,
-            "description": composition.description,
-            "created_at": composition.created_at.to_rfc3339(),
-            "device_id": composition.device_id.to_string(),
-            "metadata": composition.metadata,
-            "entry_count": entries.len(),
-        });
+        let stats = storage
+            .composition_stats()?
+            .remove(&composition.id)
+            .unwrap_or_default();
+        let mut json_output =
+            CompositionView::new(&composition, &stats).with_overview(overview.as_ref());
+        if let Some(ref history) = history {
+            json_output = json_output.with_history(history);
+        }
         println!("{}", serde_json::to_string_pretty(&json_output)?);
         return Ok(());
     }
@@ -61,6 +70,36 @@ pub fn handle_show(ctx: &AppContext, args: &CompositionShowArgs) -> anyhow::Resu
                 ),
             );
             print(&ui_ctx, &kv(&ui_ctx, "Entries", &entries.len().to_string()));
+            if let Some(ref history) = history {
+                let format =
+                    |at: chrono::DateTime<chrono::Utc>| at.format("%Y-%m-%d %H:%M UTC").to_string();
+                let rows: Vec<Vec<String>> = history
+                    .iter()
+                    .map(|membership| {
+                        vec![
+                            short_id(&membership.entry_id),
+                            format(membership.added_at),
+                            membership
+                                .removed_at
+                                .map(format)
+                                .unwrap_or_else(|| "-".to_string()),
+                        ]
+                    })
+                    .collect();
+                blank_line(&ui_ctx);
+                let columns = [
+                    Column::new("Entry"),
+                    Column::new("Added"),
+                    Column::new("Removed"),
+                ];
+                print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            }
+            if let Some(ref overview) = overview {
+                blank_line(&ui_ctx);
+                print(&ui_ctx, &divider(&ui_ctx));
+                blank_line(&ui_ctx);
+                println!("{}", overview.body);
+            }
         }
         OutputMode::Plain | OutputMode::Json => {
             println!("name={}", composition.name);
@@ -71,6 +110,21 @@ pub fn handle_show(ctx: &AppContext, args: &CompositionShowArgs) -> anyhow::Resu
             println!("created_at={}", composition.created_at.to_rfc3339());
             println!("device_id={}", composition.device_id);
             println!("entry_count={}", entries.len());
+            for membership in history.iter().flatten() {
+                println!(
+                    "history={} added_at={} removed_at={}",
+                    membership.entry_id,
+                    membership.added_at.to_rfc3339(),
+                    membership
+                        .removed_at
+                        .map(|at| at.to_rfc3339())
+                        .unwrap_or_else(|| "-".to_string())
+                );
+            }
+            if let Some(ref overview) = overview {
+                println!("overview_updated_at={}", overview.updated_at.to_rfc3339());
+                println!("overview_lines={}", overview.body.lines().count());
+            }
         }
     }
 
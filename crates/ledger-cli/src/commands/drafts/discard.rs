@@ -0,0 +1,37 @@
+use crate::app::AppContext;
+use crate::cli::DraftDiscardArgs;
+use crate::output::Receipt;
+use crate::ui::{badge, print, short_id, Badge, OutputMode};
+
+use super::find_draft;
+
+pub fn handle_discard(ctx: &AppContext, args: &DraftDiscardArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(false)?;
+    let draft = find_draft(&storage, &args.id)?;
+    storage.delete_draft(&draft.id)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(args.json, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Discarded draft {}", short_id(&draft.id)),
+                    ),
+                );
+            }
+            OutputMode::Json => {
+                Receipt::new("discard", "draft", draft.id, None).print()?;
+            }
+            OutputMode::Plain => {
+                println!("status=ok");
+                println!("draft_id={}", draft.id);
+            }
+        }
+    }
+    Ok(())
+}
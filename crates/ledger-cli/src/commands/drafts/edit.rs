@@ -0,0 +1,76 @@
+use serde_json::Value;
+
+use ledger_core::StorageEngine;
+
+use crate::app::AppContext;
+use crate::cli::DraftEditArgs;
+use crate::helpers::{read_entry_body, FieldDef};
+use crate::output::Receipt;
+use crate::ui::{badge, print, short_id, Badge, OutputMode};
+
+use super::{apply_fields, find_draft};
+
+pub fn handle_edit(ctx: &AppContext, args: &DraftEditArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let draft = find_draft(&storage, &args.id)?;
+    let entry_type = storage
+        .list_entry_types()?
+        .into_iter()
+        .find(|entry_type| entry_type.id == draft.entry_type_id)
+        .ok_or_else(|| anyhow::anyhow!("Entry type of draft {} not found", draft.id))?;
+    let fields = FieldDef::from_schema(&entry_type.schema_json);
+
+    let mut data = draft.data.as_object().cloned().unwrap_or_default();
+    apply_fields(&mut data, &fields, &args.fields, &entry_type.name)?;
+    // With only --field or --tag given, the body is left as it is
+    let has_body = fields.iter().any(|field| field.name == "body");
+    let edit_body = args.body.is_some() || (args.fields.is_empty() && args.tag.is_empty());
+    if edit_body {
+        if !has_body {
+            return Err(anyhow::anyhow!(
+                "Entry type '{}' has no body; use --field",
+                entry_type.name
+            ));
+        }
+        let editor = match args.editor.as_deref() {
+            Some(editor) => Some(editor),
+            None => ctx.editor()?,
+        };
+        let current = data.get("body").and_then(|v| v.as_str());
+        let body = read_entry_body(args.no_input, args.body.clone(), editor.into(), current)?;
+        data.insert("body".to_string(), Value::String(body));
+    }
+    let tags = if args.tag.is_empty() {
+        draft.tags.clone()
+    } else {
+        args.tag.clone()
+    };
+    storage.update_draft(&draft.id, &Value::Object(data), &tags)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(args.json, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Updated draft {}", short_id(&draft.id)),
+                    ),
+                );
+            }
+            OutputMode::Json => {
+                Receipt::new("edit", "draft", draft.id, None)
+                    .created_at(draft.created_at)
+                    .print()?;
+            }
+            OutputMode::Plain => {
+                println!("status=ok");
+                println!("draft_id={}", draft.id);
+            }
+        }
+    }
+    Ok(())
+}
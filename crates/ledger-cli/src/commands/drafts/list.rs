@@ -0,0 +1,116 @@
+//! Draft listing, most recently saved first.
+
+use chrono::{DateTime, Utc};
+use ledger_core::storage::Draft;
+
+use crate::app::AppContext;
+use crate::cli::DraftListArgs;
+use crate::output::entry_type_name_map;
+use crate::ui::{
+    blank_line, header, hint, print, short_id, simple_table, truncate, Column, OutputMode,
+};
+
+const PREVIEW_MAX: usize = 50;
+
+/// First line of the draft's body, or of its first text value.
+fn preview(draft: &Draft) -> String {
+    let text = draft
+        .data
+        .get("body")
+        .and_then(|v| v.as_str())
+        .or_else(|| {
+            draft
+                .data
+                .as_object()
+                .and_then(|data| data.values().find_map(|v| v.as_str()))
+        })
+        .unwrap_or_default();
+    let line = text.lines().find(|line| !line.trim().is_empty());
+    truncate(line.unwrap_or_default(), PREVIEW_MAX)
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+pub fn handle_list(ctx: &AppContext, args: &DraftListArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let drafts = storage.list_drafts()?;
+    let type_names = entry_type_name_map(&storage)?;
+    let type_name = |draft: &Draft| {
+        type_names
+            .get(&draft.entry_type_id)
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output: Vec<_> = drafts
+                .iter()
+                .map(|draft| {
+                    serde_json::json!({
+                        "id": draft.id,
+                        "entry_type": type_name(draft),
+                        "data": draft.data,
+                        "tags": draft.tags,
+                        "created_at": draft.created_at,
+                        "updated_at": draft.updated_at,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "drafts", None));
+            blank_line(&ui_ctx);
+            if drafts.is_empty() {
+                print(
+                    &ui_ctx,
+                    &hint(&ui_ctx, "No drafts. Start one with `ledger draft save`."),
+                );
+                return Ok(());
+            }
+            let columns = [
+                Column::new("ID"),
+                Column::new("Type"),
+                Column::new("Saved"),
+                Column::new("Preview"),
+            ];
+            let rows: Vec<Vec<String>> = drafts
+                .iter()
+                .map(|draft| {
+                    vec![
+                        short_id(&draft.id),
+                        type_name(draft),
+                        format_time(&draft.updated_at),
+                        preview(draft),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            blank_line(&ui_ctx);
+            print(
+                &ui_ctx,
+                &hint(
+                    &ui_ctx,
+                    "Drafts stay out of list, search and export until `ledger draft publish <id>`.",
+                ),
+            );
+        }
+        OutputMode::Plain => {
+            println!("count={}", drafts.len());
+            for draft in &drafts {
+                println!(
+                    "draft={} type={} updated_at={} preview={}",
+                    draft.id,
+                    type_name(draft),
+                    draft.updated_at.to_rfc3339(),
+                    preview(draft)
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Drafts: entries saved before they are ready and published later.
+
+pub mod discard;
+pub mod edit;
+pub mod list;
+pub mod publish;
+pub mod save;
+
+pub use discard::handle_discard;
+pub use edit::handle_edit;
+pub use list::handle_list;
+pub use publish::handle_publish;
+pub use save::handle_save;
+
+use ledger_core::storage::{AgeSqliteStorage, Draft};
+use serde_json::{Map, Value};
+
+use crate::app::not_found_with_hint;
+use crate::helpers::{parse_cli_fields, FieldDef};
+
+/// The draft with this ID, or the only draft whose ID starts with it.
+fn find_draft(storage: &AgeSqliteStorage, id: &str) -> anyhow::Result<Draft> {
+    let id = id.trim().to_lowercase();
+    let mut matches: Vec<Draft> = storage
+        .list_drafts()?
+        .into_iter()
+        .filter(|draft| draft.id.to_string().starts_with(&id))
+        .collect();
+    match matches.len() {
+        0 => Err(not_found_with_hint(
+            "Draft not found",
+            "Hint: Run `ledger draft list` to find draft IDs.",
+        )),
+        1 => Ok(matches.remove(0)),
+        count => Err(anyhow::anyhow!(
+            "Draft ID '{}' is ambiguous ({} drafts match)",
+            id,
+            count
+        )),
+    }
+}
+
+/// Set `--field` values on draft data, checked against the type's fields.
+fn apply_fields(
+    data: &mut Map<String, Value>,
+    fields: &[FieldDef],
+    raw: &[String],
+    type_name: &str,
+) -> anyhow::Result<()> {
+    for (name, value) in parse_cli_fields(raw)? {
+        let field = fields
+            .iter()
+            .find(|field| field.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Entry type '{}' has no field '{}'", type_name, name))?;
+        data.insert(name, field.check_value(&value)?);
+    }
+    Ok(())
+}
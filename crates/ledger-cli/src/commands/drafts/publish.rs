@@ -0,0 +1,74 @@
+use chrono::Utc;
+use ledger_core::StorageEngine;
+
+use crate::app::AppContext;
+use crate::cli::DraftPublishArgs;
+use crate::output::{stored_entry_json, Receipt};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, blank_line, hint, print, short_id, Badge, OutputMode};
+
+use super::find_draft;
+
+pub fn handle_publish(ctx: &AppContext, args: &DraftPublishArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(false)?;
+    let draft = find_draft(&storage, &args.id)?;
+    let created_at = args.now.then(Utc::now);
+    let entry_id = storage.publish_draft(&draft.id, created_at)?;
+    let created = storage
+        .get_entry(&entry_id)?
+        .map(|entry| entry.created_at)
+        .unwrap_or_else(Utc::now);
+
+    // Under --dry-run, show the entry exactly as it would have been stored
+    let preview = if ctx.dry_run() {
+        Some(stored_entry_json(&storage, &entry_id)?)
+    } else {
+        None
+    };
+    ctx.save_storage(storage, &passphrase)?;
+    if let Some(preview) = preview {
+        println!("{}", serde_json::to_string_pretty(&preview)?);
+        return Ok(());
+    }
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(args.json, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Published draft {}", short_id(&draft.id)),
+                    ),
+                );
+                let context = format!(
+                    "ID: {}  {sep}  {}  {sep}  tags: {}",
+                    short_id(&entry_id),
+                    created.format("%Y-%m-%d %H:%M UTC"),
+                    draft.tags.len()
+                );
+                println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+                blank_line(&ui_ctx);
+                print(
+                    &ui_ctx,
+                    &hint(&ui_ctx, &format!("ledger show {}", short_id(&entry_id))),
+                );
+            }
+            OutputMode::Json => {
+                Receipt::new("publish", "entry", entry_id, None)
+                    .created_at(created)
+                    .affects("draft", draft.id, None)
+                    .print()?;
+            }
+            OutputMode::Plain => {
+                println!("status=ok");
+                println!("entry_id={}", entry_id);
+                println!("draft_id={}", draft.id);
+            }
+        }
+    }
+    Ok(())
+}
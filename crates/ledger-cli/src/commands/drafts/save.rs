@@ -0,0 +1,83 @@
+use ledger_core::storage::{NewEntry, StorageEngine};
+use serde_json::{Map, Value};
+
+use crate::app::AppContext;
+use crate::cli::DraftSaveArgs;
+use crate::helpers::{ensure_builtin_entry_type, read_entry_body, require_entry_type, FieldDef};
+use crate::output::Receipt;
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, print, short_id, Badge, OutputMode};
+
+use super::apply_fields;
+
+pub fn handle_save(ctx: &AppContext, args: &DraftSaveArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let device_id = storage.metadata()?.device_id;
+    ensure_builtin_entry_type(&mut storage, &args.entry_type, device_id)?;
+    let entry_type = require_entry_type(&storage, &args.entry_type)?;
+    let fields = FieldDef::from_schema(&entry_type.schema_json);
+
+    let mut data = Map::new();
+    apply_fields(&mut data, &fields, &args.fields, &entry_type.name)?;
+    // The body comes from --body, stdin or the editor unless fields say it all
+    let has_body = fields.iter().any(|field| field.name == "body");
+    if args.body.is_some() && !has_body {
+        return Err(anyhow::anyhow!(
+            "Entry type '{}' has no body; use --field",
+            entry_type.name
+        ));
+    }
+    if has_body && !data.contains_key("body") && (args.body.is_some() || data.is_empty()) {
+        let editor = match args.editor.as_deref() {
+            Some(editor) => Some(editor),
+            None => ctx.editor()?,
+        };
+        let body = read_entry_body(args.no_input, args.body.clone(), editor.into(), None)?;
+        data.insert("body".to_string(), Value::String(body));
+    }
+    if data.is_empty() {
+        return Err(anyhow::anyhow!("Nothing to save; use --body or --field"));
+    }
+
+    let draft = NewEntry::new(
+        entry_type.id,
+        entry_type.version,
+        Value::Object(data),
+        device_id,
+    )
+    .with_tags(args.tag.clone());
+    let draft_id = storage.insert_draft(&draft)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(args.json, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Saved {} draft", entry_type.name),
+                    ),
+                );
+                let context = format!(
+                    "ID: {}  {sep}  ledger draft edit {}  {sep}  ledger draft publish {}",
+                    short_id(&draft_id),
+                    short_id(&draft_id),
+                    short_id(&draft_id)
+                );
+                println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            }
+            OutputMode::Json => {
+                Receipt::new("save", "draft", draft_id, None).print()?;
+            }
+            OutputMode::Plain => {
+                println!("status=ok");
+                println!("draft_id={}", draft_id);
+            }
+        }
+    }
+    Ok(())
+}
@@ -2,18 +2,29 @@
 
 use std::io::IsTerminal;
 
+use chrono::{Datelike, Local, Utc};
 use uuid::Uuid;
 
-use ledger_core::storage::{NewEntry, StorageEngine};
+use ledger_core::storage::{
+    AgeSqliteStorage, IdempotencyRecord, NewAttachment, NewEntry, StorageEngine,
+};
 
-use crate::app::AppContext;
+use crate::app::{resolve_ledger_path, AppContext};
 use crate::cli::AddArgs;
+#[cfg(feature = "spellcheck")]
+use crate::helpers::{add_to_user_dictionary, replace_word, Dictionary};
 use crate::helpers::{
-    parse_cli_fields, parse_datetime, prompt_for_fields, require_entry_type, FieldDef,
+    ensure_builtin_entry_type, fetch_page_title, parse_cli_fields, parse_datetime,
+    prompt_for_fields, read_capture_file, read_photo_metadata, require_entry_type,
+    transcribe_audio, validate_url, DraftRecovery, EditorSession, FieldDef, RecoveredDraft,
     TemplateDefaults,
 };
-use crate::ui::theme::{styled, styles};
-use crate::ui::{badge, blank_line, hint, print, short_id, Badge, OutputMode, UiContext};
+use crate::output::{stored_entry_json, Receipt};
+use crate::ui::prompt::{confirm_review, prompt_multi_select, prompt_select};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, blank_line, hint, print, short_id, truncate, Badge, OutputMode, UiContext};
+
+const REVIEW_VALUE_MAX: usize = 60;
 
 /// Print a step indicator for the add wizard flow.
 fn print_step(ctx: &UiContext, step: usize, total: usize, title: &str) {
@@ -26,15 +37,338 @@ fn print_step(ctx: &UiContext, step: usize, total: usize, title: &str) {
     println!("{}  {}", progress_styled, title_styled);
 }
 
+/// Field values (in schema order) and tags for the review screen.
+fn review_items(
+    fields: &[FieldDef],
+    data: &serde_json::Map<String, serde_json::Value>,
+    tags: &[String],
+) -> Vec<(String, String)> {
+    let mut items: Vec<(String, String)> = fields
+        .iter()
+        .filter_map(|field| {
+            let value = match data.get(&field.name)? {
+                serde_json::Value::String(s) => {
+                    truncate(s.lines().next().unwrap_or_default(), REVIEW_VALUE_MAX)
+                }
+                serde_json::Value::Array(values) => values
+                    .iter()
+                    .map(|v| {
+                        v.as_str()
+                            .map(str::to_string)
+                            .unwrap_or_else(|| v.to_string())
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                other => other.to_string(),
+            };
+            Some((field.name.clone(), value))
+        })
+        .collect();
+    if !tags.is_empty() {
+        items.push(("tags".to_string(), tags.join(", ")));
+    }
+    items
+}
+
+/// Spell check the body (or photo caption), offering corrections on a TTY.
+///
+/// Never stops the add: a missing word list is a warning, and without a
+/// TTY typos are only listed.
+#[cfg(feature = "spellcheck")]
+fn lint_body(
+    ctx: &AppContext,
+    ui_ctx: &UiContext,
+    data: &mut serde_json::Map<String, serde_json::Value>,
+    interactive: bool,
+) -> anyhow::Result<()> {
+    let Some((key, text)) = ["body", "caption"].into_iter().find_map(|key| {
+        data.get(key)
+            .and_then(|value| value.as_str())
+            .map(|text| (key, text.to_string()))
+    }) else {
+        return Ok(());
+    };
+    let wordlist = ctx
+        .config()?
+        .and_then(|config| config.lint.wordlist.clone());
+    let dictionary = match Dictionary::load(wordlist.as_deref().map(std::path::Path::new)) {
+        Ok(Some(dictionary)) => dictionary,
+        Ok(None) => {
+            return ctx.warn("No word list found; set [lint] wordlist to spell check");
+        }
+        Err(err) => {
+            return ctx.warn(&format!("Spell check skipped: {}", err));
+        }
+    };
+
+    let found = dictionary.misspellings(&text);
+    if !interactive {
+        for typo in &found {
+            match typo.suggestions.as_slice() {
+                [] => ctx.warn(&format!("Possible typo: {}", typo.word))?,
+                suggestions => ctx.warn(&format!(
+                    "Possible typo: {} (did you mean {}?)",
+                    typo.word,
+                    suggestions.join(", ")
+                ))?,
+            }
+        }
+        return Ok(());
+    }
+
+    let mut corrected = text;
+    for typo in &found {
+        let mut options: Vec<String> = typo
+            .suggestions
+            .iter()
+            .map(|suggestion| format!("Replace with \"{}\"", suggestion))
+            .collect();
+        options.push("Keep".to_string());
+        options.push("Add to dictionary".to_string());
+        let options: Vec<&str> = options.iter().map(String::as_str).collect();
+        let choice = prompt_select(
+            ui_ctx,
+            &format!("Possible typo: {}", typo.word),
+            &options,
+            0,
+        )?;
+        if let Some(suggestion) = typo.suggestions.get(choice) {
+            corrected = replace_word(&corrected, &typo.word, suggestion);
+        } else if choice == typo.suggestions.len() + 1 {
+            add_to_user_dictionary(&typo.word)?;
+        }
+    }
+    data.insert(key.to_string(), serde_json::Value::String(corrected));
+    Ok(())
+}
+
+#[cfg(not(feature = "spellcheck"))]
+fn lint_body(
+    ctx: &AppContext,
+    _ui_ctx: &UiContext,
+    _data: &mut serde_json::Map<String, serde_json::Value>,
+    _interactive: bool,
+) -> anyhow::Result<()> {
+    ctx.warn("This build has no spell checker; --lint was ignored")
+}
+
+/// Offer tags from similar past entries that aren't chosen already.
+///
+/// On a TTY the suggestions start checked in a checklist. Otherwise they
+/// are listed on stderr when `--suggest-tags` was passed, and never added,
+/// so scripted adds don't gain tags silently.
+fn suggest_tags(
+    ctx: &AppContext,
+    ui_ctx: &UiContext,
+    storage: &AgeSqliteStorage,
+    data: &serde_json::Map<String, serde_json::Value>,
+    tags: &mut Vec<String>,
+    interactive: bool,
+    requested: bool,
+) -> anyhow::Result<()> {
+    let Some(text) = ["body", "caption"]
+        .into_iter()
+        .find_map(|key| data.get(key).and_then(|value| value.as_str()))
+    else {
+        return Ok(());
+    };
+    let limit = ctx.tag_suggest_limit()?;
+    let suggestions: Vec<String> = storage
+        .suggest_tags(text, limit + tags.len())?
+        .into_iter()
+        .filter(|tag| !tags.iter().any(|t| t.trim().eq_ignore_ascii_case(tag)))
+        .take(limit)
+        .collect();
+    if suggestions.is_empty() {
+        return Ok(());
+    }
+
+    if !interactive {
+        if requested && !ctx.quiet() {
+            match ui_ctx.mode {
+                OutputMode::Pretty => eprintln!(
+                    "{}",
+                    hint(
+                        ui_ctx,
+                        &format!(
+                            "Suggested tags: {} (add with --tag)",
+                            suggestions.join(", ")
+                        )
+                    )
+                ),
+                OutputMode::Plain | OutputMode::Json => {
+                    eprintln!("suggested_tags={}", suggestions.join(","))
+                }
+            }
+        }
+        return Ok(());
+    }
+    let options: Vec<&str> = suggestions.iter().map(String::as_str).collect();
+    let chosen = prompt_multi_select(
+        ui_ctx,
+        "Suggested tags",
+        &options,
+        &vec![true; options.len()],
+    )?;
+    tags.extend(chosen.into_iter().map(|index| suggestions[index].clone()));
+    Ok(())
+}
+
+/// Keep expense fields consistent so reports group them together.
+///
+/// Currency codes are upper-cased and must be three letters; categories are
+/// trimmed and lower-cased.
+fn normalize_expense(data: &mut serde_json::Map<String, serde_json::Value>) -> anyhow::Result<()> {
+    if let Some(currency) = data.get("currency").and_then(|v| v.as_str()) {
+        let code = currency.trim().to_ascii_uppercase();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(anyhow::anyhow!(
+                "Invalid currency: {} (use a three-letter code like USD)",
+                currency
+            ));
+        }
+        data.insert("currency".to_string(), serde_json::Value::String(code));
+    }
+    if let Some(category) = data.get("category").and_then(|v| v.as_str()) {
+        let category = category.trim().to_lowercase();
+        data.insert("category".to_string(), serde_json::Value::String(category));
+    }
+    Ok(())
+}
+
+/// Rescue and offer the editor text of crashed `add` sessions for this
+/// ledger.
+///
+/// Returns the draft resumed into the body, to be discarded once the entry
+/// is saved.
+fn recover_drafts(
+    ctx: &AppContext,
+    ui_ctx: &UiContext,
+    recovery: &DraftRecovery,
+    template_defaults: &mut TemplateDefaults,
+    offer: bool,
+) -> anyhow::Result<Option<RecoveredDraft>> {
+    recovery.rescue_stale_session()?;
+    if let Some(pid) = recovery.active_session() {
+        ctx.warn(&format!(
+            "Another `ledger add` is editing an entry for this ledger (pid {})",
+            pid
+        ))?;
+    }
+    let drafts = recovery.drafts()?;
+    if drafts.is_empty() {
+        return Ok(None);
+    }
+    if !offer {
+        if !ctx.quiet() {
+            eprintln!(
+                "{} unsaved draft(s) from a crashed editor session; run `ledger add` in a terminal to resume or discard",
+                drafts.len()
+            );
+        }
+        return Ok(None);
+    }
+    for draft in drafts {
+        let text = match recovery.read(&draft) {
+            Ok(text) => text,
+            Err(err) => {
+                ctx.warn(&format!("Could not open a saved draft: {}", err))?;
+                continue;
+            }
+        };
+        let preview = text
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or_default();
+        let saved_at = draft
+            .saved_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M");
+        print(
+            ui_ctx,
+            &badge(
+                ui_ctx,
+                Badge::Warn,
+                &format!("Unsaved draft from {}", saved_at),
+            ),
+        );
+        print(ui_ctx, &hint(ui_ctx, &truncate(preview, REVIEW_VALUE_MAX)));
+        let choice = prompt_select(
+            ui_ctx,
+            "Resume this draft?",
+            &["Resume", "Discard", "Keep for later"],
+            0,
+        )?;
+        match choice {
+            0 => {
+                template_defaults.defaults.insert(
+                    "body".to_string(),
+                    serde_json::Value::String(text.to_string()),
+                );
+                return Ok(Some(draft));
+            }
+            1 => recovery.discard(&draft)?,
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
 pub fn handle_add(ctx: &AppContext, args: &AddArgs) -> anyhow::Result<()> {
+    if (args.url.is_some() || args.fetch_title) && args.entry_type != "bookmark" {
+        return Err(anyhow::anyhow!(
+            "--url and --fetch-title can only be used with `ledger add bookmark`"
+        ));
+    }
+
+    if args.from_audio.is_some() && args.entry_type != "journal" {
+        return Err(anyhow::anyhow!(
+            "--from-audio can only be used with `ledger add journal`"
+        ));
+    }
+
+    if args.file.is_some() && args.entry_type != "photo" {
+        return Err(anyhow::anyhow!(
+            "--file can only be used with `ledger add photo`"
+        ));
+    }
+    if args.entry_type == "photo" && args.file.is_none() {
+        return Err(anyhow::anyhow!(
+            "Photo entries need an image: `ledger add photo --file <FILE>`"
+        ));
+    }
+
+    // Read captured files up front so a bad path fails before unlocking
+    let audio = args
+        .from_audio
+        .as_ref()
+        .map(|path| read_capture_file(std::path::Path::new(path)))
+        .transpose()?;
+    let photo = args
+        .file
+        .as_ref()
+        .map(|path| read_capture_file(std::path::Path::new(path)))
+        .transpose()?;
+    let photo_metadata = photo.as_ref().map(|file| read_photo_metadata(&file.data));
+
     let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
-    let entry_type_record = require_entry_type(&storage, &args.entry_type)?;
+    if let Some(ref key) = args.idempotency_key {
+        if let Some(record) = storage.idempotency_record(key, "add")? {
+            return print_replayed(ctx, args, &storage, &record);
+        }
+    }
     let metadata = storage.metadata()?;
+    ensure_builtin_entry_type(&mut storage, &args.entry_type, metadata.device_id)?;
+    let entry_type_record = require_entry_type(&storage, &args.entry_type)?;
 
     // Create UI context for step indicators
-    let ui_ctx = ctx.ui_context(false, None);
+    let ui_ctx = ctx.ui_context(args.json, None);
     let interactive = std::io::stdin().is_terminal() && !args.no_input;
-    let needs_prompting = args.body.is_none() && args.fields.is_empty();
+    let needs_prompting = args.body.is_none()
+        && args.fields.is_empty()
+        && args.url.is_none()
+        && audio.is_none()
+        && photo.is_none();
 
     // Get template (explicit or default)
     let template = if let Some(ref template_name) = args.template {
@@ -61,41 +395,180 @@ pub fn handle_add(ctx: &AppContext, args: &AddArgs) -> anyhow::Result<()> {
         storage.get_default_template(&entry_type_record.id)?
     };
 
-    // Parse template defaults
-    let template_defaults = template
+    // Custom date (photos default to their EXIF capture time)
+    let created_at = match args.date {
+        Some(ref value) => Some(parse_datetime(value)?),
+        None => photo_metadata.as_ref().and_then(|meta| meta.taken_at),
+    };
+
+    // Parse template defaults; weekday sections follow the entry's local day
+    let local_day = created_at
+        .unwrap_or_else(Utc::now)
+        .with_timezone(&Local)
+        .date_naive();
+    let mut template_defaults = template
         .as_ref()
-        .map(|t| TemplateDefaults::from_template_json(&t.template_json))
+        .map(|t| TemplateDefaults::for_weekday(&t.template_json, local_day.weekday()))
         .unwrap_or_default();
 
+    // Journal prompt of the day, shown only when the editor opens
+    if args.entry_type == "journal" {
+        if let Some(prompt) = ctx.prompt_of_the_day(local_day)? {
+            template_defaults
+                .placeholders
+                .insert("body".to_string(), prompt);
+        }
+    }
+
     // Parse field definitions from entry type schema
     let fields = FieldDef::from_schema(&entry_type_record.schema_json);
 
+    // Editor text kept from a crashed session; nothing is kept under --dry-run
+    let recovery = if ctx.dry_run() {
+        None
+    } else {
+        Some(DraftRecovery::new(
+            &resolve_ledger_path(ctx.cli())?,
+            &passphrase,
+        )?)
+    };
+    let resumed = match recovery {
+        Some(ref recovery) => recover_drafts(
+            ctx,
+            &ui_ctx,
+            recovery,
+            &mut template_defaults,
+            interactive && needs_prompting && fields.iter().any(|f| f.name == "body"),
+        )?,
+        None => None,
+    };
+
     // Parse CLI-provided field values
     let mut cli_values = parse_cli_fields(&args.fields)?;
 
-    // Handle legacy --body flag as a field value
+    // Handle legacy --body flag as a field value (photos use it as the caption)
     if let Some(ref body) = args.body {
-        cli_values.insert("body".to_string(), body.clone());
+        let key = if photo.is_some() { "caption" } else { "body" };
+        cli_values.insert(key.to_string(), body.clone());
+    }
+
+    // Photo: file name plus EXIF time and location as structured fields
+    if let (Some(file), Some(meta)) = (&photo, &photo_metadata) {
+        cli_values.insert("file".to_string(), file.name.clone());
+        if let Some(taken_at) = meta.taken_at {
+            cli_values
+                .entry("taken_at".to_string())
+                .or_insert_with(|| taken_at.to_rfc3339());
+        }
+        if let (Some(lat), Some(lon)) = (meta.latitude, meta.longitude) {
+            cli_values
+                .entry("latitude".to_string())
+                .or_insert_with(|| lat.to_string());
+            cli_values
+                .entry("longitude".to_string())
+                .or_insert_with(|| lon.to_string());
+        }
+    }
+
+    // Audio memo: transcript becomes the body, falling back to attachment-only
+    let mut transcribed = false;
+    if let (Some(file), Some(path)) = (&audio, &args.from_audio) {
+        if !cli_values.contains_key("body") {
+            let transcript = match ctx.transcriber()? {
+                Some(command) => match transcribe_audio(command, std::path::Path::new(path)) {
+                    Ok(transcript) => transcript,
+                    Err(err) => {
+                        ctx.warn(&err.to_string())?;
+                        None
+                    }
+                },
+                None => None,
+            };
+            transcribed = transcript.is_some();
+            let body = transcript.unwrap_or_else(|| format!("Voice memo: {}", file.name));
+            cli_values.insert("body".to_string(), body);
+        }
+    }
+
+    // Bookmark URL shorthand, with optional title fetch
+    if let Some(ref url) = args.url {
+        cli_values.insert("url".to_string(), url.clone());
+    }
+    if let Some(url) = cli_values.get("url").cloned() {
+        validate_url(&url)?;
+        if args.fetch_title && !cli_values.contains_key("title") {
+            match fetch_page_title(&url) {
+                Ok(Some(title)) => {
+                    cli_values.insert("title".to_string(), title);
+                }
+                Ok(None) => ctx.warn(&format!("No page title found for {}", url))?,
+                Err(err) => ctx.warn(&format!("Could not fetch page title: {}", err))?,
+            }
+        }
     }
 
     // Get editor override
-    let editor_override = ctx.editor()?;
+    let editor_override = match args.editor.as_deref() {
+        Some(editor) => Some(editor),
+        None => ctx.editor()?,
+    };
 
     // Print wizard header if interactive
     if interactive && needs_prompting && ui_ctx.mode.is_pretty() {
+        let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
         let header = styled("Ledger", styles::bold(), ui_ctx.color);
-        println!("{} \u{00B7} add ({})\n", header, args.entry_type);
-        print_step(&ui_ctx, 1, 2, "Enter fields");
+        println!("{} {sep} add ({})\n", header, args.entry_type);
+        print_step(&ui_ctx, 1, 3, "Enter fields");
     }
 
     // Prompt for fields based on schema and template defaults
-    let data = prompt_for_fields(
+    let mut data = prompt_for_fields(
+        &ui_ctx,
         &fields,
         &template_defaults,
         &cli_values,
         args.no_input,
-        editor_override,
+        EditorSession {
+            command: editor_override,
+            recovery: recovery.as_ref(),
+        },
     )?;
+    if args.entry_type == "expense" {
+        normalize_expense(&mut data)?;
+    }
+    if args.lint {
+        lint_body(ctx, &ui_ctx, &mut data, interactive)?;
+    }
+
+    // Handle tags: CLI tags override template defaults
+    let mut tags = if !args.tag.is_empty() {
+        args.tag.clone()
+    } else {
+        template_defaults.default_tags.clone()
+    };
+    if args.suggest_tags || ctx.auto_suggest_tags()? {
+        suggest_tags(
+            ctx,
+            &ui_ctx,
+            &storage,
+            &data,
+            &mut tags,
+            interactive,
+            args.suggest_tags,
+        )?;
+    }
+
+    // Final review before anything is written
+    if interactive && needs_prompting {
+        if ui_ctx.mode.is_pretty() {
+            println!();
+        }
+        print_step(&ui_ctx, 2, 3, "Review");
+        let review = review_items(&fields, &data, &tags);
+        if !confirm_review(&ui_ctx, &review, "Save entry?")? {
+            return Err(anyhow::anyhow!("Entry creation cancelled"));
+        }
+    }
 
     // Build entry
     let mut new_entry = NewEntry::new(
@@ -104,28 +577,36 @@ pub fn handle_add(ctx: &AppContext, args: &AddArgs) -> anyhow::Result<()> {
         serde_json::Value::Object(data),
         metadata.device_id,
     );
-
-    // Handle tags: CLI tags override template defaults
-    let tags = if !args.tag.is_empty() {
-        args.tag.clone()
-    } else {
-        template_defaults.default_tags.clone()
-    };
     new_entry = new_entry.with_tags(tags);
 
-    // Handle custom date
-    if let Some(ref value) = args.date {
-        let parsed = parse_datetime(value)?;
-        new_entry = new_entry.with_created_at(parsed);
+    if let Some(created_at) = created_at {
+        new_entry = new_entry.with_created_at(created_at);
     }
 
     // Insert entry
     let entry_id = storage.insert_entry(&new_entry)?;
+    if let Some(ref template) = template {
+        storage.record_template_use(&entry_id, &template.id, template.version)?;
+    }
+
+    let mut attachments = Vec::new();
+    for file in audio.into_iter().chain(photo) {
+        let name = file.name.clone();
+        let attachment_id = storage.add_attachment(&NewAttachment::new(
+            entry_id,
+            file.name,
+            file.mime_type,
+            file.data,
+            metadata.device_id,
+        ))?;
+        attachments.push((attachment_id, name));
+    }
+    let attachment_count = attachments.len();
 
     // Handle composition attachments
+    let mut compositions = Vec::new();
     if !args.no_compose {
         // Collect compositions to attach to
-        let mut composition_ids = Vec::new();
 
         // Add CLI-specified compositions
         for comp_name in &args.compose {
@@ -136,7 +617,7 @@ pub fn handle_add(ctx: &AppContext, args: &AddArgs) -> anyhow::Result<()> {
             };
 
             if let Some(c) = comp {
-                composition_ids.push(c.id);
+                compositions.push(c);
             } else {
                 return Err(anyhow::anyhow!("Composition '{}' not found", comp_name));
             }
@@ -147,35 +628,54 @@ pub fn handle_add(ctx: &AppContext, args: &AddArgs) -> anyhow::Result<()> {
             for comp_id_str in &template_defaults.default_compositions {
                 if let Ok(uuid) = Uuid::parse_str(comp_id_str) {
                     // Verify composition exists
-                    if storage.get_composition_by_id(&uuid)?.is_some() {
-                        composition_ids.push(uuid);
+                    if let Some(c) = storage.get_composition_by_id(&uuid)? {
+                        compositions.push(c);
                     }
                 }
             }
         }
 
         // Attach entry to compositions
-        for comp_id in &composition_ids {
-            storage.attach_entry_to_composition(&entry_id, comp_id)?;
+        for comp in &compositions {
+            storage.attach_entry_to_composition(&entry_id, &comp.id)?;
         }
     }
 
-    storage.close(&passphrase)?;
+    if let Some(ref key) = args.idempotency_key {
+        storage.record_idempotency_key(key, "add", Some(&entry_id), None)?;
+    }
+
+    let created = storage
+        .get_entry(&entry_id)?
+        .map(|entry| entry.created_at)
+        .unwrap_or_else(chrono::Utc::now);
+
+    // Under --dry-run, show the entry exactly as it would have been stored
+    let preview = if ctx.dry_run() {
+        Some(stored_entry_json(&storage, &entry_id)?)
+    } else {
+        None
+    };
+    ctx.save_storage(storage, &passphrase)?;
+    if let (Some(recovery), Some(draft)) = (&recovery, &resumed) {
+        recovery.discard(draft)?;
+    }
+    if let Some(preview) = preview {
+        println!("{}", serde_json::to_string_pretty(&preview)?);
+        return Ok(());
+    }
 
     if !ctx.quiet() {
         // Get created timestamp for receipt
-        let created_at = new_entry
-            .created_at
-            .unwrap_or_else(chrono::Utc::now)
-            .format("%Y-%m-%d %H:%M UTC")
-            .to_string();
+        let created_at = created.format("%Y-%m-%d %H:%M UTC").to_string();
         let tag_count = new_entry.tags.len();
 
         match ui_ctx.mode {
             OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
                 if interactive && needs_prompting {
                     println!();
-                    print_step(&ui_ctx, 2, 2, "Creating entry");
+                    print_step(&ui_ctx, 3, 3, "Creating entry");
                 }
                 blank_line(&ui_ctx);
                 print(
@@ -187,12 +687,19 @@ pub fn handle_add(ctx: &AppContext, args: &AddArgs) -> anyhow::Result<()> {
                     ),
                 );
                 // Context line with ID, timestamp, and tag count
-                let context = format!(
-                    "ID: {}  \u{00B7}  {}  \u{00B7}  tags: {}",
+                let mut context = format!(
+                    "ID: {}  {sep}  {}  {sep}  tags: {}",
                     short_id(&entry_id),
                     created_at,
                     tag_count
                 );
+                if attachment_count > 0 {
+                    context.push_str(&format!(
+                        "  {sep}  attachments: {}{}",
+                        attachment_count,
+                        if transcribed { " (transcribed)" } else { "" }
+                    ));
+                }
                 let context_styled = styled(&context, styles::dim(), ui_ctx.color);
                 println!("{}", context_styled);
                 // Next step hints
@@ -202,21 +709,88 @@ pub fn handle_add(ctx: &AppContext, args: &AddArgs) -> anyhow::Result<()> {
                     &hint(
                         &ui_ctx,
                         &format!(
-                            "ledger show {}  \u{00B7}  ledger list  \u{00B7}  ledger edit {}",
+                            "ledger show {}  {sep}  ledger list  {sep}  ledger edit {}",
                             short_id(&entry_id),
                             short_id(&entry_id)
                         ),
                     ),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                let mut receipt = Receipt::new("add", "entry", entry_id, None).created_at(created);
+                for (attachment_id, name) in &attachments {
+                    receipt = receipt.affects("attachment", *attachment_id, Some(name));
+                }
+                for comp in &compositions {
+                    receipt = receipt.affects("composition", comp.id, Some(&comp.name));
+                }
+                receipt.print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("entry_id={}", entry_id);
                 println!("entry_type={}", args.entry_type);
                 println!("created_at={}", created_at);
                 println!("tag_count={}", tag_count);
+                if attachment_count > 0 {
+                    println!("attachment_count={}", attachment_count);
+                }
+                if args.from_audio.is_some() {
+                    println!("transcribed={}", transcribed);
+                }
             }
         }
     }
     Ok(())
 }
+
+/// Report the entry an earlier add with the same idempotency key created.
+fn print_replayed(
+    ctx: &AppContext,
+    args: &AddArgs,
+    storage: &AgeSqliteStorage,
+    record: &IdempotencyRecord,
+) -> anyhow::Result<()> {
+    let entry_id = record
+        .entry_id
+        .ok_or_else(|| anyhow::anyhow!("Idempotency key '{}' has no entry recorded", record.key))?;
+    if ctx.quiet() {
+        return Ok(());
+    }
+    let created = storage
+        .get_entry(&entry_id)?
+        .map(|entry| entry.created_at)
+        .unwrap_or(record.created_at);
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            print(
+                &ui_ctx,
+                &badge(
+                    &ui_ctx,
+                    Badge::Info,
+                    &format!("Already added (idempotency key {})", record.key),
+                ),
+            );
+            let context = format!(
+                "ID: {}  {sep}  {}",
+                short_id(&entry_id),
+                created.format("%Y-%m-%d %H:%M UTC")
+            );
+            println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+        }
+        OutputMode::Json => {
+            Receipt::new("add", "entry", entry_id, None)
+                .created_at(created)
+                .replayed()
+                .print()?;
+        }
+        OutputMode::Plain => {
+            println!("status=ok");
+            println!("entry_id={}", entry_id);
+            println!("replayed=true");
+        }
+    }
+    Ok(())
+}
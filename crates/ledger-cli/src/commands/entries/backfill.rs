@@ -0,0 +1,319 @@
+//! Backfill command handler: one entry for each day missing from a range.
+
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone, Utc};
+use uuid::Uuid;
+
+use ledger_core::storage::{AgeSqliteStorage, EntryFilter, NewEntry, StorageEngine, Template};
+
+use crate::app::AppContext;
+use crate::cli::BackfillArgs;
+use crate::collectors::local_day_bounds;
+use crate::helpers::{
+    ensure_builtin_entry_type, parse_date_range, prompt_for_fields, require_entry_type, FieldDef,
+    TemplateDefaults,
+};
+use crate::ui::prompt::prompt_select;
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, blank_line, hint, print, short_id, Badge, OutputMode};
+
+/// Longest range filled in one run, so a typo in a year can't queue
+/// thousands of editor sessions.
+const MAX_DAYS: i64 = 366;
+
+/// What happened to one day of the range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DayOutcome {
+    Added(Uuid),
+    Skipped,
+    Present,
+}
+
+impl DayOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            DayOutcome::Added(_) => "added",
+            DayOutcome::Skipped => "skipped",
+            DayOutcome::Present => "present",
+        }
+    }
+}
+
+pub fn handle_backfill(ctx: &AppContext, args: &BackfillArgs) -> anyhow::Result<()> {
+    let (start, end) = parse_date_range(&args.date)?;
+    let day_count = (end - start).num_days() + 1;
+    if day_count > MAX_DAYS {
+        return Err(anyhow::anyhow!(
+            "Date range covers {} days; backfill at most {} at a time",
+            day_count,
+            MAX_DAYS
+        ));
+    }
+
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let device_id = storage.metadata()?.device_id;
+
+    let template = args
+        .template
+        .as_deref()
+        .map(|name| find_template(&storage, name))
+        .transpose()?;
+    let type_name = match (&args.entry_type, &template) {
+        (Some(name), _) => name.clone(),
+        (None, Some(template)) => storage
+            .list_entry_types()?
+            .into_iter()
+            .find(|entry_type| entry_type.id == template.entry_type_id)
+            .map(|entry_type| entry_type.name)
+            .ok_or_else(|| anyhow::anyhow!("Template '{}' has no entry type", template.name))?,
+        (None, None) => "journal".to_string(),
+    };
+    ensure_builtin_entry_type(&mut storage, &type_name, device_id)?;
+    let entry_type = require_entry_type(&storage, &type_name)?;
+    let template = match template {
+        Some(template) if template.entry_type_id != entry_type.id => {
+            return Err(anyhow::anyhow!(
+                "Template '{}' is for a different entry type",
+                template.name
+            ));
+        }
+        Some(template) => Some(template),
+        None => storage.get_default_template(&entry_type.id)?,
+    };
+    let template_defaults = template
+        .as_ref()
+        .map(|t| TemplateDefaults::from_template_json(&t.template_json))
+        .unwrap_or_default();
+    let fields = FieldDef::from_schema(&entry_type.schema_json);
+    let tags = if args.tag.is_empty() {
+        template_defaults.default_tags.clone()
+    } else {
+        args.tag.clone()
+    };
+
+    let editor_override = ctx.editor()?;
+    let ui_ctx = ctx.ui_context(args.json, None);
+    let interactive = std::io::stdin().is_terminal() && !args.no_input;
+    let mut missing = 0;
+    for day in start.iter_days().take(day_count as usize) {
+        if !day_has_entry(&storage, entry_type.id, day)? {
+            missing += 1;
+        }
+    }
+
+    // Days written before an error or "Stop here" are still saved
+    let mut results: Vec<(NaiveDate, DayOutcome)> = Vec::new();
+    let mut prompted = 0;
+    let mut fill = || -> anyhow::Result<()> {
+        for day in start.iter_days().take(day_count as usize) {
+            if day_has_entry(&storage, entry_type.id, day)? {
+                results.push((day, DayOutcome::Present));
+                continue;
+            }
+            prompted += 1;
+            if interactive {
+                if ui_ctx.mode.is_pretty() {
+                    let progress = format!("{}/{}", prompted, missing);
+                    println!(
+                        "\n{}  {}",
+                        styled(&progress, styles::dim(), ui_ctx.color),
+                        styled(
+                            &day.format("%a %Y-%m-%d").to_string(),
+                            styles::bold(),
+                            ui_ctx.color
+                        )
+                    );
+                }
+                let choice = prompt_select(
+                    &ui_ctx,
+                    &format!("No {} entry for {}", type_name, day),
+                    &["Write it", "Skip this day", "Stop here"],
+                    0,
+                )?;
+                match choice {
+                    1 => {
+                        results.push((day, DayOutcome::Skipped));
+                        continue;
+                    }
+                    2 => break,
+                    _ => {}
+                }
+            }
+
+            let day_defaults = template
+                .as_ref()
+                .map(|t| TemplateDefaults::for_weekday(&t.template_json, day.weekday()))
+                .unwrap_or_default();
+            let data = prompt_for_fields(
+                &ui_ctx,
+                &fields,
+                &day_defaults,
+                &HashMap::new(),
+                args.no_input,
+                editor_override.into(),
+            )
+            .map_err(|e| anyhow::anyhow!("{} ({})", e, day))?;
+            let new_entry = NewEntry::new(
+                entry_type.id,
+                entry_type.version,
+                serde_json::Value::Object(data),
+                device_id,
+            )
+            .with_tags(tags.clone())
+            .with_created_at(day_time(day)?);
+            let entry_id = storage.insert_entry(&new_entry)?;
+            if let Some(ref template) = template {
+                storage.record_template_use(&entry_id, &template.id, template.version)?;
+                for composition_id in &template_defaults.default_compositions {
+                    let Ok(composition_id) = Uuid::parse_str(composition_id) else {
+                        continue;
+                    };
+                    if storage.get_composition_by_id(&composition_id)?.is_some() {
+                        storage.attach_entry_to_composition(&entry_id, &composition_id)?;
+                    }
+                }
+            }
+            results.push((day, DayOutcome::Added(entry_id)));
+        }
+        Ok(())
+    };
+    let outcome = fill();
+
+    let added: Vec<(NaiveDate, Uuid)> = results
+        .iter()
+        .filter_map(|(day, outcome)| match outcome {
+            DayOutcome::Added(id) => Some((*day, *id)),
+            _ => None,
+        })
+        .collect();
+    if !added.is_empty() {
+        ctx.save_storage(storage, &passphrase)?;
+    }
+    outcome?;
+
+    if ctx.quiet() {
+        return Ok(());
+    }
+    let count = |kind: &str| {
+        results
+            .iter()
+            .filter(|(_, outcome)| outcome.as_str() == kind)
+            .count()
+    };
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "start": start.format("%Y-%m-%d").to_string(),
+                "end": end.format("%Y-%m-%d").to_string(),
+                "entry_type": type_name,
+                "template": template.as_ref().map(|t| t.name.clone()),
+                "dry_run": ctx.dry_run(),
+                "days": results.iter().map(|(day, outcome)| {
+                    let id = match outcome {
+                        DayOutcome::Added(id) => Some(id.to_string()),
+                        _ => None,
+                    };
+                    serde_json::json!({
+                        "date": day.format("%Y-%m-%d").to_string(),
+                        "status": outcome.as_str(),
+                        "id": id,
+                    })
+                }).collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Plain => {
+            println!("status=ok");
+            println!("added={}", added.len());
+            println!("skipped={}", count("skipped"));
+            println!("present={}", count("present"));
+            for (day, id) in &added {
+                println!("day={} entry_id={}", day.format("%Y-%m-%d"), id);
+            }
+        }
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            blank_line(&ui_ctx);
+            let message = if missing == 0 {
+                format!("Nothing to backfill: every day has a {} entry", type_name)
+            } else {
+                format!(
+                    "Backfilled {} {} {}",
+                    added.len(),
+                    type_name,
+                    if added.len() == 1 { "entry" } else { "entries" }
+                )
+            };
+            print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, &message));
+            let context = format!(
+                "{}..{}  {sep}  skipped: {}  {sep}  already written: {}",
+                start,
+                end,
+                count("skipped"),
+                count("present")
+            );
+            println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            for (day, id) in &added {
+                println!(
+                    "{}  {}",
+                    day.format("%a %Y-%m-%d"),
+                    styled(
+                        &format!("ID: {}", short_id(id)),
+                        styles::dim(),
+                        ui_ctx.color
+                    )
+                );
+            }
+            if let (Some((since, _)), false) = (local_day_bounds(start), added.is_empty()) {
+                blank_line(&ui_ctx);
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        &format!(
+                            "ledger list --since {}",
+                            since.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+                        ),
+                    ),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Look a template up by name, then by ID.
+fn find_template(storage: &AgeSqliteStorage, name: &str) -> anyhow::Result<Template> {
+    let template = match Uuid::parse_str(name) {
+        Ok(uuid) => storage.get_template_by_id(&uuid)?,
+        Err(_) => storage.get_template(name)?,
+    };
+    template.ok_or_else(|| anyhow::anyhow!("Template '{}' not found", name))
+}
+
+/// Whether a current entry of the type was written on the local day.
+fn day_has_entry(
+    storage: &AgeSqliteStorage,
+    entry_type_id: Uuid,
+    day: NaiveDate,
+) -> anyhow::Result<bool> {
+    let (start, end) =
+        local_day_bounds(day).ok_or_else(|| anyhow::anyhow!("Invalid local date: {}", day))?;
+    let filter = EntryFilter::new()
+        .entry_type(entry_type_id)
+        .since(start)
+        .until(end - chrono::Duration::seconds(1))
+        .latest_only()
+        .limit(1);
+    Ok(!storage.list_entries(&filter)?.is_empty())
+}
+
+/// Timestamp for a backfilled entry: local noon on the day.
+fn day_time(day: NaiveDate) -> anyhow::Result<DateTime<Utc>> {
+    day.and_hms_opt(12, 0, 0)
+        .and_then(|noon| Local.from_local_datetime(&noon).earliest())
+        .map(|noon| noon.with_timezone(&Utc))
+        .ok_or_else(|| anyhow::anyhow!("Invalid local date: {}", day))
+}
@@ -1,25 +1,26 @@
 use chrono::Utc;
-use uuid::Uuid;
 
 use ledger_core::storage::{NewEntry, StorageEngine};
 
-use crate::app::{exit_not_found_with_hint, AppContext};
+use crate::app::{not_found_with_hint, AppContext};
 use crate::cli::EditArgs;
-use crate::helpers::{ensure_journal_type_name, read_entry_body};
-use crate::output::entry_type_name_map;
-use crate::ui::theme::{styled, styles};
+use crate::helpers::{ensure_journal_type_name, read_entry_body, resolve_entry_id};
+use crate::output::{entry_type_name_map, stored_entry_json, Receipt};
+use crate::ui::theme::{styled, styles, symbols};
 use crate::ui::{badge, blank_line, hint, print, short_id, Badge, OutputMode};
 
 pub fn handle_edit(ctx: &AppContext, args: &EditArgs) -> anyhow::Result<()> {
     let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
-    let parsed =
-        Uuid::parse_str(&args.id).map_err(|e| anyhow::anyhow!("Invalid entry ID: {}", e))?;
-    let entry = storage.get_entry(&parsed)?.unwrap_or_else(|| {
-        exit_not_found_with_hint(
+    let entry = match resolve_entry_id(&storage, &args.id)? {
+        Some(id) => storage.get_entry(&id)?,
+        None => None,
+    };
+    let entry = entry.ok_or_else(|| {
+        not_found_with_hint(
             "Entry not found",
             "Hint: Run `ledger list --last 7d` to find entry IDs.",
         )
-    });
+    })?;
 
     let entry_type_name = entry_type_name_map(&storage)?
         .get(&entry.entry_type_id)
@@ -32,11 +33,14 @@ pub fn handle_edit(ctx: &AppContext, args: &EditArgs) -> anyhow::Result<()> {
         .get("body")
         .and_then(|v| v.as_str())
         .unwrap_or("");
-    let editor_override = ctx.editor()?;
+    let editor_override = match args.editor.as_deref() {
+        Some(editor) => Some(editor),
+        None => ctx.editor()?,
+    };
     let body = read_entry_body(
         args.no_input,
         args.body.clone(),
-        editor_override,
+        editor_override.into(),
         Some(existing_body),
     )?;
     if body.trim().is_empty() {
@@ -55,19 +59,33 @@ pub fn handle_edit(ctx: &AppContext, args: &EditArgs) -> anyhow::Result<()> {
     .with_supersedes(entry.id);
 
     let entry_id = storage.insert_entry(&new_entry)?;
-    storage.close(&passphrase)?;
+    let edited = storage
+        .get_entry(&entry_id)?
+        .map(|revision| revision.created_at)
+        .unwrap_or_else(Utc::now);
+    let preview = if ctx.dry_run() {
+        Some(stored_entry_json(&storage, &entry_id)?)
+    } else {
+        None
+    };
+    ctx.save_storage(storage, &passphrase)?;
+    if let Some(preview) = preview {
+        println!("{}", serde_json::to_string_pretty(&preview)?);
+        return Ok(());
+    }
 
     if !ctx.quiet() {
-        let ui_ctx = ctx.ui_context(false, None);
-        let edited_at = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+        let ui_ctx = ctx.ui_context(args.json, None);
+        let edited_at = edited.format("%Y-%m-%d %H:%M UTC").to_string();
         let tag_count = entry.tags.len();
 
         match ui_ctx.mode {
             OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
                 print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, "Edited entry"));
                 // Context line with ID, timestamp, and supersedes
                 let context = format!(
-                    "ID: {}  \u{00B7}  {}  \u{00B7}  supersedes: {}",
+                    "ID: {}  {sep}  {}  {sep}  supersedes: {}",
                     short_id(&entry_id),
                     edited_at,
                     short_id(&entry.id)
@@ -80,11 +98,17 @@ pub fn handle_edit(ctx: &AppContext, args: &EditArgs) -> anyhow::Result<()> {
                     &ui_ctx,
                     &hint(
                         &ui_ctx,
-                        &format!("ledger show {}  \u{00B7}  ledger list", short_id(&entry_id)),
+                        &format!("ledger show {}  {sep}  ledger list", short_id(&entry_id)),
                     ),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                Receipt::new("edit", "entry", entry_id, None)
+                    .created_at(edited)
+                    .affects("superseded", entry.id, None)
+                    .print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("entry_id={}", entry_id);
                 println!("supersedes={}", entry.id);
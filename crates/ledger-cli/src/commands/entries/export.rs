@@ -1,21 +1,58 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 use std::time::Instant;
 
-use ledger_core::storage::{EntryFilter, StorageEngine};
+use anyhow::Context;
+use ledger_core::storage::encryption::encrypt;
+use ledger_core::storage::{AgeSqliteStorage, Entry, EntryFilter, ExportBundle, StorageEngine};
+use uuid::Uuid;
+use zeroize::Zeroizing;
 
 use crate::app::AppContext;
 use crate::cli::ExportArgs;
-use crate::helpers::{parse_datetime, require_entry_type};
-use crate::output::{entries_json, entry_json, entry_type_name_map};
+use crate::config::ExportProfile;
+use crate::helpers::{entries_to_org, parse_datetime, require_entry_type, task_to_todotxt};
+use crate::output::{
+    atom_feed, chain_root, entries_json, entry_type_name_map, write_entries_json,
+    write_entries_jsonl, DocumentFormat, EntrySummaries, FeedEntry, FieldFormatter,
+};
 use crate::ui::format::format_duration_secs;
 use crate::ui::progress::ProgressBar;
-use crate::ui::theme::{styled, styles};
+use crate::ui::theme::{styled, styles, symbols};
 use crate::ui::{badge, Badge, OutputMode};
 
+use super::export_chunks::export_chunked;
+
 pub fn handle_export(ctx: &AppContext, args: &ExportArgs) -> anyhow::Result<()> {
-    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let args = &match args.profile {
+        Some(ref name) => {
+            let profile = ctx
+                .config()?
+                .and_then(|config| config.export.profiles.get(name))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Export profile not found: {}\nHint: Define it under [export.profiles.{}] in config.",
+                        name,
+                        name
+                    )
+                })?;
+            apply_profile(args, profile)
+        }
+        None => args.clone(),
+    };
+    let (storage, passphrase) = ctx.open_storage(false)?;
+
+    let entry_type = args.entry_type.as_ref().or(args.r#type.as_ref());
+    if args.format() == "todotxt" && entry_type.map(String::as_str) != Some("task") {
+        return Err(anyhow::anyhow!(
+            "todo.txt export only covers tasks: use `ledger export --format todotxt --type task`"
+        ));
+    }
 
     let mut filter = EntryFilter::new();
-    if let Some(ref t) = args.entry_type {
+    if let Some(t) = entry_type {
         let entry_type_record = require_entry_type(&storage, t)?;
         filter = filter.entry_type(entry_type_record.id);
     }
@@ -23,43 +60,150 @@ pub fn handle_export(ctx: &AppContext, args: &ExportArgs) -> anyhow::Result<()>
         let parsed = parse_datetime(s)?;
         filter = filter.since(parsed);
     }
+    if let Some(ref tag) = args.tag {
+        filter = filter.tag(tag);
+    }
+    for tag in &args.redact {
+        filter = filter.exclude_tag(tag);
+    }
 
-    let entries = storage.list_entries(&filter)?;
     let name_map = entry_type_name_map(&storage)?;
-    let entry_count = entries.len();
+    if let (Some(chunk_by), Some(out_dir)) = (&args.chunk_by, &args.out_dir) {
+        return export_chunked(ctx, args, &storage, filter, &name_map, chunk_by, out_dir);
+    }
+
+    let entry_count = storage.count_entries(&filter)?;
     let start_time = Instant::now();
 
     // Get UI context for progress display
     let ui_ctx = ctx.ui_context(false, None);
     let show_progress = ui_ctx.mode.is_pretty() && !ctx.quiet() && entry_count > 10;
 
-    match args.format.as_str() {
+    let bundle = export_bundle(&storage, &args.include, &filter)?;
+    if bundle.is_some() && !matches!(args.format(), "json" | "yaml" | "toml") {
+        return Err(anyhow::anyhow!(
+            "--include requires --format json, yaml or toml (structure is exported as one document)"
+        ));
+    }
+
+    // An encrypted export is built in memory and only written once sealed
+    let mut plaintext = Zeroizing::new(Vec::new());
+    let mut out: Box<dyn Write + '_> = match args.out {
+        Some(_) if args.encrypt_output => Box::new(&mut *plaintext),
+        Some(ref path) => Box::new(BufWriter::new(
+            File::create(path).with_context(|| format!("Failed to create {}", path))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    match args.format() {
+        "json" if bundle.is_some() => {
+            let entries = storage.list_entries(&filter)?;
+            let mut output = serde_json::to_value(bundle)?;
+            output["entries"] = serde_json::Value::Array(entries_json(&entries, &name_map));
+            writeln!(out, "{}", serde_json::to_string_pretty(&output)?)?;
+        }
         "json" => {
-            let output = serde_json::to_string_pretty(&entries_json(&entries, &name_map))?;
-            println!("{}", output);
+            write_entries_json(&mut out, storage.iter_entries(&filter), &name_map)?;
         }
         "jsonl" => {
-            if show_progress {
-                let mut progress = ProgressBar::new(&ui_ctx, entry_count as u64, "Exporting");
-                for entry in &entries {
-                    let value = entry_json(entry, &name_map);
-                    println!("{}", serde_json::to_string(&value)?);
+            let mut progress =
+                show_progress.then(|| ProgressBar::new(&ui_ctx, entry_count as u64, "Exporting"));
+            let entries = storage.iter_entries(&filter).inspect(|_| {
+                if let Some(ref mut progress) = progress {
                     progress.inc(1);
                 }
+            });
+            write_entries_jsonl(&mut out, entries, &name_map)?;
+            if let Some(progress) = progress {
                 progress.finish();
+            }
+        }
+        "yaml" | "toml" => {
+            let format = if args.format() == "yaml" {
+                DocumentFormat::Yaml
             } else {
-                for value in entries_json(&entries, &name_map) {
-                    println!("{}", serde_json::to_string(&value)?);
+                DocumentFormat::Toml
+            };
+            let entries = storage.list_entries(&filter)?;
+            let entries = serde_json::Value::Array(entries_json(&entries, &name_map));
+            let document = match bundle {
+                Some(bundle) => {
+                    let mut document = serde_json::to_value(bundle)?;
+                    document["entries"] = entries;
+                    document
                 }
+                None => entries,
+            };
+            write!(out, "{}", format.render(&document, "entries")?)?;
+        }
+        "atom" => {
+            let entries = storage.list_entries(&filter)?;
+            let superseded = storage.superseded_entry_ids()?;
+            let all_entries = storage.list_entries(&EntryFilter::new())?;
+            let by_id: HashMap<Uuid, &Entry> = all_entries.iter().map(|e| (e.id, e)).collect();
+            let summaries = EntrySummaries::load(&storage)?;
+            let items: Vec<FeedEntry> = entries
+                .iter()
+                .filter(|e| !superseded.contains(&e.id))
+                .map(|entry| FeedEntry {
+                    entry,
+                    root: chain_root(entry, &by_id),
+                    type_name: name_map
+                        .get(&entry.entry_type_id)
+                        .map(String::as_str)
+                        .unwrap_or("unknown"),
+                    summary: summaries.template_summary(entry),
+                })
+                .collect();
+            let title = match args.tag {
+                Some(ref tag) => format!("Ledger: {}", tag),
+                None => "Ledger".to_string(),
+            };
+            let feed_id = feed_id(&storage.metadata()?.device_id, args);
+            write!(out, "{}", atom_feed(&title, &feed_id, &items))?;
+        }
+        "markdown" => {
+            let entries = storage.list_entries(&filter)?;
+            let superseded = storage.superseded_entry_ids()?;
+            let current: Vec<&Entry> = entries
+                .iter()
+                .filter(|e| !superseded.contains(&e.id))
+                .collect();
+            let formatter = FieldFormatter::load(&storage, ctx.locale()?)?;
+            let summaries = EntrySummaries::load(&storage)?;
+            write!(
+                out,
+                "{}",
+                markdown_export(&current, &name_map, &formatter, &summaries)
+            )?;
+        }
+        "org" => {
+            let entries = storage.list_entries(&filter)?;
+            let superseded = storage.superseded_entry_ids()?;
+            let current: Vec<&Entry> = entries
+                .iter()
+                .filter(|e| !superseded.contains(&e.id))
+                .collect();
+            write!(out, "{}", entries_to_org(&current, &name_map))?;
+        }
+        "todotxt" => {
+            // Revisions replace the task they supersede
+            for entry in storage.iter_entries(&filter.clone().latest_only()) {
+                writeln!(out, "{}", task_to_todotxt(&entry?))?;
             }
         }
         other => {
             return Err(anyhow::anyhow!(
-                "Unsupported export format: {} (use json or jsonl for portable exports)",
+                "Unsupported export format: {} (use json, jsonl, yaml, toml, atom, markdown, org or todotxt)",
                 other
             ));
         }
     }
+    out.flush()?;
+    drop(out);
+    if let (true, Some(path)) = (args.encrypt_output, &args.out) {
+        write_encrypted(Path::new(path), &plaintext, &passphrase)?;
+    }
 
     let elapsed = start_time.elapsed().as_secs_f64();
 
@@ -67,6 +211,7 @@ pub fn handle_export(ctx: &AppContext, args: &ExportArgs) -> anyhow::Result<()>
     if !ctx.quiet() {
         match ui_ctx.mode {
             OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
                 eprintln!(
                     "{}",
                     badge(
@@ -75,9 +220,14 @@ pub fn handle_export(ctx: &AppContext, args: &ExportArgs) -> anyhow::Result<()>
                         &format!("Exported {} entries", entry_count)
                     )
                 );
+                let format = if args.encrypt_output {
+                    format!("{} (age-encrypted)", args.format())
+                } else {
+                    args.format().to_string()
+                };
                 let context = format!(
-                    "Format: {}  \u{00B7}  Time: {}",
-                    args.format,
+                    "Format: {}  {sep}  Time: {}",
+                    format,
                     format_duration_secs(elapsed)
                 );
                 let context_styled = styled(&context, styles::dim(), ui_ctx.color);
@@ -86,7 +236,16 @@ pub fn handle_export(ctx: &AppContext, args: &ExportArgs) -> anyhow::Result<()>
             OutputMode::Plain | OutputMode::Json => {
                 // Plain mode: output stats to stderr so they don't mix with data
                 eprintln!("export_count={}", entry_count);
-                eprintln!("format={}", args.format);
+                eprintln!("format={}", args.format());
+                if let Some(ref profile) = args.profile {
+                    eprintln!("profile={}", profile);
+                }
+                if let Some(ref path) = args.out {
+                    eprintln!("out={}", path);
+                }
+                if args.encrypt_output {
+                    eprintln!("encrypted=true");
+                }
                 eprintln!("elapsed_ms={:.0}", elapsed * 1000.0);
             }
         }
@@ -94,3 +253,151 @@ pub fn handle_export(ctx: &AppContext, args: &ExportArgs) -> anyhow::Result<()>
 
     Ok(())
 }
+
+/// Fill in the flags left unset from an export profile.
+///
+/// Redacted tags are combined rather than replaced, so a profile's
+/// redaction always applies.
+fn apply_profile(args: &ExportArgs, profile: &ExportProfile) -> ExportArgs {
+    let mut args = args.clone();
+    args.format = args.format.or_else(|| profile.format.clone());
+    if args.entry_type.is_none() && args.r#type.is_none() {
+        args.r#type = profile.entry_type.clone();
+    }
+    args.tag = args.tag.or_else(|| profile.tag.clone());
+    args.since = args.since.or_else(|| profile.since.clone());
+    if args.include.is_empty() {
+        args.include = profile.include.clone();
+    }
+    for tag in &profile.redact {
+        if !args.redact.contains(tag) {
+            args.redact.push(tag.clone());
+        }
+    }
+    args
+}
+
+/// Encrypt an export under the ledger passphrase and write it owner-only.
+fn write_encrypted(path: &Path, plaintext: &[u8], passphrase: &str) -> anyhow::Result<()> {
+    let sealed = encrypt(plaintext, passphrase)?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(&sealed)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Render current entries as one Markdown document, a section per entry.
+///
+/// Structured fields become a bullet list formatted for the configured
+/// locale; a text body follows as-is. Types with a `summary_template`
+/// append the templated summary to the heading.
+fn markdown_export(
+    entries: &[&Entry],
+    name_map: &HashMap<Uuid, String>,
+    formatter: &FieldFormatter,
+    summaries: &EntrySummaries,
+) -> String {
+    let mut out = String::from("# Ledger export\n");
+    for entry in entries {
+        let type_name = name_map
+            .get(&entry.entry_type_id)
+            .map(String::as_str)
+            .unwrap_or("unknown");
+        out.push_str(&format!(
+            "\n## {} \u{00B7} {}",
+            formatter.locale().datetime(&entry.created_at),
+            type_name
+        ));
+        if let Some(summary) = summaries.template_summary(entry) {
+            out.push_str(&format!(" \u{2014} {}", summary));
+        }
+        out.push_str("\n\n");
+        let body = entry.data.get("body").and_then(|v| v.as_str());
+        let fields = formatter.fields(entry, &["body"]);
+        for (name, value) in &fields {
+            out.push_str(&format!("- **{}:** {}\n", name, value));
+        }
+        if !entry.tags.is_empty() {
+            out.push_str(&format!("- **tags:** {}\n", entry.tags.join(", ")));
+        }
+        if let Some(body) = body {
+            if !fields.is_empty() || !entry.tags.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(body.trim_end());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Stable feed id for this ledger and filter, without exposing the device id.
+fn feed_id(device_id: &Uuid, args: &ExportArgs) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"ledger atom feed v1");
+    hasher.update(device_id.as_bytes());
+    for part in [&args.entry_type, &args.r#type, &args.tag] {
+        hasher.update(part.as_deref().unwrap_or("").as_bytes());
+        hasher.update(&[0]);
+    }
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&hasher.finalize().as_bytes()[..16]);
+    format!(
+        "urn:uuid:{}",
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    )
+}
+
+/// Build the structure sections requested with `--include`, if any.
+///
+/// Memberships are limited to the exported entries so the bundle imports
+/// cleanly on its own.
+fn export_bundle(
+    storage: &AgeSqliteStorage,
+    include: &[String],
+    filter: &EntryFilter,
+) -> anyhow::Result<Option<ExportBundle>> {
+    if include.is_empty() {
+        return Ok(None);
+    }
+    for section in include {
+        if !matches!(section.as_str(), "compositions" | "templates" | "types") {
+            return Err(anyhow::anyhow!(
+                "Unknown export section: {} (use compositions, templates or types)",
+                section
+            ));
+        }
+    }
+    let wants = |section: &str| include.iter().any(|s| s == section);
+
+    let mut bundle = storage.export_structure()?;
+    if !wants("types") {
+        bundle.entry_types.clear();
+    }
+    if !wants("templates") {
+        bundle.templates.clear();
+    }
+    if wants("compositions") {
+        let exported = storage
+            .iter_entries(filter)
+            .map(|entry| entry.map(|entry| entry.id))
+            .collect::<Result<HashSet<_>, _>>()?;
+        bundle
+            .entry_compositions
+            .retain(|membership| exported.contains(&membership.entry_id));
+    } else {
+        bundle.compositions.clear();
+        bundle.entry_compositions.clear();
+        bundle.composition_overviews.clear();
+    }
+    Ok(Some(bundle))
+}
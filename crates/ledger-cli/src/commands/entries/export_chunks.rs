@@ -0,0 +1,332 @@
+//! Chunked export: one file per month or year plus a manifest, so long
+//! exports can be resumed after an interruption.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use ledger_core::storage::{AgeSqliteStorage, EntryFilter, StorageEngine};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::app::AppContext;
+use crate::cli::ExportArgs;
+use crate::output::entry_json;
+use crate::ui::format::format_duration_secs;
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, Badge, OutputMode};
+
+/// Name of the manifest written into the output directory.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Manifest layout version.
+const MANIFEST_VERSION: u32 = 1;
+
+/// Period each chunk file covers, by entry creation time in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkBy {
+    Month,
+    Year,
+}
+
+impl ChunkBy {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "month" => Some(Self::Month),
+            "year" => Some(Self::Year),
+            _ => None,
+        }
+    }
+
+    /// Period key for a timestamp: `2024-03` by month, `2024` by year.
+    fn key(&self, at: &DateTime<Utc>) -> String {
+        match self {
+            Self::Month => at.format("%Y-%m").to_string(),
+            Self::Year => at.format("%Y").to_string(),
+        }
+    }
+
+    /// Start of the period following `key`.
+    fn start_after(&self, key: &str) -> Option<DateTime<Utc>> {
+        let next = match self {
+            Self::Month => {
+                let first = NaiveDate::parse_from_str(&format!("{}-01", key), "%Y-%m-%d").ok()?;
+                first.checked_add_months(chrono::Months::new(1))?
+            }
+            Self::Year => NaiveDate::from_ymd_opt(key.parse::<i32>().ok()? + 1, 1, 1)?,
+        };
+        Some(Utc.from_utc_datetime(&next.and_hms_opt(0, 0, 0)?))
+    }
+}
+
+/// Options an export was started with; a resume must match them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ManifestOptions {
+    format: String,
+    chunk_by: String,
+    entry_type: Option<String>,
+    tag: Option<String>,
+    since: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    redact: Vec<String>,
+}
+
+/// One written chunk file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestChunk {
+    period: String,
+    file: String,
+    entries: usize,
+    blake3: String,
+}
+
+/// `manifest.json`: chunks in the order written, oldest first.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportManifest {
+    version: u32,
+    #[serde(flatten)]
+    options: ManifestOptions,
+    complete: bool,
+    chunks: Vec<ManifestChunk>,
+}
+
+/// Export entries matching `filter` into `out_dir`, one file per chunk.
+///
+/// Each chunk is written to a temporary file and renamed into place before
+/// the manifest records it, so the manifest only ever lists finished files.
+/// With `resume`, recorded chunks whose files still match their hashes are
+/// kept and the export restarts at the period after the last of them.
+pub fn export_chunked(
+    ctx: &AppContext,
+    args: &ExportArgs,
+    storage: &AgeSqliteStorage,
+    filter: EntryFilter,
+    name_map: &HashMap<Uuid, String>,
+    chunk_by: &str,
+    out_dir: &str,
+) -> anyhow::Result<()> {
+    let period = ChunkBy::parse(chunk_by)
+        .ok_or_else(|| anyhow::anyhow!("Invalid --chunk-by: {} (use month or year)", chunk_by))?;
+    if !matches!(args.format(), "json" | "jsonl") {
+        return Err(anyhow::anyhow!(
+            "--chunk-by requires --format json or jsonl (got {})",
+            args.format()
+        ));
+    }
+    if !args.include.is_empty() {
+        return Err(anyhow::anyhow!(
+            "--include cannot be combined with --chunk-by (structure is exported as one document)"
+        ));
+    }
+
+    let dir = Path::new(out_dir);
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let options = ManifestOptions {
+        format: args.format().to_string(),
+        chunk_by: chunk_by.to_string(),
+        entry_type: args.entry_type.clone().or_else(|| args.r#type.clone()),
+        tag: args.tag.clone(),
+        since: args.since.clone(),
+        redact: args.redact.clone(),
+    };
+
+    let mut manifest = match read_manifest(&manifest_path)? {
+        Some(_) if !args.resume => {
+            return Err(anyhow::anyhow!(
+                "{} already holds an export\nHint: Pass --resume to continue it, or choose an empty directory.",
+                dir.display()
+            ));
+        }
+        Some(existing) => {
+            if existing.options != options {
+                return Err(anyhow::anyhow!(
+                    "{} was exported with different options\nHint: Repeat the original type, --tag, --since, --redact, --format and --chunk-by, or choose an empty directory.",
+                    dir.display()
+                ));
+            }
+            existing
+        }
+        None => ExportManifest {
+            version: MANIFEST_VERSION,
+            options,
+            complete: false,
+            chunks: Vec::new(),
+        },
+    };
+
+    // Keep the prefix of chunks that are still intact on disk
+    let intact = manifest
+        .chunks
+        .iter()
+        .take_while(|chunk| chunk_is_intact(dir, chunk))
+        .count();
+    manifest.chunks.truncate(intact);
+    manifest.complete = false;
+    let skipped = manifest.chunks.len();
+
+    let mut filter = filter.oldest_first();
+    if let Some(last) = manifest.chunks.last() {
+        let resume_at = period
+            .start_after(&last.period)
+            .ok_or_else(|| anyhow::anyhow!("Invalid chunk period in manifest: {}", last.period))?;
+        filter.since = Some(filter.since.map_or(resume_at, |since| since.max(resume_at)));
+    }
+
+    let start_time = Instant::now();
+    let mut exported = 0usize;
+    let mut written = 0usize;
+    let mut current: Option<(String, Vec<serde_json::Value>)> = None;
+    for entry in storage.iter_entries(&filter) {
+        let entry = entry?;
+        let key = period.key(&entry.created_at);
+        if current.as_ref().is_some_and(|(open, _)| *open != key) {
+            if let Some((open, values)) = current.take() {
+                write_chunk(dir, &manifest_path, &mut manifest, open, &values)?;
+                written += 1;
+            }
+        }
+        current
+            .get_or_insert_with(|| (key, Vec::new()))
+            .1
+            .push(entry_json(&entry, name_map));
+        exported += 1;
+    }
+    if let Some((open, values)) = current.take() {
+        write_chunk(dir, &manifest_path, &mut manifest, open, &values)?;
+        written += 1;
+    }
+    manifest.complete = true;
+    write_manifest(&manifest_path, &manifest)?;
+
+    let elapsed = start_time.elapsed().as_secs_f64();
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+                eprintln!(
+                    "{}",
+                    badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!(
+                            "Exported {} entries in {} chunks to {}",
+                            exported, written, out_dir
+                        )
+                    )
+                );
+                let mut context = format!("Format: {}", args.format());
+                if skipped > 0 {
+                    context.push_str(&format!("  {sep}  Resumed after {} chunks", skipped));
+                }
+                context.push_str(&format!("  {sep}  Time: {}", format_duration_secs(elapsed)));
+                eprintln!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                eprintln!("export_count={}", exported);
+                eprintln!("chunk_count={}", written);
+                eprintln!("skipped_chunks={}", skipped);
+                eprintln!("format={}", args.format());
+                eprintln!("out_dir={}", out_dir);
+                eprintln!("elapsed_ms={:.0}", elapsed * 1000.0);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_manifest(path: &Path) -> anyhow::Result<Option<ExportManifest>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let manifest: ExportManifest = serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid export manifest {}", path.display()))?;
+    if manifest.version != MANIFEST_VERSION {
+        return Err(anyhow::anyhow!(
+            "Unsupported export manifest version {} in {}",
+            manifest.version,
+            path.display()
+        ));
+    }
+    Ok(Some(manifest))
+}
+
+fn chunk_is_intact(dir: &Path, chunk: &ManifestChunk) -> bool {
+    std::fs::read(dir.join(&chunk.file))
+        .is_ok_and(|bytes| blake3::hash(&bytes).to_hex().as_str() == chunk.blake3)
+}
+
+/// Write one chunk file, then record it in the manifest.
+fn write_chunk(
+    dir: &Path,
+    manifest_path: &Path,
+    manifest: &mut ExportManifest,
+    period: String,
+    values: &[serde_json::Value],
+) -> anyhow::Result<()> {
+    let mut contents = String::new();
+    if manifest.options.format == "jsonl" {
+        for value in values {
+            contents.push_str(&serde_json::to_string(value)?);
+            contents.push('\n');
+        }
+    } else {
+        contents.push_str(&serde_json::to_string_pretty(values)?);
+        contents.push('\n');
+    }
+    let file = format!("{}.{}", period, manifest.options.format);
+    write_atomic(&dir.join(&file), contents.as_bytes())?;
+    manifest.chunks.push(ManifestChunk {
+        period,
+        file,
+        entries: values.len(),
+        blake3: blake3::hash(contents.as_bytes()).to_hex().to_string(),
+    });
+    write_manifest(manifest_path, manifest)
+}
+
+fn write_manifest(path: &Path, manifest: &ExportManifest) -> anyhow::Result<()> {
+    let mut contents = serde_json::to_string_pretty(manifest)?;
+    contents.push('\n');
+    write_atomic(path, contents.as_bytes())
+}
+
+/// Write through a `.partial` file renamed into place, so an interrupted
+/// export never leaves a truncated file under the final name.
+fn write_atomic(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let mut temp = path.as_os_str().to_os_string();
+    temp.push(".partial");
+    let temp = Path::new(&temp);
+    std::fs::write(temp, contents)
+        .with_context(|| format!("Failed to write {}", temp.display()))?;
+    ledger_core::fs::rename_with_fallback(temp, path)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_keys_and_resume_points() {
+        let at = Utc.with_ymd_and_hms(2024, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(ChunkBy::Month.key(&at), "2024-12");
+        assert_eq!(ChunkBy::Year.key(&at), "2024");
+        assert_eq!(
+            ChunkBy::Month.start_after("2024-12"),
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            ChunkBy::Year.start_after("2024"),
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(ChunkBy::Month.start_after("garbage"), None);
+        assert_eq!(ChunkBy::parse("week"), None);
+    }
+}
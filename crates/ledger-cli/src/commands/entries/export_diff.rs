@@ -0,0 +1,151 @@
+//! Incremental export: the entries a previous export does not contain.
+//!
+//! Entries are append-only and edits are new revisions that supersede the
+//! old one, so whatever is not in the old export by id is exactly what an
+//! archive needs to catch up: new entries and the revisions written since.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+
+use anyhow::Context;
+use ledger_core::storage::{EntryFilter, StorageEngine};
+use uuid::Uuid;
+
+use crate::app::AppContext;
+use crate::cli::ExportDiffArgs;
+use crate::output::{entry_type_name_map, write_entries_json, write_entries_jsonl};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, Badge, OutputMode};
+
+use super::import::parse_export;
+
+pub fn handle_export_diff(ctx: &AppContext, args: &ExportDiffArgs) -> anyhow::Result<()> {
+    if !matches!(args.format.as_str(), "jsonl" | "json") {
+        return Err(anyhow::anyhow!(
+            "Unsupported export-diff format: {} (use jsonl or json)",
+            args.format
+        ));
+    }
+    let raw = if args.old_export == "-" {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        std::fs::read_to_string(&args.old_export)
+            .with_context(|| format!("Failed to read export {}", args.old_export))?
+    };
+    let known = exported_ids(&raw)?;
+
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let name_map = entry_type_name_map(&storage)?;
+
+    let mut present = 0;
+    let mut new_count = 0;
+    let entries = storage.iter_entries(&EntryFilter::new()).filter(|entry| {
+        let is_new = !matches!(entry, Ok(entry) if known.contains(&entry.id));
+        if is_new {
+            new_count += usize::from(entry.is_ok());
+        } else {
+            present += 1;
+        }
+        is_new
+    });
+
+    let mut out: Box<dyn Write> = match args.out {
+        Some(ref path) => Box::new(BufWriter::new(
+            File::create(path).with_context(|| format!("Failed to create {}", path))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    if args.format == "json" {
+        write_entries_json(&mut out, entries, &name_map)?;
+    } else {
+        write_entries_jsonl(&mut out, entries, &name_map)?;
+    }
+    out.flush()?;
+    drop(out);
+    // Ids the ledger no longer has, e.g. an export of another ledger
+    let unknown = known.len() - present;
+
+    if ctx.quiet() {
+        return Ok(());
+    }
+    let ui_ctx = ctx.ui_context(false, None);
+    match ui_ctx.mode {
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            eprintln!(
+                "{}",
+                badge(
+                    &ui_ctx,
+                    Badge::Ok,
+                    &format!("Exported {} entries not in {}", new_count, args.old_export)
+                )
+            );
+            let context = format!(
+                "Already exported: {}  {sep}  Format: {}",
+                present, args.format
+            );
+            eprintln!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            if unknown > 0 {
+                eprintln!(
+                    "{}",
+                    badge(
+                        &ui_ctx,
+                        Badge::Warn,
+                        &format!("{} exported entries are not in this ledger", unknown)
+                    )
+                );
+            }
+        }
+        OutputMode::Plain | OutputMode::Json => {
+            eprintln!("export_count={}", new_count);
+            eprintln!("already_exported={}", present);
+            eprintln!("unknown={}", unknown);
+            eprintln!("format={}", args.format);
+            if let Some(ref path) = args.out {
+                eprintln!("out={}", path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Entry ids contained in a JSON or JSONL export.
+fn exported_ids(raw: &str) -> anyhow::Result<HashSet<Uuid>> {
+    let (_, entries) = parse_export(raw)?;
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            entry
+                .get("id")
+                .and_then(|id| id.as_str())
+                .and_then(|id| Uuid::parse_str(id).ok())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Entry {} of the old export has no valid id", index + 1)
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exported_ids_reads_json_and_jsonl() {
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let jsonl = format!("{{\"id\":\"{}\"}}\n\n{{\"id\":\"{}\"}}\n", first, second);
+        let ids = exported_ids(&jsonl).unwrap();
+        assert_eq!(ids, HashSet::from([first, second]));
+
+        let json = format!("[{{\"id\":\"{}\"}}]", first);
+        assert_eq!(exported_ids(&json).unwrap(), HashSet::from([first]));
+
+        let err = exported_ids("{\"body\":\"no id\"}\n{\"body\":\"x\"}").unwrap_err();
+        assert!(err.to_string().contains("no valid id"));
+    }
+}
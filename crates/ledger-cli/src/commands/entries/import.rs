@@ -0,0 +1,354 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+use anyhow::Context;
+use chrono::Utc;
+use ledger_core::storage::{
+    AgeSqliteStorage, Entry, EntryFilter, ExportBundle, ImportSummary, NewEntry, StorageEngine,
+};
+use uuid::Uuid;
+
+use crate::app::AppContext;
+use crate::cli::ImportArgs;
+use crate::helpers::{
+    ensure_builtin_entry_type, parse_org, parse_todotxt_line, require_entry_type, task_to_todotxt,
+    OrgEntry,
+};
+use crate::ui::short_id;
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, print, Badge, OutputMode};
+
+/// A parsed import file.
+enum ImportSource<'a> {
+    Export((ExportBundle, Vec<serde_json::Value>)),
+    Org(Vec<OrgEntry>),
+    Todotxt(&'a str),
+}
+
+pub fn handle_import(ctx: &AppContext, args: &ImportArgs) -> anyhow::Result<()> {
+    let raw = if args.file == "-" {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        std::fs::read_to_string(&args.file)
+            .with_context(|| format!("Failed to read import file {}", args.file))?
+    };
+    // Parse before unlocking so a bad file fails fast
+    let source = match args.from.as_str() {
+        "ledger" => ImportSource::Export(parse_export(&raw)?),
+        "yaml" => ImportSource::Export(parse_yaml_export(&raw)?),
+        "org" => ImportSource::Org(parse_org(&raw)?),
+        "todotxt" => ImportSource::Todotxt(&raw),
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unsupported import format: {} (use ledger, yaml, org or todotxt)",
+                other
+            ));
+        }
+    };
+
+    let (mut storage, passphrase) = ctx.open_storage(false)?;
+    let replayed = match args.idempotency_key {
+        Some(ref key) => storage.idempotency_record(key, "import")?,
+        None => None,
+    };
+    let summary = match replayed {
+        Some(ref record) => match record.result {
+            Some(ref result) => serde_json::from_value(result.clone())
+                .context("Invalid import summary recorded for idempotency key")?,
+            None => ImportSummary::default(),
+        },
+        None => {
+            let summary = match source {
+                ImportSource::Export(export) => import_export(&mut storage, export)?,
+                ImportSource::Org(headlines) => import_org(&mut storage, headlines)?,
+                ImportSource::Todotxt(raw) => import_todotxt(&mut storage, raw)?,
+            };
+            if let Some(ref key) = args.idempotency_key {
+                let result = serde_json::to_value(&summary)?;
+                storage.record_idempotency_key(key, "import", None, Some(&result))?;
+            }
+            ctx.save_storage(storage, &passphrase)?;
+            summary
+        }
+    };
+    let replayed = replayed.is_some();
+
+    if ctx.quiet() && !args.json {
+        return Ok(());
+    }
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let mut output = serde_json::to_value(&summary)?;
+            if replayed {
+                output["replayed"] = serde_json::Value::Bool(true);
+            }
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            let text = if replayed {
+                format!(
+                    "Already imported {} entries (idempotency key {})",
+                    summary.entries,
+                    args.idempotency_key.as_deref().unwrap_or_default()
+                )
+            } else {
+                format!("Imported {} entries", summary.entries)
+            };
+            let kind = if replayed { Badge::Info } else { Badge::Ok };
+            print(&ui_ctx, &badge(&ui_ctx, kind, &text));
+            let context = format!(
+                "Types: {}  {sep}  Templates: {}  {sep}  Compositions: {}  {sep}  Memberships: {}  {sep}  Overviews: {}  {sep}  Skipped: {}",
+                summary.entry_types,
+                summary.templates,
+                summary.compositions,
+                summary.memberships,
+                summary.overviews,
+                summary.skipped
+            );
+            println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+        }
+        OutputMode::Plain => {
+            println!("entry_types={}", summary.entry_types);
+            println!("templates={}", summary.templates);
+            println!("compositions={}", summary.compositions);
+            println!("entries={}", summary.entries);
+            println!("memberships={}", summary.memberships);
+            println!("overviews={}", summary.overviews);
+            println!("skipped={}", summary.skipped);
+            if replayed {
+                println!("replayed=true");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Import a parsed ledger export.
+pub(crate) fn import_export(
+    storage: &mut AgeSqliteStorage,
+    (mut bundle, raw_entries): (ExportBundle, Vec<serde_json::Value>),
+) -> anyhow::Result<ImportSummary> {
+    // Plain entry exports carry only ids from the source ledger; fall back to
+    // the entry type name when the id is unknown here.
+    let existing_types = storage.list_entry_types()?;
+    let mut known_types: HashSet<Uuid> = existing_types.iter().map(|t| t.id).collect();
+    known_types.extend(bundle.entry_types.iter().map(|t| t.id));
+    let types_by_name: HashMap<String, Uuid> =
+        existing_types.into_iter().map(|t| (t.name, t.id)).collect();
+    for item in raw_entries {
+        let type_name = item
+            .get("entry_type_name")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let mut entry: Entry = serde_json::from_value(item).context("Invalid entry in import")?;
+        if !known_types.contains(&entry.entry_type_id) {
+            if let Some(id) = type_name.and_then(|name| types_by_name.get(&name).copied()) {
+                entry.entry_type_id = id;
+            }
+        }
+        bundle.entries.push(entry);
+    }
+
+    Ok(storage.import_bundle(&bundle)?)
+}
+
+/// Import an `export --format org` document.
+///
+/// Headlines whose entry is in the ledger become revisions when their body,
+/// fields or tags were edited, and are skipped otherwise. Headlines with an
+/// id from another ledger keep it, so importing the same file twice is
+/// harmless; headlines without an id are new entries.
+fn import_org(
+    storage: &mut AgeSqliteStorage,
+    headlines: Vec<OrgEntry>,
+) -> anyhow::Result<ImportSummary> {
+    let device_id = storage.metadata()?.device_id;
+    let superseded = storage.superseded_entry_ids()?;
+
+    let mut summary = ImportSummary::default();
+    let mut pending = Vec::new();
+    let mut bundle = ExportBundle::default();
+    for headline in headlines {
+        let existing = match headline.id {
+            Some(id) => storage.get_entry(&id)?,
+            None => None,
+        };
+        if let Some(entry) = existing {
+            if !headline.differs(&entry) {
+                summary.skipped += 1;
+                continue;
+            }
+            if superseded.contains(&entry.id) {
+                return Err(anyhow::anyhow!(
+                    "Entry {} on line {} has a newer revision in the ledger\nHint: Export again and redo the edit there.",
+                    short_id(&entry.id),
+                    headline.line
+                ));
+            }
+            let revision = NewEntry::new(
+                entry.entry_type_id,
+                entry.schema_version,
+                headline.data(Some(&entry.data)),
+                device_id,
+            )
+            .with_tags(headline.tags)
+            .with_supersedes(entry.id);
+            pending.push((headline.line, revision));
+            continue;
+        }
+
+        let type_name = headline.entry_type.as_deref().unwrap_or("journal");
+        ensure_builtin_entry_type(storage, type_name, device_id)?;
+        let entry_type = require_entry_type(storage, type_name)?;
+        let data = headline.data(None);
+        match headline.id {
+            Some(id) => bundle.entries.push(Entry {
+                id,
+                entry_type_id: entry_type.id,
+                schema_version: entry_type.version,
+                data,
+                tags: headline.tags,
+                created_at: headline.created_at.unwrap_or_else(Utc::now),
+                seq: 0,
+                device_id,
+                supersedes: None,
+            }),
+            None => {
+                let mut entry = NewEntry::new(entry_type.id, entry_type.version, data, device_id)
+                    .with_tags(headline.tags);
+                if let Some(created_at) = headline.created_at {
+                    entry = entry.with_created_at(created_at);
+                }
+                pending.push((headline.line, entry));
+            }
+        }
+    }
+
+    let mut line = 0;
+    storage
+        .batch(|storage| {
+            for (index, entry) in &pending {
+                line = *index;
+                storage.insert_entry(entry)?;
+            }
+            Ok(())
+        })
+        .with_context(|| format!("Invalid entry on line {}", line))?;
+    summary.entries = pending.len();
+    if !bundle.entries.is_empty() {
+        let imported = storage.import_bundle(&bundle)?;
+        summary.entries += imported.entries;
+        summary.skipped += imported.skipped;
+    }
+    Ok(summary)
+}
+
+/// Import todo.txt lines as task entries.
+///
+/// Lines matching a task already in the ledger are skipped, so re-importing
+/// the same file is harmless.
+fn import_todotxt(storage: &mut AgeSqliteStorage, raw: &str) -> anyhow::Result<ImportSummary> {
+    let device_id = storage.metadata()?.device_id;
+    ensure_builtin_entry_type(storage, "task", device_id)?;
+    let task_type = require_entry_type(storage, "task")?;
+
+    let superseded = storage.superseded_entry_ids()?;
+    let mut existing: HashSet<String> = storage
+        .list_entries(&EntryFilter::new().entry_type(task_type.id))?
+        .iter()
+        .filter(|entry| !superseded.contains(&entry.id))
+        .map(task_to_todotxt)
+        .collect();
+
+    let mut summary = ImportSummary::default();
+    let mut pending = Vec::new();
+    for (index, line) in raw.lines().enumerate() {
+        let Some(task) = parse_todotxt_line(line) else {
+            continue;
+        };
+        let mut entry = NewEntry::new(task_type.id, task_type.version, task.data(), device_id)
+            .with_tags(task.tags.clone());
+        if let Some(created_at) = task.created_at() {
+            entry = entry.with_created_at(created_at);
+        }
+        let preview = Entry {
+            id: Uuid::nil(),
+            entry_type_id: task_type.id,
+            schema_version: task_type.version,
+            data: entry.data.clone(),
+            tags: entry.tags.clone(),
+            created_at: entry.created_at.unwrap_or_else(Utc::now),
+            seq: 0,
+            device_id,
+            supersedes: None,
+        };
+        if !existing.insert(task_to_todotxt(&preview)) {
+            summary.skipped += 1;
+            continue;
+        }
+        pending.push((index + 1, entry));
+    }
+
+    let mut line = 0;
+    storage
+        .batch(|storage| {
+            for (index, entry) in &pending {
+                line = *index;
+                storage.insert_entry(entry)?;
+            }
+            Ok(())
+        })
+        .with_context(|| format!("Invalid task on line {}", line))?;
+    summary.entries = pending.len();
+    Ok(summary)
+}
+
+/// Split an export into its structure and raw entry objects.
+///
+/// Accepts `--include` bundles, plain JSON entry arrays and JSONL.
+pub(crate) fn parse_export(raw: &str) -> anyhow::Result<(ExportBundle, Vec<serde_json::Value>)> {
+    match serde_json::from_str::<serde_json::Value>(raw) {
+        Ok(serde_json::Value::Array(entries)) => Ok((ExportBundle::default(), entries)),
+        Ok(serde_json::Value::Object(map)) if !map.contains_key("id") => split_bundle(map),
+        _ => {
+            let entries = raw
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<Result<Vec<_>, _>>()
+                .context("Import file is not a ledger JSON or JSONL export")?;
+            Ok((ExportBundle::default(), entries))
+        }
+    }
+}
+
+/// Split an `export --format yaml` document, with or without `--include`.
+fn parse_yaml_export(raw: &str) -> anyhow::Result<(ExportBundle, Vec<serde_json::Value>)> {
+    let value: serde_json::Value =
+        serde_yaml::from_str(raw).context("Import file is not a ledger YAML export")?;
+    match value {
+        serde_json::Value::Array(entries) => Ok((ExportBundle::default(), entries)),
+        serde_json::Value::Object(map) if !map.contains_key("id") => split_bundle(map),
+        _ => Err(anyhow::anyhow!(
+            "Import file is not a ledger YAML export: expected a list of entries or an export document"
+        )),
+    }
+}
+
+/// Separate the entries of an `--include` export from its structure.
+fn split_bundle(
+    mut map: serde_json::Map<String, serde_json::Value>,
+) -> anyhow::Result<(ExportBundle, Vec<serde_json::Value>)> {
+    let entries = match map.remove("entries") {
+        Some(serde_json::Value::Array(entries)) => entries,
+        Some(_) => return Err(anyhow::anyhow!("Invalid export: entries is not a list")),
+        None => Vec::new(),
+    };
+    let bundle = serde_json::from_value(serde_json::Value::Object(map))
+        .context("Invalid export structure")?;
+    Ok((bundle, entries))
+}
@@ -1,20 +1,45 @@
-use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
 
-use ledger_core::storage::{EntryFilter, StorageEngine};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use ledger_core::storage::{AgeSqliteStorage, DayNumber, Entry, EntryFilter, StorageEngine};
+use ledger_core::views::EntryView;
 
 use crate::app::{resolve_ledger_path, AppContext};
 use crate::cli::ListArgs;
-use crate::helpers::{parse_duration, require_entry_type};
-use crate::output::{entries_json, entry_type_name_map};
+use crate::helpers::{parse_duration, require_entry_type, url_domain};
+use crate::output::{
+    chain_last_viewed, entry_type_badges, entry_type_name_map, write_json_array, EntrySummaries,
+    FieldFormatter,
+};
+use crate::ui::theme::{styled, styles, symbols};
 use crate::ui::{
-    blank_line, entry_summary, header_with_context, hint, print, short_id, simple_table, truncate,
-    Column, OutputMode,
+    blank_line, entry_text_summary, header_with_context, hint, print, short_id, simple_table,
+    simple_table_cells, truncate, type_badge_cell, Column, OutputMode, TableCell, UiContext,
 };
 
 const DEFAULT_LIST_LIMIT: usize = 20;
 const TABLE_SUMMARY_MAX: usize = 80;
+const NO_DOMAIN: &str = "(no url)";
 
 pub fn handle_list(ctx: &AppContext, args: &ListArgs) -> anyhow::Result<()> {
+    let by_last_viewed = match args.sort.as_deref() {
+        None | Some("created") => false,
+        Some("last-viewed") => true,
+        Some(other) => {
+            return Err(anyhow::anyhow!(
+                "Invalid sort: {} (use created or last-viewed)",
+                other
+            ));
+        }
+    };
+    if by_last_viewed && args.by_domain {
+        return Err(anyhow::anyhow!(
+            "--sort last-viewed cannot be used with --by-domain"
+        ));
+    }
+
     let (storage, _passphrase) = ctx.open_storage(false)?;
 
     // Get ledger path for header
@@ -22,6 +47,8 @@ pub fn handle_list(ctx: &AppContext, args: &ListArgs) -> anyhow::Result<()> {
 
     // Build entry type name map for display
     let name_map = entry_type_name_map(&storage)?;
+    let summaries = EntrySummaries::load(&storage)?;
+    let day_numbers = storage.day_numbers()?;
 
     let mut filter = EntryFilter::new();
     if let Some(ref t) = args.entry_type {
@@ -31,6 +58,9 @@ pub fn handle_list(ctx: &AppContext, args: &ListArgs) -> anyhow::Result<()> {
     if let Some(ref t) = args.tag {
         filter = filter.tag(t.clone());
     }
+    if let Some(ref origin) = args.origin {
+        filter = filter.origin(origin.clone());
+    }
     if let Some(ref l) = args.last {
         let window = parse_duration(l)?;
         let since_time = Utc::now() - window;
@@ -46,30 +76,80 @@ pub fn handle_list(ctx: &AppContext, args: &ListArgs) -> anyhow::Result<()> {
             .map_err(|e| anyhow::anyhow!("Invalid until timestamp: {}", e))?;
         filter = filter.until(parsed.with_timezone(&chrono::Utc));
     }
-    if let Some(lim) = args.limit {
+    let limit = args.limit.or_else(|| {
+        (args.last.is_none() && args.since.is_none() && args.until.is_none())
+            .then_some(DEFAULT_LIST_LIMIT)
+    });
+    // Sorting by views has to see every match before the limit applies
+    if let (Some(lim), false) = (limit, by_last_viewed) {
         filter = filter.limit(lim);
-    } else if args.last.is_none() && args.since.is_none() && args.until.is_none() {
-        filter = filter.limit(DEFAULT_LIST_LIMIT);
     }
 
-    let mut entries = storage.list_entries(&filter)?;
     if !args.history {
-        let superseded = storage.superseded_entry_ids()?;
-        entries.retain(|entry| !superseded.contains(&entry.id));
+        filter = filter.latest_only();
     }
 
-    // Build filter context for header (e.g., "last 7d", "tag: work")
-    let filter_context = build_filter_context(args);
-
     // Create UI context from flags
     let ui_ctx = ctx.ui_context(args.json, args.format.as_deref());
-
-    // Handle JSON output separately
     if ui_ctx.mode.is_json() {
         if args.format.is_some() {
             return Err(anyhow::anyhow!("--format cannot be used with --json"));
         }
-        let output = serde_json::to_string_pretty(&entries_json(&entries, &name_map))?;
+        // Nothing else needs the entries, so they go straight to stdout
+        if !args.by_domain && !by_last_viewed {
+            return write_json_array(
+                std::io::stdout().lock(),
+                storage
+                    .iter_entries(&filter)
+                    .map(|entry| Ok(list_entry_view(&entry?, &name_map, &day_numbers))),
+            );
+        }
+    }
+
+    let mut entries = storage.list_entries(&filter)?;
+    let last_viewed = if by_last_viewed {
+        let viewed = sort_by_last_viewed(&storage, &mut entries)?;
+        if let Some(lim) = limit {
+            entries.truncate(lim);
+        }
+        Some(viewed)
+    } else {
+        None
+    };
+
+    // Build filter context for header (e.g., "last 7d", "tag: work")
+    let filter_context = build_filter_context(args);
+
+    // Handle JSON output separately
+    if ui_ctx.mode.is_json() {
+        let output = if args.by_domain {
+            let groups: Vec<serde_json::Value> = group_by_domain(&entries)
+                .into_iter()
+                .map(|(domain, group)| {
+                    let entries: Vec<EntryView> = group
+                        .iter()
+                        .map(|entry| list_entry_view(entry, &name_map, &day_numbers))
+                        .collect();
+                    serde_json::json!({
+                        "domain": domain,
+                        "count": entries.len(),
+                        "entries": entries,
+                    })
+                })
+                .collect();
+            serde_json::to_string_pretty(&groups)?
+        } else {
+            let mut values: Vec<serde_json::Value> = entries
+                .iter()
+                .map(|entry| serde_json::json!(list_entry_view(entry, &name_map, &day_numbers)))
+                .collect();
+            if let Some(ref viewed) = last_viewed {
+                for (value, entry) in values.iter_mut().zip(&entries) {
+                    value["last_viewed"] = serde_json::json!(viewed.get(&entry.id));
+                }
+            }
+            serde_json::to_string_pretty(&values)?
+        };
         println!("{}", output);
         return Ok(());
     }
@@ -105,9 +185,23 @@ pub fn handle_list(ctx: &AppContext, args: &ListArgs) -> anyhow::Result<()> {
         return Ok(());
     }
 
+    if args.by_domain {
+        render_by_domain(
+            &ui_ctx,
+            &entries,
+            &name_map,
+            &day_numbers,
+            &summaries,
+            filter_context.as_deref(),
+            ledger_path.as_deref(),
+        );
+        return Ok(());
+    }
+
     // Render entries
     match ui_ctx.mode {
         OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
             print(
                 &ui_ctx,
                 &header_with_context(
@@ -119,43 +213,71 @@ pub fn handle_list(ctx: &AppContext, args: &ListArgs) -> anyhow::Result<()> {
             );
             blank_line(&ui_ctx);
 
-            let columns = [
+            let formatter = FieldFormatter::load(&storage, ctx.locale()?)?;
+            let mut columns = vec![
                 Column::new("ID"),
+                Column::new("No."),
                 Column::new("Created"),
                 Column::new("Type"),
                 Column::new("Summary"),
                 Column::new("Tags"),
             ];
+            if last_viewed.is_some() {
+                columns.push(Column::new("Viewed"));
+            }
 
-            let rows: Vec<Vec<String>> = entries
+            let badges = entry_type_badges(&storage)?;
+            let rows: Vec<Vec<TableCell>> = entries
                 .iter()
                 .map(|entry| {
-                    let type_name = name_map
+                    let type_cell = badges
                         .get(&entry.entry_type_id)
-                        .cloned()
-                        .unwrap_or_else(|| "unknown".to_string());
+                        .map(|badge| type_badge_cell(&ui_ctx, badge))
+                        .unwrap_or_else(|| TableCell::new("unknown"));
                     let tags_display = if entry.tags.is_empty() {
                         "-".to_string()
                     } else {
                         entry.tags.join(", ")
                     };
-                    vec![
-                        short_id(&entry.id),
-                        entry.created_at.format("%Y-%m-%d %H:%M").to_string(),
-                        type_name,
-                        truncate(&entry_summary(entry), TABLE_SUMMARY_MAX),
-                        tags_display,
-                    ]
+                    let summary = summaries
+                        .template_summary(entry)
+                        .or_else(|| entry_text_summary(entry))
+                        .unwrap_or_else(|| formatter.summary(entry, ui_ctx.unicode));
+                    let mut row = vec![
+                        TableCell::new(short_id(&entry.id)),
+                        TableCell::new(
+                            day_numbers
+                                .get(&entry.id)
+                                .map(|number| format!("#{}", number.number))
+                                .unwrap_or_else(|| "-".to_string()),
+                        ),
+                        TableCell::new(formatter.locale().datetime(&entry.created_at)),
+                        type_cell,
+                        TableCell::new(truncate(&summary, TABLE_SUMMARY_MAX)),
+                        TableCell::new(tags_display),
+                    ];
+                    if let Some(ref viewed) = last_viewed {
+                        row.push(TableCell::new(
+                            viewed
+                                .get(&entry.id)
+                                .map(|at| formatter.locale().datetime(at))
+                                .unwrap_or_else(|| "never".to_string()),
+                        ));
+                    }
+                    row
                 })
                 .collect();
 
-            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            print(&ui_ctx, &simple_table_cells(&ui_ctx, &columns, &rows));
             blank_line(&ui_ctx);
 
-            // Actionable hints with first entry ID
-            let first_id = entries.first().map(|e| short_id(&e.id));
+            // Actionable hints with the first entry's day number
+            let first_id = entries
+                .first()
+                .and_then(|e| day_numbers.get(&e.id))
+                .map(|number| number.to_string());
             let hint_text = if let Some(id) = first_id {
-                format!("ledger show {}  \u{00B7}  ledger search \"term\"", id)
+                format!("ledger show {}  {sep}  ledger search \"term\"", id)
             } else {
                 "ledger search \"term\"".to_string()
             };
@@ -168,21 +290,26 @@ pub fn handle_list(ctx: &AppContext, args: &ListArgs) -> anyhow::Result<()> {
             );
         }
         OutputMode::Plain | OutputMode::Json => {
-            // Plain mode: space-separated values with type
+            // Plain mode: space-separated values with log id and type
             for entry in &entries {
                 let type_name = name_map
                     .get(&entry.entry_type_id)
                     .cloned()
                     .unwrap_or_else(|| "unknown".to_string());
-                let summary = entry_summary(entry);
+                let summary = summaries.summary(entry);
                 let tags = if entry.tags.is_empty() {
                     "-".to_string()
                 } else {
                     entry.tags.join(",")
                 };
                 println!(
-                    "{} {} {} {} {}",
-                    entry.id, entry.created_at, type_name, tags, summary
+                    "{} {} {} {} {} {}",
+                    entry.id,
+                    plain_log_id(day_numbers.get(&entry.id)),
+                    entry.created_at,
+                    type_name,
+                    tags,
+                    summary
                 );
             }
         }
@@ -191,6 +318,154 @@ pub fn handle_list(ctx: &AppContext, args: &ListArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// An entry's view with its day number as `log_id`.
+fn list_entry_view(
+    entry: &Entry,
+    name_map: &HashMap<Uuid, String>,
+    day_numbers: &HashMap<Uuid, DayNumber>,
+) -> EntryView {
+    let name = name_map
+        .get(&entry.entry_type_id)
+        .map(String::as_str)
+        .unwrap_or("unknown");
+    EntryView::new(entry, name).with_log_id(day_numbers.get(&entry.id))
+}
+
+/// A day number for plain rows, `-` when the entry has none.
+fn plain_log_id(number: Option<&DayNumber>) -> String {
+    number
+        .map(|number| number.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Order entries by most recent view, never-viewed last.
+///
+/// Returns each entry's last view, counting views of earlier revisions.
+fn sort_by_last_viewed(
+    storage: &AgeSqliteStorage,
+    entries: &mut [Entry],
+) -> anyhow::Result<HashMap<Uuid, DateTime<Utc>>> {
+    let viewed = storage.last_accessed()?;
+    let mut parents: HashMap<Uuid, Uuid> = HashMap::new();
+    for entry in storage.iter_entries(&EntryFilter::new()) {
+        let entry = entry?;
+        if let Some(parent) = entry.supersedes {
+            parents.insert(entry.id, parent);
+        }
+    }
+    let last_viewed: HashMap<Uuid, DateTime<Utc>> = entries
+        .iter()
+        .filter_map(|entry| chain_last_viewed(entry.id, &parents, &viewed).map(|at| (entry.id, at)))
+        .collect();
+    entries.sort_by(|a, b| {
+        last_viewed
+            .get(&b.id)
+            .cmp(&last_viewed.get(&a.id))
+            .then_with(|| (b.created_at, b.seq).cmp(&(a.created_at, a.seq)))
+    });
+    Ok(last_viewed)
+}
+
+/// Group entries by the domain of their "url" field, most common domains first.
+fn group_by_domain(entries: &[Entry]) -> Vec<(String, Vec<&Entry>)> {
+    let mut groups: BTreeMap<String, Vec<&Entry>> = BTreeMap::new();
+    for entry in entries {
+        let domain = entry
+            .data
+            .get("url")
+            .and_then(|v| v.as_str())
+            .and_then(url_domain)
+            .unwrap_or_else(|| NO_DOMAIN.to_string());
+        groups.entry(domain).or_default().push(entry);
+    }
+    let mut groups: Vec<(String, Vec<&Entry>)> = groups.into_iter().collect();
+    groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+    groups
+}
+
+/// Render entries grouped by URL domain.
+fn render_by_domain(
+    ui_ctx: &UiContext,
+    entries: &[Entry],
+    name_map: &HashMap<Uuid, String>,
+    day_numbers: &HashMap<Uuid, DayNumber>,
+    summaries: &EntrySummaries,
+    filter_context: Option<&str>,
+    ledger_path: Option<&str>,
+) {
+    let groups = group_by_domain(entries);
+    match ui_ctx.mode {
+        OutputMode::Pretty => {
+            print(
+                ui_ctx,
+                &header_with_context(ui_ctx, "list", filter_context, ledger_path),
+            );
+            let columns = [
+                Column::new("ID"),
+                Column::new("Created"),
+                Column::new("Summary"),
+                Column::new("Tags"),
+            ];
+            for (domain, group) in &groups {
+                blank_line(ui_ctx);
+                let title = format!("{} ({})", domain, group.len());
+                println!("{}", styled(&title, styles::bold(), ui_ctx.color));
+                let rows: Vec<Vec<String>> = group
+                    .iter()
+                    .map(|entry| {
+                        let tags_display = if entry.tags.is_empty() {
+                            "-".to_string()
+                        } else {
+                            entry.tags.join(", ")
+                        };
+                        vec![
+                            short_id(&entry.id),
+                            entry.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                            truncate(&summaries.summary(entry), TABLE_SUMMARY_MAX),
+                            tags_display,
+                        ]
+                    })
+                    .collect();
+                print(ui_ctx, &simple_table(ui_ctx, &columns, &rows));
+            }
+            blank_line(ui_ctx);
+            print(
+                ui_ctx,
+                &hint(
+                    ui_ctx,
+                    &format!("{} entries across {} domains.", entries.len(), groups.len()),
+                ),
+            );
+        }
+        OutputMode::Plain | OutputMode::Json => {
+            // Plain mode: domain first, then the usual space-separated values
+            for (domain, group) in &groups {
+                for entry in group {
+                    let type_name = name_map
+                        .get(&entry.entry_type_id)
+                        .cloned()
+                        .unwrap_or_else(|| "unknown".to_string());
+                    let tags = if entry.tags.is_empty() {
+                        "-".to_string()
+                    } else {
+                        entry.tags.join(",")
+                    };
+                    println!(
+                        "{} {} {} {} {} {} {}",
+                        domain,
+                        entry.id,
+                        plain_log_id(day_numbers.get(&entry.id)),
+                        entry.created_at,
+                        type_name,
+                        tags,
+                        summaries.summary(entry)
+                    );
+                }
+            }
+        }
+    }
+}
+
 /// Build a filter context string for the header.
 fn build_filter_context(args: &ListArgs) -> Option<String> {
     let mut parts = Vec::new();
@@ -204,6 +479,12 @@ fn build_filter_context(args: &ListArgs) -> Option<String> {
     if let Some(ref t) = args.tag {
         parts.push(format!("tag: {}", t));
     }
+    if let Some(ref origin) = args.origin {
+        parts.push(format!("origin: {}", origin));
+    }
+    if args.by_domain {
+        parts.push("by domain".to_string());
+    }
 
     if parts.is_empty() {
         None
@@ -1,13 +1,20 @@
 pub mod add;
+pub mod backfill;
 pub mod edit;
 pub mod export;
+mod export_chunks;
+pub mod export_diff;
+pub mod import;
 pub mod list;
 pub mod search;
 pub mod show;
 
 pub use add::handle_add;
+pub use backfill::handle_backfill;
 pub use edit::handle_edit;
 pub use export::handle_export;
+pub use export_diff::handle_export_diff;
+pub use import::handle_import;
 pub use list::handle_list;
 pub use search::handle_search;
 pub use show::handle_show;
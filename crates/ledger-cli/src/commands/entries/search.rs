@@ -1,13 +1,14 @@
 use chrono::Utc;
-use ledger_core::storage::StorageEngine;
+use ledger_core::storage::{EntryFilter, StorageEngine};
 
 use crate::app::{resolve_ledger_path, AppContext};
 use crate::cli::SearchArgs;
 use crate::helpers::{parse_duration, require_entry_type};
-use crate::output::{entries_json, entry_type_name_map};
+use crate::output::{entry_type_badges, entry_type_name_map, write_entries_json, EntrySummaries};
+use crate::ui::theme::symbols;
 use crate::ui::{
-    blank_line, entry_summary, header_with_context, highlight_matches, hint, print, short_id,
-    simple_table, truncate, Column, OutputMode,
+    badge, blank_line, header_with_context, highlight_matches, hint, print, short_id,
+    simple_table_cells, truncate, type_badge_cell, Badge, Column, OutputMode, TableCell,
 };
 
 const TABLE_SUMMARY_MAX: usize = 80;
@@ -20,24 +21,27 @@ pub fn handle_search(ctx: &AppContext, args: &SearchArgs) -> anyhow::Result<()>
 
     // Build entry type name map for display
     let name_map = entry_type_name_map(&storage)?;
+    let summaries = EntrySummaries::load(&storage)?;
 
-    let mut entries = storage.search_entries(&args.query)?;
+    let mut filter = EntryFilter::new();
     if let Some(ref t) = args.r#type {
         let entry_type_record = require_entry_type(&storage, t)?;
-        entries.retain(|entry| entry.entry_type_id == entry_type_record.id);
+        filter = filter.entry_type(entry_type_record.id);
+    }
+    if let Some(ref t) = args.tag {
+        filter = filter.tag(t.clone());
     }
     if let Some(ref l) = args.last {
         let window = parse_duration(l)?;
-        let since = Utc::now() - window;
-        entries.retain(|entry| entry.created_at >= since);
+        filter = filter.since(Utc::now() - window);
     }
     if !args.history {
-        let superseded = storage.superseded_entry_ids()?;
-        entries.retain(|entry| !superseded.contains(&entry.id));
+        filter = filter.latest_only();
     }
     if let Some(lim) = args.limit {
-        entries.truncate(lim);
+        filter = filter.limit(lim);
     }
+    let entries = storage.search_entries_filtered(&args.query, &filter)?;
 
     // Create UI context from flags
     let ui_ctx = ctx.ui_context(args.json, args.format.as_deref());
@@ -45,14 +49,38 @@ pub fn handle_search(ctx: &AppContext, args: &SearchArgs) -> anyhow::Result<()>
     // Build filter context for header
     let filter_context = build_filter_context(args);
 
+    if let Some(pending) = storage.pending_search_rebuild()? {
+        let message = format!(
+            "Search index rebuild is incomplete ({} of {} entries); run `ledger reindex` to finish",
+            pending.indexed, pending.total
+        );
+        if ctx.strict() {
+            ctx.warn(&message)?;
+        } else if !ctx.quiet() && ui_ctx.mode.is_pretty() {
+            eprintln!("{}", badge(&ui_ctx, Badge::Warn, &message));
+        }
+    }
+    if let Some(configured) = ctx.search_tokenizer()? {
+        let current = storage.search_tokenizer()?;
+        if configured != current {
+            let message = format!(
+                "Search index uses the {} tokenizer; run `ledger reindex` to switch to {}",
+                current, configured
+            );
+            if ctx.strict() {
+                ctx.warn(&message)?;
+            } else if !ctx.quiet() && ui_ctx.mode.is_pretty() {
+                eprintln!("{}", badge(&ui_ctx, Badge::Warn, &message));
+            }
+        }
+    }
+
     // Handle JSON output separately
     if ui_ctx.mode.is_json() {
         if args.format.is_some() {
             return Err(anyhow::anyhow!("--format cannot be used with --json"));
         }
-        let output = serde_json::to_string_pretty(&entries_json(&entries, &name_map))?;
-        println!("{}", output);
-        return Ok(());
+        return write_entries_json(std::io::stdout().lock(), entries.iter().map(Ok), &name_map);
     }
 
     // Empty result handling
@@ -89,6 +117,7 @@ pub fn handle_search(ctx: &AppContext, args: &SearchArgs) -> anyhow::Result<()>
     // Render entries
     match ui_ctx.mode {
         OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
             print(
                 &ui_ctx,
                 &header_with_context(
@@ -108,40 +137,41 @@ pub fn handle_search(ctx: &AppContext, args: &SearchArgs) -> anyhow::Result<()>
                 Column::new("Tags"),
             ];
 
-            let rows: Vec<Vec<String>> = entries
+            let badges = entry_type_badges(&storage)?;
+            let rows: Vec<Vec<TableCell>> = entries
                 .iter()
                 .map(|entry| {
-                    let type_name = name_map
+                    let type_cell = badges
                         .get(&entry.entry_type_id)
-                        .cloned()
-                        .unwrap_or_else(|| "unknown".to_string());
+                        .map(|badge| type_badge_cell(&ui_ctx, badge))
+                        .unwrap_or_else(|| TableCell::new("unknown"));
                     let tags_display = if entry.tags.is_empty() {
                         "-".to_string()
                     } else {
                         entry.tags.join(", ")
                     };
                     // Get summary and highlight matches
-                    let summary = truncate(&entry_summary(entry), TABLE_SUMMARY_MAX);
+                    let summary = truncate(&summaries.summary(entry), TABLE_SUMMARY_MAX);
                     let highlighted_summary =
                         highlight_matches(&summary, &args.query, ui_ctx.color);
                     vec![
-                        short_id(&entry.id),
-                        entry.created_at.format("%Y-%m-%d %H:%M").to_string(),
-                        type_name,
-                        highlighted_summary,
-                        tags_display,
+                        TableCell::new(short_id(&entry.id)),
+                        TableCell::new(entry.created_at.format("%Y-%m-%d %H:%M").to_string()),
+                        type_cell,
+                        TableCell::new(highlighted_summary),
+                        TableCell::new(tags_display),
                     ]
                 })
                 .collect();
 
-            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            print(&ui_ctx, &simple_table_cells(&ui_ctx, &columns, &rows));
             blank_line(&ui_ctx);
 
             // Actionable hints with first entry ID
             let first_id = entries.first().map(|e| short_id(&e.id));
             let hint_text = if let Some(id) = first_id {
                 format!(
-                    "{} entries. ledger show {}  \u{00B7}  ledger list",
+                    "{} entries. ledger show {}  {sep}  ledger list",
                     entries.len(),
                     id
                 )
@@ -157,7 +187,7 @@ pub fn handle_search(ctx: &AppContext, args: &SearchArgs) -> anyhow::Result<()>
                     .get(&entry.entry_type_id)
                     .cloned()
                     .unwrap_or_else(|| "unknown".to_string());
-                let summary = entry_summary(entry);
+                let summary = summaries.summary(entry);
                 let tags = if entry.tags.is_empty() {
                     "-".to_string()
                 } else {
@@ -184,6 +214,9 @@ fn build_filter_context(args: &SearchArgs) -> Option<String> {
     if let Some(ref t) = args.r#type {
         parts.push(format!("type: {}", t));
     }
+    if let Some(ref t) = args.tag {
+        parts.push(format!("tag: {}", t));
+    }
 
     Some(parts.join(", "))
 }
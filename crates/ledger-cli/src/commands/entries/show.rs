@@ -1,32 +1,104 @@
+use chrono::Utc;
+use ledger_core::storage::{AgeSqliteStorage, EntryOrigin, RelatedEntry, RelatedReason};
 use ledger_core::StorageEngine;
 use uuid::Uuid;
 
-use crate::app::{exit_not_found_with_hint, AppContext};
+use crate::app::{not_found_with_hint, AppContext};
 use crate::cli::ShowArgs;
-use crate::output::{entry_json, entry_type_name_map};
-use crate::ui::{blank_line, divider, header, kv, print, OutputMode};
+use crate::helpers::resolve_entry_id;
+use crate::output::{
+    entry_json, entry_type_badges, entry_type_name_map, DocumentFormat, EntrySummaries,
+    FieldFormatter,
+};
+use crate::ui::theme::{styled, styles};
+use crate::ui::{
+    blank_line, divider, format_bytes, header, kv, print, short_id, truncate, type_badge,
+    wrap_body, OutputMode,
+};
+
+/// Fields rendered in the header (or as the body) rather than as structured fields.
+const HEADER_FIELDS: &[&str] = &[
+    "body",
+    "notes",
+    "caption",
+    "url",
+    "title",
+    "latitude",
+    "longitude",
+];
+
+const RELATED_SUMMARY_MAX: usize = 60;
 
 pub fn handle_show(ctx: &AppContext, args: &ShowArgs) -> anyhow::Result<()> {
-    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let document_format = match args.format.as_deref() {
+        Some(name) => Some(DocumentFormat::parse(name).ok_or_else(|| {
+            anyhow::anyhow!("Unsupported show format: {} (use json, yaml or toml)", name)
+        })?),
+        None => None,
+    };
+    let (storage, passphrase) = ctx.open_storage(false)?;
 
-    let parsed =
-        Uuid::parse_str(&args.id).map_err(|e| anyhow::anyhow!("Invalid entry ID: {}", e))?;
-    let entry = storage.get_entry(&parsed)?.unwrap_or_else(|| {
-        exit_not_found_with_hint(
+    let entry = match resolve_entry_id(&storage, &args.id)? {
+        Some(id) => storage.get_entry(&id)?,
+        None => None,
+    };
+    let entry = entry.ok_or_else(|| {
+        not_found_with_hint(
             "Entry not found",
             "Hint: Run `ledger list --last 7d` to find entry IDs.",
         )
-    });
+    })?;
+
+    let attachments = storage.list_attachments(&entry.id)?;
+    let template = storage.entry_template(&entry.id)?;
+    let origin = if args.verbose {
+        storage.entry_origin(&entry.id)?
+    } else {
+        None
+    };
+    let related = storage.related_entries(&entry, args.related)?;
+    let log_id = storage.day_number(&entry.id)?;
+    let summaries = EntrySummaries::load(&storage)?;
 
     // Create UI context
     let ui_ctx = ctx.ui_context(args.json, None);
 
-    // Handle JSON output
-    if ui_ctx.mode.is_json() {
+    // Handle JSON (or YAML/TOML) output
+    if ui_ctx.mode.is_json() || document_format.is_some() {
         let name_map = entry_type_name_map(&storage)?;
-        let output = serde_json::to_string_pretty(&entry_json(&entry, &name_map))?;
-        println!("{}", output);
-        return Ok(());
+        let mut value = entry_json(&entry, &name_map);
+        value["attachments"] = serde_json::json!(attachments
+            .iter()
+            .map(|a| serde_json::json!({
+                "id": a.id,
+                "name": a.name,
+                "mime_type": a.mime_type,
+                "size": a.size,
+                "created_at": a.created_at,
+            }))
+            .collect::<Vec<_>>());
+        value["template"] = serde_json::json!(template);
+        value["log_id"] = serde_json::json!(log_id.map(|number| number.to_string()));
+        if args.verbose {
+            value["origin"] = serde_json::json!(origin);
+        }
+        value["related"] = serde_json::json!(related
+            .iter()
+            .map(|item| serde_json::json!({
+                "id": item.entry.id,
+                "entry_type": name_map
+                    .get(&item.entry.entry_type_id)
+                    .map(String::as_str)
+                    .unwrap_or("unknown"),
+                "created_at": item.entry.created_at,
+                "summary": summaries.summary(&item.entry),
+                "score": item.score,
+                "reasons": item.reasons,
+            }))
+            .collect::<Vec<_>>());
+        let format = document_format.unwrap_or(DocumentFormat::Json);
+        print!("{}", format.render(&value, "entry")?);
+        return record_view(ctx, storage, &passphrase, &entry.id);
     }
 
     // Get entry type name for display
@@ -36,26 +108,49 @@ pub fn handle_show(ctx: &AppContext, args: &ShowArgs) -> anyhow::Result<()> {
         .cloned()
         .unwrap_or_else(|| "unknown".to_string());
 
-    // Extract body from entry data
-    let body = entry
-        .data
-        .get("body")
-        .and_then(|v| v.as_str())
-        .map(String::from)
-        .unwrap_or_else(|| entry.data.to_string());
+    // Extract body from entry data (bookmarks keep their text in "notes",
+    // photos in "caption")
+    let text_body = ["body", "notes", "caption"]
+        .iter()
+        .find_map(|key| entry.data.get(*key).and_then(|v| v.as_str()))
+        .map(String::from);
+    let body = text_body.clone().unwrap_or_else(|| {
+        if entry.data.get("url").is_some() || entry.data.get("file").is_some() {
+            String::new()
+        } else {
+            entry.data.to_string()
+        }
+    });
+    let url = entry.data.get("url").and_then(|v| v.as_str());
+    let title = entry.data.get("title").and_then(|v| v.as_str());
+    let location = match (
+        entry.data.get("latitude").and_then(|v| v.as_f64()),
+        entry.data.get("longitude").and_then(|v| v.as_f64()),
+    ) {
+        (Some(lat), Some(lon)) => Some(format!("{:.6}, {:.6}", lat, lon)),
+        _ => None,
+    };
 
     match ui_ctx.mode {
         OutputMode::Pretty => {
+            let formatter = FieldFormatter::load(&storage, ctx.locale()?)?;
+            let type_display = entry_type_badges(&storage)?
+                .get(&entry.entry_type_id)
+                .map(|badge| type_badge(&ui_ctx, badge))
+                .unwrap_or_else(|| entry_type_name.clone());
             if !ctx.quiet() {
                 print(&ui_ctx, &header(&ui_ctx, "show", None));
                 blank_line(&ui_ctx);
                 print(&ui_ctx, &kv(&ui_ctx, "ID", &entry.id.to_string()));
+                if let Some(log_id) = log_id {
+                    print(&ui_ctx, &kv(&ui_ctx, "Log ID", &log_id.to_string()));
+                }
                 print(
                     &ui_ctx,
                     &kv(
                         &ui_ctx,
                         "Type",
-                        &format!("{} (v{})", entry_type_name, entry.schema_version),
+                        &format!("{} (v{})", type_display, entry.schema_version),
                     ),
                 );
                 print(
@@ -63,7 +158,7 @@ pub fn handle_show(ctx: &AppContext, args: &ShowArgs) -> anyhow::Result<()> {
                     &kv(
                         &ui_ctx,
                         "Created",
-                        &entry.created_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+                        &format!("{} UTC", formatter.locale().datetime(&entry.created_at)),
                     ),
                 );
                 print(
@@ -76,15 +171,92 @@ pub fn handle_show(ctx: &AppContext, args: &ShowArgs) -> anyhow::Result<()> {
                 if let Some(supersedes) = entry.supersedes {
                     print(&ui_ctx, &kv(&ui_ctx, "Supersedes", &supersedes.to_string()));
                 }
+                if let Some(ref template) = template {
+                    let label = match template.version {
+                        Some(version) => format!("{} (v{})", template.name, version),
+                        None => template.name.clone(),
+                    };
+                    print(&ui_ctx, &kv(&ui_ctx, "Template", &label));
+                }
+                if args.verbose {
+                    let label = origin
+                        .as_ref()
+                        .map(origin_label)
+                        .unwrap_or_else(|| "not recorded".to_string());
+                    print(&ui_ctx, &kv(&ui_ctx, "Origin", &label));
+                }
+                if let Some(url) = url {
+                    print(&ui_ctx, &kv(&ui_ctx, "URL", url));
+                }
+                if let Some(title) = title {
+                    print(&ui_ctx, &kv(&ui_ctx, "Title", title));
+                }
+                if let Some(ref location) = location {
+                    print(&ui_ctx, &kv(&ui_ctx, "Location", location));
+                }
+                for attachment in &attachments {
+                    print(
+                        &ui_ctx,
+                        &kv(
+                            &ui_ctx,
+                            "Attachment",
+                            &format!(
+                                "{} ({}, {})",
+                                attachment.name,
+                                attachment.mime_type,
+                                format_bytes(attachment.size)
+                            ),
+                        ),
+                    );
+                }
                 blank_line(&ui_ctx);
                 print(&ui_ctx, &divider(&ui_ctx));
                 blank_line(&ui_ctx);
             }
-            println!("{}", body);
+            match text_body {
+                // Only pretty output wraps; plain keeps the body byte-for-byte
+                Some(ref text) => match args.width.unwrap_or(ui_ctx.width) {
+                    0 => println!("{}", text),
+                    width => println!("{}", wrap_body(text, width).join("\n")),
+                },
+                None => {
+                    for (name, value) in formatter.fields(&entry, HEADER_FIELDS) {
+                        print(&ui_ctx, &kv(&ui_ctx, &name, &value));
+                    }
+                }
+            }
+            if !related.is_empty() && !ctx.quiet() {
+                blank_line(&ui_ctx);
+                print(&ui_ctx, &divider(&ui_ctx));
+                blank_line(&ui_ctx);
+                println!("{}", styled("Related", styles::bold(), ui_ctx.color));
+                for item in &related {
+                    let type_name = name_map
+                        .get(&item.entry.entry_type_id)
+                        .map(String::as_str)
+                        .unwrap_or("unknown");
+                    let reasons = styled(
+                        &format!("({})", reasons_label(item)),
+                        styles::dim(),
+                        ui_ctx.color,
+                    );
+                    println!(
+                        "  {}  {}  {}  {}  {}",
+                        short_id(&item.entry.id),
+                        formatter.locale().date(&item.entry.created_at.date_naive()),
+                        type_name,
+                        truncate(&summaries.summary(&item.entry), RELATED_SUMMARY_MAX),
+                        reasons
+                    );
+                }
+            }
         }
         OutputMode::Plain | OutputMode::Json => {
             if !ctx.quiet() {
                 println!("id={}", entry.id);
+                if let Some(log_id) = log_id {
+                    println!("log_id={}", log_id);
+                }
                 println!("type={}", entry_type_name);
                 println!("schema_version={}", entry.schema_version);
                 println!("created_at={}", entry.created_at.to_rfc3339());
@@ -95,10 +267,96 @@ pub fn handle_show(ctx: &AppContext, args: &ShowArgs) -> anyhow::Result<()> {
                 if let Some(supersedes) = entry.supersedes {
                     println!("supersedes={}", supersedes);
                 }
+                if let Some(ref template) = template {
+                    println!("template={}", template.name);
+                    if let Some(version) = template.version {
+                        println!("template_version={}", version);
+                    }
+                }
+                if let Some(ref origin) = origin {
+                    println!("origin_app={}", origin.app);
+                    for (key, value) in [
+                        ("origin_version", &origin.version),
+                        ("origin_command", &origin.command),
+                        ("origin_hostname", &origin.hostname),
+                    ] {
+                        if let Some(value) = value {
+                            println!("{}={}", key, value);
+                        }
+                    }
+                }
+                if let Some(url) = url {
+                    println!("url={}", url);
+                }
+                if let Some(title) = title {
+                    println!("title={}", title);
+                }
+                if let Some(ref location) = location {
+                    println!("location={}", location);
+                }
+                for attachment in &attachments {
+                    println!("attachment={} {}", attachment.id, attachment.name);
+                }
+                for item in &related {
+                    let reasons: Vec<String> = item.reasons.iter().map(reason_key).collect();
+                    println!("related={} reasons={}", item.entry.id, reasons.join(","));
+                }
             }
             println!("{}", body);
         }
     }
 
+    record_view(ctx, storage, &passphrase, &entry.id)
+}
+
+/// Where an entry came from, for people: `ledger-cli 0.1.0 (add) on desk`.
+fn origin_label(origin: &EntryOrigin) -> String {
+    let mut label = origin.app.clone();
+    if let Some(ref version) = origin.version {
+        label.push_str(&format!(" {}", version));
+    }
+    if let Some(ref command) = origin.command {
+        label.push_str(&format!(" ({})", command));
+    }
+    if let Some(ref hostname) = origin.hostname {
+        label.push_str(&format!(" on {}", hostname));
+    }
+    label
+}
+
+/// Why an entry is related, for people: `tag work, similar text`.
+fn reasons_label(item: &RelatedEntry) -> String {
+    item.reasons
+        .iter()
+        .map(|reason| match reason {
+            RelatedReason::Tag(tag) => format!("tag {}", tag),
+            RelatedReason::Composition(name) => format!("composition {}", name),
+            RelatedReason::Text => "similar text".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Why an entry is related, for scripts: `tag:work`, `text`.
+fn reason_key(reason: &RelatedReason) -> String {
+    match reason {
+        RelatedReason::Tag(tag) => format!("tag:{}", tag),
+        RelatedReason::Composition(name) => format!("composition:{}", name),
+        RelatedReason::Text => "text".to_string(),
+    }
+}
+
+/// Save the view time when `[privacy] track_access` is on.
+fn record_view(
+    ctx: &AppContext,
+    mut storage: AgeSqliteStorage,
+    passphrase: &str,
+    entry_id: &Uuid,
+) -> anyhow::Result<()> {
+    if !ctx.track_access()? {
+        return Ok(());
+    }
+    storage.record_access(entry_id, Utc::now())?;
+    ctx.save_storage(storage, passphrase)?;
     Ok(())
 }
@@ -0,0 +1,44 @@
+use ledger_core::storage::{HabitSchedule, StorageEngine};
+
+use crate::app::AppContext;
+use crate::cli::HabitAddArgs;
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, print, Badge, OutputMode};
+
+pub fn handle_add(ctx: &AppContext, args: &HabitAddArgs) -> anyhow::Result<()> {
+    let schedule = HabitSchedule::parse(&args.schedule).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid schedule: {} (use daily, weekdays, weekends, mon,wed,fri, or 3/week)",
+            args.schedule
+        )
+    })?;
+
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let device_id = storage.metadata()?.device_id;
+    let habit = storage.create_habit(&args.name, &schedule, device_id)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+                print(
+                    &ui_ctx,
+                    &badge(&ui_ctx, Badge::Ok, &format!("Added habit '{}'", habit.name)),
+                );
+                let context = format!(
+                    "Schedule: {}  {sep}  Mark it with `ledger habit done {}`",
+                    habit.schedule, habit.name
+                );
+                println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                println!("status=ok");
+                println!("name={}", habit.name);
+                println!("schedule={}", habit.schedule);
+            }
+        }
+    }
+    Ok(())
+}
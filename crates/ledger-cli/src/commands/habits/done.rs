@@ -0,0 +1,85 @@
+use chrono::{Local, NaiveDate};
+
+use ledger_core::storage::{NewEntry, StorageEngine};
+
+use crate::app::AppContext;
+use crate::cli::HabitDoneArgs;
+use crate::helpers::{ensure_builtin_entry_type, parse_datetime, require_entry_type};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, print, short_id, Badge, OutputMode};
+
+use super::status::completions;
+
+pub fn handle_done(ctx: &AppContext, args: &HabitDoneArgs) -> anyhow::Result<()> {
+    let date = match args.date {
+        Some(ref value) => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| anyhow::anyhow!("Invalid date: {} (use YYYY-MM-DD)", value))?,
+        None => Local::now().date_naive(),
+    };
+
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let habit = storage.get_habit(&args.name)?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Habit not found: {}\nHint: Define it with `ledger habit add {}`.",
+            args.name,
+            args.name
+        )
+    })?;
+    let device_id = storage.metadata()?.device_id;
+    ensure_builtin_entry_type(&mut storage, "habit", device_id)?;
+    let entry_type = require_entry_type(&storage, "habit")?;
+
+    // One completion per day is enough; a second would not change anything
+    let already = completions(&storage, entry_type.id)?
+        .get(&habit.name)
+        .is_some_and(|dates| dates.contains(&date));
+    let entry_id = if already {
+        None
+    } else {
+        let mut data = serde_json::json!({
+            "habit": habit.name,
+            "date": date.format("%Y-%m-%d").to_string(),
+        });
+        if let Some(ref note) = args.note {
+            data["notes"] = serde_json::json!(note);
+        }
+        let mut entry = NewEntry::new(entry_type.id, entry_type.version, data, device_id);
+        if let Some(ref value) = args.date {
+            entry = entry.with_created_at(parse_datetime(value)?);
+        }
+        let id = storage.insert_entry(&entry)?;
+        ctx.save_storage(storage, &passphrase)?;
+        Some(id)
+    };
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+                let (status, message) = match entry_id {
+                    Some(_) => (Badge::Ok, format!("Marked '{}' done", habit.name)),
+                    None => (
+                        Badge::Info,
+                        format!("'{}' was already done that day", habit.name),
+                    ),
+                };
+                print(&ui_ctx, &badge(&ui_ctx, status, &message));
+                let mut context = format!("Date: {}", date.format("%Y-%m-%d"));
+                if let Some(id) = entry_id {
+                    context.push_str(&format!("  {sep}  ID: {}", short_id(&id)));
+                }
+                println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                println!("habit={}", habit.name);
+                println!("date={}", date.format("%Y-%m-%d"));
+                match entry_id {
+                    Some(id) => println!("id={}", id),
+                    None => println!("status=already_done"),
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,9 @@
+pub mod add;
+pub mod done;
+pub mod remove;
+pub mod status;
+
+pub use add::handle_add;
+pub use done::handle_done;
+pub use remove::handle_remove;
+pub use status::handle_status;
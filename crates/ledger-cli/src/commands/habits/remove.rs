@@ -0,0 +1,30 @@
+use crate::app::AppContext;
+use crate::cli::HabitRemoveArgs;
+use crate::ui::{badge, print, Badge, OutputMode};
+
+pub fn handle_remove(ctx: &AppContext, args: &HabitRemoveArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    storage.delete_habit(&args.name)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Removed habit '{}' (completions kept)", args.name),
+                    ),
+                );
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                println!("status=ok");
+                println!("name={}", args.name);
+            }
+        }
+    }
+    Ok(())
+}
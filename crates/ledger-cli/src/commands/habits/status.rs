@@ -0,0 +1,276 @@
+//! Habit status: a completion matrix with streaks.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{Duration, Local, NaiveDate};
+use uuid::Uuid;
+
+use ledger_core::storage::{AgeSqliteStorage, EntryFilter, Habit, HabitSchedule, StorageEngine};
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::HabitStatusArgs;
+use crate::ui::{blank_line, header_with_context, hint, print, simple_table, Column, OutputMode};
+
+/// One day of a habit's matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Done,
+    Missed,
+    /// Not scheduled, before the habit existed, or today and not done yet
+    Off,
+}
+
+impl Cell {
+    fn symbol(self, unicode: bool) -> char {
+        match (self, unicode) {
+            (Cell::Done, true) => '\u{25A0}',
+            (Cell::Missed, true) => '\u{25A1}',
+            (Cell::Off, true) => '\u{00B7}',
+            (Cell::Done, false) => 'x',
+            (Cell::Missed, false) => '.',
+            (Cell::Off, false) => '-',
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Cell::Done => "done",
+            Cell::Missed => "missed",
+            Cell::Off => "off",
+        }
+    }
+}
+
+/// A habit with its matrix and streaks.
+struct HabitRow<'a> {
+    habit: &'a Habit,
+    cells: Vec<(NaiveDate, Cell)>,
+    current: u32,
+    longest: u32,
+}
+
+/// Completion dates per habit name, from current `habit` entries.
+pub fn completions(
+    storage: &AgeSqliteStorage,
+    entry_type_id: Uuid,
+) -> anyhow::Result<HashMap<String, BTreeSet<NaiveDate>>> {
+    let superseded = storage.superseded_entry_ids()?;
+    let mut out: HashMap<String, BTreeSet<NaiveDate>> = HashMap::new();
+    for entry in storage.list_entries(&EntryFilter::new().entry_type(entry_type_id))? {
+        if superseded.contains(&entry.id) {
+            continue;
+        }
+        let habit = entry.data.get("habit").and_then(|v| v.as_str());
+        let date = entry
+            .data
+            .get("date")
+            .and_then(|v| v.as_str())
+            .and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok());
+        if let (Some(habit), Some(date)) = (habit, date) {
+            out.entry(habit.to_string()).or_default().insert(date);
+        }
+    }
+    Ok(out)
+}
+
+pub fn handle_status(ctx: &AppContext, args: &HabitStatusArgs) -> anyhow::Result<()> {
+    if args.days == 0 {
+        return Err(anyhow::anyhow!("--days must be at least 1"));
+    }
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let habits = storage.list_habits()?;
+    let done = match storage.get_entry_type("habit")? {
+        Some(entry_type) => completions(&storage, entry_type.id)?,
+        None => HashMap::new(),
+    };
+
+    let today = Local::now().date_naive();
+    let first = today - Duration::days(i64::from(args.days) - 1);
+    let empty = BTreeSet::new();
+    let rows: Vec<HabitRow> = habits
+        .iter()
+        .map(|habit| {
+            let dates = done.get(&habit.name).unwrap_or(&empty);
+            let cells = first
+                .iter_days()
+                .take_while(|date| *date <= today)
+                .map(|date| (date, cell(habit, dates, date, today)))
+                .collect();
+            let (current, longest) = habit.schedule.streaks(dates, today);
+            HabitRow {
+                habit,
+                cells,
+                current,
+                longest,
+            }
+        })
+        .collect();
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output: Vec<serde_json::Value> = rows
+                .iter()
+                .map(|row| {
+                    serde_json::json!({
+                        "name": row.habit.name,
+                        "schedule": row.habit.schedule,
+                        "current_streak": row.current,
+                        "longest_streak": row.longest,
+                        "streak_unit": streak_unit(&row.habit.schedule),
+                        "days": row
+                            .cells
+                            .iter()
+                            .map(|(date, cell)| serde_json::json!({
+                                "date": date,
+                                "status": cell.name(),
+                            }))
+                            .collect::<Vec<_>>(),
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            let context = format!("last {} days from {}", args.days, first.format("%Y-%m-%d"));
+            print(
+                &ui_ctx,
+                &header_with_context(
+                    &ui_ctx,
+                    "habit status",
+                    Some(&context),
+                    ledger_path.as_deref(),
+                ),
+            );
+            blank_line(&ui_ctx);
+            if rows.is_empty() {
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        "No habits yet. Define one with `ledger habit add <name>`.",
+                    ),
+                );
+                return Ok(());
+            }
+            let columns = [
+                Column::new("Habit"),
+                Column::new("Schedule"),
+                Column::new(if ui_ctx.unicode {
+                    "Oldest \u{2192} today"
+                } else {
+                    "Oldest -> today"
+                }),
+                Column::new("Streak"),
+                Column::new("Best"),
+            ];
+            let table: Vec<Vec<String>> = rows
+                .iter()
+                .map(|row| {
+                    let unit = streak_unit(&row.habit.schedule);
+                    vec![
+                        row.habit.name.clone(),
+                        row.habit.schedule.to_string(),
+                        matrix(&row.cells, ui_ctx.unicode),
+                        format!("{} {}", row.current, unit),
+                        format!("{} {}", row.longest, unit),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &table));
+        }
+        OutputMode::Plain => {
+            println!("count={}", rows.len());
+            for row in &rows {
+                println!(
+                    "habit={} schedule={} current={} longest={} unit={} matrix={}",
+                    row.habit.name,
+                    row.habit.schedule,
+                    row.current,
+                    row.longest,
+                    streak_unit(&row.habit.schedule),
+                    matrix(&row.cells, false)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cell(habit: &Habit, done: &BTreeSet<NaiveDate>, date: NaiveDate, today: NaiveDate) -> Cell {
+    if done.contains(&date) {
+        return Cell::Done;
+    }
+    let before_habit = date < habit.created_at.with_timezone(&Local).date_naive();
+    match habit.schedule {
+        // Weekly counts have no fixed days, so a blank day is not a miss
+        HabitSchedule::PerWeek(_) => Cell::Off,
+        _ if before_habit || date == today => Cell::Off,
+        ref schedule if schedule.is_scheduled(date) => Cell::Missed,
+        _ => Cell::Off,
+    }
+}
+
+fn matrix(cells: &[(NaiveDate, Cell)], unicode: bool) -> String {
+    cells.iter().map(|(_, cell)| cell.symbol(unicode)).collect()
+}
+
+pub fn streak_unit(schedule: &HabitSchedule) -> &'static str {
+    match schedule {
+        HabitSchedule::PerWeek(_) => "weeks",
+        _ => "days",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn habit(schedule: &str) -> Habit {
+        Habit {
+            name: "stretch".to_string(),
+            schedule: HabitSchedule::parse(schedule).unwrap(),
+            created_at: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+            device_id: Uuid::new_v4(),
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_cells_mark_misses_on_scheduled_days_only() {
+        // 2024-03-04 is a Monday
+        let habit = habit("mon,wed,fri");
+        let done: BTreeSet<NaiveDate> = [date(4)].into_iter().collect();
+        let today = date(8);
+        let cells: Vec<Cell> = (4..=8)
+            .map(|d| cell(&habit, &done, date(d), today))
+            .collect();
+        assert_eq!(
+            cells,
+            vec![Cell::Done, Cell::Off, Cell::Missed, Cell::Off, Cell::Off]
+        );
+    }
+
+    #[test]
+    fn test_weekly_habits_never_show_misses() {
+        let habit = habit("2/week");
+        let done = BTreeSet::new();
+        assert_eq!(cell(&habit, &done, date(5), date(8)), Cell::Off);
+    }
+
+    #[test]
+    fn test_matrix_symbols() {
+        let cells = [
+            (date(4), Cell::Done),
+            (date(5), Cell::Missed),
+            (date(6), Cell::Off),
+        ];
+        assert_eq!(matrix(&cells, false), "x.-");
+        assert_eq!(matrix(&cells, true), "\u{25A0}\u{25A1}\u{00B7}");
+    }
+}
@@ -1,9 +1,8 @@
 use std::io::IsTerminal;
 
-use dialoguer::{theme::ColorfulTheme, Completion, Confirm, FuzzySelect, Input, Select};
-use ledger_core::storage::{AgeSqliteStorage, NewEntryType, StorageEngine};
+use dialoguer::{Completion, Confirm, FuzzySelect, Input, Select};
+use ledger_core::storage::{AgeSqliteStorage, StorageEngine};
 use ledger_core::VERSION;
-use uuid::Uuid;
 
 use crate::app::{device_keyfile_warning, resolve_config_path, AppContext};
 use crate::cache::ledger_hash;
@@ -12,12 +11,16 @@ use crate::config::{
     default_keyfile_path, default_ledger_path, write_config, KeyfileMode, LedgerConfig,
     SecurityTier,
 };
-use crate::helpers::prompt_init_passphrase;
+use crate::helpers::{
+    ensure_builtin_entry_type, missing_wait_flag, parse_search_tokenizer, prompt_init_passphrase,
+    split_editor_command, BUILTIN_ENTRY_TYPES,
+};
 use crate::security::{
     generate_key_bytes, key_bytes_to_passphrase, keychain_set, write_keyfile_encrypted,
     write_keyfile_plain,
 };
-use crate::ui::theme::{styled, styles};
+use crate::ui::prompt::prompt_theme;
+use crate::ui::theme::{styled, styles, symbols};
 use crate::ui::{badge, banner, hint, print, Badge, OutputMode, UiContext};
 
 /// Print a step indicator for the wizard flow.
@@ -43,7 +46,7 @@ fn print_option_help(ctx: &UiContext, text: &str) {
     println!("  {}", detail_styled);
 }
 
-fn parse_timezone(value: &str) -> anyhow::Result<Option<String>> {
+pub fn parse_timezone(value: &str) -> anyhow::Result<Option<String>> {
     let trimmed = value.trim();
     if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("auto") {
         return Ok(None);
@@ -76,17 +79,21 @@ fn command_exists(cmd: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn editor_command_name(value: &str) -> Option<&str> {
-    value.split_whitespace().next().filter(|s| !s.is_empty())
+fn editor_command_name(value: &str) -> Option<String> {
+    split_editor_command(value)
+        .ok()
+        .and_then(|words| words.into_iter().next())
 }
 
 fn available_editors() -> Vec<String> {
     let mut editors = Vec::new();
 
-    if let Ok(editor) = std::env::var("EDITOR") {
-        if let Some(cmd) = editor_command_name(&editor) {
-            if command_exists(cmd) {
-                editors.push(editor);
+    for key in ["VISUAL", "EDITOR"] {
+        if let Ok(editor) = std::env::var(key) {
+            if let Some(cmd) = editor_command_name(&editor) {
+                if command_exists(&cmd) && !editors.contains(&editor) {
+                    editors.push(editor);
+                }
             }
         }
     }
@@ -105,8 +112,17 @@ fn available_editors() -> Vec<String> {
     ];
 
     for candidate in candidates {
-        if command_exists(candidate) && !editors.iter().any(|e| e == candidate) {
-            editors.push(candidate.to_string());
+        // GUI editors are offered with the flag that makes them block
+        let editor = match missing_wait_flag(&[candidate.to_string()]) {
+            Some(flag) => format!("{} {}", candidate, flag),
+            None => candidate.to_string(),
+        };
+        if command_exists(candidate)
+            && !editors
+                .iter()
+                .any(|e| editor_command_name(e).as_deref() == Some(candidate))
+        {
+            editors.push(editor);
         }
     }
 
@@ -198,6 +214,11 @@ impl Completion for PathCompletion {
 pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
     let interactive = std::io::stdin().is_terminal();
     let effective_no_input = args.no_input || !interactive;
+    let search_tokenizer = args
+        .search_tokenizer
+        .as_deref()
+        .map(parse_search_tokenizer)
+        .transpose()?;
 
     // Create UI context for step indicators
     let ui_ctx = ctx.ui_context(false, None);
@@ -214,9 +235,10 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
     }
 
     if !ctx.quiet() && !effective_no_input && ui_ctx.mode.is_pretty() {
+        let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
         // Print wizard header
         let header = styled("Ledger", styles::bold(), ui_ctx.color);
-        println!("{} \u{00B7} init\n", header);
+        println!("{} {sep} init\n", header);
     }
 
     let default_ledger = default_ledger_path()?;
@@ -233,7 +255,7 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
                     "Choose location",
                     Some("Set where your encrypted ledger file will live."),
                 );
-                let theme = ColorfulTheme::default();
+                let theme = prompt_theme(&ui_ctx);
                 let input: String = Input::with_theme(&theme)
                     .with_prompt("Ledger file location")
                     .completion_with(&path_completion)
@@ -306,7 +328,7 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
             "Passphrase + encrypted keyfile",
             "Device keyfile only (reduced security)",
         ];
-        let theme = ColorfulTheme::default();
+        let theme = prompt_theme(&ui_ctx);
         let choice = Select::with_theme(&theme)
             .with_prompt("Security level")
             .default(0)
@@ -340,7 +362,7 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
             "Advanced settings",
             Some("Set preferences and storage defaults."),
         );
-        let theme = ColorfulTheme::default();
+        let theme = prompt_theme(&ui_ctx);
 
         if timezone.is_none() {
             print_option_help(&ui_ctx, "Select the timezone used for entry timestamps.");
@@ -374,7 +396,7 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
                 let default_index = editor_command_name(&default_editor)
                     .and_then(|name| {
                         editor_choices.iter().position(|choice| {
-                            editor_command_name(choice).unwrap_or(choice) == name
+                            editor_command_name(choice).as_deref() == Some(name.as_str())
                         })
                     })
                     .unwrap_or(0);
@@ -484,10 +506,15 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
 
     let device_id = AgeSqliteStorage::create(&ledger_path, &ledger_passphrase)?;
     let mut storage = AgeSqliteStorage::open(&ledger_path, &ledger_passphrase)?;
-    ensure_journal_entry_type(&mut storage, device_id)?;
+    for name in BUILTIN_ENTRY_TYPES {
+        ensure_builtin_entry_type(&mut storage, name, device_id)?;
+    }
+    if let Some(tokenizer) = search_tokenizer {
+        storage.rebuild_search_index(tokenizer)?;
+    }
     storage.close(&ledger_passphrase)?;
 
-    let config = LedgerConfig::new(
+    let mut config = LedgerConfig::new(
         ledger_path.clone(),
         tier,
         passphrase_cache_ttl_seconds,
@@ -496,6 +523,7 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
         timezone,
         editor,
     );
+    config.search.tokenizer = search_tokenizer.map(|tokenizer| tokenizer.to_string());
     write_config(&config_path, &config)?;
 
     if matches!(tier, SecurityTier::PassphraseKeychain) {
@@ -506,6 +534,7 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
     if !ctx.quiet() {
         match ui_ctx.mode {
             OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
                 println!();
                 print(
                     &ui_ctx,
@@ -529,7 +558,7 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
                         &hint(
                             &ui_ctx,
                             &format!(
-                                "Passphrase caching keeps your passphrase in memory for {} seconds.",
+                                "Passphrase caching keeps this ledger unlocked for {} seconds.",
                                 passphrase_cache_ttl_seconds
                             ),
                         ),
@@ -541,7 +570,7 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
                     &ui_ctx,
                     &hint(
                         &ui_ctx,
-                        "ledger add journal  \u{00B7}  ledger list  \u{00B7}  ledger --help",
+                        &format!("ledger add journal  {sep}  ledger list  {sep}  ledger --help"),
                     ),
                 );
             }
@@ -560,23 +589,9 @@ pub fn handle_init(ctx: &AppContext, args: &InitArgs) -> anyhow::Result<()> {
 }
 
 fn default_editor() -> String {
-    std::env::var("EDITOR").unwrap_or_else(|_| "nano".to_string())
-}
-
-fn ensure_journal_entry_type(
-    storage: &mut AgeSqliteStorage,
-    device_id: Uuid,
-) -> anyhow::Result<()> {
-    if storage.get_entry_type("journal")?.is_some() {
-        return Ok(());
-    }
-
-    let schema = serde_json::json!({
-        "fields": [
-            {"name": "body", "type": "text", "required": true}
-        ]
-    });
-    let entry_type = NewEntryType::new("journal", schema, device_id);
-    storage.create_entry_type(&entry_type)?;
-    Ok(())
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "nano".to_string())
 }
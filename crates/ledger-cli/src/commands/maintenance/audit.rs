@@ -0,0 +1,382 @@
+//! Security audit: file permissions, security tier, and passphrase exposure.
+
+use std::path::{Path, PathBuf};
+
+use crate::app::{resolve_config_path, resolve_ledger_path, AppContext};
+use crate::cache::cache_socket_path;
+use crate::cli::AuditSecurityArgs;
+use crate::config::SecurityTier;
+use crate::ui::theme::{styled, styles};
+use crate::ui::{badge, blank_line, header, print, Badge, OutputMode};
+
+/// How urgently a finding should be fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    High,
+    Medium,
+    Low,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::High => "high",
+            Severity::Medium => "medium",
+            Severity::Low => "low",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Finding {
+    severity: Severity,
+    check: &'static str,
+    message: String,
+    remediation: String,
+}
+
+/// Backup file extensions looked for next to the ledger.
+const BACKUP_EXTENSIONS: &[&str] = &["ledger", "bak", "backup"];
+
+pub fn handle_audit_security(ctx: &AppContext, args: &AuditSecurityArgs) -> anyhow::Result<()> {
+    let mut findings = Vec::new();
+
+    let config_path = resolve_config_path()?;
+    if config_path.exists() {
+        findings.extend(check_permissions(&config_path, FileKind::Config));
+    }
+
+    let security = ctx.security_config()?;
+    if let Ok(ledger) = resolve_ledger_path(ctx.cli()) {
+        let ledger_path = PathBuf::from(ledger);
+        if ledger_path.exists() {
+            findings.extend(check_permissions(&ledger_path, FileKind::Ledger));
+            for backup in sibling_backups(&ledger_path) {
+                findings.extend(check_permissions(&backup, FileKind::Backup));
+            }
+        }
+    }
+    for backup in &args.backup {
+        let path = PathBuf::from(backup);
+        let files = if path.is_dir() {
+            std::fs::read_dir(&path)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.is_file())
+                .collect()
+        } else {
+            vec![path]
+        };
+        for file in files {
+            findings.extend(check_permissions(&file, FileKind::Backup));
+        }
+    }
+
+    let device_keyfile = matches!(security.tier, SecurityTier::DeviceKeyfile);
+    if device_keyfile {
+        findings.push(Finding {
+            severity: Severity::Medium,
+            check: "security_tier",
+            message: "device_keyfile stores an unencrypted key on disk".to_string(),
+            remediation: "Re-initialize with the passphrase_keyfile or passphrase_keychain tier"
+                .to_string(),
+        });
+    }
+    if let Some(ref keyfile) = security.keyfile_path {
+        if keyfile.exists() {
+            let kind = if device_keyfile {
+                FileKind::PlainKeyfile
+            } else {
+                FileKind::Keyfile
+            };
+            findings.extend(check_permissions(keyfile, kind));
+        }
+    }
+
+    if let Ok(socket) = cache_socket_path() {
+        if socket.exists() {
+            findings.extend(check_permissions(&socket, FileKind::CacheSocket));
+        }
+    }
+
+    if std::env::var("LEDGER_PASSPHRASE").is_ok_and(|v| !v.trim().is_empty()) {
+        findings.push(Finding {
+            severity: Severity::Medium,
+            check: "passphrase_env",
+            message: "LEDGER_PASSPHRASE is set in the environment".to_string(),
+            remediation:
+                "Unset it and use the keychain tier or passphrase cache; child processes inherit it"
+                    .to_string(),
+        });
+    }
+    for history in history_files() {
+        if let Ok(bytes) = std::fs::read(&history) {
+            if history_mentions_passphrase(&String::from_utf8_lossy(&bytes)) {
+                findings.push(Finding {
+                    severity: Severity::High,
+                    check: "shell_history",
+                    message: format!(
+                        "{} contains a LEDGER_PASSPHRASE assignment",
+                        history.display()
+                    ),
+                    remediation:
+                        "Remove those lines from the history file and change your passphrase"
+                            .to_string(),
+                });
+            }
+        }
+    }
+
+    findings.sort_by_key(|finding| finding.severity);
+    let high = findings
+        .iter()
+        .filter(|finding| finding.severity == Severity::High)
+        .count();
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "status": if findings.is_empty() { "ok" } else { "issues" },
+                "findings": findings.iter().map(|finding| serde_json::json!({
+                    "severity": finding.severity.as_str(),
+                    "check": finding.check,
+                    "message": finding.message,
+                    "remediation": finding.remediation,
+                })).collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Plain => {
+            for finding in &findings {
+                println!(
+                    "finding={} {} {}",
+                    finding.severity.as_str(),
+                    finding.check,
+                    finding.message
+                );
+                println!("remediation={}", finding.remediation);
+            }
+            println!(
+                "status={}",
+                if findings.is_empty() { "ok" } else { "issues" }
+            );
+        }
+        OutputMode::Pretty => {
+            if !ctx.quiet() || !findings.is_empty() {
+                print(&ui_ctx, &header(&ui_ctx, "audit-security", None));
+                blank_line(&ui_ctx);
+            }
+            if findings.is_empty() && !ctx.quiet() {
+                print(
+                    &ui_ctx,
+                    &badge(&ui_ctx, Badge::Ok, "No security issues found"),
+                );
+            }
+            for (index, finding) in findings.iter().enumerate() {
+                let kind = match finding.severity {
+                    Severity::High => Badge::Err,
+                    Severity::Medium => Badge::Warn,
+                    Severity::Low => Badge::Info,
+                };
+                print(&ui_ctx, &badge(&ui_ctx, kind, &finding.message));
+                let step = format!("{}. {}", index + 1, finding.remediation);
+                println!("  {}", styled(&step, styles::dim(), ui_ctx.color));
+            }
+        }
+    }
+
+    if high > 0 {
+        return Err(anyhow::anyhow!(
+            "Security audit found {} high-priority issue{}",
+            high,
+            if high == 1 { "" } else { "s" }
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileKind {
+    Config,
+    Ledger,
+    Backup,
+    Keyfile,
+    PlainKeyfile,
+    CacheSocket,
+}
+
+impl FileKind {
+    fn label(self) -> &'static str {
+        match self {
+            FileKind::Config => "config",
+            FileKind::Ledger => "ledger",
+            FileKind::Backup => "backup",
+            FileKind::Keyfile => "keyfile",
+            FileKind::PlainKeyfile => "keyfile",
+            FileKind::CacheSocket => "cache socket",
+        }
+    }
+
+    fn check(self) -> &'static str {
+        match self {
+            FileKind::Config => "config_permissions",
+            FileKind::Ledger => "ledger_permissions",
+            FileKind::Backup => "backup_permissions",
+            FileKind::Keyfile | FileKind::PlainKeyfile => "keyfile_permissions",
+            FileKind::CacheSocket => "cache_socket_permissions",
+        }
+    }
+}
+
+#[cfg(unix)]
+fn check_permissions(path: &Path, kind: FileKind) -> Option<Finding> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = std::fs::metadata(path).ok()?.permissions().mode() & 0o777;
+    permission_finding(path, kind, mode)
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path, _kind: FileKind) -> Option<Finding> {
+    None
+}
+
+/// Classify a file mode. Anything beyond owner access is reported.
+fn permission_finding(path: &Path, kind: FileKind, mode: u32) -> Option<Finding> {
+    if mode & 0o077 == 0 {
+        return None;
+    }
+    let writable_by_others = mode & 0o022 != 0;
+    let severity = match kind {
+        // Whoever can read these can unlock the ledger
+        FileKind::PlainKeyfile | FileKind::CacheSocket => Severity::High,
+        // A writable config can redirect the ledger or run commands
+        FileKind::Config if writable_by_others => Severity::High,
+        FileKind::Config => Severity::Low,
+        // Encrypted at rest, but copies enable offline guessing
+        FileKind::Ledger | FileKind::Backup | FileKind::Keyfile => Severity::Medium,
+    };
+    Some(Finding {
+        severity,
+        check: kind.check(),
+        message: format!(
+            "{} {} is accessible by other users (mode {:03o})",
+            kind.label(),
+            path.display(),
+            mode
+        ),
+        remediation: format!("chmod 600 {}", path.display()),
+    })
+}
+
+/// Backups next to the ledger (`*.ledger`, `*.bak`, `*.backup`), excluding the ledger.
+fn sibling_backups(ledger_path: &Path) -> Vec<PathBuf> {
+    let Some(dir) = ledger_path.parent() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path != ledger_path && path.is_file())
+        .filter(|path| {
+            path.extension()
+                .map(|ext| BACKUP_EXTENSIONS.contains(&ext.to_string_lossy().as_ref()))
+                .unwrap_or(false)
+        })
+        .collect();
+    backups.sort();
+    backups
+}
+
+fn history_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Ok(value) = std::env::var("HISTFILE") {
+        if !value.trim().is_empty() {
+            files.push(PathBuf::from(value));
+        }
+    }
+    if let Ok(home) = std::env::var("HOME") {
+        let home = PathBuf::from(home);
+        for name in [
+            ".bash_history",
+            ".zsh_history",
+            ".local/share/fish/fish_history",
+        ] {
+            files.push(home.join(name));
+        }
+    }
+    files.sort();
+    files.dedup();
+    files.retain(|path| path.exists());
+    files
+}
+
+/// True if a history line assigns a literal LEDGER_PASSPHRASE value.
+fn history_mentions_passphrase(contents: &str) -> bool {
+    contents.lines().any(|line| {
+        line.match_indices("LEDGER_PASSPHRASE")
+            .any(|(index, name)| {
+                let rest = line[index + name.len()..].trim_start_matches(['=', ' ', '"', '\'']);
+                let assigned = line[index + name.len()..].trim_start().starts_with('=')
+                    || line[..index].trim_end().ends_with("set -x")
+                    || line[..index].trim_end().ends_with("set -gx");
+                // `$(...)` or `$VAR` means the secret came from elsewhere
+                assigned && !rest.is_empty() && !rest.starts_with('$')
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_finding_severity() {
+        let path = Path::new("/tmp/x");
+        assert!(permission_finding(path, FileKind::Ledger, 0o600).is_none());
+        assert_eq!(
+            permission_finding(path, FileKind::Ledger, 0o644)
+                .unwrap()
+                .severity,
+            Severity::Medium
+        );
+        assert_eq!(
+            permission_finding(path, FileKind::PlainKeyfile, 0o640)
+                .unwrap()
+                .severity,
+            Severity::High
+        );
+        assert_eq!(
+            permission_finding(path, FileKind::Config, 0o644)
+                .unwrap()
+                .severity,
+            Severity::Low
+        );
+        assert_eq!(
+            permission_finding(path, FileKind::Config, 0o666)
+                .unwrap()
+                .severity,
+            Severity::High
+        );
+    }
+
+    #[test]
+    fn test_history_mentions_passphrase() {
+        assert!(history_mentions_passphrase(
+            "ls\nLEDGER_PASSPHRASE=hunter2 ledger list\n"
+        ));
+        assert!(history_mentions_passphrase(
+            ": 1700000000:0;export LEDGER_PASSPHRASE='hunter2'"
+        ));
+        assert!(history_mentions_passphrase(
+            "set -x LEDGER_PASSPHRASE hunter2"
+        ));
+        assert!(!history_mentions_passphrase(
+            "LEDGER_PASSPHRASE=$(pass show ledger) ledger list"
+        ));
+        assert!(!history_mentions_passphrase("unset LEDGER_PASSPHRASE"));
+        assert!(!history_mentions_passphrase("echo $LEDGER_PASSPHRASE"));
+    }
+}
@@ -1,13 +1,39 @@
 use std::io::IsTerminal;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
 use crate::app::{missing_ledger_message, resolve_ledger_path, AppContext};
 use crate::cli::BackupArgs;
 use crate::ui::progress::Spinner;
-use crate::ui::theme::{styled, styles};
+use crate::ui::theme::{styled, styles, symbols};
 use crate::ui::{badge, blank_line, format_bytes, hint, print, Badge, OutputMode};
 
+/// Last successful backup, stored in `<ledger>.backup` next to the ledger so
+/// `check --healthcheck` can judge its age without unlocking anything.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupRecord {
+    pub at: DateTime<Utc>,
+    pub destination: String,
+    pub bytes: u64,
+}
+
+/// Path of the backup record for a ledger.
+pub fn backup_record_path(ledger_path: &Path) -> PathBuf {
+    let mut name = ledger_path.as_os_str().to_os_string();
+    name.push(".backup");
+    PathBuf::from(name)
+}
+
+/// Read the last backup record; missing or unreadable means none.
+pub fn read_backup_record(ledger_path: &Path) -> Option<BackupRecord> {
+    std::fs::read_to_string(backup_record_path(ledger_path))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+}
+
 pub fn handle_backup(ctx: &AppContext, args: &BackupArgs) -> anyhow::Result<()> {
     let source = resolve_ledger_path(ctx.cli())?;
     let source_path = Path::new(&source);
@@ -55,12 +81,28 @@ pub fn handle_backup(ctx: &AppContext, args: &BackupArgs) -> anyhow::Result<()>
         return Err(anyhow::anyhow!("Backup failed: zero bytes written"));
     }
 
+    let record = BackupRecord {
+        at: Utc::now(),
+        destination: args.destination.clone(),
+        bytes,
+    };
+    if let Err(err) = std::fs::write(
+        backup_record_path(source_path),
+        serde_json::to_string(&record)?,
+    ) {
+        ctx.warn(&format!(
+            "Backup written, but its time could not be recorded: {}",
+            err
+        ))?;
+    }
+
     if !ctx.quiet() {
         match ui_ctx.mode {
             OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
                 // Context line with destination and size
                 let context = format!(
-                    "Path: {}  \u{00B7}  Size: {}",
+                    "Path: {}  {sep}  Size: {}",
                     args.destination,
                     format_bytes(bytes)
                 );
@@ -70,7 +112,7 @@ pub fn handle_backup(ctx: &AppContext, args: &BackupArgs) -> anyhow::Result<()>
                 blank_line(&ui_ctx);
                 print(
                     &ui_ctx,
-                    &hint(&ui_ctx, "ledger doctor  \u{00B7}  ledger check"),
+                    &hint(&ui_ctx, &format!("ledger doctor  {sep}  ledger check")),
                 );
             }
             OutputMode::Plain | OutputMode::Json => {
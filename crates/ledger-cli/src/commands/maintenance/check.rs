@@ -1,68 +1,380 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use ledger_core::storage::{IntegrityIssue, IntegritySeverity};
 use ledger_core::StorageEngine;
+use serde::{Deserialize, Serialize};
 
-use crate::app::AppContext;
-use crate::ui::{badge, hint, print, Badge, OutputMode, StepList};
+use crate::app::{resolve_config_path, resolve_ledger_path, AppContext};
+use crate::cli::CheckArgs;
+use crate::commands::maintenance::backup::read_backup_record;
+use crate::config::read_config;
+use crate::errors::CliError;
+use crate::ui::{badge, hint, print, short_id, Badge, OutputMode, StepList};
 
-pub fn handle_check(ctx: &AppContext) -> anyhow::Result<()> {
-    let (storage, _passphrase) = ctx.open_storage(false)?;
+/// Check steps, with the issue codes each one reports.
+const CHECKS: &[(&str, &[&str])] = &[
+    ("foreign keys", &["foreign_key_violation"]),
+    (
+        "entries FTS",
+        &["fts_missing", "fts_orphaned", "fts_rebuild_pending"],
+    ),
+    (
+        "composition memberships",
+        &["membership_dangling", "membership_removed_before_added"],
+    ),
+    ("entry type versions", &["entry_type_active_version"]),
+    ("metadata keys", &["metadata_missing_keys"]),
+];
 
-    let ui_ctx = ctx.ui_context(false, None);
+/// Most ids shown per issue in pretty output.
+const MAX_LISTED_IDS: usize = 5;
 
-    match storage.check_integrity() {
-        Ok(()) => {
-            if !ctx.quiet() {
-                match ui_ctx.mode {
-                    OutputMode::Pretty => {
-                        let mut steps = StepList::new(
-                            &ui_ctx,
-                            &[
-                                "foreign keys",
-                                "entries FTS",
-                                "entry type versions",
-                                "metadata keys",
-                            ],
-                        );
-                        steps.start("Integrity check");
-                        steps.ok();
-                        steps.ok();
-                        steps.ok();
-                        steps.ok();
-                        println!();
-                        print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, "All checks passed"));
-                    }
-                    OutputMode::Plain | OutputMode::Json => {
-                        println!("check=foreign_keys ok");
-                        println!("check=entries_fts ok");
-                        println!("check=entry_type_versions ok");
-                        println!("check=metadata_keys ok");
-                        println!("status=ok");
-                    }
-                }
-            }
+/// Outcome of the last integrity check, stored in `<ledger>.check` next to
+/// the ledger so `status` can show it without unlocking. Affected ids are
+/// left out; the file is not encrypted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckRecord {
+    pub at: DateTime<Utc>,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<CheckedIssue>,
+}
+
+/// One issue of a recorded check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckedIssue {
+    pub code: String,
+    pub severity: IntegritySeverity,
+    pub message: String,
+    pub count: usize,
+}
+
+/// Path of the check record for a ledger.
+pub fn check_record_path(ledger_path: &Path) -> PathBuf {
+    let mut name = ledger_path.as_os_str().to_os_string();
+    name.push(".check");
+    PathBuf::from(name)
+}
+
+/// Read the last check record; missing or unreadable means none.
+pub fn read_check_record(ledger_path: &Path) -> Option<CheckRecord> {
+    std::fs::read_to_string(check_record_path(ledger_path))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+}
+
+/// Record the outcome of a check for `status`.
+fn write_check_record(ctx: &AppContext, issues: &[IntegrityIssue]) -> anyhow::Result<()> {
+    let ledger_path = resolve_ledger_path(ctx.cli())?;
+    let errors = issues.iter().filter(|issue| issue.is_error()).count();
+    let record = CheckRecord {
+        at: Utc::now(),
+        errors,
+        warnings: issues.len() - errors,
+        issues: issues
+            .iter()
+            .map(|issue| CheckedIssue {
+                code: issue.code.clone(),
+                severity: issue.severity,
+                message: issue.message.clone(),
+                count: issue.ids.len(),
+            })
+            .collect(),
+    };
+    if let Err(err) = std::fs::write(
+        check_record_path(Path::new(&ledger_path)),
+        serde_json::to_string(&record)?,
+    ) {
+        ctx.warn(&format!(
+            "Check finished, but its result could not be recorded: {}",
+            err
+        ))?;
+    }
+    Ok(())
+}
+
+pub fn handle_check(ctx: &AppContext, args: &CheckArgs) -> anyhow::Result<()> {
+    if args.healthcheck {
+        return handle_healthcheck(ctx, args);
+    }
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let issues = storage.check_integrity()?;
+    write_check_record(ctx, &issues)?;
+    let errors = issues.iter().filter(|issue| issue.is_error()).count();
+    let warnings = issues.len() - errors;
+    let status = if errors > 0 {
+        "failed"
+    } else if warnings > 0 {
+        "warn"
+    } else {
+        "ok"
+    };
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "status": status,
+                "errors": errors,
+                "warnings": warnings,
+                "issues": issues,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
         }
-        Err(err) => {
-            match ui_ctx.mode {
-                OutputMode::Pretty => {
+        OutputMode::Pretty => {
+            if !ctx.quiet() || errors > 0 {
+                let mut steps = StepList::new(
+                    &ui_ctx,
+                    &CHECKS.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+                );
+                steps.start("Integrity check");
+                for (_, codes) in CHECKS {
+                    steps.complete(step_badge(&issues, codes));
+                }
+                println!();
+                for issue in &issues {
+                    let kind = if issue.is_error() {
+                        Badge::Err
+                    } else {
+                        Badge::Warn
+                    };
                     print(
                         &ui_ctx,
-                        &badge(&ui_ctx, Badge::Err, "Integrity check failed"),
+                        &badge(
+                            &ui_ctx,
+                            kind,
+                            &format!("{} ({})", issue.message, issue.code),
+                        ),
                     );
-                    eprintln!("Error: {}", err);
-                    print(
+                    if !issue.ids.is_empty() {
+                        println!("  ids: {}", format_ids(&issue.ids));
+                    }
+                }
+                if !issues.is_empty() {
+                    println!();
+                }
+                match status {
+                    "failed" => {
+                        print(
+                            &ui_ctx,
+                            &badge(&ui_ctx, Badge::Err, "Integrity check failed"),
+                        );
+                        print(
+                            &ui_ctx,
+                            &hint(
+                                &ui_ctx,
+                                "Restore from a backup or export data before retrying.",
+                            ),
+                        );
+                    }
+                    "warn" => print(
                         &ui_ctx,
-                        &hint(
+                        &badge(
                             &ui_ctx,
-                            "Restore from a backup or export data before retrying.",
+                            Badge::Warn,
+                            &format!("Passed with {} warning(s)", warnings),
                         ),
+                    ),
+                    _ => print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, "All checks passed")),
+                }
+            }
+        }
+        OutputMode::Plain => {
+            if !ctx.quiet() || errors > 0 {
+                let mut steps = StepList::new(
+                    &ui_ctx,
+                    &CHECKS.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+                );
+                for (_, codes) in CHECKS {
+                    steps.complete(step_badge(&issues, codes));
+                }
+                for issue in issues.iter().filter(|issue| !issue.is_error()) {
+                    println!(
+                        "warning={} count={} ids={}",
+                        issue.code,
+                        issue.ids.len(),
+                        issue.ids.join(",")
                     );
                 }
-                OutputMode::Plain | OutputMode::Json => {
+                if errors > 0 {
                     eprintln!("status=failed");
-                    eprintln!("error={}", err);
+                    for issue in issues.iter().filter(|issue| issue.is_error()) {
+                        eprintln!(
+                            "error={} code={} ids={}",
+                            issue.message,
+                            issue.code,
+                            issue.ids.join(",")
+                        );
+                    }
+                } else {
+                    println!("status={}", status);
                 }
             }
-            return Err(anyhow::anyhow!("Integrity check failed"));
         }
     }
+
+    if errors > 0 {
+        return Err(anyhow::anyhow!("Integrity check failed"));
+    }
+    Ok(())
+}
+
+/// `check --healthcheck`: one status line and an exit code for cron jobs and
+/// systemd timers.
+///
+/// Never prompts: unlocking relies on the passphrase cache, keychain,
+/// keyfile or `LEDGER_PASSPHRASE`. Unlock failures keep their usual exit
+/// code; integrity errors exit 6 and a missing or stale backup exits 8.
+/// Integrity warnings report `status=warn` but still exit 0.
+fn handle_healthcheck(ctx: &AppContext, args: &CheckArgs) -> anyhow::Result<()> {
+    let ledger_path = resolve_ledger_path(ctx.cli())?;
+    let max_age_hours = resolve_config_path()
+        .ok()
+        .and_then(|path| read_config(&path).ok())
+        .and_then(|config| config.backup.max_age_hours);
+    let backup = read_backup_record(Path::new(&ledger_path));
+    let backup_age_hours = backup
+        .as_ref()
+        .map(|record| (Utc::now() - record.at).num_hours());
+
+    let mut line = vec![];
+    let issues = match ctx.open_storage(true) {
+        Ok((storage, _passphrase)) => storage.check_integrity(),
+        Err(err) => {
+            line.push(("status", "failed".to_string()));
+            line.push(("reason", "unlock".to_string()));
+            print_health(args, &line);
+            return Err(err);
+        }
+    };
+    let issues = match issues {
+        Ok(issues) => issues,
+        Err(err) => {
+            line.push(("status", "failed".to_string()));
+            line.push(("reason", "integrity".to_string()));
+            print_health(args, &line);
+            return Err(err.into());
+        }
+    };
+    write_check_record(ctx, &issues)?;
+    let errors = issues.iter().filter(|issue| issue.is_error()).count();
+    let integrity = if errors > 0 {
+        "failed"
+    } else if issues.is_empty() {
+        "ok"
+    } else {
+        "warn"
+    };
+    let stale = match (max_age_hours, backup_age_hours) {
+        (Some(max), Some(age)) => age > i64::try_from(max).unwrap_or(i64::MAX),
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    let (status, reason) = if errors > 0 {
+        ("failed", Some("integrity"))
+    } else if stale {
+        let reason = if backup.is_some() {
+            "stale_backup"
+        } else {
+            "no_backup"
+        };
+        ("failed", Some(reason))
+    } else {
+        (integrity, None)
+    };
+
+    line.push(("status", status.to_string()));
+    if let Some(reason) = reason {
+        line.push(("reason", reason.to_string()));
+    }
+    line.push(("integrity", integrity.to_string()));
+    line.push((
+        "last_backup",
+        backup
+            .as_ref()
+            .map(|record| record.at.to_rfc3339())
+            .unwrap_or_else(|| "none".to_string()),
+    ));
+    if let Some(age) = backup_age_hours {
+        line.push(("backup_age_hours", age.to_string()));
+    }
+    if let Some(max) = max_age_hours {
+        line.push(("max_age_hours", max.to_string()));
+    }
+    print_health(args, &line);
+
+    if errors > 0 {
+        return Err(CliError::IntegrityFailed(format!(
+            "Integrity check failed with {} error(s)\nHint: Run `ledger check` for details.",
+            errors
+        ))
+        .into());
+    }
+    if stale {
+        let message = match backup_age_hours {
+            Some(age) => format!("Last backup is {}h old", age),
+            None => "No backup recorded for this ledger".to_string(),
+        };
+        return Err(CliError::BackupStale(format!(
+            "{} (max {}h)\nHint: Run `ledger backup <DEST>`.",
+            message,
+            max_age_hours.unwrap_or_default()
+        ))
+        .into());
+    }
     Ok(())
 }
+
+/// Print the health fields as one `key=value` line, or one JSON object.
+fn print_health(args: &CheckArgs, fields: &[(&str, String)]) {
+    if args.json {
+        let object: serde_json::Map<String, serde_json::Value> = fields
+            .iter()
+            .map(|(key, value)| {
+                let value = value
+                    .parse::<i64>()
+                    .map(serde_json::Value::from)
+                    .unwrap_or_else(|_| serde_json::Value::from(value.as_str()));
+                (key.to_string(), value)
+            })
+            .collect();
+        println!("{}", serde_json::Value::Object(object));
+    } else {
+        let line: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        println!("{}", line.join(" "));
+    }
+}
+
+fn step_badge(issues: &[IntegrityIssue], codes: &[&str]) -> Badge {
+    let matching = issues
+        .iter()
+        .filter(|issue| codes.contains(&issue.code.as_str()));
+    let mut result = Badge::Ok;
+    for issue in matching {
+        if issue.is_error() {
+            return Badge::Err;
+        }
+        result = Badge::Warn;
+    }
+    result
+}
+
+fn format_ids(ids: &[String]) -> String {
+    let shown: Vec<String> = ids
+        .iter()
+        .take(MAX_LISTED_IDS)
+        .map(|id| match uuid::Uuid::parse_str(id) {
+            Ok(uuid) => short_id(&uuid),
+            Err(_) => id.clone(),
+        })
+        .collect();
+    let mut text = shown.join(", ");
+    if ids.len() > MAX_LISTED_IDS {
+        text.push_str(&format!(" (+{} more)", ids.len() - MAX_LISTED_IDS));
+    }
+    text
+}
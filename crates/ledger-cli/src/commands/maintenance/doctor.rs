@@ -1,6 +1,11 @@
+use ledger_core::fs::path_risks;
+use ledger_core::storage::encryption::scrypt_work_factor;
 use ledger_core::StorageEngine;
 
-use crate::app::{missing_config_message, missing_ledger_message, resolve_config_path, AppContext};
+use crate::app::{
+    format_wait, missing_config_message, missing_ledger_message, read_lockout, resolve_config_path,
+    AppContext,
+};
 use crate::cli::DoctorArgs;
 use crate::config::read_config;
 use crate::ui::{badge, banner, header, hint, kv, Badge, OutputMode, StepList};
@@ -34,6 +39,58 @@ pub fn handle_doctor(ctx: &AppContext, args: &DoctorArgs) -> anyhow::Result<()>
         return Err(anyhow::anyhow!("Ledger file missing"));
     }
 
+    let location_risks = path_risks(&ledger_path);
+
+    // Surface lockout before trying to unlock (a successful unlock resets it)
+    let lockout = config.security.lockout;
+    let lockout_state = read_lockout(&ledger_path);
+    if lockout && !ctx.cli().no_lockout {
+        if let Some(remaining) = lockout_state.remaining(chrono::Utc::now()) {
+            match ui_ctx.mode {
+                OutputMode::Pretty => {
+                    println!("{}", header(&ui_ctx, "doctor", None));
+                    println!();
+                    println!("{}", badge(&ui_ctx, Badge::Err, "Unlock is locked out"));
+                    println!(
+                        "  {}",
+                        kv(
+                            &ui_ctx,
+                            "Failed attempts",
+                            &lockout_state.failed_attempts.to_string()
+                        )
+                    );
+                    println!("  {}", kv(&ui_ctx, "Retry in", &format_wait(remaining)));
+                }
+                OutputMode::Plain | OutputMode::Json => {
+                    println!("check=config ok");
+                    println!("check=ledger ok");
+                    println!("check=lockout locked");
+                    println!("failed_attempts={}", lockout_state.failed_attempts);
+                    println!("retry_in_seconds={}", remaining.num_seconds());
+                    println!("status=failed");
+                }
+            }
+            return Err(anyhow::anyhow!(
+                "Ledger is locked after {} failed unlock attempts\nHint: Try again in {}, or pass --no-lockout if this is your ledger.",
+                lockout_state.failed_attempts,
+                format_wait(remaining)
+            ));
+        }
+    }
+    let lockout_summary = if lockout {
+        format!(
+            "on ({} failed attempt{} before this unlock)",
+            lockout_state.failed_attempts,
+            if lockout_state.failed_attempts == 1 {
+                ""
+            } else {
+                "s"
+            }
+        )
+    } else {
+        "off".to_string()
+    };
+
     let (storage, _passphrase) = ctx.open_storage(args.no_input).map_err(|e| {
         anyhow::anyhow!(
             "Failed to open ledger for diagnostics: {}\nHint: Set LEDGER_PASSPHRASE or run in a TTY.",
@@ -41,11 +98,32 @@ pub fn handle_doctor(ctx: &AppContext, args: &DoctorArgs) -> anyhow::Result<()>
         )
     })?;
 
-    // Run integrity check first
-    let integrity_result = storage.check_integrity();
+    // Encryption age: since the last `ledger reencrypt`, else since creation
+    let encrypted_since = match storage.last_reencrypted()? {
+        Some(at) => at,
+        None => storage.metadata()?.created_at,
+    };
+    let encryption_age_days = (chrono::Utc::now() - encrypted_since).num_days();
+    let reencrypt_after_days = config.security.reencrypt_after_days;
+    let encryption_stale = reencrypt_after_days
+        .is_some_and(|max| encryption_age_days > i64::try_from(max).unwrap_or(i64::MAX));
+    let work_factor = std::fs::read(&ledger_path)
+        .ok()
+        .and_then(|data| scrypt_work_factor(&data));
+
+    // Run integrity check first; warnings are reported but do not fail doctor
+    let issues = storage.check_integrity()?;
+    let integrity_warnings = issues.iter().filter(|issue| !issue.is_error()).count();
+    let integrity_error = issues
+        .iter()
+        .filter(|issue| issue.is_error())
+        .map(|issue| issue.message.clone())
+        .collect::<Vec<_>>()
+        .join("; ");
 
     // Handle errors (always output, regardless of quiet)
-    if let Err(ref err) = integrity_result {
+    if !integrity_error.is_empty() {
+        let err = &integrity_error;
         match ui_ctx.mode {
             OutputMode::Pretty => {
                 if show_banner {
@@ -103,15 +181,85 @@ pub fn handle_doctor(ctx: &AppContext, args: &DoctorArgs) -> anyhow::Result<()>
                     StepList::new(&ui_ctx, &["Config file", "Ledger file", "Integrity check"]);
                 steps.ok();
                 steps.ok();
-                steps.ok();
+                if integrity_warnings > 0 {
+                    steps.warn();
+                } else {
+                    steps.ok();
+                }
 
                 println!();
                 println!("{}", badge(&ui_ctx, Badge::Ok, "Ledger is healthy"));
+                if integrity_warnings > 0 {
+                    println!(
+                        "  {}",
+                        kv(
+                            &ui_ctx,
+                            "Warnings",
+                            &format!("{} (run `ledger check`)", integrity_warnings)
+                        )
+                    );
+                }
+                println!("  {}", kv(&ui_ctx, "Lockout", &lockout_summary));
+                if location_risks.is_empty() {
+                    println!("  {}", kv(&ui_ctx, "Location", "local"));
+                }
+                for risk in &location_risks {
+                    println!("{}", badge(&ui_ctx, Badge::Warn, &risk.to_string()));
+                }
+                let kdf = work_factor
+                    .map(|log_n| format!("scrypt 2^{}, ", log_n))
+                    .unwrap_or_default();
+                println!(
+                    "  {}",
+                    kv(
+                        &ui_ctx,
+                        "Encryption",
+                        &format!("{}{} days since re-encryption", kdf, encryption_age_days)
+                    )
+                );
+                if let (true, Some(max)) = (encryption_stale, reencrypt_after_days) {
+                    println!(
+                        "{}",
+                        badge(
+                            &ui_ctx,
+                            Badge::Warn,
+                            &format!(
+                                "Encryption parameters are {} days old (policy: {} days)",
+                                encryption_age_days, max
+                            )
+                        )
+                    );
+                    println!("{}", hint(&ui_ctx, "ledger reencrypt"));
+                }
             }
             OutputMode::Plain | OutputMode::Json => {
                 println!("check=config ok");
                 println!("check=ledger ok");
-                println!("check=integrity ok");
+                if integrity_warnings > 0 {
+                    println!("check=integrity warn");
+                    println!("integrity_warnings={}", integrity_warnings);
+                } else {
+                    println!("check=integrity ok");
+                }
+                println!("lockout={}", if lockout { "on" } else { "off" });
+                println!("failed_attempts={}", lockout_state.failed_attempts);
+                if location_risks.is_empty() {
+                    println!("check=location ok");
+                } else {
+                    println!("check=location warn");
+                }
+                for risk in &location_risks {
+                    println!("location_risk={}", risk);
+                }
+                if encryption_stale {
+                    println!("check=encryption warn");
+                } else {
+                    println!("check=encryption ok");
+                }
+                println!("encryption_age_days={}", encryption_age_days);
+                if let Some(log_n) = work_factor {
+                    println!("kdf_work_factor={}", log_n);
+                }
                 println!("status=ok");
             }
         }
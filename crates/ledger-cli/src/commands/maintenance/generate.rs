@@ -0,0 +1,345 @@
+//! Internal generator: fill a ledger with plausible entries for benches and
+//! manual performance testing of list, search and export at scale.
+
+use std::time::Instant;
+
+use chrono::{Duration, Utc};
+use ledger_core::storage::{NewEntry, NewEntryType, StorageEngine};
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::InternalGenerateArgs;
+use crate::ui::format::format_duration_secs;
+use crate::ui::progress::ProgressBar;
+use crate::ui::{badge, blank_line, header, kv, print, Badge, OutputMode};
+
+/// Words bodies, titles and tags are drawn from.
+const WORDS: &[&str] = &[
+    "morning",
+    "coffee",
+    "meeting",
+    "garden",
+    "river",
+    "walk",
+    "project",
+    "deadline",
+    "review",
+    "dinner",
+    "friends",
+    "music",
+    "book",
+    "chapter",
+    "train",
+    "weather",
+    "rain",
+    "sunny",
+    "plan",
+    "budget",
+    "groceries",
+    "run",
+    "bike",
+    "doctor",
+    "call",
+    "email",
+    "draft",
+    "release",
+    "bug",
+    "fix",
+    "design",
+    "sketch",
+    "photo",
+    "trip",
+    "mountain",
+    "beach",
+    "kitchen",
+    "recipe",
+    "bread",
+    "tomatoes",
+    "herbs",
+    "quiet",
+    "tired",
+    "focused",
+    "team",
+    "quarter",
+    "notes",
+    "idea",
+    "family",
+    "weekend",
+    "movie",
+    "podcast",
+    "lesson",
+    "practice",
+    "guitar",
+    "yoga",
+    "stretch",
+    "sleep",
+    "market",
+    "repair",
+    "bills",
+    "savings",
+    "library",
+    "museum",
+    "concert",
+    "birthday",
+    "gift",
+];
+
+/// Entries inserted between progress updates.
+const PROGRESS_STEP: usize = 1000;
+
+pub fn handle_internal_generate(
+    ctx: &AppContext,
+    args: &InternalGenerateArgs,
+) -> anyhow::Result<()> {
+    let started = Instant::now();
+    let (mut storage, passphrase) = ctx.open_storage(false)?;
+    let device_id = storage.metadata()?.device_id;
+
+    let mut entry_types = Vec::new();
+    for n in 1..=args.types {
+        let name = format!("gen_{}", n);
+        let entry_type = match storage.get_entry_type(&name)? {
+            Some(entry_type) => entry_type,
+            None => {
+                storage.create_entry_type(&NewEntryType::new(
+                    &name,
+                    generated_schema(),
+                    device_id,
+                ))?;
+                storage
+                    .get_entry_type(&name)?
+                    .ok_or_else(|| anyhow::anyhow!("Entry type {} was not created", name))?
+            }
+        };
+        entry_types.push(entry_type);
+    }
+    let tags = tag_vocabulary(args.tags);
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    let mut progress = ProgressBar::new(&ui_ctx, args.entries as u64, "Generating");
+    let mut rng = Rng::new(args.seed);
+    let now = Utc::now();
+    let span = Duration::days(i64::from(args.days));
+    let step = span / args.entries.max(1) as i32;
+    storage.batch(|storage| {
+        for i in 0..args.entries {
+            // Oldest first, so insertion order matches creation time
+            let offset = step * i as i32 + jitter(&mut rng, step);
+            let created_at = now - span + offset;
+            let entry_type = &entry_types[rng.below(entry_types.len())];
+            let entry = NewEntry::new(
+                entry_type.id,
+                entry_type.version,
+                generated_data(&mut rng),
+                device_id,
+            )
+            .with_tags(pick_tags(&mut rng, &tags))
+            .with_created_at(created_at);
+            storage.insert_entry(&entry)?;
+            if (i + 1) % PROGRESS_STEP == 0 {
+                progress.inc(PROGRESS_STEP as u64);
+            }
+        }
+        Ok(())
+    })?;
+    progress.finish();
+    ctx.save_storage(storage, &passphrase)?;
+    let elapsed = started.elapsed();
+
+    if ctx.quiet() && !args.json {
+        return Ok(());
+    }
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "status": "ok",
+                "entries": args.entries,
+                "types": args.types,
+                "tags": tags.len(),
+                "seed": args.seed,
+                "elapsed_ms": elapsed.as_millis() as u64,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            print(
+                &ui_ctx,
+                &header(&ui_ctx, "internal-generate", ledger_path.as_deref()),
+            );
+            blank_line(&ui_ctx);
+            print(
+                &ui_ctx,
+                &badge(
+                    &ui_ctx,
+                    Badge::Ok,
+                    &format!("Generated {} entries", args.entries),
+                ),
+            );
+            print(&ui_ctx, &kv(&ui_ctx, "Types", &args.types.to_string()));
+            print(&ui_ctx, &kv(&ui_ctx, "Tags", &tags.len().to_string()));
+            print(&ui_ctx, &kv(&ui_ctx, "Seed", &args.seed.to_string()));
+            print(
+                &ui_ctx,
+                &kv(
+                    &ui_ctx,
+                    "Elapsed",
+                    &format_duration_secs(elapsed.as_secs_f64()),
+                ),
+            );
+        }
+        OutputMode::Plain => {
+            println!("status=ok");
+            println!("entries={}", args.entries);
+            println!("types={}", args.types);
+            println!("tags={}", tags.len());
+            println!("seed={}", args.seed);
+            println!("elapsed_ms={}", elapsed.as_millis());
+        }
+    }
+    Ok(())
+}
+
+/// A title, a body of free text and two optional numeric fields, so list
+/// summaries, search and aggregates all have something to work on.
+fn generated_schema() -> serde_json::Value {
+    serde_json::json!({
+        "fields": [
+            {"name": "title", "type": "string", "required": true, "order": 1},
+            {"name": "body", "type": "text", "required": true, "order": 2},
+            {"name": "rating", "type": "integer", "required": false, "order": 3},
+            {"name": "amount", "type": "number", "required": false, "order": 4}
+        ]
+    })
+}
+
+fn generated_data(rng: &mut Rng) -> serde_json::Value {
+    let title_len = 2 + rng.below(4);
+    let body_len = 8 + rng.below(60);
+    let mut data = serde_json::json!({
+        "title": capitalize(&words(rng, title_len)),
+        "body": format!("{}.", capitalize(&words(rng, body_len))),
+    });
+    if rng.below(2) == 0 {
+        data["rating"] = serde_json::json!(1 + rng.below(5));
+    }
+    if rng.below(4) == 0 {
+        data["amount"] = serde_json::json!(rng.below(20_000) as f64 / 100.0);
+    }
+    data
+}
+
+/// Tag names: the word list first, then numbered variants of it.
+fn tag_vocabulary(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| {
+            let word = WORDS[i % WORDS.len()];
+            match i / WORDS.len() {
+                0 => word.to_string(),
+                n => format!("{}-{}", word, n),
+            }
+        })
+        .collect()
+}
+
+/// Up to three tags, favouring the start of the vocabulary so a few tags are
+/// common and most are rare.
+fn pick_tags(rng: &mut Rng, tags: &[String]) -> Vec<String> {
+    if tags.is_empty() {
+        return Vec::new();
+    }
+    let mut picked: Vec<String> = Vec::new();
+    for _ in 0..rng.below(4) {
+        let skewed = rng.unit() * rng.unit();
+        let tag = &tags[((skewed * tags.len() as f64) as usize).min(tags.len() - 1)];
+        if !picked.contains(tag) {
+            picked.push(tag.clone());
+        }
+    }
+    picked
+}
+
+fn words(rng: &mut Rng, count: usize) -> String {
+    (0..count)
+        .map(|_| WORDS[rng.below(WORDS.len())])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A random offset within one step, so timestamps aren't evenly spaced but
+/// stay in order.
+fn jitter(rng: &mut Rng, step: Duration) -> Duration {
+    let millis = step.num_milliseconds();
+    if millis <= 1 {
+        return Duration::zero();
+    }
+    Duration::milliseconds(rng.below(millis as usize) as i64)
+}
+
+/// splitmix64: small, fast and good enough for test data.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`; `n` must be non-zero.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_data() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        assert_eq!(generated_data(&mut a), generated_data(&mut b));
+        assert_ne!(generated_data(&mut a), generated_data(&mut Rng::new(8)));
+    }
+
+    #[test]
+    fn test_tag_vocabulary_is_unique() {
+        let tags = tag_vocabulary(WORDS.len() * 2 + 5);
+        let unique: std::collections::HashSet<_> = tags.iter().collect();
+        assert_eq!(unique.len(), tags.len());
+        assert_eq!(tags[WORDS.len()], format!("{}-1", WORDS[0]));
+        assert!(tag_vocabulary(0).is_empty());
+    }
+
+    #[test]
+    fn test_pick_tags_are_distinct() {
+        let tags = tag_vocabulary(3);
+        let mut rng = Rng::new(1);
+        for _ in 0..100 {
+            let picked = pick_tags(&mut rng, &tags);
+            assert!(picked.len() <= 3);
+            let unique: std::collections::HashSet<_> = picked.iter().collect();
+            assert_eq!(unique.len(), picked.len());
+        }
+    }
+}
@@ -1,16 +1,59 @@
-use crate::app::AppContext;
-use crate::cache::{cache_clear, cache_socket_path};
-use crate::ui::{badge, blank_line, header, kv, print, Badge, OutputMode};
+use std::io::{IsTerminal, Write};
+use std::path::Path;
 
-pub fn handle_lock(ctx: &AppContext) -> anyhow::Result<()> {
-    if let Ok(socket_path) = cache_socket_path() {
-        let _ = cache_clear(&socket_path);
+use crate::app::{format_wait, AppContext};
+use crate::cache::{
+    cache_clear, cache_clear_ledger, cache_sessions, cache_socket_path, cache_stop, ledger_hash,
+    CacheSession,
+};
+use crate::cli::LockArgs;
+use crate::helpers::wipe_editor_drafts;
+use crate::ui::theme::{styled, styles};
+use crate::ui::{badge, blank_line, header, kv, print, simple_table, Badge, Column, OutputMode};
+
+pub fn handle_lock(ctx: &AppContext, args: &LockArgs) -> anyhow::Result<()> {
+    if args.status {
+        return handle_lock_status(ctx, args);
+    }
+    if args.now {
+        return handle_lock_now(ctx);
     }
 
+    // Only an explicit --ledger flag narrows the lock; LEDGER_PATH alone locks everything
+    let selected = ctx
+        .cli()
+        .ledger
+        .as_ref()
+        .filter(|path| std::env::var("LEDGER_PATH").ok().as_ref() != Some(*path));
+
+    let socket_path = cache_socket_path().ok();
+    let locked_one = match (selected, socket_path.as_ref()) {
+        (Some(path), Some(socket)) => {
+            Some(cache_clear_ledger(socket, &ledger_hash(Path::new(path)))?)
+        }
+        (Some(_), None) => Some(false),
+        (None, Some(socket)) => {
+            let _ = cache_clear(socket);
+            None
+        }
+        (None, None) => None,
+    };
+
     if !ctx.quiet() {
         let ui_ctx = ctx.ui_context(false, None);
-        match ui_ctx.mode {
-            OutputMode::Pretty => {
+        match (ui_ctx.mode, selected) {
+            (OutputMode::Pretty, Some(path)) => {
+                print(&ui_ctx, &header(&ui_ctx, "lock", None));
+                blank_line(&ui_ctx);
+                let message = if locked_one == Some(true) {
+                    "Ledger locked"
+                } else {
+                    "Ledger was not unlocked"
+                };
+                print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, message));
+                print(&ui_ctx, &kv(&ui_ctx, "Ledger", path));
+            }
+            (OutputMode::Pretty, None) => {
                 print(&ui_ctx, &header(&ui_ctx, "lock", None));
                 blank_line(&ui_ctx);
                 print(
@@ -19,7 +62,12 @@ pub fn handle_lock(ctx: &AppContext) -> anyhow::Result<()> {
                 );
                 print(&ui_ctx, &kv(&ui_ctx, "Cache", "empty"));
             }
-            OutputMode::Plain | OutputMode::Json => {
+            (OutputMode::Plain | OutputMode::Json, Some(path)) => {
+                println!("status=ok");
+                println!("ledger={}", path);
+                println!("locked={}", locked_one == Some(true));
+            }
+            (OutputMode::Plain | OutputMode::Json, None) => {
                 println!("status=ok");
                 println!("cache=empty");
             }
@@ -28,3 +76,109 @@ pub fn handle_lock(ctx: &AppContext) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Panic lock: everything `lock` does, plus tearing down anything that could
+/// still show or recover plaintext. Pretty mode leaves a blank screen behind.
+fn handle_lock_now(ctx: &AppContext) -> anyhow::Result<()> {
+    let stopped = match cache_socket_path() {
+        Ok(socket) => cache_stop(&socket).unwrap_or(false),
+        Err(_) => false,
+    };
+    let drafts = wipe_editor_drafts();
+
+    let mut stdout = std::io::stdout();
+    if stdout.is_terminal() {
+        // Clear the screen and scrollback, then home the cursor
+        let _ = stdout.write_all(b"\x1b[2J\x1b[3J\x1b[H");
+        let _ = stdout.flush();
+    }
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        if !ui_ctx.mode.is_pretty() {
+            println!("status=ok");
+            println!("cache=empty");
+            println!("daemon={}", if stopped { "stopped" } else { "not_running" });
+            println!("drafts_wiped={}", drafts);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_lock_status(ctx: &AppContext, args: &LockArgs) -> anyhow::Result<()> {
+    let sessions = match cache_socket_path() {
+        Ok(socket) => cache_sessions(&socket).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+
+    if ctx.quiet() && !args.json {
+        return Ok(());
+    }
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let items: Vec<serde_json::Value> = sessions
+                .iter()
+                .map(|session| {
+                    serde_json::json!({
+                        "key": session.key,
+                        "ledger": session.ledger_path,
+                        "ttl_seconds": session.ttl.as_secs(),
+                        "remaining_seconds": session.remaining.as_secs(),
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&items)?);
+        }
+        OutputMode::Plain => {
+            for session in &sessions {
+                println!(
+                    "ledger={} key={} ttl={} remaining={}",
+                    session_label(session),
+                    session.key,
+                    session.ttl.as_secs(),
+                    session.remaining.as_secs()
+                );
+            }
+            println!("sessions={}", sessions.len());
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "lock", Some("status")));
+            blank_line(&ui_ctx);
+            if sessions.is_empty() {
+                print(&ui_ctx, &badge(&ui_ctx, Badge::Info, "No unlocked ledgers"));
+                return Ok(());
+            }
+            let columns = [
+                Column::new("Ledger"),
+                Column::new("Expires in"),
+                Column::new("TTL"),
+            ];
+            let rows: Vec<Vec<String>> = sessions
+                .iter()
+                .map(|session| {
+                    vec![
+                        session_label(session),
+                        format_wait(chrono::Duration::seconds(session.remaining.as_secs() as i64)),
+                        format!("{}s", session.ttl.as_secs()),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            blank_line(&ui_ctx);
+            let summary = format!("{} unlocked", sessions.len());
+            println!("{}", styled(&summary, styles::dim(), ui_ctx.color));
+        }
+    }
+
+    Ok(())
+}
+
+fn session_label(session: &CacheSession) -> String {
+    session
+        .ledger_path
+        .clone()
+        .unwrap_or_else(|| session.key.clone())
+}
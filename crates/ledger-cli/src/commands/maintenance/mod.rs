@@ -1,11 +1,29 @@
+pub mod audit;
 pub mod backup;
 pub mod cache_daemon;
 pub mod check;
 pub mod doctor;
+#[cfg(feature = "test-support")]
+pub mod generate;
 pub mod lock;
+pub mod recover;
+pub mod reencrypt;
+pub mod reindex;
+pub mod relocate;
+pub mod replica_sync;
+pub mod status;
 
+pub use audit::handle_audit_security;
 pub use backup::handle_backup;
 pub use cache_daemon::handle_internal_cache_daemon;
 pub use check::handle_check;
 pub use doctor::handle_doctor;
+#[cfg(feature = "test-support")]
+pub use generate::handle_internal_generate;
 pub use lock::handle_lock;
+pub use recover::handle_recover;
+pub use reencrypt::handle_reencrypt;
+pub use reindex::handle_reindex;
+pub use relocate::handle_move;
+pub use replica_sync::handle_internal_replicate;
+pub use status::handle_status;
@@ -0,0 +1,182 @@
+use std::path::Path;
+
+use ledger_core::error::LedgerError;
+use ledger_core::storage::{AgeSqliteStorage, RowRange, TableRecovery};
+
+use crate::app::{missing_ledger_message, resolve_ledger_path, AppContext};
+use crate::cli::RecoverArgs;
+use crate::errors::CliError;
+use crate::ui::progress::Spinner;
+use crate::ui::theme::{styled, styles};
+use crate::ui::{badge, blank_line, header, hint, print, simple_table, Badge, Column, OutputMode};
+
+pub fn handle_recover(ctx: &AppContext, args: &RecoverArgs) -> anyhow::Result<()> {
+    let source = resolve_ledger_path(ctx.cli())?;
+    let source_path = Path::new(&source);
+    if !source_path.exists() {
+        return Err(anyhow::anyhow!(missing_ledger_message(source_path)));
+    }
+    let out_path = Path::new(&args.out);
+    if out_path.exists() {
+        return Err(anyhow::anyhow!(
+            "Output file already exists: {}\nHint: Choose a new path; recover never overwrites files.",
+            out_path.display()
+        ));
+    }
+
+    let passphrase = ctx.unlock_passphrase(args.no_input)?;
+    let ui_ctx = ctx.ui_context(args.json, None);
+
+    let spinner = if ui_ctx.mode.is_pretty() && !ctx.quiet() {
+        let s = Spinner::new(&ui_ctx, "Salvaging rows");
+        s.start();
+        Some(s)
+    } else {
+        None
+    };
+    let report = match AgeSqliteStorage::recover(source_path, out_path, &passphrase) {
+        Ok(report) => report,
+        Err(LedgerError::IncorrectPassphrase) => {
+            return Err(CliError::auth_failed("Incorrect passphrase.").into())
+        }
+        Err(err) => return Err(err.into()),
+    };
+    if let Some(s) = spinner {
+        s.finish("Recovery finished");
+    }
+
+    let status = if report.is_complete() {
+        "ok"
+    } else {
+        "partial"
+    };
+
+    if ctx.quiet() && !args.json {
+        return Ok(());
+    }
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "status": status,
+                "source": source,
+                "out": args.out,
+                "tables": report.tables,
+                "issues": report.issues,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Plain => {
+            for table in &report.tables {
+                println!(
+                    "table={} recovered={} rejected={} orphaned={} unreadable={} missing={}",
+                    table.table,
+                    table.recovered,
+                    table.rejected,
+                    table.orphaned,
+                    format_ranges(&table.unreadable),
+                    table.missing
+                );
+            }
+            for issue in &report.issues {
+                println!("issue={} severity={}", issue.code, issue.severity.as_str());
+            }
+            println!("out={}", args.out);
+            println!("status={}", status);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "recover", None));
+            blank_line(&ui_ctx);
+            let columns = [
+                Column::new("Table"),
+                Column::new("Recovered"),
+                Column::new("Lost"),
+                Column::new("Unreadable rowids"),
+            ];
+            let rows: Vec<Vec<String>> = report
+                .tables
+                .iter()
+                .map(|table| {
+                    vec![
+                        table.table.clone(),
+                        table.recovered.to_string(),
+                        lost_summary(table),
+                        format_ranges(&table.unreadable),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            blank_line(&ui_ctx);
+            if report.is_complete() {
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Recovered everything into {}", args.out),
+                    ),
+                );
+            } else {
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Warn,
+                        &format!("Recovered what was readable into {}", args.out),
+                    ),
+                );
+            }
+            for issue in &report.issues {
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Warn,
+                        &format!("{} ({})", issue.message, issue.code),
+                    ),
+                );
+            }
+            let source_note = format!("Original left untouched: {}", source);
+            println!("{}", styled(&source_note, styles::dim(), ui_ctx.color));
+            blank_line(&ui_ctx);
+            print(
+                &ui_ctx,
+                &hint(&ui_ctx, &format!("ledger check --ledger {}", args.out)),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn lost_summary(table: &TableRecovery) -> String {
+    if table.missing {
+        return "table unreadable".to_string();
+    }
+    let mut parts = Vec::new();
+    if table.rejected > 0 {
+        parts.push(format!("{} invalid", table.rejected));
+    }
+    if table.orphaned > 0 {
+        parts.push(format!("{} orphaned", table.orphaned));
+    }
+    if parts.is_empty() {
+        "-".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+fn format_ranges(ranges: &[RowRange]) -> String {
+    if ranges.is_empty() {
+        return "-".to_string();
+    }
+    ranges
+        .iter()
+        .map(|range| match range.end {
+            Some(end) if end == range.start => range.start.to_string(),
+            Some(end) => format!("{}-{}", range.start, end),
+            None => format!("{}-end", range.start),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
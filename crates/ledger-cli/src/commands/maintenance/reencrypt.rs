@@ -0,0 +1,80 @@
+//! Reencrypt: rewrite the ledger with a fresh salt, nonce and work factor.
+//!
+//! The passphrase stays the same. The scrypt work factor is recalibrated by
+//! age on this machine, so an archive created on slower hardware picks up
+//! stronger parameters.
+
+use std::path::Path;
+
+use ledger_core::storage::encryption::scrypt_work_factor;
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::ReencryptArgs;
+use crate::ui::format::format_datetime;
+use crate::ui::{badge, blank_line, header, kv, print, Badge, OutputMode};
+
+pub fn handle_reencrypt(ctx: &AppContext, args: &ReencryptArgs) -> anyhow::Result<()> {
+    let ledger_path = resolve_ledger_path(ctx.cli())?;
+    let read_work_factor = || {
+        std::fs::read(Path::new(&ledger_path))
+            .ok()
+            .and_then(|data| scrypt_work_factor(&data))
+    };
+    let previous_work_factor = read_work_factor();
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let previous = storage.last_reencrypted()?;
+    let at = storage.record_reencryption()?;
+    ctx.save_storage(storage, &passphrase)?;
+    let work_factor = read_work_factor();
+
+    if ctx.quiet() && !args.json {
+        return Ok(());
+    }
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "status": "ok",
+                "reencrypted_at": at,
+                "previous_reencrypted_at": previous,
+                "work_factor": work_factor,
+                "previous_work_factor": previous_work_factor,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "reencrypt", Some(&ledger_path)));
+            blank_line(&ui_ctx);
+            print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, "Ledger re-encrypted"));
+            let kdf = match (work_factor, previous_work_factor) {
+                (Some(now), Some(was)) if now != was => {
+                    format!("scrypt 2^{} (was 2^{})", now, was)
+                }
+                (Some(now), _) => format!("scrypt 2^{}", now),
+                (None, _) => "unknown".to_string(),
+            };
+            print(&ui_ctx, &kv(&ui_ctx, "KDF", &kdf));
+            let previous = previous
+                .map(|at| format_datetime(&at, true))
+                .unwrap_or_else(|| "never".to_string());
+            print(&ui_ctx, &kv(&ui_ctx, "Previously", &previous));
+        }
+        OutputMode::Plain => {
+            println!("status=ok");
+            println!("reencrypted_at={}", at.to_rfc3339());
+            println!(
+                "previous_reencrypted_at={}",
+                previous
+                    .map(|at| at.to_rfc3339())
+                    .unwrap_or_else(|| "never".to_string())
+            );
+            if let Some(work_factor) = work_factor {
+                println!("work_factor={}", work_factor);
+            }
+            if let Some(work_factor) = previous_work_factor {
+                println!("previous_work_factor={}", work_factor);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! Reindex: rebuild the full-text search index, optionally switching tokenizer.
+//!
+//! The rebuild runs in batches so a large ledger shows progress and can be
+//! throttled. Ctrl-C stops after the current batch and saves the ledger with
+//! the rebuild cursor, so the next `ledger reindex` picks up where it left off.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::ReindexArgs;
+use crate::helpers::parse_search_tokenizer;
+use crate::ui::progress::ProgressBar;
+use crate::ui::{badge, blank_line, header, kv, print, Badge, OutputMode};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+pub fn handle_reindex(ctx: &AppContext, args: &ReindexArgs) -> anyhow::Result<()> {
+    if args.batch_size == 0 {
+        return Err(anyhow::anyhow!("--batch-size must be at least 1"));
+    }
+    let requested = match args.tokenizer {
+        Some(ref name) => Some(parse_search_tokenizer(name)?),
+        None => ctx.search_tokenizer()?,
+    };
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let previous = storage.search_tokenizer()?;
+    let pending = storage.pending_search_rebuild()?;
+    let tokenizer = requested
+        .or(pending.map(|pending| pending.tokenizer))
+        .unwrap_or(previous);
+    let resumed = pending.is_some_and(|pending| pending.tokenizer == tokenizer);
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    let show_progress = ui_ctx.mode.is_pretty() && !ctx.quiet();
+    let mut progress = storage.begin_search_rebuild(tokenizer)?;
+    let mut bar = show_progress.then(|| {
+        let mut bar = ProgressBar::new(&ui_ctx, progress.total as u64, "Indexing");
+        bar.set(progress.indexed as u64);
+        bar
+    });
+    let _guard = InterruptGuard::install();
+    let pause = Duration::from_millis(args.pause_ms);
+    // Always run one batch: it also closes out a rebuild with nothing left
+    loop {
+        progress = storage.continue_search_rebuild(args.batch_size)?;
+        if let Some(ref mut bar) = bar {
+            bar.set(progress.indexed as u64);
+        }
+        if progress.is_complete() || INTERRUPTED.load(Ordering::SeqCst) {
+            break;
+        }
+        pause_between_batches(pause);
+    }
+    if let Some(bar) = bar {
+        bar.finish();
+    }
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !progress.is_complete() {
+        return Err(anyhow::anyhow!(
+            "Reindex interrupted after {} of {} entries\nHint: Run `ledger reindex` again to resume.",
+            progress.indexed,
+            progress.total
+        ));
+    }
+
+    if ctx.quiet() && !args.json {
+        return Ok(());
+    }
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "status": "ok",
+                "tokenizer": tokenizer,
+                "previous_tokenizer": previous,
+                "entries": progress.total,
+                "resumed": resumed,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            print(&ui_ctx, &header(&ui_ctx, "reindex", ledger_path.as_deref()));
+            blank_line(&ui_ctx);
+            let message = if resumed {
+                "Search index rebuilt (resumed)"
+            } else {
+                "Search index rebuilt"
+            };
+            print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, message));
+            let tokenizer_label = if tokenizer == previous {
+                tokenizer.to_string()
+            } else {
+                format!("{} (was {})", tokenizer, previous)
+            };
+            print(&ui_ctx, &kv(&ui_ctx, "Tokenizer", &tokenizer_label));
+            print(
+                &ui_ctx,
+                &kv(&ui_ctx, "Entries", &progress.total.to_string()),
+            );
+        }
+        OutputMode::Plain => {
+            println!("status=ok");
+            println!("tokenizer={}", tokenizer);
+            println!("previous_tokenizer={}", previous);
+            println!("entries={}", progress.total);
+            println!("resumed={}", resumed);
+        }
+    }
+    Ok(())
+}
+
+/// Sleep between batches, waking early on Ctrl-C.
+fn pause_between_batches(pause: Duration) {
+    let deadline = Instant::now() + pause;
+    while !INTERRUPTED.load(Ordering::SeqCst) {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            break;
+        }
+        std::thread::sleep(left.min(Duration::from_millis(100)));
+    }
+}
+
+/// Turns Ctrl-C into a stop request for the duration of the rebuild.
+struct InterruptGuard;
+
+impl InterruptGuard {
+    fn install() -> Self {
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(
+                libc::SIGINT,
+                on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+        Self
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
@@ -0,0 +1,203 @@
+use std::io::IsTerminal;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app::{
+    lockout_path, missing_ledger_message, resolve_config_path, resolve_ledger_path, AppContext,
+};
+use crate::cache::{cache_clear_ledger, cache_config, cache_get, cache_store, ledger_hash};
+use crate::cli::MoveArgs;
+use crate::commands::maintenance::backup::backup_record_path;
+use crate::config::{read_config, write_config};
+use crate::security::{keychain_clear, keychain_get, keychain_set};
+use crate::ui::prompt::confirm_destructive;
+use crate::ui::{badge, blank_line, header, hint, kv, print, Badge, OutputMode};
+
+pub fn handle_move(ctx: &AppContext, args: &MoveArgs) -> anyhow::Result<()> {
+    let source = resolve_ledger_path(ctx.cli())?;
+    let source_path = Path::new(&source);
+    if !source_path.exists() {
+        return Err(anyhow::anyhow!(missing_ledger_message(source_path)));
+    }
+    let destination = Path::new(&args.to);
+    if destination.exists() {
+        return Err(anyhow::anyhow!(
+            "Destination already exists: {}\nHint: Choose a new path; `ledger move` never overwrites a file.",
+            destination.display()
+        ));
+    }
+
+    let checksum = copy_verified(source_path, destination)?;
+    let config_updated = update_config_path(source_path, destination)?;
+    let credentials_moved = move_credentials(ctx, source_path, destination)?;
+
+    let old_lockout = lockout_path(source_path);
+    if old_lockout.exists() {
+        ledger_core::fs::rename_with_fallback(&old_lockout, &lockout_path(destination))
+            .map_err(|e| anyhow::anyhow!("Failed to move lockout state: {}", e))?;
+    }
+    let old_backup = backup_record_path(source_path);
+    if old_backup.exists() {
+        ledger_core::fs::rename_with_fallback(&old_backup, &backup_record_path(destination))
+            .map_err(|e| anyhow::anyhow!("Failed to move backup record: {}", e))?;
+    }
+
+    // Without anyone to ask, the old ledger is kept rather than failing the move
+    let yes = args.force || ctx.assume_yes();
+    let remove_old = if yes || (!args.no_input && std::io::stdin().is_terminal()) {
+        let ui_ctx = ctx.ui_context(false, None);
+        let prompt = format!(
+            "Copy verified. Remove the old ledger at {}?",
+            source_path.display()
+        );
+        confirm_destructive(&ui_ctx, &prompt, yes, args.no_input)?
+    } else {
+        false
+    };
+    if remove_old {
+        std::fs::remove_file(source_path).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to remove old ledger {}: {}",
+                source_path.display(),
+                e
+            )
+        })?;
+    }
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                print(&ui_ctx, &header(&ui_ctx, "move", None));
+                blank_line(&ui_ctx);
+                print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, "Ledger moved"));
+                print(&ui_ctx, &kv(&ui_ctx, "From", &source));
+                print(&ui_ctx, &kv(&ui_ctx, "To", &args.to));
+                print(&ui_ctx, &kv(&ui_ctx, "Checksum", &checksum));
+                if !config_updated {
+                    print(
+                        &ui_ctx,
+                        &badge(
+                            &ui_ctx,
+                            Badge::Warn,
+                            "Config does not point at this ledger; ledger.path left unchanged",
+                        ),
+                    );
+                }
+                if !remove_old {
+                    blank_line(&ui_ctx);
+                    print(
+                        &ui_ctx,
+                        &hint(
+                            &ui_ctx,
+                            &format!("Old ledger kept; remove {} when ready", source),
+                        ),
+                    );
+                }
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                println!("status=ok");
+                println!("from={}", source);
+                println!("to={}", args.to);
+                println!("checksum={}", checksum);
+                println!("config_updated={}", config_updated);
+                println!("credentials_moved={}", credentials_moved);
+                println!("old_removed={}", remove_old);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copy the ledger next to `destination`, check it byte-for-byte, then rename
+/// it into place. Returns the blake3 checksum of the copy.
+fn copy_verified(source: &Path, destination: &Path) -> anyhow::Result<String> {
+    let parent = destination
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    std::fs::create_dir_all(parent)
+        .map_err(|e| anyhow::anyhow!("Failed to create directory {}: {}", parent.display(), e))?;
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| anyhow::anyhow!("System time error: {}", e))?
+        .as_nanos();
+    let temp_path = parent.join(format!(".ledger-move-{}.tmp", nanos));
+
+    std::fs::copy(source, &temp_path).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to copy ledger from {} to {}: {}",
+            source.display(),
+            destination.display(),
+            e
+        )
+    })?;
+
+    let expected = file_checksum(source)?;
+    let actual = file_checksum(&temp_path)?;
+    if expected != actual {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(anyhow::anyhow!(
+            "Checksum mismatch after copying to {}; the original ledger was left in place",
+            destination.display()
+        ));
+    }
+
+    ledger_core::fs::rename_with_fallback(&temp_path, destination)
+        .map_err(|e| anyhow::anyhow!("Atomic rename failed: {}", e))?;
+    Ok(actual)
+}
+
+fn file_checksum(path: &Path) -> anyhow::Result<String> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    Ok(blake3::hash(&bytes).to_hex().to_string())
+}
+
+/// Point `ledger.path` at the new file if the config referred to the old one.
+fn update_config_path(source: &Path, destination: &Path) -> anyhow::Result<bool> {
+    let config_path = resolve_config_path()?;
+    if !config_path.exists() {
+        return Ok(false);
+    }
+    let mut config = read_config(&config_path)?;
+    if ledger_hash(Path::new(&config.ledger.path)) != ledger_hash(source) {
+        return Ok(false);
+    }
+    let destination = destination
+        .canonicalize()
+        .unwrap_or_else(|_| destination.to_path_buf());
+    config.ledger.path = destination.to_string_lossy().to_string();
+    write_config(&config_path, &config)?;
+    Ok(true)
+}
+
+/// Re-key the keychain entry and cached passphrase, which are stored under
+/// a hash of the ledger path, so they keep working after the move.
+fn move_credentials(ctx: &AppContext, source: &Path, destination: &Path) -> anyhow::Result<bool> {
+    let security = ctx.security_config()?;
+    let mut moved = false;
+
+    if security.keychain_enabled {
+        let old_account = ledger_hash(source);
+        if let Some(passphrase) = keychain_get(&old_account)? {
+            keychain_set(&ledger_hash(destination), &passphrase)?;
+            keychain_clear(&old_account)?;
+            moved = true;
+        }
+    }
+
+    if let (Some(old_cache), Some(new_cache)) = (
+        cache_config(source, security.cache_ttl_seconds)?,
+        cache_config(destination, security.cache_ttl_seconds)?,
+    ) {
+        if let Some(passphrase) = cache_get(&old_cache)? {
+            cache_store(&new_cache, &passphrase)?;
+            cache_clear_ledger(&old_cache.socket_path, &old_cache.key)?;
+            moved = true;
+        }
+    }
+
+    Ok(moved)
+}
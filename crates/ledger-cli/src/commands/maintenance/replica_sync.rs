@@ -0,0 +1,9 @@
+use std::path::Path;
+
+use crate::cli::InternalReplicateArgs;
+use crate::replica::run_replica_sync;
+
+pub fn handle_internal_replicate(args: &InternalReplicateArgs) -> anyhow::Result<()> {
+    run_replica_sync(Path::new(&args.source), Path::new(&args.dest))?;
+    Ok(())
+}
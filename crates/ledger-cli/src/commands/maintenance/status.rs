@@ -0,0 +1,361 @@
+//! `ledger status`: a quick look at whether anything needs attention.
+//!
+//! Everything that can be read without the passphrase always is; drafts, the
+//! last entry and attachment usage are only shown when the ledger can be
+//! unlocked without a prompt (cache, keychain, device keyfile or
+//! `LEDGER_PASSPHRASE`).
+
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use ledger_core::storage::{AttachmentUsage, EntryFilter};
+use ledger_core::StorageEngine;
+
+use crate::app::{
+    format_wait, missing_ledger_message, resolve_config_path, resolve_ledger_path, AppContext,
+};
+use crate::cache::{cache_sessions, cache_socket_path, ledger_hash};
+use crate::cli::StatusArgs;
+use crate::commands::maintenance::backup::read_backup_record;
+use crate::commands::maintenance::check::{read_check_record, CheckRecord};
+use crate::config::{read_config, SecurityTier};
+use crate::helpers::DraftRecovery;
+use crate::replica::read_replica_record;
+use crate::security::keychain_get;
+use crate::ui::format::format_datetime;
+use crate::ui::{badge, blank_line, format_bytes, header, kv, print, Badge, OutputMode};
+
+/// Where a prompt-free unlock would get its secret from.
+struct UnlockSource {
+    name: &'static str,
+    /// Time left on a cached passphrase
+    remaining: Option<Duration>,
+}
+
+/// What could only be read by unlocking the ledger.
+struct Unlocked {
+    drafts: usize,
+    recovered_drafts: usize,
+    last_entry: Option<DateTime<Utc>>,
+    attachments: AttachmentUsage,
+}
+
+pub fn handle_status(ctx: &AppContext, args: &StatusArgs) -> anyhow::Result<()> {
+    let ledger_path = resolve_ledger_path(ctx.cli())?;
+    let path = Path::new(&ledger_path);
+    let size = std::fs::metadata(path)
+        .map_err(|_| anyhow::anyhow!(missing_ledger_message(path)))?
+        .len();
+
+    let source = unlock_source(ctx, path);
+    let unlocked = source.as_ref().map(|_| read_unlocked(ctx, &ledger_path));
+    let backup = read_backup_record(path);
+    let backup_age_hours = backup
+        .as_ref()
+        .map(|record| (Utc::now() - record.at).num_hours());
+    let max_age_hours = resolve_config_path()
+        .ok()
+        .and_then(|config_path| read_config(&config_path).ok())
+        .and_then(|config| config.backup.max_age_hours);
+    let check = read_check_record(path);
+    let replica = read_replica_record(path);
+
+    let mut attention = Vec::new();
+    if let Some(Ok(ref unlocked)) = unlocked {
+        if unlocked.drafts > 0 {
+            attention.push(format!("{} draft(s) not published", unlocked.drafts));
+        }
+        if unlocked.recovered_drafts > 0 {
+            attention.push(format!(
+                "{} unsaved draft(s) from a crashed editor session",
+                unlocked.recovered_drafts
+            ));
+        }
+        if unlocked.attachments.unreferenced_blobs > 0 {
+            attention.push(format!(
+                "{} of unreferenced attachment blobs; run `ledger attachments gc`",
+                format_bytes(unlocked.attachments.unreferenced_bytes)
+            ));
+        }
+    }
+    match (backup_age_hours, max_age_hours) {
+        (None, _) => attention.push("No backup recorded".to_string()),
+        (Some(age), Some(max)) if age > i64::try_from(max).unwrap_or(i64::MAX) => {
+            attention.push(format!("Last backup is {}h old (max {}h)", age, max))
+        }
+        _ => {}
+    }
+    if let Some(replica) = replica.as_ref().filter(|replica| !replica.ok) {
+        attention.push(format!(
+            "Last replica sync to {} failed: {}",
+            replica.destination,
+            replica.error.as_deref().unwrap_or("unknown error")
+        ));
+    }
+    if let Some(ref check) = check {
+        if check.errors > 0 {
+            attention.push(format!("Last check found {} error(s)", check.errors));
+        } else if check.warnings > 0 {
+            attention.push(format!("Last check found {} warning(s)", check.warnings));
+        }
+    }
+    let state = if attention.is_empty() {
+        "clean"
+    } else {
+        "dirty"
+    };
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let unlocked_value = |value: fn(&Unlocked) -> serde_json::Value| match unlocked {
+                Some(Ok(ref unlocked)) => value(unlocked),
+                _ => serde_json::Value::Null,
+            };
+            let output = serde_json::json!({
+                "state": state,
+                "ledger": ledger_path,
+                "size_bytes": size,
+                "lock": {
+                    "state": lock_state(&source, &unlocked),
+                    "source": source.as_ref().map(|source| source.name),
+                    "remaining_seconds": source
+                        .as_ref()
+                        .and_then(|source| source.remaining)
+                        .map(|remaining| remaining.as_secs()),
+                    "error": match unlocked {
+                        Some(Err(ref err)) => Some(err.to_string()),
+                        _ => None,
+                    },
+                },
+                "drafts": unlocked_value(|u| u.drafts.into()),
+                "recovered_drafts": unlocked_value(|u| u.recovered_drafts.into()),
+                "last_entry": unlocked_value(|u| serde_json::json!(u.last_entry)),
+                "attachments": unlocked_value(|u| serde_json::json!(u.attachments)),
+                "last_backup": backup.as_ref().map(|record| record.at),
+                "backup_age_hours": backup_age_hours,
+                "last_check": check,
+                "last_replica": replica,
+                "attention": attention,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Plain => {
+            println!("state={}", state);
+            println!("ledger={}", ledger_path);
+            println!("size_bytes={}", size);
+            println!("lock={}", lock_state(&source, &unlocked));
+            if let Some(ref source) = source {
+                println!("unlock_source={}", source.name);
+            }
+            if let Some(Ok(ref unlocked)) = unlocked {
+                println!("drafts={}", unlocked.drafts);
+                println!("recovered_drafts={}", unlocked.recovered_drafts);
+                println!(
+                    "last_entry={}",
+                    unlocked
+                        .last_entry
+                        .map(|at| at.to_rfc3339())
+                        .unwrap_or_else(|| "none".to_string())
+                );
+                println!("attachments={}", unlocked.attachments.attachments);
+                println!(
+                    "attachment_stored_bytes={}",
+                    unlocked.attachments.stored_bytes
+                );
+            }
+            println!(
+                "last_backup={}",
+                backup
+                    .as_ref()
+                    .map(|record| record.at.to_rfc3339())
+                    .unwrap_or_else(|| "none".to_string())
+            );
+            println!("check={}", check_state(check.as_ref()));
+            for reason in &attention {
+                println!("attention={}", reason);
+            }
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "status", None));
+            blank_line(&ui_ctx);
+            print(
+                &ui_ctx,
+                &kv(
+                    &ui_ctx,
+                    "Ledger",
+                    &format!("{} ({})", ledger_path, format_bytes(size)),
+                ),
+            );
+            let lock = match (&source, &unlocked) {
+                (Some(_), Some(Err(err))) => format!("unlock failed: {}", first_line(err)),
+                (Some(source), _) => match source.remaining {
+                    Some(remaining) => format!(
+                        "unlocked ({}, {} left)",
+                        source.name,
+                        format_wait(chrono::Duration::seconds(remaining.as_secs() as i64))
+                    ),
+                    None => format!("unlocked ({})", source.name),
+                },
+                (None, _) => "locked".to_string(),
+            };
+            print(&ui_ctx, &kv(&ui_ctx, "Lock", &lock));
+            match unlocked {
+                Some(Ok(ref unlocked)) => {
+                    let drafts = match (unlocked.drafts, unlocked.recovered_drafts) {
+                        (0, 0) => "none".to_string(),
+                        (saved, 0) => format!("{} saved", saved),
+                        (saved, recovered) => {
+                            format!("{} saved, {} recovered", saved, recovered)
+                        }
+                    };
+                    print(&ui_ctx, &kv(&ui_ctx, "Drafts", &drafts));
+                    let last_entry = unlocked
+                        .last_entry
+                        .map(|at| format_datetime(&at, true))
+                        .unwrap_or_else(|| "none".to_string());
+                    print(&ui_ctx, &kv(&ui_ctx, "Last entry", &last_entry));
+                    print(
+                        &ui_ctx,
+                        &kv(
+                            &ui_ctx,
+                            "Attachments",
+                            &attachment_summary(&unlocked.attachments),
+                        ),
+                    );
+                }
+                _ => {
+                    print(&ui_ctx, &kv(&ui_ctx, "Drafts", "unknown (locked)"));
+                    print(&ui_ctx, &kv(&ui_ctx, "Last entry", "unknown (locked)"));
+                    print(&ui_ctx, &kv(&ui_ctx, "Attachments", "unknown (locked)"));
+                }
+            }
+            let last_backup = match (&backup, backup_age_hours) {
+                (Some(record), Some(age)) => {
+                    format!("{} ({}h ago)", format_datetime(&record.at, true), age)
+                }
+                _ => "never".to_string(),
+            };
+            print(&ui_ctx, &kv(&ui_ctx, "Last backup", &last_backup));
+            let last_check = match check {
+                Some(ref check) => format!(
+                    "{} at {}",
+                    check_state(Some(check)),
+                    format_datetime(&check.at, true)
+                ),
+                None => "never run".to_string(),
+            };
+            print(&ui_ctx, &kv(&ui_ctx, "Last check", &last_check));
+            blank_line(&ui_ctx);
+            if attention.is_empty() {
+                print(
+                    &ui_ctx,
+                    &badge(&ui_ctx, Badge::Ok, "Nothing needs attention"),
+                );
+            }
+            for reason in &attention {
+                print(&ui_ctx, &badge(&ui_ctx, Badge::Warn, reason));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// How the ledger could be unlocked without prompting, if at all.
+fn unlock_source(ctx: &AppContext, path: &Path) -> Option<UnlockSource> {
+    let key = ledger_hash(path);
+    let cached = cache_socket_path()
+        .ok()
+        .and_then(|socket| cache_sessions(&socket).ok())
+        .and_then(|sessions| sessions.into_iter().find(|session| session.key == key));
+    if let Some(session) = cached {
+        return Some(UnlockSource {
+            name: "cache",
+            remaining: Some(session.remaining),
+        });
+    }
+    let source = |name| {
+        Some(UnlockSource {
+            name,
+            remaining: None,
+        })
+    };
+    let security = ctx.security_config().ok()?;
+    match security.tier {
+        SecurityTier::DeviceKeyfile => return source("keyfile"),
+        SecurityTier::PassphraseKeychain if security.keychain_enabled => {
+            if matches!(keychain_get(&key), Ok(Some(_))) {
+                return source("keychain");
+            }
+        }
+        _ => {}
+    }
+    std::env::var("LEDGER_PASSPHRASE")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .and_then(|_| source("environment"))
+}
+
+fn read_unlocked(ctx: &AppContext, ledger_path: &str) -> anyhow::Result<Unlocked> {
+    let (storage, passphrase) = ctx.open_storage(true)?;
+    let recovered_drafts = DraftRecovery::new(ledger_path, &passphrase)
+        .and_then(|recovery| recovery.drafts())
+        .map(|drafts| drafts.len())
+        .unwrap_or_default();
+    Ok(Unlocked {
+        drafts: storage.list_drafts()?.len(),
+        recovered_drafts,
+        last_entry: storage
+            .list_entries(&EntryFilter::new().limit(1))?
+            .first()
+            .map(|entry| entry.created_at),
+        attachments: storage.attachment_usage()?,
+    })
+}
+
+/// Attachment count and stored size, with what deduplication saved.
+fn attachment_summary(usage: &AttachmentUsage) -> String {
+    if usage.attachments == 0 && usage.blobs == 0 {
+        return "none".to_string();
+    }
+    let mut summary = format!(
+        "{} ({})",
+        usage.attachments,
+        format_bytes(usage.stored_bytes)
+    );
+    let saved = usage.logical_bytes.saturating_sub(usage.stored_bytes);
+    if saved > 0 {
+        summary.push_str(&format!(", {} saved by deduplication", format_bytes(saved)));
+    }
+    summary
+}
+
+fn lock_state(
+    source: &Option<UnlockSource>,
+    unlocked: &Option<anyhow::Result<Unlocked>>,
+) -> &'static str {
+    match (source, unlocked) {
+        (Some(_), Some(Ok(_))) => "unlocked",
+        (Some(_), _) => "unlock_failed",
+        (None, _) => "locked",
+    }
+}
+
+fn check_state(check: Option<&CheckRecord>) -> &'static str {
+    match check {
+        None => "never",
+        Some(check) if check.errors > 0 => "failed",
+        Some(check) if check.warnings > 0 => "warn",
+        Some(_) => "ok",
+    }
+}
+
+fn first_line(err: &anyhow::Error) -> String {
+    err.to_string()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
@@ -1,10 +1,27 @@
 use clap::CommandFactory;
 use clap_complete::generate;
+use ledger_core::views::{view_schema, VIEW_NAMES};
 
-use crate::cli::{Cli, CompletionsArgs};
+use crate::cli::{Cli, CompletionsArgs, SchemaArgs};
 
 pub fn handle_completions(args: &CompletionsArgs) -> anyhow::Result<()> {
     let mut cmd = Cli::command();
     generate(args.shell, &mut cmd, "ledger", &mut std::io::stdout());
     Ok(())
 }
+
+/// Print the JSON Schema of one `--json` view, or of all of them keyed by
+/// view name.
+pub fn handle_schema(args: &SchemaArgs) -> anyhow::Result<()> {
+    let output = match args.view.as_deref() {
+        Some(view) => view_schema(view).ok_or_else(|| anyhow::anyhow!("Unknown view: {}", view))?,
+        None => serde_json::Value::Object(
+            VIEW_NAMES
+                .iter()
+                .filter_map(|name| Some((name.to_string(), view_schema(name)?)))
+                .collect(),
+        ),
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
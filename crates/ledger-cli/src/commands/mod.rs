@@ -1,7 +1,24 @@
+pub mod adopt;
+pub mod api;
 pub mod associations;
+pub mod attachments;
+pub mod collect;
 pub mod compositions;
+pub mod drafts;
 pub mod entries;
+pub mod habits;
 pub mod init;
 pub mod maintenance;
 pub mod misc;
+pub mod packs;
+pub mod prompts;
+pub mod reports;
+pub mod scratch;
+pub mod secrets;
+pub mod services;
+pub mod share;
+pub mod sync;
+pub mod tags;
 pub mod templates;
+pub mod types;
+pub mod vault;
@@ -0,0 +1,284 @@
+//! Install an entry type pack from a manifest file or a built-in pack.
+//!
+//! Installing is idempotent: types and templates that already match are left
+//! alone. A newer manifest adds schema versions and template versions for the
+//! types and templates the pack installed before. Anything with the same name
+//! that the pack does not own is a conflict, and nothing is written.
+
+use std::path::Path;
+
+use chrono::Utc;
+use ledger_core::storage::{InstalledPack, NewEntryType, NewTemplate, StorageEngine};
+
+use crate::app::AppContext;
+use crate::cli::PackInstallArgs;
+use crate::helpers::{builtin_pack, PackManifest, BUILTIN_PACKS};
+use crate::ui::theme::symbols;
+use crate::ui::{badge, blank_line, hint, kv, print, Badge, OutputMode};
+
+/// What an install did to one entry type or template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+impl Action {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Action::Created => "created",
+            Action::Updated => "updated",
+            Action::Unchanged => "unchanged",
+        }
+    }
+}
+
+pub fn handle_install(ctx: &AppContext, args: &PackInstallArgs) -> anyhow::Result<()> {
+    let manifest = load_manifest(&args.source)?;
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let device_id = storage.metadata()?.device_id;
+    let previous = storage.get_pack(&manifest.name)?;
+    let owns = |kind: &str, name: &str| {
+        previous.as_ref().is_some_and(|pack| match kind {
+            "entry_type" => pack.entry_types.iter().any(|owned| owned == name),
+            _ => pack.templates.iter().any(|owned| owned == name),
+        })
+    };
+
+    // Plan everything first so a conflict writes nothing
+    let mut type_actions = Vec::new();
+    for entry_type in &manifest.entry_types {
+        let action = match storage.get_entry_type(&entry_type.name)? {
+            None => Action::Created,
+            Some(existing) if existing.schema_json == entry_type.schema => Action::Unchanged,
+            Some(_) if owns("entry_type", &entry_type.name) => Action::Updated,
+            Some(_) => {
+                return Err(conflict(
+                    "Entry type",
+                    &entry_type.name,
+                    &manifest.name,
+                    &format!("ledger types show {}", entry_type.name),
+                ))
+            }
+        };
+        type_actions.push((entry_type, action));
+    }
+    let mut template_actions = Vec::new();
+    for template in &manifest.templates {
+        let declared = manifest
+            .entry_types
+            .iter()
+            .any(|entry_type| entry_type.name == template.entry_type);
+        if !declared && storage.get_entry_type(&template.entry_type)?.is_none() {
+            return Err(anyhow::anyhow!(
+                "Template '{}' refers to unknown entry type '{}'",
+                template.name,
+                template.entry_type
+            ));
+        }
+        let template_json = manifest.template_json(template);
+        let action = match storage.get_template(&template.name)? {
+            None => Action::Created,
+            Some(existing) => {
+                let same_type = storage
+                    .get_entry_type(&template.entry_type)?
+                    .is_some_and(|entry_type| entry_type.id == existing.entry_type_id);
+                if same_type && existing.template_json == template_json {
+                    Action::Unchanged
+                } else if same_type && owns("template", &template.name) {
+                    Action::Updated
+                } else {
+                    return Err(conflict(
+                        "Template",
+                        &template.name,
+                        &manifest.name,
+                        &format!("ledger templates show {}", template.name),
+                    ));
+                }
+            }
+        };
+        template_actions.push((template, template_json, action));
+    }
+
+    storage.batch(|storage| {
+        for (entry_type, action) in &type_actions {
+            if *action != Action::Unchanged {
+                storage.create_entry_type(&NewEntryType::new(
+                    &entry_type.name,
+                    entry_type.schema.clone(),
+                    device_id,
+                ))?;
+            }
+        }
+        for (template, template_json, action) in &template_actions {
+            let entry_type = storage
+                .get_entry_type(&template.entry_type)?
+                .ok_or_else(|| {
+                    ledger_core::LedgerError::NotFound(format!(
+                        "Entry type '{}' not found",
+                        template.entry_type
+                    ))
+                })?;
+            let template_id = match (action, storage.get_template(&template.name)?) {
+                (Action::Created, _) | (_, None) => {
+                    let mut new_template = NewTemplate::new(
+                        &template.name,
+                        entry_type.id,
+                        template_json.clone(),
+                        device_id,
+                    );
+                    if let Some(ref description) = template.description {
+                        new_template = new_template.with_description(description);
+                    }
+                    storage.create_template(&new_template)?
+                }
+                (Action::Updated, Some(existing)) => {
+                    storage.update_template(&existing.id, template_json.clone())?;
+                    existing.id
+                }
+                (Action::Unchanged, Some(existing)) => existing.id,
+            };
+            if template.default {
+                storage.set_default_template(&entry_type.id, &template_id)?;
+            }
+        }
+        storage.record_pack(&InstalledPack {
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            description: manifest.description.clone(),
+            entry_types: manifest
+                .entry_types
+                .iter()
+                .map(|entry_type| entry_type.name.clone())
+                .collect(),
+            templates: manifest
+                .templates
+                .iter()
+                .map(|template| template.name.clone())
+                .collect(),
+            installed_at: Utc::now(),
+            device_id,
+        })
+    })?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if ctx.quiet() && !args.json {
+        return Ok(());
+    }
+    let ui_ctx = ctx.ui_context(args.json, None);
+    let previous_version = previous.map(|pack| pack.version);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "status": "ok",
+                "pack": manifest.name,
+                "version": manifest.version,
+                "previous_version": previous_version,
+                "entry_types": type_actions
+                    .iter()
+                    .map(|(entry_type, action)| serde_json::json!({
+                        "name": entry_type.name,
+                        "action": action.as_str(),
+                    }))
+                    .collect::<Vec<_>>(),
+                "templates": template_actions
+                    .iter()
+                    .map(|(template, _, action)| serde_json::json!({
+                        "name": template.name,
+                        "entry_type": template.entry_type,
+                        "action": action.as_str(),
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let message = match previous_version {
+                Some(ref version) if *version != manifest.version => format!(
+                    "Upgraded pack '{}' from {} to {}",
+                    manifest.name, version, manifest.version
+                ),
+                Some(_) => format!("Pack '{}' {} is installed", manifest.name, manifest.version),
+                None => format!("Installed pack '{}' {}", manifest.name, manifest.version),
+            };
+            print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, &message));
+            for (entry_type, action) in &type_actions {
+                print(
+                    &ui_ctx,
+                    &kv(
+                        &ui_ctx,
+                        "Type",
+                        &format!("{} ({})", entry_type.name, action.as_str()),
+                    ),
+                );
+            }
+            for (template, _, action) in &template_actions {
+                print(
+                    &ui_ctx,
+                    &kv(
+                        &ui_ctx,
+                        "Template",
+                        &format!("{} ({})", template.name, action.as_str()),
+                    ),
+                );
+            }
+            if let Some(first) = manifest.entry_types.first() {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+                blank_line(&ui_ctx);
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        &format!(
+                            "ledger add {}  {sep}  ledger types show {}",
+                            first.name, first.name
+                        ),
+                    ),
+                );
+            }
+        }
+        OutputMode::Plain => {
+            println!("status=ok");
+            println!("pack={}", manifest.name);
+            println!("version={}", manifest.version);
+            if let Some(ref version) = previous_version {
+                println!("previous_version={}", version);
+            }
+            for (entry_type, action) in &type_actions {
+                println!("entry_type={} action={}", entry_type.name, action.as_str());
+            }
+            for (template, _, action) in &template_actions {
+                println!("template={} action={}", template.name, action.as_str());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read a manifest from a file, or a built-in pack by name.
+fn load_manifest(source: &str) -> anyhow::Result<PackManifest> {
+    let path = Path::new(source);
+    if path.exists() {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", source, e))?;
+        return PackManifest::parse(&json);
+    }
+    builtin_pack(source).ok_or_else(|| {
+        anyhow::anyhow!(
+            "No pack manifest at {} and no built-in pack by that name\nHint: Built-in packs: {}",
+            source,
+            BUILTIN_PACKS.join(", ")
+        )
+    })
+}
+
+fn conflict(kind: &str, name: &str, pack: &str, compare: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "{} '{}' already exists and does not belong to pack '{}'\nHint: Rename it in the manifest, or run `{}` to compare.",
+        kind,
+        name,
+        pack,
+        compare
+    )
+}
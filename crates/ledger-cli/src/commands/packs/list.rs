@@ -0,0 +1,69 @@
+//! List installed entry type packs.
+
+use crate::app::AppContext;
+use crate::cli::PackListArgs;
+use crate::helpers::BUILTIN_PACKS;
+use crate::ui::{blank_line, header, hint, print, simple_table, Column, OutputMode};
+
+pub fn handle_list(ctx: &AppContext, args: &PackListArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let packs = storage.list_packs()?;
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            println!("{}", serde_json::to_string_pretty(&packs)?);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "packs", None));
+            blank_line(&ui_ctx);
+            if packs.is_empty() {
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        &format!(
+                            "No packs installed. Built-in packs: {}. Try `ledger packs install {}`.",
+                            BUILTIN_PACKS.join(", "),
+                            BUILTIN_PACKS[0]
+                        ),
+                    ),
+                );
+                return Ok(());
+            }
+            let columns = [
+                Column::new("Pack"),
+                Column::new("Version"),
+                Column::new("Types"),
+                Column::new("Templates"),
+                Column::new("Installed"),
+            ];
+            let rows: Vec<Vec<String>> = packs
+                .iter()
+                .map(|pack| {
+                    vec![
+                        pack.name.clone(),
+                        pack.version.clone(),
+                        pack.entry_types.join(", "),
+                        pack.templates.join(", "),
+                        pack.installed_at.format("%Y-%m-%d").to_string(),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+        }
+        OutputMode::Plain => {
+            println!("count={}", packs.len());
+            for pack in &packs {
+                println!(
+                    "pack={} version={} types={} templates={}",
+                    pack.name,
+                    pack.version,
+                    pack.entry_types.join(","),
+                    pack.templates.join(",")
+                );
+            }
+        }
+    }
+    Ok(())
+}
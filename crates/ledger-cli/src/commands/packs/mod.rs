@@ -0,0 +1,5 @@
+pub mod install;
+pub mod list;
+
+pub use install::handle_install;
+pub use list::handle_list;
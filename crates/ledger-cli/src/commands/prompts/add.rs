@@ -0,0 +1,61 @@
+//! Add a prompt to the prompts file.
+
+use crate::app::AppContext;
+use crate::cli::PromptAddArgs;
+use crate::helpers::append_prompt_line;
+use crate::ui::{badge, print, Badge, OutputMode};
+
+pub fn handle_add(ctx: &AppContext, args: &PromptAddArgs) -> anyhow::Result<()> {
+    let text = args.text.trim();
+    if text.is_empty() {
+        return Err(anyhow::anyhow!("Prompt text cannot be empty"));
+    }
+    if text.contains('\n') || text.starts_with('!') || text.starts_with('#') {
+        return Err(anyhow::anyhow!(
+            "Prompt must be one line and not start with '!' or '#'"
+        ));
+    }
+
+    let path = ctx.prompts_file()?;
+    let prompts = ctx.prompts()?;
+    let existing = prompts.all().iter().position(|prompt| prompt.text == text);
+    if existing.is_none() {
+        append_prompt_line(&path, text)?;
+    }
+    let number = existing.unwrap_or(prompts.all().len()) + 1;
+    let changed = existing.is_none();
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(args.json, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let message = match existing.map(|index| prompts.all()[index].disabled) {
+                    None => format!("Added prompt {} to {}", number, path.display()),
+                    Some(false) => format!("Prompt {} already exists", number),
+                    Some(true) => format!(
+                        "Prompt {} already exists but is disabled; run `ledger prompts disable {} --undo`",
+                        number, number
+                    ),
+                };
+                let kind = if changed { Badge::Ok } else { Badge::Info };
+                print(&ui_ctx, &badge(&ui_ctx, kind, &message));
+            }
+            OutputMode::Json => {
+                let output = serde_json::json!({
+                    "operation": "prompt.add",
+                    "number": number,
+                    "text": text,
+                    "changed": changed,
+                    "file": path,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+            OutputMode::Plain => {
+                println!("status=ok");
+                println!("prompt={}", number);
+                println!("changed={}", changed);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Take a prompt out of the rotation, or put it back.
+
+use crate::app::AppContext;
+use crate::cli::PromptDisableArgs;
+use crate::helpers::{append_prompt_line, remove_disable_lines};
+use crate::ui::{badge, print, Badge, OutputMode};
+
+pub fn handle_disable(ctx: &AppContext, args: &PromptDisableArgs) -> anyhow::Result<()> {
+    let path = ctx.prompts_file()?;
+    let prompts = ctx.prompts()?;
+    let prompt = prompts.find(&args.prompt).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Prompt '{}' not found\nHint: Run `ledger prompts list` to see prompt numbers.",
+            args.prompt
+        )
+    })?;
+
+    let changed = if args.undo {
+        remove_disable_lines(&path, &prompt.text)?
+    } else if prompt.disabled {
+        false
+    } else {
+        append_prompt_line(&path, &format!("!{}", prompt.text))?;
+        true
+    };
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(args.json, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let message = match (args.undo, changed) {
+                    (false, true) => format!("Disabled prompt: {}", prompt.text),
+                    (false, false) => format!("Prompt is already disabled: {}", prompt.text),
+                    (true, true) => format!("Prompt is back in the rotation: {}", prompt.text),
+                    (true, false) => format!("Prompt is not disabled: {}", prompt.text),
+                };
+                let kind = if changed { Badge::Ok } else { Badge::Info };
+                print(&ui_ctx, &badge(&ui_ctx, kind, &message));
+            }
+            OutputMode::Json => {
+                let operation = if args.undo {
+                    "prompt.enable"
+                } else {
+                    "prompt.disable"
+                };
+                let output = serde_json::json!({
+                    "operation": operation,
+                    "text": prompt.text,
+                    "changed": changed,
+                    "file": path,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+            OutputMode::Plain => {
+                println!("status=ok");
+                println!("disabled={}", !args.undo);
+                println!("changed={}", changed);
+            }
+        }
+    }
+    Ok(())
+}
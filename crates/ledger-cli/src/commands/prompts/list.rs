@@ -0,0 +1,102 @@
+//! Prompt listing, with today's prompt and disabled ones marked.
+
+use chrono::Local;
+
+use crate::app::AppContext;
+use crate::cli::PromptListArgs;
+use crate::ui::{blank_line, header, hint, print, simple_table, Column, OutputMode};
+
+pub fn handle_list(ctx: &AppContext, args: &PromptListArgs) -> anyhow::Result<()> {
+    let prompts = ctx.prompts()?;
+    let enabled = ctx.prompts_enabled()?;
+    let today = prompts
+        .for_day(Local::now().date_naive())
+        .map(|prompt| prompt.text.clone());
+    let is_today = |text: &str| enabled && today.as_deref() == Some(text);
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output: Vec<_> = prompts
+                .all()
+                .iter()
+                .enumerate()
+                .map(|(index, prompt)| {
+                    serde_json::json!({
+                        "number": index + 1,
+                        "text": prompt.text,
+                        "source": prompt.source,
+                        "disabled": prompt.disabled,
+                        "today": is_today(&prompt.text),
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "prompts", None));
+            blank_line(&ui_ctx);
+            if prompts.all().is_empty() {
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        "No prompts yet. Add one with `ledger prompts add`.",
+                    ),
+                );
+                return Ok(());
+            }
+            let columns = [
+                Column::new("#"),
+                Column::new("Prompt"),
+                Column::new("Source"),
+                Column::new("Status"),
+            ];
+            let rows: Vec<Vec<String>> = prompts
+                .all()
+                .iter()
+                .enumerate()
+                .map(|(index, prompt)| {
+                    let status = if prompt.disabled {
+                        "disabled"
+                    } else if is_today(&prompt.text) {
+                        "today"
+                    } else {
+                        ""
+                    };
+                    vec![
+                        (index + 1).to_string(),
+                        prompt.text.clone(),
+                        prompt.source.as_str().to_string(),
+                        status.to_string(),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            if !enabled {
+                blank_line(&ui_ctx);
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        "Set [prompts] enabled = true to show a prompt in `add journal` and `today`.",
+                    ),
+                );
+            }
+        }
+        OutputMode::Plain => {
+            println!("count={}", prompts.all().len());
+            for (index, prompt) in prompts.all().iter().enumerate() {
+                println!(
+                    "prompt={} source={} disabled={} today={} text={}",
+                    index + 1,
+                    prompt.source.as_str(),
+                    prompt.disabled,
+                    is_today(&prompt.text),
+                    prompt.text
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,7 @@
+pub mod add;
+pub mod disable;
+pub mod list;
+
+pub use add::handle_add;
+pub use disable::handle_disable;
+pub use list::handle_list;
@@ -0,0 +1,250 @@
+//! Flag unusual values of a numeric metric field.
+//!
+//! Each value is compared with the values logged in the window before it
+//! (outliers) and with the value just before it (jumps, which catch typos
+//! such as 8.5 for 85 even without much history).
+
+use chrono::{Duration, Local};
+
+use ledger_core::storage::{Entry, EntryFilter, StorageEngine};
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::MetricAnomaliesArgs;
+use crate::helpers::{
+    jump_ratio, metric_day, numeric_field, parse_duration, rolling_baselines, Summary, MIN_BASELINE,
+};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{
+    badge, blank_line, header_with_context, hint, print, short_id, simple_table, Badge, Column,
+    OutputMode,
+};
+
+/// One flagged value and why it stood out.
+struct Anomaly<'a> {
+    entry: &'a Entry,
+    day: chrono::NaiveDate,
+    value: f64,
+    baseline: Option<Summary>,
+    z: Option<f64>,
+    previous: Option<f64>,
+    ratio: Option<f64>,
+    reasons: Vec<&'static str>,
+}
+
+pub fn handle_metric_anomalies(ctx: &AppContext, args: &MetricAnomaliesArgs) -> anyhow::Result<()> {
+    if !args.sigma.is_finite() || args.sigma <= 0.0 {
+        return Err(anyhow::anyhow!("--sigma must be greater than 0"));
+    }
+    if !args.max_ratio.is_finite() || args.max_ratio <= 1.0 {
+        return Err(anyhow::anyhow!("--max-ratio must be greater than 1"));
+    }
+    let window_days = parse_duration(&args.window)?.num_days().max(1);
+    let (type_name, field) = match args.metric.split_once('.') {
+        Some((type_name, field)) => (type_name, Some(field)),
+        None => (args.metric.as_str(), None),
+    };
+
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let entry_type = storage
+        .get_entry_type(type_name)?
+        .ok_or_else(|| anyhow::anyhow!("Entry type not found: {}", type_name))?;
+    let field = numeric_field(&entry_type.schema_json, type_name, field)?;
+
+    let superseded = storage.superseded_entry_ids()?;
+    let mut entries: Vec<Entry> = storage
+        .list_entries(&EntryFilter::new().entry_type(entry_type.id))?
+        .into_iter()
+        .filter(|entry| !superseded.contains(&entry.id))
+        .collect();
+    entries.sort_by_key(|entry| (metric_day(entry), entry.created_at));
+    let readings: Vec<(&Entry, chrono::NaiveDate, f64)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let value = entry.data.get(&field).and_then(|v| v.as_f64())?;
+            Some((entry, metric_day(entry), value))
+        })
+        .collect();
+    let points: Vec<(chrono::NaiveDate, f64)> = readings
+        .iter()
+        .map(|(_, day, value)| (*day, *value))
+        .collect();
+    let baselines = rolling_baselines(&points, window_days);
+
+    let today = Local::now().date_naive();
+    let first_day = today - Duration::days(window_days - 1);
+    let mut checked = 0;
+    let mut anomalies = Vec::new();
+    // Jumps are measured from the last unflagged value, so the reading after
+    // a typo isn't flagged for jumping back
+    let mut previous: Option<f64> = None;
+    for (index, (entry, day, value)) in readings.iter().enumerate() {
+        if *day < first_day {
+            previous = Some(*value);
+            continue;
+        }
+        checked += 1;
+        let baseline = baselines[index];
+        let z = baseline
+            .filter(|summary| summary.count >= MIN_BASELINE)
+            .and_then(|summary| summary.z_score(*value));
+        let ratio = previous.and_then(|prev| jump_ratio(prev, *value));
+
+        let mut reasons = Vec::new();
+        if z.is_some_and(|z| z.abs() > args.sigma) {
+            reasons.push("sigma");
+        }
+        if ratio.is_some_and(|ratio| ratio >= args.max_ratio) {
+            reasons.push("jump");
+        }
+        if reasons.is_empty() {
+            previous = Some(*value);
+        } else {
+            anomalies.push(Anomaly {
+                entry,
+                day: *day,
+                value: *value,
+                baseline,
+                z,
+                previous,
+                ratio,
+                reasons,
+            });
+        }
+    }
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "metric": type_name,
+                "field": field,
+                "window": args.window,
+                "sigma": args.sigma,
+                "max_ratio": args.max_ratio,
+                "checked": checked,
+                "anomalies": anomalies
+                    .iter()
+                    .map(|anomaly| serde_json::json!({
+                        "id": anomaly.entry.id,
+                        "date": anomaly.day,
+                        "created_at": anomaly.entry.created_at.to_rfc3339(),
+                        "value": anomaly.value,
+                        "mean": anomaly.baseline.map(|b| round3(b.mean)),
+                        "stddev": anomaly.baseline.map(|b| round3(b.stddev)),
+                        "z": anomaly.z.map(round3),
+                        "previous": anomaly.previous,
+                        "ratio": anomaly.ratio.map(round3),
+                        "reasons": anomaly.reasons,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Plain => {
+            println!("metric={}.{}", type_name, field);
+            println!("checked={}", checked);
+            for anomaly in &anomalies {
+                println!(
+                    "anomaly={} date={} value={} reasons={}",
+                    anomaly.entry.id,
+                    anomaly.day,
+                    anomaly.value,
+                    anomaly.reasons.join(",")
+                );
+            }
+        }
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            let context = format!(
+                "{}.{}  {sep}  last {}  {sep}  {} checked",
+                type_name, field, args.window, checked
+            );
+            print(
+                &ui_ctx,
+                &header_with_context(&ui_ctx, "anomalies", Some(&context), ledger_path.as_deref()),
+            );
+            blank_line(&ui_ctx);
+            if anomalies.is_empty() {
+                print(
+                    &ui_ctx,
+                    &badge(&ui_ctx, Badge::Ok, "No anomalies in this window"),
+                );
+                return Ok(());
+            }
+            let columns = [
+                Column::new("Date"),
+                Column::new("Value"),
+                Column::new("Expected"),
+                Column::new("Why"),
+                Column::new("ID"),
+            ];
+            let plus_minus = if ui_ctx.unicode { "±" } else { "+/-" };
+            let rows: Vec<Vec<String>> = anomalies
+                .iter()
+                .map(|anomaly| {
+                    vec![
+                        anomaly.day.to_string(),
+                        format_value(anomaly.value),
+                        anomaly
+                            .baseline
+                            .filter(|b| b.count >= MIN_BASELINE)
+                            .map(|b| {
+                                format!(
+                                    "{} {} {}",
+                                    format_value(b.mean),
+                                    plus_minus,
+                                    format_value(b.stddev)
+                                )
+                            })
+                            .or_else(|| anomaly.previous.map(|p| format!("~{}", format_value(p))))
+                            .unwrap_or_else(|| "-".to_string()),
+                        describe(anomaly),
+                        short_id(&anomaly.entry.id),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            blank_line(&ui_ctx);
+            println!(
+                "{}",
+                styled(
+                    &format!(
+                        "Outliers are over {} standard deviations from the previous {}; jumps are {}x the previous value.",
+                        args.sigma, args.window, args.max_ratio
+                    ),
+                    styles::dim(),
+                    ui_ctx.color
+                )
+            );
+            print(
+                &ui_ctx,
+                &hint(
+                    &ui_ctx,
+                    &format!("ledger edit {}", short_id(&anomalies[0].entry.id)),
+                ),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn describe(anomaly: &Anomaly<'_>) -> String {
+    let mut parts = Vec::new();
+    if let Some(z) = anomaly.z.filter(|_| anomaly.reasons.contains(&"sigma")) {
+        parts.push(format!("{:+.1} sd", z));
+    }
+    if let Some(ratio) = anomaly.ratio.filter(|_| anomaly.reasons.contains(&"jump")) {
+        parts.push(format!("{:.1}x jump", ratio));
+    }
+    parts.join(", ")
+}
+
+fn format_value(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    rounded.to_string()
+}
+
+fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
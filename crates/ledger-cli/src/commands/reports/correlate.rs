@@ -0,0 +1,328 @@
+//! Correlate two daily series: numeric metric fields or habit completions.
+
+use std::collections::BTreeMap;
+
+use chrono::{Duration, Local, NaiveDate};
+
+use ledger_core::storage::{AgeSqliteStorage, EntryFilter, StorageEngine};
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::CorrelateArgs;
+use crate::commands::habits::status::completions;
+use crate::helpers::{metric_day, numeric_field, parse_duration};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{
+    blank_line, header_with_context, hint, print, simple_table, sparkline, Column, OutputMode,
+};
+
+/// Largest lag that can be scanned with `--max-lag`.
+const MAX_LAG_DAYS: u32 = 60;
+
+/// Fewest paired days before a coefficient is reported.
+const MIN_PAIRS: usize = 3;
+
+/// Correlation at one lag.
+#[derive(Debug, Clone, PartialEq)]
+struct LagResult {
+    lag: u32,
+    pairs: usize,
+    r: Option<f64>,
+}
+
+pub fn handle_correlate(ctx: &AppContext, args: &CorrelateArgs) -> anyhow::Result<()> {
+    let days = parse_duration(&args.last)?.num_days().max(1);
+    let max_lag = args.max_lag.unwrap_or(args.lag);
+    if max_lag > MAX_LAG_DAYS {
+        return Err(anyhow::anyhow!("Lag is limited to {} days", MAX_LAG_DAYS));
+    }
+    let lags: Vec<u32> = match args.max_lag {
+        Some(max) => (0..=max).collect(),
+        None => vec![args.lag],
+    };
+
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let today = Local::now().date_naive();
+    let first_day = today - Duration::days(days - 1);
+    let first = daily_series(&storage, &args.first, first_day, today)?;
+    let second = daily_series(&storage, &args.second, first_day, today)?;
+    let results: Vec<LagResult> = lags
+        .iter()
+        .map(|&lag| {
+            let (pairs, r) = lagged_correlation(&first, &second, lag as usize);
+            LagResult { lag, pairs, r }
+        })
+        .collect();
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "first": args.first,
+                "second": args.second,
+                "from": first_day,
+                "to": today,
+                "results": results
+                    .iter()
+                    .map(|result| serde_json::json!({
+                        "lag": result.lag,
+                        "pairs": result.pairs,
+                        "r": result.r.map(round3),
+                    }))
+                    .collect::<Vec<_>>(),
+                "series": {
+                    "first": first,
+                    "second": second,
+                },
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            let context = format!(
+                "{} ~ {}  {sep}  last {}",
+                args.first, args.second, args.last
+            );
+            print(
+                &ui_ctx,
+                &header_with_context(&ui_ctx, "correlate", Some(&context), ledger_path.as_deref()),
+            );
+            blank_line(&ui_ctx);
+            let columns = [
+                Column::new("Lag"),
+                Column::new("Days"),
+                Column::new("r"),
+                Column::new("Strength"),
+            ];
+            let rows: Vec<Vec<String>> = results
+                .iter()
+                .map(|result| {
+                    vec![
+                        format!("{}d", result.lag),
+                        result.pairs.to_string(),
+                        result
+                            .r
+                            .map(|r| format!("{:+.2}", r))
+                            .unwrap_or_else(|| "-".to_string()),
+                        result
+                            .r
+                            .map(strength)
+                            .unwrap_or_else(|| "not enough data".to_string()),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            blank_line(&ui_ctx);
+            let label_width = args.first.len().max(args.second.len());
+            for (label, series) in [(&args.first, &first), (&args.second, &second)] {
+                println!(
+                    "{}  {}",
+                    styled(
+                        &format!("{:<width$}", label, width = label_width),
+                        styles::dim(),
+                        ui_ctx.color
+                    ),
+                    sparkline(series, ui_ctx.unicode)
+                );
+            }
+            blank_line(&ui_ctx);
+            print(
+                &ui_ctx,
+                &hint(
+                    &ui_ctx,
+                    "Correlation is not causation; small samples swing widely.",
+                ),
+            );
+        }
+        OutputMode::Plain => {
+            println!("first={}", args.first);
+            println!("second={}", args.second);
+            println!("from={}", first_day);
+            println!("to={}", today);
+            for result in &results {
+                let r = result
+                    .r
+                    .map(|r| format!("{:.3}", r))
+                    .unwrap_or_else(|| "-".to_string());
+                println!("lag={} pairs={} r={}", result.lag, result.pairs, r);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One value per day from `first` to `last`, `None` where nothing was logged.
+///
+/// `metric:<type>[.<field>]` averages a numeric field per day; entries with
+/// a `date` field count on that day, others on their local creation date.
+/// `habit:<name>` is 1 on completed days and 0 on other scheduled days.
+fn daily_series(
+    storage: &AgeSqliteStorage,
+    spec: &str,
+    first: NaiveDate,
+    last: NaiveDate,
+) -> anyhow::Result<Vec<Option<f64>>> {
+    let days = (last - first).num_days() as usize + 1;
+    let day_index = |date: NaiveDate| {
+        let offset = (date - first).num_days();
+        (0..days as i64)
+            .contains(&offset)
+            .then_some(offset as usize)
+    };
+
+    let (kind, name) = spec.split_once(':').ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid series: {} (use metric:<type>[.<field>] or habit:<name>)",
+            spec
+        )
+    })?;
+    match kind {
+        "metric" => {
+            let (type_name, field) = match name.split_once('.') {
+                Some((type_name, field)) => (type_name, Some(field)),
+                None => (name, None),
+            };
+            let entry_type = storage
+                .get_entry_type(type_name)?
+                .ok_or_else(|| anyhow::anyhow!("Entry type not found: {}", type_name))?;
+            let field = numeric_field(&entry_type.schema_json, type_name, field)?;
+
+            let superseded = storage.superseded_entry_ids()?;
+            let mut sums: BTreeMap<usize, (f64, usize)> = BTreeMap::new();
+            for entry in storage.list_entries(&EntryFilter::new().entry_type(entry_type.id))? {
+                if superseded.contains(&entry.id) {
+                    continue;
+                }
+                let Some(value) = entry.data.get(&field).and_then(|v| v.as_f64()) else {
+                    continue;
+                };
+                if let Some(index) = day_index(metric_day(&entry)) {
+                    let slot = sums.entry(index).or_insert((0.0, 0));
+                    slot.0 += value;
+                    slot.1 += 1;
+                }
+            }
+            let mut series = vec![None; days];
+            for (index, (sum, count)) in sums {
+                series[index] = Some(sum / count as f64);
+            }
+            Ok(series)
+        }
+        "habit" => {
+            let habit = storage
+                .get_habit(name)?
+                .ok_or_else(|| anyhow::anyhow!("Habit not found: {}", name))?;
+            let done = match storage.get_entry_type("habit")? {
+                Some(entry_type) => completions(storage, entry_type.id)?
+                    .remove(&habit.name)
+                    .unwrap_or_default(),
+                None => Default::default(),
+            };
+            let since = done
+                .iter()
+                .next()
+                .copied()
+                .into_iter()
+                .chain([habit.created_at.with_timezone(&Local).date_naive()])
+                .min()
+                .unwrap_or(first);
+            Ok(first
+                .iter_days()
+                .take(days)
+                .map(|date| {
+                    if done.contains(&date) {
+                        Some(1.0)
+                    } else if date >= since && habit.schedule.is_scheduled(date) {
+                        Some(0.0)
+                    } else {
+                        None
+                    }
+                })
+                .collect())
+        }
+        other => Err(anyhow::anyhow!(
+            "Unknown series kind: {} (use metric or habit)",
+            other
+        )),
+    }
+}
+
+/// Pearson correlation of `a[i]` with `b[i + lag]` over days where both exist.
+fn lagged_correlation(a: &[Option<f64>], b: &[Option<f64>], lag: usize) -> (usize, Option<f64>) {
+    let pairs: Vec<(f64, f64)> = a
+        .iter()
+        .zip(b.iter().skip(lag))
+        .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+        .collect();
+    let n = pairs.len();
+    if n < MIN_PAIRS {
+        return (n, None);
+    }
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n as f64;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n as f64;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in &pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    // A flat series has no defined correlation
+    if var_x == 0.0 || var_y == 0.0 {
+        return (n, None);
+    }
+    (n, Some(cov / (var_x * var_y).sqrt()))
+}
+
+fn strength(r: f64) -> String {
+    let size = match r.abs() {
+        a if a < 0.1 => return "none".to_string(),
+        a if a < 0.3 => "weak",
+        a if a < 0.5 => "moderate",
+        _ => "strong",
+    };
+    let direction = if r > 0.0 { "positive" } else { "negative" };
+    format!("{} {}", size, direction)
+}
+
+fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lagged_correlation() {
+        let a = [Some(1.0), Some(2.0), Some(3.0), Some(4.0), None];
+        let b = [Some(2.0), Some(4.0), Some(6.0), Some(8.0), Some(10.0)];
+        let (pairs, r) = lagged_correlation(&a, &b, 0);
+        assert_eq!(pairs, 4);
+        assert!((r.unwrap() - 1.0).abs() < 1e-9);
+
+        // Shifted by a day, b rises while a rises too
+        let (pairs, r) = lagged_correlation(&a, &b, 1);
+        assert_eq!(pairs, 4);
+        assert!((r.unwrap() - 1.0).abs() < 1e-9);
+
+        let reversed: Vec<Option<f64>> = b.iter().rev().copied().collect();
+        let (_, r) = lagged_correlation(&a, &reversed, 0);
+        assert!((r.unwrap() + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_correlation_needs_pairs_and_variance() {
+        let a = [Some(1.0), Some(2.0)];
+        assert_eq!(lagged_correlation(&a, &a, 0), (2, None));
+        let flat = [Some(3.0), Some(3.0), Some(3.0)];
+        let rising = [Some(1.0), Some(2.0), Some(3.0)];
+        assert_eq!(lagged_correlation(&flat, &rising, 0), (3, None));
+    }
+
+    #[test]
+    fn test_strength_labels() {
+        assert_eq!(strength(0.05), "none");
+        assert_eq!(strength(-0.4), "moderate negative");
+        assert_eq!(strength(0.8), "strong positive");
+    }
+}
@@ -0,0 +1,142 @@
+//! Digest: a week or month of entries, habit streaks and tagged highlights,
+//! rendered as Markdown or HTML for mailing from cron.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use anyhow::Context;
+use chrono::{Duration, Local};
+
+use ledger_core::storage::{Entry, EntryFilter, StorageEngine};
+
+use crate::app::AppContext;
+use crate::cli::DigestArgs;
+use crate::collectors::local_day_bounds;
+use crate::commands::habits::status::{completions, streak_unit};
+use crate::output::{
+    entry_type_name_map, Digest, DigestFormat, DigestItem, DigestPeriod, DigestSection,
+    DigestStreak, EntrySummaries,
+};
+use crate::ui::{badge, single_line, truncate, Badge, OutputMode};
+
+/// Maximum length of a listed entry's summary.
+const ITEM_MAX: usize = 120;
+
+pub fn handle_digest(ctx: &AppContext, args: &DigestArgs) -> anyhow::Result<()> {
+    let period = DigestPeriod::parse(&args.period)
+        .ok_or_else(|| anyhow::anyhow!("Invalid period: {} (use week or month)", args.period))?;
+    let format = DigestFormat::parse(&args.format)
+        .ok_or_else(|| anyhow::anyhow!("Invalid format: {} (use md or html)", args.format))?;
+
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let name_map = entry_type_name_map(&storage)?;
+    let summaries = EntrySummaries::load(&storage)?;
+
+    let last = Local::now().date_naive();
+    let first = last - Duration::days(period.days() - 1);
+    let (since, _) = local_day_bounds(first)
+        .ok_or_else(|| anyhow::anyhow!("Invalid period start: {}", first))?;
+    let entries = storage.list_entries(&EntryFilter::new().since(since).latest_only())?;
+
+    let type_name = |entry: &Entry| {
+        name_map
+            .get(&entry.entry_type_id)
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in &entries {
+        *counts.entry(type_name(entry)).or_default() += 1;
+    }
+    let mut by_type: Vec<(String, usize)> = counts.into_iter().collect();
+    by_type.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let done = match storage.get_entry_type("habit")? {
+        Some(entry_type) => completions(&storage, entry_type.id)?,
+        None => HashMap::new(),
+    };
+    let streaks = storage
+        .list_habits()?
+        .into_iter()
+        .map(|habit| {
+            let (current, longest) = match done.get(&habit.name) {
+                Some(dates) => habit.schedule.streaks(dates, last),
+                None => (0, 0),
+            };
+            DigestStreak {
+                unit: streak_unit(&habit.schedule),
+                habit: habit.name,
+                current,
+                longest,
+            }
+        })
+        .collect();
+
+    let items = |selected: Vec<&Entry>| -> Vec<DigestItem> {
+        selected
+            .into_iter()
+            .take(args.limit)
+            .map(|entry| DigestItem {
+                date: entry.created_at.with_timezone(&Local).date_naive(),
+                entry_type: type_name(entry),
+                summary: truncate(&single_line(&summaries.summary(entry)), ITEM_MAX),
+            })
+            .collect()
+    };
+    let sections = if args.tag.is_empty() {
+        vec![DigestSection {
+            title: "Recent entries".to_string(),
+            items: items(entries.iter().collect()),
+        }]
+    } else {
+        args.tag
+            .iter()
+            .map(|tag| DigestSection {
+                title: format!("#{}", tag),
+                items: items(entries.iter().filter(|e| e.tags.contains(tag)).collect()),
+            })
+            .collect()
+    };
+
+    let digest = Digest {
+        period,
+        first,
+        last,
+        total: entries.len(),
+        by_type,
+        streaks,
+        sections,
+    };
+    let rendered = digest.render(format);
+
+    let Some(ref path) = args.out else {
+        print!("{}", rendered);
+        return Ok(());
+    };
+    let mut out =
+        BufWriter::new(File::create(path).with_context(|| format!("Failed to create {}", path))?);
+    out.write_all(rendered.as_bytes())?;
+    out.flush()?;
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                eprintln!(
+                    "{}",
+                    badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Wrote {} digest to {}", args.period, path)
+                    )
+                );
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                eprintln!("out={}", path);
+                eprintln!("entry_count={}", entries.len());
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,359 @@
+//! Expenses report: monthly category totals against `[budgets]`.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+
+use ledger_core::storage::{AggregateQuery, StorageEngine};
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::ExpensesReportArgs;
+use crate::output::Locale;
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{blank_line, header_with_context, hint, print, simple_table, Column, OutputMode};
+
+/// One category line of the report.
+#[derive(Debug, Clone, PartialEq)]
+struct CategoryTotal {
+    category: String,
+    currency: String,
+    count: usize,
+    spent: f64,
+    budget: Option<f64>,
+}
+
+impl CategoryTotal {
+    fn remaining(&self) -> Option<f64> {
+        self.budget.map(|budget| budget - self.spent)
+    }
+
+    /// Share of the budget already spent, in percent.
+    fn used_percent(&self) -> Option<u32> {
+        match self.budget {
+            Some(budget) if budget > 0.0 => Some((self.spent / budget * 100.0).round() as u32),
+            _ => None,
+        }
+    }
+}
+
+pub fn handle_expenses_report(ctx: &AppContext, args: &ExpensesReportArgs) -> anyhow::Result<()> {
+    let today = Local::now().date_naive();
+    let first = match args.month {
+        Some(ref month) => parse_month(month)?,
+        None => today.with_day(1).unwrap_or(today),
+    };
+    let next = next_month(first);
+    let budget_currency = args
+        .currency
+        .as_ref()
+        .map(|code| code.trim().to_ascii_uppercase());
+
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let rows = match storage.get_entry_type("expense")? {
+        Some(expense) => storage.aggregate(
+            &AggregateQuery::new(expense.id, "amount")
+                .group_by("currency")
+                .group_by("category")
+                .since(local_midnight(first))
+                .until(local_midnight(next) - Duration::nanoseconds(1)),
+        )?,
+        None => Vec::new(),
+    };
+
+    let budgets: BTreeMap<String, f64> = ctx
+        .config()?
+        .map(|config| {
+            config
+                .budgets
+                .iter()
+                .map(|(category, amount)| (category.trim().to_lowercase(), *amount))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut currencies: Vec<String> = rows
+        .iter()
+        .map(|row| row.group[0].clone().unwrap_or_default())
+        .collect();
+    currencies.dedup();
+    // Budgets are plain numbers, so they only apply to one currency
+    let budgeted = budget_currency
+        .clone()
+        .or_else(|| match currencies.as_slice() {
+            [only] => Some(only.clone()),
+            _ => None,
+        });
+
+    let mut categories: Vec<CategoryTotal> = rows
+        .iter()
+        .map(|row| {
+            let currency = row.group[0].clone().unwrap_or_default();
+            let category = row.group[1]
+                .clone()
+                .unwrap_or_else(|| "uncategorized".to_string());
+            let budget = if budgeted.as_deref() == Some(currency.as_str()) {
+                budgets.get(&category).copied()
+            } else {
+                None
+            };
+            CategoryTotal {
+                category,
+                currency,
+                count: row.count,
+                spent: row.sum,
+                budget,
+            }
+        })
+        .collect();
+    if let Some(ref currency) = budgeted {
+        for (category, budget) in &budgets {
+            let seen = categories
+                .iter()
+                .any(|c| &c.category == category && &c.currency == currency);
+            if !seen {
+                categories.push(CategoryTotal {
+                    category: category.clone(),
+                    currency: currency.clone(),
+                    count: 0,
+                    spent: 0.0,
+                    budget: Some(*budget),
+                });
+            }
+        }
+    }
+    categories.sort_by(|a, b| (&a.currency, &a.category).cmp(&(&b.currency, &b.category)));
+    let totals = currency_totals(&categories);
+
+    // Pace only means something while the month is still running
+    let pace = (first <= today && today < next).then(|| {
+        let days = (next - first).num_days();
+        (today.day() as i64, days)
+    });
+    let month = first.format("%Y-%m").to_string();
+    let mixed_without_budget_currency =
+        budgeted.is_none() && currencies.len() > 1 && !budgets.is_empty();
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "month": month,
+                "budget_currency": budgeted,
+                "day": pace.map(|(day, _)| day),
+                "days": pace.map(|(_, days)| days),
+                "categories": categories
+                    .iter()
+                    .map(|c| serde_json::json!({
+                        "category": c.category,
+                        "currency": c.currency,
+                        "count": c.count,
+                        "spent": round_cents(c.spent),
+                        "budget": c.budget,
+                        "remaining": c.remaining().map(round_cents),
+                    }))
+                    .collect::<Vec<_>>(),
+                "totals": totals
+                    .iter()
+                    .map(|(currency, count, spent)| serde_json::json!({
+                        "currency": currency,
+                        "count": count,
+                        "spent": round_cents(*spent),
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            let locale = ctx.locale()?;
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            let context = match pace {
+                Some((day, days)) => format!("{}  {sep}  day {} of {}", month, day, days),
+                None => month.clone(),
+            };
+            print(
+                &ui_ctx,
+                &header_with_context(
+                    &ui_ctx,
+                    "expenses report",
+                    Some(&context),
+                    ledger_path.as_deref(),
+                ),
+            );
+            blank_line(&ui_ctx);
+            if categories.is_empty() {
+                print(&ui_ctx, &hint(&ui_ctx, "No expenses this month."));
+                return Ok(());
+            }
+            let columns = [
+                Column::new("Category"),
+                Column::new("Count"),
+                Column::new("Spent"),
+                Column::new("Budget"),
+                Column::new("Remaining"),
+                Column::new("Used"),
+            ];
+            let rows: Vec<Vec<String>> = categories
+                .iter()
+                .map(|c| {
+                    vec![
+                        c.category.clone(),
+                        c.count.to_string(),
+                        money(&locale, c.spent, &c.currency),
+                        c.budget
+                            .map(|b| money(&locale, b, &c.currency))
+                            .unwrap_or_else(|| "-".to_string()),
+                        c.remaining()
+                            .map(|r| money(&locale, r, &c.currency))
+                            .unwrap_or_else(|| "-".to_string()),
+                        c.used_percent()
+                            .map(|p| format!("{}%", p))
+                            .unwrap_or_else(|| "-".to_string()),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            blank_line(&ui_ctx);
+            let summary: Vec<String> = totals
+                .iter()
+                .map(|(currency, count, spent)| {
+                    format!("{} in {} entries", money(&locale, *spent, currency), count)
+                })
+                .collect();
+            let mut context = format!("Total: {}", summary.join(&format!("  {sep}  ")));
+            if let Some((day, days)) = pace {
+                context.push_str(&format!("  {sep}  {}% of the month gone", day * 100 / days));
+            }
+            println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            if mixed_without_budget_currency {
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        "Expenses use several currencies; pass --currency to compare budgets.",
+                    ),
+                );
+            }
+        }
+        OutputMode::Plain => {
+            println!("month={}", month);
+            if let Some((day, days)) = pace {
+                println!("day={}", day);
+                println!("days={}", days);
+            }
+            for c in &categories {
+                let mut line = format!(
+                    "category={} currency={} count={} spent={:.2}",
+                    c.category, c.currency, c.count, c.spent
+                );
+                if let (Some(budget), Some(remaining)) = (c.budget, c.remaining()) {
+                    line.push_str(&format!(" budget={:.2} remaining={:.2}", budget, remaining));
+                }
+                println!("{}", line);
+            }
+            for (currency, count, spent) in &totals {
+                println!(
+                    "total currency={} count={} spent={:.2}",
+                    currency, count, spent
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse `YYYY-MM` into the first day of that month.
+fn parse_month(value: &str) -> anyhow::Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", value.trim()), "%Y-%m-%d")
+        .map_err(|_| anyhow::anyhow!("Invalid month: {} (use YYYY-MM, e.g. 2025-01)", value))
+}
+
+fn next_month(first: NaiveDate) -> NaiveDate {
+    let (year, month) = if first.month() == 12 {
+        (first.year() + 1, 1)
+    } else {
+        (first.year(), first.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(first)
+}
+
+/// Start of a local calendar day, as UTC.
+fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    let naive = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&naive))
+}
+
+/// Per-currency entry counts and sums, in currency order.
+fn currency_totals(categories: &[CategoryTotal]) -> Vec<(String, usize, f64)> {
+    let mut totals: Vec<(String, usize, f64)> = Vec::new();
+    for c in categories.iter().filter(|c| c.count > 0) {
+        match totals.last_mut() {
+            Some((currency, count, spent)) if *currency == c.currency => {
+                *count += c.count;
+                *spent += c.spent;
+            }
+            _ => totals.push((c.currency.clone(), c.count, c.spent)),
+        }
+    }
+    totals
+}
+
+fn money(locale: &Locale, amount: f64, currency: &str) -> String {
+    match serde_json::Number::from_f64(amount) {
+        Some(number) => locale.currency(&number, currency, None),
+        None => format!("{} {}", amount, currency),
+    }
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_month() {
+        assert_eq!(
+            parse_month("2025-01").unwrap(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        );
+        assert!(parse_month("2025-13").is_err());
+        assert!(parse_month("January").is_err());
+    }
+
+    #[test]
+    fn test_next_month_rolls_over_year() {
+        let december = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+        assert_eq!(
+            next_month(december),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_currency_totals_skip_unspent_budgets() {
+        let line = |category: &str, currency: &str, count, spent| CategoryTotal {
+            category: category.to_string(),
+            currency: currency.to_string(),
+            count,
+            spent,
+            budget: None,
+        };
+        let totals = currency_totals(&[
+            line("food", "EUR", 2, 10.0),
+            line("rent", "EUR", 1, 500.0),
+            line("travel", "EUR", 0, 0.0),
+            line("food", "USD", 1, 4.5),
+        ]);
+        assert_eq!(
+            totals,
+            vec![("EUR".to_string(), 3, 510.0), ("USD".to_string(), 1, 4.5)]
+        );
+    }
+}
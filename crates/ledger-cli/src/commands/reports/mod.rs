@@ -0,0 +1,19 @@
+pub mod anomalies;
+pub mod correlate;
+pub mod digest;
+pub mod expenses;
+pub mod standup;
+pub mod stats;
+pub mod today;
+pub mod upcoming;
+pub mod week;
+
+pub use anomalies::handle_metric_anomalies;
+pub use correlate::handle_correlate;
+pub use digest::handle_digest;
+pub use expenses::handle_expenses_report;
+pub use standup::handle_standup;
+pub use stats::handle_stats;
+pub use today::handle_today;
+pub use upcoming::handle_upcoming;
+pub use week::handle_week;
@@ -0,0 +1,239 @@
+//! Standup report: a "Yesterday / Today / Blockers" markdown skeleton.
+
+use std::collections::BTreeMap;
+
+use chrono::{Local, NaiveDate, Utc};
+use uuid::Uuid;
+
+use ledger_core::storage::{EntryFilter, StorageEngine};
+
+use crate::app::AppContext;
+use crate::cli::StandupArgs;
+use crate::helpers::{copy_to_clipboard, parse_duration};
+use crate::output::{entry_type_name_map, EntrySummaries};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, print, single_line, truncate, Badge, OutputMode};
+
+/// Tags that move an entry from "Yesterday" to "Blockers".
+const BLOCKER_TAGS: &[&str] = &["blocker", "blocked"];
+
+/// Maximum length of a single bullet.
+const ITEM_MAX: usize = 120;
+
+/// Heading for entries that are not part of any composition.
+const UNGROUPED: &str = "Other";
+
+struct StandupItem {
+    id: Uuid,
+    entry_type: String,
+    text: String,
+}
+
+struct StandupGroup {
+    composition: Option<String>,
+    items: Vec<StandupItem>,
+}
+
+pub fn handle_standup(ctx: &AppContext, args: &StandupArgs) -> anyhow::Result<()> {
+    if args.copy && args.json {
+        return Err(anyhow::anyhow!("--copy cannot be used with --json"));
+    }
+    let window = parse_duration(&args.last)?;
+
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let name_map = entry_type_name_map(&storage)?;
+    let summaries = EntrySummaries::load(&storage)?;
+
+    let mut filter = EntryFilter::new().since(Utc::now() - window);
+    if let Some(ref tag) = args.tag {
+        filter = filter.tag(tag.clone());
+    }
+    let mut entries = storage.list_entries(&filter)?;
+    let superseded = storage.superseded_entry_ids()?;
+    entries.retain(|entry| !superseded.contains(&entry.id));
+    // Oldest first reads naturally in a status update
+    entries.reverse();
+
+    let mut grouped: BTreeMap<String, Vec<StandupItem>> = BTreeMap::new();
+    let mut ungrouped = Vec::new();
+    let mut blockers = Vec::new();
+    for entry in &entries {
+        let item = StandupItem {
+            id: entry.id,
+            entry_type: name_map
+                .get(&entry.entry_type_id)
+                .cloned()
+                .unwrap_or_else(|| "unknown".to_string()),
+            text: truncate(&single_line(&summaries.summary(entry)), ITEM_MAX),
+        };
+        if entry
+            .tags
+            .iter()
+            .any(|tag| BLOCKER_TAGS.contains(&tag.as_str()))
+        {
+            blockers.push(item);
+            continue;
+        }
+        let mut compositions = storage.get_entry_compositions(&entry.id)?;
+        compositions.sort_by(|a, b| a.name.cmp(&b.name));
+        match compositions.into_iter().next() {
+            Some(composition) => grouped.entry(composition.name).or_default().push(item),
+            None => ungrouped.push(item),
+        }
+    }
+
+    let mut groups: Vec<StandupGroup> = grouped
+        .into_iter()
+        .map(|(name, items)| StandupGroup {
+            composition: Some(name),
+            items,
+        })
+        .collect();
+    if !ungrouped.is_empty() {
+        groups.push(StandupGroup {
+            composition: None,
+            items: ungrouped,
+        });
+    }
+
+    let today = Local::now().date_naive();
+    let markdown = render_standup(today, &groups, &blockers);
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    if ui_ctx.mode.is_json() {
+        let items_json = |items: &[StandupItem]| -> Vec<serde_json::Value> {
+            items
+                .iter()
+                .map(|item| {
+                    serde_json::json!({
+                        "id": item.id.to_string(),
+                        "entry_type": item.entry_type,
+                        "summary": item.text,
+                    })
+                })
+                .collect()
+        };
+        let output = serde_json::json!({
+            "date": today.format("%Y-%m-%d").to_string(),
+            "last": args.last,
+            "tag": args.tag,
+            "yesterday": groups.iter().map(|group| serde_json::json!({
+                "composition": group.composition,
+                "entries": items_json(&group.items),
+            })).collect::<Vec<_>>(),
+            "blockers": items_json(&blockers),
+            "markdown": markdown,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if !args.copy {
+        print!("{}", markdown);
+        return Ok(());
+    }
+
+    copy_to_clipboard(&markdown)?;
+    if !ctx.quiet() {
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+                print(
+                    &ui_ctx,
+                    &badge(&ui_ctx, Badge::Ok, "Copied standup report to clipboard"),
+                );
+                let context = format!(
+                    "entries: {}  {sep}  blockers: {}  {sep}  last {}",
+                    entries.len() - blockers.len(),
+                    blockers.len(),
+                    args.last
+                );
+                println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                println!("copied=true");
+                println!("entry_count={}", entries.len());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Render the standup markdown. Empty sections keep a blank bullet to fill in.
+fn render_standup(date: NaiveDate, groups: &[StandupGroup], blockers: &[StandupItem]) -> String {
+    let mut out = format!("# Standup {}\n\n## Yesterday\n\n", date.format("%Y-%m-%d"));
+
+    let only_ungrouped = groups.len() == 1 && groups[0].composition.is_none();
+    if groups.is_empty() {
+        out.push_str("- \n");
+    }
+    for group in groups {
+        if !only_ungrouped {
+            let heading = group.composition.as_deref().unwrap_or(UNGROUPED);
+            out.push_str(&format!("### {}\n\n", heading));
+        }
+        for item in &group.items {
+            out.push_str(&format!("- {}\n", item.text));
+        }
+        if !only_ungrouped {
+            out.push('\n');
+        }
+    }
+    if groups.is_empty() || only_ungrouped {
+        out.push('\n');
+    }
+
+    out.push_str("## Today\n\n- \n\n## Blockers\n\n");
+    if blockers.is_empty() {
+        out.push_str("- \n");
+    }
+    for item in blockers {
+        out.push_str(&format!("- {}\n", item.text));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str) -> StandupItem {
+        StandupItem {
+            id: Uuid::new_v4(),
+            entry_type: "journal".to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_standup_groups_by_composition() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let groups = vec![
+            StandupGroup {
+                composition: Some("billing".to_string()),
+                items: vec![item("Fixed invoice rounding")],
+            },
+            StandupGroup {
+                composition: None,
+                items: vec![item("Reviewed PRs")],
+            },
+        ];
+        let markdown = render_standup(date, &groups, &[item("Waiting on API keys")]);
+        assert_eq!(
+            markdown,
+            "# Standup 2024-03-05\n\n## Yesterday\n\n### billing\n\n- Fixed invoice rounding\n\n\
+### Other\n\n- Reviewed PRs\n\n## Today\n\n- \n\n## Blockers\n\n- Waiting on API keys\n"
+        );
+    }
+
+    #[test]
+    fn test_render_standup_empty_is_skeleton() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let markdown = render_standup(date, &[], &[]);
+        assert_eq!(
+            markdown,
+            "# Standup 2024-03-05\n\n## Yesterday\n\n- \n\n## Today\n\n- \n\n## Blockers\n\n- \n"
+        );
+    }
+}
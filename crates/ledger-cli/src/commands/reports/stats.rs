@@ -0,0 +1,199 @@
+//! Stats: entry counts, and entries never viewed since they were written.
+
+use std::collections::{BTreeMap, HashMap};
+
+use uuid::Uuid;
+
+use ledger_core::storage::{Entry, EntryFilter, StorageEngine};
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::StatsArgs;
+use crate::output::{chain_last_viewed, entries_json, entry_type_name_map, EntrySummaries};
+use crate::ui::{
+    badge, blank_line, header, hint, kv, print, short_id, simple_table, truncate, Badge, Column,
+    OutputMode,
+};
+
+const TABLE_SUMMARY_MAX: usize = 80;
+
+pub fn handle_stats(ctx: &AppContext, args: &StatsArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let name_map = entry_type_name_map(&storage)?;
+    let summaries = EntrySummaries::load(&storage)?;
+    let tracking = ctx.track_access()?;
+
+    let superseded = storage.superseded_entry_ids()?;
+    let viewed = storage.last_accessed()?;
+    // Stream the ledger keeping only ids: revision links, and the type of
+    // each current entry. Oldest first, so the longest-forgotten entries
+    // lead the unread list.
+    let mut parents: HashMap<Uuid, Uuid> = HashMap::new();
+    let mut heads: Vec<(Uuid, Uuid)> = Vec::new();
+    let mut total = 0;
+    for entry in storage.iter_entries(&EntryFilter::new().oldest_first()) {
+        let entry = entry?;
+        total += 1;
+        if let Some(parent) = entry.supersedes {
+            parents.insert(entry.id, parent);
+        }
+        if !superseded.contains(&entry.id) {
+            heads.push((entry.id, entry.entry_type_id));
+        }
+    }
+    let unread: Vec<(Uuid, Uuid)> = heads
+        .iter()
+        .copied()
+        .filter(|(id, _)| chain_last_viewed(*id, &parents, &viewed).is_none())
+        .collect();
+    let type_name = |type_id: &Uuid| {
+        name_map
+            .get(type_id)
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    if !tracking && !ctx.quiet() && ui_ctx.mode.is_pretty() {
+        eprintln!(
+            "{}",
+            badge(
+                &ui_ctx,
+                Badge::Warn,
+                "Views are not tracked; set [privacy] track_access = true to record them"
+            )
+        );
+    }
+
+    if args.unread {
+        let mut shown: Vec<Entry> = Vec::new();
+        for (id, _) in unread.iter().take(args.limit.unwrap_or(usize::MAX)) {
+            shown.extend(storage.get_entry(id)?);
+        }
+        match ui_ctx.mode {
+            OutputMode::Json => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&entries_json(&shown, &name_map))?
+                );
+            }
+            OutputMode::Pretty => {
+                let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+                print(&ui_ctx, &header(&ui_ctx, "stats", ledger_path.as_deref()));
+                blank_line(&ui_ctx);
+                if shown.is_empty() {
+                    print(&ui_ctx, &hint(&ui_ctx, "Every entry has been viewed."));
+                    return Ok(());
+                }
+                let columns = [
+                    Column::new("ID"),
+                    Column::new("Created"),
+                    Column::new("Type"),
+                    Column::new("Summary"),
+                ];
+                let rows: Vec<Vec<String>> = shown
+                    .iter()
+                    .map(|entry| {
+                        vec![
+                            short_id(&entry.id),
+                            entry.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                            type_name(&entry.entry_type_id),
+                            truncate(&summaries.summary(entry), TABLE_SUMMARY_MAX),
+                        ]
+                    })
+                    .collect();
+                print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+                blank_line(&ui_ctx);
+                let first = shown.first().map(|e| short_id(&e.id)).unwrap_or_default();
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        &format!(
+                            "{} of {} entries never viewed. ledger show {}",
+                            unread.len(),
+                            heads.len(),
+                            first
+                        ),
+                    ),
+                );
+            }
+            OutputMode::Plain => {
+                println!("count={}", shown.len());
+                for entry in &shown {
+                    println!(
+                        "{} {} {} {}",
+                        entry.id,
+                        entry.created_at,
+                        type_name(&entry.entry_type_id),
+                        summaries.summary(entry)
+                    );
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let mut by_type: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for (_, type_id) in &heads {
+        by_type.entry(type_name(type_id)).or_default().0 += 1;
+    }
+    for (_, type_id) in &unread {
+        by_type.entry(type_name(type_id)).or_default().1 += 1;
+    }
+    let revisions = total - heads.len();
+
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "entries": heads.len(),
+                "revisions": revisions,
+                "viewed": heads.len() - unread.len(),
+                "unread": unread.len(),
+                "track_access": tracking,
+                "types": by_type
+                    .iter()
+                    .map(|(name, (count, unread))| serde_json::json!({
+                        "type": name,
+                        "entries": count,
+                        "unread": unread,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            print(&ui_ctx, &header(&ui_ctx, "stats", ledger_path.as_deref()));
+            blank_line(&ui_ctx);
+            print(&ui_ctx, &kv(&ui_ctx, "Entries", &heads.len().to_string()));
+            print(&ui_ctx, &kv(&ui_ctx, "Revisions", &revisions.to_string()));
+            print(
+                &ui_ctx,
+                &kv(&ui_ctx, "Never viewed", &unread.len().to_string()),
+            );
+            blank_line(&ui_ctx);
+            let columns = [
+                Column::new("Type"),
+                Column::new("Entries"),
+                Column::new("Never viewed"),
+            ];
+            let rows: Vec<Vec<String>> = by_type
+                .iter()
+                .map(|(name, (count, unread))| {
+                    vec![name.clone(), count.to_string(), unread.to_string()]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+        }
+        OutputMode::Plain => {
+            println!("entries={}", heads.len());
+            println!("revisions={}", revisions);
+            println!("viewed={}", heads.len() - unread.len());
+            println!("unread={}", unread.len());
+            for (name, (count, unread)) in &by_type {
+                println!("type={} entries={} unread={}", name, count, unread);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,360 @@
+//! Today dashboard: entries written today, tasks due, habits scheduled for
+//! today, recurring dates coming up and the journal prompt, on one screen.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{Duration, Local, NaiveDate};
+
+use ledger_core::storage::{DueEntry, Entry, EntryFilter, Habit, StorageEngine};
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::TodayArgs;
+use crate::collectors::local_day_bounds;
+use crate::commands::habits::status::{completions, streak_unit};
+use crate::commands::reports::upcoming::relative_days;
+use crate::helpers::parse_duration;
+use crate::output::{entry_json, entry_type_name_map, EntrySummaries};
+use crate::ui::theme::{styled, styles};
+use crate::ui::{
+    blank_line, header_with_context, hint, kv, print, short_id, simple_table, single_line,
+    truncate, Column, OutputMode, UiContext,
+};
+
+const TABLE_SUMMARY_MAX: usize = 60;
+
+/// An open task with a due date on or before today.
+struct DueTask<'a> {
+    entry: &'a Entry,
+    due: NaiveDate,
+}
+
+/// A habit scheduled for today.
+struct HabitToday<'a> {
+    habit: &'a Habit,
+    done: bool,
+    streak: u32,
+}
+
+pub fn handle_today(ctx: &AppContext, args: &TodayArgs) -> anyhow::Result<()> {
+    let window = parse_duration(&args.within)?;
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let name_map = entry_type_name_map(&storage)?;
+    let summaries = EntrySummaries::load(&storage)?;
+
+    let today = Local::now().date_naive();
+    let (start, end) =
+        local_day_bounds(today).ok_or_else(|| anyhow::anyhow!("Invalid date: {}", today))?;
+    let mut entries =
+        storage.list_entries(&EntryFilter::new().since(start).until(end).latest_only())?;
+    // Oldest first, so the day reads in order
+    entries.reverse();
+
+    let task_entries = match storage.get_entry_type("task")? {
+        Some(entry_type) => {
+            storage.list_entries(&EntryFilter::new().entry_type(entry_type.id).latest_only())?
+        }
+        None => Vec::new(),
+    };
+    let tasks = due_tasks(&task_entries, today);
+
+    let habits = storage.list_habits()?;
+    let done = match storage.get_entry_type("habit")? {
+        Some(entry_type) => completions(&storage, entry_type.id)?,
+        None => HashMap::new(),
+    };
+    let empty = BTreeSet::new();
+    let habits_today: Vec<HabitToday> = habits
+        .iter()
+        .filter(|habit| habit.schedule.is_scheduled(today))
+        .map(|habit| {
+            let dates = done.get(&habit.name).unwrap_or(&empty);
+            HabitToday {
+                habit,
+                done: dates.contains(&today),
+                streak: habit.schedule.streaks(dates, today).0,
+            }
+        })
+        .collect();
+
+    let reminders = storage.due_entries(today, today + Duration::days(window.num_days()))?;
+    let prompt = ctx.prompt_of_the_day(today)?;
+
+    let type_name = |entry: &Entry| {
+        name_map
+            .get(&entry.entry_type_id)
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+    let title = |entry: &Entry| {
+        entry
+            .data
+            .get("title")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| summaries.summary(entry))
+    };
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "date": today,
+                "entries": entries
+                    .iter()
+                    .map(|entry| entry_json(entry, &name_map))
+                    .collect::<Vec<_>>(),
+                "tasks": tasks
+                    .iter()
+                    .map(|task| serde_json::json!({
+                        "due": task.due,
+                        "overdue_days": (today - task.due).num_days(),
+                        "entry": entry_json(task.entry, &name_map),
+                    }))
+                    .collect::<Vec<_>>(),
+                "habits": habits_today
+                    .iter()
+                    .map(|row| serde_json::json!({
+                        "name": row.habit.name,
+                        "schedule": row.habit.schedule,
+                        "done": row.done,
+                        "current_streak": row.streak,
+                        "streak_unit": streak_unit(&row.habit.schedule),
+                    }))
+                    .collect::<Vec<_>>(),
+                "reminders": reminders
+                    .iter()
+                    .map(|item| serde_json::json!({
+                        "due": item.due,
+                        "in_days": (item.due - today).num_days(),
+                        "field": item.field,
+                        "entry": entry_json(&item.entry, &name_map),
+                    }))
+                    .collect::<Vec<_>>(),
+                "prompt": prompt,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Plain => {
+            println!("date={}", today);
+            for entry in &entries {
+                println!(
+                    "entry={} type={} summary={}",
+                    entry.id,
+                    type_name(entry),
+                    single_line(&summaries.summary(entry))
+                );
+            }
+            for task in &tasks {
+                println!(
+                    "task={} due={} title={}",
+                    task.entry.id,
+                    task.due,
+                    single_line(&title(task.entry))
+                );
+            }
+            for row in &habits_today {
+                println!(
+                    "habit={} done={} streak={} unit={}",
+                    row.habit.name,
+                    row.done,
+                    row.streak,
+                    streak_unit(&row.habit.schedule)
+                );
+            }
+            for item in &reminders {
+                println!(
+                    "reminder={} due={} type={} summary={}",
+                    item.entry.id,
+                    item.due,
+                    type_name(&item.entry),
+                    single_line(&summaries.summary(&item.entry))
+                );
+            }
+            if let Some(ref prompt) = prompt {
+                println!("prompt={}", single_line(prompt));
+            }
+        }
+        OutputMode::Pretty => {
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            let context = today.format("%a %Y-%m-%d").to_string();
+            print(
+                &ui_ctx,
+                &header_with_context(&ui_ctx, "today", Some(&context), ledger_path.as_deref()),
+            );
+            if let Some(ref prompt) = prompt {
+                blank_line(&ui_ctx);
+                print(&ui_ctx, &kv(&ui_ctx, "Prompt", prompt));
+            }
+
+            section(&ui_ctx, "Entries", entries.len());
+            if entries.is_empty() {
+                print(
+                    &ui_ctx,
+                    &hint(&ui_ctx, "Nothing written yet today. ledger add journal"),
+                );
+            } else {
+                let rows: Vec<Vec<String>> = entries
+                    .iter()
+                    .map(|entry| {
+                        vec![
+                            entry
+                                .created_at
+                                .with_timezone(&Local)
+                                .format("%H:%M")
+                                .to_string(),
+                            type_name(entry),
+                            truncate(&summaries.summary(entry), TABLE_SUMMARY_MAX),
+                            short_id(&entry.id),
+                        ]
+                    })
+                    .collect();
+                let columns = [
+                    Column::new("Time"),
+                    Column::new("Type"),
+                    Column::new("Summary"),
+                    Column::new("ID"),
+                ];
+                print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            }
+
+            section(&ui_ctx, "Tasks due", tasks.len());
+            if tasks.is_empty() {
+                print(&ui_ctx, &hint(&ui_ctx, "No open tasks due."));
+            } else {
+                let rows: Vec<Vec<String>> = tasks
+                    .iter()
+                    .map(|task| {
+                        let due = match (today - task.due).num_days() {
+                            0 => "today".to_string(),
+                            days => format!("{}d overdue", days),
+                        };
+                        vec![
+                            due,
+                            truncate(&title(task.entry), TABLE_SUMMARY_MAX),
+                            short_id(&task.entry.id),
+                        ]
+                    })
+                    .collect();
+                let columns = [Column::new("Due"), Column::new("Task"), Column::new("ID")];
+                print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            }
+
+            section(&ui_ctx, "Habits", habits_today.len());
+            if habits_today.is_empty() {
+                print(&ui_ctx, &hint(&ui_ctx, "No habits scheduled today."));
+            } else {
+                let rows: Vec<Vec<String>> = habits_today
+                    .iter()
+                    .map(|row| {
+                        let status = if row.done { "done" } else { "pending" };
+                        vec![
+                            row.habit.name.clone(),
+                            status.to_string(),
+                            format!("{} {}", row.streak, streak_unit(&row.habit.schedule)),
+                        ]
+                    })
+                    .collect();
+                let columns = [
+                    Column::new("Habit"),
+                    Column::new("Today"),
+                    Column::new("Streak"),
+                ];
+                print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            }
+
+            section(&ui_ctx, "Reminders", reminders.len());
+            if reminders.is_empty() {
+                let text = format!("Nothing due within {}.", args.within);
+                print(&ui_ctx, &hint(&ui_ctx, &text));
+            } else {
+                let rows: Vec<Vec<String>> = reminders
+                    .iter()
+                    .map(|item: &DueEntry| {
+                        vec![
+                            relative_days((item.due - today).num_days()),
+                            type_name(&item.entry),
+                            truncate(&summaries.summary(&item.entry), TABLE_SUMMARY_MAX),
+                            short_id(&item.entry.id),
+                        ]
+                    })
+                    .collect();
+                let columns = [
+                    Column::new("In"),
+                    Column::new("Type"),
+                    Column::new("Summary"),
+                    Column::new("ID"),
+                ];
+                print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn section(ui_ctx: &UiContext, title: &str, count: usize) {
+    blank_line(ui_ctx);
+    let title = format!("{} ({})", title, count);
+    println!("{}", styled(&title, styles::bold(), ui_ctx.color));
+}
+
+/// Open tasks due today or earlier, most overdue first.
+fn due_tasks(entries: &[Entry], today: NaiveDate) -> Vec<DueTask<'_>> {
+    let mut tasks: Vec<DueTask> = entries
+        .iter()
+        .filter(|entry| {
+            !entry
+                .data
+                .get("done")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+        })
+        .filter_map(|entry| {
+            let due = entry
+                .data
+                .get("due")
+                .and_then(|v| v.as_str())
+                .and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())?;
+            (due <= today).then_some(DueTask { entry, due })
+        })
+        .collect();
+    tasks.sort_by_key(|task| (task.due, task.entry.created_at));
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn task(data: serde_json::Value) -> Entry {
+        Entry {
+            id: Uuid::new_v4(),
+            entry_type_id: Uuid::new_v4(),
+            schema_version: 1,
+            data,
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            seq: 0,
+            device_id: Uuid::new_v4(),
+            supersedes: None,
+        }
+    }
+
+    #[test]
+    fn test_due_tasks_skip_done_and_future() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 8).unwrap();
+        let entries = vec![
+            task(serde_json::json!({"title": "today", "due": "2024-03-08"})),
+            task(serde_json::json!({"title": "late", "due": "2024-03-01"})),
+            task(serde_json::json!({"title": "done", "due": "2024-03-02", "done": true})),
+            task(serde_json::json!({"title": "later", "due": "2024-03-09"})),
+            task(serde_json::json!({"title": "someday"})),
+        ];
+        let titles: Vec<&str> = due_tasks(&entries, today)
+            .iter()
+            .map(|task| task.entry.data["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, vec!["late", "today"]);
+    }
+}
@@ -0,0 +1,203 @@
+//! Upcoming report: recurring date fields that fall due soon.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{Duration, Local, NaiveDate};
+use uuid::Uuid;
+
+use ledger_core::storage::{DueEntry, Recurrence};
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::UpcomingArgs;
+use crate::helpers::{parse_duration, require_entry_type};
+use crate::notify::{reminder_key, NotifiedLog, Notifier};
+use crate::output::{entry_json, entry_type_name_map, EntrySummaries};
+use crate::ui::{
+    blank_line, header_with_context, hint, print, short_id, simple_table, truncate, Column,
+    OutputMode,
+};
+
+const TABLE_SUMMARY_MAX: usize = 60;
+
+pub fn handle_upcoming(ctx: &AppContext, args: &UpcomingArgs) -> anyhow::Result<()> {
+    let window = parse_duration(&args.within)?;
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let name_map = entry_type_name_map(&storage)?;
+    let summaries = EntrySummaries::load(&storage)?;
+
+    let today = Local::now().date_naive();
+    let until = today + Duration::days(window.num_days());
+    let mut due = storage.due_entries(today, until)?;
+    if let Some(ref t) = args.entry_type {
+        let entry_type = require_entry_type(&storage, t)?;
+        due.retain(|item| item.entry.entry_type_id == entry_type.id);
+    }
+    let notified = if args.notify {
+        let command = ctx
+            .config()?
+            .and_then(|config| config.reminders.notify_command.clone());
+        let ledger_path = resolve_ledger_path(ctx.cli())?;
+        Some(notify_due_today(
+            &due,
+            today,
+            &Notifier::new(command.as_deref()),
+            Path::new(&ledger_path),
+            &name_map,
+            &summaries,
+        )?)
+    } else {
+        None
+    };
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    if ui_ctx.mode.is_json() {
+        let output: Vec<serde_json::Value> = due
+            .iter()
+            .map(|item| {
+                serde_json::json!({
+                    "due": item.due,
+                    "in_days": (item.due - today).num_days(),
+                    "field": item.field,
+                    "date": item.date,
+                    "occurrence": item.occurrence,
+                    "entry": entry_json(&item.entry, &name_map),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    let type_name = |item: &DueEntry| {
+        name_map
+            .get(&item.entry.entry_type_id)
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+    match ui_ctx.mode {
+        OutputMode::Pretty => {
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            let context = format!("within {}", args.within);
+            print(
+                &ui_ctx,
+                &header_with_context(&ui_ctx, "upcoming", Some(&context), ledger_path.as_deref()),
+            );
+            blank_line(&ui_ctx);
+            if let Some(sent) = notified.filter(|sent| *sent > 0) {
+                print(
+                    &ui_ctx,
+                    &hint(&ui_ctx, &format!("Sent {} desktop notifications.", sent)),
+                );
+            }
+            if due.is_empty() {
+                print(&ui_ctx, &hint(&ui_ctx, "Nothing due in this window."));
+                return Ok(());
+            }
+            let columns = [
+                Column::new("Due"),
+                Column::new("In"),
+                Column::new("Type"),
+                Column::new("Summary"),
+                Column::new("Occasion"),
+                Column::new("ID"),
+            ];
+            let rows: Vec<Vec<String>> = due
+                .iter()
+                .map(|item| {
+                    vec![
+                        item.due.format("%Y-%m-%d").to_string(),
+                        relative_days((item.due - today).num_days()),
+                        type_name(item),
+                        truncate(&summaries.summary(&item.entry), TABLE_SUMMARY_MAX),
+                        occasion(item),
+                        short_id(&item.entry.id),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+        }
+        OutputMode::Plain | OutputMode::Json => {
+            println!("count={}", due.len());
+            if let Some(sent) = notified {
+                println!("notified={}", sent);
+            }
+            for item in &due {
+                println!(
+                    "{} {} {} {} {}",
+                    item.due.format("%Y-%m-%d"),
+                    item.entry.id,
+                    type_name(item),
+                    item.field,
+                    summaries.summary(&item.entry)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Notify each reminder due today that has not been notified yet today,
+/// skipping fields annotated `"notify": false`. Returns how many were sent.
+fn notify_due_today(
+    due: &[DueEntry],
+    today: NaiveDate,
+    notifier: &Notifier,
+    ledger_path: &Path,
+    name_map: &HashMap<Uuid, String>,
+    summaries: &EntrySummaries,
+) -> anyhow::Result<usize> {
+    let mut log = NotifiedLog::load(ledger_path, today);
+    let mut sent = 0;
+    let mut failure = None;
+    for item in due.iter().filter(|item| item.due == today && item.notify) {
+        let key = reminder_key(&item.entry.id, &item.field);
+        if log.sent.contains(&key) {
+            continue;
+        }
+        let type_name = name_map
+            .get(&item.entry.entry_type_id)
+            .map(String::as_str)
+            .unwrap_or("unknown");
+        let body = format!("{} {} today ({})", type_name, item.field, occasion(item));
+        match notifier.send(&summaries.summary(&item.entry), &body) {
+            Ok(()) => {
+                log.sent.insert(key);
+                sent += 1;
+            }
+            Err(err) => {
+                failure = Some(err);
+                break;
+            }
+        }
+    }
+    if sent > 0 {
+        log.save(ledger_path)?;
+    }
+    match failure {
+        Some(err) => Err(anyhow::anyhow!(
+            "{}\nHint: Set [reminders].notify_command to a command that shows notifications.",
+            err
+        )),
+        None => Ok(sent),
+    }
+}
+
+pub fn relative_days(days: i64) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        n => format!("{}d", n),
+    }
+}
+
+/// "5 years" for yearly fields, otherwise how often the date repeats.
+fn occasion(item: &DueEntry) -> String {
+    match item.recurrence {
+        Recurrence::Yearly if item.occurrence == 1 => "1 year".to_string(),
+        Recurrence::Yearly if item.occurrence > 1 => format!("{} years", item.occurrence),
+        Recurrence::Yearly => "first".to_string(),
+        Recurrence::Monthly => "monthly".to_string(),
+        Recurrence::EveryDays(days) => format!("every {}d", days),
+    }
+}
@@ -0,0 +1,310 @@
+//! Week view: seven day columns of entry counts and titles, tasks due, and
+//! habit completion.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{Datelike, Duration, Local, NaiveDate, Offset};
+
+use ledger_core::storage::{Entry, EntryFilter, HabitSchedule, StorageEngine};
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::WeekArgs;
+use crate::collectors::local_day_bounds;
+use crate::commands::habits::status::completions;
+use crate::output::{entry_type_name_map, EntrySummaries};
+use crate::ui::{
+    blank_line, column_grid, header_with_context, hint, print, single_line, OutputMode,
+};
+
+/// Entry titles listed per day before "+N more".
+const TITLES_PER_DAY: usize = 3;
+
+/// Everything shown for one day of the week.
+struct WeekDay<'a> {
+    date: NaiveDate,
+    by_type: BTreeMap<String, usize>,
+    entries: Vec<&'a Entry>,
+    tasks_due: Vec<&'a Entry>,
+    habits_done: Vec<String>,
+    habits_scheduled: usize,
+}
+
+impl WeekDay<'_> {
+    fn entry_count(&self) -> usize {
+        self.by_type.values().sum()
+    }
+}
+
+pub fn handle_week(ctx: &AppContext, args: &WeekArgs) -> anyhow::Result<()> {
+    let anchor = match args.date {
+        Some(ref date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| anyhow::anyhow!("Invalid date: {} (use YYYY-MM-DD)", date))?,
+        None => Local::now().date_naive(),
+    };
+    let monday = week_start(anchor) + Duration::weeks(args.offset);
+    let sunday = monday + Duration::days(6);
+    let today = Local::now().date_naive();
+
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let name_map = entry_type_name_map(&storage)?;
+    let summaries = EntrySummaries::load(&storage)?;
+
+    let offset = Local::now().offset().fix();
+    let mut by_type: HashMap<NaiveDate, BTreeMap<String, usize>> = HashMap::new();
+    for row in storage.day_counts(monday, sunday, offset)? {
+        let name = name_map
+            .get(&row.entry_type_id)
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string());
+        *by_type
+            .entry(row.date)
+            .or_default()
+            .entry(name)
+            .or_default() += row.count;
+    }
+
+    let (since, _) = local_day_bounds(monday)
+        .ok_or_else(|| anyhow::anyhow!("Invalid week start: {}", monday))?;
+    let (_, until) =
+        local_day_bounds(sunday).ok_or_else(|| anyhow::anyhow!("Invalid week end: {}", sunday))?;
+    let mut entries =
+        storage.list_entries(&EntryFilter::new().since(since).until(until).latest_only())?;
+    entries.reverse();
+    let habit_type = storage.get_entry_type("habit")?;
+    // Habit completions show as habits, not as entries
+    entries.retain(|entry| habit_type.as_ref().map(|t| t.id) != Some(entry.entry_type_id));
+
+    let tasks = match storage.get_entry_type("task")? {
+        Some(entry_type) => {
+            storage.list_entries(&EntryFilter::new().entry_type(entry_type.id).latest_only())?
+        }
+        None => Vec::new(),
+    };
+
+    let habits = storage.list_habits()?;
+    let done = match habit_type {
+        Some(ref entry_type) => completions(&storage, entry_type.id)?,
+        None => HashMap::new(),
+    };
+    let empty = BTreeSet::new();
+
+    let days: Vec<WeekDay> = monday
+        .iter_days()
+        .take(7)
+        .map(|date| {
+            let mut day_types = by_type.remove(&date).unwrap_or_default();
+            if let Some(ref entry_type) = habit_type {
+                day_types.remove(&entry_type.name);
+            }
+            let live: Vec<_> = habits
+                .iter()
+                .filter(|habit| habit.created_at.with_timezone(&Local).date_naive() <= date)
+                .collect();
+            WeekDay {
+                date,
+                by_type: day_types,
+                entries: entries
+                    .iter()
+                    .filter(|entry| entry.created_at.with_timezone(&Local).date_naive() == date)
+                    .collect(),
+                tasks_due: tasks
+                    .iter()
+                    .filter(|task| open_due(task) == Some(date))
+                    .collect(),
+                habits_done: live
+                    .iter()
+                    .filter(|habit| done.get(&habit.name).unwrap_or(&empty).contains(&date))
+                    .map(|habit| habit.name.clone())
+                    .collect(),
+                habits_scheduled: live
+                    .iter()
+                    // Weekly counts have no fixed days to fall behind on
+                    .filter(|habit| {
+                        !matches!(habit.schedule, HabitSchedule::PerWeek(_))
+                            && habit.schedule.is_scheduled(date)
+                    })
+                    .count(),
+            }
+        })
+        .collect();
+
+    let summary = |entry: &Entry| single_line(&summaries.summary(entry));
+    let task_title = |entry: &Entry| {
+        entry
+            .data
+            .get("title")
+            .and_then(|v| v.as_str())
+            .map(single_line)
+            .unwrap_or_else(|| summary(entry))
+    };
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "start": monday,
+                "end": sunday,
+                "offset": args.offset,
+                "days": days
+                    .iter()
+                    .map(|day| serde_json::json!({
+                        "date": day.date,
+                        "count": day.entry_count(),
+                        "by_type": day.by_type,
+                        "entries": day
+                            .entries
+                            .iter()
+                            .map(|entry| serde_json::json!({
+                                "id": entry.id,
+                                "summary": summary(entry),
+                            }))
+                            .collect::<Vec<_>>(),
+                        "tasks_due": day
+                            .tasks_due
+                            .iter()
+                            .map(|task| serde_json::json!({
+                                "id": task.id,
+                                "title": task_title(task),
+                            }))
+                            .collect::<Vec<_>>(),
+                        "habits_done": day.habits_done,
+                        "habits_scheduled": day.habits_scheduled,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Plain => {
+            println!("start={}", monday);
+            println!("end={}", sunday);
+            for day in &days {
+                println!(
+                    "day={} entries={} tasks_due={} habits_done={} habits_scheduled={}",
+                    day.date,
+                    day.entry_count(),
+                    day.tasks_due.len(),
+                    day.habits_done.len(),
+                    day.habits_scheduled
+                );
+            }
+        }
+        OutputMode::Pretty => {
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            let context = format!(
+                "{} to {}",
+                monday.format("%Y-%m-%d"),
+                sunday.format("%Y-%m-%d")
+            );
+            print(
+                &ui_ctx,
+                &header_with_context(&ui_ctx, "week", Some(&context), ledger_path.as_deref()),
+            );
+            blank_line(&ui_ctx);
+            let columns: Vec<(String, Vec<String>)> = days
+                .iter()
+                .map(|day| {
+                    let mut title = day.date.format("%a %d").to_string();
+                    if day.date == today {
+                        title.push_str(" *");
+                    }
+                    (title, day_lines(day, &summary, &task_title))
+                })
+                .collect();
+            print(&ui_ctx, &column_grid(&ui_ctx, &columns));
+            blank_line(&ui_ctx);
+            let previous = args.offset - 1;
+            let next = args.offset + 1;
+            print(
+                &ui_ctx,
+                &hint(
+                    &ui_ctx,
+                    &format!(
+                        "ledger week --offset {}  |  ledger week --offset {}",
+                        previous, next
+                    ),
+                ),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Lines of one day's column: counts, a few titles, tasks, habits.
+fn day_lines(
+    day: &WeekDay<'_>,
+    summary: &dyn Fn(&Entry) -> String,
+    task_title: &dyn Fn(&Entry) -> String,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    match day.entry_count() {
+        0 => lines.push("-".to_string()),
+        1 => lines.push("1 entry".to_string()),
+        n => lines.push(format!("{} entries", n)),
+    }
+    for entry in day.entries.iter().take(TITLES_PER_DAY) {
+        lines.push(summary(entry));
+    }
+    if day.entries.len() > TITLES_PER_DAY {
+        lines.push(format!("+{} more", day.entries.len() - TITLES_PER_DAY));
+    }
+    for task in &day.tasks_due {
+        lines.push(format!("due: {}", task_title(task)));
+    }
+    if day.habits_scheduled > 0 || !day.habits_done.is_empty() {
+        lines.push(format!(
+            "habits {}/{}",
+            day.habits_done.len(),
+            day.habits_scheduled
+        ));
+    }
+    lines
+}
+
+/// Monday of the week containing `date`.
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
+/// Due date of a task that is not done yet.
+fn open_due(task: &Entry) -> Option<NaiveDate> {
+    if task.data.get("done").and_then(|v| v.as_bool()) == Some(true) {
+        return None;
+    }
+    task.data
+        .get("due")
+        .and_then(|v| v.as_str())
+        .and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_week_start_is_monday() {
+        // 2024-03-06 is a Wednesday
+        let wednesday = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        assert_eq!(week_start(wednesday), monday);
+        assert_eq!(week_start(monday), monday);
+        let sunday = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        assert_eq!(week_start(sunday), monday);
+    }
+
+    #[test]
+    fn test_day_lines_empty_day_shows_habits() {
+        let day = WeekDay {
+            date: NaiveDate::from_ymd_opt(2024, 3, 4).unwrap(),
+            by_type: [("journal".to_string(), 0)].into_iter().collect(),
+            entries: Vec::new(),
+            tasks_due: Vec::new(),
+            habits_done: vec!["stretch".to_string()],
+            habits_scheduled: 2,
+        };
+        let summary = |_: &Entry| String::new();
+        assert_eq!(
+            day_lines(&day, &summary, &summary),
+            vec!["-".to_string(), "habits 1/2".to_string()]
+        );
+    }
+}
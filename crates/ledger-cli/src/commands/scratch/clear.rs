@@ -0,0 +1,47 @@
+use crate::app::AppContext;
+use crate::cli::ScratchClearArgs;
+use crate::ui::prompt::confirm_destructive;
+use crate::ui::{badge, print, Badge, OutputMode};
+
+pub fn handle_clear(ctx: &AppContext, args: &ScratchClearArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(false)?;
+    let ui_ctx = ctx.ui_context(false, None);
+
+    if storage.scratchpad()?.is_some() {
+        let yes = args.force || ctx.assume_yes();
+        if !confirm_destructive(&ui_ctx, "Clear the scratchpad?", yes, false)? {
+            if !ctx.quiet() {
+                match ui_ctx.mode {
+                    OutputMode::Pretty => {
+                        print(&ui_ctx, &badge(&ui_ctx, Badge::Info, "Cancelled"));
+                    }
+                    OutputMode::Plain | OutputMode::Json => {
+                        println!("status=cancelled");
+                    }
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    let cleared = storage.clear_scratchpad()?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !ctx.quiet() {
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let message = if cleared {
+                    "Cleared scratchpad"
+                } else {
+                    "Scratchpad was already empty"
+                };
+                print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, message));
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                println!("status=ok");
+                println!("cleared={}", cleared);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use crate::app::AppContext;
+use crate::cli::ScratchEditArgs;
+use crate::helpers::read_entry_body;
+use crate::ui::theme::{styled, styles};
+use crate::ui::{badge, print, Badge, OutputMode};
+
+pub fn handle_edit(ctx: &AppContext, args: &ScratchEditArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let current = storage.scratchpad()?.map(|scratch| scratch.text);
+
+    // Appending starts from an empty draft; editing starts from the text
+    let initial = if args.append {
+        None
+    } else {
+        current.as_deref()
+    };
+    let text = read_entry_body(
+        args.no_input,
+        args.body.clone(),
+        ctx.editor()?.into(),
+        initial,
+    )?;
+    let text = match (args.append, current) {
+        (true, Some(current)) => format!("{}\n{}", current.trim_end(), text),
+        _ => text,
+    };
+    storage.set_scratchpad(&text)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        let lines = text.lines().count();
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let verb = if args.append { "Appended to" } else { "Saved" };
+                print(
+                    &ui_ctx,
+                    &badge(&ui_ctx, Badge::Ok, &format!("{} scratchpad", verb)),
+                );
+                let context = format!("{} lines  ·  ledger scratch promote", lines);
+                println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                println!("status=ok");
+                println!("lines={}", lines);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,9 @@
+pub mod clear;
+pub mod edit;
+pub mod promote;
+pub mod show;
+
+pub use clear::handle_clear;
+pub use edit::handle_edit;
+pub use promote::handle_promote;
+pub use show::handle_show;
@@ -0,0 +1,94 @@
+use ledger_core::storage::{NewEntry, StorageEngine};
+
+use crate::app::AppContext;
+use crate::cli::ScratchPromoteArgs;
+use crate::helpers::{ensure_builtin_entry_type, require_entry_type};
+use crate::output::{stored_entry_json, Receipt};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, blank_line, hint, print, short_id, Badge, OutputMode};
+
+pub fn handle_promote(ctx: &AppContext, args: &ScratchPromoteArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(false)?;
+    let scratch = storage.scratchpad()?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Scratchpad is empty\nHint: Jot something with `ledger scratch edit` first."
+        )
+    })?;
+    let body = scratch.text.trim_end();
+    if body.trim().is_empty() {
+        return Err(anyhow::anyhow!("Scratchpad is empty"));
+    }
+
+    let device_id = storage.metadata()?.device_id;
+    ensure_builtin_entry_type(&mut storage, "journal", device_id)?;
+    let journal = require_entry_type(&storage, "journal")?;
+    let new_entry = NewEntry::new(
+        journal.id,
+        journal.version,
+        serde_json::json!({ "body": body }),
+        device_id,
+    )
+    .with_tags(args.tag.clone());
+    let entry_id = storage.insert_entry(&new_entry)?;
+    if !args.keep {
+        storage.clear_scratchpad()?;
+    }
+    let created = storage
+        .get_entry(&entry_id)?
+        .map(|entry| entry.created_at)
+        .unwrap_or_else(chrono::Utc::now);
+
+    let preview = if ctx.dry_run() {
+        Some(stored_entry_json(&storage, &entry_id)?)
+    } else {
+        None
+    };
+    ctx.save_storage(storage, &passphrase)?;
+    if let Some(preview) = preview {
+        println!("{}", serde_json::to_string_pretty(&preview)?);
+        return Ok(());
+    }
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(args.json, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+                print(
+                    &ui_ctx,
+                    &badge(&ui_ctx, Badge::Ok, "Promoted scratchpad to a journal entry"),
+                );
+                let context = format!(
+                    "ID: {}  {sep}  {}  {sep}  tags: {}",
+                    short_id(&entry_id),
+                    created.format("%Y-%m-%d %H:%M UTC"),
+                    new_entry.tags.len()
+                );
+                println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+                blank_line(&ui_ctx);
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        &format!(
+                            "ledger show {}  {sep}  ledger edit {}",
+                            short_id(&entry_id),
+                            short_id(&entry_id)
+                        ),
+                    ),
+                );
+            }
+            OutputMode::Json => {
+                Receipt::new("add", "entry", entry_id, None)
+                    .created_at(created)
+                    .print()?;
+            }
+            OutputMode::Plain => {
+                println!("status=ok");
+                println!("entry_id={}", entry_id);
+                println!("scratch_cleared={}", !args.keep);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::ScratchShowArgs;
+use crate::ui::{blank_line, header, hint, kv, print, OutputMode};
+
+pub fn handle_show(ctx: &AppContext, args: &ScratchShowArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let scratch = storage.scratchpad()?;
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = match scratch {
+                Some(ref scratch) => serde_json::json!({
+                    "text": scratch.text,
+                    "updated_at": scratch.updated_at,
+                }),
+                None => serde_json::json!({ "text": null, "updated_at": null }),
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            print(&ui_ctx, &header(&ui_ctx, "scratch", ledger_path.as_deref()));
+            blank_line(&ui_ctx);
+            match scratch {
+                Some(scratch) => {
+                    let updated = scratch.updated_at.format("%Y-%m-%d %H:%M UTC").to_string();
+                    print(&ui_ctx, &kv(&ui_ctx, "Updated", &updated));
+                    blank_line(&ui_ctx);
+                    println!("{}", scratch.text);
+                }
+                None => print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        "Scratchpad is empty. Jot something with `ledger scratch edit`.",
+                    ),
+                ),
+            }
+        }
+        OutputMode::Plain => {
+            // Just the text, so it pipes cleanly
+            if let Some(scratch) = scratch {
+                println!("{}", scratch.text);
+            }
+        }
+    }
+    Ok(())
+}
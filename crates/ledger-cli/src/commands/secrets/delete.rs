@@ -0,0 +1,54 @@
+use ledger_core::storage::StorageEngine;
+
+use crate::app::AppContext;
+use crate::cli::SecretDeleteArgs;
+use crate::ui::prompt::confirm_destructive;
+use crate::ui::{badge, print, Badge, OutputMode};
+
+pub fn handle_delete(ctx: &AppContext, args: &SecretDeleteArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(false)?;
+
+    if storage.get_secret(&args.name)?.is_none() {
+        return Err(anyhow::anyhow!("Secret '{}' not found", args.name));
+    }
+
+    let ui_ctx = ctx.ui_context(false, None);
+
+    let prompt = format!("Delete secret '{}'?", args.name);
+    if !confirm_destructive(&ui_ctx, &prompt, args.force || ctx.assume_yes(), false)? {
+        if !ctx.quiet() {
+            match ui_ctx.mode {
+                OutputMode::Pretty => {
+                    print(&ui_ctx, &badge(&ui_ctx, Badge::Info, "Cancelled"));
+                }
+                OutputMode::Plain | OutputMode::Json => {
+                    println!("status=cancelled");
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    storage.delete_secret(&args.name)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !ctx.quiet() {
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Deleted secret '{}'", args.name),
+                    ),
+                );
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                println!("status=ok");
+                println!("deleted={}", args.name);
+            }
+        }
+    }
+    Ok(())
+}
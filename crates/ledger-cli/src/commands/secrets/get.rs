@@ -0,0 +1,15 @@
+use ledger_core::storage::StorageEngine;
+
+use crate::app::AppContext;
+use crate::cli::SecretGetArgs;
+
+pub fn handle_get(ctx: &AppContext, args: &SecretGetArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(args.no_input)?;
+    let value = storage
+        .get_secret(&args.name)?
+        .ok_or_else(|| anyhow::anyhow!("Secret '{}' not found", args.name))?;
+
+    // Raw value only, so it can be piped
+    println!("{}", value);
+    Ok(())
+}
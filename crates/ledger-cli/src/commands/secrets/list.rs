@@ -0,0 +1,39 @@
+use ledger_core::storage::StorageEngine;
+
+use crate::app::AppContext;
+use crate::cli::SecretListArgs;
+use crate::ui::{blank_line, header, hint, print, OutputMode};
+
+pub fn handle_list(ctx: &AppContext, args: &SecretListArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let names = storage.list_secret_names()?;
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            println!("{}", serde_json::to_string_pretty(&names)?);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "secrets", None));
+            blank_line(&ui_ctx);
+            if names.is_empty() {
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        "No secrets stored. Add one with `ledger secret set <name>`.",
+                    ),
+                );
+            }
+            for name in &names {
+                println!("{}", name);
+            }
+        }
+        OutputMode::Plain => {
+            for name in &names {
+                println!("{}", name);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,9 @@
+pub mod delete;
+pub mod get;
+pub mod list;
+pub mod set;
+
+pub use delete::handle_delete;
+pub use get::handle_get;
+pub use list::handle_list;
+pub use set::handle_set;
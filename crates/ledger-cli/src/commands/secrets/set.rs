@@ -0,0 +1,71 @@
+use std::io::{IsTerminal, Read};
+
+use dialoguer::Password;
+
+use ledger_core::storage::StorageEngine;
+
+use crate::app::AppContext;
+use crate::cli::SecretSetArgs;
+use crate::config::SECRET_REF_PREFIX;
+use crate::ui::theme::{styled, styles};
+use crate::ui::{badge, print, Badge, OutputMode};
+
+pub fn handle_set(ctx: &AppContext, args: &SecretSetArgs) -> anyhow::Result<()> {
+    let value = match args.value {
+        Some(ref value) => value.clone(),
+        None => read_secret_value(args.no_input)?,
+    };
+
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let existed = storage.get_secret(&args.name)?.is_some();
+    storage.set_secret(&args.name, &value)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let verb = if existed { "Updated" } else { "Stored" };
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("{} secret '{}'", verb, args.name),
+                    ),
+                );
+                let context = format!(
+                    "Reference it in config as {}{}",
+                    SECRET_REF_PREFIX, args.name
+                );
+                println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                println!("status=ok");
+                println!("name={}", args.name);
+                println!("updated={}", existed);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read a secret from piped stdin, or prompt for it without echo.
+fn read_secret_value(no_input: bool) -> anyhow::Result<String> {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        let mut value = String::new();
+        stdin.lock().read_to_string(&mut value)?;
+        let value = value.trim_end_matches(['\n', '\r']).to_string();
+        if value.is_empty() {
+            return Err(anyhow::anyhow!("No secret value provided on stdin"));
+        }
+        return Ok(value);
+    }
+    if no_input {
+        return Err(anyhow::anyhow!(
+            "Secret value required: pipe it on stdin or pass --value"
+        ));
+    }
+    Ok(Password::new().with_prompt("Secret value").interact()?)
+}
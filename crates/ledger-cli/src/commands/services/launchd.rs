@@ -0,0 +1,271 @@
+//! `ledger launchd install`: write (and load) launchd agents for the cache
+//! daemon, scheduled backups and healthchecks on macOS.
+
+use std::path::{Path, PathBuf};
+
+use crate::app::AppContext;
+use crate::cli::LaunchdInstallArgs;
+use crate::helpers::parse_duration;
+
+use super::{
+    check_replaceable, print_install_report, write_files, InstallReport, Service, ServiceFile,
+    ServicePlan,
+};
+
+/// Comment in every generated plist; files without it are not replaced.
+const MARKER: &str = "Generated by `ledger launchd install`; rerun it to update.";
+
+/// Agent settings beyond the shared service plan.
+struct AgentOptions {
+    /// Environment pinned for every job
+    env: Vec<(String, String)>,
+    log_dir: PathBuf,
+    backup_hour: u32,
+    healthcheck_seconds: i64,
+}
+
+pub fn handle_launchd_install(ctx: &AppContext, args: &LaunchdInstallArgs) -> anyhow::Result<()> {
+    if args.backup_hour > 23 {
+        return Err(anyhow::anyhow!(
+            "Invalid --backup-hour: {} (use 0-23)",
+            args.backup_hour
+        ));
+    }
+    let healthcheck_seconds = parse_duration(&args.healthcheck_every)?.num_seconds();
+    if healthcheck_seconds < 60 {
+        return Err(anyhow::anyhow!(
+            "Invalid --healthcheck-every: {} (use at least 1m)",
+            args.healthcheck_every
+        ));
+    }
+
+    let plan = ServicePlan::resolve(ctx, args.backup_to.as_deref())?;
+    let home = PathBuf::from(
+        std::env::var("HOME")
+            .map_err(|_| anyhow::anyhow!("HOME is not set; cannot resolve LaunchAgents"))?,
+    );
+    let dir = match args.dir {
+        Some(ref dir) => PathBuf::from(dir),
+        None => home.join("Library").join("LaunchAgents"),
+    };
+
+    // launchd does not always hand agents the login session's TMPDIR, and the
+    // cache socket lives there on macOS: pin it so every job and the cache
+    // daemon agree on the socket path.
+    let mut env = vec![(
+        "LEDGER_CONFIG".to_string(),
+        plan.config.display().to_string(),
+    )];
+    if let Some(tmpdir) = std::env::var("TMPDIR")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        env.push(("TMPDIR".to_string(), tmpdir));
+    }
+    let options = AgentOptions {
+        env,
+        log_dir: home.join("Library").join("Logs").join("ledger"),
+        backup_hour: args.backup_hour,
+        healthcheck_seconds,
+    };
+
+    let services = plan.services();
+    let agents: Vec<ServiceFile> = services
+        .iter()
+        .map(|service| ServiceFile {
+            path: dir.join(format!("{}.plist", label(*service))),
+            contents: agent_plist(&plan, *service, &options),
+        })
+        .collect();
+
+    check_replaceable(&agents, MARKER, args.force)?;
+    let dry_run = ctx.dry_run();
+    if !dry_run {
+        write_files(&dir, &agents)?;
+        std::fs::create_dir_all(&options.log_dir).map_err(|e| {
+            anyhow::anyhow!("Failed to create {}: {}", options.log_dir.display(), e)
+        })?;
+    }
+
+    let load_command = agents
+        .iter()
+        .map(|agent| format!("launchctl bootstrap gui/$(id -u) {}", agent.path.display()))
+        .collect::<Vec<_>>()
+        .join(" && ");
+    let loaded = if dry_run || args.no_load {
+        false
+    } else {
+        match load_agents(&services, &agents) {
+            Ok(()) => true,
+            Err(err) => {
+                ctx.warn(&format!(
+                    "Agents written but not loaded: {}\nHint: Run `{}`.",
+                    err, load_command
+                ))?;
+                false
+            }
+        }
+    };
+
+    print_install_report(
+        ctx,
+        args.json,
+        &InstallReport {
+            title: "launchd",
+            dir: &dir,
+            files: &agents,
+            dry_run,
+            enabled: loaded,
+            cache: services.contains(&Service::Cache),
+            next: if loaded {
+                "launchctl list | grep local.ledger".to_string()
+            } else {
+                load_command
+            },
+        },
+    )
+}
+
+/// Agent label, e.g. `local.ledger.backup`.
+fn label(service: Service) -> String {
+    format!("local.{}", service.name().replace('-', "."))
+}
+
+fn agent_plist(plan: &ServicePlan, service: Service, options: &AgentOptions) -> String {
+    let mut body = String::new();
+    push_key(&mut body, "Label", &string(&label(service)));
+    let arguments: String = plan
+        .command(service)
+        .iter()
+        .map(|arg| format!("\n\t\t{}", string(arg)))
+        .collect();
+    push_key(
+        &mut body,
+        "ProgramArguments",
+        &format!("<array>{}\n\t</array>", arguments),
+    );
+    let env: String = options
+        .env
+        .iter()
+        .map(|(key, value)| format!("\n\t\t<key>{}</key>\n\t\t{}", escape(key), string(value)))
+        .collect();
+    push_key(
+        &mut body,
+        "EnvironmentVariables",
+        &format!("<dict>{}\n\t</dict>", env),
+    );
+    let log = log_path(&options.log_dir, service);
+    push_key(&mut body, "StandardOutPath", &string(&log));
+    push_key(&mut body, "StandardErrorPath", &string(&log));
+    match service {
+        Service::Cache => {
+            push_key(&mut body, "RunAtLoad", "<true/>");
+            push_key(
+                &mut body,
+                "KeepAlive",
+                "<dict>\n\t\t<key>SuccessfulExit</key>\n\t\t<false/>\n\t</dict>",
+            );
+        }
+        Service::Backup => push_key(
+            &mut body,
+            "StartCalendarInterval",
+            &format!(
+                "<dict>\n\t\t<key>Hour</key>\n\t\t<integer>{}</integer>\n\t\t\
+                 <key>Minute</key>\n\t\t<integer>0</integer>\n\t</dict>",
+                options.backup_hour
+            ),
+        ),
+        Service::Healthcheck => push_key(
+            &mut body,
+            "StartInterval",
+            &format!("<integer>{}</integer>", options.healthcheck_seconds),
+        ),
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+         \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <!-- {} -->\n<plist version=\"1.0\">\n<dict>\n{}</dict>\n</plist>\n",
+        MARKER, body
+    )
+}
+
+fn push_key(body: &mut String, key: &str, value: &str) {
+    body.push_str(&format!("\t<key>{}</key>\n\t{}\n", key, value));
+}
+
+fn string(value: &str) -> String {
+    format!("<string>{}</string>", escape(value))
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// `~/Library/Logs/ledger/<job>.log`
+fn log_path(log_dir: &Path, service: Service) -> String {
+    let name = service.name().trim_start_matches("ledger-");
+    log_dir.join(format!("{}.log", name)).display().to_string()
+}
+
+/// Replace any loaded copy of each agent with the new plist.
+fn load_agents(services: &[Service], agents: &[ServiceFile]) -> anyhow::Result<()> {
+    let domain = format!("gui/{}", unsafe { libc::geteuid() });
+    for (service, agent) in services.iter().zip(agents) {
+        let _ = std::process::Command::new("launchctl")
+            .args(["bootout", &format!("{}/{}", domain, label(*service))])
+            .output();
+        let output = std::process::Command::new("launchctl")
+            .args(["bootstrap", &domain])
+            .arg(&agent.path)
+            .output()
+            .map_err(|e| anyhow::anyhow!("launchctl not available: {}", e))?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "launchctl bootstrap {} failed: {}",
+                agent.path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_plist_pins_tmpdir_and_schedule() {
+        let plan = ServicePlan {
+            exe: PathBuf::from("/usr/local/bin/ledger"),
+            ledger: PathBuf::from("/Users/me/R&D.ledger"),
+            config: PathBuf::from("/Users/me/.config/ledger/config.toml"),
+            backup_to: PathBuf::from("/Volumes/Backup/ledger.bak"),
+            socket: PathBuf::from("/var/folders/xy/T/ledger-cache.sock"),
+            cache_ttl_seconds: 300,
+        };
+        let options = AgentOptions {
+            env: vec![("TMPDIR".to_string(), "/var/folders/xy/T/".to_string())],
+            log_dir: PathBuf::from("/Users/me/Library/Logs/ledger"),
+            backup_hour: 3,
+            healthcheck_seconds: 3600,
+        };
+        let backup = agent_plist(&plan, Service::Backup, &options);
+        assert!(backup.contains(MARKER));
+        assert!(backup.contains("<string>local.ledger.backup</string>"));
+        assert!(backup.contains("<string>/Users/me/R&amp;D.ledger</string>"));
+        assert!(backup.contains("<key>TMPDIR</key>\n\t\t<string>/var/folders/xy/T/</string>"));
+        assert!(backup.contains("<key>Hour</key>\n\t\t<integer>3</integer>"));
+        assert!(backup.contains("/Users/me/Library/Logs/ledger/backup.log"));
+
+        let cache = agent_plist(&plan, Service::Cache, &options);
+        assert!(cache.contains("<string>/var/folders/xy/T/ledger-cache.sock</string>"));
+        assert!(cache.contains("<key>RunAtLoad</key>\n\t<true/>"));
+        let health = agent_plist(&plan, Service::Healthcheck, &options);
+        assert!(health.contains("<key>StartInterval</key>\n\t<integer>3600</integer>"));
+    }
+}
@@ -0,0 +1,243 @@
+//! Background services for service managers: the passphrase cache daemon,
+//! scheduled backups and healthchecks, written as systemd units or launchd
+//! agents.
+
+pub mod launchd;
+pub mod systemd;
+
+pub use launchd::handle_launchd_install;
+pub use systemd::handle_systemd_install;
+
+use std::path::{Path, PathBuf};
+
+use crate::app::{resolve_config_path, resolve_ledger_path, AppContext};
+use crate::cache::cache_socket_path;
+use crate::config::{read_config, xdg_data_dir};
+use crate::ui::{badge, blank_line, header, hint, print, Badge, OutputMode};
+
+/// A job a generated unit runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    /// Long-running passphrase cache daemon
+    Cache,
+    /// `ledger backup` on a schedule
+    Backup,
+    /// `ledger check --healthcheck` on a schedule
+    Healthcheck,
+}
+
+impl Service {
+    /// Unit or job name, without a file extension.
+    pub fn name(self) -> &'static str {
+        match self {
+            Service::Cache => "ledger-cache",
+            Service::Backup => "ledger-backup",
+            Service::Healthcheck => "ledger-healthcheck",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Service::Cache => "Ledger passphrase cache",
+            Service::Backup => "Ledger backup",
+            Service::Healthcheck => "Ledger healthcheck",
+        }
+    }
+}
+
+/// Paths and settings every generated service needs, resolved once so the
+/// units do not depend on the environment of whoever starts them.
+#[derive(Debug, Clone)]
+pub struct ServicePlan {
+    pub exe: PathBuf,
+    pub ledger: PathBuf,
+    pub config: PathBuf,
+    pub backup_to: PathBuf,
+    pub socket: PathBuf,
+    /// Passphrase cache TTL; the cache service is skipped when it is 0.
+    pub cache_ttl_seconds: u64,
+}
+
+impl ServicePlan {
+    pub fn resolve(ctx: &AppContext, backup_to: Option<&str>) -> anyhow::Result<Self> {
+        let exe = std::env::current_exe()
+            .map_err(|e| anyhow::anyhow!("Failed to locate the ledger binary: {}", e))?;
+        let ledger = PathBuf::from(resolve_ledger_path(ctx.cli())?);
+        let ledger = std::fs::canonicalize(&ledger).unwrap_or(ledger);
+        let config = resolve_config_path()?;
+        let cache_ttl_seconds = read_config(&config)
+            .map(|config| config.security.passphrase_cache_ttl_seconds)
+            .unwrap_or(0);
+        let backup_to = match backup_to {
+            Some(path) => PathBuf::from(path),
+            None => default_backup_path(&xdg_data_dir()?, &ledger),
+        };
+        Ok(Self {
+            exe,
+            ledger,
+            config,
+            backup_to,
+            socket: cache_socket_path()?,
+            cache_ttl_seconds,
+        })
+    }
+
+    /// Services to install: the cache only when passphrase caching is on.
+    pub fn services(&self) -> Vec<Service> {
+        let mut services = Vec::new();
+        if self.cache_ttl_seconds > 0 {
+            services.push(Service::Cache);
+        }
+        services.extend([Service::Backup, Service::Healthcheck]);
+        services
+    }
+
+    /// Command line a service runs, binary first.
+    pub fn command(&self, service: Service) -> Vec<String> {
+        let mut argv = vec![self.exe.display().to_string()];
+        match service {
+            Service::Cache => argv.extend([
+                "internal-cache-daemon".to_string(),
+                "--ttl".to_string(),
+                self.cache_ttl_seconds.to_string(),
+                "--socket".to_string(),
+                self.socket.display().to_string(),
+                "--keep-alive".to_string(),
+            ]),
+            Service::Backup => argv.extend([
+                "--quiet".to_string(),
+                "--ledger".to_string(),
+                self.ledger.display().to_string(),
+                "backup".to_string(),
+                self.backup_to.display().to_string(),
+            ]),
+            Service::Healthcheck => argv.extend([
+                "--quiet".to_string(),
+                "--ledger".to_string(),
+                self.ledger.display().to_string(),
+                "check".to_string(),
+                "--healthcheck".to_string(),
+            ]),
+        }
+        argv
+    }
+}
+
+/// `<data dir>/backups/<ledger file name>.bak`
+fn default_backup_path(data_dir: &Path, ledger: &Path) -> PathBuf {
+    let name = ledger
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "ledger.ledger".to_string());
+    data_dir.join("backups").join(format!("{}.bak", name))
+}
+
+/// A generated unit or job file.
+pub struct ServiceFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Refuse to replace files that do not carry `marker` unless `force` is set.
+pub fn check_replaceable(files: &[ServiceFile], marker: &str, force: bool) -> anyhow::Result<()> {
+    for file in files {
+        if let Ok(existing) = std::fs::read_to_string(&file.path) {
+            if !existing.contains(marker) && !force {
+                return Err(anyhow::anyhow!(
+                    "{} already exists and was not generated by ledger\nHint: Use --force to replace it.",
+                    file.path.display()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn write_files(dir: &Path, files: &[ServiceFile]) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", dir.display(), e))?;
+    for file in files {
+        std::fs::write(&file.path, &file.contents)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", file.path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// What an `install` command wrote and whether the services were started.
+pub struct InstallReport<'a> {
+    pub title: &'a str,
+    pub dir: &'a Path,
+    pub files: &'a [ServiceFile],
+    pub dry_run: bool,
+    pub enabled: bool,
+    /// Whether a cache service was included
+    pub cache: bool,
+    /// Command to run next: a status check, or how to enable by hand
+    pub next: String,
+}
+
+pub fn print_install_report(
+    ctx: &AppContext,
+    json: bool,
+    report: &InstallReport,
+) -> anyhow::Result<()> {
+    let ui_ctx = ctx.ui_context(json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "dir": report.dir,
+                "dry_run": report.dry_run,
+                "enabled": report.enabled,
+                "files": report
+                    .files
+                    .iter()
+                    .map(|file| serde_json::json!({
+                        "path": file.path,
+                        "contents": file.contents,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Plain => {
+            for file in report.files {
+                println!("file={}", file.path.display());
+                if report.dry_run {
+                    println!("{}", file.contents);
+                }
+            }
+            println!("enabled={}", report.enabled);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, report.title, None));
+            blank_line(&ui_ctx);
+            for file in report.files {
+                if report.dry_run {
+                    let text = format!("Would write {}", file.path.display());
+                    print(&ui_ctx, &badge(&ui_ctx, Badge::Info, &text));
+                    println!("{}", file.contents);
+                } else {
+                    let text = format!("Wrote {}", file.path.display());
+                    print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, &text));
+                }
+            }
+            if !report.cache {
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        "No cache service: passphrase caching is off ([security] passphrase_cache_ttl_seconds).",
+                    ),
+                );
+            }
+            if report.enabled {
+                print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, "Services enabled"));
+            }
+            if !report.dry_run {
+                blank_line(&ui_ctx);
+                print(&ui_ctx, &hint(&ui_ctx, &report.next));
+            }
+        }
+    }
+    Ok(())
+}
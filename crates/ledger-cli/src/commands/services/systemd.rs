@@ -0,0 +1,271 @@
+//! `ledger systemd install`: write (and enable) systemd units for the cache
+//! daemon and for backup and healthcheck timers.
+
+use std::path::PathBuf;
+
+use crate::app::AppContext;
+use crate::cli::SystemdInstallArgs;
+use crate::config::xdg_config_dir;
+
+use super::{
+    check_replaceable, print_install_report, write_files, InstallReport, Service, ServiceFile,
+    ServicePlan,
+};
+
+/// First line of every generated unit; files without it are not replaced.
+const MARKER: &str = "# Generated by `ledger systemd install`; rerun it to update.";
+
+/// Directory for system-wide units.
+const SYSTEM_UNIT_DIR: &str = "/etc/systemd/system";
+
+pub fn handle_systemd_install(ctx: &AppContext, args: &SystemdInstallArgs) -> anyhow::Result<()> {
+    let plan = ServicePlan::resolve(ctx, args.backup_to.as_deref())?;
+    let dir = match args.dir {
+        Some(ref dir) => PathBuf::from(dir),
+        None => unit_dir(args.user)?,
+    };
+
+    let system_env = if args.user {
+        Vec::new()
+    } else {
+        ["HOME", "XDG_RUNTIME_DIR"]
+            .into_iter()
+            .filter_map(|key| {
+                std::env::var(key)
+                    .ok()
+                    .filter(|value| !value.trim().is_empty())
+                    .map(|value| (key, value))
+            })
+            .collect()
+    };
+    let run_as = if args.user {
+        None
+    } else {
+        std::env::var("USER").ok().filter(|user| user != "root")
+    };
+
+    let mut units = Vec::new();
+    let mut enable = Vec::new();
+    for service in plan.services() {
+        units.push(ServiceFile {
+            path: dir.join(format!("{}.service", service.name())),
+            contents: service_unit(&plan, service, args.user, run_as.as_deref(), &system_env),
+        });
+        let schedule = match service {
+            Service::Cache => {
+                enable.push(format!("{}.service", service.name()));
+                continue;
+            }
+            Service::Backup => &args.backup_schedule,
+            Service::Healthcheck => &args.healthcheck_schedule,
+        };
+        units.push(ServiceFile {
+            path: dir.join(format!("{}.timer", service.name())),
+            contents: timer_unit(service, schedule),
+        });
+        enable.push(format!("{}.timer", service.name()));
+    }
+
+    check_replaceable(&units, MARKER, args.force)?;
+    let dry_run = ctx.dry_run();
+    if !dry_run {
+        write_files(&dir, &units)?;
+    }
+
+    let systemctl = if args.user {
+        "systemctl --user"
+    } else {
+        "systemctl"
+    };
+    let enable_command = format!(
+        "{} daemon-reload && {} enable --now {}",
+        systemctl,
+        systemctl,
+        enable.join(" ")
+    );
+    let enabled = if dry_run || args.no_enable {
+        false
+    } else {
+        match enable_units(args.user, &enable) {
+            Ok(()) => true,
+            Err(err) => {
+                ctx.warn(&format!(
+                    "Units written but not enabled: {}\nHint: Run `{}`.",
+                    err, enable_command
+                ))?;
+                false
+            }
+        }
+    };
+
+    print_install_report(
+        ctx,
+        args.json,
+        &InstallReport {
+            title: "systemd",
+            dir: &dir,
+            files: &units,
+            dry_run,
+            enabled,
+            cache: plan.services().contains(&Service::Cache),
+            next: if enabled {
+                format!("{} list-timers 'ledger-*'", systemctl)
+            } else {
+                enable_command
+            },
+        },
+    )
+}
+
+/// Default unit directory: `~/.config/systemd/user` or `/etc/systemd/system`.
+fn unit_dir(user: bool) -> anyhow::Result<PathBuf> {
+    if !user {
+        return Ok(PathBuf::from(SYSTEM_UNIT_DIR));
+    }
+    let ledger_config = xdg_config_dir()?;
+    let config_home = ledger_config
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Cannot resolve the config directory"))?;
+    Ok(config_home.join("systemd").join("user"))
+}
+
+fn service_unit(
+    plan: &ServicePlan,
+    service: Service,
+    user: bool,
+    run_as: Option<&str>,
+    env: &[(&str, String)],
+) -> String {
+    let mut unit = format!(
+        "{}\n[Unit]\nDescription={}\n",
+        MARKER,
+        service.description()
+    );
+    if service == Service::Backup {
+        // Skip rather than fail while the ledger is missing (e.g. unmounted)
+        unit.push_str(&format!("ConditionPathExists={}\n", plan.ledger.display()));
+    }
+    unit.push_str("\n[Service]\n");
+    match service {
+        Service::Cache => unit.push_str("Type=simple\nRestart=on-failure\n"),
+        Service::Backup | Service::Healthcheck => unit.push_str("Type=oneshot\n"),
+    }
+    if let Some(run_as) = run_as {
+        unit.push_str(&format!("User={}\n", run_as));
+    }
+    let config = format!("LEDGER_CONFIG={}", plan.config.display());
+    unit.push_str(&format!("Environment={}\n", quote(&config)));
+    for (key, value) in env {
+        unit.push_str(&format!(
+            "Environment={}\n",
+            quote(&format!("{}={}", key, value))
+        ));
+    }
+    let command: Vec<String> = plan.command(service).iter().map(|arg| quote(arg)).collect();
+    unit.push_str(&format!("ExecStart={}\n", command.join(" ")));
+    if service == Service::Cache {
+        let target = if user {
+            "default.target"
+        } else {
+            "multi-user.target"
+        };
+        unit.push_str(&format!("\n[Install]\nWantedBy={}\n", target));
+    }
+    unit
+}
+
+fn timer_unit(service: Service, on_calendar: &str) -> String {
+    format!(
+        "{}\n[Unit]\nDescription={} ({})\n\n[Timer]\nOnCalendar={}\nPersistent=true\n\n\
+         [Install]\nWantedBy=timers.target\n",
+        MARKER,
+        service.description(),
+        on_calendar,
+        on_calendar
+    )
+}
+
+/// Quote a word for a unit file: `%` and `$` are escaped, and words with
+/// spaces, quotes or backslashes are wrapped in double quotes.
+fn quote(word: &str) -> String {
+    let escaped = word.replace('%', "%%").replace('$', "$$");
+    if escaped.is_empty()
+        || escaped
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '\'' || c == '\\')
+    {
+        format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        escaped
+    }
+}
+
+fn enable_units(user: bool, units: &[String]) -> anyhow::Result<()> {
+    let systemctl = |args: &[&str]| -> anyhow::Result<()> {
+        let mut command = std::process::Command::new("systemctl");
+        if user {
+            command.arg("--user");
+        }
+        let output = command
+            .args(args)
+            .output()
+            .map_err(|e| anyhow::anyhow!("systemctl not available: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "systemctl {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    };
+    systemctl(&["daemon-reload"])?;
+    let mut enable = vec!["enable", "--now"];
+    enable.extend(units.iter().map(String::as_str));
+    systemctl(&enable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(ttl: u64) -> ServicePlan {
+        ServicePlan {
+            exe: PathBuf::from("/usr/bin/ledger"),
+            ledger: PathBuf::from("/home/me/My Ledger/main.ledger"),
+            config: PathBuf::from("/home/me/.config/ledger/config.toml"),
+            backup_to: PathBuf::from("/backups/main.ledger.bak"),
+            socket: PathBuf::from("/run/user/1000/ledger/cache.sock"),
+            cache_ttl_seconds: ttl,
+        }
+    }
+
+    #[test]
+    fn test_service_unit_quotes_paths() {
+        let unit = service_unit(&plan(0), Service::Backup, true, None, &[]);
+        assert!(unit.starts_with(MARKER));
+        assert!(unit.contains("Type=oneshot\n"));
+        assert!(unit.contains(
+            "ExecStart=/usr/bin/ledger --quiet --ledger \"/home/me/My Ledger/main.ledger\" \
+             backup /backups/main.ledger.bak\n"
+        ));
+        assert!(unit.contains("Environment=LEDGER_CONFIG=/home/me/.config/ledger/config.toml\n"));
+        assert!(!unit.contains("[Install]"));
+        assert_eq!(quote("100%"), "100%%");
+        assert_eq!(quote("a \"b\""), "\"a \\\"b\\\"\"");
+    }
+
+    #[test]
+    fn test_cache_unit_only_with_ttl() {
+        assert_eq!(
+            plan(0).services(),
+            vec![Service::Backup, Service::Healthcheck]
+        );
+        let unit = service_unit(&plan(300), Service::Cache, false, Some("me"), &[]);
+        assert!(unit.contains("User=me\n"));
+        assert!(unit.contains("--ttl 300 --socket /run/user/1000/ledger/cache.sock --keep-alive"));
+        assert!(unit.contains("WantedBy=multi-user.target"));
+        assert!(timer_unit(Service::Healthcheck, "hourly").contains("OnCalendar=hourly\n"));
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+
+use anyhow::Context;
+use chrono::Utc;
+use ledger_core::storage::encryption::encrypt_to_recipients;
+use ledger_core::storage::{AgeSqliteStorage, EntryFilter, StorageEngine};
+
+use crate::app::AppContext;
+use crate::cli::ShareArgs;
+use crate::helpers::{parse_datetime, require_entry_type};
+use crate::output::{entries_json, entry_type_name_map};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, print, Badge, OutputMode};
+
+use super::SHARE_FORMAT;
+
+pub fn handle_share(ctx: &AppContext, args: &ShareArgs) -> anyhow::Result<()> {
+    let out = args
+        .out
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Missing --out <PATH> for the share bundle"))?;
+    if args.recipients.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Missing --to-age-recipient: a share bundle must be encrypted to someone"
+        ));
+    }
+    if args.filters.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Missing --where: choose what to share (e.g. --where composition=trip-2024)"
+        ));
+    }
+
+    let (storage, _passphrase) = ctx.open_storage(args.no_input)?;
+    let (filter, composition) = parse_filters(&storage, &args.filters)?;
+
+    // Share current revisions only; earlier drafts stay private
+    let superseded = storage.superseded_entry_ids()?;
+    let mut entries = storage.list_entries(&filter)?;
+    entries.retain(|entry| !superseded.contains(&entry.id));
+    for entry in &mut entries {
+        entry.supersedes = None;
+    }
+    let shared_ids: HashSet<_> = entries.iter().map(|entry| entry.id).collect();
+    let shared_types: HashSet<_> = entries.iter().map(|entry| entry.entry_type_id).collect();
+
+    let mut bundle = storage.export_structure()?;
+    bundle.templates.clear();
+    bundle
+        .entry_types
+        .retain(|entry_type| shared_types.contains(&entry_type.id));
+    bundle
+        .compositions
+        .retain(|c| Some(c.id) == composition.as_ref().map(|c| c.id));
+    bundle.entry_compositions.retain(|membership| {
+        membership.removed_at.is_none()
+            && shared_ids.contains(&membership.entry_id)
+            && Some(membership.composition_id) == composition.as_ref().map(|c| c.id)
+    });
+    bundle
+        .composition_overviews
+        .retain(|overview| Some(overview.composition_id) == composition.as_ref().map(|c| c.id));
+
+    let name_map = entry_type_name_map(&storage)?;
+    let mut document = serde_json::to_value(&bundle)?;
+    document["format"] = serde_json::json!(SHARE_FORMAT);
+    document["version"] = serde_json::json!(1);
+    document["shared_at"] = serde_json::json!(Utc::now());
+    document["where"] = serde_json::json!(args.filters);
+    document["entries"] = serde_json::Value::Array(entries_json(&entries, &name_map));
+
+    let plaintext = serde_json::to_vec(&document)?;
+    let encrypted = encrypt_to_recipients(&plaintext, &args.recipients)?;
+    std::fs::write(out, encrypted).with_context(|| format!("Failed to write {}", out))?;
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+                print(
+                    &ui_ctx,
+                    &badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Shared {} entries", entries.len()),
+                    ),
+                );
+                let context = format!(
+                    "Recipients: {}  {sep}  Bundle: {}",
+                    args.recipients.len(),
+                    out
+                );
+                println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                println!("entries={}", entries.len());
+                println!("recipients={}", args.recipients.len());
+                println!("out={}", out);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Turn `--where key=value` clauses into an entry filter.
+///
+/// Returns the selected composition too, since its name and memberships
+/// travel with the bundle.
+fn parse_filters(
+    storage: &AgeSqliteStorage,
+    clauses: &[String],
+) -> anyhow::Result<(EntryFilter, Option<ledger_core::storage::Composition>)> {
+    let mut filter = EntryFilter::new();
+    let mut composition = None;
+    let mut seen = HashSet::new();
+    for clause in clauses {
+        let (key, value) = clause
+            .split_once('=')
+            .map(|(k, v)| (k.trim(), v.trim()))
+            .filter(|(_, v)| !v.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Invalid --where clause: {} (use KEY=VALUE)", clause))?;
+        if !seen.insert(key.to_string()) {
+            return Err(anyhow::anyhow!("--where {} can only be given once", key));
+        }
+        match key {
+            "composition" => {
+                let found = storage
+                    .get_composition(value)?
+                    .ok_or_else(|| anyhow::anyhow!("Composition not found: {}", value))?;
+                filter = filter.composition(found.id);
+                composition = Some(found);
+            }
+            "tag" => filter = filter.tag(value),
+            "type" => filter = filter.entry_type(require_entry_type(storage, value)?.id),
+            "since" => filter = filter.since(parse_datetime(value)?),
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Unknown --where key: {} (use composition, tag, type or since)",
+                    other
+                ));
+            }
+        }
+    }
+    Ok((filter, composition))
+}
@@ -0,0 +1,8 @@
+pub mod create;
+pub mod open;
+
+pub use create::handle_share;
+pub use open::handle_open;
+
+/// Marker identifying a decrypted share bundle.
+pub const SHARE_FORMAT: &str = "ledger-share";
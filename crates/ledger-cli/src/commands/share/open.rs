@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use ledger_core::storage::encryption::decrypt_with_identities;
+use ledger_core::storage::ExportBundle;
+
+use crate::app::AppContext;
+use crate::cli::ShareOpenArgs;
+use crate::output::{entries_json, EntrySummaries};
+use crate::ui::theme::symbols;
+use crate::ui::{
+    blank_line, header_with_context, hint, print, short_id, simple_table, truncate, Column,
+    OutputMode,
+};
+
+use super::SHARE_FORMAT;
+
+const TABLE_SUMMARY_MAX: usize = 80;
+
+/// Show a share bundle without importing it anywhere.
+pub fn handle_open(ctx: &AppContext, args: &ShareOpenArgs) -> anyhow::Result<()> {
+    let encrypted =
+        std::fs::read(&args.file).with_context(|| format!("Failed to read {}", args.file))?;
+    let identity = std::fs::read_to_string(&args.identity)
+        .with_context(|| format!("Failed to read identity file {}", args.identity))?;
+    let plaintext = decrypt_with_identities(&encrypted, &identity)?;
+
+    let document: serde_json::Value =
+        serde_json::from_slice(&plaintext).context("Share bundle is not valid JSON")?;
+    if document.get("format").and_then(|v| v.as_str()) != Some(SHARE_FORMAT) {
+        return Err(anyhow::anyhow!("Not a ledger share bundle: {}", args.file));
+    }
+    let bundle: ExportBundle =
+        serde_json::from_value(document.clone()).context("Share bundle is malformed")?;
+    let name_map: HashMap<_, _> = bundle
+        .entry_types
+        .iter()
+        .map(|entry_type| (entry_type.id, entry_type.name.clone()))
+        .collect();
+    let summaries = EntrySummaries::from_records(&bundle.entry_types);
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    if ui_ctx.mode == OutputMode::Json {
+        let output = serde_json::json!({
+            "shared_at": document.get("shared_at"),
+            "where": document.get("where"),
+            "compositions": bundle.compositions,
+            "entries": entries_json(&bundle.entries, &name_map),
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    let type_name = |id| {
+        name_map
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+    match ui_ctx.mode {
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            let shared_at = document
+                .get("shared_at")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            let context = format!("shared {}  {sep}  read-only", shared_at);
+            print(
+                &ui_ctx,
+                &header_with_context(&ui_ctx, "share open", Some(&context), Some(&args.file)),
+            );
+            blank_line(&ui_ctx);
+            let columns = [
+                Column::new("ID"),
+                Column::new("Created"),
+                Column::new("Type"),
+                Column::new("Summary"),
+                Column::new("Tags"),
+            ];
+            let rows: Vec<Vec<String>> = bundle
+                .entries
+                .iter()
+                .map(|entry| {
+                    vec![
+                        short_id(&entry.id),
+                        entry.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                        type_name(&entry.entry_type_id),
+                        truncate(&summaries.summary(entry), TABLE_SUMMARY_MAX),
+                        if entry.tags.is_empty() {
+                            "-".to_string()
+                        } else {
+                            entry.tags.join(", ")
+                        },
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+            blank_line(&ui_ctx);
+            print(
+                &ui_ctx,
+                &hint(
+                    &ui_ctx,
+                    &format!(
+                        "{} entries. Use --json to read full entries.",
+                        bundle.entries.len()
+                    ),
+                ),
+            );
+        }
+        OutputMode::Plain | OutputMode::Json => {
+            println!("count={}", bundle.entries.len());
+            for entry in &bundle.entries {
+                let tags = if entry.tags.is_empty() {
+                    "-".to_string()
+                } else {
+                    entry.tags.join(",")
+                };
+                println!(
+                    "{} {} {} {} {}",
+                    entry.id,
+                    entry.created_at,
+                    type_name(&entry.entry_type_id),
+                    tags,
+                    summaries.summary(entry)
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,205 @@
+//! `ledger sync export`: write a change bundle for another ledger.
+//!
+//! A change bundle is the document `ledger export --include` writes: entries
+//! created since a point in time, plus the types, templates and compositions
+//! they need, all with their ids. `ledger import` on the receiving machine
+//! applies it and skips whatever it already has. A peer's
+//! `[sync.peers.<name>]` rules decide which entries go in; structure only
+//! travels with the entries that use it, so nothing about excluded entries
+//! reaches the peer, nor do memberships since removed or in compositions the
+//! rules leave out. Exporting for a peer records how far into the ledger's
+//! insertion order it got, and unless `--since` says otherwise the next
+//! export for it holds only entries inserted after that, however old their
+//! dates: backfilled, `add --date` and imported entries included.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use anyhow::Context;
+use ledger_core::storage::{EntryFilter, StorageEngine, SyncDirection};
+use uuid::Uuid;
+
+use crate::app::AppContext;
+use crate::cli::SyncExportArgs;
+use crate::config::SyncPeerRules;
+use crate::helpers::parse_datetime;
+use crate::output::{entries_json, entry_type_name_map};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, Badge, OutputMode};
+
+pub fn handle_export(ctx: &AppContext, args: &SyncExportArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let peer = match args.peer {
+        Some(ref name) => Some(storage.get_sync_peer(name)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Sync peer not found: {}\nHint: Add it with `ledger sync peers add {} --fingerprint <fingerprint>`.",
+                name,
+                name
+            )
+        })?),
+        None => None,
+    };
+    // A peer without `[sync.peers.<name>]` rules receives every entry
+    let rules = match peer {
+        Some(ref peer) => ctx
+            .config()?
+            .and_then(|config| config.sync.peers.get(&peer.name))
+            .cloned()
+            .unwrap_or_default(),
+        None => SyncPeerRules::default(),
+    };
+    let exported_at = storage.now();
+
+    let mut filter = EntryFilter::new();
+    let since = args.since.as_deref().map(parse_datetime).transpose()?;
+    if let Some(since) = since {
+        filter = filter.since(since);
+    }
+    // Without --since, start after the last entry the peer was sent
+    let after_seq = match since {
+        Some(_) => None,
+        None => peer.as_ref().and_then(|peer| peer.last_sent_seq),
+    };
+    let name_map = entry_type_name_map(&storage)?;
+    let mut bundle = storage.export_structure()?;
+
+    let composition_names: HashMap<Uuid, &str> = bundle
+        .compositions
+        .iter()
+        .map(|composition| (composition.id, composition.name.as_str()))
+        .collect();
+    let mut current_compositions: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for membership in &bundle.entry_compositions {
+        if membership.removed_at.is_none() {
+            if let Some(name) = composition_names.get(&membership.composition_id) {
+                current_compositions
+                    .entry(membership.entry_id)
+                    .or_default()
+                    .push(name.to_string());
+            }
+        }
+    }
+
+    let mut entries = storage.list_entries(&filter)?;
+    entries.retain(|entry| {
+        if after_seq.is_some_and(|seq| entry.seq <= seq) {
+            return false;
+        }
+        let type_name = name_map
+            .get(&entry.entry_type_id)
+            .map(String::as_str)
+            .unwrap_or("unknown");
+        let compositions = current_compositions
+            .get(&entry.id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        rules.admits(type_name, &entry.tags, compositions)
+    });
+
+    // Keep only the structure the sent entries use
+    let sent: HashSet<Uuid> = entries.iter().map(|entry| entry.id).collect();
+    let sent_types: HashSet<Uuid> = entries.iter().map(|entry| entry.entry_type_id).collect();
+    bundle
+        .entry_types
+        .retain(|record| sent_types.contains(&record.id));
+    bundle
+        .templates
+        .retain(|record| sent_types.contains(&record.entry_type_id));
+    // Only current memberships of sent entries, in compositions the peer may
+    // see; the compositions and overviews sent follow from those
+    let admitted: HashSet<Uuid> = bundle
+        .compositions
+        .iter()
+        .filter(|composition| rules.admits_composition(&composition.name))
+        .map(|composition| composition.id)
+        .collect();
+    bundle.entry_compositions.retain(|membership| {
+        membership.removed_at.is_none()
+            && sent.contains(&membership.entry_id)
+            && admitted.contains(&membership.composition_id)
+    });
+    let sent_compositions: HashSet<Uuid> = bundle
+        .entry_compositions
+        .iter()
+        .map(|membership| membership.composition_id)
+        .collect();
+    bundle
+        .compositions
+        .retain(|composition| sent_compositions.contains(&composition.id));
+    bundle
+        .composition_overviews
+        .retain(|overview| sent_compositions.contains(&overview.composition_id));
+
+    let mut output = serde_json::to_value(&bundle)?;
+    output["entries"] = serde_json::Value::Array(entries_json(&entries, &name_map));
+    // `ledger import` ignores this; `ledger sync import` checks the sender
+    output["sync"] = serde_json::json!({
+        "from": storage.device_fingerprint(),
+        "exported_at": exported_at.to_rfc3339(),
+    });
+    let document = serde_json::to_string_pretty(&output)?;
+    match args.out {
+        Some(ref path) => std::fs::write(path, format!("{}\n", document))
+            .with_context(|| format!("Failed to write {}", path))?,
+        None => writeln!(std::io::stdout().lock(), "{}", document)?,
+    }
+
+    if let Some(ref peer) = peer {
+        storage.mark_peer_synced(&peer.name, SyncDirection::Sent, exported_at)?;
+        ctx.save_storage(storage, &passphrase)?;
+    }
+
+    // Summary goes to stderr so it never mixes with a bundle on stdout
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(false, None);
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+                let target = match args.peer {
+                    Some(ref peer) => format!(" for {}", peer),
+                    None => String::new(),
+                };
+                eprintln!(
+                    "{}",
+                    badge(
+                        &ui_ctx,
+                        Badge::Ok,
+                        &format!("Exported {} entries{}", entries.len(), target)
+                    )
+                );
+                let mut context = format!(
+                    "Types: {}  {sep}  Templates: {}  {sep}  Compositions: {}",
+                    bundle.entry_types.len(),
+                    bundle.templates.len(),
+                    bundle.compositions.len()
+                );
+                if let Some(since) = since {
+                    context.push_str(&format!(
+                        "  {sep}  Since: {}",
+                        since.format("%Y-%m-%d %H:%M")
+                    ));
+                }
+                if after_seq.is_some() {
+                    context.push_str(&format!("  {sep}  Since: last export"));
+                }
+                eprintln!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            }
+            OutputMode::Plain | OutputMode::Json => {
+                eprintln!("sync_count={}", entries.len());
+                if let Some(since) = since {
+                    eprintln!("since={}", since.to_rfc3339());
+                }
+                if let Some(seq) = after_seq {
+                    eprintln!("after_seq={}", seq);
+                }
+                if let Some(ref peer) = args.peer {
+                    eprintln!("peer={}", peer);
+                }
+                if let Some(ref path) = args.out {
+                    eprintln!("out={}", path);
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+//! `ledger sync import`: apply a change bundle from a peer.
+//!
+//! The bundle is applied exactly as `ledger import` would, after checking that
+//! the fingerprint it was exported under is the peer's.
+
+use std::io::Read;
+
+use anyhow::Context;
+use ledger_core::storage::SyncDirection;
+
+use crate::app::AppContext;
+use crate::cli::SyncImportArgs;
+use crate::commands::entries::import::{import_export, parse_export};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, print, Badge, OutputMode};
+
+/// The fingerprint a change bundle was exported under, if it has one.
+fn bundle_sender(raw: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(raw).ok()?;
+    value.get("sync")?.get("from")?.as_str().map(str::to_string)
+}
+
+pub fn handle_import(ctx: &AppContext, args: &SyncImportArgs) -> anyhow::Result<()> {
+    let raw = if args.file == "-" {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        std::fs::read_to_string(&args.file)
+            .with_context(|| format!("Failed to read change bundle {}", args.file))?
+    };
+    let sender = bundle_sender(&raw).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Not a change bundle: {}\nHint: Write one with `ledger sync export --peer <name>`, or use `ledger import` for plain exports.",
+            args.file
+        )
+    })?;
+    let export = parse_export(&raw)?;
+
+    let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+    let peer = storage.get_sync_peer(&args.peer)?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Sync peer not found: {}\nHint: Add it with `ledger sync peers add {} --fingerprint {}`.",
+            args.peer,
+            args.peer,
+            sender
+        )
+    })?;
+    if peer.fingerprint != sender {
+        return Err(anyhow::anyhow!(
+            "Change bundle is from {}, not peer '{}' ({})",
+            sender,
+            peer.name,
+            peer.fingerprint
+        ));
+    }
+    let summary = import_export(&mut storage, export)?;
+    let received_at = storage.now();
+    storage.mark_peer_synced(&peer.name, SyncDirection::Received, received_at)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if ctx.quiet() && !args.json {
+        return Ok(());
+    }
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let mut output = serde_json::to_value(&summary)?;
+            output["peer"] = serde_json::Value::String(peer.name);
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            print(
+                &ui_ctx,
+                &badge(
+                    &ui_ctx,
+                    Badge::Ok,
+                    &format!("Imported {} entries from {}", summary.entries, peer.name),
+                ),
+            );
+            let context = format!(
+                "Types: {}  {sep}  Templates: {}  {sep}  Compositions: {}  {sep}  Skipped: {}",
+                summary.entry_types, summary.templates, summary.compositions, summary.skipped
+            );
+            println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+        }
+        OutputMode::Plain => {
+            println!("peer={}", peer.name);
+            println!("entries={}", summary.entries);
+            println!("skipped={}", summary.skipped);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,7 @@
+pub mod export;
+pub mod import;
+pub mod peers;
+
+pub use export::handle_export;
+pub use import::handle_import;
+pub use peers::{handle_peers_add, handle_peers_list, handle_peers_remove};
@@ -0,0 +1,139 @@
+//! `ledger sync peers`: the ledgers this one exchanges change bundles with.
+
+use chrono::{DateTime, Utc};
+
+use crate::app::AppContext;
+use crate::cli::{SyncPeerAddArgs, SyncPeerListArgs, SyncPeerRemoveArgs};
+use crate::ui::{
+    badge, blank_line, header, hint, kv, print, simple_table, Badge, Column, OutputMode,
+};
+
+fn sync_time(at: Option<DateTime<Utc>>) -> String {
+    at.map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "never".to_string())
+}
+
+pub fn handle_peers_list(ctx: &AppContext, args: &SyncPeerListArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let fingerprint = storage.device_fingerprint();
+    let peers = storage.list_sync_peers()?;
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "fingerprint": fingerprint,
+                "peers": peers,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "sync peers", None));
+            blank_line(&ui_ctx);
+            print(&ui_ctx, &kv(&ui_ctx, "This ledger", &fingerprint));
+            blank_line(&ui_ctx);
+            if peers.is_empty() {
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        "No peers. Add one with `ledger sync peers add <name> --fingerprint <fingerprint>`.",
+                    ),
+                );
+                return Ok(());
+            }
+            let columns = [
+                Column::new("Peer"),
+                Column::new("Fingerprint"),
+                Column::new("Last sent"),
+                Column::new("Last received"),
+            ];
+            let rows: Vec<Vec<String>> = peers
+                .iter()
+                .map(|peer| {
+                    vec![
+                        peer.name.clone(),
+                        peer.fingerprint.clone(),
+                        sync_time(peer.last_sent_at),
+                        sync_time(peer.last_received_at),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+        }
+        OutputMode::Plain => {
+            println!("fingerprint={}", fingerprint);
+            println!("count={}", peers.len());
+            for peer in &peers {
+                println!(
+                    "peer={} fingerprint={} last_sent={} last_received={}",
+                    peer.name,
+                    peer.fingerprint,
+                    peer.last_sent_at
+                        .map(|at| at.to_rfc3339())
+                        .unwrap_or_else(|| "never".to_string()),
+                    peer.last_received_at
+                        .map(|at| at.to_rfc3339())
+                        .unwrap_or_else(|| "never".to_string())
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_peers_add(ctx: &AppContext, args: &SyncPeerAddArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(false)?;
+    let peer = storage.add_sync_peer(&args.name, &args.fingerprint)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if ctx.quiet() && !args.json {
+        return Ok(());
+    }
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            println!("{}", serde_json::to_string_pretty(&peer)?);
+        }
+        OutputMode::Pretty => {
+            print(
+                &ui_ctx,
+                &badge(
+                    &ui_ctx,
+                    Badge::Ok,
+                    &format!("Added peer '{}' ({})", peer.name, peer.fingerprint),
+                ),
+            );
+        }
+        OutputMode::Plain => {
+            println!("status=ok");
+            println!("peer={}", peer.name);
+            println!("fingerprint={}", peer.fingerprint);
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_peers_remove(ctx: &AppContext, args: &SyncPeerRemoveArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(false)?;
+    storage.remove_sync_peer(&args.name)?;
+    ctx.save_storage(storage, &passphrase)?;
+
+    if ctx.quiet() {
+        return Ok(());
+    }
+    let ui_ctx = ctx.ui_context(false, None);
+    match ui_ctx.mode {
+        OutputMode::Pretty => {
+            print(
+                &ui_ctx,
+                &badge(&ui_ctx, Badge::Ok, &format!("Removed peer '{}'", args.name)),
+            );
+        }
+        OutputMode::Plain | OutputMode::Json => {
+            println!("status=ok");
+            println!("removed={}", args.name);
+        }
+    }
+    Ok(())
+}
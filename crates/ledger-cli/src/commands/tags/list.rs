@@ -0,0 +1,80 @@
+//! Tag listing, flat or as a hierarchy with rolled-up counts.
+
+use ledger_core::storage::TagCount;
+
+use crate::app::{resolve_ledger_path, AppContext};
+use crate::cli::TagListArgs;
+use crate::ui::{blank_line, header_with_context, hint, print, simple_table, Column, OutputMode};
+
+pub fn handle_list(ctx: &AppContext, args: &TagListArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let tags = storage.tag_counts()?;
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            println!("{}", serde_json::to_string_pretty(&tags)?);
+        }
+        OutputMode::Pretty => {
+            let ledger_path = resolve_ledger_path(ctx.cli()).ok();
+            let context = args.tree.then_some("tree");
+            print(
+                &ui_ctx,
+                &header_with_context(&ui_ctx, "tags", context, ledger_path.as_deref()),
+            );
+            blank_line(&ui_ctx);
+            if tags.is_empty() {
+                print(
+                    &ui_ctx,
+                    &hint(&ui_ctx, "No tags yet. Tag entries with `ledger add --tag`."),
+                );
+                return Ok(());
+            }
+            let columns = [
+                Column::new("Tag"),
+                Column::new("Entries"),
+                Column::new("With nested"),
+            ];
+            let rows: Vec<Vec<String>> = tags
+                .iter()
+                .map(|tag| {
+                    let label = if args.tree {
+                        tree_label(tag)
+                    } else {
+                        tag.tag.clone()
+                    };
+                    vec![label, tag.count.to_string(), tag.total.to_string()]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+        }
+        OutputMode::Plain => {
+            println!("count={}", tags.len());
+            for tag in &tags {
+                if args.tree {
+                    println!(
+                        "tag={} depth={} count={} total={}",
+                        tag.tag,
+                        depth(tag),
+                        tag.count,
+                        tag.total
+                    );
+                } else {
+                    println!("tag={} count={} total={}", tag.tag, tag.count, tag.total);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Nesting level: 0 for `work`, 1 for `work/project-x`.
+fn depth(tag: &TagCount) -> usize {
+    tag.tag.matches('/').count()
+}
+
+/// Last segment, indented by depth: `work/project-x` becomes `  project-x`.
+fn tree_label(tag: &TagCount) -> String {
+    let name = tag.tag.rsplit('/').next().unwrap_or(&tag.tag);
+    format!("{}{}", "  ".repeat(depth(tag)), name)
+}
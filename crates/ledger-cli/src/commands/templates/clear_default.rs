@@ -3,6 +3,7 @@ use ledger_core::storage::StorageEngine;
 use crate::app::AppContext;
 use crate::cli::TemplateClearDefaultArgs;
 use crate::helpers::require_entry_type;
+use crate::output::Receipt;
 use crate::ui::{badge, print, Badge, OutputMode};
 
 pub fn handle_clear_default(
@@ -13,10 +14,10 @@ pub fn handle_clear_default(
     let entry_type = require_entry_type(&storage, &args.entry_type)?;
 
     storage.clear_default_template(&entry_type.id)?;
-    storage.close(&passphrase)?;
+    ctx.save_storage(storage, &passphrase)?;
 
     if !ctx.quiet() {
-        let ui_ctx = ctx.ui_context(false, None);
+        let ui_ctx = ctx.ui_context(args.json, None);
         match ui_ctx.mode {
             OutputMode::Pretty => {
                 print(
@@ -28,7 +29,16 @@ pub fn handle_clear_default(
                     ),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                Receipt::new(
+                    "template.clear_default",
+                    "entry_type",
+                    entry_type.id,
+                    Some(&args.entry_type),
+                )
+                .print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("entry_type={}", args.entry_type);
             }
@@ -4,8 +4,9 @@ use ledger_core::storage::{NewTemplate, StorageEngine};
 
 use crate::app::AppContext;
 use crate::cli::TemplateCreateArgs;
-use crate::helpers::require_entry_type;
-use crate::ui::theme::{styled, styles};
+use crate::helpers::{parse_by_weekday, require_entry_type};
+use crate::output::Receipt;
+use crate::ui::theme::{styled, styles, symbols};
 use crate::ui::{badge, blank_line, hint, print, short_id, Badge, OutputMode};
 
 pub fn handle_create(ctx: &AppContext, args: &TemplateCreateArgs) -> anyhow::Result<()> {
@@ -14,15 +15,15 @@ pub fn handle_create(ctx: &AppContext, args: &TemplateCreateArgs) -> anyhow::Res
     let metadata = storage.metadata()?;
 
     // Wrap user-provided defaults in the proper template JSON structure
-    let template_json: serde_json::Value = if let Some(ref defaults) = args.defaults {
+    let mut template_json = serde_json::json!({});
+    if let Some(ref defaults) = args.defaults {
         let user_defaults: serde_json::Value = serde_json::from_str(defaults)
             .map_err(|e| anyhow::anyhow!("Invalid JSON for defaults: {}", e))?;
-        serde_json::json!({
-            "defaults": user_defaults
-        })
-    } else {
-        serde_json::json!({})
-    };
+        template_json["defaults"] = user_defaults;
+    }
+    if let Some(ref by_weekday) = args.by_weekday {
+        template_json["by_weekday"] = parse_by_weekday_arg(by_weekday)?;
+    }
 
     let mut new_template =
         NewTemplate::new(&args.name, entry_type.id, template_json, metadata.device_id);
@@ -36,18 +37,19 @@ pub fn handle_create(ctx: &AppContext, args: &TemplateCreateArgs) -> anyhow::Res
         storage.set_default_template(&entry_type.id, &template_id)?;
     }
 
-    storage.close(&passphrase)?;
+    ctx.save_storage(storage, &passphrase)?;
 
     if !ctx.quiet() {
-        let ui_ctx = ctx.ui_context(false, None);
+        let ui_ctx = ctx.ui_context(args.json, None);
         let created_at = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
 
         match ui_ctx.mode {
             OutputMode::Pretty => {
+                let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
                 print(&ui_ctx, &badge(&ui_ctx, Badge::Ok, "Created template"));
                 // Context line with name, ID, and entry type
                 let context = format!(
-                    "Name: {}  \u{00B7}  ID: {}  \u{00B7}  type: {}",
+                    "Name: {}  {sep}  ID: {}  {sep}  type: {}",
                     args.name,
                     short_id(&template_id),
                     args.entry_type
@@ -71,13 +73,18 @@ pub fn handle_create(ctx: &AppContext, args: &TemplateCreateArgs) -> anyhow::Res
                     &hint(
                         &ui_ctx,
                         &format!(
-                            "ledger add {} --template {}  \u{00B7}  ledger template list",
+                            "ledger add {} --template {}  {sep}  ledger template list",
                             args.entry_type, args.name
                         ),
                     ),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                Receipt::new("template.create", "template", template_id, Some(&args.name))
+                    .affects("entry_type", entry_type.id, Some(&args.entry_type))
+                    .print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("template_id={}", template_id);
                 println!("name={}", args.name);
@@ -91,3 +98,11 @@ pub fn handle_create(ctx: &AppContext, args: &TemplateCreateArgs) -> anyhow::Res
     }
     Ok(())
 }
+
+/// Parse and validate a `--by-weekday` JSON map.
+pub(super) fn parse_by_weekday_arg(value: &str) -> anyhow::Result<serde_json::Value> {
+    let by_weekday: serde_json::Value = serde_json::from_str(value)
+        .map_err(|e| anyhow::anyhow!("Invalid JSON for by_weekday: {}", e))?;
+    parse_by_weekday(&by_weekday)?;
+    Ok(by_weekday)
+}
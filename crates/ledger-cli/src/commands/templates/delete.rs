@@ -1,11 +1,12 @@
-use dialoguer::Confirm;
 use uuid::Uuid;
 
 use ledger_core::storage::StorageEngine;
 
 use crate::app::AppContext;
 use crate::cli::TemplateDeleteArgs;
-use crate::ui::{badge, print, Badge, OutputMode};
+use crate::output::Receipt;
+use crate::ui::prompt::confirm_destructive;
+use crate::ui::{badge, print, short_id, Badge, OutputMode};
 
 pub fn handle_delete(ctx: &AppContext, args: &TemplateDeleteArgs) -> anyhow::Result<()> {
     let (mut storage, passphrase) = ctx.open_storage(false)?;
@@ -20,32 +21,44 @@ pub fn handle_delete(ctx: &AppContext, args: &TemplateDeleteArgs) -> anyhow::Res
     let template =
         template.ok_or_else(|| anyhow::anyhow!("Template '{}' not found", args.name_or_id))?;
 
-    let ui_ctx = ctx.ui_context(false, None);
+    // Entries made from it keep pointing at it, so it stays
+    let created = storage.template_entries(&template.id)?;
+    if !created.is_empty() {
+        let mut ids: Vec<String> = created.iter().take(5).map(short_id).collect();
+        if created.len() > ids.len() {
+            ids.push(format!("and {} more", created.len() - ids.len()));
+        }
+        return Err(anyhow::anyhow!(
+            "Template '{}' was used to create {} entries: {}\nHint: Templates that created entries are kept; run `ledger templates stats` to review usage.",
+            template.name,
+            created.len(),
+            ids.join(", ")
+        ));
+    }
 
-    if !args.force {
-        let confirmed = Confirm::new()
-            .with_prompt(format!("Delete template '{}'?", template.name))
-            .default(false)
-            .interact()?;
+    let ui_ctx = ctx.ui_context(args.json, None);
 
-        if !confirmed {
-            if !ctx.quiet() {
-                match ui_ctx.mode {
-                    OutputMode::Pretty => {
-                        print(&ui_ctx, &badge(&ui_ctx, Badge::Info, "Cancelled"));
-                    }
-                    OutputMode::Plain | OutputMode::Json => {
-                        println!("status=cancelled");
-                    }
+    let prompt = format!("Delete template '{}'?", template.name);
+    if !confirm_destructive(&ui_ctx, &prompt, args.force || ctx.assume_yes(), false)? {
+        if !ctx.quiet() {
+            match ui_ctx.mode {
+                OutputMode::Pretty => {
+                    print(&ui_ctx, &badge(&ui_ctx, Badge::Info, "Cancelled"));
+                }
+                OutputMode::Json => {
+                    println!("{}", serde_json::json!({ "status": "cancelled" }));
+                }
+                OutputMode::Plain => {
+                    println!("status=cancelled");
                 }
             }
-            return Ok(());
         }
+        return Ok(());
     }
 
     let name = template.name.clone();
     storage.delete_template(&template.id)?;
-    storage.close(&passphrase)?;
+    ctx.save_storage(storage, &passphrase)?;
 
     if !ctx.quiet() {
         match ui_ctx.mode {
@@ -55,7 +68,10 @@ pub fn handle_delete(ctx: &AppContext, args: &TemplateDeleteArgs) -> anyhow::Res
                     &badge(&ui_ctx, Badge::Ok, &format!("Deleted template '{}'", name)),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                Receipt::new("template.delete", "template", template.id, Some(&name)).print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("deleted={}", name);
             }
@@ -1,4 +1,5 @@
 use ledger_core::storage::StorageEngine;
+use ledger_core::views::TemplateView;
 
 use crate::app::AppContext;
 use crate::cli::TemplateListArgs;
@@ -9,6 +10,8 @@ pub fn handle_list(ctx: &AppContext, args: &TemplateListArgs) -> anyhow::Result<
     let (storage, _passphrase) = ctx.open_storage(false)?;
 
     let templates = storage.list_templates()?;
+    let usage = storage.template_usage()?;
+    let usage_for = |id| usage.get(id).cloned().unwrap_or_default();
 
     // Filter by entry type if specified
     let filtered_templates: Vec<_> = if let Some(ref entry_type_name) = args.entry_type {
@@ -38,18 +41,9 @@ pub fn handle_list(ctx: &AppContext, args: &TemplateListArgs) -> anyhow::Result<
             .map(|t| {
                 let entry_type_name = entry_type_names
                     .get(&t.entry_type_id)
-                    .cloned()
-                    .unwrap_or_else(|| "unknown".to_string());
-                serde_json::json!({
-                    "id": t.id.to_string(),
-                    "name": t.name,
-                    "entry_type": entry_type_name,
-                    "entry_type_id": t.entry_type_id.to_string(),
-                    "version": t.version,
-                    "description": t.description,
-                    "created_at": t.created_at.to_rfc3339(),
-                    "template_json": t.template_json,
-                })
+                    .map(String::as_str)
+                    .unwrap_or("unknown");
+                TemplateView::new(t, entry_type_name).with_usage(&usage_for(&t.id))
             })
             .collect();
         println!("{}", serde_json::to_string_pretty(&json_output)?);
@@ -83,6 +77,8 @@ pub fn handle_list(ctx: &AppContext, args: &TemplateListArgs) -> anyhow::Result<
                 Column::new("Name"),
                 Column::new("Type"),
                 Column::new("Ver"),
+                Column::new("Default"),
+                Column::new("Uses"),
                 Column::new("Description"),
                 Column::new("ID"),
             ];
@@ -94,10 +90,13 @@ pub fn handle_list(ctx: &AppContext, args: &TemplateListArgs) -> anyhow::Result<
                         .get(&t.entry_type_id)
                         .cloned()
                         .unwrap_or_else(|| "unknown".to_string());
+                    let usage = usage_for(&t.id);
                     vec![
                         t.name.clone(),
                         entry_type_name,
                         format!("v{}", t.version),
+                        if usage.is_default { "*" } else { "" }.to_string(),
+                        usage.uses.to_string(),
                         t.description.clone().unwrap_or_default(),
                         short_id(&t.id),
                     ]
@@ -117,10 +116,12 @@ pub fn handle_list(ctx: &AppContext, args: &TemplateListArgs) -> anyhow::Result<
                     .get(&tmpl.entry_type_id)
                     .cloned()
                     .unwrap_or_else(|| "unknown".to_string());
+                let usage = usage_for(&tmpl.id);
+                let default = if usage.is_default { "default" } else { "-" };
                 let desc = tmpl.description.as_deref().unwrap_or("");
                 println!(
-                    "{} {} {} {} {}",
-                    tmpl.id, tmpl.name, entry_type_name, tmpl.version, desc
+                    "{} {} {} {} {} {} {}",
+                    tmpl.id, tmpl.name, entry_type_name, tmpl.version, default, usage.uses, desc
                 );
             }
         }
@@ -4,6 +4,7 @@ pub mod delete;
 pub mod list;
 pub mod set_default;
 pub mod show;
+pub mod stats;
 pub mod update;
 
 pub use clear_default::handle_clear_default;
@@ -12,4 +13,5 @@ pub use delete::handle_delete;
 pub use list::handle_list;
 pub use set_default::handle_set_default;
 pub use show::handle_show;
+pub use stats::handle_stats;
 pub use update::handle_update;
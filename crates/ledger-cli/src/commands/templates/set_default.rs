@@ -5,6 +5,7 @@ use ledger_core::storage::StorageEngine;
 use crate::app::AppContext;
 use crate::cli::TemplateSetDefaultArgs;
 use crate::helpers::require_entry_type;
+use crate::output::Receipt;
 use crate::ui::{badge, print, Badge, OutputMode};
 
 pub fn handle_set_default(ctx: &AppContext, args: &TemplateSetDefaultArgs) -> anyhow::Result<()> {
@@ -39,10 +40,10 @@ pub fn handle_set_default(ctx: &AppContext, args: &TemplateSetDefaultArgs) -> an
 
     let template_name = template.name.clone();
     storage.set_default_template(&entry_type.id, &template.id)?;
-    storage.close(&passphrase)?;
+    ctx.save_storage(storage, &passphrase)?;
 
     if !ctx.quiet() {
-        let ui_ctx = ctx.ui_context(false, None);
+        let ui_ctx = ctx.ui_context(args.json, None);
         match ui_ctx.mode {
             OutputMode::Pretty => {
                 print(
@@ -57,7 +58,17 @@ pub fn handle_set_default(ctx: &AppContext, args: &TemplateSetDefaultArgs) -> an
                     ),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                Receipt::new(
+                    "template.set_default",
+                    "template",
+                    template.id,
+                    Some(&template_name),
+                )
+                .affects("entry_type", entry_type.id, Some(&args.entry_type))
+                .print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("template={}", template_name);
                 println!("entry_type={}", args.entry_type);
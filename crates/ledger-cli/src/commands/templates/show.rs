@@ -1,6 +1,7 @@
 use uuid::Uuid;
 
 use ledger_core::storage::StorageEngine;
+use ledger_core::views::TemplateView;
 
 use crate::app::AppContext;
 use crate::cli::TemplateShowArgs;
@@ -27,22 +28,31 @@ pub fn handle_show(ctx: &AppContext, args: &TemplateShowArgs) -> anyhow::Result<
         .map(|et| et.name.clone())
         .unwrap_or_else(|| "unknown".to_string());
 
+    // Weekday sections as (days, overridden fields)
+    let by_weekday: Vec<(String, String)> = template
+        .template_json
+        .get("by_weekday")
+        .and_then(|sections| sections.as_object())
+        .map(|sections| {
+            sections
+                .iter()
+                .map(|(days, defaults)| {
+                    let fields = defaults
+                        .as_object()
+                        .map(|defaults| defaults.keys().cloned().collect::<Vec<_>>().join(", "))
+                        .unwrap_or_default();
+                    (days.clone(), fields)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
     // Create UI context
     let ui_ctx = ctx.ui_context(args.json, None);
 
     // Handle JSON output
     if ui_ctx.mode.is_json() {
-        let json_output = serde_json::json!({
-            "id": template.id.to_string(),
-            "name": template.name,
-            "entry_type": entry_type_name,
-            "entry_type_id": template.entry_type_id.to_string(),
-            "version": template.version,
-            "description": template.description,
-            "created_at": template.created_at.to_rfc3339(),
-            "device_id": template.device_id.to_string(),
-            "template_json": template.template_json,
-        });
+        let json_output = TemplateView::new(&template, &entry_type_name);
         println!("{}", serde_json::to_string_pretty(&json_output)?);
         return Ok(());
     }
@@ -69,6 +79,16 @@ pub fn handle_show(ctx: &AppContext, args: &TemplateShowArgs) -> anyhow::Result<
                     &template.created_at.format("%Y-%m-%d %H:%M UTC").to_string(),
                 ),
             );
+            for (days, fields) in &by_weekday {
+                print(
+                    &ui_ctx,
+                    &kv(
+                        &ui_ctx,
+                        &format!("On {}", days),
+                        &format!("sets {}", fields),
+                    ),
+                );
+            }
             blank_line(&ui_ctx);
             print(&ui_ctx, &divider(&ui_ctx));
             blank_line(&ui_ctx);
@@ -85,6 +105,9 @@ pub fn handle_show(ctx: &AppContext, args: &TemplateShowArgs) -> anyhow::Result<
             }
             println!("created_at={}", template.created_at.to_rfc3339());
             println!("device_id={}", template.device_id);
+            for (days, fields) in &by_weekday {
+                println!("by_weekday.{}={}", days, fields.replace(", ", ","));
+            }
             println!(
                 "template_json={}",
                 serde_json::to_string(&template.template_json)?
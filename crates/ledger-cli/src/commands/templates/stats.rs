@@ -0,0 +1,143 @@
+//! Template usage: how many entries each template created, by version.
+
+use std::collections::BTreeMap;
+
+use ledger_core::storage::StorageEngine;
+
+use crate::app::AppContext;
+use crate::cli::TemplateStatsArgs;
+use crate::helpers::require_entry_type;
+use crate::ui::{blank_line, header, hint, print, simple_table, Column, OutputMode};
+
+pub fn handle_stats(ctx: &AppContext, args: &TemplateStatsArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+
+    let mut templates = storage.list_templates()?;
+    if let Some(ref entry_type_name) = args.entry_type {
+        let entry_type = require_entry_type(&storage, entry_type_name)?;
+        templates.retain(|t| t.entry_type_id == entry_type.id);
+    }
+    let usage = storage.template_usage()?;
+    let entry_type_names: std::collections::HashMap<_, _> = storage
+        .list_entry_types()?
+        .into_iter()
+        .map(|et| (et.id, et.name))
+        .collect();
+
+    let mut rows: Vec<_> = templates
+        .iter()
+        .map(|t| {
+            let entry_type = entry_type_names
+                .get(&t.entry_type_id)
+                .cloned()
+                .unwrap_or_else(|| "unknown".to_string());
+            (t, entry_type, usage.get(&t.id).cloned().unwrap_or_default())
+        })
+        .collect();
+    // Most used first, so pruning candidates collect at the bottom
+    rows.sort_by(|a, b| {
+        b.2.uses
+            .cmp(&a.2.uses)
+            .then_with(|| a.0.name.cmp(&b.0.name))
+    });
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let json_output: Vec<_> = rows
+                .iter()
+                .map(|(t, entry_type, usage)| {
+                    serde_json::json!({
+                        "id": t.id.to_string(),
+                        "name": t.name,
+                        "entry_type": entry_type,
+                        "version": t.version,
+                        "is_default": usage.is_default,
+                        "usage_count": usage.uses,
+                        "by_version": usage.by_version,
+                        "last_used": usage.last_used.map(|at| at.to_rfc3339()),
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json_output)?);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "templates", Some("stats")));
+            blank_line(&ui_ctx);
+            if rows.is_empty() {
+                print(&ui_ctx, &hint(&ui_ctx, "No templates found."));
+                return Ok(());
+            }
+            let columns = [
+                Column::new("Name"),
+                Column::new("Type"),
+                Column::new("Default"),
+                Column::new("Uses"),
+                Column::new("By version"),
+                Column::new("Last used"),
+            ];
+            let table_rows: Vec<Vec<String>> = rows
+                .iter()
+                .map(|(t, entry_type, usage)| {
+                    vec![
+                        t.name.clone(),
+                        entry_type.clone(),
+                        if usage.is_default { "*" } else { "" }.to_string(),
+                        usage.uses.to_string(),
+                        version_label(&usage.by_version, true),
+                        usage
+                            .last_used
+                            .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+                            .unwrap_or_else(|| "-".to_string()),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &table_rows));
+            let unused = rows.iter().filter(|(_, _, usage)| usage.uses == 0).count();
+            if unused > 0 {
+                blank_line(&ui_ctx);
+                print(
+                    &ui_ctx,
+                    &hint(&ui_ctx, &format!("{} templates never used", unused)),
+                );
+            }
+        }
+        OutputMode::Plain => {
+            println!("count={}", rows.len());
+            for (t, entry_type, usage) in &rows {
+                println!(
+                    "template={} type={} default={} uses={} by_version={} last_used={}",
+                    t.name,
+                    entry_type,
+                    usage.is_default,
+                    usage.uses,
+                    version_label(&usage.by_version, false),
+                    usage
+                        .last_used
+                        .map(|at| at.to_rfc3339())
+                        .unwrap_or_else(|| "-".to_string())
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Per-version counts: `v1: 3, v2: 1` for people, `1:3,2:1` for scripts;
+/// `-` when none were recorded.
+fn version_label(by_version: &BTreeMap<i32, usize>, pretty: bool) -> String {
+    if by_version.is_empty() {
+        return "-".to_string();
+    }
+    let parts: Vec<String> = by_version
+        .iter()
+        .map(|(version, count)| {
+            if pretty {
+                format!("v{}: {}", version, count)
+            } else {
+                format!("{}:{}", version, count)
+            }
+        })
+        .collect();
+    parts.join(if pretty { ", " } else { "," })
+}
@@ -4,8 +4,11 @@ use ledger_core::storage::StorageEngine;
 
 use crate::app::AppContext;
 use crate::cli::TemplateUpdateArgs;
+use crate::output::Receipt;
 use crate::ui::{badge, print, Badge, OutputMode};
 
+use super::create::parse_by_weekday_arg;
+
 pub fn handle_update(ctx: &AppContext, args: &TemplateUpdateArgs) -> anyhow::Result<()> {
     let (mut storage, passphrase) = ctx.open_storage(false)?;
 
@@ -19,19 +22,33 @@ pub fn handle_update(ctx: &AppContext, args: &TemplateUpdateArgs) -> anyhow::Res
     let template =
         template.ok_or_else(|| anyhow::anyhow!("Template '{}' not found", args.name_or_id))?;
 
-    // Wrap user-provided defaults in the proper template JSON structure
-    let user_defaults: serde_json::Value = serde_json::from_str(&args.defaults)
-        .map_err(|e| anyhow::anyhow!("Invalid JSON for defaults: {}", e))?;
-    let new_template_json = serde_json::json!({
-        "defaults": user_defaults
-    });
+    // Replace the given parts of the template JSON, keeping the rest
+    let mut new_template_json = match template.template_json {
+        serde_json::Value::Object(ref object) => serde_json::Value::Object(object.clone()),
+        _ => serde_json::json!({}),
+    };
+    if let Some(ref defaults) = args.defaults {
+        let user_defaults: serde_json::Value = serde_json::from_str(defaults)
+            .map_err(|e| anyhow::anyhow!("Invalid JSON for defaults: {}", e))?;
+        new_template_json["defaults"] = user_defaults;
+    }
+    if let Some(ref by_weekday) = args.by_weekday {
+        let by_weekday = parse_by_weekday_arg(by_weekday)?;
+        if by_weekday.as_object().is_some_and(|days| days.is_empty()) {
+            if let Some(object) = new_template_json.as_object_mut() {
+                object.remove("by_weekday");
+            }
+        } else {
+            new_template_json["by_weekday"] = by_weekday;
+        }
+    }
 
     let name = template.name.clone();
     let new_version = storage.update_template(&template.id, new_template_json)?;
-    storage.close(&passphrase)?;
+    ctx.save_storage(storage, &passphrase)?;
 
     if !ctx.quiet() {
-        let ui_ctx = ctx.ui_context(false, None);
+        let ui_ctx = ctx.ui_context(args.json, None);
         match ui_ctx.mode {
             OutputMode::Pretty => {
                 print(
@@ -43,7 +60,10 @@ pub fn handle_update(ctx: &AppContext, args: &TemplateUpdateArgs) -> anyhow::Res
                     ),
                 );
             }
-            OutputMode::Plain | OutputMode::Json => {
+            OutputMode::Json => {
+                Receipt::new("template.update", "template", template.id, Some(&name)).print()?;
+            }
+            OutputMode::Plain => {
                 println!("status=ok");
                 println!("name={}", name);
                 println!("version={}", new_version);
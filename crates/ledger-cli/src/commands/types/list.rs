@@ -0,0 +1,78 @@
+//! Entry type listing, with retired types marked.
+
+use ledger_core::storage::StorageEngine;
+
+use crate::app::AppContext;
+use crate::cli::TypeListArgs;
+use crate::ui::{blank_line, header, hint, print, simple_table, Column, OutputMode};
+
+pub fn handle_list(ctx: &AppContext, args: &TypeListArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let mut entry_types = storage.list_entry_types()?;
+    entry_types.sort_by(|a, b| a.name.cmp(&b.name));
+    let retired = storage.retired_entry_types()?;
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let json_output: Vec<_> = entry_types
+                .iter()
+                .map(|et| {
+                    serde_json::json!({
+                        "id": et.id.to_string(),
+                        "name": et.name,
+                        "version": et.version,
+                        "created_at": et.created_at.to_rfc3339(),
+                        "retired_at": retired.get(&et.id).map(|at| at.to_rfc3339()),
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json_output)?);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "types", None));
+            blank_line(&ui_ctx);
+            if entry_types.is_empty() {
+                print(
+                    &ui_ctx,
+                    &hint(
+                        &ui_ctx,
+                        "No entry types yet. Add one with `ledger add journal`.",
+                    ),
+                );
+                return Ok(());
+            }
+            let columns = [
+                Column::new("Name"),
+                Column::new("Ver"),
+                Column::new("Retired"),
+            ];
+            let rows: Vec<Vec<String>> = entry_types
+                .iter()
+                .map(|et| {
+                    vec![
+                        et.name.clone(),
+                        format!("v{}", et.version),
+                        retired
+                            .get(&et.id)
+                            .map(|at| at.format("%Y-%m-%d").to_string())
+                            .unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+        }
+        OutputMode::Plain => {
+            println!("count={}", entry_types.len());
+            for et in &entry_types {
+                println!(
+                    "type={} version={} retired={}",
+                    et.name,
+                    et.version,
+                    retired.contains_key(&et.id)
+                );
+            }
+        }
+    }
+    Ok(())
+}
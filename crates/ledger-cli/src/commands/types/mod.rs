@@ -0,0 +1,7 @@
+pub mod list;
+pub mod retire;
+pub mod show;
+
+pub use list::handle_list;
+pub use retire::handle_retire;
+pub use show::handle_show;
@@ -0,0 +1,59 @@
+//! Retire an entry type, or bring a retired one back.
+
+use chrono::Utc;
+
+use crate::app::AppContext;
+use crate::cli::TypeRetireArgs;
+use crate::helpers::require_entry_type;
+use crate::output::Receipt;
+use crate::ui::{badge, print, Badge, OutputMode};
+
+pub fn handle_retire(ctx: &AppContext, args: &TypeRetireArgs) -> anyhow::Result<()> {
+    let (mut storage, passphrase) = ctx.open_storage(false)?;
+    let entry_type = require_entry_type(&storage, &args.name)?;
+
+    let changed = if args.undo {
+        storage.unretire_entry_type(&entry_type.id)?
+    } else {
+        let was_retired = storage.retired_entry_types()?.contains_key(&entry_type.id);
+        storage.retire_entry_type(&entry_type.id, Utc::now())?;
+        !was_retired
+    };
+    if changed {
+        ctx.save_storage(storage, &passphrase)?;
+    }
+
+    if !ctx.quiet() {
+        let ui_ctx = ctx.ui_context(args.json, None);
+        let message = match (args.undo, changed) {
+            (false, true) => format!(
+                "Retired '{}'; existing entries are kept, new ones refused",
+                args.name
+            ),
+            (false, false) => format!("'{}' is already retired", args.name),
+            (true, true) => format!("'{}' takes new entries again", args.name),
+            (true, false) => format!("'{}' is not retired", args.name),
+        };
+        match ui_ctx.mode {
+            OutputMode::Pretty => {
+                let kind = if changed { Badge::Ok } else { Badge::Info };
+                print(&ui_ctx, &badge(&ui_ctx, kind, &message));
+            }
+            OutputMode::Json => {
+                let operation = if args.undo {
+                    "entry_type.unretire"
+                } else {
+                    "entry_type.retire"
+                };
+                Receipt::new(operation, "entry_type", entry_type.id, Some(&args.name)).print()?;
+            }
+            OutputMode::Plain => {
+                println!("status=ok");
+                println!("entry_type={}", args.name);
+                println!("retired={}", !args.undo);
+                println!("changed={}", changed);
+            }
+        }
+    }
+    Ok(())
+}
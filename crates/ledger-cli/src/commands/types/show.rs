@@ -0,0 +1,103 @@
+//! Entry type details: the schema's own documentation and its fields.
+
+use ledger_core::storage::StorageEngine;
+
+use crate::app::AppContext;
+use crate::cli::TypeShowArgs;
+use crate::helpers::FieldDef;
+use crate::ui::{blank_line, header, kv, print, simple_table, Column, OutputMode};
+
+pub fn handle_show(ctx: &AppContext, args: &TypeShowArgs) -> anyhow::Result<()> {
+    let (storage, _passphrase) = ctx.open_storage(false)?;
+    let entry_type = storage.get_entry_type(&args.name)?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Entry type '{}' not found\nHint: Run `ledger types list` to see available types.",
+            args.name
+        )
+    })?;
+    let retired_at = storage.retired_entry_types()?.remove(&entry_type.id);
+    let schema = &entry_type.schema_json;
+    let description = schema.get("description").and_then(|d| d.as_str());
+    let fields = FieldDef::from_schema(schema);
+
+    let ui_ctx = ctx.ui_context(args.json, None);
+    match ui_ctx.mode {
+        OutputMode::Json => {
+            let output = serde_json::json!({
+                "id": entry_type.id.to_string(),
+                "name": entry_type.name,
+                "version": entry_type.version,
+                "created_at": entry_type.created_at.to_rfc3339(),
+                "retired_at": retired_at.map(|at| at.to_rfc3339()),
+                "schema": schema,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputMode::Pretty => {
+            print(&ui_ctx, &header(&ui_ctx, "type", None));
+            blank_line(&ui_ctx);
+            print(&ui_ctx, &kv(&ui_ctx, "Name", &entry_type.name));
+            if let Some(description) = description {
+                print(&ui_ctx, &kv(&ui_ctx, "Description", description));
+            }
+            print(
+                &ui_ctx,
+                &kv(&ui_ctx, "Version", &format!("v{}", entry_type.version)),
+            );
+            if let Some(at) = retired_at {
+                print(
+                    &ui_ctx,
+                    &kv(&ui_ctx, "Retired", &at.format("%Y-%m-%d").to_string()),
+                );
+            }
+            blank_line(&ui_ctx);
+            let columns = [
+                Column::new("Field"),
+                Column::new("Type"),
+                Column::new("Req"),
+                Column::new("Unit"),
+                Column::new("Example"),
+                Column::new("Description"),
+            ];
+            let rows: Vec<Vec<String>> = fields
+                .iter()
+                .map(|field| {
+                    vec![
+                        field.name.clone(),
+                        field.field_type.clone(),
+                        if field.required { "yes" } else { "" }.to_string(),
+                        field.unit.clone().unwrap_or_default(),
+                        field.example.clone().unwrap_or_default(),
+                        field.description.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            print(&ui_ctx, &simple_table(&ui_ctx, &columns, &rows));
+        }
+        OutputMode::Plain => {
+            println!("name={}", entry_type.name);
+            if let Some(description) = description {
+                println!("description={}", description);
+            }
+            println!("version={}", entry_type.version);
+            println!("retired={}", retired_at.is_some());
+            for field in &fields {
+                let mut line = format!(
+                    "field={} type={} required={}",
+                    field.name, field.field_type, field.required
+                );
+                if let Some(ref unit) = field.unit {
+                    line.push_str(&format!(" unit={}", unit));
+                }
+                if let Some(ref example) = field.example {
+                    line.push_str(&format!(" example={}", example));
+                }
+                if let Some(ref description) = field.description {
+                    line.push_str(&format!(" description={}", description));
+                }
+                println!("{}", line);
+            }
+        }
+    }
+    Ok(())
+}
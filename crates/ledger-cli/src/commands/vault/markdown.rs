@@ -0,0 +1,219 @@
+//! Markdown rendering and parsing for vault notes.
+//!
+//! Notes use YAML front matter that Obsidian understands. Values are written
+//! as JSON scalars and flow collections, which are valid YAML, so the parser
+//! only needs to handle the subset this module writes plus light hand edits.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use ledger_core::storage::Entry;
+use uuid::Uuid;
+
+use crate::ui::short_id;
+
+/// Fields read back from a vault note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultNote {
+    pub id: Uuid,
+    pub tags: Vec<String>,
+    pub body: String,
+}
+
+/// Render an entry as a Markdown note with front matter.
+pub fn render_note(entry: &Entry, type_name: &str) -> String {
+    let mut out = String::from("---\n");
+    out.push_str(&format!("id: {}\n", entry.id));
+    out.push_str(&format!("type: {}\n", type_name));
+    out.push_str(&format!("tags: [{}]\n", entry.tags.join(", ")));
+    out.push_str(&format!(
+        "created_at: {}\n",
+        entry
+            .created_at
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    ));
+    if let Some(fields) = entry.data.as_object() {
+        let extra: serde_json::Map<_, _> = fields
+            .iter()
+            .filter(|(key, _)| key.as_str() != "body")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        if !extra.is_empty() {
+            out.push_str(&format!("fields: {}\n", serde_json::Value::Object(extra)));
+        }
+    }
+    out.push_str("---\n");
+    if let Some(body) = entry_body(entry) {
+        out.push_str(body);
+        if !body.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Parse a vault note; `None` if it has no front matter or no valid id.
+pub fn parse_note(content: &str) -> Option<VaultNote> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let (front, body) = split_front_matter(rest)?;
+
+    let mut id = None;
+    let mut tags = Vec::new();
+    for line in front.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "id" => id = Uuid::parse_str(value.trim_matches('"')).ok(),
+            "tags" => tags = parse_tags(value),
+            _ => {}
+        }
+    }
+
+    Some(VaultNote {
+        id: id?,
+        tags,
+        body: body.to_string(),
+    })
+}
+
+/// Vault-relative path for an entry chain, keyed by its first revision.
+///
+/// Revisions keep the path of the entry they supersede, so edits in the
+/// vault land in the same file after a sync.
+pub fn note_path(type_name: &str, root_id: &Uuid, root_created_at: &DateTime<Utc>) -> PathBuf {
+    PathBuf::from(sanitize_segment(type_name))
+        .join(root_created_at.format("%Y-%m").to_string())
+        .join(format!(
+            "{}-{}.md",
+            root_created_at.format("%Y-%m-%d"),
+            short_id(root_id)
+        ))
+}
+
+/// The entry's Markdown body, if it has one.
+pub fn entry_body(entry: &Entry) -> Option<&str> {
+    entry.data.get("body").and_then(|value| value.as_str())
+}
+
+/// Whether a note differs from the entry in the parts that sync back.
+pub fn note_differs(note: &VaultNote, entry: &Entry) -> bool {
+    let body = entry_body(entry).unwrap_or("");
+    let mut note_tags = note.tags.clone();
+    let mut entry_tags = entry.tags.clone();
+    note_tags.sort();
+    entry_tags.sort();
+    note.body.trim_end() != body.trim_end() || note_tags != entry_tags
+}
+
+fn split_front_matter(rest: &str) -> Option<(&str, &str)> {
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Accept `[a, b]`, `["a", "b"]`, `a, b` and `a b` (Obsidian style).
+fn parse_tags(value: &str) -> Vec<String> {
+    let inner = value
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .unwrap_or(value);
+    inner
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(|tag| {
+            tag.trim()
+                .trim_matches('"')
+                .trim_matches('\'')
+                .trim_start_matches('#')
+        })
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| tag.to_ascii_lowercase())
+        .collect()
+}
+
+fn sanitize_segment(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_entry() -> Entry {
+        Entry {
+            id: Uuid::parse_str("6f1c1e2a-0000-4000-8000-000000000001").unwrap(),
+            entry_type_id: Uuid::new_v4(),
+            schema_version: 1,
+            data: serde_json::json!({"body": "Hello vault", "mood": 3}),
+            tags: vec!["work".to_string(), "idea".to_string()],
+            created_at: DateTime::parse_from_rfc3339("2024-03-05T10:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            seq: 0,
+            device_id: Uuid::new_v4(),
+            supersedes: None,
+        }
+    }
+
+    #[test]
+    fn test_render_then_parse_round_trips() {
+        let entry = sample_entry();
+        let rendered = render_note(&entry, "journal");
+        assert!(rendered.starts_with("---\nid: 6f1c1e2a-"));
+        assert!(rendered.contains("tags: [work, idea]\n"));
+        assert!(rendered.contains("fields: {\"mood\":3}\n"));
+
+        let note = parse_note(&rendered).expect("parse note");
+        assert_eq!(note.id, entry.id);
+        assert_eq!(note.body, "Hello vault\n");
+        assert!(!note_differs(&note, &entry));
+    }
+
+    #[test]
+    fn test_parse_detects_hand_edits() {
+        let entry = sample_entry();
+        let edited = render_note(&entry, "journal")
+            .replace(
+                "tags: [work, idea]",
+                "tags: [\"idea\", \"#Work\", \"later\"]",
+            )
+            .replace("Hello vault", "Hello edited vault");
+        let note = parse_note(&edited).expect("parse note");
+        assert_eq!(note.tags, vec!["idea", "work", "later"]);
+        assert_eq!(note.body, "Hello edited vault\n");
+        assert!(note_differs(&note, &entry));
+    }
+
+    #[test]
+    fn test_parse_rejects_notes_without_id() {
+        assert!(parse_note("# Just a note\n").is_none());
+        assert!(parse_note("---\ntitle: x\n---\nbody\n").is_none());
+    }
+
+    #[test]
+    fn test_note_path_uses_type_and_date() {
+        let entry = sample_entry();
+        let path = note_path("my type", &entry.id, &entry.created_at);
+        assert_eq!(
+            path,
+            PathBuf::from("my_type/2024-03/2024-03-05-6f1c1e2a.md")
+        );
+    }
+}
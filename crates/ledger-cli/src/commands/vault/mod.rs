@@ -0,0 +1,4 @@
+pub mod markdown;
+pub mod sync;
+
+pub use sync::handle_sync;
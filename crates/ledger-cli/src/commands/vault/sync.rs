@@ -0,0 +1,232 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+use ledger_core::storage::encryption::{decrypt, encrypt};
+use ledger_core::storage::{AgeSqliteStorage, Entry, EntryFilter, NewEntry, StorageEngine};
+use uuid::Uuid;
+
+use crate::app::AppContext;
+use crate::cli::VaultSyncArgs;
+use crate::output::{chain_root, entry_type_name_map};
+use crate::ui::theme::{styled, styles, symbols};
+use crate::ui::{badge, print, Badge, OutputMode};
+
+use super::markdown::{entry_body, note_differs, note_path, parse_note, render_note};
+
+/// Outcome of one sync pass.
+#[derive(Debug, Default)]
+struct SyncReport {
+    pulled: usize,
+    written: usize,
+    unchanged: usize,
+    conflicts: Vec<PathBuf>,
+}
+
+pub fn handle_sync(ctx: &AppContext, args: &VaultSyncArgs) -> anyhow::Result<()> {
+    if !args.plaintext && !args.encrypt {
+        return Err(anyhow::anyhow!(
+            "Vault notes would be written unencrypted. Pass --plaintext to accept that, or --encrypt to age-encrypt each note."
+        ));
+    }
+    let dir = PathBuf::from(&args.dir);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create vault directory {}", dir.display()))?;
+
+    let ui_ctx = ctx.ui_context(false, None);
+    if args.plaintext && !ctx.quiet() {
+        eprintln!(
+            "{}",
+            badge(
+                &ui_ctx,
+                Badge::Warn,
+                "Vault notes are plaintext; anything that can read the folder can read your entries"
+            )
+        );
+    }
+
+    loop {
+        let (mut storage, passphrase) = ctx.open_storage(args.no_input)?;
+        let report = sync_once(&mut storage, &dir, &passphrase, args.encrypt)?;
+        if report.pulled > 0 {
+            ctx.save_storage(storage, &passphrase)?;
+        }
+
+        if !ctx.quiet() {
+            print_report(ctx, &report);
+        }
+        if !args.watch {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_secs(args.interval.max(1)));
+    }
+}
+
+fn print_report(ctx: &AppContext, report: &SyncReport) {
+    let ui_ctx = ctx.ui_context(false, None);
+    match ui_ctx.mode {
+        OutputMode::Pretty => {
+            let sep = symbols::SEPARATOR.get(ui_ctx.unicode);
+            let status = if report.conflicts.is_empty() {
+                Badge::Ok
+            } else {
+                Badge::Warn
+            };
+            print(
+                &ui_ctx,
+                &badge(
+                    &ui_ctx,
+                    status,
+                    &format!(
+                        "Vault synced: {} pulled, {} written",
+                        report.pulled, report.written
+                    ),
+                ),
+            );
+            let context = format!(
+                "Unchanged: {}  {sep}  Conflicts: {}",
+                report.unchanged,
+                report.conflicts.len()
+            );
+            println!("{}", styled(&context, styles::dim(), ui_ctx.color));
+            for path in &report.conflicts {
+                println!(
+                    "{}",
+                    styled(
+                        &format!("  conflict: {}", path.display()),
+                        styles::dim(),
+                        ui_ctx.color
+                    )
+                );
+            }
+        }
+        OutputMode::Plain | OutputMode::Json => {
+            println!("pulled={}", report.pulled);
+            println!("written={}", report.written);
+            println!("unchanged={}", report.unchanged);
+            println!("conflicts={}", report.conflicts.len());
+            for path in &report.conflicts {
+                println!("conflict={}", path.display());
+            }
+        }
+    }
+}
+
+/// Pull vault edits into the ledger, then write every current entry out.
+///
+/// An edited note whose entry has since been revised in the ledger is a
+/// conflict: it is reported and left untouched so neither side is lost.
+fn sync_once(
+    storage: &mut AgeSqliteStorage,
+    dir: &Path,
+    passphrase: &str,
+    encrypt_notes: bool,
+) -> anyhow::Result<SyncReport> {
+    let mut report = SyncReport::default();
+    let extension = if encrypt_notes { "md.age" } else { "md" };
+    let device_id = storage.metadata()?.device_id;
+
+    let mut notes = Vec::new();
+    collect_notes(dir, extension, &mut notes)?;
+    let superseded = storage.superseded_entry_ids()?;
+    let mut conflicts = HashSet::new();
+    for path in notes {
+        let content = read_note(&path, passphrase, encrypt_notes)?;
+        let Some(note) = parse_note(&content) else {
+            continue;
+        };
+        let Some(entry) = storage.get_entry(&note.id)? else {
+            continue;
+        };
+        if !note_differs(&note, &entry) {
+            continue;
+        }
+        if superseded.contains(&entry.id) {
+            conflicts.insert(path.clone());
+            report.conflicts.push(path);
+            continue;
+        }
+        let mut data = entry.data.clone();
+        if entry_body(&entry).is_some() {
+            data["body"] = serde_json::Value::String(note.body.trim_end().to_string());
+        }
+        let revision = NewEntry::new(entry.entry_type_id, entry.schema_version, data, device_id)
+            .with_tags(note.tags)
+            .with_supersedes(entry.id);
+        storage
+            .insert_entry(&revision)
+            .with_context(|| format!("Failed to pull edits from {}", path.display()))?;
+        report.pulled += 1;
+    }
+
+    let entries = storage.list_entries(&EntryFilter::new())?;
+    let superseded = storage.superseded_entry_ids()?;
+    let name_map = entry_type_name_map(storage)?;
+    let by_id: HashMap<Uuid, &Entry> = entries.iter().map(|entry| (entry.id, entry)).collect();
+    for entry in entries.iter().filter(|e| !superseded.contains(&e.id)) {
+        let root = chain_root(entry, &by_id);
+        let type_name = name_map
+            .get(&entry.entry_type_id)
+            .map(String::as_str)
+            .unwrap_or("unknown");
+        let mut path = dir.join(note_path(type_name, &root.id, &root.created_at));
+        if encrypt_notes {
+            path.set_extension(extension);
+        }
+        if conflicts.contains(&path) {
+            continue;
+        }
+        let rendered = render_note(entry, type_name);
+        if path.exists() && read_note(&path, passphrase, encrypt_notes)? == rendered {
+            report.unchanged += 1;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let bytes = if encrypt_notes {
+            encrypt(rendered.as_bytes(), passphrase)?
+        } else {
+            rendered.into_bytes()
+        };
+        fs::write(&path, bytes)
+            .with_context(|| format!("Failed to write vault note {}", path.display()))?;
+        report.written += 1;
+    }
+
+    Ok(report)
+}
+
+fn read_note(path: &Path, passphrase: &str, encrypted: bool) -> anyhow::Result<String> {
+    let bytes = fs::read(path)?;
+    let bytes = if encrypted {
+        decrypt(&bytes, passphrase)
+            .with_context(|| format!("Failed to decrypt vault note {}", path.display()))?
+    } else {
+        bytes
+    };
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Collect note files below `dir`, skipping hidden folders like `.obsidian`.
+fn collect_notes(dir: &Path, extension: &str, out: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let suffix = format!(".{}", extension);
+    for item in fs::read_dir(dir)? {
+        let path = item?.path();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_notes(&path, extension, out)?;
+        } else if name.ends_with(&suffix) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
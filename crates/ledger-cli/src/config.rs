@@ -1,15 +1,48 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LedgerConfig {
+    /// Layout version; files without one predate versioning and read as 0.
+    #[serde(default)]
+    pub config_version: u32,
     pub ledger: LedgerSection,
     pub security: SecuritySection,
     pub keychain: KeychainSection,
     pub keyfile: KeyfileSection,
     #[serde(default)]
     pub ui: UiSection,
+    #[serde(default, skip_serializing_if = "CaptureSection::is_empty")]
+    pub capture: CaptureSection,
+    #[serde(default, skip_serializing_if = "CollectorsSection::is_empty")]
+    pub collectors: CollectorsSection,
+    #[serde(default, skip_serializing_if = "StorageSection::is_empty")]
+    pub storage: StorageSection,
+    #[serde(default, skip_serializing_if = "PrivacySection::is_empty")]
+    pub privacy: PrivacySection,
+    #[serde(default, skip_serializing_if = "SearchSection::is_empty")]
+    pub search: SearchSection,
+    #[serde(default, skip_serializing_if = "LintSection::is_empty")]
+    pub lint: LintSection,
+    #[serde(default, skip_serializing_if = "TagsSection::is_empty")]
+    pub tags: TagsSection,
+    #[serde(default, skip_serializing_if = "ExportSection::is_empty")]
+    pub export: ExportSection,
+    #[serde(default, skip_serializing_if = "BackupSection::is_empty")]
+    pub backup: BackupSection,
+    #[serde(default, skip_serializing_if = "PromptsSection::is_empty")]
+    pub prompts: PromptsSection,
+    #[serde(default, skip_serializing_if = "ReplicaSection::is_empty")]
+    pub replica: ReplicaSection,
+    #[serde(default, skip_serializing_if = "RemindersSection::is_empty")]
+    pub reminders: RemindersSection,
+    #[serde(default, skip_serializing_if = "SyncSection::is_empty")]
+    pub sync: SyncSection,
+    /// Monthly spending limits per expense category.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub budgets: BTreeMap<String, f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,6 +54,12 @@ pub struct LedgerSection {
 pub struct SecuritySection {
     pub tier: SecurityTier,
     pub passphrase_cache_ttl_seconds: u64,
+    /// Back off after repeated failed unlocks (state kept next to the ledger).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lockout: bool,
+    /// `doctor` warns once the last `ledger reencrypt` is older than this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reencrypt_after_days: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -38,6 +77,327 @@ pub struct KeyfileSection {
 pub struct UiSection {
     pub timezone: Option<String>,
     pub editor: Option<String>,
+    /// Locale for numbers and dates in human output (e.g. "en-US", "de-DE").
+    pub locale: Option<String>,
+    /// Color theme for human output: "dark" (default), "light", "none" or
+    /// "colorblind", or a `[ui.theme]` table of colors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemeConfig>,
+}
+
+/// `[ui] theme`: a preset name, or a `[ui.theme]` table of custom colors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ThemeConfig {
+    Preset(String),
+    Custom(CustomTheme),
+}
+
+/// `[ui.theme]`: colors given as a name, an ANSI index or `#rrggbb`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CustomTheme {
+    /// Preset supplying the colors left unset (default "dark").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// `accent`, `ok`, `warn`, `error` and `info`.
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, toml::Value>,
+}
+
+impl ThemeConfig {
+    /// The preset named, or the base of a custom theme.
+    pub fn preset(&self) -> Option<&str> {
+        match self {
+            Self::Preset(name) => Some(name),
+            Self::Custom(custom) => custom.base.as_deref(),
+        }
+    }
+
+    /// Custom colors by key, ANSI indices given as numbers turned to text.
+    pub fn colors(&self) -> Vec<(&str, String)> {
+        let Self::Custom(custom) = self else {
+            return Vec::new();
+        };
+        custom
+            .colors
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (key.as_str(), value)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct CaptureSection {
+    /// Command that prints a transcript for an audio file passed as its last argument.
+    pub transcriber: Option<String>,
+}
+
+impl CaptureSection {
+    fn is_empty(&self) -> bool {
+        self.transcriber.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct CollectorsSection {
+    /// Collectors run by `ledger collect` (shell_history, git, calendar).
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    /// Shell history file (defaults to $HISTFILE, then ~/.zsh_history, ~/.bash_history).
+    pub history_file: Option<String>,
+    /// Git repositories to summarize commits from.
+    #[serde(default)]
+    pub git_repos: Vec<String>,
+    /// Only count commits by this author (name or email pattern).
+    pub git_author: Option<String>,
+    /// iCalendar (.ics) files to read completed events from.
+    #[serde(default)]
+    pub calendar_files: Vec<String>,
+}
+
+impl CollectorsSection {
+    fn is_empty(&self) -> bool {
+        self.enabled.is_empty()
+            && self.history_file.is_none()
+            && self.git_repos.is_empty()
+            && self.git_author.is_none()
+            && self.calendar_files.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct StorageSection {
+    /// Re-read and verify the ledger file after every write.
+    #[serde(default)]
+    pub verify_after_write: bool,
+    /// Precision kept for new entry timestamps: seconds, millis, micros or nanos.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_precision: Option<String>,
+}
+
+impl StorageSection {
+    fn is_empty(&self) -> bool {
+        !self.verify_after_write && self.timestamp_precision.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct PrivacySection {
+    /// Record when each entry is last shown (`list --sort last-viewed`).
+    #[serde(default)]
+    pub track_access: bool,
+}
+
+impl PrivacySection {
+    fn is_empty(&self) -> bool {
+        !self.track_access
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct SearchSection {
+    /// Search index tokenizer: unicode61, porter, or trigram.
+    ///
+    /// Applied at init; run `ledger reindex` after changing it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
+}
+
+impl SearchSection {
+    fn is_empty(&self) -> bool {
+        self.tokenizer.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct LintSection {
+    /// Word list for `add --lint`, one word per line (default: /usr/share/dict/words).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wordlist: Option<String>,
+}
+
+impl LintSection {
+    fn is_empty(&self) -> bool {
+        self.wordlist.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct TagsSection {
+    /// Offer tag suggestions on every `add`, as with `--suggest-tags`.
+    #[serde(default)]
+    pub auto_suggest: bool,
+    /// Most tags suggested at once (default: 3).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggest_limit: Option<usize>,
+}
+
+impl TagsSection {
+    fn is_empty(&self) -> bool {
+        !self.auto_suggest && self.suggest_limit.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ExportSection {
+    /// Named export settings for `export --profile <name>`.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ExportProfile>,
+}
+
+impl ExportSection {
+    fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct BackupSection {
+    /// Oldest acceptable last backup for `check --healthcheck`.
+    pub max_age_hours: Option<u64>,
+}
+
+impl BackupSection {
+    fn is_empty(&self) -> bool {
+        self.max_age_hours.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ReplicaSection {
+    /// Second encrypted copy written and verified in the background after every save.
+    pub path: Option<String>,
+}
+
+impl ReplicaSection {
+    fn is_empty(&self) -> bool {
+        self.path.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct RemindersSection {
+    /// Command run by `upcoming --notify` with a title and body appended
+    /// (default: osascript on macOS, notify-send elsewhere).
+    pub notify_command: Option<String>,
+}
+
+impl RemindersSection {
+    fn is_empty(&self) -> bool {
+        self.notify_command.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct SyncSection {
+    /// Rules for what each peer's change bundles may contain.
+    #[serde(default)]
+    pub peers: BTreeMap<String, SyncPeerRules>,
+}
+
+impl SyncSection {
+    fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct PromptsSection {
+    /// Show a prompt of the day in the `add journal` editor and in `today`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Include the built-in prompts (default: true).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub builtin: Option<bool>,
+    /// Prompts file, one per line (default: prompts.txt next to the config).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl PromptsSection {
+    fn is_empty(&self) -> bool {
+        !self.enabled && self.builtin.is_none() && self.file.is_none()
+    }
+}
+
+/// `[export.profiles.<name>]`: defaults for the matching `export` flags.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub entry_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Tags whose entries are always left out, added to any `--redact`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact: Vec<String>,
+}
+
+/// `[sync.peers.<name>]`: which entries `sync export --peer <name>` sends.
+///
+/// An entry is sent when it matches every non-empty `include_*` list and
+/// none of the `exclude_*` lists. Tags match tags nested under them too.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncPeerRules {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_types: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_types: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_compositions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_compositions: Vec<String>,
+}
+
+impl SyncPeerRules {
+    /// Whether an entry of `entry_type` with `tags`, in `compositions`, may
+    /// be sent to the peer.
+    pub fn admits(&self, entry_type: &str, tags: &[String], compositions: &[String]) -> bool {
+        let tag_matches = |rule: &String| {
+            let rule = rule.trim_end_matches('/');
+            tags.iter().any(|tag| {
+                tag == rule
+                    || tag
+                        .strip_prefix(rule)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+        };
+        let type_matches = |rule: &String| rule == entry_type;
+        let composition_matches = |rule: &String| compositions.contains(rule);
+
+        let included = (self.include_types.is_empty()
+            || self.include_types.iter().any(type_matches))
+            && (self.include_tags.is_empty() || self.include_tags.iter().any(tag_matches))
+            && (self.include_compositions.is_empty()
+                || self.include_compositions.iter().any(composition_matches));
+        included
+            && !self.exclude_types.iter().any(type_matches)
+            && !self.exclude_tags.iter().any(tag_matches)
+            && !self.exclude_compositions.iter().any(composition_matches)
+    }
+
+    /// Whether the peer may learn of the composition `name`: a sent entry's
+    /// membership in any other is left out of the bundle.
+    pub fn admits_composition(&self, name: &str) -> bool {
+        (self.include_compositions.is_empty()
+            || self.include_compositions.iter().any(|c| c == name))
+            && !self.exclude_compositions.iter().any(|c| c == name)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -68,12 +428,15 @@ impl LedgerConfig {
         editor: Option<String>,
     ) -> Self {
         Self {
+            config_version: CONFIG_VERSION,
             ledger: LedgerSection {
                 path: ledger_path.to_string_lossy().to_string(),
             },
             security: SecuritySection {
                 tier,
                 passphrase_cache_ttl_seconds,
+                lockout: false,
+                reencrypt_after_days: None,
             },
             keychain: KeychainSection {
                 enabled: matches!(tier, SecurityTier::PassphraseKeychain),
@@ -82,11 +445,88 @@ impl LedgerConfig {
                 mode: keyfile_mode,
                 path: keyfile_path.map(|path| path.to_string_lossy().to_string()),
             },
-            ui: UiSection { timezone, editor },
+            ui: UiSection {
+                timezone,
+                editor,
+                locale: None,
+                theme: None,
+            },
+            capture: CaptureSection::default(),
+            collectors: CollectorsSection::default(),
+            storage: StorageSection::default(),
+            privacy: PrivacySection::default(),
+            search: SearchSection::default(),
+            lint: LintSection::default(),
+            tags: TagsSection::default(),
+            export: ExportSection::default(),
+            backup: BackupSection::default(),
+            prompts: PromptsSection::default(),
+            replica: ReplicaSection::default(),
+            reminders: RemindersSection::default(),
+            sync: SyncSection::default(),
+            budgets: BTreeMap::new(),
         }
     }
 }
 
+/// Prefix for config values stored in the ledger's secret store.
+pub const SECRET_REF_PREFIX: &str = "secret://";
+
+/// Return the secret name if a config value is a `secret://name` reference.
+pub fn secret_ref_name(value: &str) -> Option<&str> {
+    value.strip_prefix(SECRET_REF_PREFIX)
+}
+
+impl LedgerConfig {
+    /// Names of secrets referenced anywhere in the config.
+    pub fn secret_refs(&self) -> anyhow::Result<Vec<String>> {
+        let mut value = toml::Value::try_from(self)?;
+        let mut names = Vec::new();
+        visit_strings(&mut value, &mut |s| {
+            if let Some(name) = secret_ref_name(s) {
+                names.push(name.to_string());
+            }
+            Ok(())
+        })?;
+        Ok(names)
+    }
+
+    /// Return a copy with every `secret://name` value replaced by `lookup(name)`.
+    pub fn resolve_secrets(
+        &self,
+        mut lookup: impl FnMut(&str) -> anyhow::Result<Option<String>>,
+    ) -> anyhow::Result<LedgerConfig> {
+        let mut value = toml::Value::try_from(self)?;
+        visit_strings(&mut value, &mut |s| {
+            if let Some(name) = secret_ref_name(s) {
+                *s = lookup(name)?.ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Secret '{}' referenced in config not found\nHint: Run `ledger secret set {}`.",
+                        name,
+                        name
+                    )
+                })?;
+            }
+            Ok(())
+        })?;
+        Ok(value.try_into()?)
+    }
+}
+
+fn visit_strings(
+    value: &mut toml::Value,
+    f: &mut dyn FnMut(&mut String) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    match value {
+        toml::Value::String(s) => f(s),
+        toml::Value::Array(items) => items.iter_mut().try_for_each(|item| visit_strings(item, f)),
+        toml::Value::Table(table) => table
+            .iter_mut()
+            .try_for_each(|(_, item)| visit_strings(item, f)),
+        _ => Ok(()),
+    }
+}
+
 pub fn default_config_path() -> anyhow::Result<PathBuf> {
     Ok(xdg_config_dir()?.join("config.toml"))
 }
@@ -106,6 +546,272 @@ pub fn read_config(path: &Path) -> anyhow::Result<LedgerConfig> {
         .map_err(|e| anyhow::anyhow!("Failed to parse config {}: {}", path.display(), e))
 }
 
+/// Current config layout version, written by `ledger init`.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Keys renamed between layout versions, as `(version, old, new)`: configs
+/// older than `version` have `old` moved to `new` when they are migrated.
+const KEY_RENAMES: &[(u32, &str, &str)] = &[];
+
+/// Result of bringing a config file up to `CONFIG_VERSION`.
+#[derive(Debug)]
+pub struct ConfigMigration {
+    pub from: u32,
+    pub to: u32,
+    /// `(old, new)` key paths that were moved.
+    pub renamed: Vec<(String, String)>,
+    /// Copy of the file as it was before the migration.
+    pub backup: PathBuf,
+}
+
+/// Layout version declared by a config file (0 when absent).
+pub fn config_version(table: &toml::Table) -> u32 {
+    table
+        .get("config_version")
+        .and_then(|v| v.as_integer())
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(0)
+}
+
+/// Apply `renames` newer than the table's version, then stamp it with `to`.
+///
+/// A rename is skipped when the new key is already set, so a setting the
+/// user wrote by hand never gets overwritten. Returns the moved keys.
+fn migrate_table(
+    table: &mut toml::Table,
+    renames: &[(u32, &str, &str)],
+    to: u32,
+) -> Vec<(String, String)> {
+    let from = config_version(table);
+    let mut renamed = Vec::new();
+    for (_, old, new) in renames.iter().filter(|(version, _, _)| *version > from) {
+        if has_key(table, new) {
+            continue;
+        }
+        if let Some(value) = take_key(table, old) {
+            put_key(table, new, value);
+            renamed.push((old.to_string(), new.to_string()));
+        }
+    }
+    table.insert(
+        "config_version".to_string(),
+        toml::Value::Integer(to.into()),
+    );
+    renamed
+}
+
+fn has_key(table: &toml::Table, path: &str) -> bool {
+    match path.split_once('.') {
+        None => table.contains_key(path),
+        Some((head, rest)) => match table.get(head) {
+            Some(toml::Value::Table(inner)) => has_key(inner, rest),
+            _ => false,
+        },
+    }
+}
+
+/// Remove a dotted key path, dropping tables it leaves empty.
+fn take_key(table: &mut toml::Table, path: &str) -> Option<toml::Value> {
+    match path.split_once('.') {
+        None => table.remove(path),
+        Some((head, rest)) => {
+            let toml::Value::Table(inner) = table.get_mut(head)? else {
+                return None;
+            };
+            let value = take_key(inner, rest)?;
+            if inner.is_empty() {
+                table.remove(head);
+            }
+            Some(value)
+        }
+    }
+}
+
+/// Set a dotted key path, creating intermediate tables.
+fn put_key(table: &mut toml::Table, path: &str, value: toml::Value) {
+    match path.split_once('.') {
+        None => {
+            table.insert(path.to_string(), value);
+        }
+        Some((head, rest)) => {
+            let inner = table
+                .entry(head)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            if let toml::Value::Table(inner) = inner {
+                put_key(inner, rest, value);
+            }
+        }
+    }
+}
+
+/// Rewrite a config file older than `CONFIG_VERSION` in the current layout.
+///
+/// Returns `None` when the file is already current. Otherwise the previous
+/// file is copied to `config.toml.<checksum>.bak` before anything is
+/// written, named after a BLAKE3 checksum of its contents so that backups of
+/// different files never overwrite each other. Comments are not carried over
+/// to the rewritten file; they stay in the backup. With `write` unset the
+/// migration is only reported.
+pub fn migrate_config_file(path: &Path, write: bool) -> anyhow::Result<Option<ConfigMigration>> {
+    migrate_config_file_with(path, KEY_RENAMES, CONFIG_VERSION, write)
+}
+
+fn migrate_config_file_with(
+    path: &Path,
+    renames: &[(u32, &str, &str)],
+    to: u32,
+    write: bool,
+) -> anyhow::Result<Option<ConfigMigration>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read config {}: {}", path.display(), e))?;
+    let mut table: toml::Table = toml::from_str(&contents)
+        .map_err(|e| anyhow::anyhow!("Failed to parse config {}: {}", path.display(), e))?;
+    let from = config_version(&table);
+    if from >= to {
+        return Ok(None);
+    }
+    let renamed = migrate_table(&mut table, renames, to);
+    let checksum = blake3::hash(contents.as_bytes()).to_hex();
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".{}.bak", &checksum[..12]));
+    let backup = PathBuf::from(backup);
+    if write {
+        std::fs::write(&backup, &contents).map_err(|e| {
+            anyhow::anyhow!("Failed to back up config to {}: {}", backup.display(), e)
+        })?;
+        let migrated =
+            toml::to_string_pretty(&table).map_err(|e| anyhow::anyhow!("TOML error: {}", e))?;
+        std::fs::write(path, migrated)
+            .map_err(|e| anyhow::anyhow!("Failed to write config {}: {}", path.display(), e))?;
+    }
+    Ok(Some(ConfigMigration {
+        from,
+        to,
+        renamed,
+        backup,
+    }))
+}
+
+/// Every `section.key` that `LedgerConfig` understands.
+const KNOWN_KEYS: &[(&str, &[&str])] = &[
+    ("ledger", &["path"]),
+    (
+        "security",
+        &[
+            "tier",
+            "passphrase_cache_ttl_seconds",
+            "lockout",
+            "reencrypt_after_days",
+        ],
+    ),
+    ("keychain", &["enabled"]),
+    ("keyfile", &["mode", "path"]),
+    ("ui", &["timezone", "editor", "locale", "theme"]),
+    ("capture", &["transcriber"]),
+    (
+        "collectors",
+        &[
+            "enabled",
+            "history_file",
+            "git_repos",
+            "git_author",
+            "calendar_files",
+        ],
+    ),
+    ("storage", &["verify_after_write", "timestamp_precision"]),
+    ("privacy", &["track_access"]),
+    ("search", &["tokenizer"]),
+    ("lint", &["wordlist"]),
+    ("tags", &["auto_suggest", "suggest_limit"]),
+    ("export", &["profiles"]),
+    ("backup", &["max_age_hours"]),
+    ("prompts", &["enabled", "builtin", "file"]),
+    ("replica", &["path"]),
+    ("reminders", &["notify_command"]),
+    ("sync", &["peers"]),
+];
+
+/// Every key of the config tables nested one level deeper.
+const KNOWN_NESTED_KEYS: &[(&str, &[&str])] = &[(
+    "ui.theme",
+    &["base", "accent", "ok", "warn", "error", "info"],
+)];
+
+/// Every key of an `[export.profiles.<name>]` table.
+const EXPORT_PROFILE_KEYS: &[&str] = &["format", "type", "tag", "since", "include", "redact"];
+
+/// Every key of a `[sync.peers.<name>]` table.
+const SYNC_PEER_KEYS: &[&str] = &[
+    "include_types",
+    "exclude_types",
+    "include_tags",
+    "exclude_tags",
+    "include_compositions",
+    "exclude_compositions",
+];
+
+/// Keys in a config file that `LedgerConfig` silently ignores.
+///
+/// `[budgets]` holds free-form category names, so its keys are not checked;
+/// nor are profile names under `[export.profiles]` or peer names under
+/// `[sync.peers]`, only their settings.
+pub fn unknown_config_keys(contents: &str) -> anyhow::Result<Vec<String>> {
+    let table: toml::Table = toml::from_str(contents)?;
+    let mut unknown = Vec::new();
+    for (section, value) in &table {
+        if section == "budgets" || section == "config_version" {
+            continue;
+        }
+        let Some(known) = KNOWN_KEYS
+            .iter()
+            .find(|(name, _)| name == section)
+            .map(|(_, keys)| *keys)
+        else {
+            unknown.push(section.clone());
+            continue;
+        };
+        let toml::Value::Table(keys) = value else {
+            continue;
+        };
+        for (key, value) in keys {
+            let path = format!("{}.{}", section, key);
+            if !known.contains(&key.as_str()) {
+                unknown.push(path);
+                continue;
+            }
+            let nested = KNOWN_NESTED_KEYS
+                .iter()
+                .find(|(name, _)| *name == path)
+                .map(|(_, keys)| *keys);
+            if let (Some(nested), toml::Value::Table(keys)) = (nested, value) {
+                unknown.extend(
+                    keys.keys()
+                        .filter(|key| !nested.contains(&key.as_str()))
+                        .map(|key| format!("{}.{}", path, key)),
+                );
+            }
+            let named_keys = match path.as_str() {
+                "export.profiles" => Some(EXPORT_PROFILE_KEYS),
+                "sync.peers" => Some(SYNC_PEER_KEYS),
+                _ => None,
+            };
+            if let (Some(named_keys), toml::Value::Table(tables)) = (named_keys, value) {
+                for (name, table) in tables {
+                    let toml::Value::Table(keys) = table else {
+                        continue;
+                    };
+                    unknown.extend(
+                        keys.keys()
+                            .filter(|key| !named_keys.contains(&key.as_str()))
+                            .map(|key| format!("{}.{}.{}", path, name, key)),
+                    );
+                }
+            }
+        }
+    }
+    Ok(unknown)
+}
+
 pub fn write_config(path: &Path, config: &LedgerConfig) -> anyhow::Result<()> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| {
@@ -184,6 +890,411 @@ mod tests {
         assert_eq!(config.keyfile.path.as_deref(), Some("/tmp/ledger.key"));
         assert_eq!(config.ui.timezone.as_deref(), Some("UTC"));
         assert_eq!(config.ui.editor.as_deref(), Some("vim"));
+        assert!(config.capture.transcriber.is_none());
+    }
+
+    #[test]
+    fn test_parse_capture_section() {
+        let toml = r#"
+            [ledger]
+            path = "/tmp/ledger.ledger"
+
+            [security]
+            tier = "passphrase"
+            passphrase_cache_ttl_seconds = 0
+
+            [keychain]
+            enabled = false
+
+            [keyfile]
+            mode = "none"
+
+            [capture]
+            transcriber = "whisper-cli --model base"
+        "#;
+        let config: LedgerConfig = toml::from_str(toml).expect("parse config");
+        assert_eq!(
+            config.capture.transcriber.as_deref(),
+            Some("whisper-cli --model base")
+        );
+    }
+
+    #[test]
+    fn test_parse_collectors_section() {
+        let toml = r#"
+            [ledger]
+            path = "/tmp/ledger.ledger"
+
+            [security]
+            tier = "passphrase"
+            passphrase_cache_ttl_seconds = 0
+
+            [keychain]
+            enabled = false
+
+            [keyfile]
+            mode = "none"
+
+            [collectors]
+            enabled = ["git", "calendar"]
+            git_repos = ["~/code/ledger"]
+            calendar_files = ["~/calendar.ics"]
+        "#;
+        let config: LedgerConfig = toml::from_str(toml).expect("parse config");
+        assert_eq!(config.collectors.enabled, vec!["git", "calendar"]);
+        assert_eq!(config.collectors.git_repos, vec!["~/code/ledger"]);
+        assert!(config.collectors.history_file.is_none());
+    }
+
+    #[test]
+    fn test_parse_budgets_section() {
+        let toml = r#"
+            [ledger]
+            path = "/tmp/ledger.ledger"
+
+            [security]
+            tier = "passphrase"
+            passphrase_cache_ttl_seconds = 0
+
+            [keychain]
+            enabled = false
+
+            [keyfile]
+            mode = "none"
+
+            [budgets]
+            groceries = 400
+            dining = 150.5
+        "#;
+        let config: LedgerConfig = toml::from_str(toml).expect("parse config");
+        assert_eq!(config.budgets.get("groceries"), Some(&400.0));
+        assert_eq!(config.budgets.get("dining"), Some(&150.5));
+    }
+
+    #[test]
+    fn test_resolve_secret_refs() {
+        let toml = r#"
+            [ledger]
+            path = "/tmp/ledger.ledger"
+
+            [security]
+            tier = "passphrase"
+            passphrase_cache_ttl_seconds = 0
+
+            [keychain]
+            enabled = false
+
+            [keyfile]
+            mode = "none"
+
+            [capture]
+            transcriber = "secret://transcriber"
+
+            [collectors]
+            git_repos = ["~/code/ledger", "secret://work-repo"]
+        "#;
+        let config: LedgerConfig = toml::from_str(toml).expect("parse config");
+        assert_eq!(
+            config.secret_refs().unwrap(),
+            vec!["transcriber", "work-repo"]
+        );
+
+        let resolved = config
+            .resolve_secrets(|name| Ok(Some(format!("value-of-{}", name))))
+            .unwrap();
+        assert_eq!(
+            resolved.capture.transcriber.as_deref(),
+            Some("value-of-transcriber")
+        );
+        assert_eq!(
+            resolved.collectors.git_repos,
+            vec!["~/code/ledger", "value-of-work-repo"]
+        );
+
+        let missing = config.resolve_secrets(|_| Ok(None));
+        assert!(missing.unwrap_err().to_string().contains("not found"));
+    }
+
+    #[test]
+    fn test_unknown_config_keys() {
+        let toml = r#"
+            [ledger]
+            path = "/tmp/ledger.ledger"
+
+            [security]
+            tier = "passphrase"
+            passphrase_cache_ttl_seconds = 0
+            lockout = true
+
+            [keychain]
+            enabled = false
+
+            [keyfile]
+            mode = "none"
+            path = "/tmp/ledger.key"
+
+            [ui]
+            timezone = "UTC"
+            editor = "vim"
+            locale = "en-US"
+            theme = "light"
+
+            [capture]
+            transcriber = "whisper"
+
+            [collectors]
+            enabled = ["git"]
+            history_file = "~/.zsh_history"
+            git_repos = ["~/src"]
+            git_author = "me"
+            calendar_files = ["~/cal.ics"]
+
+            [storage]
+            verify_after_write = true
+            timestamp_precision = "millis"
+
+            [privacy]
+            track_access = true
+
+            [search]
+            tokenizer = "trigram"
+
+            [lint]
+            wordlist = "/usr/share/dict/words"
+
+            [backup]
+            max_age_hours = 24
+
+            [budgets]
+            groceries = 400.0
+        "#;
+        assert!(unknown_config_keys(toml).unwrap().is_empty());
+
+        let config: LedgerConfig = toml::from_str(toml).expect("parse config");
+        let written = toml::to_string(&config).expect("serialize config");
+        assert!(unknown_config_keys(&written).unwrap().is_empty());
+
+        let typo = format!(
+            "{}\n[theme]\nname = \"dark\"\n",
+            toml.replace("timezone", "timezon")
+        );
+        assert_eq!(
+            unknown_config_keys(&typo).unwrap(),
+            vec!["theme".to_string(), "ui.timezon".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_ui_theme_table() {
+        let base = "[ledger]\npath = \"/tmp/l\"\n[security]\ntier = \"passphrase\"\n\
+                    passphrase_cache_ttl_seconds = 0\n[keychain]\nenabled = false\n\
+                    [keyfile]\nmode = \"none\"\n";
+        let toml = format!(
+            "{}[ui.theme]\nbase = \"light\"\naccent = \"#87d7ff\"\nwarn = 214\n",
+            base
+        );
+        assert!(unknown_config_keys(&toml).unwrap().is_empty());
+        let config: LedgerConfig = toml::from_str(&toml).expect("parse config");
+        let theme = config.ui.theme.as_ref().expect("theme");
+        assert_eq!(theme.preset(), Some("light"));
+        assert_eq!(
+            theme.colors(),
+            vec![
+                ("accent", "#87d7ff".to_string()),
+                ("warn", "214".to_string())
+            ]
+        );
+        let written = toml::to_string(&config).expect("serialize config");
+        let reread: LedgerConfig = toml::from_str(&written).expect("reparse config");
+        assert_eq!(reread.ui.theme, config.ui.theme);
+
+        let typo = format!("{}[ui.theme]\naccnt = \"cyan\"\n", base);
+        assert_eq!(
+            unknown_config_keys(&typo).unwrap(),
+            vec!["ui.theme.accnt".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_export_profiles() {
+        let base = "[ledger]\npath = \"/tmp/l\"\n[security]\ntier = \"passphrase\"\n\
+                    passphrase_cache_ttl_seconds = 0\n[keychain]\nenabled = false\n\
+                    [keyfile]\nmode = \"none\"\n";
+        let toml = format!(
+            "{}[export.profiles.blog]\nformat = \"markdown\"\ntag = \"public\"\n\
+             redact = [\"private\"]\n",
+            base
+        );
+        assert!(unknown_config_keys(&toml).unwrap().is_empty());
+        let config: LedgerConfig = toml::from_str(&toml).expect("parse config");
+        let blog = &config.export.profiles["blog"];
+        assert_eq!(blog.format.as_deref(), Some("markdown"));
+        assert_eq!(blog.tag.as_deref(), Some("public"));
+        assert_eq!(blog.redact, vec!["private"]);
+        assert!(blog.entry_type.is_none());
+        let written = toml::to_string(&config).expect("serialize config");
+        let reread: LedgerConfig = toml::from_str(&written).expect("reparse config");
+        assert_eq!(reread.export.profiles, config.export.profiles);
+
+        let typo = format!("{}[export.profiles.blog]\nredacted = [\"private\"]\n", base);
+        assert_eq!(
+            unknown_config_keys(&typo).unwrap(),
+            vec!["export.profiles.blog.redacted".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_sync_peer_rules() {
+        let base = "[ledger]\npath = \"/tmp/l\"\n[security]\ntier = \"passphrase\"\n\
+                    passphrase_cache_ttl_seconds = 0\n[keychain]\nenabled = false\n\
+                    [keyfile]\nmode = \"none\"\n";
+        let toml = format!(
+            "{}[sync.peers.work]\ninclude_tags = [\"work\"]\nexclude_types = [\"journal\"]\n\
+             exclude_compositions = [\"reviews\"]\n",
+            base
+        );
+        assert!(unknown_config_keys(&toml).unwrap().is_empty());
+        let config: LedgerConfig = toml::from_str(&toml).expect("parse config");
+        let work = &config.sync.peers["work"];
+        let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert!(work.admits("task", &tags(&["work"]), &[]));
+        assert!(work.admits("task", &tags(&["home", "work/meetings"]), &[]));
+        assert!(!work.admits("task", &tags(&["workshop"]), &[]));
+        assert!(!work.admits("journal", &tags(&["work"]), &[]));
+        assert!(!work.admits("task", &tags(&["work"]), &["reviews".to_string()]));
+        assert!(SyncPeerRules::default().admits("journal", &[], &[]));
+        assert!(work.admits_composition("trips"));
+        assert!(!work.admits_composition("reviews"));
+
+        let typo = format!("{}[sync.peers.work]\ninclude_tag = [\"work\"]\n", base);
+        assert_eq!(
+            unknown_config_keys(&typo).unwrap(),
+            vec!["sync.peers.work.include_tag".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_migrate_table_renames_older_keys() {
+        let renames = &[
+            (
+                1,
+                "security.cache_ttl",
+                "security.passphrase_cache_ttl_seconds",
+            ),
+            (1, "ui.zone", "ui.timezone"),
+            (2, "capture.command", "capture.transcriber"),
+        ];
+        let mut table: toml::Table = toml::from_str(
+            "[security]\ncache_ttl = 300\n[ui]\nzone = \"UTC\"\ntimezone = \"local\"\n\
+             [capture]\ncommand = \"whisper\"\n",
+        )
+        .unwrap();
+        let renamed = migrate_table(&mut table, renames, 2);
+        assert_eq!(
+            renamed,
+            vec![
+                (
+                    "security.cache_ttl".to_string(),
+                    "security.passphrase_cache_ttl_seconds".to_string()
+                ),
+                (
+                    "capture.command".to_string(),
+                    "capture.transcriber".to_string()
+                ),
+            ]
+        );
+        assert_eq!(config_version(&table), 2);
+        assert_eq!(
+            table["security"]["passphrase_cache_ttl_seconds"].as_integer(),
+            Some(300)
+        );
+        // An explicit new key wins over the old one, which is kept as is
+        assert_eq!(table["ui"]["timezone"].as_str(), Some("local"));
+        assert_eq!(table["ui"]["zone"].as_str(), Some("UTC"));
+        assert!(table.get("capture").unwrap().get("command").is_none());
+
+        // Renames up to the file's own version were applied already
+        let mut table: toml::Table =
+            toml::from_str("config_version = 1\n[ui]\nzone = \"UTC\"\n").unwrap();
+        assert!(migrate_table(&mut table, renames, 2).is_empty());
+        assert_eq!(table["ui"]["zone"].as_str(), Some("UTC"));
+    }
+
+    #[test]
+    fn test_migrate_config_file_backs_up_and_renames() {
+        let dir =
+            std::env::temp_dir().join(format!("ledger_config_migrate_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        let legacy = "# tuned by hand\n[security]\ntier = \"passphrase\"\ncache_ttl = 300\n";
+        std::fs::write(&path, legacy).unwrap();
+        let renames = &[(
+            1,
+            "security.cache_ttl",
+            "security.passphrase_cache_ttl_seconds",
+        )];
+
+        // A dry run reports the migration and writes nothing
+        let migration = migrate_config_file_with(&path, renames, 1, false)
+            .unwrap()
+            .expect("migration");
+        assert_eq!((migration.from, migration.to), (0, 1));
+        assert!(!migration.backup.exists());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), legacy);
+
+        let migration = migrate_config_file_with(&path, renames, 1, true)
+            .unwrap()
+            .expect("migration");
+        assert_eq!(
+            migration.renamed,
+            vec![(
+                "security.cache_ttl".to_string(),
+                "security.passphrase_cache_ttl_seconds".to_string()
+            )]
+        );
+        let checksum = blake3::hash(legacy.as_bytes()).to_hex();
+        assert_eq!(
+            migration.backup,
+            dir.join(format!("config.toml.{}.bak", &checksum[..12]))
+        );
+        assert_eq!(std::fs::read_to_string(&migration.backup).unwrap(), legacy);
+
+        let migrated: toml::Table =
+            toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(config_version(&migrated), 1);
+        assert_eq!(
+            migrated["security"]["passphrase_cache_ttl_seconds"].as_integer(),
+            Some(300)
+        );
+        assert!(migrated["security"].get("cache_ttl").is_none());
+        assert_eq!(migrated["security"]["tier"].as_str(), Some("passphrase"));
+
+        // A current file is left alone
+        assert!(migrate_config_file_with(&path, renames, 1, true)
+            .unwrap()
+            .is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_config_version_is_known_and_written() {
+        let config = LedgerConfig::new(
+            PathBuf::from("/tmp/l"),
+            SecurityTier::Passphrase,
+            0,
+            KeyfileMode::None,
+            None,
+            None,
+            None,
+        );
+        let written = toml::to_string(&config).expect("serialize config");
+        assert!(written.starts_with(&format!("config_version = {}", CONFIG_VERSION)));
+        assert!(unknown_config_keys(&written).unwrap().is_empty());
+
+        let legacy = written.replace(&format!("config_version = {}\n", CONFIG_VERSION), "");
+        let config: LedgerConfig = toml::from_str(&legacy).expect("parse config");
+        assert_eq!(config.config_version, 0);
     }
 
     #[test]
@@ -18,6 +18,11 @@ pub mod exit_codes {
     pub const AUTH_FAILED: i32 = 5;
 
     /// Integrity check failed.
-    #[allow(dead_code)]
     pub const INTEGRITY_FAILED: i32 = 6;
+
+    /// A warning was raised while `--strict` was set.
+    pub const STRICT_WARNING: i32 = 7;
+
+    /// The last backup is missing or older than `[backup] max_age_hours`.
+    pub const BACKUP_STALE: i32 = 8;
 }
@@ -18,8 +18,16 @@ pub enum CliError {
     },
 
     /// Invalid user input
-    #[allow(dead_code)]
     InvalidInput(String),
+
+    /// A warning raised under `--strict`
+    StrictWarning(String),
+
+    /// The ledger failed its integrity checks
+    IntegrityFailed(String),
+
+    /// No backup recent enough for `[backup] max_age_hours`
+    BackupStale(String),
 }
 
 impl fmt::Display for CliError {
@@ -35,7 +43,14 @@ impl fmt::Display for CliError {
                     write!(f, "{}", message)
                 }
             }
-            CliError::InvalidInput(message) => write!(f, "{}", message),
+            CliError::InvalidInput(message)
+            | CliError::IntegrityFailed(message)
+            | CliError::BackupStale(message) => write!(f, "{}", message),
+            CliError::StrictWarning(message) => write!(
+                f,
+                "{}\nHint: --strict treats warnings as errors; run without it to continue.",
+                message
+            ),
         }
     }
 }
@@ -68,7 +83,6 @@ impl CliError {
     }
 
     /// Create an InvalidInput error.
-    #[allow(dead_code)]
     pub fn invalid_input(message: impl Into<String>) -> Self {
         CliError::InvalidInput(message.into())
     }
@@ -80,12 +94,46 @@ impl CliError {
             CliError::NotFound { .. } => exit_codes::NOT_FOUND,
             CliError::AuthFailed { .. } => exit_codes::AUTH_FAILED,
             CliError::InvalidInput(_) => exit_codes::INVALID_INPUT,
+            CliError::StrictWarning(_) => exit_codes::STRICT_WARNING,
+            CliError::IntegrityFailed(_) => exit_codes::INTEGRITY_FAILED,
+            CliError::BackupStale(_) => exit_codes::BACKUP_STALE,
         }
     }
+}
+
+/// Print `message` as a warning, or fail with it under `--strict`.
+pub fn warn(strict: bool, quiet: bool, message: &str) -> Result<(), CliError> {
+    if strict {
+        return Err(CliError::StrictWarning(message.to_string()));
+    }
+    if !quiet {
+        eprintln!("Warning: {}", message);
+    }
+    Ok(())
+}
+
+/// Exit code for an error returned to `main`: the `CliError` code when the
+/// chain holds one, otherwise 1.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<CliError>())
+        .map(CliError::exit_code)
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::exit_codes;
+
+    #[test]
+    fn test_exit_code_follows_cli_error() {
+        let err = anyhow::Error::from(CliError::auth_failed("Incorrect passphrase."));
+        assert_eq!(exit_code(&err), exit_codes::AUTH_FAILED);
+
+        let wrapped = err.context("while opening");
+        assert_eq!(exit_code(&wrapped), exit_codes::AUTH_FAILED);
 
-    /// Print error message to stderr and exit with appropriate code.
-    pub fn exit(&self) -> ! {
-        eprintln!("Error: {}", self);
-        std::process::exit(self.exit_code())
+        assert_eq!(exit_code(&anyhow::anyhow!("plain failure")), 1);
     }
 }
@@ -0,0 +1,143 @@
+//! Helpers for bookmark entries (URL parsing and page title fetch).
+
+use std::process::Command;
+
+/// Seconds to wait for a page before giving up on the title fetch.
+const FETCH_TIMEOUT_SECS: u64 = 10;
+
+/// Maximum number of bytes read when looking for a page title.
+const FETCH_MAX_BYTES: u64 = 512 * 1024;
+
+/// Validate that a bookmark URL uses http or https.
+pub fn validate_url(url: &str) -> anyhow::Result<()> {
+    let lower = url.trim().to_ascii_lowercase();
+    if !(lower.starts_with("http://") || lower.starts_with("https://")) {
+        return Err(anyhow::anyhow!(
+            "Invalid URL: {} (expected http:// or https://)",
+            url
+        ));
+    }
+    if url_domain(url).is_none() {
+        return Err(anyhow::anyhow!("Invalid URL: {} (missing host)", url));
+    }
+    Ok(())
+}
+
+/// Extract the host portion of a URL, without port, credentials, or a leading `www.`.
+pub fn url_domain(url: &str) -> Option<String> {
+    let trimmed = url.trim();
+    let rest = trimmed
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(trimmed);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host_port = authority
+        .rsplit_once('@')
+        .map(|(_, h)| h)
+        .unwrap_or(authority);
+    let host = if host_port.starts_with('[') {
+        // IPv6 literal
+        host_port.split(']').next().map(|h| format!("{}]", h))?
+    } else {
+        host_port.split(':').next().unwrap_or("").to_string()
+    };
+    let host = host.to_ascii_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host).to_string();
+    if host.is_empty() {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+/// Fetch a page and return its `<title>`, if any.
+///
+/// Uses the system `curl` so the CLI does not carry an HTTP client.
+pub fn fetch_page_title(url: &str) -> anyhow::Result<Option<String>> {
+    validate_url(url)?;
+    let output = Command::new("curl")
+        .arg("--silent")
+        .arg("--show-error")
+        .arg("--fail")
+        .arg("--location")
+        .arg("--max-time")
+        .arg(FETCH_TIMEOUT_SECS.to_string())
+        .arg("--max-filesize")
+        .arg(FETCH_MAX_BYTES.to_string())
+        .arg("--")
+        .arg(url)
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "Failed to fetch {}: {}",
+            url,
+            stderr.trim()
+        ));
+    }
+    let html = String::from_utf8_lossy(&output.stdout);
+    Ok(extract_html_title(&html))
+}
+
+/// Extract and normalize the contents of the first `<title>` element.
+pub fn extract_html_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let raw = &html[start..end];
+    let title = decode_entities(&raw.split_whitespace().collect::<Vec<_>>().join(" "));
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_domain_strips_scheme_path_and_www() {
+        assert_eq!(
+            url_domain("https://www.example.com/a/b?c=d"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(
+            url_domain("http://user:pw@Blog.Example.org:8080/#x"),
+            Some("blog.example.org".to_string())
+        );
+        assert_eq!(url_domain("https://[::1]:80/"), Some("[::1]".to_string()));
+        assert_eq!(url_domain("https:///path"), None);
+    }
+
+    #[test]
+    fn test_validate_url_requires_http_scheme() {
+        assert!(validate_url("https://example.com").is_ok());
+        assert!(validate_url("ftp://example.com").is_err());
+        assert!(validate_url("example.com").is_err());
+        assert!(validate_url("https://").is_err());
+    }
+
+    #[test]
+    fn test_extract_html_title() {
+        let html = "<html><head><TITLE lang=\"en\">\n  Rust &amp; You\n</TITLE></head></html>";
+        assert_eq!(extract_html_title(html), Some("Rust & You".to_string()));
+        assert_eq!(extract_html_title("<title></title>"), None);
+        assert_eq!(extract_html_title("<p>no title</p>"), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! Helpers for capturing files (audio, images) as entry attachments.
+
+use std::path::Path;
+use std::process::Command;
+
+/// A file read from disk, ready to be stored as an attachment.
+pub struct CapturedFile {
+    pub name: String,
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Read a file for attachment, keeping only its file name.
+pub fn read_capture_file(path: &Path) -> anyhow::Result<CapturedFile> {
+    let data = std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow::anyhow!("Invalid file path: {}", path.display()))?;
+    Ok(CapturedFile {
+        mime_type: mime_type_for_path(path).to_string(),
+        name,
+        data,
+    })
+}
+
+/// Guess a MIME type from a file extension.
+pub fn mime_type_for_path(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "m4a" | "mp4" => "audio/mp4",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" | "opus" => "audio/ogg",
+        "flac" => "audio/flac",
+        "webm" => "audio/webm",
+        "aac" => "audio/aac",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "heic" => "image/heic",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "tif" | "tiff" => "image/tiff",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Run the configured transcriber on an audio file and return its output.
+///
+/// The command is split on whitespace and the audio path is appended as the
+/// last argument. Returns `Ok(None)` when the transcriber prints nothing.
+pub fn transcribe_audio(command: &str, path: &Path) -> anyhow::Result<Option<String>> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Transcriber command is empty"))?;
+    let output = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to run transcriber '{}': {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "Transcriber exited with failure: {}",
+            stderr.trim()
+        ));
+    }
+    let transcript = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if transcript.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(transcript))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mime_type_for_path() {
+        assert_eq!(mime_type_for_path(Path::new("memo.M4A")), "audio/mp4");
+        assert_eq!(
+            mime_type_for_path(Path::new("a/b/photo.jpeg")),
+            "image/jpeg"
+        );
+        assert_eq!(
+            mime_type_for_path(Path::new("notes")),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_transcribe_audio_uses_stdout() {
+        let transcript = transcribe_audio("echo hello", Path::new("memo.m4a")).unwrap();
+        assert_eq!(transcript.as_deref(), Some("hello memo.m4a"));
+    }
+
+    #[test]
+    fn test_transcribe_audio_missing_command_errors() {
+        assert!(transcribe_audio("ledger-no-such-transcriber", Path::new("a.wav")).is_err());
+        assert!(transcribe_audio("   ", Path::new("a.wav")).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+//! Clipboard support via the platform's clipboard command.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Clipboard commands tried in order (macOS, Wayland, X11, Windows/WSL).
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["clip.exe"],
+];
+
+/// Copy text to the system clipboard.
+///
+/// Uses the first clipboard command found on PATH so the CLI does not link
+/// against a windowing system.
+pub fn copy_to_clipboard(text: &str) -> anyhow::Result<()> {
+    for command in CLIPBOARD_COMMANDS {
+        let mut child = match Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(anyhow::anyhow!("Failed to run {}: {}", command[0], e)),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let status = child.wait()?;
+        if status.success() {
+            return Ok(());
+        }
+        return Err(anyhow::anyhow!("{} exited with failure", command[0]));
+    }
+    Err(anyhow::anyhow!(
+        "No clipboard command found\nHint: Install pbcopy, wl-copy, xclip, or xsel, or omit --copy."
+    ))
+}
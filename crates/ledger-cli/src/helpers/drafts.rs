@@ -0,0 +1,484 @@
+//! Private storage for the plaintext drafts handed to `$EDITOR`.
+//!
+//! An editor needs a real file, so entry bodies being edited exist in
+//! plaintext for as long as the editor is open. Drafts go to a per-user
+//! `0700` directory, preferring memory-backed locations
+//! (`$XDG_RUNTIME_DIR`, then `/dev/shm`) so they never reach a disk, and are
+//! overwritten with zeros before being removed.
+//!
+//! Text from an `add` session whose editor crashed is kept encrypted under
+//! the ledger passphrase instead (`DraftRecovery`), until it is resumed or
+//! discarded.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use ledger_core::storage::encryption::{decrypt, encrypt};
+use zeroize::Zeroizing;
+
+/// File name prefix of the plaintext drafts handed to `$EDITOR`.
+const DRAFT_PREFIX: &str = "ledger_entry_";
+
+/// File name suffix of encrypted drafts rescued from crashed sessions.
+const RECOVERED_SUFFIX: &str = ".age";
+
+/// Candidate directories named `name` for private runtime files, most
+/// private first.
+fn private_dirs(name: &str) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    #[cfg(unix)]
+    {
+        if let Ok(value) = std::env::var("XDG_RUNTIME_DIR") {
+            if !value.trim().is_empty() {
+                dirs.push(PathBuf::from(value).join("ledger").join(name));
+            }
+        }
+        let uid = unsafe { libc::geteuid() };
+        let shm = Path::new("/dev/shm");
+        if shm.is_dir() {
+            dirs.push(shm.join(format!("ledger-{}", uid)).join(name));
+        }
+        dirs.push(
+            std::env::temp_dir()
+                .join(format!("ledger-{}", uid))
+                .join(name),
+        );
+    }
+    #[cfg(not(unix))]
+    dirs.push(std::env::temp_dir().join(format!("ledger-{}", name)));
+    dirs
+}
+
+/// Candidate directories for drafts, most private first.
+///
+/// The first is used when it can be created; the rest are still swept by
+/// [`wipe_editor_drafts`].
+fn draft_dirs() -> Vec<PathBuf> {
+    private_dirs("drafts")
+}
+
+/// Create `path` (and its parents) with the final directory private to the
+/// current user, refusing one that exists but belongs to someone else.
+pub fn create_private_dir(path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+        match fs::DirBuilder::new().mode(0o700).create(path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Failed to create {}: {}",
+                    path.display(),
+                    e
+                ))
+            }
+        }
+        let metadata = fs::symlink_metadata(path)?;
+        if !metadata.is_dir() || metadata.uid() != unsafe { libc::geteuid() } {
+            return Err(anyhow::anyhow!(
+                "Refusing to use {}: it is not a directory owned by you",
+                path.display()
+            ));
+        }
+        if metadata.permissions().mode() & 0o777 != 0o700 {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o700))?;
+        }
+    }
+    #[cfg(not(unix))]
+    fs::create_dir_all(path)?;
+    Ok(())
+}
+
+/// Create one of the [`private_dirs`] along with the per-user `ledger` or
+/// `ledger-<uid>` directory that holds it, both private to the current user.
+///
+/// The per-user directory has a predictable name, in `/tmp` or `/dev/shm`
+/// for anyone to create first, so it gets the same checks as the final one.
+fn create_private_subdir(path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)?;
+    }
+    create_private_dir(path)
+}
+
+/// Write a new draft holding `initial` and return its path.
+pub fn create_draft(initial: &str) -> anyhow::Result<PathBuf> {
+    let dir = draft_dirs()
+        .into_iter()
+        .find(|dir| create_private_subdir(dir).is_ok())
+        .ok_or_else(|| anyhow::anyhow!("Failed to create a private drafts directory"))?;
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| anyhow::anyhow!("System time error: {}", e))?
+        .as_nanos();
+    let path = dir.join(format!(
+        "{}{}_{}.md",
+        DRAFT_PREFIX,
+        std::process::id(),
+        nanos
+    ));
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to create temp file: {}", e))?;
+    file.write_all(initial.as_bytes())
+        .map_err(|e| anyhow::anyhow!("Failed to create temp file: {}", e))?;
+    Ok(path)
+}
+
+/// Overwrite a file with zeros, flush it to disk, then remove it.
+///
+/// On copy-on-write and journaling filesystems the old blocks may survive
+/// the overwrite; memory-backed draft directories avoid that.
+pub fn shred_file(path: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_file() {
+        if let Ok(mut file) = OpenOptions::new().write(true).open(path) {
+            let zeros = [0u8; 8192];
+            let mut remaining = metadata.len();
+            while remaining > 0 {
+                let chunk = remaining.min(zeros.len() as u64) as usize;
+                file.write_all(&zeros[..chunk])?;
+                remaining -= chunk as u64;
+            }
+            file.sync_all()?;
+        }
+    }
+    fs::remove_file(path)
+}
+
+/// Overwrite and delete editor drafts, including ones still open in an editor.
+///
+/// Drafts left in the system temp directory by older versions are swept too.
+/// Returns the number of drafts removed.
+pub fn wipe_editor_drafts() -> usize {
+    let mut dirs = draft_dirs();
+    dirs.push(std::env::temp_dir());
+
+    let mut removed = 0;
+    for dir in dirs {
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read_dir.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with(DRAFT_PREFIX) || !name.ends_with(".md") {
+                continue;
+            }
+            let path = entry.path();
+            let is_file = fs::symlink_metadata(&path)
+                .map(|metadata| metadata.is_file())
+                .unwrap_or(false);
+            if is_file && shred_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
+
+/// Encrypted drafts for one ledger, kept when an `add` editor session
+/// crashes.
+///
+/// While the editor is open, a lock file for the ledger names the process
+/// and its plaintext draft, so a later `add` can rescue the draft of a
+/// process that died and warn about one still running.
+pub struct DraftRecovery {
+    dir: PathBuf,
+    key: String,
+    passphrase: Zeroizing<String>,
+}
+
+/// An encrypted draft waiting to be resumed or discarded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredDraft {
+    pub path: PathBuf,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Removes the ledger's lock file when the editor session ends.
+pub struct EditLock {
+    path: PathBuf,
+}
+
+impl Drop for EditLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl DraftRecovery {
+    /// Recovery for the ledger at `ledger_path`, encrypting under `passphrase`.
+    pub fn new(ledger_path: &str, passphrase: &str) -> anyhow::Result<Self> {
+        let dir = private_dirs("recovery")
+            .into_iter()
+            .find(|dir| create_private_subdir(dir).is_ok())
+            .ok_or_else(|| anyhow::anyhow!("Failed to create a private recovery directory"))?;
+        let key = blake3::hash(ledger_path.as_bytes()).to_hex()[..16].to_string();
+        Ok(Self {
+            dir,
+            key,
+            passphrase: Zeroizing::new(passphrase.to_string()),
+        })
+    }
+
+    fn lock_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.lock", self.key))
+    }
+
+    /// The process and draft named by the lock file, if there is one.
+    fn read_lock(&self) -> Option<(u32, PathBuf)> {
+        let contents = fs::read_to_string(self.lock_path()).ok()?;
+        let (pid, draft) = contents.trim_end().split_once('\n')?;
+        Some((pid.parse().ok()?, PathBuf::from(draft)))
+    }
+
+    /// Record that this process is editing `draft` until the lock is dropped.
+    pub fn lock(&self, draft: &Path) -> anyhow::Result<EditLock> {
+        let path = self.lock_path();
+        fs::write(
+            &path,
+            format!("{}\n{}\n", std::process::id(), draft.display()),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+        Ok(EditLock { path })
+    }
+
+    /// The process id of another `add` editing an entry for this ledger.
+    pub fn active_session(&self) -> Option<u32> {
+        let (pid, _) = self.read_lock()?;
+        (pid != std::process::id() && process_alive(pid)).then_some(pid)
+    }
+
+    /// Encrypt the draft left behind by a session whose process died, then
+    /// shred it. Returns whether anything was rescued.
+    pub fn rescue_stale_session(&self) -> anyhow::Result<bool> {
+        let Some((pid, draft)) = self.read_lock() else {
+            return Ok(false);
+        };
+        if pid == std::process::id() || process_alive(pid) {
+            return Ok(false);
+        }
+        let text = fs::read_to_string(&draft).ok().map(Zeroizing::new);
+        let rescued = match text {
+            Some(ref text) if !text.trim().is_empty() => {
+                self.save(text)?;
+                true
+            }
+            _ => false,
+        };
+        if draft.exists() {
+            shred_file(&draft)?;
+        }
+        let _ = fs::remove_file(self.lock_path());
+        Ok(rescued)
+    }
+
+    /// Encrypt `text` as a new recovered draft.
+    pub fn save(&self, text: &str) -> anyhow::Result<PathBuf> {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| anyhow::anyhow!("System time error: {}", e))?
+            .as_millis();
+        let path = self
+            .dir
+            .join(format!("{}_{}{}", self.key, millis, RECOVERED_SUFFIX));
+        let sealed = encrypt(text.as_bytes(), &self.passphrase)?;
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options
+            .open(&path)
+            .and_then(|mut file| file.write_all(&sealed))
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// Recovered drafts for this ledger, oldest first.
+    pub fn drafts(&self) -> anyhow::Result<Vec<RecoveredDraft>> {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return Ok(Vec::new());
+        };
+        let prefix = format!("{}_", self.key);
+        let mut drafts: Vec<RecoveredDraft> = read_dir
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let millis: i64 = name
+                    .strip_prefix(&prefix)?
+                    .strip_suffix(RECOVERED_SUFFIX)?
+                    .parse()
+                    .ok()?;
+                Some(RecoveredDraft {
+                    path: entry.path(),
+                    saved_at: DateTime::from_timestamp_millis(millis)?,
+                })
+            })
+            .collect();
+        drafts.sort_by_key(|draft| draft.saved_at);
+        Ok(drafts)
+    }
+
+    /// Decrypt a recovered draft.
+    pub fn read(&self, draft: &RecoveredDraft) -> anyhow::Result<Zeroizing<String>> {
+        let sealed = fs::read(&draft.path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", draft.path.display(), e))?;
+        let text = Zeroizing::new(decrypt(&sealed, &self.passphrase)?);
+        Ok(Zeroizing::new(String::from_utf8_lossy(&text).into_owned()))
+    }
+
+    /// Delete a recovered draft.
+    pub fn discard(&self, draft: &RecoveredDraft) -> anyhow::Result<()> {
+        fs::remove_file(&draft.path)
+            .map_err(|e| anyhow::anyhow!("Failed to remove {}: {}", draft.path.display(), e))
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    let signalled = unsafe { libc::kill(pid, 0) } == 0;
+    signalled || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Without a cheap liveness check, a locked session is assumed to be running.
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ledger_drafts_{}_{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_shred_file_removes_file() {
+        let dir = scratch_dir("shred");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("draft.md");
+        fs::write(&path, "secret body").unwrap();
+        shred_file(&path).unwrap();
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_private_dir_tightens_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = scratch_dir("private");
+        fs::create_dir_all(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        create_private_dir(&dir).unwrap();
+        let mode = fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_recovery_rescues_draft_of_dead_session() {
+        let dir = scratch_dir("recovery");
+        create_private_dir(&dir).unwrap();
+        let recovery = DraftRecovery {
+            dir: dir.clone(),
+            key: "0123456789abcdef".to_string(),
+            passphrase: Zeroizing::new("test-passphrase-secure-123".to_string()),
+        };
+        let draft = dir.join("ledger_entry_1_1.md");
+        fs::write(&draft, "Half a thought").unwrap();
+
+        // A live process keeps its draft; a finished one leaves it behind
+        {
+            let _lock = recovery.lock(&draft).unwrap();
+            assert!(!recovery.rescue_stale_session().unwrap());
+            assert_eq!(recovery.active_session(), None);
+        }
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        fs::write(
+            recovery.lock_path(),
+            format!("{}\n{}\n", pid, draft.display()),
+        )
+        .unwrap();
+        assert!(recovery.rescue_stale_session().unwrap());
+        assert!(!draft.exists());
+        assert!(!recovery.lock_path().exists());
+
+        let drafts = recovery.drafts().unwrap();
+        assert_eq!(drafts.len(), 1);
+        assert!(!fs::read(&drafts[0].path)
+            .unwrap()
+            .windows(4)
+            .any(|w| w == b"Half"));
+        assert_eq!(
+            recovery.read(&drafts[0]).unwrap().as_str(),
+            "Half a thought"
+        );
+        recovery.discard(&drafts[0]).unwrap();
+        assert!(recovery.drafts().unwrap().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_private_subdir_checks_per_user_dir() {
+        use std::os::unix::fs::PermissionsExt;
+        let parent = scratch_dir("subdir");
+        fs::create_dir_all(&parent).unwrap();
+        fs::set_permissions(&parent, fs::Permissions::from_mode(0o777)).unwrap();
+        create_private_subdir(&parent.join("drafts")).unwrap();
+        let mode = fs::metadata(&parent).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        let _ = fs::remove_dir_all(&parent);
+
+        // A per-user directory planted as a symlink is refused outright
+        let target = scratch_dir("subdir_target");
+        fs::create_dir_all(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &parent).unwrap();
+        assert!(create_private_subdir(&parent.join("drafts")).is_err());
+        assert!(!target.join("drafts").exists());
+        let _ = fs::remove_file(&parent);
+        let _ = fs::remove_dir_all(&target);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_private_dir_refuses_symlink() {
+        let dir = scratch_dir("link");
+        let target = scratch_dir("link_target");
+        fs::create_dir_all(&target).unwrap();
+        let _ = fs::remove_file(&dir);
+        std::os::unix::fs::symlink(&target, &dir).unwrap();
+        assert!(create_private_dir(&dir).is_err());
+        let _ = fs::remove_file(&dir);
+        let _ = fs::remove_dir_all(&target);
+    }
+}
@@ -0,0 +1,153 @@
+//! Resolving the editor used to compose entry bodies.
+
+use std::path::Path;
+
+/// GUI editors that return immediately unless told to wait: program name,
+/// flags that make them block, and the flag to suggest.
+const WAIT_FLAGS: &[(&str, &[&str], &str)] = &[
+    ("code", &["--wait", "-w"], "--wait"),
+    ("code-insiders", &["--wait", "-w"], "--wait"),
+    ("codium", &["--wait", "-w"], "--wait"),
+    ("cursor", &["--wait", "-w"], "--wait"),
+    ("windsurf", &["--wait", "-w"], "--wait"),
+    ("zed", &["--wait", "-w"], "--wait"),
+    ("subl", &["--wait", "-w"], "-w"),
+    ("atom", &["--wait", "-w"], "--wait"),
+    ("mate", &["--wait", "-w"], "-w"),
+    ("gvim", &["--nofork", "-f"], "-f"),
+    ("mvim", &["--nofork", "-f"], "-f"),
+    ("open", &["--wait-apps", "-W"], "-W"),
+];
+
+/// The editor command to run: an explicit override (`--editor` or
+/// `[ui] editor`), then `$VISUAL`, then `$EDITOR`.
+pub fn resolve_editor(editor_override: Option<&str>) -> Option<String> {
+    editor_override
+        .map(|value| value.to_string())
+        .into_iter()
+        .chain(
+            ["VISUAL", "EDITOR"]
+                .iter()
+                .filter_map(|key| std::env::var(key).ok()),
+        )
+        .find(|value| !value.trim().is_empty())
+}
+
+/// Split an editor command such as `code --wait` or `"my editor" -f` into
+/// the program and its arguments, honoring shell-style quotes.
+///
+/// A command naming an existing file is taken whole, so unquoted paths with
+/// spaces keep working.
+pub fn split_editor_command(command: &str) -> anyhow::Result<Vec<String>> {
+    if Path::new(command).is_file() {
+        return Ok(vec![command.to_string()]);
+    }
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(unterminated(command)),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(unterminated(command)),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(unterminated(command)),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(c) = chars.next() {
+                    word.push(c);
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    if words.is_empty() {
+        return Err(anyhow::anyhow!("Editor command is empty"));
+    }
+    Ok(words)
+}
+
+fn unterminated(command: &str) -> anyhow::Error {
+    anyhow::anyhow!("Unterminated quote in editor command: {}", command)
+}
+
+/// The flag a GUI editor needs to wait for the file to be closed, if the
+/// command lacks it.
+pub fn missing_wait_flag(command: &[String]) -> Option<&'static str> {
+    let program = Path::new(command.first()?).file_name()?.to_str()?;
+    let program = program.strip_suffix(".exe").unwrap_or(program);
+    let (_, flags, suggest) = WAIT_FLAGS.iter().find(|(name, _, _)| *name == program)?;
+    if command[1..].iter().any(|arg| flags.contains(&arg.as_str())) {
+        None
+    } else {
+        Some(suggest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_editor_command() {
+        assert_eq!(
+            split_editor_command("code --wait").unwrap(),
+            vec!["code", "--wait"]
+        );
+        assert_eq!(
+            split_editor_command("  \"/opt/My Editor/bin/ed\" -f 'a b' c\\ d ").unwrap(),
+            vec!["/opt/My Editor/bin/ed", "-f", "a b", "c d"]
+        );
+        assert!(split_editor_command("vim 'oops").is_err());
+        assert!(split_editor_command("   ").is_err());
+    }
+
+    #[test]
+    fn test_missing_wait_flag() {
+        let command = |value: &str| split_editor_command(value).unwrap();
+        assert_eq!(missing_wait_flag(&command("code")), Some("--wait"));
+        assert_eq!(
+            missing_wait_flag(&command("/usr/local/bin/subl")),
+            Some("-w")
+        );
+        assert_eq!(missing_wait_flag(&command("code --wait")), None);
+        assert_eq!(missing_wait_flag(&command("subl -w")), None);
+        assert_eq!(missing_wait_flag(&command("vim")), None);
+    }
+}
@@ -0,0 +1,157 @@
+//! Built-in entry types shipped with the CLI.
+
+use ledger_core::storage::{AgeSqliteStorage, NewEntryType, StorageEngine};
+use uuid::Uuid;
+
+/// Names of the entry types the CLI knows how to create.
+pub const BUILTIN_ENTRY_TYPES: &[&str] = &[
+    "journal",
+    "bookmark",
+    "photo",
+    "activity",
+    "task",
+    "anniversary",
+    "expense",
+    "habit",
+];
+
+/// Return true if the name refers to a built-in entry type.
+pub fn is_builtin_entry_type(name: &str) -> bool {
+    BUILTIN_ENTRY_TYPES.contains(&name)
+}
+
+/// Schema JSON for a built-in entry type.
+pub fn builtin_schema(name: &str) -> Option<serde_json::Value> {
+    match name {
+        "journal" => Some(serde_json::json!({
+            "icon": "\u{1F4D3}",
+            "color": "blue",
+            "fields": [
+                {"name": "body", "type": "text", "required": true}
+            ]
+        })),
+        "bookmark" => Some(serde_json::json!({
+            "icon": "\u{1F516}",
+            "color": "cyan",
+            "fields": [
+                {"name": "url", "type": "string", "required": true, "prompt": "URL", "order": 1},
+                {"name": "title", "type": "string", "required": false, "prompt": "Title", "order": 2},
+                {"name": "notes", "type": "text", "required": false, "prompt": "Notes", "order": 3}
+            ]
+        })),
+        "photo" => Some(serde_json::json!({
+            "icon": "\u{1F4F7}",
+            "color": "magenta",
+            "fields": [
+                {"name": "file", "type": "string", "required": true, "prompt": "File name", "order": 1},
+                {"name": "caption", "type": "string", "required": false, "prompt": "Caption", "order": 2},
+                {"name": "taken_at", "type": "datetime", "required": false, "order": 3},
+                {"name": "latitude", "type": "number", "required": false, "order": 4},
+                {"name": "longitude", "type": "number", "required": false, "order": 5}
+            ]
+        })),
+        "activity" => Some(serde_json::json!({
+            "icon": "\u{1F4CB}",
+            "color": "gray",
+            "fields": [
+                {"name": "source", "type": "string", "required": true, "order": 1},
+                {"name": "date", "type": "date", "required": true, "order": 2},
+                {"name": "subject", "type": "string", "required": false, "order": 3},
+                {"name": "summary", "type": "string", "required": true, "order": 4},
+                {"name": "count", "type": "integer", "required": false, "order": 5},
+                {"name": "details", "type": "text", "required": false, "order": 6}
+            ]
+        })),
+        "task" => Some(serde_json::json!({
+            "icon": "\u{2705}",
+            "color": "green",
+            "fields": [
+                {"name": "title", "type": "string", "required": true, "prompt": "Task", "order": 1},
+                {"name": "done", "type": "boolean", "required": false, "order": 2},
+                {"name": "due", "type": "date", "required": false, "prompt": "Due date", "order": 3},
+                {"name": "priority", "type": "string", "required": false, "prompt": "Priority (A-Z)", "order": 4},
+                {"name": "completed_at", "type": "date", "required": false, "order": 5}
+            ]
+        })),
+        "anniversary" => Some(serde_json::json!({
+            "icon": "\u{1F382}",
+            "color": "red",
+            "fields": [
+                {"name": "title", "type": "string", "required": true, "prompt": "What", "order": 1},
+                {"name": "date", "type": "date", "required": true, "prompt": "Date (YYYY-MM-DD)", "recurrence": "yearly", "order": 2},
+                {"name": "notes", "type": "text", "required": false, "prompt": "Notes", "order": 3}
+            ]
+        })),
+        "expense" => Some(serde_json::json!({
+            "icon": "\u{1F4B8}",
+            "color": "yellow",
+            "fields": [
+                {"name": "amount", "type": "number", "required": true, "prompt": "Amount", "decimals": 2, "order": 1},
+                {"name": "currency", "type": "string", "required": true, "prompt": "Currency (e.g. USD)", "order": 2},
+                {"name": "category", "type": "string", "required": true, "prompt": "Category", "order": 3},
+                {"name": "merchant", "type": "string", "required": false, "prompt": "Merchant", "order": 4},
+                {"name": "notes", "type": "text", "required": false, "prompt": "Notes", "order": 5}
+            ]
+        })),
+        "habit" => Some(serde_json::json!({
+            "icon": "\u{1F501}",
+            "color": "green",
+            "fields": [
+                {"name": "habit", "type": "string", "required": true, "prompt": "Habit", "order": 1},
+                {"name": "date", "type": "date", "required": true, "prompt": "Date (YYYY-MM-DD)", "order": 2},
+                {"name": "notes", "type": "text", "required": false, "prompt": "Notes", "order": 3}
+            ]
+        })),
+        _ => None,
+    }
+}
+
+/// Create a built-in entry type if it does not exist yet.
+///
+/// Ledgers created before a built-in type was introduced get it lazily the
+/// first time it is used. Unknown names are ignored.
+pub fn ensure_builtin_entry_type(
+    storage: &mut AgeSqliteStorage,
+    name: &str,
+    device_id: Uuid,
+) -> anyhow::Result<()> {
+    let Some(schema) = builtin_schema(name) else {
+        return Ok(());
+    };
+    if storage.get_entry_type(name)?.is_some() {
+        return Ok(());
+    }
+
+    let entry_type = NewEntryType::new(name, schema, device_id);
+    storage.create_entry_type(&entry_type)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_names_have_schemas() {
+        for name in BUILTIN_ENTRY_TYPES {
+            assert!(
+                builtin_schema(name).is_some(),
+                "missing schema for {}",
+                name
+            );
+        }
+        assert!(builtin_schema("workout").is_none());
+    }
+
+    #[test]
+    fn test_bookmark_requires_url_only() {
+        let schema = builtin_schema("bookmark").unwrap();
+        let fields = schema["fields"].as_array().unwrap();
+        let required: Vec<&str> = fields
+            .iter()
+            .filter(|f| f["required"].as_bool() == Some(true))
+            .filter_map(|f| f["name"].as_str())
+            .collect();
+        assert_eq!(required, vec!["url"]);
+    }
+}
@@ -3,9 +3,17 @@
 use std::collections::HashMap;
 use std::io::{self, IsTerminal};
 
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::Weekday;
 use dialoguer::{Input, MultiSelect, Select};
 use serde_json::Value;
 
+use super::input::EditorSession;
+use super::prompts::{prompt_placeholder, strip_prompt_placeholder};
+use crate::ui::prompt::{print_field_help, prompt_validated};
+use crate::ui::UiContext;
+
 /// Field definition parsed from entry type schema
 #[derive(Debug, Clone)]
 pub struct FieldDef {
@@ -16,6 +24,11 @@ pub struct FieldDef {
     pub order: Option<i32>,
     pub values: Option<Vec<String>>, // For enum fields
     pub multiple: bool,              // For multi-select enums
+    pub description: Option<String>,
+    pub unit: Option<String>, // For number/integer fields
+    pub example: Option<String>,
+    pub min: Option<f64>, // For number/integer fields
+    pub max: Option<f64>,
 }
 
 impl FieldDef {
@@ -58,6 +71,24 @@ impl FieldDef {
                         .and_then(|m| m.as_bool())
                         .unwrap_or(false);
 
+                    let description = field
+                        .get("description")
+                        .and_then(|d| d.as_str())
+                        .map(|s| s.to_string());
+
+                    let unit = field
+                        .get("unit")
+                        .and_then(|u| u.as_str())
+                        .map(|s| s.to_string());
+
+                    let example = field.get("example").map(|e| match e {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    });
+
+                    let min = field.get("min").and_then(|m| m.as_f64());
+                    let max = field.get("max").and_then(|m| m.as_f64());
+
                     fields.push(FieldDef {
                         name: name.to_string(),
                         field_type,
@@ -66,6 +97,11 @@ impl FieldDef {
                         order,
                         values,
                         multiple,
+                        description,
+                        unit,
+                        example,
+                        min,
+                        max,
                     });
                 }
             }
@@ -81,6 +117,58 @@ impl FieldDef {
 
         fields
     }
+
+    /// Help line shown above the prompt: the description plus any range,
+    /// unit and example.
+    pub fn help_text(&self) -> Option<String> {
+        let range = self.range_text();
+        let details = [
+            range.clone(),
+            self.unit.as_ref().map(|unit| format!("in {}", unit)),
+            self.example
+                .as_ref()
+                .map(|example| format!("e.g. {}", example)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        match (&self.description, range) {
+            (Some(desc), _) if details.is_empty() => Some(desc.clone()),
+            (Some(desc), _) => Some(format!("{} ({})", desc, details.join(", "))),
+            (None, Some(range)) if details.len() == 1 => Some(format!("Must be {}", range)),
+            (None, Some(range)) => Some(format!("Must be {} ({})", range, details[1..].join(", "))),
+            (None, None) if details.is_empty() => None,
+            (None, None) => Some(capitalize(&details.join(", "))),
+        }
+    }
+
+    /// Parse raw input for this field and check it against the schema's
+    /// type, allowed values, and range.
+    pub fn check_value(&self, raw: &str) -> anyhow::Result<Value> {
+        let value = parse_field_value(&self.field_type, raw, &self.values, self.multiple)?;
+        if let (Some(number), Some(range)) = (value.as_f64(), self.range_text()) {
+            let below = self.min.is_some_and(|min| number < min);
+            let above = self.max.is_some_and(|max| number > max);
+            if below || above {
+                return Err(anyhow::anyhow!(
+                    "Field '{}' must be {} (got {})",
+                    self.name,
+                    range,
+                    raw
+                ));
+            }
+        }
+        Ok(value)
+    }
+
+    fn range_text(&self) -> Option<String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => Some(format!("between {} and {}", min, max)),
+            (Some(min), None) => Some(format!("at least {}", min)),
+            (None, Some(max)) => Some(format!("at most {}", max)),
+            (None, None) => None,
+        }
+    }
 }
 
 /// Template defaults parsed from template JSON
@@ -90,6 +178,9 @@ pub struct TemplateDefaults {
     pub default_tags: Vec<String>,
     pub default_compositions: Vec<String>,
     pub prompt_overrides: HashMap<String, String>,
+    /// Editor starting text for text fields without a default, such as the
+    /// journal prompt of the day; never stored.
+    pub placeholders: HashMap<String, String>,
 }
 
 impl TemplateDefaults {
@@ -133,15 +224,78 @@ impl TemplateDefaults {
 
         result
     }
+
+    /// Parse template defaults for an entry written on `weekday`: matching
+    /// `by_weekday` sections override `defaults` field by field.
+    pub fn for_weekday(template_json: &Value, weekday: Weekday) -> Self {
+        let mut result = Self::from_template_json(template_json);
+        let sections = template_json
+            .get("by_weekday")
+            .and_then(|sections| parse_by_weekday(sections).ok())
+            .unwrap_or_default();
+        for (days, defaults) in sections {
+            if days.contains(&weekday) {
+                for (k, v) in defaults {
+                    result.defaults.insert(k, v);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Days a `by_weekday` section applies to, and its field defaults.
+pub type WeekdayDefaults = (Vec<Weekday>, serde_json::Map<String, Value>);
+
+/// Parse a template's `by_weekday` map: keys are day names (`fri`,
+/// `friday`), comma lists (`mon,wed`), `weekdays` or `weekends`, and values
+/// are field defaults.
+pub fn parse_by_weekday(value: &Value) -> anyhow::Result<Vec<WeekdayDefaults>> {
+    let sections = value
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("by_weekday must be a JSON object"))?;
+    sections
+        .iter()
+        .map(|(key, defaults)| {
+            let days = parse_weekday_key(key).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Invalid by_weekday key: {} (use mon..sun, mon,wed, weekdays or weekends)",
+                    key
+                )
+            })?;
+            let defaults = defaults.as_object().cloned().ok_or_else(|| {
+                anyhow::anyhow!("by_weekday.{} must be an object of field defaults", key)
+            })?;
+            Ok((days, defaults))
+        })
+        .collect()
+}
+
+fn parse_weekday_key(key: &str) -> Option<Vec<Weekday>> {
+    match key.trim().to_ascii_lowercase().as_str() {
+        "weekdays" => Some(vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ]),
+        "weekends" => Some(vec![Weekday::Sat, Weekday::Sun]),
+        days => days
+            .split(',')
+            .map(|day| day.trim().parse::<Weekday>().ok())
+            .collect(),
+    }
 }
 
 /// Prompt for field values based on schema and template defaults
 pub fn prompt_for_fields(
+    ui_ctx: &UiContext,
     fields: &[FieldDef],
     template_defaults: &TemplateDefaults,
     cli_values: &HashMap<String, String>,
     no_input: bool,
-    editor_override: Option<&str>,
+    editor: EditorSession,
 ) -> anyhow::Result<serde_json::Map<String, Value>> {
     let mut data = serde_json::Map::new();
     let interactive = io::stdin().is_terminal() && !no_input;
@@ -149,9 +303,7 @@ pub fn prompt_for_fields(
     for field in fields {
         // Check if value was provided via CLI
         if let Some(cli_value) = cli_values.get(&field.name) {
-            let value =
-                parse_field_value(&field.field_type, cli_value, &field.values, field.multiple)?;
-            data.insert(field.name.clone(), value);
+            data.insert(field.name.clone(), field.check_value(cli_value)?);
             continue;
         }
 
@@ -182,7 +334,7 @@ pub fn prompt_for_fields(
 
         if needs_prompt {
             // Text fields can use editor even without a TTY
-            let can_use_editor = field.field_type == "text" && editor_override.is_some();
+            let can_use_editor = field.field_type == "text" && editor.command.is_some();
 
             if !interactive && !can_use_editor {
                 if field.required {
@@ -196,10 +348,15 @@ pub fn prompt_for_fields(
             }
 
             let value = prompt_single_field(
+                ui_ctx,
                 field,
                 &prompt_text,
                 default_value,
-                editor_override,
+                template_defaults
+                    .placeholders
+                    .get(&field.name)
+                    .map(String::as_str),
+                editor,
                 interactive,
             )?;
             if let Some(v) = value {
@@ -224,14 +381,17 @@ pub fn prompt_for_fields(
 
 /// Prompt for a single field value
 fn prompt_single_field(
+    ui_ctx: &UiContext,
     field: &FieldDef,
     prompt_text: &str,
     default_value: Option<&Value>,
-    editor_override: Option<&str>,
+    placeholder: Option<&str>,
+    editor: EditorSession,
     interactive: bool,
 ) -> anyhow::Result<Option<Value>> {
+    let help = field.help_text();
     match field.field_type.as_str() {
-        "string" | "date" | "datetime" | "number" | "integer" => {
+        "string" | "date" | "datetime" | "number" | "integer" | "bytes" => {
             if !interactive {
                 // Non-interactive mode - use default or fail
                 if let Some(default) = default_value {
@@ -249,43 +409,58 @@ fn prompt_single_field(
                 _ => None,
             });
 
-            let mut input = Input::<String>::new().with_prompt(prompt_text);
-
-            if let Some(ref default) = default_str {
-                input = input.default(default.clone());
-            }
-
-            if !field.required {
-                input = input.allow_empty(true);
-            }
-
-            let result = input.interact_text()?;
+            // Invalid input re-prompts with the error instead of aborting
+            let result = prompt_validated(
+                ui_ctx,
+                prompt_text,
+                help.as_deref(),
+                default_str.as_deref(),
+                !field.required,
+                |input| {
+                    field
+                        .check_value(input)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                },
+            )?;
 
             if result.is_empty() && !field.required {
                 return Ok(None);
             }
 
-            parse_field_value(&field.field_type, &result, &field.values, field.multiple).map(Some)
+            field.check_value(&result).map(Some)
         }
 
         "text" => {
             // Text fields need editor - check if we can use one
-            if !interactive && editor_override.is_none() {
+            if !interactive && editor.command.is_none() {
                 // Non-interactive mode without editor - use default or fail
                 if let Some(default) = default_value {
                     return Ok(Some(default.clone()));
                 }
                 if field.required {
                     return Err(anyhow::anyhow!(
-                        "Required text field '{}' needs an editor (set $EDITOR or use --editor)",
+                        "Required text field '{}' needs an editor (set $VISUAL/$EDITOR or use --editor)",
                         field.name
                     ));
                 }
                 return Ok(None);
             }
             // Use editor for multiline text (works even without TTY if editor is set)
-            let initial = default_value.and_then(|v| v.as_str());
-            let body = super::read_entry_body(false, None, editor_override, initial)?;
+            if interactive {
+                print_field_help(ui_ctx, help.as_deref());
+            }
+            let placeholder = placeholder.map(prompt_placeholder);
+            let initial = default_value
+                .and_then(|v| v.as_str())
+                .or(placeholder.as_deref());
+            let mut body = super::read_entry_body(false, None, editor, initial)?;
+            if placeholder.is_some() {
+                body = strip_prompt_placeholder(&body);
+                if body.is_empty() {
+                    return Err(anyhow::anyhow!("Entry body is empty"));
+                }
+            }
             Ok(Some(Value::String(body)))
         }
 
@@ -294,6 +469,8 @@ fn prompt_single_field(
             let options = vec!["Yes", "No"];
             let default_idx = if default_bool { 0 } else { 1 };
 
+            print_field_help(ui_ctx, help.as_deref());
+
             let selection = Select::new()
                 .with_prompt(prompt_text)
                 .items(&options)
@@ -305,6 +482,7 @@ fn prompt_single_field(
 
         "enum" => {
             if let Some(ref values) = field.values {
+                print_field_help(ui_ctx, help.as_deref());
                 if field.multiple {
                     // Multi-select enum
                     let defaults: Vec<bool> = if let Some(Value::Array(arr)) = default_value {
@@ -362,6 +540,7 @@ fn prompt_single_field(
                 })
                 .unwrap_or_default();
 
+            print_field_help(ui_ctx, help.as_deref());
             let mut input = Input::<String>::new()
                 .with_prompt(format!("{} (comma-separated)", prompt_text))
                 .allow_empty(true);
@@ -386,6 +565,7 @@ fn prompt_single_field(
 
         _ => {
             // Unknown type - treat as string
+            print_field_help(ui_ctx, help.as_deref());
             let default_str = default_value
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string());
@@ -495,6 +675,22 @@ fn parse_field_value(
             Ok(Value::Array(tags))
         }
 
+        // `@path` reads a file; anything else must already be base64
+        "bytes" => match value.strip_prefix('@') {
+            Some(path) => {
+                let data = std::fs::read(path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path, e))?;
+                Ok(Value::String(BASE64.encode(data)))
+            }
+            None => {
+                let value = value.trim();
+                BASE64.decode(value).map_err(|_| {
+                    anyhow::anyhow!("Invalid base64: use base64 text or @path to read a file")
+                })?;
+                Ok(Value::String(value.to_string()))
+            }
+        },
+
         _ => Ok(Value::String(value.to_string())),
     }
 }
@@ -523,3 +719,102 @@ pub fn parse_cli_fields(fields: &[String]) -> anyhow::Result<HashMap<String, Str
     }
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mood_field() -> FieldDef {
+        let schema = serde_json::json!({
+            "fields": [{
+                "name": "mood",
+                "type": "integer",
+                "description": "How did the day feel?",
+                "min": 1,
+                "max": 10
+            }]
+        });
+        FieldDef::from_schema(&schema).remove(0)
+    }
+
+    #[test]
+    fn test_bytes_field_reads_files_and_checks_base64() {
+        let path = std::env::temp_dir().join(format!("ledger_bytes_{}.bin", std::process::id()));
+        std::fs::write(&path, [0u8, 1, 2, 255]).unwrap();
+        let value = parse_field_value("bytes", &format!("@{}", path.display()), &None, false);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(value.unwrap(), serde_json::json!("AAEC/w=="));
+        assert_eq!(
+            parse_field_value("bytes", "AAEC/w==", &None, false).unwrap(),
+            serde_json::json!("AAEC/w==")
+        );
+        assert!(parse_field_value("bytes", "not base64!", &None, false).is_err());
+    }
+
+    #[test]
+    fn test_check_value_enforces_type_and_range() {
+        let field = mood_field();
+        assert_eq!(field.check_value("7").unwrap(), serde_json::json!(7));
+        assert!(field.check_value("seven").is_err());
+        let err = field.check_value("11").unwrap_err().to_string();
+        assert_eq!(err, "Field 'mood' must be between 1 and 10 (got 11)");
+    }
+
+    #[test]
+    fn test_help_text_combines_description_and_range() {
+        let mut field = mood_field();
+        assert_eq!(
+            field.help_text().as_deref(),
+            Some("How did the day feel? (between 1 and 10)")
+        );
+        field.description = None;
+        field.max = None;
+        assert_eq!(field.help_text().as_deref(), Some("Must be at least 1"));
+    }
+
+    #[test]
+    fn test_help_text_shows_unit_and_example() {
+        let schema = serde_json::json!({
+            "fields": [{
+                "name": "distance",
+                "type": "number",
+                "description": "How far you ran",
+                "unit": "km",
+                "example": 5.2
+            }]
+        });
+        let mut field = FieldDef::from_schema(&schema).remove(0);
+        assert_eq!(
+            field.help_text().as_deref(),
+            Some("How far you ran (in km, e.g. 5.2)")
+        );
+        field.description = None;
+        assert_eq!(field.help_text().as_deref(), Some("In km, e.g. 5.2"));
+        field.min = Some(0.0);
+        assert_eq!(
+            field.help_text().as_deref(),
+            Some("Must be at least 0 (in km, e.g. 5.2)")
+        );
+    }
+
+    #[test]
+    fn test_template_defaults_for_weekday() {
+        let template = serde_json::json!({
+            "defaults": { "body": "Today", "mood": 5 },
+            "by_weekday": {
+                "fri": { "body": "Today\n\n## Week retro" },
+                "weekends": { "mood": 7 }
+            }
+        });
+        let friday = TemplateDefaults::for_weekday(&template, Weekday::Fri);
+        assert_eq!(friday.defaults["body"], "Today\n\n## Week retro");
+        assert_eq!(friday.defaults["mood"], 5);
+        let sunday = TemplateDefaults::for_weekday(&template, Weekday::Sun);
+        assert_eq!(sunday.defaults["body"], "Today");
+        assert_eq!(sunday.defaults["mood"], 7);
+
+        assert!(parse_by_weekday(&serde_json::json!({ "mon,wed": {} })).is_ok());
+        assert!(parse_by_weekday(&serde_json::json!({ "someday": {} })).is_err());
+        assert!(parse_by_weekday(&serde_json::json!({ "fri": "retro" })).is_err());
+    }
+}
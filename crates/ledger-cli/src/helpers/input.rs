@@ -1,12 +1,15 @@
 //! Input handling helpers for passphrase and entry body reading.
 
 use std::io::{self, IsTerminal, Read};
+use std::path::Path;
 use std::process::Command;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 use dialoguer::Password;
 use ledger_core::crypto::validate_passphrase;
 
+use super::drafts::{create_draft, shred_file, DraftRecovery};
+use super::editor::{missing_wait_flag, resolve_editor, split_editor_command};
+
 /// Prompt for passphrase, or read from LEDGER_PASSPHRASE env var.
 pub fn prompt_passphrase(interactive: bool) -> anyhow::Result<String> {
     if let Ok(value) = std::env::var("LEDGER_PASSPHRASE") {
@@ -48,11 +51,29 @@ pub fn prompt_init_passphrase() -> anyhow::Result<String> {
     }
 }
 
-/// Read entry body from --body flag, stdin, or $EDITOR.
+/// The editor for an entry body, and where `add` keeps what was typed if
+/// the editor crashes.
+#[derive(Clone, Copy, Default)]
+pub struct EditorSession<'a> {
+    pub command: Option<&'a str>,
+    pub recovery: Option<&'a DraftRecovery>,
+}
+
+impl<'a> From<Option<&'a str>> for EditorSession<'a> {
+    fn from(command: Option<&'a str>) -> Self {
+        Self {
+            command,
+            recovery: None,
+        }
+    }
+}
+
+/// Read entry body from --body flag, stdin, or the editor (`--editor`,
+/// `[ui] editor`, `$VISUAL` or `$EDITOR`).
 pub fn read_entry_body(
     no_input: bool,
     body: Option<String>,
-    editor_override: Option<&str>,
+    editor: EditorSession,
     initial_body: Option<&str>,
 ) -> anyhow::Result<String> {
     if let Some(value) = body {
@@ -72,8 +93,8 @@ pub fn read_entry_body(
             if no_input {
                 return Err(anyhow::anyhow!("No input provided on stdin"));
             }
-            if editor_override.is_some() {
-                return read_body_from_editor(editor_override, initial_body);
+            if editor.command.is_some() {
+                return read_body_from_editor(editor, initial_body);
             }
             return Err(anyhow::anyhow!("No input provided on stdin"));
         }
@@ -84,44 +105,52 @@ pub fn read_entry_body(
         return Err(anyhow::anyhow!("--no-input requires content from stdin"));
     }
 
-    read_body_from_editor(editor_override, initial_body)
+    read_body_from_editor(editor, initial_body)
 }
 
-/// Open $EDITOR to compose entry body.
+/// Open the editor to compose entry body.
 fn read_body_from_editor(
-    editor_override: Option<&str>,
+    session: EditorSession,
     initial_body: Option<&str>,
 ) -> anyhow::Result<String> {
-    let editor = editor_override
-        .map(|value| value.to_string())
-        .or_else(|| std::env::var("EDITOR").ok())
-        .ok_or_else(|| {
-            anyhow::anyhow!("$EDITOR is not set; use --body or pipe content via stdin")
-        })?;
-
-    let nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_err(|e| anyhow::anyhow!("System time error: {}", e))?
-        .as_nanos();
-    let filename = format!("ledger_entry_{}_{}.md", std::process::id(), nanos);
-    let path = std::env::temp_dir().join(filename);
-
-    let initial = initial_body.unwrap_or("");
-    std::fs::write(&path, initial)
-        .map_err(|e| anyhow::anyhow!("Failed to create temp file: {}", e))?;
-
-    let status = Command::new(editor)
+    let editor = resolve_editor(session.command).ok_or_else(|| {
+        anyhow::anyhow!(
+            "No editor set ($VISUAL or $EDITOR); use --editor, --body or pipe content via stdin"
+        )
+    })?;
+    let command = split_editor_command(&editor)?;
+
+    let initial_body = initial_body.unwrap_or("");
+    let path = create_draft(initial_body)?;
+    let lock = match session.recovery {
+        Some(recovery) => Some(recovery.lock(&path)?),
+        None => None,
+    };
+    let status = Command::new(&command[0])
+        .args(&command[1..])
         .arg(&path)
-        .status()
-        .map_err(|e| anyhow::anyhow!("Failed to launch editor: {}", e))?;
-    if !status.success() {
-        let _ = std::fs::remove_file(&path);
-        return Err(anyhow::anyhow!("Editor exited with failure"));
-    }
+        .status();
+    let contents = match status {
+        Ok(status) if status.success() => std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read temp file: {}", e)),
+        Ok(_) => Err(editor_failed(session.recovery, &path, initial_body)),
+        Err(e) => Err(anyhow::anyhow!("Failed to launch editor: {}", e)),
+    };
+    let _ = shred_file(&path);
+    drop(lock);
+    let contents = contents?;
 
-    let contents = std::fs::read_to_string(&path)
-        .map_err(|e| anyhow::anyhow!("Failed to read temp file: {}", e))?;
-    let _ = std::fs::remove_file(&path);
+    // A GUI editor without its wait flag returns before anything is saved
+    if let Some(flag) = missing_wait_flag(&command) {
+        if contents.trim_end() == initial_body.trim_end() {
+            return Err(anyhow::anyhow!(
+                "Editor `{}` returned before the entry was saved\nHint: Use `{} {}` so ledger waits for the file to be closed.",
+                editor,
+                editor,
+                flag
+            ));
+        }
+    }
 
     let trimmed = contents.trim_end().to_string();
     if trimmed.is_empty() {
@@ -130,3 +159,25 @@ fn read_body_from_editor(
 
     Ok(trimmed)
 }
+
+/// The error for an editor that exited with failure, after saving what was
+/// typed when there is somewhere to keep it.
+fn editor_failed(recovery: Option<&DraftRecovery>, draft: &Path, initial: &str) -> anyhow::Error {
+    let message = "Editor exited with failure";
+    let Some(recovery) = recovery else {
+        return anyhow::anyhow!(message);
+    };
+    let Ok(text) = std::fs::read_to_string(draft).map(zeroize::Zeroizing::new) else {
+        return anyhow::anyhow!(message);
+    };
+    if text.trim().is_empty() || text.trim_end() == initial.trim_end() {
+        return anyhow::anyhow!(message);
+    }
+    match recovery.save(&text) {
+        Ok(_) => anyhow::anyhow!(
+            "{}\nHint: The draft was saved encrypted; the next `ledger add` offers to resume it.",
+            message
+        ),
+        Err(e) => anyhow::anyhow!("{}; the draft could not be saved: {}", message, e),
+    }
+}
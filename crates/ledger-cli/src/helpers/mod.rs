@@ -2,14 +2,59 @@
 //!
 //! This module provides utilities for:
 //! - Passphrase prompting and entry body reading (`input`)
+//! - Private, shredded editor drafts and encrypted crash recovery (`drafts`)
+//! - Editor resolution: `$VISUAL`, arguments and GUI wait flags (`editor`)
 //! - Datetime, duration, and format parsing (`parsing`)
 //! - Field prompting and validation (`fields`)
+//! - Built-in entry type schemas (`entry_types`)
+//! - Entry type pack manifests and built-in packs (`packs`)
+//! - Bookmark URL handling (`bookmark`)
+//! - File capture and transcription (`capture`)
+//! - Photo EXIF extraction (`photo`)
+//! - Metric fields and rolling statistics (`stats`)
+//! - todo.txt conversion for task entries (`todotxt`)
+//! - Org-mode export and import (`org`)
+//! - Journaling prompts, built-in and from `prompts.txt` (`prompts`)
+//! - Spell checking for `add --lint` (`spellcheck`, feature-gated)
 
+mod bookmark;
+mod capture;
+mod clipboard;
+mod drafts;
+mod editor;
+mod entry_types;
 mod fields;
 mod input;
+mod org;
+mod packs;
 mod parsing;
+mod photo;
+mod prompts;
+#[cfg(feature = "spellcheck")]
+mod spellcheck;
+mod stats;
+mod todotxt;
 
 // Re-export public API
-pub use fields::{parse_cli_fields, prompt_for_fields, FieldDef, TemplateDefaults};
-pub use input::{prompt_init_passphrase, prompt_passphrase, read_entry_body};
-pub use parsing::{ensure_journal_type_name, parse_datetime, parse_duration, require_entry_type};
+pub use bookmark::{fetch_page_title, url_domain, validate_url};
+pub use capture::{read_capture_file, transcribe_audio};
+pub use clipboard::copy_to_clipboard;
+pub use drafts::{create_private_dir, wipe_editor_drafts, DraftRecovery, RecoveredDraft};
+pub use editor::{missing_wait_flag, split_editor_command};
+pub use entry_types::{builtin_schema, ensure_builtin_entry_type, BUILTIN_ENTRY_TYPES};
+pub use fields::{
+    parse_by_weekday, parse_cli_fields, prompt_for_fields, FieldDef, TemplateDefaults,
+};
+pub use input::{prompt_init_passphrase, prompt_passphrase, read_entry_body, EditorSession};
+pub use org::{entries_to_org, parse_org, OrgEntry};
+pub use packs::{builtin_pack, PackManifest, BUILTIN_PACKS};
+pub use parsing::{
+    ensure_journal_type_name, parse_date_range, parse_datetime, parse_duration,
+    parse_search_tokenizer, require_entry_type, resolve_entry_id,
+};
+pub use photo::read_photo_metadata;
+pub use prompts::{append_prompt_line, remove_disable_lines, user_prompts_path, PromptSet};
+#[cfg(feature = "spellcheck")]
+pub use spellcheck::{add_to_user_dictionary, replace_word, Dictionary};
+pub use stats::{jump_ratio, metric_day, numeric_field, rolling_baselines, Summary, MIN_BASELINE};
+pub use todotxt::{parse_todotxt_line, task_to_todotxt};
@@ -0,0 +1,308 @@
+//! Conversion between entries and Org-mode documents.
+//!
+//! Each entry is a top-level headline with a properties drawer holding its
+//! id, type, tags and creation time; fields other than the body are kept as
+//! JSON in a `FIELDS` property. The body follows the drawer as the
+//! headline's text, with lines that Org would read as structure escaped by
+//! a leading comma, as Org does inside source blocks.
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use chrono::{DateTime, SecondsFormat, Utc};
+use ledger_core::storage::Entry;
+use uuid::Uuid;
+
+/// Longest headline title taken from the first line of a body.
+const TITLE_MAX: usize = 60;
+
+/// A headline parsed from an Org document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrgEntry {
+    /// Line of the headline, for error messages.
+    pub line: usize,
+    pub id: Option<Uuid>,
+    pub entry_type: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub fields: serde_json::Map<String, serde_json::Value>,
+    pub body: String,
+}
+
+impl OrgEntry {
+    /// Entry data: the fields plus the body.
+    ///
+    /// An empty body is left out unless `existing` data had one, so
+    /// clearing a body in Org clears it in the ledger too.
+    pub fn data(&self, existing: Option<&serde_json::Value>) -> serde_json::Value {
+        let mut data = self.fields.clone();
+        let had_body = existing.is_some_and(|data| data.get("body").is_some());
+        if !self.body.is_empty() || had_body {
+            data.insert(
+                "body".to_string(),
+                serde_json::Value::String(self.body.clone()),
+            );
+        }
+        serde_json::Value::Object(data)
+    }
+
+    /// Whether the headline differs from the entry in data or tags.
+    pub fn differs(&self, entry: &Entry) -> bool {
+        let mut current = entry.data.clone();
+        if let Some(body) = current.get("body").and_then(|v| v.as_str()) {
+            current["body"] = serde_json::Value::String(body.trim_end().to_string());
+        }
+        let mut org_tags = self.tags.clone();
+        let mut entry_tags = entry.tags.clone();
+        org_tags.sort();
+        entry_tags.sort();
+        self.data(Some(&entry.data)) != current || org_tags != entry_tags
+    }
+}
+
+/// Render entries as one Org document, a headline per entry.
+pub fn entries_to_org(entries: &[&Entry], name_map: &HashMap<Uuid, String>) -> String {
+    let mut out = String::from("#+TITLE: Ledger export\n");
+    for entry in entries {
+        let type_name = name_map
+            .get(&entry.entry_type_id)
+            .map(String::as_str)
+            .unwrap_or("unknown");
+        let body = entry.data.get("body").and_then(|v| v.as_str());
+        let title = body
+            .and_then(|body| body.lines().map(str::trim).find(|line| !line.is_empty()))
+            .map(|line| truncate_title(line.trim_start_matches('*').trim()))
+            .unwrap_or_else(|| type_name.to_string());
+
+        out.push_str(&format!(
+            "\n* [{}] {}\n",
+            entry.created_at.format("%Y-%m-%d %a %H:%M"),
+            title
+        ));
+        out.push_str(":PROPERTIES:\n");
+        out.push_str(&format!(":ID: {}\n", entry.id));
+        out.push_str(&format!(":TYPE: {}\n", type_name));
+        if !entry.tags.is_empty() {
+            out.push_str(&format!(":TAGS: {}\n", entry.tags.join(" ")));
+        }
+        out.push_str(&format!(
+            ":CREATED: {}\n",
+            entry.created_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ));
+        if let Some(fields) = entry.data.as_object() {
+            let extra: serde_json::Map<_, _> = fields
+                .iter()
+                .filter(|(key, _)| key.as_str() != "body")
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            if !extra.is_empty() {
+                out.push_str(&format!(":FIELDS: {}\n", serde_json::Value::Object(extra)));
+            }
+        }
+        out.push_str(":END:\n");
+        if let Some(body) = body {
+            for line in body.trim_end().lines() {
+                out.push_str(&escape_line(line));
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Parse the top-level headlines of an Org document.
+///
+/// Text before the first headline (`#+TITLE` and the like) is ignored, as
+/// are headlines with neither properties nor text.
+pub fn parse_org(raw: &str) -> anyhow::Result<Vec<OrgEntry>> {
+    let raw = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    let mut sections: Vec<(usize, Vec<&str>)> = Vec::new();
+    for (index, line) in raw.lines().enumerate() {
+        if line.starts_with("* ") || line == "*" {
+            sections.push((index + 1, Vec::new()));
+        } else if let Some((_, lines)) = sections.last_mut() {
+            lines.push(line);
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (line, lines) in sections {
+        let entry = parse_section(line, &lines)?;
+        if entry.id.is_none() && entry.entry_type.is_none() && entry.body.is_empty() {
+            continue;
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn parse_section(line: usize, lines: &[&str]) -> anyhow::Result<OrgEntry> {
+    let mut entry = OrgEntry {
+        line,
+        id: None,
+        entry_type: None,
+        tags: Vec::new(),
+        created_at: None,
+        fields: serde_json::Map::new(),
+        body: String::new(),
+    };
+
+    let mut rest = lines;
+    let drawer_start = lines.iter().position(|l| !l.trim().is_empty());
+    if let Some(start) = drawer_start.filter(|&i| lines[i].trim() == ":PROPERTIES:") {
+        let end = lines[start..]
+            .iter()
+            .position(|l| l.trim() == ":END:")
+            .map(|offset| start + offset)
+            .ok_or_else(|| anyhow::anyhow!("Unclosed properties drawer on line {}", line))?;
+        for property in &lines[start + 1..end] {
+            let Some((key, value)) = property
+                .trim()
+                .strip_prefix(':')
+                .and_then(|p| p.split_once(':'))
+            else {
+                continue;
+            };
+            let value = value.trim();
+            match key.to_ascii_uppercase().as_str() {
+                "ID" => {
+                    entry.id = Some(
+                        Uuid::parse_str(value)
+                            .with_context(|| format!("Invalid ID on line {}", line))?,
+                    );
+                }
+                "TYPE" if !value.is_empty() => entry.entry_type = Some(value.to_string()),
+                "TAGS" => {
+                    entry.tags = value
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                }
+                "CREATED" => {
+                    entry.created_at = Some(
+                        DateTime::parse_from_rfc3339(value)
+                            .with_context(|| format!("Invalid CREATED on line {}", line))?
+                            .with_timezone(&Utc),
+                    );
+                }
+                "FIELDS" => match serde_json::from_str(value) {
+                    Ok(serde_json::Value::Object(fields)) => entry.fields = fields,
+                    _ => {
+                        return Err(anyhow::anyhow!(
+                            "Invalid FIELDS on line {}: expected a JSON object",
+                            line
+                        ));
+                    }
+                },
+                _ => {}
+            }
+        }
+        rest = &lines[end + 1..];
+    }
+
+    let body: Vec<&str> = rest.iter().map(|l| unescape_line(l)).collect();
+    entry.body = body.join("\n").trim_matches('\n').trim_end().to_string();
+    Ok(entry)
+}
+
+/// Prefix a comma to lines Org would read as a headline or keyword.
+fn escape_line(line: &str) -> String {
+    let unescaped = line.trim_start_matches(',');
+    if unescaped.starts_with('*') || unescaped.starts_with("#+") {
+        format!(",{}", line)
+    } else {
+        line.to_string()
+    }
+}
+
+fn unescape_line(line: &str) -> &str {
+    match line.strip_prefix(',') {
+        Some(rest)
+            if rest.trim_start_matches(',').starts_with('*')
+                || rest.trim_start_matches(',').starts_with("#+") =>
+        {
+            rest
+        }
+        _ => line,
+    }
+}
+
+fn truncate_title(line: &str) -> String {
+    if line.chars().count() <= TITLE_MAX {
+        return line.to_string();
+    }
+    let mut title: String = line.chars().take(TITLE_MAX - 1).collect();
+    title.push('\u{2026}');
+    title
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_entry() -> Entry {
+        Entry {
+            id: Uuid::new_v4(),
+            entry_type_id: Uuid::new_v4(),
+            schema_version: 1,
+            data: serde_json::json!({
+                "body": "Walked home\n* not a headline\n,* already escaped\n#+ nor a keyword",
+                "mood": 4,
+            }),
+            tags: vec!["walk".to_string(), "home".to_string()],
+            created_at: DateTime::parse_from_rfc3339("2024-03-05T10:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            seq: 1,
+            device_id: Uuid::new_v4(),
+            supersedes: None,
+        }
+    }
+
+    #[test]
+    fn test_render_then_parse_round_trips() {
+        let entry = sample_entry();
+        let name_map = HashMap::from([(entry.entry_type_id, "journal".to_string())]);
+        let org = entries_to_org(&[&entry], &name_map);
+        assert!(org.contains("* [2024-03-05 Tue 10:00] Walked home\n"));
+        assert!(org.contains("\n,* not a headline\n,,* already escaped\n,#+ nor"));
+
+        let parsed = parse_org(&org).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].id, Some(entry.id));
+        assert_eq!(parsed[0].entry_type.as_deref(), Some("journal"));
+        assert_eq!(parsed[0].created_at, Some(entry.created_at));
+        assert_eq!(parsed[0].data(None), entry.data);
+        assert!(!parsed[0].differs(&entry));
+    }
+
+    #[test]
+    fn test_parse_detects_edits() {
+        let entry = sample_entry();
+        let org = entries_to_org(&[&entry], &HashMap::new())
+            .replace("Walked home", "Walked home slowly")
+            .replace(":TAGS: walk home", ":TAGS: walk");
+        let parsed = parse_org(&org).unwrap();
+        assert!(parsed[0].differs(&entry));
+        assert_eq!(parsed[0].tags, vec!["walk".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_new_headline_without_drawer() {
+        let parsed = parse_org("#+TITLE: Notes\n\n* Idea\nWrite it down.\n\n* Empty\n").unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].id, None);
+        assert_eq!(parsed[0].body, "Write it down.");
+        assert_eq!(parsed[0].line, 3);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_properties() {
+        let err = parse_org("* x\n:PROPERTIES:\n:ID: nope\n:END:\n").unwrap_err();
+        assert!(err.to_string().contains("Invalid ID on line 1"));
+        let err = parse_org("* x\n:PROPERTIES:\n:FIELDS: [1]\n:END:\n").unwrap_err();
+        assert!(err.to_string().contains("Invalid FIELDS"));
+        assert!(parse_org("* x\n:PROPERTIES:\n:ID: 1\n").is_err());
+    }
+}
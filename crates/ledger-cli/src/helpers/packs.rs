@@ -0,0 +1,238 @@
+//! Entry type pack manifests, and the packs shipped with the CLI.
+//!
+//! A manifest is JSON:
+//!
+//! ```json
+//! {
+//!   "name": "fitness",
+//!   "version": "1.0",
+//!   "description": "Workouts and body measurements",
+//!   "default_tags": ["fitness"],
+//!   "entry_types": [{"name": "workout", "schema": {"fields": [...]}}],
+//!   "templates": [{"name": "run", "entry_type": "workout", "defaults": {...}}]
+//! }
+//! ```
+//!
+//! Pack-level `default_tags` are added to every template of the pack.
+
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+/// Names of the packs the CLI ships.
+pub const BUILTIN_PACKS: &[&str] = &["fitness", "gardening"];
+
+/// A parsed pack manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub default_tags: Vec<String>,
+    #[serde(default)]
+    pub entry_types: Vec<PackEntryType>,
+    #[serde(default)]
+    pub templates: Vec<PackTemplate>,
+}
+
+/// An entry type defined by a pack.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackEntryType {
+    pub name: String,
+    pub schema: serde_json::Value,
+}
+
+/// A template defined by a pack.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackTemplate {
+    pub name: String,
+    pub entry_type: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub defaults: Option<serde_json::Map<String, serde_json::Value>>,
+    #[serde(default)]
+    pub default_tags: Vec<String>,
+    /// Make this the entry type's default template
+    #[serde(default)]
+    pub default: bool,
+}
+
+impl PackManifest {
+    /// Parse and sanity-check a manifest.
+    pub fn parse(json: &str) -> anyhow::Result<Self> {
+        let manifest: PackManifest = serde_json::from_str(json)
+            .map_err(|e| anyhow::anyhow!("Invalid pack manifest: {}", e))?;
+        if manifest.version.trim().is_empty() {
+            return Err(anyhow::anyhow!("Invalid pack manifest: version is empty"));
+        }
+        let mut seen = HashSet::new();
+        for entry_type in &manifest.entry_types {
+            if !seen.insert(entry_type.name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Invalid pack manifest: entry type '{}' is defined twice",
+                    entry_type.name
+                ));
+            }
+        }
+        let mut seen = HashSet::new();
+        for template in &manifest.templates {
+            if !seen.insert(template.name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Invalid pack manifest: template '{}' is defined twice",
+                    template.name
+                ));
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Template JSON for one of the manifest's templates, with the pack's
+    /// default tags ahead of the template's own.
+    pub fn template_json(&self, template: &PackTemplate) -> serde_json::Value {
+        let mut json = serde_json::json!({});
+        if let Some(ref defaults) = template.defaults {
+            json["defaults"] = serde_json::Value::Object(defaults.clone());
+        }
+        let mut tags: Vec<&String> = Vec::new();
+        for tag in self.default_tags.iter().chain(&template.default_tags) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        if !tags.is_empty() {
+            json["default_tags"] = serde_json::json!(tags);
+        }
+        json
+    }
+}
+
+/// Manifest of a built-in pack.
+pub fn builtin_pack(name: &str) -> Option<PackManifest> {
+    let manifest = match name {
+        "fitness" => serde_json::json!({
+            "name": "fitness",
+            "version": "1.0",
+            "description": "Workouts and body measurements",
+            "default_tags": ["fitness"],
+            "entry_types": [
+                {"name": "workout", "schema": {
+                    "icon": "\u{1F3CB}",
+                    "color": "green",
+                    "description": "A training session",
+                    "summary_template": "{activity} {duration}",
+                    "fields": [
+                        {"name": "activity", "type": "string", "required": true, "prompt": "Activity", "example": "run", "order": 1},
+                        {"name": "duration", "type": "integer", "required": true, "prompt": "Duration (minutes)", "unit": "min", "order": 2},
+                        {"name": "distance", "type": "number", "required": false, "prompt": "Distance (km)", "unit": "km", "decimals": 2, "order": 3},
+                        {"name": "effort", "type": "integer", "required": false, "prompt": "Effort (1-10)", "description": "How hard it felt, 1 to 10", "order": 4},
+                        {"name": "notes", "type": "text", "required": false, "prompt": "Notes", "order": 5}
+                    ]
+                }},
+                {"name": "measurement", "schema": {
+                    "icon": "\u{1F4CF}",
+                    "color": "cyan",
+                    "description": "Body weight and related measurements",
+                    "fields": [
+                        {"name": "weight", "type": "number", "required": true, "prompt": "Weight (kg)", "unit": "kg", "decimals": 1, "order": 1},
+                        {"name": "body_fat", "type": "number", "required": false, "prompt": "Body fat (%)", "unit": "%", "decimals": 1, "order": 2},
+                        {"name": "notes", "type": "text", "required": false, "prompt": "Notes", "order": 3}
+                    ]
+                }}
+            ],
+            "templates": [
+                {"name": "run", "entry_type": "workout", "description": "An easy run",
+                 "defaults": {"activity": "run"}, "default_tags": ["run"], "default": true},
+                {"name": "strength", "entry_type": "workout", "description": "A strength session",
+                 "defaults": {"activity": "strength"}, "default_tags": ["strength"]}
+            ]
+        }),
+        "gardening" => serde_json::json!({
+            "name": "gardening",
+            "version": "1.0",
+            "description": "Sowing, planting and harvests",
+            "default_tags": ["garden"],
+            "entry_types": [
+                {"name": "planting", "schema": {
+                    "icon": "\u{1F331}",
+                    "color": "green",
+                    "description": "Seeds sown or plants set out",
+                    "summary_template": "{plant} in {bed}",
+                    "fields": [
+                        {"name": "plant", "type": "string", "required": true, "prompt": "Plant", "example": "tomato", "order": 1},
+                        {"name": "variety", "type": "string", "required": false, "prompt": "Variety", "order": 2},
+                        {"name": "bed", "type": "string", "required": true, "prompt": "Bed or pot", "order": 3},
+                        {"name": "notes", "type": "text", "required": false, "prompt": "Notes", "order": 4}
+                    ]
+                }},
+                {"name": "harvest", "schema": {
+                    "icon": "\u{1F955}",
+                    "color": "yellow",
+                    "description": "What came out of the garden",
+                    "summary_template": "{plant} {weight}",
+                    "fields": [
+                        {"name": "plant", "type": "string", "required": true, "prompt": "Plant", "order": 1},
+                        {"name": "weight", "type": "number", "required": false, "prompt": "Weight (g)", "unit": "g", "decimals": 0, "order": 2},
+                        {"name": "notes", "type": "text", "required": false, "prompt": "Notes", "order": 3}
+                    ]
+                }}
+            ],
+            "templates": [
+                {"name": "sowing", "entry_type": "planting", "description": "Seeds sown indoors",
+                 "defaults": {"bed": "seed tray"}, "default_tags": ["sowing"]}
+            ]
+        }),
+        _ => return None,
+    };
+    serde_json::from_value(manifest).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_packs_parse() {
+        for name in BUILTIN_PACKS {
+            let pack = builtin_pack(name).unwrap_or_else(|| panic!("missing pack {}", name));
+            assert_eq!(pack.name, *name);
+            for template in &pack.templates {
+                assert!(
+                    pack.entry_types
+                        .iter()
+                        .any(|entry_type| entry_type.name == template.entry_type),
+                    "template {} refers to a type outside the pack",
+                    template.name
+                );
+            }
+        }
+        assert!(builtin_pack("cooking").is_none());
+    }
+
+    #[test]
+    fn test_template_json_merges_pack_tags() {
+        let pack = builtin_pack("fitness").unwrap();
+        let run = &pack.templates[0];
+        assert_eq!(
+            pack.template_json(run),
+            serde_json::json!({
+                "defaults": {"activity": "run"},
+                "default_tags": ["fitness", "run"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_duplicates_and_unknown_keys() {
+        let duplicate = r#"{"name": "x", "version": "1", "entry_types": [
+            {"name": "a", "schema": {}}, {"name": "a", "schema": {}}]}"#;
+        assert!(PackManifest::parse(duplicate).is_err());
+        assert!(PackManifest::parse(r#"{"name": "x", "version": "1", "types": []}"#).is_err());
+        assert!(PackManifest::parse(r#"{"name": "x", "version": " "}"#).is_err());
+    }
+}
@@ -1,8 +1,11 @@
 //! Parsing helpers for datetime, duration, and output format.
 
 use chrono::{DateTime, Duration, NaiveDate, Utc};
+use ledger_core::storage::{DayNumber, SearchTokenizer};
 use ledger_core::StorageEngine;
 
+use super::entry_types::{is_builtin_entry_type, BUILTIN_ENTRY_TYPES};
+
 /// Parse a datetime string (ISO-8601 or YYYY-MM-DD).
 pub fn parse_datetime(value: &str) -> anyhow::Result<DateTime<Utc>> {
     if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
@@ -22,6 +25,41 @@ pub fn parse_datetime(value: &str) -> anyhow::Result<DateTime<Utc>> {
     ))
 }
 
+/// Parse an inclusive day range (`YYYY-MM-DD..YYYY-MM-DD`, or one day).
+pub fn parse_date_range(value: &str) -> anyhow::Result<(NaiveDate, NaiveDate)> {
+    let parse = |part: &str| {
+        NaiveDate::parse_from_str(part.trim(), "%Y-%m-%d").map_err(|_| {
+            anyhow::anyhow!(
+                "Invalid date range: {} (expected YYYY-MM-DD..YYYY-MM-DD)",
+                value
+            )
+        })
+    };
+    let (start, end) = match value.split_once("..") {
+        Some((start, end)) => (parse(start)?, parse(end)?),
+        None => (parse(value)?, parse(value)?),
+    };
+    if end < start {
+        return Err(anyhow::anyhow!(
+            "Invalid date range: {} (end is before start)",
+            value
+        ));
+    }
+    Ok((start, end))
+}
+
+/// Parse a search index tokenizer name (`unicode61`, `porter`, `trigram`).
+pub fn parse_search_tokenizer(value: &str) -> anyhow::Result<SearchTokenizer> {
+    SearchTokenizer::parse(value).ok_or_else(|| {
+        let names: Vec<&str> = SearchTokenizer::ALL.iter().map(|t| t.as_str()).collect();
+        anyhow::anyhow!(
+            "Unknown search tokenizer: {}\nHint: Use one of {}.",
+            value,
+            names.join(", ")
+        )
+    })
+}
+
 /// Parse a duration string (e.g., "7d", "24h").
 pub fn parse_duration(value: &str) -> anyhow::Result<Duration> {
     if value.len() < 2 {
@@ -51,7 +89,7 @@ pub fn parse_duration(value: &str) -> anyhow::Result<Duration> {
     }
 }
 
-/// Ensure entry type is "journal" (only supported type in Phase 0.1).
+/// Ensure entry type is "journal" (the only type with a free-form body to edit).
 pub fn ensure_journal_type_name(entry_type: &str) -> anyhow::Result<()> {
     if entry_type != "journal" {
         return Err(anyhow::anyhow!(
@@ -62,20 +100,54 @@ pub fn ensure_journal_type_name(entry_type: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Ensure entry type is one of the built-in types.
+pub fn ensure_builtin_type_name(entry_type: &str) -> anyhow::Result<()> {
+    if !is_builtin_entry_type(entry_type) {
+        return Err(anyhow::anyhow!(
+            "Entry type \"{}\" is not supported in the CLI yet. Available: {}.\nHint: Use `ledger add journal`, `ledger add bookmark --url <URL>`, or install more types with `ledger packs install`.",
+            entry_type,
+            BUILTIN_ENTRY_TYPES.join(", ")
+        ));
+    }
+    Ok(())
+}
+
 /// Look up an entry type by name, returning an error if not found.
 ///
-/// This combines `ensure_journal_type_name` with the storage lookup,
-/// providing a single function for the common pattern of validating
-/// and fetching an entry type.
+/// Types already in the ledger (built-in or installed from a pack) are
+/// returned as-is; other names must be built-in, so a typo gets the list of
+/// available types rather than a bare "not found".
 pub fn require_entry_type(
     storage: &ledger_core::storage::AgeSqliteStorage,
     entry_type_name: &str,
 ) -> anyhow::Result<ledger_core::storage::EntryType> {
-    ensure_journal_type_name(entry_type_name)?;
+    if let Some(entry_type) = storage.get_entry_type(entry_type_name)? {
+        return Ok(entry_type);
+    }
+    ensure_builtin_type_name(entry_type_name)?;
     storage.get_entry_type(entry_type_name)?.ok_or_else(|| {
         anyhow::anyhow!(
-            "Entry type \"{}\" not found.\nHint: Only \"journal\" is available in Phase 0.1.",
+            "Entry type \"{}\" not found.\nHint: Add an entry with `ledger add {}` first.",
+            entry_type_name,
             entry_type_name
         )
     })
 }
+
+/// Resolve an entry selector: a UUID, or a day number like `2025-02-03#4`.
+///
+/// Returns `None` when the day number is not assigned to any entry.
+pub fn resolve_entry_id(
+    storage: &ledger_core::storage::AgeSqliteStorage,
+    value: &str,
+) -> anyhow::Result<Option<uuid::Uuid>> {
+    if let Some(number) = DayNumber::parse(value) {
+        return Ok(storage.entry_by_day_number(&number)?);
+    }
+    uuid::Uuid::parse_str(value).map(Some).map_err(|e| {
+        anyhow::anyhow!(
+            "Invalid entry ID: {} (use a UUID or a day number like 2025-02-03#4)",
+            e
+        )
+    })
+}
@@ -0,0 +1,157 @@
+//! EXIF extraction for photo entries.
+
+use std::io::Cursor;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeZone, Utc};
+use exif::{Exif, In, Tag, Value};
+
+/// Structured metadata pulled from a photo's EXIF block.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PhotoMetadata {
+    pub taken_at: Option<DateTime<Utc>>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+/// Read EXIF metadata from an image container (JPEG, PNG, HEIF, WebP, TIFF).
+///
+/// Images without EXIF yield empty metadata rather than an error.
+pub fn read_photo_metadata(data: &[u8]) -> PhotoMetadata {
+    let mut cursor = Cursor::new(data);
+    match exif::Reader::new().read_from_container(&mut cursor) {
+        Ok(exif) => metadata_from_exif(&exif),
+        Err(_) => PhotoMetadata::default(),
+    }
+}
+
+fn metadata_from_exif(exif: &Exif) -> PhotoMetadata {
+    PhotoMetadata {
+        taken_at: taken_at(exif),
+        latitude: gps_coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S'),
+        longitude: gps_coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W'),
+    }
+}
+
+fn ascii_field(exif: &Exif, tag: Tag) -> Option<&[u8]> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => values.first().map(|v| v.as_slice()),
+        _ => None,
+    }
+}
+
+/// Capture time, preferring DateTimeOriginal and falling back to DateTime.
+///
+/// EXIF timestamps carry no zone unless OffsetTimeOriginal is present; without
+/// it the local timezone is assumed.
+fn taken_at(exif: &Exif) -> Option<DateTime<Utc>> {
+    let raw =
+        ascii_field(exif, Tag::DateTimeOriginal).or_else(|| ascii_field(exif, Tag::DateTime))?;
+    let mut parsed = exif::DateTime::from_ascii(raw).ok()?;
+    if let Some(offset) = ascii_field(exif, Tag::OffsetTimeOriginal) {
+        let _ = parsed.parse_offset(offset);
+    }
+
+    let naive =
+        NaiveDate::from_ymd_opt(parsed.year.into(), parsed.month.into(), parsed.day.into())?
+            .and_hms_opt(
+                parsed.hour.into(),
+                parsed.minute.into(),
+                parsed.second.into(),
+            )?;
+
+    match parsed.offset {
+        Some(minutes) => FixedOffset::east_opt(i32::from(minutes) * 60)?
+            .from_local_datetime(&naive)
+            .single()
+            .map(|dt| dt.with_timezone(&Utc)),
+        None => Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc)),
+    }
+}
+
+/// Decimal degrees from a degrees/minutes/seconds rational triple.
+fn gps_coordinate(exif: &Exif, tag: Tag, ref_tag: Tag, negative_ref: u8) -> Option<f64> {
+    let parts = match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(parts) if parts.len() == 3 => parts,
+        _ => return None,
+    };
+    let degrees = parts[0].to_f64() + parts[1].to_f64() / 60.0 + parts[2].to_f64() / 3600.0;
+    if !degrees.is_finite() {
+        return None;
+    }
+    let negative = ascii_field(exif, ref_tag)
+        .and_then(|r| r.first())
+        .map(|c| c.eq_ignore_ascii_case(&negative_ref))
+        .unwrap_or(false);
+    Some(if negative { -degrees } else { degrees })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exif::experimental::Writer;
+    use exif::{Field, Rational};
+
+    fn ascii(tag: Tag, value: &str) -> Field {
+        Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![value.as_bytes().to_vec()]),
+        }
+    }
+
+    fn dms(tag: Tag, d: u32, m: u32, s_hundredths: u32) -> Field {
+        Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value: Value::Rational(vec![
+                Rational::from((d, 1)),
+                Rational::from((m, 1)),
+                Rational::from((s_hundredths, 100)),
+            ]),
+        }
+    }
+
+    fn exif_from_fields(fields: &[Field]) -> Exif {
+        let mut writer = Writer::new();
+        for field in fields {
+            writer.push_field(field);
+        }
+        let mut buf = Cursor::new(Vec::new());
+        writer.write(&mut buf, false).expect("write exif");
+        exif::Reader::new()
+            .read_raw(buf.into_inner())
+            .expect("read exif")
+    }
+
+    #[test]
+    fn test_metadata_with_offset_and_gps() {
+        let exif = exif_from_fields(&[
+            ascii(Tag::DateTimeOriginal, "2024:05:06 07:08:09"),
+            ascii(Tag::OffsetTimeOriginal, "+02:00"),
+            ascii(Tag::GPSLatitudeRef, "N"),
+            dms(Tag::GPSLatitude, 48, 51, 3000),
+            ascii(Tag::GPSLongitudeRef, "W"),
+            dms(Tag::GPSLongitude, 2, 21, 0),
+        ]);
+        let meta = metadata_from_exif(&exif);
+        assert_eq!(
+            meta.taken_at.map(|t| t.to_rfc3339()),
+            Some("2024-05-06T05:08:09+00:00".to_string())
+        );
+        let lat = meta.latitude.expect("latitude");
+        let lon = meta.longitude.expect("longitude");
+        assert!((lat - 48.858333).abs() < 1e-5, "lat={}", lat);
+        assert!((lon + 2.35).abs() < 1e-9, "lon={}", lon);
+    }
+
+    #[test]
+    fn test_metadata_without_exif_is_empty() {
+        assert_eq!(
+            read_photo_metadata(b"not an image"),
+            PhotoMetadata::default()
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Journaling prompts for `add journal` and `today`.
+//!
+//! Prompts come from a built-in list and from `prompts.txt` in the config
+//! directory (or `[prompts] file`), one prompt per line. A line starting
+//! with `!` disables the prompt with that text, built-in or not; lines
+//! starting with `#` are comments. One active prompt is chosen per day,
+//! rotating through the list.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+
+use crate::config::xdg_config_dir;
+
+/// Prompts offered unless `[prompts] builtin = false`.
+pub const BUILTIN_PROMPTS: &[&str] = &[
+    "What took most of your attention today?",
+    "What are you grateful for right now?",
+    "What did you learn today?",
+    "What would make tomorrow a good day?",
+    "What drained your energy, and what restored it?",
+    "Who did you talk to today, and what stayed with you?",
+    "What are you putting off, and why?",
+    "What went better than you expected?",
+    "What would you do differently if you had today again?",
+    "What is one small thing you want to remember about today?",
+    "What are you looking forward to?",
+    "What decision are you weighing at the moment?",
+    "How did you take care of yourself today?",
+    "What surprised you recently?",
+];
+
+/// Marker wrapped around a prompt shown in the editor; such lines are
+/// removed from the body on save.
+const PLACEHOLDER_START: &str = "<!-- prompt: ";
+const PLACEHOLDER_END: &str = " -->";
+
+/// Where a prompt comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptSource {
+    Builtin,
+    File,
+}
+
+impl PromptSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Builtin => "builtin",
+            Self::File => "file",
+        }
+    }
+}
+
+/// A prompt and whether it takes part in the rotation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Prompt {
+    pub text: String,
+    pub source: PromptSource,
+    pub disabled: bool,
+}
+
+/// Every known prompt, built-ins first, then the file's in order.
+#[derive(Debug, Clone, Default)]
+pub struct PromptSet {
+    prompts: Vec<Prompt>,
+}
+
+impl PromptSet {
+    /// Load the prompts file at `path`, which need not exist.
+    pub fn load(path: &Path, builtin: bool) -> anyhow::Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Failed to read prompts {}: {}",
+                    path.display(),
+                    e
+                ))
+            }
+        };
+        Ok(Self::parse(&contents, builtin))
+    }
+
+    /// Parse a prompts file, adding the built-in list first if `builtin`.
+    pub fn parse(contents: &str, builtin: bool) -> Self {
+        let mut prompts: Vec<Prompt> = Vec::new();
+        let mut disabled = Vec::new();
+        if builtin {
+            prompts.extend(BUILTIN_PROMPTS.iter().map(|text| Prompt {
+                text: text.to_string(),
+                source: PromptSource::Builtin,
+                disabled: false,
+            }));
+        }
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(text) = line.strip_prefix('!') {
+                disabled.push(text.trim());
+            } else if !prompts.iter().any(|prompt| prompt.text == line) {
+                prompts.push(Prompt {
+                    text: line.to_string(),
+                    source: PromptSource::File,
+                    disabled: false,
+                });
+            }
+        }
+        for prompt in &mut prompts {
+            prompt.disabled = disabled.contains(&prompt.text.as_str());
+        }
+        Self { prompts }
+    }
+
+    /// All prompts, disabled ones included; list numbers are 1-based indexes.
+    pub fn all(&self) -> &[Prompt] {
+        &self.prompts
+    }
+
+    /// The prompt `number` (1-based) or with exactly this text.
+    pub fn find(&self, prompt: &str) -> Option<&Prompt> {
+        match prompt.parse::<usize>() {
+            Ok(number) => number
+                .checked_sub(1)
+                .and_then(|index| self.prompts.get(index)),
+            Err(_) => self.prompts.iter().find(|p| p.text == prompt.trim()),
+        }
+    }
+
+    /// The active prompt for `day`; consecutive days step through the list.
+    pub fn for_day(&self, day: NaiveDate) -> Option<&Prompt> {
+        let active: Vec<&Prompt> = self.prompts.iter().filter(|p| !p.disabled).collect();
+        if active.is_empty() {
+            return None;
+        }
+        let index = day.num_days_from_ce().rem_euclid(active.len() as i32) as usize;
+        Some(active[index])
+    }
+}
+
+/// The default prompts file: `prompts.txt` in the config directory.
+pub fn user_prompts_path() -> anyhow::Result<PathBuf> {
+    Ok(xdg_config_dir()?.join("prompts.txt"))
+}
+
+/// Append a line to the prompts file, creating it if needed.
+pub fn append_prompt_line(path: &Path, line: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Remove the lines disabling `text` from the prompts file.
+///
+/// Returns whether any line was removed.
+pub fn remove_disable_lines(path: &Path, text: &str) -> anyhow::Result<bool> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Ok(false);
+    };
+    let mut removed = false;
+    let mut kept = String::with_capacity(contents.len());
+    for line in contents.lines() {
+        if line.trim().strip_prefix('!').map(str::trim) == Some(text) {
+            removed = true;
+            continue;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+    }
+    if removed {
+        std::fs::write(path, kept)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(removed)
+}
+
+/// Editor starting text showing `prompt`, removed again on save.
+pub fn prompt_placeholder(prompt: &str) -> String {
+    format!("{}{}{}\n\n", PLACEHOLDER_START, prompt, PLACEHOLDER_END)
+}
+
+/// Drop prompt placeholder lines from an edited body.
+pub fn strip_prompt_placeholder(body: &str) -> String {
+    body.lines()
+        .filter(|line| {
+            let line = line.trim();
+            !(line.starts_with(PLACEHOLDER_START) && line.ends_with(PLACEHOLDER_END.trim()))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_merges_file_prompts_and_disables() {
+        let contents =
+            "# mine\nHow was the walk?\n\n!What did you learn today?\nHow was the walk?\n";
+        let set = PromptSet::parse(contents, true);
+        assert_eq!(set.all().len(), BUILTIN_PROMPTS.len() + 1);
+        let mine = set.all().last().unwrap();
+        assert_eq!(mine.text, "How was the walk?");
+        assert_eq!(mine.source, PromptSource::File);
+        assert!(set.find("What did you learn today?").unwrap().disabled);
+        assert_eq!(set.find("1").unwrap().text, BUILTIN_PROMPTS[0]);
+        assert!(set.find("0").is_none());
+
+        let set = PromptSet::parse(contents, false);
+        assert_eq!(set.all().len(), 1);
+    }
+
+    #[test]
+    fn test_for_day_rotates_through_active_prompts() {
+        let set = PromptSet::parse("One\nTwo\n!Two\nThree\n", false);
+        let day = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let first = set.for_day(day).unwrap();
+        let next = set.for_day(day.succ_opt().unwrap()).unwrap();
+        assert_ne!(first.text, next.text);
+        assert!(!first.disabled && !next.disabled);
+        assert_eq!(
+            set.for_day(day + chrono::Duration::days(2)).unwrap().text,
+            first.text
+        );
+        assert!(PromptSet::parse("!One\nOne\n", false)
+            .for_day(day)
+            .is_none());
+    }
+
+    #[test]
+    fn test_strip_prompt_placeholder() {
+        let edited = format!("{}Walked to the lake.\n", prompt_placeholder("Where to?"));
+        assert_eq!(strip_prompt_placeholder(&edited), "Walked to the lake.");
+        assert_eq!(strip_prompt_placeholder(&prompt_placeholder("Where?")), "");
+        assert_eq!(
+            strip_prompt_placeholder("<!-- a note -->\nKept"),
+            "<!-- a note -->\nKept"
+        );
+    }
+}
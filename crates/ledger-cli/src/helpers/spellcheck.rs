@@ -0,0 +1,224 @@
+//! Spell checking for entry bodies (`add --lint`).
+//!
+//! Words are checked against a system word list plus a personal dictionary
+//! in the config directory (`dictionary.txt`, one word per line). Only built
+//! with the `spellcheck` feature.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::xdg_config_dir;
+
+/// Word lists tried when `[lint] wordlist` is not set.
+const SYSTEM_WORDLISTS: &[&str] = &["/usr/share/dict/words", "/usr/dict/words"];
+
+/// Most suggestions offered per word.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Largest edit distance still offered as a suggestion.
+const MAX_EDIT_DISTANCE: usize = 2;
+
+/// A word missing from the dictionary, with likely corrections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    pub word: String,
+    pub suggestions: Vec<String>,
+}
+
+/// Known words: a system word list plus the personal dictionary.
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Load the word list at `wordlist` (or the first system list found) and
+    /// the personal dictionary.
+    ///
+    /// Returns `None` when no word list is available.
+    pub fn load(wordlist: Option<&Path>) -> anyhow::Result<Option<Self>> {
+        let path = match wordlist {
+            Some(path) => {
+                if !path.exists() {
+                    return Err(anyhow::anyhow!(
+                        "Word list not found: {}\nHint: Fix [lint] wordlist in your config.",
+                        path.display()
+                    ));
+                }
+                path.to_path_buf()
+            }
+            None => match SYSTEM_WORDLISTS.iter().map(Path::new).find(|p| p.exists()) {
+                Some(path) => path.to_path_buf(),
+                None => return Ok(None),
+            },
+        };
+        let mut words = HashSet::new();
+        read_words(&path, &mut words)?;
+        let personal = user_dictionary_path()?;
+        if personal.exists() {
+            read_words(&personal, &mut words)?;
+        }
+        Ok(Some(Self { words }))
+    }
+
+    #[cfg(test)]
+    fn from_words<'a>(words: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            words: words.into_iter().map(|word| word.to_lowercase()).collect(),
+        }
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        let lower = word.to_lowercase();
+        self.words.contains(&lower)
+            || lower
+                .strip_suffix("'s")
+                .is_some_and(|stem| self.words.contains(stem))
+    }
+
+    /// Words in `text` missing from the dictionary, each listed once.
+    pub fn misspellings(&self, text: &str) -> Vec<Misspelling> {
+        let mut seen = HashSet::new();
+        let mut found = Vec::new();
+        for word in checkable_words(text) {
+            if self.contains(word) || !seen.insert(word) {
+                continue;
+            }
+            found.push(Misspelling {
+                word: word.to_string(),
+                suggestions: self.suggestions(word),
+            });
+        }
+        found
+    }
+
+    /// Closest dictionary words by edit distance, in the word's capitalization.
+    fn suggestions(&self, word: &str) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let length = lower.chars().count();
+        let mut candidates: Vec<(usize, &String)> = self
+            .words
+            .iter()
+            .filter(|candidate| candidate.chars().count().abs_diff(length) <= MAX_EDIT_DISTANCE)
+            .map(|candidate| (strsim::damerau_levenshtein(&lower, candidate), candidate))
+            .filter(|(distance, _)| *distance <= MAX_EDIT_DISTANCE)
+            .collect();
+        candidates.sort();
+        candidates
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, candidate)| match_case(word, candidate))
+            .collect()
+    }
+}
+
+/// The personal dictionary: `dictionary.txt` in the config directory.
+pub fn user_dictionary_path() -> anyhow::Result<PathBuf> {
+    Ok(xdg_config_dir()?.join("dictionary.txt"))
+}
+
+/// Append `word` to the personal dictionary.
+pub fn add_to_user_dictionary(word: &str) -> anyhow::Result<()> {
+    let path = user_dictionary_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", word.to_lowercase())?;
+    Ok(())
+}
+
+/// Replace whole-word occurrences of `word` in `text`.
+pub fn replace_word(text: &str, word: &str, replacement: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(word) {
+        let before = rest[..index].chars().next_back();
+        let after = rest[index + word.len()..].chars().next();
+        out.push_str(&rest[..index]);
+        if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+            out.push_str(word);
+        } else {
+            out.push_str(replacement);
+        }
+        rest = &rest[index + word.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn read_words(path: &Path, words: &mut HashSet<String>) -> anyhow::Result<()> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    words.extend(
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_lowercase),
+    );
+    Ok(())
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '\''
+}
+
+/// Words worth checking: skips URLs, paths, short words, acronyms, and
+/// anything with digits or non-Latin letters.
+fn checkable_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace()
+        .filter(|token| !token.contains("://") && !token.contains('/') && !token.contains('@'))
+        .flat_map(|token| token.split(|c: char| !is_word_char(c)))
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| {
+            word.chars().count() >= 3
+                && word.chars().all(|c| c.is_ascii_alphabetic() || c == '\'')
+                && !word.chars().all(|c| c.is_ascii_uppercase())
+        })
+}
+
+fn match_case(original: &str, suggestion: &str) -> String {
+    let mut chars = suggestion.chars();
+    match (original.chars().next(), chars.next()) {
+        (Some(first), Some(head)) if first.is_uppercase() => {
+            head.to_uppercase().chain(chars).collect()
+        }
+        _ => suggestion.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_misspellings_suggest_close_words() {
+        let dictionary = Dictionary::from_words(["the", "quick", "brown", "fox", "then", "ten"]);
+        let found = dictionary.misspellings("Teh quick brwn fox, teh end of https://exmaple.com");
+        let words: Vec<&str> = found.iter().map(|m| m.word.as_str()).collect();
+        assert_eq!(words, vec!["Teh", "brwn", "teh", "end"]);
+        assert_eq!(found[0].suggestions, vec!["Ten", "The", "Then"]);
+        assert_eq!(found[1].suggestions, vec!["brown".to_string()]);
+    }
+
+    #[test]
+    fn test_skips_acronyms_numbers_and_possessives() {
+        let dictionary = Dictionary::from_words(["ship", "friday"]);
+        let found = dictionary.misspellings("NASA ship's v2 on Friday's run");
+        let words: Vec<&str> = found.iter().map(|m| m.word.as_str()).collect();
+        assert_eq!(words, vec!["run"]);
+    }
+
+    #[test]
+    fn test_replace_word_keeps_partial_matches() {
+        assert_eq!(
+            replace_word("teh tehran teh.", "teh", "the"),
+            "the tehran the."
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Numeric metric series: field lookup and rolling statistics.
+//!
+//! A metric is a numeric field of an entry type (`sleep.hours`). Entries
+//! with a `date` field count on that day, others on their local creation
+//! date, so backdated readings land where they belong.
+
+use chrono::{Local, NaiveDate};
+use ledger_core::storage::Entry;
+
+use super::fields::FieldDef;
+
+/// Fewest earlier values needed before a value can be called an outlier.
+pub const MIN_BASELINE: usize = 5;
+
+/// Mean and sample standard deviation of some values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl Summary {
+    /// Standard deviations between `value` and the mean; `None` for a flat
+    /// sample, where every change is infinitely unusual.
+    pub fn z_score(&self, value: f64) -> Option<f64> {
+        (self.stddev > 0.0).then(|| (value - self.mean) / self.stddev)
+    }
+}
+
+/// Running sums for a window that values enter and leave.
+#[derive(Debug, Clone, Copy, Default)]
+struct Rolling {
+    count: usize,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl Rolling {
+    fn push(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.sum_sq += value * value;
+    }
+
+    fn pop(&mut self, value: f64) {
+        self.count -= 1;
+        self.sum -= value;
+        self.sum_sq -= value * value;
+    }
+
+    fn summary(&self) -> Option<Summary> {
+        if self.count == 0 {
+            return None;
+        }
+        let n = self.count as f64;
+        let mean = self.sum / n;
+        let variance = if self.count > 1 {
+            // Rounding can leave a flat window slightly negative
+            ((self.sum_sq - n * mean * mean) / (n - 1.0)).max(0.0)
+        } else {
+            0.0
+        };
+        Some(Summary {
+            count: self.count,
+            mean,
+            stddev: variance.sqrt(),
+        })
+    }
+}
+
+/// For each point, a summary of the points before it within `window_days`.
+///
+/// `points` must be sorted by day; points on the same day count as before
+/// one another in slice order.
+pub fn rolling_baselines(points: &[(NaiveDate, f64)], window_days: i64) -> Vec<Option<Summary>> {
+    let mut rolling = Rolling::default();
+    let mut start = 0;
+    let mut baselines = Vec::with_capacity(points.len());
+    for (index, (day, value)) in points.iter().enumerate() {
+        while start < index && (*day - points[start].0).num_days() > window_days {
+            rolling.pop(points[start].1);
+            start += 1;
+        }
+        baselines.push(rolling.summary());
+        rolling.push(*value);
+    }
+    baselines
+}
+
+/// How many times larger or smaller `value` is than `previous`.
+///
+/// Only defined when both are positive, as ratios across zero mean nothing.
+pub fn jump_ratio(previous: f64, value: f64) -> Option<f64> {
+    (previous > 0.0 && value > 0.0).then(|| (value / previous).max(previous / value))
+}
+
+/// Resolve the numeric field to read, defaulting to the only one.
+pub fn numeric_field(
+    schema: &serde_json::Value,
+    type_name: &str,
+    requested: Option<&str>,
+) -> anyhow::Result<String> {
+    let numeric: Vec<String> = FieldDef::from_schema(schema)
+        .into_iter()
+        .filter(|field| matches!(field.field_type.as_str(), "number" | "integer"))
+        .map(|field| field.name)
+        .collect();
+    match requested {
+        Some(field) if numeric.iter().any(|name| name == field) => Ok(field.to_string()),
+        Some(field) => Err(anyhow::anyhow!(
+            "{}.{} is not a numeric field",
+            type_name,
+            field
+        )),
+        None => match numeric.as_slice() {
+            [only] => Ok(only.clone()),
+            [] => Err(anyhow::anyhow!("{} has no numeric fields", type_name)),
+            several => Err(anyhow::anyhow!(
+                "{} has several numeric fields; pick one with {}.<field> ({})",
+                type_name,
+                type_name,
+                several.join(", ")
+            )),
+        },
+    }
+}
+
+/// Day a metric reading belongs to: its `date` field, else its local
+/// creation date.
+pub fn metric_day(entry: &Entry) -> NaiveDate {
+    entry
+        .data
+        .get("date")
+        .and_then(|v| v.as_str())
+        .and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| entry.created_at.with_timezone(&Local).date_naive())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(n: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, n).unwrap()
+    }
+
+    #[test]
+    fn test_baseline_mean_and_sample_stddev() {
+        let points: Vec<(NaiveDate, f64)> = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0, 0.0]
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (day(i as u32 + 1), *value))
+            .collect();
+        let summary = rolling_baselines(&points, 30)[8].unwrap();
+        assert_eq!(summary.count, 8);
+        assert!((summary.mean - 5.0).abs() < 1e-9);
+        assert!((summary.stddev - 2.138_089_935).abs() < 1e-6);
+        assert!((summary.z_score(9.276_179_87).unwrap() - 2.0).abs() < 1e-6);
+
+        let flat = rolling_baselines(&[(day(1), 3.0), (day(2), 3.0), (day(3), 4.0)], 30);
+        assert_eq!(flat[2].unwrap().z_score(4.0), None);
+    }
+
+    #[test]
+    fn test_rolling_baselines_drop_old_points() {
+        let points = [
+            (day(1), 10.0),
+            (day(2), 20.0),
+            (day(5), 30.0),
+            (day(6), 40.0),
+        ];
+        let baselines = rolling_baselines(&points, 3);
+        assert!(baselines[0].is_none());
+        assert_eq!(baselines[1].unwrap().count, 1);
+        // Day 1 is four days before day 5, outside the window
+        let third = baselines[2].unwrap();
+        assert_eq!(third.count, 1);
+        assert!((third.mean - 20.0).abs() < 1e-9);
+        let fourth = baselines[3].unwrap();
+        assert_eq!(fourth.count, 1);
+        assert!((fourth.mean - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_jump_ratio_is_symmetric() {
+        assert_eq!(jump_ratio(85.0, 8.5), Some(10.0));
+        assert_eq!(jump_ratio(8.5, 85.0), Some(10.0));
+        assert_eq!(jump_ratio(0.0, 5.0), None);
+    }
+
+    #[test]
+    fn test_numeric_field_defaults_to_only_number() {
+        let schema = serde_json::json!({"fields": [
+            {"name": "hours", "type": "number"},
+            {"name": "notes", "type": "text"}
+        ]});
+        assert_eq!(numeric_field(&schema, "sleep", None).unwrap(), "hours");
+        assert!(numeric_field(&schema, "sleep", Some("notes")).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+//! Conversion between task entries and todo.txt lines.
+//!
+//! See <https://github.com/todotxt/todo.txt> for the format. Ledger tags map
+//! to `+project` tokens, except `context:<name>` tags which map to `@name`.
+//! `due:` is carried in the task's `due` field; other `key:value` tokens stay
+//! in the title so nothing is lost on a round trip.
+
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+use ledger_core::storage::Entry;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A task parsed from one todo.txt line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoTask {
+    pub title: String,
+    pub done: bool,
+    pub priority: Option<char>,
+    pub created: Option<NaiveDate>,
+    pub completed: Option<NaiveDate>,
+    pub due: Option<NaiveDate>,
+    pub tags: Vec<String>,
+}
+
+impl TodoTask {
+    /// Entry data for the built-in `task` type.
+    pub fn data(&self) -> serde_json::Value {
+        let mut data = serde_json::json!({
+            "title": self.title,
+            "done": self.done,
+        });
+        if let Some(priority) = self.priority {
+            data["priority"] = serde_json::Value::String(priority.to_string());
+        }
+        if let Some(due) = self.due {
+            data["due"] = serde_json::Value::String(due.format(DATE_FORMAT).to_string());
+        }
+        if let Some(completed) = self.completed {
+            data["completed_at"] =
+                serde_json::Value::String(completed.format(DATE_FORMAT).to_string());
+        }
+        data
+    }
+
+    /// Creation date as a timestamp, for `NewEntry::with_created_at`.
+    pub fn created_at(&self) -> Option<chrono::DateTime<Utc>> {
+        self.created
+            .map(|date| Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN)))
+    }
+}
+
+/// Render a task entry as a todo.txt line.
+pub fn task_to_todotxt(entry: &Entry) -> String {
+    let field = |name: &str| entry.data.get(name).and_then(|v| v.as_str());
+    let done = entry
+        .data
+        .get("done")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let priority = field("priority").and_then(parse_priority);
+
+    let mut parts = Vec::new();
+    if done {
+        parts.push("x".to_string());
+        if let Some(completed) = field("completed_at") {
+            parts.push(completed.to_string());
+        }
+    } else if let Some(priority) = priority {
+        parts.push(format!("({})", priority));
+    }
+    parts.push(entry.created_at.format(DATE_FORMAT).to_string());
+    parts.push(field("title").unwrap_or("").to_string());
+    for tag in &entry.tags {
+        match tag.strip_prefix("context:") {
+            Some(context) => parts.push(format!("@{}", context)),
+            None => parts.push(format!("+{}", tag)),
+        }
+    }
+    if let Some(due) = field("due") {
+        parts.push(format!("due:{}", due));
+    }
+    if done {
+        if let Some(priority) = priority {
+            parts.push(format!("pri:{}", priority));
+        }
+    }
+    parts.retain(|part| !part.is_empty());
+    parts.join(" ")
+}
+
+/// Parse one todo.txt line; `None` for blank lines and lines with no title.
+pub fn parse_todotxt_line(line: &str) -> Option<TodoTask> {
+    let mut tokens = line.split_whitespace().peekable();
+    let mut task = TodoTask {
+        title: String::new(),
+        done: false,
+        priority: None,
+        created: None,
+        completed: None,
+        due: None,
+        tags: Vec::new(),
+    };
+
+    if tokens.peek() == Some(&"x") {
+        tokens.next();
+        task.done = true;
+        task.completed = tokens.peek().and_then(|t| parse_date(t));
+        if task.completed.is_some() {
+            tokens.next();
+        }
+    } else if let Some(priority) = tokens
+        .peek()
+        .and_then(|t| t.strip_prefix('(')?.strip_suffix(')'))
+        .and_then(parse_priority)
+    {
+        tokens.next();
+        task.priority = Some(priority);
+    }
+    task.created = tokens.peek().and_then(|t| parse_date(t));
+    if task.created.is_some() {
+        tokens.next();
+    }
+
+    let mut title = Vec::new();
+    for token in tokens {
+        if let Some(project) = token.strip_prefix('+').filter(|p| !p.is_empty()) {
+            task.tags.push(tag_name(project));
+        } else if let Some(context) = token.strip_prefix('@').filter(|c| !c.is_empty()) {
+            task.tags.push(format!("context:{}", tag_name(context)));
+        } else if let Some(due) = token.strip_prefix("due:").and_then(parse_date) {
+            task.due = Some(due);
+        } else if let Some(priority) = token.strip_prefix("pri:").and_then(parse_priority) {
+            task.priority = Some(priority);
+        } else {
+            title.push(token);
+        }
+    }
+    task.title = title.join(" ");
+    if task.title.is_empty() {
+        return None;
+    }
+    task.tags.dedup();
+    Some(task)
+}
+
+fn parse_date(token: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(token, DATE_FORMAT).ok()
+}
+
+fn parse_priority(token: &str) -> Option<char> {
+    let mut chars = token.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_uppercase() => Some(c),
+        _ => None,
+    }
+}
+
+/// Fold a project or context name into the ledger tag alphabet.
+fn tag_name(raw: &str) -> String {
+    raw.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn task_entry(data: serde_json::Value, tags: &[&str]) -> Entry {
+        Entry {
+            id: Uuid::new_v4(),
+            entry_type_id: Uuid::new_v4(),
+            schema_version: 1,
+            data,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            created_at: Utc.with_ymd_and_hms(2024, 3, 1, 9, 30, 0).unwrap(),
+            seq: 0,
+            device_id: Uuid::new_v4(),
+            supersedes: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_open_task() {
+        let task =
+            parse_todotxt_line("(A) 2024-03-01 Call Mom +Family @phone due:2024-03-05").unwrap();
+        assert_eq!(task.title, "Call Mom");
+        assert!(!task.done);
+        assert_eq!(task.priority, Some('A'));
+        assert_eq!(task.created, NaiveDate::from_ymd_opt(2024, 3, 1));
+        assert_eq!(task.due, NaiveDate::from_ymd_opt(2024, 3, 5));
+        assert_eq!(task.tags, vec!["family", "context:phone"]);
+    }
+
+    #[test]
+    fn test_parse_done_task() {
+        let task = parse_todotxt_line("x 2024-03-04 2024-03-01 File taxes pri:B").unwrap();
+        assert!(task.done);
+        assert_eq!(task.completed, NaiveDate::from_ymd_opt(2024, 3, 4));
+        assert_eq!(task.created, NaiveDate::from_ymd_opt(2024, 3, 1));
+        assert_eq!(task.priority, Some('B'));
+        assert_eq!(task.title, "File taxes");
+    }
+
+    #[test]
+    fn test_parse_skips_blank_lines() {
+        assert!(parse_todotxt_line("   ").is_none());
+        assert!(parse_todotxt_line("(A) +project").is_none());
+    }
+
+    #[test]
+    fn test_render_round_trips() {
+        let open = task_entry(
+            serde_json::json!({"title": "Call Mom", "done": false, "priority": "A", "due": "2024-03-05"}),
+            &["family", "context:phone"],
+        );
+        let line = task_to_todotxt(&open);
+        assert_eq!(
+            line,
+            "(A) 2024-03-01 Call Mom +family @phone due:2024-03-05"
+        );
+        assert_eq!(parse_todotxt_line(&line).unwrap().data(), open.data);
+
+        let done = task_entry(
+            serde_json::json!({"title": "File taxes", "done": true, "priority": "B", "completed_at": "2024-03-04"}),
+            &[],
+        );
+        let line = task_to_todotxt(&done);
+        assert_eq!(line, "x 2024-03-04 2024-03-01 File taxes pri:B");
+        assert_eq!(parse_todotxt_line(&line).unwrap().data(), done.data);
+    }
+}
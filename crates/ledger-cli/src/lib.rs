@@ -0,0 +1,19 @@
+//! Ledger CLI support shared by the `ledger` and `ledger-viewer` binaries
+//!
+//! Context, configuration, output and prompts live here. Command handlers
+//! stay with their binary, so `ledger-viewer` compiles only the read-only
+//! ones.
+
+pub mod app;
+pub mod cache;
+pub mod cli;
+pub mod collectors;
+pub mod config;
+pub mod constants;
+pub mod errors;
+pub mod helpers;
+pub mod notify;
+pub mod output;
+pub mod replica;
+pub mod security;
+pub mod ui;
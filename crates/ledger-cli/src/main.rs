@@ -3,33 +3,40 @@
 //! This is the command-line interface for Ledger. It provides a user-friendly
 //! interface to the core library functionality.
 
-mod app;
-mod cache;
-mod cli;
 mod commands;
-mod config;
-mod constants;
-mod errors;
-mod helpers;
-mod output;
-mod security;
-mod ui;
 
-use clap::Parser;
-use ledger_core::VERSION;
+use clap::{CommandFactory, FromArgMatches};
+use ledger_cli::{
+    app, cache, cli, collectors, config, constants, errors, helpers, notify, output, replica,
+    security, ui,
+};
+use ledger_core::{LedgerError, VERSION};
 
 use std::path::PathBuf;
 
 use crate::app::{resolve_config_path, AppContext};
-use crate::cli::{Cli, Commands, CompositionsSubcommand, TemplatesSubcommand};
-use crate::commands::{associations, compositions, entries, init, maintenance, misc, templates};
+use crate::cli::{
+    AttachmentsSubcommand, Cli, Commands, CompositionsSubcommand, DraftSubcommand,
+    ExpensesSubcommand, HabitSubcommand, LaunchdSubcommand, MetricsSubcommand, PacksSubcommand,
+    PromptsSubcommand, ReportSubcommand, ScratchSubcommand, SecretSubcommand, ShareSubcommand,
+    SyncPeersSubcommand, SyncSubcommand, SystemdSubcommand, TagsSubcommand, TemplatesSubcommand,
+    TypesSubcommand, VaultSubcommand,
+};
+use crate::commands::{
+    adopt, api, associations, attachments, collect, compositions, drafts, entries, habits, init,
+    maintenance, misc, packs, prompts, reports, scratch, secrets, services, share, sync, tags,
+    templates, types, vault,
+};
 use crate::config::read_config;
 use crate::ui::theme::{styled, styles};
 use crate::ui::{banner, blank_line, hint, kv, print, print_error, OutputMode};
 
 fn main() {
-    let cli = Cli::parse();
-    let ctx = AppContext::new(&cli);
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)
+        .map_err(|e| e.format(&mut Cli::command()))
+        .unwrap_or_else(|e| e.exit());
+    let ctx = AppContext::new(&cli).with_command(command_path(&matches));
 
     if let Err(e) = run(&ctx, &cli) {
         // Get UI context for error formatting
@@ -37,16 +44,18 @@ fn main() {
 
         // Extract hint from error chain if available
         let error_msg = format!("{}", e);
-        let hint = extract_error_hint(&error_msg);
+        let hint = extract_error_hint(&e);
 
         print_error(&ui_ctx, &error_msg, hint.as_deref());
-        std::process::exit(1);
+        std::process::exit(errors::exit_code(&e));
     }
 }
 
 /// Extract a hint from an error message if it contains "Hint:" or similar patterns,
 /// or provide contextual hints for common error types.
-fn extract_error_hint(error: &str) -> Option<String> {
+fn extract_error_hint(err: &anyhow::Error) -> Option<String> {
+    let error = format!("{}", err);
+
     // Check for explicit hint patterns in error messages
     if let Some(idx) = error.find("\nHint:") {
         return Some(error[idx + 1..].to_string());
@@ -55,6 +64,15 @@ fn extract_error_hint(error: &str) -> Option<String> {
         return Some(error[idx + 1..].to_string());
     }
 
+    // Core errors that say what went wrong by their variant
+    if let Some(hint) = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<LedgerError>())
+        .and_then(ledger_error_hint)
+    {
+        return Some(hint.to_string());
+    }
+
     // Provide contextual hints for common error patterns
     let error_lower = error.to_lowercase();
 
@@ -75,6 +93,14 @@ fn extract_error_hint(error: &str) -> Option<String> {
         );
     }
 
+    // Retired entry type
+    if error_lower.contains("is retired") {
+        return Some(
+            "Hint: Run `ledger types retire <type> --undo` to accept new entries again."
+                .to_string(),
+        );
+    }
+
     // Invalid entry ID format
     if error_lower.contains("invalid entry id") {
         return Some(
@@ -95,7 +121,7 @@ fn extract_error_hint(error: &str) -> Option<String> {
     // Entry type not found
     if error_lower.contains("entry type") && error_lower.contains("not found") {
         return Some(
-            "Hint: Valid entry types are 'journal' (built-in). Custom types require manual setup."
+            "Hint: Valid entry types are 'journal', 'bookmark', 'photo', and 'activity' (built-in). Custom types require manual setup."
                 .to_string(),
         );
     }
@@ -116,14 +142,76 @@ fn extract_error_hint(error: &str) -> Option<String> {
     None
 }
 
+/// Hint for a core error variant that needs no guessing from its text.
+fn ledger_error_hint(err: &LedgerError) -> Option<&'static str> {
+    match err {
+        LedgerError::IncorrectPassphrase => Some("Hint: Check your passphrase. Set LEDGER_PASSPHRASE env var or use --no-input with a keyfile."),
+        LedgerError::Corrupted(_) => Some("Hint: The ledger file is damaged. Run `ledger recover --out <new-file>` to salvage what is readable."),
+        LedgerError::VersionMismatch { .. } => Some("Hint: A newer version of ledger wrote this file; upgrade to open it."),
+        LedgerError::LockHeld(_) => Some("Hint: Wait for the other ledger command to finish, or remove the lockfile if none is running."),
+        LedgerError::TemplateNotFound(_) => Some("Hint: Run `ledger templates list` to see available templates."),
+        LedgerError::CompositionNotFound(_) => Some("Hint: Run `ledger compositions list` to see available compositions."),
+        _ => None,
+    }
+}
+
+/// Subcommand path such as `secret set`, recorded as the origin of new entries.
+fn command_path(matches: &clap::ArgMatches) -> Option<String> {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    (!names.is_empty()).then(|| names.join(" "))
+}
+
+/// Commands that touch files beyond the ledger, which `--dry-run` cannot hold back.
+fn dry_run_unsupported(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::Init(_) => Some("init"),
+        Commands::Adopt(_) => Some("adopt"),
+        Commands::Backup(_) => Some("backup"),
+        Commands::Recover(_) => Some("recover"),
+        Commands::Move(_) => Some("move"),
+        Commands::Lock(_) => Some("lock"),
+        Commands::Upcoming(args) if args.notify => Some("upcoming --notify"),
+        Commands::Share(args) if args.command.is_none() => Some("share"),
+        Commands::Vault(_) => Some("vault sync"),
+        Commands::Prompts(args) => match args.command {
+            PromptsSubcommand::Add(_) => Some("prompts add"),
+            PromptsSubcommand::Disable(_) => Some("prompts disable"),
+            PromptsSubcommand::List(_) => None,
+        },
+        _ => None,
+    }
+}
+
 fn run(ctx: &AppContext, cli: &Cli) -> anyhow::Result<()> {
+    ctx.check_config_keys()?;
+
+    if ctx.dry_run() {
+        if let Some(name) = cli.command.as_ref().and_then(dry_run_unsupported) {
+            return Err(anyhow::anyhow!(
+                "--dry-run is not supported by `ledger {}`: it writes outside the ledger\nHint: Run it without --dry-run.",
+                name
+            ));
+        }
+    }
+
     match &cli.command {
         Some(Commands::Init(args)) => {
             init::handle_init(ctx, args)?;
         }
+        Some(Commands::Adopt(args)) => {
+            adopt::handle_adopt(ctx, args)?;
+        }
         Some(Commands::Add(args)) => {
             entries::handle_add(ctx, args)?;
         }
+        Some(Commands::Backfill(args)) => {
+            entries::handle_backfill(ctx, args)?;
+        }
         Some(Commands::Edit(args)) => {
             entries::handle_edit(ctx, args)?;
         }
@@ -139,24 +227,71 @@ fn run(ctx: &AppContext, cli: &Cli) -> anyhow::Result<()> {
         Some(Commands::Export(args)) => {
             entries::handle_export(ctx, args)?;
         }
-        Some(Commands::Check) => {
-            maintenance::handle_check(ctx)?;
+        Some(Commands::ExportDiff(args)) => {
+            entries::handle_export_diff(ctx, args)?;
+        }
+        Some(Commands::Import(args)) => {
+            entries::handle_import(ctx, args)?;
+        }
+        Some(Commands::Check(args)) => {
+            maintenance::handle_check(ctx, args)?;
+        }
+        Some(Commands::Status(args)) => {
+            maintenance::handle_status(ctx, args)?;
         }
         Some(Commands::Backup(args)) => {
             maintenance::handle_backup(ctx, args)?;
         }
-        Some(Commands::Lock) => {
-            maintenance::handle_lock(ctx)?;
+        Some(Commands::Move(args)) => {
+            maintenance::handle_move(ctx, args)?;
+        }
+        Some(Commands::Launchd(args)) => match &args.command {
+            LaunchdSubcommand::Install(install_args) => {
+                services::handle_launchd_install(ctx, install_args)?;
+            }
+        },
+        Some(Commands::Systemd(args)) => match &args.command {
+            SystemdSubcommand::Install(install_args) => {
+                services::handle_systemd_install(ctx, install_args)?;
+            }
+        },
+        Some(Commands::Recover(args)) => {
+            maintenance::handle_recover(ctx, args)?;
+        }
+        Some(Commands::Reindex(args)) => {
+            maintenance::handle_reindex(ctx, args)?;
+        }
+        Some(Commands::Reencrypt(args)) => {
+            maintenance::handle_reencrypt(ctx, args)?;
+        }
+        Some(Commands::Lock(args)) => {
+            maintenance::handle_lock(ctx, args)?;
         }
         Some(Commands::Doctor(args)) => {
             maintenance::handle_doctor(ctx, args)?;
         }
+        Some(Commands::AuditSecurity(args)) => {
+            maintenance::handle_audit_security(ctx, args)?;
+        }
         Some(Commands::Completions(args)) => {
             misc::handle_completions(args)?;
         }
+        Some(Commands::Schema(args)) => {
+            misc::handle_schema(args)?;
+        }
+        Some(Commands::Api(args)) => {
+            api::handle_api(ctx, args)?;
+        }
         Some(Commands::InternalCacheDaemon(args)) => {
             maintenance::handle_internal_cache_daemon(args)?;
         }
+        Some(Commands::InternalReplicate(args)) => {
+            maintenance::handle_internal_replicate(args)?;
+        }
+        #[cfg(feature = "test-support")]
+        Some(Commands::InternalGenerate(args)) => {
+            maintenance::handle_internal_generate(ctx, args)?;
+        }
         Some(Commands::Compositions(args)) => match &args.command {
             CompositionsSubcommand::Create(create_args) => {
                 compositions::handle_create(ctx, create_args)?;
@@ -167,6 +302,9 @@ fn run(ctx: &AppContext, cli: &Cli) -> anyhow::Result<()> {
             CompositionsSubcommand::Show(show_args) => {
                 compositions::handle_show(ctx, show_args)?;
             }
+            CompositionsSubcommand::Edit(edit_args) => {
+                compositions::handle_edit(ctx, edit_args)?;
+            }
             CompositionsSubcommand::Rename(rename_args) => {
                 compositions::handle_rename(ctx, rename_args)?;
             }
@@ -196,6 +334,9 @@ fn run(ctx: &AppContext, cli: &Cli) -> anyhow::Result<()> {
             TemplatesSubcommand::ClearDefault(clear_default_args) => {
                 templates::handle_clear_default(ctx, clear_default_args)?;
             }
+            TemplatesSubcommand::Stats(stats_args) => {
+                templates::handle_stats(ctx, stats_args)?;
+            }
         },
         Some(Commands::Attach(args)) => {
             associations::handle_attach(ctx, args)?;
@@ -203,6 +344,173 @@ fn run(ctx: &AppContext, cli: &Cli) -> anyhow::Result<()> {
         Some(Commands::Detach(args)) => {
             associations::handle_detach(ctx, args)?;
         }
+        Some(Commands::Collect(args)) => {
+            collect::handle_collect(ctx, args)?;
+        }
+        Some(Commands::Report(args)) => match &args.command {
+            ReportSubcommand::Standup(standup_args) => {
+                reports::handle_standup(ctx, standup_args)?;
+            }
+        },
+        Some(Commands::Upcoming(args)) => {
+            reports::handle_upcoming(ctx, args)?;
+        }
+        Some(Commands::Today(args)) => {
+            reports::handle_today(ctx, args)?;
+        }
+        Some(Commands::Week(args)) => {
+            reports::handle_week(ctx, args)?;
+        }
+        Some(Commands::Stats(args)) => {
+            reports::handle_stats(ctx, args)?;
+        }
+        Some(Commands::Digest(args)) => {
+            reports::handle_digest(ctx, args)?;
+        }
+        Some(Commands::Correlate(args)) => {
+            reports::handle_correlate(ctx, args)?;
+        }
+        Some(Commands::Metrics(args)) => match &args.command {
+            MetricsSubcommand::Anomalies(anomalies_args) => {
+                reports::handle_metric_anomalies(ctx, anomalies_args)?;
+            }
+        },
+        Some(Commands::Expenses(args)) => match &args.command {
+            ExpensesSubcommand::Report(report_args) => {
+                reports::handle_expenses_report(ctx, report_args)?;
+            }
+        },
+        Some(Commands::Habit(args)) => match &args.command {
+            HabitSubcommand::Add(add_args) => {
+                habits::handle_add(ctx, add_args)?;
+            }
+            HabitSubcommand::Done(done_args) => {
+                habits::handle_done(ctx, done_args)?;
+            }
+            HabitSubcommand::Status(status_args) => {
+                habits::handle_status(ctx, status_args)?;
+            }
+            HabitSubcommand::Remove(remove_args) => {
+                habits::handle_remove(ctx, remove_args)?;
+            }
+        },
+        Some(Commands::Secret(args)) => match &args.command {
+            SecretSubcommand::Set(set_args) => {
+                secrets::handle_set(ctx, set_args)?;
+            }
+            SecretSubcommand::Get(get_args) => {
+                secrets::handle_get(ctx, get_args)?;
+            }
+            SecretSubcommand::List(list_args) => {
+                secrets::handle_list(ctx, list_args)?;
+            }
+            SecretSubcommand::Delete(delete_args) => {
+                secrets::handle_delete(ctx, delete_args)?;
+            }
+        },
+        Some(Commands::Scratch(args)) => match &args.command {
+            ScratchSubcommand::Edit(edit_args) => {
+                scratch::handle_edit(ctx, edit_args)?;
+            }
+            ScratchSubcommand::Show(show_args) => {
+                scratch::handle_show(ctx, show_args)?;
+            }
+            ScratchSubcommand::Clear(clear_args) => {
+                scratch::handle_clear(ctx, clear_args)?;
+            }
+            ScratchSubcommand::Promote(promote_args) => {
+                scratch::handle_promote(ctx, promote_args)?;
+            }
+        },
+        Some(Commands::Draft(args)) => match &args.command {
+            DraftSubcommand::Save(save_args) => {
+                drafts::handle_save(ctx, save_args)?;
+            }
+            DraftSubcommand::List(list_args) => {
+                drafts::handle_list(ctx, list_args)?;
+            }
+            DraftSubcommand::Edit(edit_args) => {
+                drafts::handle_edit(ctx, edit_args)?;
+            }
+            DraftSubcommand::Publish(publish_args) => {
+                drafts::handle_publish(ctx, publish_args)?;
+            }
+            DraftSubcommand::Discard(discard_args) => {
+                drafts::handle_discard(ctx, discard_args)?;
+            }
+        },
+        Some(Commands::Tags(args)) => match &args.command {
+            TagsSubcommand::List(list_args) => {
+                tags::handle_list(ctx, list_args)?;
+            }
+        },
+        Some(Commands::Types(args)) => match &args.command {
+            TypesSubcommand::List(list_args) => {
+                types::handle_list(ctx, list_args)?;
+            }
+            TypesSubcommand::Show(show_args) => {
+                types::handle_show(ctx, show_args)?;
+            }
+            TypesSubcommand::Retire(retire_args) => {
+                types::handle_retire(ctx, retire_args)?;
+            }
+        },
+        Some(Commands::Packs(args)) => match &args.command {
+            PacksSubcommand::Install(install_args) => {
+                packs::handle_install(ctx, install_args)?;
+            }
+            PacksSubcommand::List(list_args) => {
+                packs::handle_list(ctx, list_args)?;
+            }
+        },
+        Some(Commands::Attachments(args)) => match &args.command {
+            AttachmentsSubcommand::Gc(gc_args) => {
+                attachments::handle_gc(ctx, gc_args)?;
+            }
+        },
+        Some(Commands::Prompts(args)) => match &args.command {
+            PromptsSubcommand::List(list_args) => {
+                prompts::handle_list(ctx, list_args)?;
+            }
+            PromptsSubcommand::Add(add_args) => {
+                prompts::handle_add(ctx, add_args)?;
+            }
+            PromptsSubcommand::Disable(disable_args) => {
+                prompts::handle_disable(ctx, disable_args)?;
+            }
+        },
+        Some(Commands::Share(args)) => match &args.command {
+            Some(ShareSubcommand::Open(open_args)) => {
+                share::handle_open(ctx, open_args)?;
+            }
+            None => {
+                share::handle_share(ctx, args)?;
+            }
+        },
+        Some(Commands::Sync(args)) => match &args.command {
+            SyncSubcommand::Export(export_args) => {
+                sync::handle_export(ctx, export_args)?;
+            }
+            SyncSubcommand::Import(import_args) => {
+                sync::handle_import(ctx, import_args)?;
+            }
+            SyncSubcommand::Peers(peers_args) => match &peers_args.command {
+                SyncPeersSubcommand::List(list_args) => {
+                    sync::handle_peers_list(ctx, list_args)?;
+                }
+                SyncPeersSubcommand::Add(add_args) => {
+                    sync::handle_peers_add(ctx, add_args)?;
+                }
+                SyncPeersSubcommand::Remove(remove_args) => {
+                    sync::handle_peers_remove(ctx, remove_args)?;
+                }
+            },
+        },
+        Some(Commands::Vault(args)) => match &args.command {
+            VaultSubcommand::Sync(sync_args) => {
+                vault::handle_sync(ctx, sync_args)?;
+            }
+        },
         None => {
             if ctx.quiet() {
                 return Ok(());
@@ -237,6 +545,7 @@ fn run(ctx: &AppContext, cli: &Cli) -> anyhow::Result<()> {
                             "ledger init",
                             "ledger init --help",
                             "ledger --ledger /path/to/my.ledger init",
+                            "ledger adopt /path/to/existing.ledger",
                         ],
                     );
                 }
@@ -0,0 +1,169 @@
+//! Desktop notifications for reminders that fall due.
+//!
+//! Notifications are opt-in (`ledger upcoming --notify`), so a cron job or
+//! timer can surface anniversaries and other recurring dates without a
+//! terminal open. The backend is `[reminders].notify_command` when set,
+//! otherwise `osascript` on macOS and `notify-send` elsewhere. Each reminder
+//! is sent once per day: what went out is kept in `<ledger>.notified`.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// How notifications are delivered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notifier {
+    /// A configured command; the title and body are appended as arguments.
+    Command(String),
+    /// `osascript -e 'display notification …'` (macOS).
+    Osascript,
+    /// `notify-send TITLE BODY` (freedesktop).
+    NotifySend,
+}
+
+impl Notifier {
+    /// The configured command, or the platform default.
+    pub fn new(command: Option<&str>) -> Self {
+        match command {
+            Some(command) => Self::Command(command.to_string()),
+            None if cfg!(target_os = "macos") => Self::Osascript,
+            None => Self::NotifySend,
+        }
+    }
+
+    /// Program name, for messages.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Command(command) => command.split_whitespace().next().unwrap_or(""),
+            Self::Osascript => "osascript",
+            Self::NotifySend => "notify-send",
+        }
+    }
+
+    /// Show one notification and wait for the backend to accept it.
+    pub fn send(&self, title: &str, body: &str) -> anyhow::Result<()> {
+        let mut command = match self {
+            Self::Command(command) => {
+                let mut parts = command.split_whitespace();
+                let program = parts
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("Notification command is empty"))?;
+                let mut command = Command::new(program);
+                command.args(parts).arg(title).arg(body);
+                command
+            }
+            Self::Osascript => {
+                let mut command = Command::new("osascript");
+                command.arg("-e").arg(format!(
+                    "display notification {} with title {}",
+                    applescript_string(body),
+                    applescript_string(title)
+                ));
+                command
+            }
+            Self::NotifySend => {
+                let mut command = Command::new("notify-send");
+                command.arg("--app-name=ledger").arg(title).arg(body);
+                command
+            }
+        };
+        let status = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", self.name(), e))?;
+        if !status.success() {
+            return Err(anyhow::anyhow!("{} exited with {}", self.name(), status));
+        }
+        Ok(())
+    }
+}
+
+/// Quote a string for AppleScript.
+fn applescript_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Reminders already notified today, kept in `<ledger>.notified`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NotifiedLog {
+    pub date: Option<NaiveDate>,
+    #[serde(default)]
+    pub sent: BTreeSet<String>,
+}
+
+impl NotifiedLog {
+    /// Load the log for `day`; a log from another day starts empty.
+    pub fn load(ledger_path: &Path, day: NaiveDate) -> Self {
+        let log: Self = std::fs::read_to_string(notified_path(ledger_path))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        if log.date == Some(day) {
+            log
+        } else {
+            Self {
+                date: Some(day),
+                sent: BTreeSet::new(),
+            }
+        }
+    }
+
+    pub fn save(&self, ledger_path: &Path) -> anyhow::Result<()> {
+        std::fs::write(notified_path(ledger_path), serde_json::to_string(self)?)
+            .map_err(|e| anyhow::anyhow!("Failed to record sent notifications: {}", e))
+    }
+}
+
+/// Key of one reminder in the log: the entry's chain and the due field.
+pub fn reminder_key(entry_id: &uuid::Uuid, field: &str) -> String {
+    format!("{}:{}", entry_id, field)
+}
+
+fn notified_path(ledger_path: &Path) -> PathBuf {
+    let mut name = ledger_path.as_os_str().to_os_string();
+    name.push(".notified");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_applescript_string_escapes_quotes() {
+        assert_eq!(
+            applescript_string(r#"Say "hi" \ bye"#),
+            r#""Say \"hi\" \\ bye""#
+        );
+    }
+
+    #[test]
+    fn test_command_notifier_appends_title_and_body() {
+        let notifier = Notifier::new(Some("true --flag"));
+        assert_eq!(notifier.name(), "true");
+        notifier.send("Title", "Body").unwrap();
+        assert!(Notifier::new(Some("false")).send("Title", "Body").is_err());
+        assert!(Notifier::new(Some("  ")).send("Title", "Body").is_err());
+    }
+
+    #[test]
+    fn test_notified_log_resets_each_day() {
+        let dir = std::env::temp_dir().join(format!("ledger_notified_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ledger = dir.join("ledger.age");
+        let day = NaiveDate::from_ymd_opt(2025, 6, 20).unwrap();
+
+        let mut log = NotifiedLog::load(&ledger, day);
+        log.sent.insert("a:date".to_string());
+        log.save(&ledger).unwrap();
+        assert!(NotifiedLog::load(&ledger, day).sent.contains("a:date"));
+        assert!(NotifiedLog::load(&ledger, day.succ_opt().unwrap())
+            .sent
+            .is_empty());
+    }
+}
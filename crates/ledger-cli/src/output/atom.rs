@@ -0,0 +1,199 @@
+//! Atom (RFC 4287) feed rendering.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use ledger_core::storage::Entry;
+
+/// Longest title derived from an entry body.
+const MAX_TITLE_CHARS: usize = 80;
+
+/// One feed item: the current revision and the root of its revision chain.
+///
+/// The root supplies the stable `<id>` and `<published>` so feed readers
+/// treat later revisions as updates rather than new posts.
+pub struct FeedEntry<'a> {
+    pub entry: &'a Entry,
+    pub root: &'a Entry,
+    pub type_name: &'a str,
+    /// Summary from the type's `summary_template`, if it produced any text
+    pub summary: Option<String>,
+}
+
+/// Render an Atom feed document.
+pub fn atom_feed(title: &str, feed_id: &str, items: &[FeedEntry]) -> String {
+    let updated = items
+        .iter()
+        .map(|item| item.entry.created_at)
+        .max()
+        .unwrap_or_else(Utc::now);
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    out.push_str(&format!("  <title>{}</title>\n", escape(title)));
+    out.push_str(&format!("  <id>{}</id>\n", escape(feed_id)));
+    out.push_str(&format!("  <updated>{}</updated>\n", timestamp(&updated)));
+    out.push_str("  <author><name>ledger</name></author>\n");
+    out.push_str("  <generator>ledger</generator>\n");
+    for item in items {
+        out.push_str("  <entry>\n");
+        out.push_str(&format!(
+            "    <title>{}</title>\n",
+            escape(&entry_title(item))
+        ));
+        out.push_str(&format!("    <id>urn:uuid:{}</id>\n", item.root.id));
+        out.push_str(&format!(
+            "    <published>{}</published>\n",
+            timestamp(&item.root.created_at)
+        ));
+        out.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            timestamp(&item.entry.created_at)
+        ));
+        for tag in &item.entry.tags {
+            out.push_str(&format!("    <category term=\"{}\"/>\n", escape(tag)));
+        }
+        if let Some(summary) = item.summary.clone().or_else(|| entry_summary(item.entry)) {
+            out.push_str(&format!(
+                "    <summary type=\"text\">{}</summary>\n",
+                escape(&summary)
+            ));
+        }
+        out.push_str("  </entry>\n");
+    }
+    out.push_str("</feed>\n");
+    out
+}
+
+fn timestamp(value: &DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// `title` field, else the templated summary, else the first body line,
+/// else type and date.
+fn entry_title(item: &FeedEntry) -> String {
+    let data = &item.entry.data;
+    let from_field = data.get("title").and_then(|v| v.as_str());
+    let from_body = ["body", "summary", "caption", "notes"]
+        .iter()
+        .filter_map(|key| data.get(*key).and_then(|v| v.as_str()))
+        .flat_map(str::lines)
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .find(|line| !line.is_empty());
+    match from_field.or(item.summary.as_deref()).or(from_body) {
+        Some(text) if text.chars().count() > MAX_TITLE_CHARS => {
+            let cut: String = text.chars().take(MAX_TITLE_CHARS - 1).collect();
+            format!("{}\u{2026}", cut.trim_end())
+        }
+        Some(text) => text.to_string(),
+        None => format!(
+            "{} {}",
+            item.type_name,
+            item.entry.created_at.format("%Y-%m-%d")
+        ),
+    }
+}
+
+fn entry_summary(entry: &Entry) -> Option<String> {
+    ["body", "summary", "notes", "caption", "url"]
+        .iter()
+        .find_map(|key| entry.data.get(*key).and_then(|v| v.as_str()))
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+/// Escape text for XML and HTML content and attribute values.
+pub(super) fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn entry(data: serde_json::Value, supersedes: Option<Uuid>, day: u32) -> Entry {
+        Entry {
+            id: Uuid::new_v4(),
+            entry_type_id: Uuid::new_v4(),
+            schema_version: 1,
+            data,
+            tags: vec!["public".to_string()],
+            created_at: Utc.with_ymd_and_hms(2024, 3, day, 8, 0, 0).unwrap(),
+            seq: 0,
+            device_id: Uuid::new_v4(),
+            supersedes,
+        }
+    }
+
+    #[test]
+    fn test_feed_uses_root_id_and_escapes_text() {
+        let original = entry(serde_json::json!({"body": "First"}), None, 1);
+        let revised = entry(
+            serde_json::json!({"body": "# Fish & <chips>\nMore text"}),
+            Some(original.id),
+            2,
+        );
+        let feed = atom_feed(
+            "Public",
+            "urn:uuid:00000000-0000-0000-0000-000000000000",
+            &[FeedEntry {
+                entry: &revised,
+                root: &original,
+                type_name: "journal",
+                summary: None,
+            }],
+        );
+        assert!(feed.contains("<title>Fish &amp; &lt;chips&gt;</title>"));
+        assert!(feed.contains(&format!("<id>urn:uuid:{}</id>", original.id)));
+        assert!(feed.contains("<published>2024-03-01T08:00:00Z</published>"));
+        assert!(feed.contains("<updated>2024-03-02T08:00:00Z</updated>"));
+        assert!(feed.contains("<category term=\"public\"/>"));
+        assert!(
+            feed.contains("<summary type=\"text\"># Fish &amp; &lt;chips&gt;\nMore text</summary>")
+        );
+    }
+
+    #[test]
+    fn test_title_falls_back_to_type_and_date() {
+        let plain = entry(serde_json::json!({"count": 3}), None, 5);
+        let item = FeedEntry {
+            entry: &plain,
+            root: &plain,
+            type_name: "activity",
+            summary: None,
+        };
+        assert_eq!(entry_title(&item), "activity 2024-03-05");
+        let item = FeedEntry {
+            summary: Some("3 laps".to_string()),
+            ..item
+        };
+        assert_eq!(entry_title(&item), "3 laps");
+    }
+
+    #[test]
+    fn test_long_titles_are_truncated() {
+        let long = entry(serde_json::json!({"body": "word ".repeat(40)}), None, 1);
+        let item = FeedEntry {
+            entry: &long,
+            root: &long,
+            type_name: "journal",
+            summary: None,
+        };
+        let title = entry_title(&item);
+        assert!(title.ends_with('\u{2026}'));
+        assert!(title.chars().count() <= MAX_TITLE_CHARS);
+    }
+}
@@ -0,0 +1,277 @@
+//! Periodic digest rendering: entry counts, habit streaks and entries
+//! picked by tag, as Markdown or a self-contained HTML page.
+//!
+//! Nothing here sends mail; the output is meant to be piped into
+//! `sendmail`/`mail` from cron.
+
+use chrono::NaiveDate;
+
+use super::atom::escape;
+
+/// Span a digest covers, ending today.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestPeriod {
+    Week,
+    Month,
+}
+
+impl DigestPeriod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "week" => Some(Self::Week),
+            "month" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    /// Number of days covered, today included.
+    pub fn days(&self) -> i64 {
+        match self {
+            Self::Week => 7,
+            Self::Month => 30,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Week => "Weekly digest",
+            Self::Month => "Monthly digest",
+        }
+    }
+}
+
+/// Document format of a rendered digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestFormat {
+    Markdown,
+    Html,
+}
+
+impl DigestFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+/// A habit's streaks at the end of the period.
+pub struct DigestStreak {
+    pub habit: String,
+    pub current: u32,
+    pub longest: u32,
+    pub unit: &'static str,
+}
+
+/// One listed entry.
+pub struct DigestItem {
+    pub date: NaiveDate,
+    pub entry_type: String,
+    pub summary: String,
+}
+
+/// A titled list of entries, e.g. everything tagged `work`.
+pub struct DigestSection {
+    pub title: String,
+    pub items: Vec<DigestItem>,
+}
+
+/// Everything a digest shows, gathered before rendering.
+pub struct Digest {
+    pub period: DigestPeriod,
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+    pub total: usize,
+    /// Entry counts per type name, most common first
+    pub by_type: Vec<(String, usize)>,
+    pub streaks: Vec<DigestStreak>,
+    pub sections: Vec<DigestSection>,
+}
+
+impl Digest {
+    pub fn render(&self, format: DigestFormat) -> String {
+        match format {
+            DigestFormat::Markdown => self.markdown(),
+            DigestFormat::Html => self.html(),
+        }
+    }
+
+    fn range(&self) -> String {
+        format!(
+            "{} \u{2013} {}",
+            self.first.format("%Y-%m-%d"),
+            self.last.format("%Y-%m-%d")
+        )
+    }
+
+    fn counts(&self) -> Vec<String> {
+        let mut lines = vec![format!("{} entries", self.total)];
+        lines.extend(
+            self.by_type
+                .iter()
+                .map(|(name, count)| format!("{}: {}", name, count)),
+        );
+        lines
+    }
+
+    fn markdown(&self) -> String {
+        let mut out = format!(
+            "# {}\n\n{}\n\n## Summary\n\n",
+            self.period.title(),
+            self.range()
+        );
+        for line in self.counts() {
+            out.push_str(&format!("- {}\n", line));
+        }
+        if !self.streaks.is_empty() {
+            out.push_str("\n## Streaks\n\n");
+            for streak in &self.streaks {
+                out.push_str(&format!("- {}\n", streak_line(streak)));
+            }
+        }
+        for section in &self.sections {
+            out.push_str(&format!("\n## {}\n\n", section.title));
+            if section.items.is_empty() {
+                out.push_str("- No entries\n");
+            }
+            for item in &section.items {
+                out.push_str(&format!("- {}\n", item_line(item)));
+            }
+        }
+        out
+    }
+
+    fn html(&self) -> String {
+        let title = format!("{} {}", self.period.title(), self.range());
+        let mut out = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+        out.push_str("<meta charset=\"utf-8\">\n");
+        out.push_str(&format!("<title>{}</title>\n", escape(&title)));
+        out.push_str(
+            "<style>\nbody { font-family: sans-serif; max-width: 40em; margin: 2em auto; \
+             color: #222; }\nh1 { margin-bottom: 0; }\n.range { color: #666; margin-top: 0.25em; }\n\
+             li { margin: 0.2em 0; }\n.meta { color: #666; }\n</style>\n",
+        );
+        out.push_str("</head>\n<body>\n");
+        out.push_str(&format!("<h1>{}</h1>\n", escape(self.period.title())));
+        out.push_str(&format!(
+            "<p class=\"range\">{}</p>\n",
+            escape(&self.range())
+        ));
+        out.push_str("<h2>Summary</h2>\n<ul>\n");
+        for line in self.counts() {
+            out.push_str(&format!("<li>{}</li>\n", escape(&line)));
+        }
+        out.push_str("</ul>\n");
+        if !self.streaks.is_empty() {
+            out.push_str("<h2>Streaks</h2>\n<ul>\n");
+            for streak in &self.streaks {
+                out.push_str(&format!("<li>{}</li>\n", escape(&streak_line(streak))));
+            }
+            out.push_str("</ul>\n");
+        }
+        for section in &self.sections {
+            out.push_str(&format!("<h2>{}</h2>\n<ul>\n", escape(&section.title)));
+            if section.items.is_empty() {
+                out.push_str("<li>No entries</li>\n");
+            }
+            for item in &section.items {
+                out.push_str(&format!(
+                    "<li><span class=\"meta\">{} \u{00B7} {}</span> {}</li>\n",
+                    item.date.format("%Y-%m-%d"),
+                    escape(&item.entry_type),
+                    escape(&item.summary)
+                ));
+            }
+            out.push_str("</ul>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+fn streak_line(streak: &DigestStreak) -> String {
+    format!(
+        "{}: {} {} (best {} {})",
+        streak.habit, streak.current, streak.unit, streak.longest, streak.unit
+    )
+}
+
+fn item_line(item: &DigestItem) -> String {
+    format!(
+        "{} \u{00B7} {} \u{00B7} {}",
+        item.date.format("%Y-%m-%d"),
+        item.entry_type,
+        item.summary
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest() -> Digest {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+        Digest {
+            period: DigestPeriod::Week,
+            first: date(4),
+            last: date(10),
+            total: 3,
+            by_type: vec![("journal".to_string(), 2), ("task".to_string(), 1)],
+            streaks: vec![DigestStreak {
+                habit: "stretch".to_string(),
+                current: 3,
+                longest: 5,
+                unit: "days",
+            }],
+            sections: vec![DigestSection {
+                title: "#work".to_string(),
+                items: vec![DigestItem {
+                    date: date(5),
+                    entry_type: "journal".to_string(),
+                    summary: "Fish & <chips>".to_string(),
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_digest_markdown() {
+        assert_eq!(
+            digest().render(DigestFormat::Markdown),
+            "# Weekly digest\n\n2024-03-04 \u{2013} 2024-03-10\n\n## Summary\n\n\
+- 3 entries\n- journal: 2\n- task: 1\n\n## Streaks\n\n- stretch: 3 days (best 5 days)\n\n\
+## #work\n\n- 2024-03-05 \u{00B7} journal \u{00B7} Fish & <chips>\n"
+        );
+    }
+
+    #[test]
+    fn test_digest_html_is_self_contained_and_escaped() {
+        let html = digest().render(DigestFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<style>"));
+        assert!(!html.contains("<link") && !html.contains("<script") && !html.contains("src="));
+        assert!(html.contains("Fish &amp; &lt;chips&gt;"));
+        assert!(html.contains("<li>stretch: 3 days (best 5 days)</li>"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn test_empty_section_and_no_habits() {
+        let mut digest = digest();
+        digest.streaks.clear();
+        digest.sections[0].items.clear();
+        let markdown = digest.render(DigestFormat::Markdown);
+        assert!(!markdown.contains("## Streaks"));
+        assert!(markdown.ends_with("## #work\n\n- No entries\n"));
+    }
+
+    #[test]
+    fn test_digest_period_and_format_parse() {
+        assert_eq!(DigestPeriod::parse("month").map(|p| p.days()), Some(30));
+        assert_eq!(DigestPeriod::parse("year"), None);
+        assert_eq!(DigestFormat::parse("md"), Some(DigestFormat::Markdown));
+        assert_eq!(DigestFormat::parse("pdf"), None);
+    }
+}
@@ -0,0 +1,117 @@
+//! Whole-document output in JSON, YAML or TOML.
+//!
+//! Used where a command prints one document (`show`, non-streaming
+//! `export`). The document is built in memory first; `export --format jsonl`
+//! is the only streaming format.
+
+use std::fmt;
+
+/// Format of a structured document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl DocumentFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    /// Render `value` as a document, ending with a newline.
+    ///
+    /// TOML has no null, so null fields are left out; its top level must be
+    /// a table, so anything else is wrapped as `key = value`.
+    pub fn render(self, value: &serde_json::Value, key: &str) -> anyhow::Result<String> {
+        let mut out = match self {
+            Self::Json => serde_json::to_string_pretty(value)?,
+            Self::Yaml => serde_yaml::to_string(value)?,
+            Self::Toml => {
+                let mut value = without_nulls(value);
+                if !value.is_object() {
+                    value = serde_json::json!({ key: value });
+                }
+                toml::to_string(&value)?
+            }
+        };
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+impl fmt::Display for DocumentFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+        })
+    }
+}
+
+fn without_nulls(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .filter(|(_, v)| !v.is_null())
+            .map(|(k, v)| (k.clone(), without_nulls(v)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter(|v| !v.is_null())
+            .map(without_nulls)
+            .collect::<Vec<_>>()
+            .into(),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> serde_json::Value {
+        serde_json::json!({
+            "id": "7f1c",
+            "supersedes": null,
+            "tags": ["home", "walk"],
+            "data": {"body": "line one\nline two", "mood": 4, "note": null},
+        })
+    }
+
+    #[test]
+    fn test_parse_names() {
+        assert_eq!(DocumentFormat::parse("yml"), Some(DocumentFormat::Yaml));
+        assert_eq!(DocumentFormat::parse("toml"), Some(DocumentFormat::Toml));
+        assert_eq!(DocumentFormat::parse("xml"), None);
+    }
+
+    #[test]
+    fn test_yaml_round_trips() {
+        let yaml = DocumentFormat::Yaml.render(&sample(), "entry").unwrap();
+        let back: serde_json::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(back, sample());
+    }
+
+    #[test]
+    fn test_toml_drops_nulls_and_wraps_arrays() {
+        let toml_text = DocumentFormat::Toml.render(&sample(), "entry").unwrap();
+        assert!(!toml_text.contains("supersedes"));
+        assert!(!toml_text.contains("note"));
+        let back: toml::Value = toml::from_str(&toml_text).unwrap();
+        assert_eq!(back["data"]["mood"].as_integer(), Some(4));
+
+        let list = serde_json::json!([{"id": "a"}, {"id": "b"}]);
+        let toml_text = DocumentFormat::Toml.render(&list, "entries").unwrap();
+        assert!(toml_text.contains("[[entries]]"));
+    }
+}
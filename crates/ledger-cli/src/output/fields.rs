@@ -0,0 +1,382 @@
+//! Human formatting of structured entry fields.
+//!
+//! Schema fields may carry display annotations: `unit` (appended after the
+//! number, e.g. `"kg"`), `decimals` (fixed fraction digits) and `currency`
+//! (an ISO 4217 code such as `"EUR"`). Numbers and dates are then rendered
+//! for the `[ui] locale` from the config. `bytes` fields show their size.
+
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, NaiveDate, Utc};
+use ledger_core::storage::{AgeSqliteStorage, Entry, StorageEngine};
+use uuid::Uuid;
+
+use crate::ui::format_bytes;
+use crate::ui::theme::symbols;
+
+/// Where a currency symbol goes relative to the amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SymbolPosition {
+    Before,
+    After,
+}
+
+/// Number and date conventions for one locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    decimal: char,
+    group: Option<char>,
+    date_format: &'static str,
+    symbol: SymbolPosition,
+}
+
+impl Default for Locale {
+    /// ISO-style output: `1234.5`, `2024-03-01`.
+    fn default() -> Self {
+        Self {
+            decimal: '.',
+            group: None,
+            date_format: "%Y-%m-%d",
+            symbol: SymbolPosition::Before,
+        }
+    }
+}
+
+/// Locale tags accepted in `[ui] locale`.
+pub const SUPPORTED_LOCALES: &[&str] = &[
+    "en-US", "en-GB", "de-DE", "fr-FR", "es-ES", "it-IT", "nl-NL", "sv-SE", "ja-JP",
+];
+
+impl Locale {
+    /// Parse a locale tag such as `de-DE`, `en_GB.UTF-8` or `fr`.
+    pub fn parse(tag: &str) -> Option<Self> {
+        let normalized = tag
+            .split('.')
+            .next()
+            .unwrap_or_default()
+            .replace('_', "-")
+            .to_ascii_lowercase();
+        let (language, region) = match normalized.split_once('-') {
+            Some((language, region)) => (language.to_string(), Some(region.to_string())),
+            None => (normalized.clone(), None),
+        };
+        let locale = |decimal, group, date_format, symbol| Self {
+            decimal,
+            group,
+            date_format,
+            symbol,
+        };
+        use SymbolPosition::{After, Before};
+        match (language.as_str(), region.as_deref()) {
+            ("c" | "posix" | "iso", _) => Some(Self::default()),
+            ("en", Some("gb" | "ie" | "au" | "nz")) => {
+                Some(locale('.', Some(','), "%d/%m/%Y", Before))
+            }
+            ("en", _) => Some(locale('.', Some(','), "%m/%d/%Y", Before)),
+            ("de", _) => Some(locale(',', Some('.'), "%d.%m.%Y", After)),
+            ("fr", _) => Some(locale(',', Some('\u{202F}'), "%d/%m/%Y", After)),
+            ("es" | "it", _) => Some(locale(',', Some('.'), "%d/%m/%Y", After)),
+            ("nl", _) => Some(locale(',', Some('.'), "%d-%m-%Y", Before)),
+            ("sv", _) => Some(locale(',', Some('\u{00A0}'), "%Y-%m-%d", After)),
+            ("ja", _) => Some(locale('.', Some(','), "%Y/%m/%d", Before)),
+            _ => None,
+        }
+    }
+
+    /// Format a number, optionally with a fixed number of decimals.
+    pub fn number(&self, value: &serde_json::Number, decimals: Option<usize>) -> String {
+        let raw = match (decimals, value.as_f64()) {
+            (Some(decimals), Some(float)) => format!("{:.*}", decimals, float),
+            _ => value.to_string(),
+        };
+        let (sign, digits) = match raw.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", raw.as_str()),
+        };
+        let (whole, fraction) = match digits.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (digits, None),
+        };
+
+        let mut out = String::from(sign);
+        for (i, c) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                if let Some(group) = self.group {
+                    out.push(group);
+                }
+            }
+            out.push(c);
+        }
+        if let Some(fraction) = fraction {
+            out.push(self.decimal);
+            out.push_str(fraction);
+        }
+        out
+    }
+
+    /// Format an amount in a currency, e.g. `$1,234.50` or `1.234,50 €`.
+    pub fn currency(
+        &self,
+        value: &serde_json::Number,
+        code: &str,
+        decimals: Option<usize>,
+    ) -> String {
+        let code = code.to_ascii_uppercase();
+        let (symbol, default_decimals) = match code.as_str() {
+            "USD" => ("$", 2),
+            "EUR" => ("\u{20AC}", 2),
+            "GBP" => ("\u{00A3}", 2),
+            "JPY" => ("\u{00A5}", 0),
+            _ => (code.as_str(), 2),
+        };
+        let amount = self.number(value, Some(decimals.unwrap_or(default_decimals)));
+        let spaced = symbol.chars().count() > 1;
+        match (self.symbol, spaced) {
+            (SymbolPosition::Before, false) => format!("{}{}", symbol, amount),
+            (SymbolPosition::Before, true) => format!("{} {}", symbol, amount),
+            (SymbolPosition::After, _) => format!("{} {}", amount, symbol),
+        }
+    }
+
+    /// Format a calendar date.
+    pub fn date(&self, date: &NaiveDate) -> String {
+        date.format(self.date_format).to_string()
+    }
+
+    /// Format a timestamp as date and 24-hour time.
+    pub fn datetime(&self, value: &DateTime<Utc>) -> String {
+        format!(
+            "{} {}",
+            self.date(&value.date_naive()),
+            value.format("%H:%M")
+        )
+    }
+}
+
+/// Formats entry fields using each entry type's active schema.
+pub struct FieldFormatter {
+    locale: Locale,
+    schemas: HashMap<Uuid, Vec<serde_json::Value>>,
+}
+
+impl FieldFormatter {
+    /// Load the active schema of every entry type.
+    pub fn load(storage: &AgeSqliteStorage, locale: Locale) -> anyhow::Result<Self> {
+        let schemas = storage
+            .list_entry_types()?
+            .into_iter()
+            .map(|entry_type| {
+                let mut fields = entry_type
+                    .schema_json
+                    .get("fields")
+                    .and_then(|f| f.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                fields.sort_by_key(|f| f.get("order").and_then(|o| o.as_i64()).unwrap_or(0));
+                (entry_type.id, fields)
+            })
+            .collect();
+        Ok(Self { locale, schemas })
+    }
+
+    pub fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    /// Formatted `(name, value)` pairs in schema order, skipping `skip`.
+    ///
+    /// Fields missing from the schema follow in data order with raw values.
+    pub fn fields(&self, entry: &Entry, skip: &[&str]) -> Vec<(String, String)> {
+        let Some(data) = entry.data.as_object() else {
+            return Vec::new();
+        };
+        let schema = self
+            .schemas
+            .get(&entry.entry_type_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let mut out = Vec::new();
+        for field in schema {
+            let Some(name) = field.get("name").and_then(|n| n.as_str()) else {
+                continue;
+            };
+            match data.get(name) {
+                Some(value) if !value.is_null() && !skip.contains(&name) => {
+                    out.push((name.to_string(), self.value(field, value)));
+                }
+                _ => {}
+            }
+        }
+        for (name, value) in data {
+            let known = schema
+                .iter()
+                .any(|f| f.get("name").and_then(|n| n.as_str()) == Some(name));
+            if !known && !value.is_null() && !skip.contains(&name.as_str()) {
+                out.push((name.clone(), plain_value(value)));
+            }
+        }
+        out
+    }
+
+    /// One-line `name value` summary of an entry's fields.
+    pub fn summary(&self, entry: &Entry, unicode: bool) -> String {
+        self.fields(entry, &[])
+            .into_iter()
+            .map(|(name, value)| format!("{} {}", name, value))
+            .collect::<Vec<_>>()
+            .join(&format!(" {} ", symbols::SEPARATOR.get(unicode)))
+    }
+
+    fn value(&self, field: &serde_json::Value, value: &serde_json::Value) -> String {
+        let annotation = |key: &str| field.get(key).and_then(|v| v.as_str());
+        let decimals = field
+            .get("decimals")
+            .and_then(|v| v.as_u64())
+            .map(|d| d as usize);
+        match value {
+            serde_json::Value::Number(number) => {
+                let formatted = match annotation("currency") {
+                    Some(code) => self.locale.currency(number, code, decimals),
+                    None => self.locale.number(number, decimals),
+                };
+                match annotation("unit") {
+                    Some(unit) => format!("{} {}", formatted, unit),
+                    None => formatted,
+                }
+            }
+            serde_json::Value::String(raw) => match annotation("type") {
+                Some("date") => NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                    .map(|date| self.locale.date(&date))
+                    .unwrap_or_else(|_| raw.clone()),
+                Some("datetime") => DateTime::parse_from_rfc3339(raw)
+                    .map(|dt| self.locale.datetime(&dt.with_timezone(&Utc)))
+                    .unwrap_or_else(|_| raw.clone()),
+                // Binary payloads are summarized; JSON output keeps the base64
+                Some("bytes") => match BASE64.decode(raw) {
+                    Ok(data) => format!("<binary, {}>", format_bytes(data.len() as u64)),
+                    Err(_) => raw.clone(),
+                },
+                _ => raw.clone(),
+            },
+            other => plain_value(other),
+        }
+    }
+}
+
+fn plain_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn number(value: serde_json::Value) -> serde_json::Number {
+        match value {
+            serde_json::Value::Number(n) => n,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_locale_parse_variants() {
+        assert_eq!(Locale::parse("de_DE.UTF-8"), Locale::parse("de"));
+        assert!(Locale::parse("en-GB").is_some());
+        assert!(Locale::parse("tlh").is_none());
+        for tag in SUPPORTED_LOCALES {
+            assert!(Locale::parse(tag).is_some(), "{} should parse", tag);
+        }
+    }
+
+    #[test]
+    fn test_number_grouping_and_decimals() {
+        let us = Locale::parse("en-US").unwrap();
+        let de = Locale::parse("de-DE").unwrap();
+        let value = number(serde_json::json!(1234567.5));
+        assert_eq!(us.number(&value, None), "1,234,567.5");
+        assert_eq!(de.number(&value, Some(2)), "1.234.567,50");
+        assert_eq!(Locale::default().number(&value, None), "1234567.5");
+        assert_eq!(us.number(&number(serde_json::json!(-1200)), None), "-1,200");
+        assert_eq!(us.number(&number(serde_json::json!(73.5)), None), "73.5");
+    }
+
+    #[test]
+    fn test_currency_symbol_placement() {
+        let value = number(serde_json::json!(1234.5));
+        assert_eq!(
+            Locale::parse("en-US")
+                .unwrap()
+                .currency(&value, "usd", None),
+            "$1,234.50"
+        );
+        assert_eq!(
+            Locale::parse("de-DE")
+                .unwrap()
+                .currency(&value, "EUR", None),
+            "1.234,50 \u{20AC}"
+        );
+        assert_eq!(
+            Locale::parse("en-US")
+                .unwrap()
+                .currency(&value, "CHF", None),
+            "CHF 1,234.50"
+        );
+    }
+
+    #[test]
+    fn test_dates_follow_locale() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        assert_eq!(Locale::parse("en-US").unwrap().date(&date), "03/09/2024");
+        assert_eq!(Locale::parse("de").unwrap().date(&date), "09.03.2024");
+        let at = Utc.with_ymd_and_hms(2024, 3, 9, 14, 5, 0).unwrap();
+        assert_eq!(Locale::default().datetime(&at), "2024-03-09 14:05");
+    }
+
+    #[test]
+    fn test_formatter_applies_schema_annotations() {
+        let type_id = Uuid::new_v4();
+        let formatter = FieldFormatter {
+            locale: Locale::parse("en-US").unwrap(),
+            schemas: HashMap::from([(
+                type_id,
+                vec![
+                    serde_json::json!({"name": "weight", "type": "number", "unit": "kg"}),
+                    serde_json::json!({"name": "cost", "type": "number", "currency": "USD"}),
+                    serde_json::json!({"name": "on", "type": "date"}),
+                ],
+            )]),
+        };
+        let entry = Entry {
+            id: Uuid::new_v4(),
+            entry_type_id: type_id,
+            schema_version: 1,
+            data: serde_json::json!({"weight": 73.5, "cost": 12, "on": "2024-03-09"}),
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            seq: 0,
+            device_id: Uuid::new_v4(),
+            supersedes: None,
+        };
+        assert_eq!(
+            formatter.fields(&entry, &["on"]),
+            vec![
+                ("weight".to_string(), "73.5 kg".to_string()),
+                ("cost".to_string(), "$12.00".to_string()),
+            ]
+        );
+        assert_eq!(
+            formatter.summary(&entry, true),
+            "weight 73.5 kg \u{00B7} cost $12.00 \u{00B7} on 03/09/2024"
+        );
+        assert_eq!(
+            formatter.summary(&entry, false),
+            "weight 73.5 kg - cost $12.00 - on 03/09/2024"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Streaming JSON output for entries.
+//!
+//! Large result sets are written one entry at a time instead of being built
+//! into a `serde_json::Value` tree first, so memory stays flat however many
+//! entries are printed.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::io::Write;
+
+use ledger_core::storage::Entry;
+use ledger_core::views::EntryView;
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use uuid::Uuid;
+
+/// The shared view of `entry`, with its type's name from `name_map`.
+fn entry_view(entry: &Entry, name_map: &HashMap<Uuid, String>) -> EntryView {
+    let name = name_map
+        .get(&entry.entry_type_id)
+        .map(String::as_str)
+        .unwrap_or("unknown");
+    EntryView::new(entry, name)
+}
+
+/// Write entries as a pretty-printed JSON array followed by a newline.
+pub fn write_entries_json<W, E>(
+    out: W,
+    entries: impl IntoIterator<Item = ledger_core::Result<E>>,
+    name_map: &HashMap<Uuid, String>,
+) -> anyhow::Result<()>
+where
+    W: Write,
+    E: Borrow<Entry>,
+{
+    write_json_array(
+        out,
+        entries
+            .into_iter()
+            .map(|entry| Ok(entry_view(entry?.borrow(), name_map))),
+    )
+}
+
+/// Write items as a pretty-printed JSON array followed by a newline.
+pub fn write_json_array<W, T>(
+    out: W,
+    items: impl IntoIterator<Item = anyhow::Result<T>>,
+) -> anyhow::Result<()>
+where
+    W: Write,
+    T: Serialize,
+{
+    let mut serializer = serde_json::Serializer::pretty(out);
+    let mut array = serializer.serialize_seq(None)?;
+    for item in items {
+        array.serialize_element(&item?)?;
+    }
+    array.end()?;
+    writeln!(serializer.into_inner())?;
+    Ok(())
+}
+
+/// Write entries as JSON Lines, one compact object per line.
+pub fn write_entries_jsonl<W, E>(
+    mut out: W,
+    entries: impl IntoIterator<Item = ledger_core::Result<E>>,
+    name_map: &HashMap<Uuid, String>,
+) -> anyhow::Result<()>
+where
+    W: Write,
+    E: Borrow<Entry>,
+{
+    for entry in entries {
+        serde_json::to_writer(&mut out, &entry_view(entry?.borrow(), name_map))?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{entries_json, entry_json};
+    use chrono::Utc;
+
+    fn sample_entries() -> Vec<Entry> {
+        let entry_type_id = Uuid::new_v4();
+        let first = Entry {
+            id: Uuid::new_v4(),
+            entry_type_id,
+            schema_version: 2,
+            data: serde_json::json!({"body": "Walked \"home\"", "mood": 4, "nested": {"b": 1, "a": [1, 2]}}),
+            tags: vec!["home".to_string(), "walk".to_string()],
+            created_at: Utc::now(),
+            seq: 7,
+            device_id: Uuid::new_v4(),
+            supersedes: None,
+        };
+        let second = Entry {
+            id: Uuid::new_v4(),
+            entry_type_id: Uuid::new_v4(),
+            tags: Vec::new(),
+            seq: 8,
+            supersedes: Some(first.id),
+            ..first.clone()
+        };
+        vec![first, second]
+    }
+
+    #[test]
+    fn test_streamed_json_matches_value_output() {
+        let entries = sample_entries();
+        let name_map = HashMap::from([(entries[0].entry_type_id, "journal".to_string())]);
+
+        let mut streamed = Vec::new();
+        write_entries_json(&mut streamed, entries.iter().map(Ok), &name_map).unwrap();
+        let expected = format!(
+            "{}\n",
+            serde_json::to_string_pretty(&entries_json(&entries, &name_map)).unwrap()
+        );
+        assert_eq!(String::from_utf8(streamed).unwrap(), expected);
+
+        let mut lines = Vec::new();
+        write_entries_jsonl(&mut lines, entries.iter().map(Ok), &name_map).unwrap();
+        let expected: String = entries
+            .iter()
+            .map(|e| format!("{}\n", entry_json(e, &name_map)))
+            .collect();
+        assert_eq!(String::from_utf8(lines).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_streamed_json_empty() {
+        let mut out = Vec::new();
+        write_entries_json(
+            &mut out,
+            Vec::<ledger_core::Result<Entry>>::new(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert_eq!(out, b"[]\n");
+    }
+
+    #[test]
+    fn test_streamed_json_stops_at_error() {
+        let entries = sample_entries();
+        let items = vec![
+            Ok(entries[0].clone()),
+            Err(ledger_core::LedgerError::Storage("boom".to_string())),
+        ];
+        let mut out = Vec::new();
+        let err = write_entries_json(&mut out, items, &HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("boom"));
+    }
+}
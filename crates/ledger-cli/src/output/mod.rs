@@ -1,30 +1,43 @@
 //! Output formatting helpers for the CLI.
 //!
 //! This module provides formatting utilities for displaying entries
-//! in various formats (JSON output, name maps for display).
+//! in various formats (JSON output, streamed or built, YAML/TOML documents, Atom feeds, digests, locale-aware field
+//! values, templated summaries, name maps for display, mutation receipts).
 
-use std::collections::HashMap;
+mod atom;
+mod digest;
+mod document;
+mod fields;
+mod json;
+mod receipt;
+mod summary;
 
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
 use ledger_core::storage::{AgeSqliteStorage, Entry, StorageEngine};
+use ledger_core::views::EntryView;
 use uuid::Uuid;
 
+use crate::helpers::builtin_schema;
+use crate::ui::theme::TypeBadge;
+
+pub use atom::{atom_feed, FeedEntry};
+pub use digest::{Digest, DigestFormat, DigestItem, DigestPeriod, DigestSection, DigestStreak};
+pub use document::DocumentFormat;
+pub use fields::{FieldFormatter, Locale, SUPPORTED_LOCALES};
+pub use json::{write_entries_json, write_entries_jsonl, write_json_array};
+pub use receipt::Receipt;
+pub use summary::EntrySummaries;
+
 /// Convert an entry to JSON for output.
 pub fn entry_json(entry: &Entry, name_map: &HashMap<Uuid, String>) -> serde_json::Value {
     let entry_type_name = name_map
         .get(&entry.entry_type_id)
-        .cloned()
-        .unwrap_or_else(|| "unknown".to_string());
-    serde_json::json!({
-        "id": entry.id,
-        "entry_type_id": entry.entry_type_id,
-        "entry_type_name": entry_type_name,
-        "schema_version": entry.schema_version,
-        "created_at": entry.created_at,
-        "device_id": entry.device_id,
-        "tags": entry.tags,
-        "data": entry.data,
-        "supersedes": entry.supersedes,
-    })
+        .map(String::as_str)
+        .unwrap_or("unknown");
+    serde_json::to_value(EntryView::new(entry, entry_type_name))
+        .expect("entry views serialize to JSON")
 }
 
 /// Convert multiple entries to JSON array for output.
@@ -35,6 +48,34 @@ pub fn entries_json(entries: &[Entry], name_map: &HashMap<Uuid, String>) -> Vec<
         .collect()
 }
 
+/// An entry as stored, with its compositions and attachments.
+///
+/// Used by `--dry-run` to show what would be written.
+pub fn stored_entry_json(
+    storage: &AgeSqliteStorage,
+    id: &Uuid,
+) -> anyhow::Result<serde_json::Value> {
+    let entry = storage
+        .get_entry(id)?
+        .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", id))?;
+    let mut value = entry_json(&entry, &entry_type_name_map(storage)?);
+    value["compositions"] = serde_json::json!(storage
+        .get_entry_compositions(id)?
+        .iter()
+        .map(|c| c.name.clone())
+        .collect::<Vec<_>>());
+    value["attachments"] = serde_json::json!(storage
+        .list_attachments(id)?
+        .iter()
+        .map(|a| serde_json::json!({
+            "name": a.name,
+            "mime_type": a.mime_type,
+            "size": a.size,
+        }))
+        .collect::<Vec<_>>());
+    Ok(value)
+}
+
 /// Build a map of entry type ID -> name for display.
 pub fn entry_type_name_map(storage: &AgeSqliteStorage) -> anyhow::Result<HashMap<Uuid, String>> {
     let types = storage.list_entry_types()?;
@@ -44,3 +85,66 @@ pub fn entry_type_name_map(storage: &AgeSqliteStorage) -> anyhow::Result<HashMap
     }
     Ok(map)
 }
+
+/// Build a map of entry type ID -> badge (icon, name, color) for display.
+///
+/// Built-in types created before they declared an icon and color fall back
+/// to the ones they ship with now.
+pub fn entry_type_badges(storage: &AgeSqliteStorage) -> anyhow::Result<HashMap<Uuid, TypeBadge>> {
+    let mut map = HashMap::new();
+    for entry_type in storage.list_entry_types()? {
+        let builtin = builtin_schema(&entry_type.name).unwrap_or_default();
+        let fallback = |key: &str| builtin.get(key).and_then(|value| value.as_str());
+        let badge = TypeBadge {
+            icon: entry_type
+                .icon()
+                .or_else(|| fallback("icon"))
+                .map(str::to_string),
+            color: entry_type
+                .color()
+                .or_else(|| fallback("color"))
+                .map(str::to_string),
+            name: entry_type.name.clone(),
+        };
+        map.insert(entry_type.id, badge);
+    }
+    Ok(map)
+}
+
+/// Most recent view of any revision in the chain ending at `entry_id`.
+///
+/// `parents` maps each revision to the entry it supersedes. Editing an entry
+/// creates a new revision, which should not make it look unread again.
+pub fn chain_last_viewed(
+    entry_id: Uuid,
+    parents: &HashMap<Uuid, Uuid>,
+    viewed: &HashMap<Uuid, DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    let mut latest = viewed.get(&entry_id).copied();
+    let mut current = entry_id;
+    let mut seen = HashSet::new();
+    while let Some(&parent) = parents.get(&current) {
+        if !seen.insert(current) {
+            break;
+        }
+        current = parent;
+        latest = latest.max(viewed.get(&current).copied());
+    }
+    latest
+}
+
+/// First revision in the chain that `entry` belongs to.
+///
+/// Revisions share their root's identity in outputs that must stay stable
+/// across edits (vault paths, feed ids).
+pub fn chain_root<'a>(entry: &'a Entry, by_id: &HashMap<Uuid, &'a Entry>) -> &'a Entry {
+    let mut current = entry;
+    let mut seen = HashSet::new();
+    while let Some(parent) = current.supersedes.and_then(|id| by_id.get(&id)) {
+        if !seen.insert(current.id) {
+            break;
+        }
+        current = parent;
+    }
+    current
+}
@@ -0,0 +1,82 @@
+//! Structured receipts printed by mutating commands under `--json`.
+
+use chrono::{DateTime, Utc};
+use ledger_core::views::{AffectedView, ReceiptView};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Builds and prints the shared [`ReceiptView`].
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Receipt(ReceiptView);
+
+impl Receipt {
+    /// Start a receipt for `operation` on the record `id` of type `kind`.
+    pub fn new(operation: &str, kind: &str, id: Uuid, name: Option<&str>) -> Self {
+        Self(ReceiptView {
+            operation: operation.to_string(),
+            id,
+            created_at: Utc::now(),
+            affected: vec![AffectedView {
+                kind: kind.to_string(),
+                id,
+                name: name.map(str::to_string),
+            }],
+            replayed: false,
+        })
+    }
+
+    /// Use the record's own timestamp instead of the time of the command.
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.0.created_at = created_at;
+        self
+    }
+
+    /// Mark the receipt as a replay of an earlier run.
+    pub fn replayed(mut self) -> Self {
+        self.0.replayed = true;
+        self
+    }
+
+    /// Record another touched record.
+    pub fn affects(mut self, kind: &str, id: Uuid, name: Option<&str>) -> Self {
+        self.0.affected.push(AffectedView {
+            kind: kind.to_string(),
+            id,
+            name: name.map(str::to_string),
+        });
+        self
+    }
+
+    /// Print the receipt as pretty JSON on stdout.
+    pub fn print(&self) -> anyhow::Result<()> {
+        println!("{}", serde_json::to_string_pretty(self)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_receipt_lists_primary_record_first() {
+        let entry = Uuid::new_v4();
+        let composition = Uuid::new_v4();
+        let receipt = Receipt::new("attach", "entry", entry, None).affects(
+            "composition",
+            composition,
+            Some("trip"),
+        );
+        let value = serde_json::to_value(&receipt).unwrap();
+        assert_eq!(value["operation"], "attach");
+        assert_eq!(value["id"], entry.to_string());
+        assert_eq!(
+            value["affected"],
+            serde_json::json!([
+                {"kind": "entry", "id": entry},
+                {"kind": "composition", "id": composition, "name": "trip"},
+            ])
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! One-line entry summaries that honor each type's `summary_template`.
+
+use std::collections::HashMap;
+
+use ledger_core::storage::{
+    AgeSqliteStorage, Entry, EntryTypeRecord, StorageEngine, SummaryTemplate,
+};
+use uuid::Uuid;
+
+use crate::ui::entry_summary;
+
+/// Summary templates by entry type ID.
+///
+/// Types without a template, and entries whose template fields are all
+/// empty, fall back to the body-derived [`entry_summary`].
+pub struct EntrySummaries {
+    templates: HashMap<Uuid, SummaryTemplate>,
+}
+
+impl EntrySummaries {
+    /// Load the active template of every entry type.
+    pub fn load(storage: &AgeSqliteStorage) -> anyhow::Result<Self> {
+        let templates = storage
+            .list_entry_types()?
+            .into_iter()
+            .filter_map(|entry_type| Some((entry_type.id, entry_type.summary_template()?)))
+            .collect();
+        Ok(Self { templates })
+    }
+
+    /// Templates from exported type records (share bundles), using each
+    /// type's active version.
+    pub fn from_records(records: &[EntryTypeRecord]) -> Self {
+        let templates = records
+            .iter()
+            .filter_map(|record| {
+                let version = record.versions.iter().rev().find(|v| v.active)?;
+                let template = version.body.get("summary_template")?.as_str()?;
+                Some((record.id, SummaryTemplate::parse(template)?))
+            })
+            .collect();
+        Self { templates }
+    }
+
+    /// The entry's templated summary, if its type has a template that
+    /// produced any text.
+    pub fn template_summary(&self, entry: &Entry) -> Option<String> {
+        self.templates
+            .get(&entry.entry_type_id)?
+            .render(&entry.data)
+    }
+
+    /// Templated summary, else the body-derived one.
+    pub fn summary(&self, entry: &Entry) -> String {
+        self.template_summary(entry)
+            .unwrap_or_else(|| entry_summary(entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn entry(type_id: Uuid, data: serde_json::Value) -> Entry {
+        Entry {
+            id: Uuid::new_v4(),
+            entry_type_id: type_id,
+            schema_version: 1,
+            data,
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            seq: 0,
+            device_id: Uuid::new_v4(),
+            supersedes: None,
+        }
+    }
+
+    #[test]
+    fn test_summary_prefers_template_then_body() {
+        let weight = Uuid::new_v4();
+        let summaries = EntrySummaries {
+            templates: HashMap::from([(
+                weight,
+                SummaryTemplate::parse("{weight} kg \u{2014} {notes}").unwrap(),
+            )]),
+        };
+        assert_eq!(
+            summaries.summary(&entry(
+                weight,
+                serde_json::json!({"weight": 80, "notes": "morning"})
+            )),
+            "80 kg \u{2014} morning"
+        );
+        let journal = entry(Uuid::new_v4(), serde_json::json!({"body": "Hello"}));
+        assert_eq!(summaries.summary(&journal), "Hello");
+        assert_eq!(summaries.template_summary(&journal), None);
+    }
+}
@@ -0,0 +1,220 @@
+//! Second encrypted replica, kept current on every write.
+//!
+//! After each successful save the CLI spawns a detached `internal-replicate`
+//! process so the command itself never waits on slow or removable media. The
+//! replicator copies the already-encrypted ledger file (the passphrase never
+//! leaves the saving process), reads the copy back to verify it, and retries
+//! with exponential backoff while the destination is unavailable. If the
+//! ledger changed again while it worked it goes round once more, so whichever
+//! replicator finishes last leaves the current file behind.
+//!
+//! The replica's directory is never created: an unmounted drive must fail
+//! and retry, not fill a directory on the root filesystem.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Attempts before a sync gives up (about 30 seconds of backoff in total).
+pub const MAX_ATTEMPTS: u32 = 6;
+
+/// Wait before the first retry; doubled after each failure.
+const FIRST_RETRY: Duration = Duration::from_secs(1);
+
+/// Outcome of the last replica sync, kept in `<ledger>.replica`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicaRecord {
+    pub at: DateTime<Utc>,
+    pub destination: String,
+    pub ok: bool,
+    pub bytes: u64,
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Path of the replica record for a ledger.
+pub fn replica_record_path(ledger_path: &Path) -> PathBuf {
+    let mut name = ledger_path.as_os_str().to_os_string();
+    name.push(".replica");
+    PathBuf::from(name)
+}
+
+/// Read the last replica record; missing or unreadable means none.
+pub fn read_replica_record(ledger_path: &Path) -> Option<ReplicaRecord> {
+    std::fs::read_to_string(replica_record_path(ledger_path))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+}
+
+/// Start a detached replicator for `ledger_path` and return at once.
+pub fn spawn_replica_sync(ledger_path: &Path, replica_path: &Path) -> anyhow::Result<()> {
+    let exe = std::env::current_exe().map_err(|e| anyhow::anyhow!("{}", e))?;
+    std::process::Command::new(exe)
+        .arg("internal-replicate")
+        .arg("--source")
+        .arg(ledger_path)
+        .arg("--dest")
+        .arg(replica_path)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to spawn replicator: {}", e))?;
+    Ok(())
+}
+
+/// Copy and verify the ledger at `source` to `dest`, retrying with backoff,
+/// and record the outcome next to the ledger.
+pub fn run_replica_sync(source: &Path, dest: &Path) -> anyhow::Result<ReplicaRecord> {
+    run_with_backoff(source, dest, FIRST_RETRY)
+}
+
+fn run_with_backoff(
+    source: &Path,
+    dest: &Path,
+    first_retry: Duration,
+) -> anyhow::Result<ReplicaRecord> {
+    let mut delay = first_retry;
+    let mut attempts = 0;
+    let outcome = loop {
+        attempts += 1;
+        match replicate_once(source, dest) {
+            Ok((bytes, written)) => {
+                // A newer save landed mid-copy; its own replicator may already be done
+                if file_hash(source).ok() != Some(written) && attempts < MAX_ATTEMPTS {
+                    continue;
+                }
+                break Ok(bytes);
+            }
+            Err(_) if attempts < MAX_ATTEMPTS => {
+                std::thread::sleep(delay);
+                delay *= 2;
+            }
+            Err(err) => break Err(err),
+        }
+    };
+
+    let record = ReplicaRecord {
+        at: Utc::now(),
+        destination: dest.display().to_string(),
+        ok: outcome.is_ok(),
+        bytes: *outcome.as_ref().unwrap_or(&0),
+        attempts,
+        error: outcome.as_ref().err().map(|err| err.to_string()),
+    };
+    std::fs::write(replica_record_path(source), serde_json::to_string(&record)?)
+        .map_err(|e| anyhow::anyhow!("Failed to record replica sync: {}", e))?;
+    outcome.map(|_| record)
+}
+
+/// One copy: write through a temporary file, rename it into place and read
+/// it back. Returns the size and hash of what was written.
+fn replicate_once(source: &Path, dest: &Path) -> anyhow::Result<(u64, blake3::Hash)> {
+    let data = std::fs::read(source)
+        .map_err(|e| anyhow::anyhow!("Failed to read ledger {}: {}", source.display(), e))?;
+    let hash = blake3::hash(&data);
+
+    let parent = dest
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    if !parent.is_dir() {
+        return Err(anyhow::anyhow!(
+            "Replica directory {} is not available",
+            parent.display()
+        ));
+    }
+    let file_name = dest
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Replica path {} has no file name", dest.display()))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = parent.join(temp_name);
+
+    let write = || -> std::io::Result<()> {
+        let mut file = std::fs::File::create(&temp_path)?;
+        file.write_all(&data)?;
+        file.sync_all()
+    };
+    if let Err(err) = write() {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(anyhow::anyhow!(
+            "Failed to write replica {}: {}",
+            dest.display(),
+            err
+        ));
+    }
+    ledger_core::fs::rename_with_fallback(&temp_path, dest).map_err(|e| {
+        let _ = std::fs::remove_file(&temp_path);
+        anyhow::anyhow!("Failed to write replica {}: {}", dest.display(), e)
+    })?;
+
+    if file_hash(dest)? != hash {
+        return Err(anyhow::anyhow!(
+            "Replica verification failed: {} does not match the ledger",
+            dest.display()
+        ));
+    }
+    Ok((data.len() as u64, hash))
+}
+
+fn file_hash(path: &Path) -> anyhow::Result<blake3::Hash> {
+    let data = std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    Ok(blake3::hash(&data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "ledger_replica_{}_{}_{}",
+            name,
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_sync_copies_verifies_and_records() {
+        let dir = temp_dir("copy");
+        let source = dir.join("ledger.age");
+        let dest = dir.join("mirror").join("ledger.replica");
+        std::fs::write(&source, b"encrypted bytes").unwrap();
+        std::fs::create_dir(dir.join("mirror")).unwrap();
+
+        let record = run_with_backoff(&source, &dest, Duration::ZERO).unwrap();
+        assert!(record.ok);
+        assert_eq!(record.attempts, 1);
+        assert_eq!(record.bytes, 15);
+        assert_eq!(std::fs::read(&dest).unwrap(), b"encrypted bytes");
+        assert!(read_replica_record(&source).unwrap().ok);
+        // No temporary files left beside the replica
+        assert_eq!(std::fs::read_dir(dir.join("mirror")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_missing_destination_retries_then_records_failure() {
+        let dir = temp_dir("missing");
+        let source = dir.join("ledger.age");
+        let dest = dir.join("unmounted").join("ledger.replica");
+        std::fs::write(&source, b"encrypted bytes").unwrap();
+
+        let err = run_with_backoff(&source, &dest, Duration::ZERO).unwrap_err();
+        assert!(err.to_string().contains("not available"));
+        assert!(!dir.join("unmounted").exists());
+        let record = read_replica_record(&source).unwrap();
+        assert!(!record.ok);
+        assert_eq!(record.attempts, MAX_ATTEMPTS);
+        assert!(record.error.unwrap().contains("not available"));
+    }
+}
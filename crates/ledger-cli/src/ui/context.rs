@@ -3,6 +3,7 @@
 use std::io::IsTerminal;
 
 use super::mode::OutputMode;
+use super::theme::{Palette, ThemeName};
 
 /// Terminal and environment context for UI decisions.
 #[derive(Debug, Clone)]
@@ -18,6 +19,10 @@ pub struct UiContext {
     pub width: usize,
     /// Resolved output mode
     pub mode: OutputMode,
+    /// Palette shades from `[ui] theme`
+    pub theme: ThemeName,
+    /// Status and accent colors: the theme's preset plus `[ui.theme]` colors
+    pub palette: Palette,
 }
 
 impl UiContext {
@@ -56,9 +61,27 @@ impl UiContext {
             unicode,
             width,
             mode,
+            theme: ThemeName::default(),
+            palette: Palette::default(),
         }
     }
 
+    /// Apply the `[ui] theme`; `none` disables color like `NO_COLOR`.
+    pub fn with_theme(mut self, theme: ThemeName) -> Self {
+        self.theme = theme;
+        self.palette = Palette::preset(theme);
+        if theme == ThemeName::None {
+            self.color = false;
+        }
+        self
+    }
+
+    /// Use `palette` for badges, spinners and progress bars.
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
     /// Check if interactive prompts are allowed.
     #[allow(dead_code)]
     pub fn is_interactive(&self) -> bool {
@@ -125,6 +148,13 @@ mod tests {
         assert!(!ctx.color);
     }
 
+    #[test]
+    fn test_none_theme_disables_color() {
+        let ctx = UiContext::from_env(false, None, false, false).with_theme(ThemeName::None);
+        assert!(!ctx.color);
+        assert_eq!(ctx.theme, ThemeName::None);
+    }
+
     #[test]
     fn test_width_has_default() {
         let ctx = UiContext::from_env(false, None, false, false);
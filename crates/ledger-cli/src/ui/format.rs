@@ -5,13 +5,19 @@ use ledger_core::storage::Entry;
 use uuid::Uuid;
 
 /// Extract a summary from an entry's data, preferring the "body" field.
+///
+/// Bookmark and photo entries have no body, so fall back to their title,
+/// caption, URL, or file name.
 pub fn entry_summary(entry: &Entry) -> String {
-    entry
-        .data
-        .get("body")
-        .and_then(|v| v.as_str())
+    entry_text_summary(entry).unwrap_or_else(|| entry.data.to_string())
+}
+
+/// Like [`entry_summary`], but `None` for purely structured entries.
+pub fn entry_text_summary(entry: &Entry) -> Option<String> {
+    ["body", "title", "caption", "summary", "url", "file"]
+        .iter()
+        .find_map(|key| entry.data.get(*key).and_then(|v| v.as_str()))
         .map(String::from)
-        .unwrap_or_else(|| entry.data.to_string())
 }
 
 /// Truncate a string to max length, adding ellipsis if needed.
@@ -77,6 +83,72 @@ pub fn wrap(text: &str, width: usize) -> Vec<String> {
     lines
 }
 
+/// Soft-wrap an entry body for display.
+///
+/// Fenced code blocks pass through untouched, list items wrap with a hanging
+/// indent under their text, block quotes repeat their `>` marker, and words
+/// longer than the width are left whole rather than broken.
+pub fn wrap_body(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for line in text.split('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            lines.push(line.to_string());
+            continue;
+        }
+        if in_code || line.chars().count() <= width {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let (prefix, rest) = split_line_marker(line);
+        let continuation = if prefix.trim_start().starts_with('>') {
+            prefix.to_string()
+        } else {
+            " ".repeat(prefix.chars().count())
+        };
+        let available = width.saturating_sub(prefix.chars().count()).max(1);
+        for (i, wrapped) in wrap(rest, available).into_iter().enumerate() {
+            let lead = if i == 0 { prefix } else { &continuation };
+            lines.push(format!("{}{}", lead, wrapped));
+        }
+    }
+
+    lines
+}
+
+/// Split a line into its indentation plus list or quote marker, and the text.
+fn split_line_marker(line: &str) -> (&str, &str) {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+
+    let mut marker = ["- ", "* ", "+ ", "> "]
+        .iter()
+        .find(|m| rest.starts_with(**m))
+        .map(|m| m.len())
+        .or_else(|| {
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+            let after = &rest[digits..];
+            (digits > 0 && (after.starts_with(". ") || after.starts_with(") ")))
+                .then_some(digits + 2)
+        })
+        .unwrap_or(0);
+    if marker > 0 {
+        for checkbox in ["[ ] ", "[x] ", "[X] "] {
+            if rest[marker..].starts_with(checkbox) {
+                marker += checkbox.len();
+            }
+        }
+    }
+
+    let text = &rest[marker..];
+    let end = indent + marker + (text.len() - text.trim_start().len());
+    (&line[..end], &line[end..])
+}
+
 /// Format a short ID from a UUID (first 8 characters).
 pub fn short_id(id: &Uuid) -> String {
     id.to_string()[..8].to_string()
@@ -126,6 +198,34 @@ pub fn single_line(s: &str) -> String {
     s.replace('\n', " ").replace('\r', "")
 }
 
+/// Render a series as a one-line sparkline, scaled to its own range.
+///
+/// Missing values become spaces. Without unicode, ASCII ramp characters
+/// stand in for the block glyphs.
+pub fn sparkline(values: &[Option<f64>], unicode: bool) -> String {
+    const BLOCKS: [char; 8] = [
+        '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}',
+        '\u{2588}',
+    ];
+    const ASCII: [char; 8] = ['_', '.', ':', '-', '=', '+', '*', '#'];
+    let ramp = if unicode { &BLOCKS } else { &ASCII };
+
+    let present = values.iter().flatten().copied();
+    let min = present.clone().fold(f64::INFINITY, f64::min);
+    let max = present.fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|value| match value {
+            None => ' ',
+            Some(_) if max <= min => ramp[ramp.len() / 2],
+            Some(v) => {
+                let scaled = (v - min) / (max - min) * (ramp.len() - 1) as f64;
+                ramp[scaled.round() as usize]
+            }
+        })
+        .collect()
+}
+
 /// Highlight occurrences of a query in text (case-insensitive).
 ///
 /// When color is enabled, matches are styled with bold.
@@ -162,6 +262,39 @@ pub fn highlight_matches(text: &str, query: &str, color: bool) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sparkline_scales_to_range() {
+        let values = [Some(1.0), None, Some(5.0), Some(3.0)];
+        assert_eq!(sparkline(&values, true), "\u{2581} \u{2588}\u{2585}");
+        assert_eq!(sparkline(&values, false), "_ #=");
+        assert_eq!(sparkline(&[Some(2.0), Some(2.0)], false), "==");
+        assert_eq!(sparkline(&[None, None], false), "  ");
+    }
+
+    fn entry_with_data(data: serde_json::Value) -> Entry {
+        Entry {
+            id: Uuid::nil(),
+            entry_type_id: Uuid::nil(),
+            schema_version: 1,
+            data,
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            seq: 0,
+            device_id: Uuid::nil(),
+            supersedes: None,
+        }
+    }
+
+    #[test]
+    fn test_entry_summary_prefers_body_then_title_then_url() {
+        let entry = entry_with_data(serde_json::json!({"body": "hello", "title": "t"}));
+        assert_eq!(entry_summary(&entry), "hello");
+        let entry = entry_with_data(serde_json::json!({"url": "https://a.b", "title": "t"}));
+        assert_eq!(entry_summary(&entry), "t");
+        let entry = entry_with_data(serde_json::json!({"url": "https://a.b"}));
+        assert_eq!(entry_summary(&entry), "https://a.b");
+    }
+
     #[test]
     fn test_truncate_short() {
         assert_eq!(truncate("hello", 10), "hello");
@@ -206,6 +339,38 @@ mod tests {
         assert_eq!(lines, vec!["hello", "", "world"]);
     }
 
+    #[test]
+    fn test_wrap_body_hanging_indent_for_lists() {
+        let lines = wrap_body("- one two three four\n  12. alpha beta gamma", 12);
+        assert_eq!(
+            lines,
+            vec![
+                "- one two",
+                "  three four",
+                "  12. alpha",
+                "      beta",
+                "      gamma"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrap_body_keeps_code_and_long_words() {
+        let body = "```\nlet x = some_really_long_call(1, 2, 3);\n```\nsee https://example.com/a/very/long/path ok";
+        let lines = wrap_body(body, 16);
+        assert_eq!(lines[1], "let x = some_really_long_call(1, 2, 3);");
+        assert_eq!(
+            &lines[3..],
+            &["see", "https://example.com/a/very/long/path", "ok"]
+        );
+    }
+
+    #[test]
+    fn test_wrap_body_repeats_quote_marker() {
+        let lines = wrap_body("> quoted words that wrap", 12);
+        assert_eq!(lines, vec!["> quoted", "> words that", "> wrap"]);
+    }
+
     #[test]
     fn test_short_id() {
         let id = Uuid::parse_str("7a2e3c0b-1234-5678-9abc-def012345678").unwrap();
@@ -45,12 +45,15 @@ pub use theme::Badge;
 
 // Re-export commonly used render functions
 pub use render::{
-    badge, banner, blank_line, divider, header, header_with_context, hint, kv, print, print_error,
-    simple_table, Column,
+    badge, banner, blank_line, column_grid, divider, header, header_with_context, hint, kv, print,
+    print_error, simple_table, simple_table_cells, type_badge, type_badge_cell, Column, TableCell,
 };
 
 // Re-export progress types (for future P2 use)
 pub use progress::StepList;
 
 // Re-export commonly used format functions
-pub use format::{entry_summary, format_bytes, highlight_matches, short_id, truncate};
+pub use format::{
+    entry_summary, entry_text_summary, format_bytes, highlight_matches, short_id, single_line,
+    sparkline, truncate, wrap_body,
+};
@@ -17,7 +17,8 @@ impl OutputMode {
     ///
     /// Routing rules:
     /// 1. `--json` overrides everything (exclusive mode)
-    /// 2. `--format plain` forces plain
+    /// 2. `--format plain` forces plain; `--format table` forces pretty, so
+    ///    tables can be piped (colors still follow the terminal)
     /// 3. `TERM=dumb` forces plain
     /// 4. Pretty only when stdout is TTY
     /// 5. Default to plain for non-TTY
@@ -32,11 +33,14 @@ impl OutputMode {
             return Self::Json;
         }
 
-        // Rule 2: --format plain forces plain
+        // Rule 2: --format forces its mode
         if let Some(fmt) = format_flag {
             if fmt == "plain" {
                 return Self::Plain;
             }
+            if fmt == "table" {
+                return Self::Pretty;
+            }
         }
 
         // Rule 3: TERM=dumb forces plain
@@ -110,4 +114,12 @@ mod tests {
         let mode = OutputMode::resolve(false, Some("table"), true, false);
         assert_eq!(mode, OutputMode::Pretty);
     }
+
+    #[test]
+    fn test_table_format_forces_pretty_when_piped() {
+        let mode = OutputMode::resolve(false, Some("table"), false, false);
+        assert_eq!(mode, OutputMode::Pretty);
+        let mode = OutputMode::resolve(false, Some("table"), false, true);
+        assert_eq!(mode, OutputMode::Pretty);
+    }
 }
@@ -19,13 +19,16 @@ impl<'a> Spinner<'a> {
         let bar = if ctx.allows_animation() {
             let pb = IndicatifBar::new_spinner();
             let template = if ctx.unicode {
-                "{spinner:.cyan} {msg}..."
+                format!(
+                    "{{spinner:.{}}} {{msg}}...",
+                    ctx.palette.accent.ansi_index()
+                )
             } else {
-                "{spinner} {msg}..."
+                "{spinner} {msg}...".to_string()
             };
             pb.set_style(
                 ProgressStyle::default_spinner()
-                    .template(template)
+                    .template(&template)
                     .unwrap()
                     .tick_strings(if ctx.unicode {
                         &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏", ""]
@@ -107,13 +110,16 @@ impl<'a> ProgressBar<'a> {
         let bar = if ctx.allows_animation() {
             let pb = IndicatifBar::new(total);
             let template = if ctx.unicode {
-                "{msg} [{bar:20.cyan/dim}] {percent}%"
+                format!(
+                    "{{msg}} [{{bar:20.{}/dim}}] {{percent}}%",
+                    ctx.palette.accent.ansi_index()
+                )
             } else {
-                "{msg} [{bar:20}] {percent}%"
+                "{msg} [{bar:20}] {percent}%".to_string()
             };
             pb.set_style(
                 ProgressStyle::default_bar()
-                    .template(template)
+                    .template(&template)
                     .unwrap()
                     .progress_chars(if ctx.unicode { "━━─" } else { "=>-" }),
             );
@@ -272,6 +278,7 @@ impl<'a> StepList<'a> {
 mod tests {
     use super::*;
     use crate::ui::mode::OutputMode;
+    use crate::ui::theme::{Palette, ThemeName};
 
     fn test_ctx(animated: bool) -> UiContext {
         UiContext {
@@ -284,6 +291,8 @@ mod tests {
             } else {
                 OutputMode::Plain
             },
+            theme: ThemeName::Dark,
+            palette: Palette::default(),
         }
     }
 
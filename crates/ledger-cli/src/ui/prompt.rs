@@ -5,11 +5,12 @@
 
 use std::io::IsTerminal;
 
-use dialoguer::{theme::ColorfulTheme, Confirm, Input, Password, Select};
+use dialoguer::console::style;
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, MultiSelect, Password, Select};
 
 use super::context::UiContext;
 use super::render::{badge, blank_line, divider, hint, kv, print};
-use super::theme::{styled, styles, Badge};
+use super::theme::{styled, styles, symbols, Badge};
 
 /// A wizard step with a title and optional description.
 #[derive(Debug, Clone)]
@@ -63,7 +64,12 @@ impl<'a> Wizard<'a> {
         }
 
         let header = styled("Ledger", styles::bold(), self.ctx.color);
-        println!("{} \u{00B7} {}", header, self.title);
+        println!(
+            "{} {} {}",
+            header,
+            symbols::SEPARATOR.get(self.ctx.unicode),
+            self.title
+        );
         println!();
     }
 
@@ -152,14 +158,33 @@ impl<'a> Wizard<'a> {
         }
 
         println!();
-        let joined = hints.join("  \u{00B7}  ");
+        let joined = hints.join(&format!("  {}  ", symbols::SEPARATOR.get(self.ctx.unicode)));
         print(self.ctx, &hint(self.ctx, &joined));
     }
 }
 
+/// dialoguer's colorful theme, with ASCII markers under `--ascii`.
+pub fn prompt_theme(ctx: &UiContext) -> ColorfulTheme {
+    let theme = ColorfulTheme::default();
+    if ctx.unicode {
+        return theme;
+    }
+    ColorfulTheme {
+        prompt_suffix: style(">".to_string()).for_stderr().black().bright(),
+        success_prefix: style("+".to_string()).for_stderr().green(),
+        success_suffix: style("-".to_string()).for_stderr().black().bright(),
+        error_prefix: style("x".to_string()).for_stderr().red(),
+        active_item_prefix: style(">".to_string()).for_stderr().green(),
+        checked_item_prefix: style("[x]".to_string()).for_stderr().green(),
+        unchecked_item_prefix: style("[ ]".to_string()).for_stderr().magenta(),
+        picked_item_prefix: style(">".to_string()).for_stderr().green(),
+        ..theme
+    }
+}
+
 /// Prompt for text input with styled formatting.
 pub fn prompt_input(
-    _ctx: &UiContext,
+    ctx: &UiContext,
     prompt: &str,
     default: Option<&str>,
 ) -> anyhow::Result<String> {
@@ -169,7 +194,7 @@ pub fn prompt_input(
         ));
     }
 
-    let theme = ColorfulTheme::default();
+    let theme = prompt_theme(ctx);
     let builder = Input::<String>::with_theme(&theme).with_prompt(prompt);
 
     let result = if let Some(def) = default {
@@ -181,15 +206,79 @@ pub fn prompt_input(
     Ok(result)
 }
 
+/// Prompt for text input, re-prompting until `validate` accepts it.
+///
+/// `help` is printed dimmed above the prompt. With `allow_empty`, an empty
+/// answer skips validation and is returned as-is.
+pub fn prompt_validated<F>(
+    ctx: &UiContext,
+    prompt: &str,
+    help: Option<&str>,
+    default: Option<&str>,
+    allow_empty: bool,
+    validate: F,
+) -> anyhow::Result<String>
+where
+    F: Fn(&str) -> Result<(), String>,
+{
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "Interactive input required. Use flags or run on a TTY."
+        ));
+    }
+
+    print_field_help(ctx, help);
+    let theme = prompt_theme(ctx);
+    let mut builder = Input::<String>::with_theme(&theme)
+        .with_prompt(prompt)
+        .allow_empty(allow_empty)
+        .validate_with(|input: &String| -> Result<(), String> {
+            if allow_empty && input.is_empty() {
+                Ok(())
+            } else {
+                validate(input)
+            }
+        });
+    if let Some(def) = default {
+        builder = builder.default(def.to_string());
+    }
+
+    Ok(builder.interact_text()?)
+}
+
+/// Print a dimmed help line (e.g. a field description) above a prompt.
+pub fn print_field_help(ctx: &UiContext, help: Option<&str>) {
+    if let Some(text) = help {
+        if ctx.mode.is_pretty() {
+            println!("  {}", styled(text, styles::dim(), ctx.color));
+        }
+    }
+}
+
+/// Show a review screen of collected values and ask whether to proceed.
+pub fn confirm_review(
+    ctx: &UiContext,
+    items: &[(String, String)],
+    prompt: &str,
+) -> anyhow::Result<bool> {
+    if ctx.mode.is_pretty() {
+        for (key, value) in items {
+            println!("   {}", kv(ctx, key, value));
+        }
+        println!();
+    }
+    prompt_confirm(ctx, prompt, true)
+}
+
 /// Prompt for password input with confirmation.
-pub fn prompt_passphrase(_ctx: &UiContext, confirm: bool) -> anyhow::Result<String> {
+pub fn prompt_passphrase(ctx: &UiContext, confirm: bool) -> anyhow::Result<String> {
     if !std::io::stdin().is_terminal() {
         return Err(anyhow::anyhow!(
             "Interactive passphrase input required. Set LEDGER_PASSPHRASE or run on a TTY."
         ));
     }
 
-    let theme = ColorfulTheme::default();
+    let theme = prompt_theme(ctx);
     let builder = Password::with_theme(&theme).with_prompt("Passphrase");
 
     let result = if confirm {
@@ -205,7 +294,7 @@ pub fn prompt_passphrase(_ctx: &UiContext, confirm: bool) -> anyhow::Result<Stri
 
 /// Prompt for selection from a list of options.
 pub fn prompt_select(
-    _ctx: &UiContext,
+    ctx: &UiContext,
     prompt: &str,
     options: &[&str],
     default: usize,
@@ -216,7 +305,7 @@ pub fn prompt_select(
         ));
     }
 
-    let theme = ColorfulTheme::default();
+    let theme = prompt_theme(ctx);
     let result = Select::with_theme(&theme)
         .with_prompt(prompt)
         .items(options)
@@ -226,15 +315,38 @@ pub fn prompt_select(
     Ok(result)
 }
 
+/// Prompt for any number of options; returns the chosen indices.
+pub fn prompt_multi_select(
+    ctx: &UiContext,
+    prompt: &str,
+    options: &[&str],
+    defaults: &[bool],
+) -> anyhow::Result<Vec<usize>> {
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "Interactive selection required. Use flags or run on a TTY."
+        ));
+    }
+
+    let theme = prompt_theme(ctx);
+    let result = MultiSelect::with_theme(&theme)
+        .with_prompt(prompt)
+        .items(options)
+        .defaults(defaults)
+        .interact()?;
+
+    Ok(result)
+}
+
 /// Prompt for confirmation.
-pub fn prompt_confirm(_ctx: &UiContext, prompt: &str, default: bool) -> anyhow::Result<bool> {
+pub fn prompt_confirm(ctx: &UiContext, prompt: &str, default: bool) -> anyhow::Result<bool> {
     if !std::io::stdin().is_terminal() {
         return Err(anyhow::anyhow!(
             "Interactive confirmation required. Use flags or run on a TTY."
         ));
     }
 
-    let theme = ColorfulTheme::default();
+    let theme = prompt_theme(ctx);
     let result = Confirm::with_theme(&theme)
         .with_prompt(prompt)
         .default(default)
@@ -243,6 +355,29 @@ pub fn prompt_confirm(_ctx: &UiContext, prompt: &str, default: bool) -> anyhow::
     Ok(result)
 }
 
+/// Ask before a destructive change; `Ok(false)` means the user declined.
+///
+/// `yes` (the global `--yes`, or a command's `--force`) confirms without
+/// asking. Without a TTY, or under `--no-input`, there is nobody to ask, so
+/// this fails and names the flag rather than guessing either way.
+pub fn confirm_destructive(
+    ctx: &UiContext,
+    prompt: &str,
+    yes: bool,
+    no_input: bool,
+) -> anyhow::Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    if no_input || !std::io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "Confirmation required: {}\nHint: Pass --yes to confirm without a prompt.",
+            prompt
+        ));
+    }
+    prompt_confirm(ctx, prompt, false)
+}
+
 /// Result of an init wizard.
 #[derive(Debug)]
 pub struct InitWizardResult {
@@ -637,6 +772,7 @@ pub fn print_divider(ctx: &UiContext) {
 mod tests {
     use super::*;
     use crate::ui::mode::OutputMode;
+    use crate::ui::theme::{Palette, ThemeName};
 
     fn test_ctx() -> UiContext {
         UiContext {
@@ -645,6 +781,8 @@ mod tests {
             unicode: true,
             width: 80,
             mode: OutputMode::Plain,
+            theme: ThemeName::Dark,
+            palette: Palette::default(),
         }
     }
 
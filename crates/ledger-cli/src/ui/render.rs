@@ -3,13 +3,14 @@
 use comfy_table::modifiers::UTF8_ROUND_CORNERS;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{
-    Attribute, Cell, ColumnConstraint, ContentArrangement, Table as ComfyTable, Width,
+    Attribute, Cell, Color, ColumnConstraint, ContentArrangement, Table as ComfyTable, Width,
 };
 use owo_colors::Style;
 
 use super::context::UiContext;
+use super::format::{pad_right, truncate};
 use super::mode::OutputMode;
-use super::theme::{styled, styles, Badge};
+use super::theme::{styled, styles, symbols, Badge, TypeBadge};
 
 /// Render a header line for a command.
 ///
@@ -29,15 +30,17 @@ pub fn header_with_context(
     match ctx.mode {
         OutputMode::Pretty => {
             let title = styled("Ledger", styles::bold(), ctx.color);
+            let separator = symbols::SEPARATOR.get(ctx.unicode);
             let mut out = if let Some(c) = context {
-                format!("{} \u{00B7} {} ({})", title, command, c)
+                format!("{} {} {} ({})", title, separator, command, c)
             } else {
-                format!("{} \u{00B7} {}", title, command)
+                format!("{} {} {}", title, separator, command)
             };
             if let Some(p) = path {
                 // Truncate long paths
-                let display_path = if p.len() > 50 {
-                    format!("...{}", &p[p.len() - 47..])
+                let chars = p.chars().count();
+                let display_path = if chars > 50 {
+                    format!("...{}", p.chars().skip(chars - 47).collect::<String>())
                 } else {
                     p.to_string()
                 };
@@ -63,7 +66,7 @@ pub fn header(ctx: &UiContext, command: &str, context: Option<&str>) -> String {
 /// Render a divider line.
 pub fn divider(ctx: &UiContext) -> String {
     if ctx.mode.is_pretty() {
-        "\u{2500}".repeat(ctx.width.min(60))
+        symbols::RULE.get(ctx.unicode).repeat(ctx.width.min(60))
     } else {
         "---".to_string()
     }
@@ -72,7 +75,7 @@ pub fn divider(ctx: &UiContext) -> String {
 /// Render a badge with optional message.
 pub fn badge(ctx: &UiContext, kind: Badge, message: &str) -> String {
     let badge_text = kind.display(ctx.unicode);
-    let colored_badge = styled(badge_text, kind.style(), ctx.color);
+    let colored_badge = styled(badge_text, kind.style(&ctx.palette), ctx.color);
 
     if message.is_empty() {
         colored_badge
@@ -81,6 +84,16 @@ pub fn badge(ctx: &UiContext, kind: Badge, message: &str) -> String {
     }
 }
 
+/// Render an entry type badge (icon, name, palette color) for inline output.
+pub fn type_badge(ctx: &UiContext, badge: &TypeBadge) -> String {
+    badge.display(ctx.unicode, ctx.color, ctx.theme)
+}
+
+/// An entry type badge as a table cell.
+pub fn type_badge_cell(ctx: &UiContext, badge: &TypeBadge) -> TableCell {
+    TableCell::new(badge.text(ctx.unicode)).color(badge.color_index(ctx.color, ctx.theme))
+}
+
 /// Render a key-value pair.
 ///
 /// Pretty mode: "Key: value" with dim key
@@ -210,6 +223,8 @@ pub fn table(ctx: &UiContext, columns: &[Column], rows: &[Vec<String>]) -> Strin
         }
 
         table.set_content_arrangement(ContentArrangement::Dynamic);
+        // Wrap to the resolved width, not whatever comfy-table detects itself
+        table.set_width(ctx.width.min(u16::MAX as usize) as u16);
 
         // Set headers
         let headers: Vec<&str> = columns.iter().map(|c| c.header).collect();
@@ -239,12 +254,48 @@ pub fn table(ctx: &UiContext, columns: &[Column], rows: &[Vec<String>]) -> Strin
     }
 }
 
+/// A table cell with an optional ANSI color index from the theme palette.
+#[derive(Debug, Clone)]
+pub struct TableCell {
+    pub text: String,
+    pub color: Option<u8>,
+}
+
+impl TableCell {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            color: None,
+        }
+    }
+
+    pub fn color(mut self, color: Option<u8>) -> Self {
+        self.color = color;
+        self
+    }
+}
+
 /// Render a simple table without borders (for lists like entries).
 pub fn simple_table(ctx: &UiContext, columns: &[Column], rows: &[Vec<String>]) -> String {
+    let rows: Vec<Vec<TableCell>> = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|text| TableCell::new(text.clone()))
+                .collect()
+        })
+        .collect();
+    simple_table_cells(ctx, columns, &rows)
+}
+
+/// Like [`simple_table`], with cells that may carry a palette color.
+pub fn simple_table_cells(ctx: &UiContext, columns: &[Column], rows: &[Vec<TableCell>]) -> String {
     if ctx.mode.is_pretty() {
         let mut table = ComfyTable::new();
         table.load_preset(comfy_table::presets::NOTHING);
         table.set_content_arrangement(ContentArrangement::Dynamic);
+        // Wrap to the resolved width, not whatever comfy-table detects itself
+        table.set_width(ctx.width.min(u16::MAX as usize) as u16);
 
         // Set headers with dim styling using comfy-table's built-in styling
         // This ensures proper column width calculation
@@ -272,19 +323,93 @@ pub fn simple_table(ctx: &UiContext, columns: &[Column], rows: &[Vec<String>]) -
 
         // Add rows
         for row in rows {
-            table.add_row(row);
+            table.add_row(row.iter().map(|cell| {
+                let styled = Cell::new(&cell.text);
+                match cell.color {
+                    Some(index) if ctx.color => styled.fg(Color::AnsiValue(index)),
+                    _ => styled,
+                }
+            }));
         }
 
         table.to_string()
     } else {
         // Plain mode: space-separated values, no header
         rows.iter()
-            .map(|row| row.join(" "))
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
             .collect::<Vec<_>>()
             .join("\n")
     }
 }
 
+/// Narrowest column [`column_grid`] lays out side by side.
+const GRID_MIN_WIDTH: usize = 12;
+
+/// Spaces between side-by-side grid columns.
+const GRID_GAP: usize = 2;
+
+/// Render titled columns of lines, such as the days of a week.
+///
+/// Pretty mode: columns side by side, sharing the terminal width, with
+/// lines truncated to fit. When the terminal is too narrow for every column
+/// to get `GRID_MIN_WIDTH`, each column is stacked under its title instead.
+/// Plain mode: always stacked, titles unstyled.
+pub fn column_grid(ctx: &UiContext, columns: &[(String, Vec<String>)]) -> String {
+    let count = columns.len().max(1);
+    let width = ctx.width.saturating_sub(GRID_GAP * (count - 1)) / count;
+    if !ctx.mode.is_pretty() || width < GRID_MIN_WIDTH {
+        return columns
+            .iter()
+            .map(|(title, lines)| {
+                let mut block = vec![if ctx.mode.is_pretty() {
+                    styled(title, styles::bold(), ctx.color)
+                } else {
+                    title.clone()
+                }];
+                block.extend(lines.iter().map(|line| format!("  {}", line)));
+                block.join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join(if ctx.mode.is_pretty() { "\n\n" } else { "\n" });
+    }
+
+    let gap = " ".repeat(GRID_GAP);
+    let row = |cells: Vec<String>| cells.join(&gap).trim_end().to_string();
+    let mut out = vec![
+        row(columns
+            .iter()
+            .map(|(title, _)| {
+                let title = pad_right(&truncate(title, width), width);
+                styled(&title, styles::bold(), ctx.color)
+            })
+            .collect()),
+        row(columns
+            .iter()
+            .map(|_| symbols::RULE.get(ctx.unicode).repeat(width))
+            .collect()),
+    ];
+    let height = columns
+        .iter()
+        .map(|(_, lines)| lines.len())
+        .max()
+        .unwrap_or(0);
+    for i in 0..height {
+        out.push(row(columns
+            .iter()
+            .map(|(_, lines)| {
+                let line = lines.get(i).map(String::as_str).unwrap_or("");
+                pad_right(&truncate(line, width), width)
+            })
+            .collect()));
+    }
+    out.join("\n")
+}
+
 /// Print a message to stdout with proper mode handling.
 ///
 /// In JSON mode, this does nothing (JSON output should be handled separately).
@@ -332,6 +457,7 @@ pub fn print_error(ctx: &UiContext, message: &str, error_hint: Option<&str>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ui::theme::{Palette, ThemeName};
 
     fn plain_ctx() -> UiContext {
         UiContext {
@@ -340,6 +466,8 @@ mod tests {
             unicode: false,
             width: 80,
             mode: OutputMode::Plain,
+            theme: ThemeName::Dark,
+            palette: Palette::default(),
         }
     }
 
@@ -350,9 +478,110 @@ mod tests {
             unicode: true,
             width: 80,
             mode: OutputMode::Pretty,
+            theme: ThemeName::Dark,
+            palette: Palette::default(),
         }
     }
 
+    fn ascii_ctx() -> UiContext {
+        UiContext {
+            unicode: false,
+            ..pretty_ctx()
+        }
+    }
+
+    #[test]
+    fn test_column_grid_side_by_side_truncates_to_width() {
+        let ctx = UiContext {
+            width: 40,
+            ..ascii_ctx()
+        };
+        let columns = vec![
+            (
+                "Mon".to_string(),
+                vec![
+                    "2 entries".to_string(),
+                    "Morning pages and more".to_string(),
+                ],
+            ),
+            ("Tue".to_string(), vec![]),
+            ("Wed".to_string(), vec!["1 entry".to_string()]),
+        ];
+        assert_eq!(
+            column_grid(&ctx, &columns),
+            "Mon           Tue           Wed\n\
+             ------------  ------------  ------------\n\
+             2 entries                   1 entry\n\
+             Morning p..."
+        );
+    }
+
+    #[test]
+    fn test_column_grid_stacks_when_narrow_or_plain() {
+        let columns = vec![
+            ("Mon".to_string(), vec!["2 entries".to_string()]),
+            ("Tue".to_string(), vec!["-".to_string()]),
+        ];
+        let narrow = UiContext {
+            width: 20,
+            ..ascii_ctx()
+        };
+        assert_eq!(
+            column_grid(&narrow, &columns),
+            "Mon\n  2 entries\n\nTue\n  -"
+        );
+        assert_eq!(
+            column_grid(&plain_ctx(), &columns),
+            "Mon\n  2 entries\nTue\n  -"
+        );
+    }
+
+    #[test]
+    fn test_ascii_pretty_output_snapshot() {
+        let ctx = ascii_ctx();
+        let journal = TypeBadge {
+            name: "journal".to_string(),
+            icon: Some("\u{1F4D3}".to_string()),
+            color: Some("cyan".to_string()),
+        };
+        let out = [
+            header_with_context(
+                &ctx,
+                "list",
+                Some("last 7d"),
+                Some("/home/someone/.local/share/ledger/archive/2024/ledger.ledger"),
+            ),
+            divider(&ctx),
+            badge(&ctx, Badge::Ok, "Added entry"),
+            type_badge(&ctx, &journal),
+            simple_table(
+                &ctx,
+                &[Column::new("ID"), Column::new("Summary")],
+                &[vec!["7a2e3c0b".to_string(), "Weekly review".to_string()]],
+            ),
+            table(
+                &ctx,
+                &[Column::new("Key"), Column::new("Value")],
+                &[vec!["tier".to_string(), "passphrase".to_string()]],
+            ),
+        ]
+        .join("\n");
+        assert!(out.is_ascii(), "{}", out);
+        assert_eq!(
+            out,
+            "Ledger - list (last 7d)\n\
+             Path: .../.local/share/ledger/archive/2024/ledger.ledger\n\
+             ------------------------------------------------------------\n\
+             [OK] Added entry\n\
+             journal\n\
+             ID        Summary        \n\
+             7a2e3c0b  Weekly review  \n\
+             | Key  | Value      |\n\
+             |------|------------|\n\
+             | tier | passphrase |"
+        );
+    }
+
     #[test]
     fn test_header_pretty() {
         let ctx = pretty_ctx();
@@ -604,6 +833,8 @@ mod tests {
             unicode: false, // ASCII mode
             width: 80,
             mode: OutputMode::Pretty,
+            theme: ThemeName::Dark,
+            palette: Palette::default(),
         };
         let ok_ascii = badge(&ascii_ctx, Badge::Ok, "Success");
         assert!(ok_ascii.contains("[OK]"));
@@ -1,16 +1,14 @@
 //! Theme definitions for colors, symbols, and badges.
 
-use owo_colors::{OwoColorize, Style};
+use owo_colors::{AnsiColors, OwoColorize, Style, XtermColors};
 
 /// Symbol pair for ASCII and Unicode variants.
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct SymbolPair {
     pub ascii: &'static str,
     pub unicode: &'static str,
 }
 
-#[allow(dead_code)]
 impl SymbolPair {
     pub const fn new(ascii: &'static str, unicode: &'static str) -> Self {
         Self { ascii, unicode }
@@ -26,6 +24,16 @@ impl SymbolPair {
     }
 }
 
+/// Decorations with an ASCII fallback for `--ascii`.
+pub mod symbols {
+    use super::SymbolPair;
+
+    /// Separates the parts of a header or a one-line summary.
+    pub const SEPARATOR: SymbolPair = SymbolPair::new("-", "\u{00B7}");
+    /// Repeated to draw a divider.
+    pub const RULE: SymbolPair = SymbolPair::new("-", "\u{2500}");
+}
+
 /// Badge types for status indicators.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Badge {
@@ -81,14 +89,15 @@ impl Badge {
         }
     }
 
-    /// Get the owo-colors style for this badge type.
-    pub fn style(&self) -> Style {
-        match self {
-            Self::Ok => Style::new().green(),
-            Self::Warn => Style::new().yellow(),
-            Self::Err => Style::new().red(),
-            Self::Info => Style::new().cyan(),
-        }
+    /// Get the owo-colors style for this badge type under `palette`.
+    pub fn style(&self, palette: &Palette) -> Style {
+        let color = match self {
+            Self::Ok => palette.ok,
+            Self::Warn => palette.warn,
+            Self::Err => palette.error,
+            Self::Info => palette.info,
+        };
+        color.style()
     }
 }
 
@@ -140,6 +149,258 @@ pub fn styled(text: &str, style: Style, color_enabled: bool) -> String {
     }
 }
 
+/// Color theme chosen with `[ui] theme`.
+///
+/// `dark` and `light` pick palette shades that read well on that background;
+/// `none` turns color off like `NO_COLOR` does; `colorblind` keeps statuses
+/// and entry types apart without relying on red against green.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    None,
+    ColorBlind,
+}
+
+impl ThemeName {
+    pub const ALL: [ThemeName; 4] = [Self::Dark, Self::Light, Self::None, Self::ColorBlind];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.as_str() == value.trim().to_ascii_lowercase())
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+            Self::None => "none",
+            Self::ColorBlind => "colorblind",
+        }
+    }
+}
+
+/// A theme color: an ANSI index, or RGB for terminals with true color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeColor {
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// Names accepted for the 16 basic ANSI colors, by index.
+const ANSI_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "gray",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+
+impl ThemeColor {
+    /// Parse a color name (`cyan`), an ANSI index (`214`) or `#rrggbb`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        if let Some(hex) = value.strip_prefix('#') {
+            if hex.len() != 6 || !hex.is_ascii() {
+                return None;
+            }
+            let channel = |at: usize| u8::from_str_radix(&hex[at..at + 2], 16).ok();
+            return Some(Self::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+        if let Ok(index) = value.parse::<u8>() {
+            return Some(Self::Ansi(index));
+        }
+        let name = if value == "grey" { "gray" } else { &value };
+        ANSI_NAMES
+            .iter()
+            .position(|n| *n == name)
+            .map(|index| Self::Ansi(index as u8))
+    }
+
+    /// Foreground style; the 16 basic colors use the terminal's own shades.
+    pub fn style(self) -> Style {
+        match self {
+            Self::Ansi(index) => match basic_color(index) {
+                Some(color) => Style::new().color(color),
+                None => Style::new().color(XtermColors::from(index)),
+            },
+            Self::Rgb(r, g, b) => Style::new().truecolor(r, g, b),
+        }
+    }
+
+    /// Nearest ANSI index, for renderers without true color.
+    pub fn ansi_index(self) -> u8 {
+        match self {
+            Self::Ansi(index) => index,
+            Self::Rgb(r, g, b) => {
+                // Nearest step of the 6x6x6 cube at indices 16-231
+                let step = |c: u8| match c {
+                    0..=47 => 0,
+                    48..=114 => 1,
+                    c => (c - 35) / 40,
+                };
+                16 + 36 * step(r) + 6 * step(g) + step(b)
+            }
+        }
+    }
+}
+
+fn basic_color(index: u8) -> Option<AnsiColors> {
+    use AnsiColors::*;
+    const BASIC: [AnsiColors; 16] = [
+        Black,
+        Red,
+        Green,
+        Yellow,
+        Blue,
+        Magenta,
+        Cyan,
+        White,
+        BrightBlack,
+        BrightRed,
+        BrightGreen,
+        BrightYellow,
+        BrightBlue,
+        BrightMagenta,
+        BrightCyan,
+        BrightWhite,
+    ];
+    BASIC.get(index as usize).copied()
+}
+
+/// Status and accent colors used by badges, spinners and progress bars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub accent: ThemeColor,
+    pub ok: ThemeColor,
+    pub warn: ThemeColor,
+    pub error: ThemeColor,
+    pub info: ThemeColor,
+}
+
+impl Palette {
+    /// The shipped palette for a preset.
+    pub fn preset(theme: ThemeName) -> Self {
+        match theme {
+            // Okabe-Ito hues: blue, orange and vermillion stay distinct
+            // under the common forms of color blindness
+            ThemeName::ColorBlind => Self {
+                accent: ThemeColor::Ansi(74),
+                ok: ThemeColor::Ansi(25),
+                warn: ThemeColor::Ansi(178),
+                error: ThemeColor::Ansi(166),
+                info: ThemeColor::Ansi(74),
+            },
+            ThemeName::Dark | ThemeName::Light | ThemeName::None => Self {
+                accent: ThemeColor::Ansi(6),
+                ok: ThemeColor::Ansi(2),
+                warn: ThemeColor::Ansi(3),
+                error: ThemeColor::Ansi(1),
+                info: ThemeColor::Ansi(6),
+            },
+        }
+    }
+
+    /// Override one color by its `[ui.theme]` key; false for unknown keys.
+    pub fn set(&mut self, key: &str, color: ThemeColor) -> bool {
+        let slot = match key {
+            "accent" => &mut self.accent,
+            "ok" => &mut self.ok,
+            "warn" => &mut self.warn,
+            "error" => &mut self.error,
+            "info" => &mut self.info,
+            _ => return false,
+        };
+        *slot = color;
+        true
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::preset(ThemeName::default())
+    }
+}
+
+/// Palette for entry type colors: name, then the ANSI color index used on
+/// dark and on light backgrounds, and under `colorblind`.
+///
+/// Names match `ledger_core::storage::ENTRY_TYPE_COLORS`.
+const TYPE_PALETTE: &[(&str, u8, u8, u8)] = &[
+    ("red", 9, 1, 166),
+    ("green", 10, 2, 36),
+    ("yellow", 11, 3, 221),
+    ("blue", 12, 4, 25),
+    ("magenta", 13, 5, 175),
+    ("cyan", 14, 6, 74),
+    ("gray", 7, 8, 246),
+];
+
+/// ANSI color index for an entry type color name under `theme`.
+///
+/// Returns `None` for unknown names and for the `none` theme.
+pub fn type_color(name: &str, theme: ThemeName) -> Option<u8> {
+    let (_, dark, light, colorblind) = TYPE_PALETTE.iter().find(|(n, ..)| *n == name)?;
+    match theme {
+        ThemeName::Dark => Some(*dark),
+        ThemeName::Light => Some(*light),
+        ThemeName::ColorBlind => Some(*colorblind),
+        ThemeName::None => None,
+    }
+}
+
+/// Badge shown for an entry type: its icon, name and color.
+#[derive(Debug, Clone, Default)]
+pub struct TypeBadge {
+    pub name: String,
+    pub icon: Option<String>,
+    pub color: Option<String>,
+}
+
+impl TypeBadge {
+    /// Badge text, e.g. `📓 journal`.
+    ///
+    /// Non-ASCII icons are dropped under `--ascii`.
+    pub fn text(&self, unicode: bool) -> String {
+        match self.icon.as_deref() {
+            Some(icon) if unicode || icon.is_ascii() => format!("{} {}", icon, self.name),
+            _ => self.name.clone(),
+        }
+    }
+
+    /// ANSI color index for this badge, if color is enabled.
+    pub fn color_index(&self, color_enabled: bool, theme: ThemeName) -> Option<u8> {
+        if !color_enabled {
+            return None;
+        }
+        type_color(self.color.as_deref()?, theme)
+    }
+
+    /// Badge text styled for inline output.
+    pub fn display(&self, unicode: bool, color_enabled: bool, theme: ThemeName) -> String {
+        let text = self.text(unicode);
+        match self.color_index(color_enabled, theme) {
+            Some(index) => text
+                .style(Style::new().color(XtermColors::from(index)))
+                .to_string(),
+            None => text,
+        }
+    }
+}
+
 /// Theme configuration for UI rendering.
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
@@ -227,6 +488,85 @@ mod tests {
         assert!(text.contains("hello"));
     }
 
+    #[test]
+    fn test_theme_name_parse() {
+        assert_eq!(ThemeName::parse("Light"), Some(ThemeName::Light));
+        assert_eq!(ThemeName::parse("none"), Some(ThemeName::None));
+        assert_eq!(ThemeName::parse("colorblind"), Some(ThemeName::ColorBlind));
+        assert_eq!(ThemeName::parse("solarized"), None);
+    }
+
+    #[test]
+    fn test_theme_color_parse() {
+        assert_eq!(ThemeColor::parse("cyan"), Some(ThemeColor::Ansi(6)));
+        assert_eq!(ThemeColor::parse("Grey"), Some(ThemeColor::Ansi(8)));
+        assert_eq!(ThemeColor::parse("214"), Some(ThemeColor::Ansi(214)));
+        assert_eq!(
+            ThemeColor::parse("#87D7ff"),
+            Some(ThemeColor::Rgb(0x87, 0xd7, 0xff))
+        );
+        assert_eq!(ThemeColor::parse("#87d7f"), None);
+        assert_eq!(ThemeColor::parse("256"), None);
+        assert_eq!(ThemeColor::parse("teal"), None);
+    }
+
+    #[test]
+    fn test_theme_color_ansi_index() {
+        assert_eq!(ThemeColor::Ansi(214).ansi_index(), 214);
+        assert_eq!(ThemeColor::Rgb(0x87, 0xd7, 0xff).ansi_index(), 117);
+        assert_eq!(ThemeColor::Rgb(0, 0, 0).ansi_index(), 16);
+        assert_eq!(ThemeColor::Rgb(255, 255, 255).ansi_index(), 231);
+    }
+
+    #[test]
+    fn test_badge_style_follows_palette() {
+        let mut palette = Palette::preset(ThemeName::Dark);
+        assert_eq!(
+            styled("x", Badge::Ok.style(&palette), true),
+            "\u{1b}[32mx\u{1b}[0m"
+        );
+        assert!(palette.set("ok", ThemeColor::Rgb(1, 2, 3)));
+        assert!(!palette.set("accnt", ThemeColor::Ansi(1)));
+        assert!(styled("x", Badge::Ok.style(&palette), true).contains("38;2;1;2;3"));
+        let colorblind = Palette::preset(ThemeName::ColorBlind);
+        assert_ne!(colorblind.ok, palette.ok);
+        assert_ne!(colorblind.ok, colorblind.error);
+    }
+
+    #[test]
+    fn test_palette_covers_core_type_colors() {
+        for name in ledger_core::storage::ENTRY_TYPE_COLORS {
+            assert!(type_color(name, ThemeName::Dark).is_some(), "{}", name);
+            assert!(type_color(name, ThemeName::Light).is_some(), "{}", name);
+            assert!(
+                type_color(name, ThemeName::ColorBlind).is_some(),
+                "{}",
+                name
+            );
+            assert_eq!(type_color(name, ThemeName::None), None);
+        }
+    }
+
+    #[test]
+    fn test_type_badge_text_and_color() {
+        let badge = TypeBadge {
+            name: "journal".to_string(),
+            icon: Some("\u{1F4D3}".to_string()),
+            color: Some("blue".to_string()),
+        };
+        assert_eq!(badge.text(true), "\u{1F4D3} journal");
+        assert_eq!(badge.text(false), "journal");
+        assert_eq!(badge.color_index(true, ThemeName::Light), Some(4));
+        assert_eq!(badge.color_index(false, ThemeName::Dark), None);
+        assert_eq!(
+            badge.display(true, false, ThemeName::Dark),
+            "\u{1F4D3} journal"
+        );
+        assert!(badge
+            .display(true, true, ThemeName::Dark)
+            .contains("\u{1b}[38;5;12m"));
+    }
+
     #[test]
     fn test_styled_without_color() {
         let text = styled("hello", styles::success(), false);
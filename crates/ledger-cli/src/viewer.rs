@@ -0,0 +1,120 @@
+//! Ledger Viewer - a read-only build of the Ledger CLI
+//!
+//! `ledger-viewer` unlocks a ledger and can list, search, show and export
+//! its entries, nothing more. It shares the `ledger_cli` library with
+//! `ledger` but compiles only the four read-only command handlers below, and
+//! its context writes nothing: not the ledger, the config, the session cache
+//! or the lockout counter. Build it with `--features viewer`.
+
+// The read-only handlers, from the same files `ledger` uses
+mod commands {
+    pub mod entries {
+        pub mod export;
+        mod export_chunks;
+        pub mod list;
+        pub mod search;
+        pub mod show;
+
+        pub use export::handle_export;
+        pub use list::handle_list;
+        pub use search::handle_search;
+        pub use show::handle_show;
+    }
+}
+
+use clap::{Parser, Subcommand};
+use ledger_cli::{app, cli, config, errors, helpers, output, ui};
+use ledger_core::VERSION;
+
+use crate::app::AppContext;
+use crate::cli::{Cli, ExportArgs, ListArgs, SearchArgs, ShowArgs};
+use crate::commands::entries;
+use crate::ui::print_error;
+
+/// Ledger Viewer - read-only access to an encrypted ledger
+#[derive(Parser)]
+#[command(name = "ledger-viewer")]
+#[command(author, version = VERSION, about, long_about = None)]
+#[command(propagate_version = true)]
+struct ViewerCli {
+    /// Path to the ledger file
+    #[arg(short, long, global = true, env = "LEDGER_PATH")]
+    ledger: Option<String>,
+
+    #[command(subcommand)]
+    command: ViewerCommands,
+
+    /// Quiet mode (minimal output)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Disable colored output
+    #[arg(
+        long,
+        global = true,
+        env = "NO_COLOR",
+        value_parser = clap::builder::FalseyValueParser::new()
+    )]
+    no_color: bool,
+
+    /// Use ASCII-only symbols (no Unicode)
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    /// Skip the failed-unlock lockout wait (attempts are still recorded)
+    #[arg(long, global = true)]
+    no_lockout: bool,
+
+    /// Fail on warnings (unknown config keys, skipped steps) with exit code 7
+    #[arg(long, global = true)]
+    strict: bool,
+}
+
+#[derive(Subcommand)]
+enum ViewerCommands {
+    /// List entries
+    List(ListArgs),
+
+    /// Search entries using full-text search
+    Search(SearchArgs),
+
+    /// Show a specific entry by ID
+    Show(ShowArgs),
+
+    /// Export entries (portable formats, you own your data)
+    Export(ExportArgs),
+}
+
+fn main() {
+    let viewer = ViewerCli::parse();
+    // Global options mean the same as for `ledger`
+    let cli = Cli {
+        ledger: viewer.ledger,
+        command: None,
+        quiet: viewer.quiet,
+        no_color: viewer.no_color,
+        ascii: viewer.ascii,
+        no_lockout: viewer.no_lockout,
+        dry_run: false,
+        strict: viewer.strict,
+        yes: false,
+    };
+    let ctx = AppContext::new(&cli).read_only();
+
+    if let Err(e) = run(&ctx, &viewer.command) {
+        let ui_ctx = ctx.ui_context(false, None);
+        print_error(&ui_ctx, &format!("{}", e), None);
+        std::process::exit(errors::exit_code(&e));
+    }
+}
+
+fn run(ctx: &AppContext, command: &ViewerCommands) -> anyhow::Result<()> {
+    ctx.check_config_keys()?;
+
+    match command {
+        ViewerCommands::List(args) => entries::handle_list(ctx, args),
+        ViewerCommands::Search(args) => entries::handle_search(ctx, args),
+        ViewerCommands::Show(args) => entries::handle_show(ctx, args),
+        ViewerCommands::Export(args) => entries::handle_export(ctx, args),
+    }
+}
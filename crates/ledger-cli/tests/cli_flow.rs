@@ -1,6 +1,6 @@
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output};
 use std::ptr::NonNull;
 use std::thread::sleep;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -12,6 +12,8 @@ use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
 use ledger_core::storage::{AgeSqliteStorage, StorageEngine};
 
+const TEST_PASSPHRASE: &str = "test-passphrase-secure-123";
+
 fn bin() -> PathBuf {
     PathBuf::from(env!("CARGO_BIN_EXE_ledger"))
 }
@@ -42,7 +44,7 @@ fn temp_xdg_dirs(prefix: &str) -> (PathBuf, PathBuf) {
     (config, data)
 }
 
-fn apply_xdg_env(cmd: &mut Command, config: &PathBuf, data: &PathBuf) {
+fn apply_xdg_env(cmd: &mut Command, config: &Path, data: &Path) {
     let runtime = data.parent().unwrap().join("runtime");
     cmd.env("XDG_CONFIG_HOME", config)
         .env("XDG_DATA_HOME", data)
@@ -51,6 +53,37 @@ fn apply_xdg_env(cmd: &mut Command, config: &PathBuf, data: &PathBuf) {
         .env("TMPDIR", &runtime);
 }
 
+/// `ledger <args> --ledger <ledger>` unlocked with the test passphrase, using
+/// the XDG config and data dirs in `home`.
+fn ledger_command(ledger: &Path, home: (&Path, &Path), args: &[&str]) -> Command {
+    let mut cmd = Command::new(bin());
+    cmd.args(args)
+        .arg("--ledger")
+        .arg(ledger)
+        .env("LEDGER_PASSPHRASE", TEST_PASSPHRASE);
+    apply_xdg_env(&mut cmd, home.0, home.1);
+    cmd
+}
+
+/// Run `ledger_command` and return its output, successful or not.
+fn run_ledger(ledger: &Path, home: (&Path, &Path), args: &[&str]) -> Output {
+    ledger_command(ledger, home, args)
+        .output()
+        .expect("run ledger")
+}
+
+/// Run `ledger_command`, fail the test unless it succeeds, and return stdout.
+fn run_ledger_ok(ledger: &Path, home: (&Path, &Path), args: &[&str]) -> String {
+    let output = run_ledger(ledger, home, args);
+    assert!(
+        output.status.success(),
+        "{:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
 fn write_config_file(
     config_home: &Path,
     ledger_path: &Path,
@@ -70,7 +103,7 @@ fn write_config_file(
     };
     let keychain_enabled = tier == "passphrase_keychain";
     let contents = format!(
-        "[ledger]\npath = \"{}\"\n\n[security]\ntier = \"{}\"\npassphrase_cache_ttl_seconds = {}\n\n[keychain]\nenabled = {}\n\n[keyfile]\nmode = \"{}\"\n{}",
+        "config_version = 1\n\n[ledger]\npath = \"{}\"\n\n[security]\ntier = \"{}\"\npassphrase_cache_ttl_seconds = {}\n\n[keychain]\nenabled = {}\n\n[keyfile]\nmode = \"{}\"\n{}",
         ledger_path.to_string_lossy(),
         tier,
         cache_ttl_seconds,
@@ -153,8 +186,16 @@ fn cache_store_raw(socket_path: &Path, key: &str, passphrase: &str) {
     let mut stream = stream.expect("connect cache after retries");
 
     let encoded = STANDARD.encode(passphrase.as_bytes());
-    let payload = format!("STORE {} {}\n", key, encoded);
-    stream.write_all(payload.as_bytes()).expect("write cache");
+    // Length-prefixed hello and request frames
+    let mut payload = Vec::new();
+    for frame in [
+        "HELLO ledger-cache 3".to_string(),
+        format!("STORE {} {}", key, encoded),
+    ] {
+        payload.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+        payload.extend_from_slice(frame.as_bytes());
+    }
+    stream.write_all(&payload).expect("write cache");
     stream.shutdown(Shutdown::Write).expect("shutdown write");
     let mut response = String::new();
     stream.read_to_string(&mut response).expect("read cache");
@@ -345,6 +386,181 @@ fn test_cli_check_failure() {
     assert!(output.contains("error="));
 }
 
+#[test]
+fn test_cli_check_warnings_do_not_fail() {
+    let ledger_path = temp_ledger_path("ledger_cli_check_warn");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_check_warn");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let conn = open_sqlite_from_file(&ledger_path, passphrase);
+    conn.execute(
+        "INSERT INTO entries_fts (entry_id, content) VALUES ('stale-id', 'stale')",
+        [],
+    )
+    .expect("insert orphan fts");
+    let data = conn.serialize(DatabaseName::Main).expect("serialize");
+    let encrypted =
+        ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase).expect("encrypt");
+    std::fs::write(&ledger_path, encrypted).expect("write");
+
+    let mut check = Command::new(bin());
+    check
+        .arg("check")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut check, &config_home, &data_home);
+    let check = check.output().expect("run check");
+    assert!(check.status.success());
+    let stdout = String::from_utf8_lossy(&check.stdout);
+    assert!(
+        stdout.contains("check=entries_fts warn"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains("warning=fts_orphaned count=1 ids=stale-id"));
+    assert!(stdout.contains("status=warn"));
+
+    let mut check_json = Command::new(bin());
+    check_json
+        .arg("check")
+        .arg("--json")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut check_json, &config_home, &data_home);
+    let check_json = check_json.output().expect("run check json");
+    assert!(check_json.status.success());
+    let value: serde_json::Value =
+        serde_json::from_slice(&check_json.stdout).expect("parse check json");
+    assert_eq!(value["status"], "warn");
+    assert_eq!(value["issues"][0]["code"], "fts_orphaned");
+    assert_eq!(value["issues"][0]["severity"], "warning");
+    assert_eq!(value["issues"][0]["ids"][0], "stale-id");
+}
+
+#[test]
+fn test_cli_verify_after_write_config() {
+    let ledger_path = temp_ledger_path("ledger_cli_verify_write");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_verify_write");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str("\n[storage]\nverify_after_write = true\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    let mut add = Command::new(bin());
+    add.arg("add")
+        .arg("journal")
+        .arg("--body")
+        .arg("Verified")
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut add, &config_home, &data_home);
+    let add = add.output().expect("run add");
+    assert!(
+        add.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&add.stderr)
+    );
+
+    let mut list = Command::new(bin());
+    list.arg("list")
+        .arg("--format")
+        .arg("plain")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut list, &config_home, &data_home);
+    let list = list.output().expect("run list");
+    assert!(String::from_utf8_lossy(&list.stdout).contains("Verified"));
+}
+
+#[test]
+fn test_cli_recover_writes_new_ledger() {
+    let ledger_path = temp_ledger_path("ledger_cli_recover");
+    let out_path = temp_ledger_path("ledger_cli_recover_out");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_recover");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let mut add = Command::new(bin());
+    add.arg("add")
+        .arg("journal")
+        .arg("--body")
+        .arg("Salvage me")
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut add, &config_home, &data_home);
+    assert!(add.output().expect("run add").status.success());
+    let original = std::fs::read(&ledger_path).expect("read original");
+
+    let recover = || {
+        let mut recover = Command::new(bin());
+        recover
+            .arg("recover")
+            .arg("--out")
+            .arg(&out_path)
+            .arg("--no-input")
+            .env("LEDGER_PASSPHRASE", passphrase);
+        apply_xdg_env(&mut recover, &config_home, &data_home);
+        recover.output().expect("run recover")
+    };
+    let first = recover();
+    assert!(
+        first.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&first.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&first.stdout);
+    assert!(stdout
+        .contains("table=entries recovered=1 rejected=0 orphaned=0 unreadable=- missing=false"));
+    assert!(stdout.contains("status=ok"));
+    assert_eq!(
+        std::fs::read(&ledger_path).expect("reread original"),
+        original
+    );
+
+    let mut search = Command::new(bin());
+    search
+        .arg("search")
+        .arg("Salvage")
+        .arg("--ledger")
+        .arg(&out_path)
+        .arg("--format")
+        .arg("plain")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut search, &config_home, &data_home);
+    let search = search.output().expect("run search");
+    assert!(String::from_utf8_lossy(&search.stdout).contains("Salvage me"));
+
+    let second = recover();
+    assert!(!second.status.success());
+    assert!(String::from_utf8_lossy(&second.stderr).contains("already exists"));
+}
+
 #[test]
 fn test_cli_init_writes_default_config() {
     let passphrase = "test-passphrase-secure-123";
@@ -376,7 +592,8 @@ fn test_cli_init_writes_default_config() {
     assert!(keys.contains(&&"keychain".to_string()));
     assert!(keys.contains(&&"keyfile".to_string()));
     assert!(keys.contains(&&"ui".to_string()));
-    assert_eq!(keys.len(), 5);
+    assert!(keys.contains(&&"config_version".to_string()));
+    assert_eq!(keys.len(), 6);
 
     assert_eq!(
         value
@@ -645,6 +862,224 @@ fn test_cli_list_empty_message() {
     assert!(stdout.contains("count=0"));
 }
 
+#[test]
+fn test_cli_init_search_tokenizer_and_reindex() {
+    let ledger_path = temp_ledger_path("ledger_cli_tokenizer");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_tokenizer");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    let mut bad = Command::new(bin());
+    bad.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .arg("--search-tokenizer")
+        .arg("icu")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut bad, &config_home, &data_home);
+    let bad = bad.output().expect("run init");
+    assert!(!bad.status.success());
+    assert!(
+        !ledger_path.exists(),
+        "invalid tokenizer must not create a ledger"
+    );
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .arg("--search-tokenizer")
+        .arg("unicode61")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    let init = init.output().expect("run init");
+    assert!(
+        init.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&init.stderr)
+    );
+    let config_path = config_home.join("ledger").join("config.toml");
+    let config = std::fs::read_to_string(&config_path).expect("read config");
+    assert!(config.contains("[search]"), "config: {}", config);
+    assert!(config.contains("tokenizer = \"unicode61\""));
+
+    let add = run(&[
+        "add",
+        "journal",
+        "--body",
+        "Déjà vu while running",
+        "--no-input",
+    ]);
+    assert!(add.status.success());
+    let count = |output: std::process::Output| {
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout)
+            .expect("parse search json")
+            .as_array()
+            .expect("array")
+            .len()
+    };
+    assert_eq!(count(run(&["search", "deja", "--json"])), 1);
+    assert_eq!(count(run(&["search", "run", "--json"])), 0);
+
+    let reindex = run(&["reindex", "--tokenizer", "porter"]);
+    assert!(reindex.status.success());
+    let stdout = String::from_utf8_lossy(&reindex.stdout);
+    assert!(stdout.contains("tokenizer=porter"), "stdout: {}", stdout);
+    assert!(stdout.contains("previous_tokenizer=unicode61"));
+    assert!(stdout.contains("entries="));
+    assert_eq!(count(run(&["search", "run", "--json"])), 1);
+
+    // Without a flag, reindex follows [search] tokenizer from config
+    let reindex = run(&["reindex"]);
+    assert!(String::from_utf8_lossy(&reindex.stdout).contains("tokenizer=unicode61"));
+    assert_eq!(count(run(&["search", "run", "--json"])), 0);
+
+    // Batched rebuilds index the same entries
+    assert!(
+        run(&["add", "journal", "--body", "Runs home", "--no-input"])
+            .status
+            .success()
+    );
+    let reindex = run(&["reindex", "--tokenizer", "porter", "--batch-size", "1"]);
+    assert!(reindex.status.success());
+    let stdout = String::from_utf8_lossy(&reindex.stdout);
+    assert!(stdout.contains("entries=2"), "stdout: {}", stdout);
+    assert!(stdout.contains("resumed=false"));
+    assert_eq!(count(run(&["search", "run", "--json"])), 2);
+    assert!(!run(&["reindex", "--batch-size", "0"]).status.success());
+}
+
+#[test]
+fn test_cli_trigram_ledger_searches_cjk() {
+    let ledger_path = temp_ledger_path("ledger_cli_cjk");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_cjk");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .arg("--search-tokenizer")
+        .arg("trigram")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    for body in ["週末は京都で紅葉を見た", "今天在上海吃了小笼包"] {
+        assert!(run(&["add", "journal", "--body", body, "--no-input"])
+            .status
+            .success());
+    }
+
+    for (query, expected) in [("京都", "紅葉"), ("小笼包", "上海"), ("紅葉を見", "京都")]
+    {
+        let search = run(&["search", query, "--json"]);
+        assert!(search.status.success());
+        let value: serde_json::Value =
+            serde_json::from_slice(&search.stdout).expect("parse search json");
+        let entries = value.as_array().expect("array");
+        assert_eq!(entries.len(), 1, "query {}", query);
+        assert!(entries[0].to_string().contains(expected));
+    }
+}
+
+#[test]
+fn test_cli_dry_run_leaves_ledger_untouched() {
+    let ledger_path = temp_ledger_path("ledger_cli_dry_run");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_dry_run");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    let before = std::fs::read(&ledger_path).expect("read ledger");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    let add = run(&[
+        "add",
+        "journal",
+        "--body",
+        "Would be written",
+        "--tag",
+        "Work",
+        "--no-input",
+        "--dry-run",
+    ]);
+    assert!(add.status.success());
+    let preview: serde_json::Value =
+        serde_json::from_slice(&add.stdout).expect("parse dry-run json");
+    assert_eq!(preview["data"]["body"], "Would be written");
+    assert_eq!(preview["tags"], serde_json::json!(["work"]));
+    assert_eq!(preview["entry_type_name"], "journal");
+    assert!(String::from_utf8_lossy(&add.stderr).contains("Dry run: no changes written"));
+
+    assert_eq!(std::fs::read(&ledger_path).expect("read ledger"), before);
+    let list = run(&["list", "--json"]);
+    let entries: serde_json::Value = serde_json::from_slice(&list.stdout).expect("parse list");
+    assert_eq!(entries.as_array().map(Vec::len), Some(0));
+
+    let backup = run(&["backup", "/tmp/never-written.ledger", "--dry-run"]);
+    assert!(!backup.status.success());
+    assert!(String::from_utf8_lossy(&backup.stderr).contains("not supported"));
+}
+
+#[test]
+fn test_cli_json_receipts_for_mutations() {
+    let ledger_path = temp_ledger_path("ledger_cli_receipts");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_receipts");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+
+    let run = |args: &[&str]| -> serde_json::Value {
+        let args = [args, &["--json"]].concat();
+        serde_json::from_str(&run_ledger_ok(
+            &ledger_path,
+            (&config_home, &data_home),
+            &args,
+        ))
+        .expect("parse receipt")
+    };
+
+    let composition = run(&["compositions", "create", "trip"]);
+    assert_eq!(composition["operation"], "composition.create");
+    let composition_id = composition["id"].as_str().expect("id").to_string();
+
+    let add = run(&[
+        "add",
+        "journal",
+        "--body",
+        "Receipt me",
+        "--compose",
+        "trip",
+        "--no-input",
+    ]);
+    assert_eq!(add["operation"], "add");
+    let entry_id = add["id"].as_str().expect("id").to_string();
+    assert!(add["created_at"].is_string());
+    assert_eq!(add["affected"][0]["kind"], "entry");
+    assert_eq!(add["affected"][1]["kind"], "composition");
+    assert_eq!(add["affected"][1]["id"], composition_id.as_str());
+
+    let edit = run(&[
+        "edit",
+        &entry_id,
+        "--body",
+        "Receipt me again",
+        "--no-input",
+    ]);
+    assert_eq!(edit["operation"], "edit");
+    assert_ne!(edit["id"], entry_id.as_str());
+    assert_eq!(edit["affected"][1]["kind"], "superseded");
+    assert_eq!(edit["affected"][1]["id"], entry_id.as_str());
+
+    let template = run(&["templates", "create", "daily", "--entry-type", "journal"]);
+    assert_eq!(template["operation"], "template.create");
+    assert_eq!(template["affected"][0]["name"], "daily");
+}
+
 #[test]
 fn test_cli_search_empty_message() {
     let ledger_path = temp_ledger_path("ledger_cli_search_empty");
@@ -752,6 +1187,68 @@ fn test_cli_add_body_no_input_skips_prompt() {
     assert!(add.status.success());
 }
 
+#[cfg(feature = "spellcheck")]
+#[test]
+fn test_cli_add_lint_warns_without_blocking() {
+    let ledger_path = temp_ledger_path("ledger_cli_add_lint");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_add_lint");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let wordlist = data_home.parent().unwrap().join("words");
+    std::fs::write(&wordlist, "the\nquick\nbrown\nfox\njumps\n").expect("write words");
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str(&format!("\n[lint]\nwordlist = {:?}\n", wordlist));
+    std::fs::write(&config_path, config).expect("write config");
+    std::fs::write(config_home.join("ledger").join("dictionary.txt"), "ledgr\n")
+        .expect("write dictionary");
+
+    let mut add = Command::new(bin());
+    add.arg("add")
+        .arg("journal")
+        .arg("--body")
+        .arg("The quikc brown fox jumps over ledgr")
+        .arg("--lint")
+        .arg("--no-input")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut add, &config_home, &data_home);
+    let add = add.output().expect("run add");
+    assert!(add.status.success());
+    let stderr = String::from_utf8_lossy(&add.stderr);
+    assert!(
+        stderr.contains("Possible typo: quikc (did you mean quick?)"),
+        "stderr: {}",
+        stderr
+    );
+    assert!(
+        stderr.contains("Possible typo: over\n"),
+        "stderr: {}",
+        stderr
+    );
+    assert!(!stderr.contains("ledgr"), "personal dictionary words pass");
+
+    let mut search = Command::new(bin());
+    search
+        .arg("search")
+        .arg("quikc")
+        .arg("--json")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut search, &config_home, &data_home);
+    let search = search.output().expect("run search");
+    let value: serde_json::Value = serde_json::from_slice(&search.stdout).expect("parse json");
+    assert_eq!(
+        value.as_array().map(Vec::len),
+        Some(1),
+        "body saved as typed"
+    );
+}
+
 #[test]
 fn test_cli_edit_creates_revision() {
     let ledger_path = temp_ledger_path("ledger_cli_edit_revision");
@@ -1024,7 +1521,11 @@ fn test_cli_editor_override_is_used() {
     ));
     std::fs::create_dir_all(&editor_dir).expect("create editor dir");
     let editor_path = editor_dir.join("editor.sh");
-    let script = "#!/bin/sh\nprintf \"Editor content\" > \"$1\"\n";
+    let seen_path = editor_dir.join("draft_path.txt");
+    let script = format!(
+        "#!/bin/sh\nprintf \"Editor content\" > \"$1\"\nprintf \"%s\" \"$1\" > \"{}\"\n",
+        seen_path.display()
+    );
     std::fs::write(&editor_path, script).expect("write editor script");
     let mut perms = std::fs::metadata(&editor_path)
         .expect("stat editor")
@@ -1038,7 +1539,7 @@ fn test_cli_editor_override_is_used() {
 
     let config_path = config_home.join("ledger").join("config.toml");
     let contents = format!(
-        "[ledger]\npath = \"{}\"\n\n[security]\ntier = \"passphrase\"\npassphrase_cache_ttl_seconds = 0\n\n[keychain]\nenabled = false\n\n[keyfile]\nmode = \"none\"\n\n[ui]\neditor = \"{}\"\n",
+        "config_version = 1\n\n[ledger]\npath = \"{}\"\n\n[security]\ntier = \"passphrase\"\npassphrase_cache_ttl_seconds = 0\n\n[keychain]\nenabled = false\n\n[keyfile]\nmode = \"none\"\n\n[ui]\neditor = \"{}\"\n",
         ledger_path.to_string_lossy(),
         editor_path.to_string_lossy()
     );
@@ -1071,6 +1572,129 @@ fn test_cli_editor_override_is_used() {
         .and_then(|v| v.as_str())
         .expect("body");
     assert_eq!(body, "Editor content");
+
+    // The draft lived in a private runtime directory and is gone
+    let draft = PathBuf::from(std::fs::read_to_string(&seen_path).expect("draft path"));
+    let runtime = data_home.parent().unwrap().join("runtime");
+    assert!(draft.starts_with(runtime.join("ledger").join("drafts")));
+    assert!(!draft.exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(draft.parent().unwrap())
+            .expect("stat drafts dir")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+}
+
+#[test]
+fn test_cli_add_editor_flag_with_arguments() {
+    let ledger_path = temp_ledger_path("ledger_cli_editor_flag");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_editor_flag");
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    // The editor command is split into program and arguments
+    let output = run(&[
+        "add",
+        "journal",
+        "--editor",
+        "sh -c 'printf \"From the flag\" > \"$1\"' editor",
+        "--json",
+    ]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let output = run(&["list", "--json"]);
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).expect("parse list");
+    assert_eq!(value[0]["data"]["body"], "From the flag");
+
+    // A GUI editor launched without its wait flag returns at once
+    let editor_dir = data_home.join("bin");
+    std::fs::create_dir_all(&editor_dir).expect("create editor dir");
+    let code = editor_dir.join("code");
+    std::fs::write(&code, "#!/bin/sh\nexit 0\n").expect("write editor");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&code, std::fs::Permissions::from_mode(0o700))
+            .expect("chmod editor");
+    }
+    let output = run(&["add", "journal", "--editor", code.to_str().unwrap()]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--wait"), "stderr={}", stderr);
+}
+
+#[test]
+fn test_cli_idempotency_key_replays_add_and_import() {
+    let ledger_path = temp_ledger_path("ledger_cli_idempotency");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_idempotency");
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    let add = [
+        "add",
+        "journal",
+        "--body",
+        "Retried",
+        "--idempotency-key",
+        "cron-2024-01-01",
+        "--json",
+    ];
+    let first: serde_json::Value = serde_json::from_str(&run(&add)).expect("parse receipt");
+    assert!(first.get("replayed").is_none());
+    let second: serde_json::Value = serde_json::from_str(&run(&add)).expect("parse receipt");
+    assert_eq!(second["id"], first["id"]);
+    assert_eq!(second["replayed"], true);
+    let list: serde_json::Value =
+        serde_json::from_str(&run(&["list", "--json"])).expect("parse list");
+    assert_eq!(list.as_array().map(Vec::len), Some(1));
+
+    let tasks = data_home.join("todo.txt");
+    std::fs::write(&tasks, "Call the bank\n").expect("write todo.txt");
+    let import = [
+        "import",
+        tasks.to_str().unwrap(),
+        "--from",
+        "todotxt",
+        "--idempotency-key",
+        "sync-1",
+    ];
+    let stdout = run(&import);
+    assert!(stdout.contains("entries=1"), "{}", stdout);
+    assert!(!stdout.contains("replayed"));
+    let stdout = run(&import);
+    assert!(stdout.contains("entries=1"), "{}", stdout);
+    assert!(stdout.contains("replayed=true"), "{}", stdout);
+
+    // Keys are scoped to the operation that used them first
+    let mut reuse = Command::new(bin());
+    reuse
+        .args([
+            "add",
+            "journal",
+            "--body",
+            "x",
+            "--idempotency-key",
+            "sync-1",
+        ])
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut reuse, &config_home, &data_home);
+    let reuse = reuse.output().expect("run add");
+    assert!(!reuse.status.success());
+    assert!(String::from_utf8_lossy(&reuse.stderr).contains("already used for import"));
 }
 
 #[test]
@@ -1358,7 +1982,130 @@ fn test_cli_cache_lock_clears_cache() {
 }
 
 #[test]
-fn test_cli_cache_expires_after_ttl() {
+fn test_cli_cache_issues_token_file_and_lock_removes_it() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ledger_path = temp_ledger_path("ledger_cli_cache_token");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_cache_token");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 300);
+
+    let mut list = Command::new(bin());
+    list.arg("list")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut list, &config_home, &data_home);
+    let list = list.output().expect("run list");
+    assert!(list.status.success());
+
+    let token_path = cache_socket_path(&data_home)
+        .with_file_name(format!("cache-{}.token", ledger_hash(&ledger_path)));
+    let token = std::fs::read_to_string(&token_path).expect("token file");
+    assert!(!token.contains(&STANDARD.encode(passphrase.as_bytes())));
+    let mode = std::fs::metadata(&token_path)
+        .expect("token metadata")
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    let mut lock = Command::new(bin());
+    lock.arg("lock");
+    apply_xdg_env(&mut lock, &config_home, &data_home);
+    assert!(lock.output().expect("run lock").status.success());
+    assert!(!token_path.exists());
+}
+
+#[test]
+fn test_cli_lock_status_and_single_ledger() {
+    let first_path = temp_ledger_path("ledger_cli_lock_first");
+    let second_path = temp_ledger_path("ledger_cli_lock_second");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_lock_multi");
+
+    create_ledger_with_passphrase(&first_path, passphrase);
+    create_ledger_with_passphrase(&second_path, passphrase);
+    write_config_file(&config_home, &first_path, "passphrase", "none", None, 300);
+
+    let list_ledger = |path: &Path, with_passphrase: bool| {
+        let mut list = ledger_command(path, (&config_home, &data_home), &["list"]);
+        if !with_passphrase {
+            list.env_remove("LEDGER_PASSPHRASE");
+        }
+        list.output().expect("run list").status.success()
+    };
+    assert!(list_ledger(&first_path, true));
+    assert!(list_ledger(&second_path, true));
+
+    let mut status = Command::new(bin());
+    status.arg("lock").arg("--status");
+    apply_xdg_env(&mut status, &config_home, &data_home);
+    let status = status.output().expect("run lock status");
+    assert!(status.status.success());
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(stdout.contains("sessions=2"), "stdout: {}", stdout);
+    assert!(stdout.contains(&ledger_hash(&first_path)));
+    assert!(stdout.contains(&ledger_hash(&second_path)));
+
+    let mut lock = Command::new(bin());
+    lock.arg("lock").arg("--ledger").arg(&first_path);
+    apply_xdg_env(&mut lock, &config_home, &data_home);
+    let lock = lock.output().expect("run lock ledger");
+    assert!(lock.status.success());
+    assert!(String::from_utf8_lossy(&lock.stdout).contains("locked=true"));
+
+    assert!(!list_ledger(&first_path, false));
+    assert!(list_ledger(&second_path, false));
+}
+
+#[test]
+fn test_cli_lock_now_stops_daemon_and_wipes_drafts() {
+    let ledger_path = temp_ledger_path("ledger_cli_lock_now");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_lock_now");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 300);
+
+    let list_ledger = |with_passphrase: bool| {
+        let mut list = ledger_command(&ledger_path, (&config_home, &data_home), &["list"]);
+        if !with_passphrase {
+            list.env_remove("LEDGER_PASSPHRASE");
+        }
+        list.output().expect("run list").status.success()
+    };
+    assert!(list_ledger(true));
+    assert!(list_ledger(false), "passphrase should be cached");
+
+    // A draft left behind by an editor session, in the test's TMPDIR
+    let runtime = data_home.parent().unwrap().join("runtime");
+    let draft = runtime.join("ledger_entry_1_1.md");
+    std::fs::write(&draft, "plaintext draft").expect("write draft");
+
+    let mut lock = Command::new(bin());
+    lock.arg("lock").arg("--now");
+    apply_xdg_env(&mut lock, &config_home, &data_home);
+    let lock = lock.output().expect("run lock now");
+    assert!(lock.status.success());
+    let stdout = String::from_utf8_lossy(&lock.stdout);
+    assert!(stdout.contains("daemon=stopped"), "stdout: {}", stdout);
+    assert!(stdout.contains("drafts_wiped=1"), "stdout: {}", stdout);
+    assert!(!draft.exists());
+
+    assert!(!list_ledger(false), "cache should be gone after lock --now");
+
+    let mut again = Command::new(bin());
+    again.arg("lock").arg("--now");
+    apply_xdg_env(&mut again, &config_home, &data_home);
+    let again = again.output().expect("run lock now again");
+    assert!(again.status.success());
+    assert!(String::from_utf8_lossy(&again.stdout).contains("daemon=not_running"));
+}
+
+#[test]
+fn test_cli_cache_expires_after_ttl() {
     let ledger_path = temp_ledger_path("ledger_cli_cache_ttl");
     let passphrase = "test-passphrase-secure-123";
     let (config_home, data_home) = temp_xdg_dirs("ledger_cli_cache_ttl");
@@ -1478,6 +2225,84 @@ fn test_cli_cache_clears_on_incorrect_passphrase() {
     assert!(list_after.status.success());
 }
 
+#[test]
+fn test_cli_move_relocates_ledger_and_config() {
+    let ledger_path = temp_ledger_path("ledger_cli_move");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_move");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let moved_path = ledger_path.with_extension("moved");
+
+    // Without --force and no prompt, the old ledger is kept
+    let mut keep = Command::new(bin());
+    keep.arg("move")
+        .arg("--to")
+        .arg(&moved_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut keep, &config_home, &data_home);
+    let keep = keep.output().expect("run move");
+    assert!(
+        keep.status.success(),
+        "{}",
+        String::from_utf8_lossy(&keep.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&keep.stdout);
+    assert!(stdout.contains("config_updated=true"));
+    assert!(stdout.contains("old_removed=false"));
+    assert!(ledger_path.exists());
+    assert_eq!(
+        std::fs::read(&ledger_path).expect("read old"),
+        std::fs::read(&moved_path).expect("read new")
+    );
+
+    // The config now points at the new file, so no --ledger is needed
+    let mut list = Command::new(bin());
+    list.arg("list").env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut list, &config_home, &data_home);
+    let list = list.output().expect("run list");
+    assert!(
+        list.status.success(),
+        "{}",
+        String::from_utf8_lossy(&list.stderr)
+    );
+    let config = std::fs::read_to_string(config_home.join("ledger").join("config.toml"))
+        .expect("read config");
+    assert!(config.contains(&*moved_path.file_name().unwrap().to_string_lossy()));
+
+    // Refuses to overwrite an existing file
+    let mut clash = Command::new(bin());
+    clash
+        .arg("move")
+        .arg("--to")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut clash, &config_home, &data_home);
+    let clash = clash.output().expect("run move clash");
+    assert!(!clash.status.success());
+
+    let back_path = ledger_path.with_extension("back");
+    let mut force = Command::new(bin());
+    force
+        .arg("move")
+        .arg("--to")
+        .arg(&back_path)
+        .arg("--force")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut force, &config_home, &data_home);
+    let force = force.output().expect("run move --force");
+    assert!(
+        force.status.success(),
+        "{}",
+        String::from_utf8_lossy(&force.stderr)
+    );
+    assert!(!moved_path.exists());
+    assert!(back_path.exists());
+}
+
 #[test]
 fn test_cli_wrong_passphrase_exit_code() {
     let ledger_path = temp_ledger_path("ledger_cli_wrong_passphrase");
@@ -1544,6 +2369,251 @@ fn test_cli_passphrase_retry_exits_after_three_failures() {
     assert!(stderr.contains("Too many failed passphrase attempts"));
 }
 
+#[cfg(feature = "viewer")]
+#[test]
+fn test_cli_viewer_reads_without_writing() {
+    let ledger_path = temp_ledger_path("ledger_cli_viewer");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_viewer");
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+
+    let mut add = Command::new(bin());
+    add.args(["add", "journal", "--body", "Read me elsewhere"])
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut add, &config_home, &data_home);
+    assert!(add.output().expect("run add").status.success());
+    let before = std::fs::read(&ledger_path).expect("read ledger");
+
+    let viewer = |args: &[&str]| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_ledger-viewer"));
+        cmd.args(args)
+            .arg("--ledger")
+            .arg(&ledger_path)
+            .env("LEDGER_PASSPHRASE", passphrase);
+        apply_xdg_env(&mut cmd, &config_home, &data_home);
+        cmd.output().expect("run ledger-viewer")
+    };
+
+    let list = viewer(&["list", "--json"]);
+    assert!(
+        list.status.success(),
+        "{}",
+        String::from_utf8_lossy(&list.stderr)
+    );
+    let entries: serde_json::Value = serde_json::from_slice(&list.stdout).expect("parse list");
+    assert_eq!(entries.as_array().map(Vec::len), Some(1));
+    let search = viewer(&["search", "elsewhere"]);
+    assert!(String::from_utf8_lossy(&search.stdout).contains("Read me elsewhere"));
+
+    // Mutating commands are not part of the viewer
+    let add = viewer(&["add", "journal", "--body", "nope"]);
+    assert!(!add.status.success());
+    assert!(String::from_utf8_lossy(&add.stderr).contains("unrecognized subcommand"));
+    assert_eq!(std::fs::read(&ledger_path).expect("read ledger"), before);
+
+    // A failed unlock is refused without recording it
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let config = std::fs::read_to_string(&config_path).expect("read config");
+    let config = config.replace("[security]\n", "[security]\nlockout = true\n");
+    std::fs::write(&config_path, &config).expect("write config");
+    let mut wrong = Command::new(env!("CARGO_BIN_EXE_ledger-viewer"));
+    wrong
+        .args(["list", "--ledger"])
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", "wrong-passphrase-123456");
+    apply_xdg_env(&mut wrong, &config_home, &data_home);
+    let wrong = wrong.output().expect("run ledger-viewer");
+    assert!(!wrong.status.success());
+    assert!(String::from_utf8_lossy(&wrong.stderr).contains("Incorrect passphrase"));
+    let lockout_file = PathBuf::from(format!("{}.lockout", ledger_path.display()));
+    assert!(!lockout_file.exists());
+    assert_eq!(
+        std::fs::read_to_string(&config_path).expect("read config"),
+        config
+    );
+}
+
+#[cfg(feature = "test-support")]
+#[test]
+fn test_cli_internal_generate_fills_ledger() {
+    let ledger_path = temp_ledger_path("ledger_cli_generate");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_generate");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    let generated = run(&[
+        "internal-generate",
+        "--entries",
+        "150",
+        "--types",
+        "2",
+        "--tags",
+        "10",
+        "--json",
+    ]);
+    let generated: serde_json::Value = serde_json::from_str(&generated).expect("parse json");
+    assert_eq!(generated["entries"], 150);
+
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_str(&run(&["list", "--limit", "1000", "--json"])).expect("parse list");
+    assert_eq!(entries.len(), 150);
+    let types: std::collections::HashSet<_> = entries
+        .iter()
+        .map(|e| {
+            e["entry_type_name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(types.len(), 2);
+    assert!(
+        entries
+            .iter()
+            .flat_map(|e| e["tags"].as_array().cloned().unwrap_or_default())
+            .count()
+            > 0
+    );
+
+    // Generated types are reused, so a second run only adds entries
+    run(&["internal-generate", "--entries", "10", "--types", "2"]);
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_str(&run(&["list", "--limit", "1000", "--json"])).expect("parse list");
+    assert_eq!(entries.len(), 160);
+}
+
+#[test]
+fn test_cli_exit_code_contract() {
+    let ledger_path = temp_ledger_path("ledger_cli_exit_contract");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_exit_contract");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str("\n[ui]\ntimezon = \"UTC\"\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    let missing = ledger_path.with_extension("missing");
+    let cases: &[(&[&str], &str, i32)] = &[
+        (&["list"], passphrase, 0),
+        (&["add"], passphrase, 2),
+        (
+            &["show", "00000000-0000-0000-0000-000000000000"],
+            passphrase,
+            3,
+        ),
+        (&["list"], "wrong-passphrase", 5),
+        (&["--strict", "list"], passphrase, 7),
+    ];
+    for (args, unlock, expected) in cases {
+        let mut cmd = Command::new(bin());
+        cmd.args(*args)
+            .arg("--ledger")
+            .arg(&ledger_path)
+            .env("LEDGER_PASSPHRASE", unlock);
+        apply_xdg_env(&mut cmd, &config_home, &data_home);
+        let output = cmd.output().expect("run ledger");
+        assert_eq!(
+            output.status.code(),
+            Some(*expected),
+            "ledger {} -> {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let mut list = Command::new(bin());
+    list.arg("list")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut list, &config_home, &data_home);
+    let list = list.output().expect("run list");
+    let stderr = String::from_utf8_lossy(&list.stderr);
+    assert!(stderr.contains("Warning: Unknown config key"));
+    assert!(stderr.contains("ui.timezon"));
+
+    let mut strict = Command::new(bin());
+    strict
+        .arg("--strict")
+        .arg("list")
+        .arg("--ledger")
+        .arg(&missing)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut strict, &config_home, &data_home);
+    let strict = strict.output().expect("run strict list");
+    assert_eq!(strict.status.code(), Some(7));
+    let stderr = String::from_utf8_lossy(&strict.stderr);
+    assert!(stderr.contains("--strict treats warnings as errors"));
+}
+
+#[test]
+fn test_cli_theme_config_is_checked() {
+    let ledger_path = temp_ledger_path("ledger_cli_theme");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_theme");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let base = std::fs::read_to_string(&config_path).expect("read config");
+
+    let run_with = |ui: &str| {
+        std::fs::write(&config_path, format!("{}\n{}", base, ui)).expect("write config");
+        run_ledger(
+            &ledger_path,
+            (&config_home, &data_home),
+            &["--strict", "list", "--json"],
+        )
+    };
+    let run = |theme: &str| run_with(&format!("[ui]\ntheme = \"{}\"\n", theme));
+
+    for theme in ["dark", "light", "none", "colorblind"] {
+        let output = run(theme);
+        assert!(
+            output.status.success(),
+            "theme {}: {}",
+            theme,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let output = run("solarized");
+    assert_eq!(output.status.code(), Some(7));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unsupported theme in config: solarized"));
+    assert!(stderr.contains("dark, light, none, colorblind"));
+
+    let output = run_with("[ui.theme]\nbase = \"colorblind\"\naccent = \"#87d7ff\"\nwarn = 214\n");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = run_with("[ui.theme]\nwarm = \"red\"\n");
+    assert_eq!(output.status.code(), Some(7));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ui.theme.warm"), "{}", stderr);
+
+    let output = run_with("[ui.theme]\naccent = \"#87d7f\"\n");
+    assert_eq!(output.status.code(), Some(7));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Unsupported color in config: ui.theme.accent = #87d7f"),
+        "{}",
+        stderr
+    );
+}
+
 #[test]
 fn test_cli_invalid_args_exit_code() {
     let output = Command::new(bin()).arg("add").output().expect("run add");
@@ -1598,6 +2668,48 @@ fn test_cli_doctor_ok() {
     let stdout = String::from_utf8_lossy(&doctor.stdout);
     // Plain mode output (non-TTY) uses key=value format
     assert!(stdout.contains("status=ok"));
+    assert!(stdout.contains("check=location ok"));
+}
+
+#[test]
+fn test_cli_warns_for_synced_folder_ledger() {
+    let scratch = temp_ledger_path("ledger_cli_synced").with_extension("d");
+    let synced_dir = scratch.join("Dropbox");
+    std::fs::create_dir_all(&synced_dir).expect("create synced dir");
+    let ledger_path = synced_dir.join("my.ledger");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_synced");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let mut list = Command::new(bin());
+    list.arg("list").env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut list, &config_home, &data_home);
+    let list = list.output().expect("run list");
+    assert!(list.status.success());
+    let stderr = String::from_utf8_lossy(&list.stderr);
+    assert!(stderr.contains("Warning: Ledger is in a Dropbox folder"));
+
+    let mut strict = Command::new(bin());
+    strict
+        .arg("--strict")
+        .arg("list")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut strict, &config_home, &data_home);
+    let strict = strict.output().expect("run strict list");
+    assert_eq!(strict.status.code(), Some(7));
+
+    let mut doctor = Command::new(bin());
+    doctor.arg("doctor").env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut doctor, &config_home, &data_home);
+    let doctor = doctor.output().expect("run doctor");
+    assert!(doctor.status.success());
+    let stdout = String::from_utf8_lossy(&doctor.stdout);
+    assert!(stdout.contains("check=location warn"));
+    assert!(stdout.contains("location_risk=Ledger is in a Dropbox folder"));
+
+    let _ = std::fs::remove_dir_all(&scratch);
 }
 
 // ============================================================================
@@ -1865,6 +2977,27 @@ fn test_cli_attach_detach_entry() {
         "expected 0 entries, got: {}",
         stdout
     );
+
+    // History keeps the detached membership
+    let mut history = Command::new(bin());
+    history
+        .arg("compositions")
+        .arg("show")
+        .arg("research")
+        .arg("--history")
+        .arg("--json")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut history, &config_home, &data_home);
+    let history = history.output().expect("run compositions show --history");
+    assert!(history.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&history.stdout).expect("parse json");
+    assert_eq!(value["entry_count"], 0);
+    let rows = value["history"].as_array().expect("history array");
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["entry_id"], entry_id);
+    assert!(rows[0]["removed_at"].is_string());
 }
 
 // ============================================================================
@@ -2326,6 +3459,86 @@ fn test_cli_composition_not_found() {
     assert!(!delete.status.success());
 }
 
+#[test]
+fn test_cli_template_by_weekday_defaults() {
+    let ledger_path = temp_ledger_path("ledger_cli_tmpl_weekday");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_tmpl_weekday");
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let output = run(&[
+        "templates",
+        "create",
+        "daily",
+        "--entry-type",
+        "journal",
+        "--defaults",
+        r#"{"body": "Today"}"#,
+        "--by-weekday",
+        r#"{"fri": {"body": "Week retro"}}"#,
+    ]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let output = run(&[
+        "templates",
+        "update",
+        "daily",
+        "--by-weekday",
+        r#"{"someday": {}}"#,
+    ]);
+    assert!(!output.status.success());
+
+    let output = run(&["templates", "show", "daily", "--no-color"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("by_weekday.fri=body"), "stdout={}", stdout);
+
+    // 2026-10-16 is a Friday, 2026-10-14 a Wednesday
+    for date in ["2026-10-16T12:00:00Z", "2026-10-14T12:00:00Z"] {
+        let output = run(&[
+            "add",
+            "journal",
+            "--template",
+            "daily",
+            "--date",
+            date,
+            "--no-input",
+        ]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let output = run(&["list", "--json"]);
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).expect("parse list");
+    let bodies: Vec<(&str, &str)> = value
+        .as_array()
+        .expect("list array")
+        .iter()
+        .map(|entry| {
+            (
+                &entry["created_at"].as_str().unwrap()[..10],
+                entry["data"]["body"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert!(
+        bodies.contains(&("2026-10-16", "Week retro")),
+        "{:?}",
+        bodies
+    );
+    assert!(bodies.contains(&("2026-10-14", "Today")), "{:?}", bodies);
+}
+
 #[test]
 fn test_cli_template_not_found() {
     let ledger_path = temp_ledger_path("ledger_cli_tmpl_nf");
@@ -2683,3 +3896,4380 @@ fn test_cli_no_compose_prevents_attachment() {
     // Plain mode output: entry_count=0
     assert!(stdout.contains("entry_count=0"), "stdout: {}", stdout);
 }
+
+#[test]
+fn test_cli_bookmark_add_and_group_by_domain() {
+    let ledger_path = temp_ledger_path("ledger_cli_bookmark");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_bookmark");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    let init = init.output().expect("run init");
+    assert!(init.status.success());
+
+    for (url, title) in [
+        ("https://www.example.com/one", "First"),
+        ("https://example.com/two", "Second"),
+        ("https://blog.rust-lang.org/post", "Rust"),
+    ] {
+        let mut add = Command::new(bin());
+        add.arg("add")
+            .arg("bookmark")
+            .arg("--url")
+            .arg(url)
+            .arg("--field")
+            .arg(format!("title={}", title))
+            .arg("--no-input")
+            .arg("--ledger")
+            .arg(&ledger_path)
+            .env("LEDGER_PASSPHRASE", passphrase);
+        apply_xdg_env(&mut add, &config_home, &data_home);
+        let add = add.output().expect("run add bookmark");
+        assert!(
+            add.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&add.stderr)
+        );
+    }
+
+    // Invalid URLs are rejected
+    let mut bad = Command::new(bin());
+    bad.arg("add")
+        .arg("bookmark")
+        .arg("--url")
+        .arg("not-a-url")
+        .arg("--no-input")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut bad, &config_home, &data_home);
+    let bad = bad.output().expect("run add bookmark with bad url");
+    assert!(!bad.status.success());
+
+    let mut list = Command::new(bin());
+    list.arg("list")
+        .arg("bookmark")
+        .arg("--by-domain")
+        .arg("--format")
+        .arg("plain")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut list, &config_home, &data_home);
+    let list = list.output().expect("run list by domain");
+    assert!(list.status.success());
+    let stdout = String::from_utf8_lossy(&list.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3, "stdout: {}", stdout);
+    assert!(lines[0].starts_with("example.com "), "stdout: {}", stdout);
+    assert!(lines[1].starts_with("example.com "), "stdout: {}", stdout);
+    assert!(
+        lines[2].starts_with("blog.rust-lang.org "),
+        "stdout: {}",
+        stdout
+    );
+    assert!(lines[2].ends_with("Rust"), "stdout: {}", stdout);
+
+    let mut list_json = Command::new(bin());
+    list_json
+        .arg("list")
+        .arg("bookmark")
+        .arg("--by-domain")
+        .arg("--json")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut list_json, &config_home, &data_home);
+    let list_json = list_json.output().expect("run list by domain json");
+    let value: serde_json::Value =
+        serde_json::from_slice(&list_json.stdout).expect("parse grouped json");
+    assert_eq!(value[0]["domain"], "example.com");
+    assert_eq!(value[0]["count"], 2);
+    assert_eq!(value[1]["entries"][0]["data"]["title"], "Rust");
+}
+
+#[test]
+fn test_cli_add_from_audio_transcribes_and_attaches() {
+    let ledger_path = temp_ledger_path("ledger_cli_audio");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_audio");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    let init = init.output().expect("run init");
+    assert!(init.status.success());
+
+    let audio_path = data_home.join("memo.m4a");
+    std::fs::write(&audio_path, b"fake audio").expect("write audio");
+
+    let add_audio = |expect_transcript: bool| {
+        let mut add = Command::new(bin());
+        add.arg("add")
+            .arg("journal")
+            .arg("--from-audio")
+            .arg(&audio_path)
+            .arg("--no-input")
+            .arg("--ledger")
+            .arg(&ledger_path)
+            .env("LEDGER_PASSPHRASE", passphrase);
+        apply_xdg_env(&mut add, &config_home, &data_home);
+        let add = add.output().expect("run add from audio");
+        assert!(
+            add.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&add.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&add.stdout).to_string();
+        assert!(stdout.contains("attachment_count=1"), "stdout: {}", stdout);
+        assert!(
+            stdout.contains(&format!("transcribed={}", expect_transcript)),
+            "stdout: {}",
+            stdout
+        );
+        stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("entry_id="))
+            .expect("entry id")
+            .to_string()
+    };
+
+    let show_json = |entry_id: &str| -> serde_json::Value {
+        let show = run_ledger(
+            &ledger_path,
+            (&config_home, &data_home),
+            &["show", entry_id, "--json"],
+        );
+        serde_json::from_slice(&show.stdout).expect("parse show json")
+    };
+
+    // No transcriber configured: attachment-only with a placeholder body
+    let entry_id = add_audio(false);
+    let value = show_json(&entry_id);
+    assert_eq!(value["data"]["body"], "Voice memo: memo.m4a");
+    assert_eq!(value["attachments"][0]["name"], "memo.m4a");
+    assert_eq!(value["attachments"][0]["mime_type"], "audio/mp4");
+
+    // Configure a transcriber that echoes its input
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str("\n[capture]\ntranscriber = \"echo transcript of\"\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    let entry_id = add_audio(true);
+    let value = show_json(&entry_id);
+    let body = value["data"]["body"].as_str().expect("body");
+    assert!(body.starts_with("transcript of "), "body: {}", body);
+    assert!(body.ends_with("memo.m4a"), "body: {}", body);
+    assert_eq!(value["attachments"].as_array().map(|a| a.len()), Some(1));
+}
+
+fn jpeg_with_exif() -> Vec<u8> {
+    use exif::experimental::Writer;
+    use exif::{Field, In, Rational, Tag, Value};
+
+    let fields = [
+        Field {
+            tag: Tag::DateTimeOriginal,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"2023:08:14 18:30:00".to_vec()]),
+        },
+        Field {
+            tag: Tag::OffsetTimeOriginal,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"+00:00".to_vec()]),
+        },
+        Field {
+            tag: Tag::GPSLatitudeRef,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"N".to_vec()]),
+        },
+        Field {
+            tag: Tag::GPSLatitude,
+            ifd_num: In::PRIMARY,
+            value: Value::Rational(vec![
+                Rational::from((51, 1)),
+                Rational::from((30, 1)),
+                Rational::from((0, 1)),
+            ]),
+        },
+        Field {
+            tag: Tag::GPSLongitudeRef,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"W".to_vec()]),
+        },
+        Field {
+            tag: Tag::GPSLongitude,
+            ifd_num: In::PRIMARY,
+            value: Value::Rational(vec![
+                Rational::from((0, 1)),
+                Rational::from((7, 1)),
+                Rational::from((30, 1)),
+            ]),
+        },
+    ];
+    let mut writer = Writer::new();
+    for field in &fields {
+        writer.push_field(field);
+    }
+    let mut tiff = std::io::Cursor::new(Vec::new());
+    writer.write(&mut tiff, false).expect("write exif");
+    let tiff = tiff.into_inner();
+
+    let mut app1 = b"Exif\0\0".to_vec();
+    app1.extend_from_slice(&tiff);
+    let len = (app1.len() + 2) as u16;
+    let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+    jpeg.extend_from_slice(&len.to_be_bytes());
+    jpeg.extend_from_slice(&app1);
+    jpeg.extend_from_slice(&[0xFF, 0xD9]);
+    jpeg
+}
+
+#[test]
+fn test_cli_add_photo_uses_exif_metadata() {
+    let ledger_path = temp_ledger_path("ledger_cli_photo");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_photo");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    let init = init.output().expect("run init");
+    assert!(init.status.success());
+
+    let photo_path = data_home.join("sunset.jpg");
+    std::fs::write(&photo_path, jpeg_with_exif()).expect("write photo");
+
+    let mut add = Command::new(bin());
+    add.arg("add")
+        .arg("photo")
+        .arg("--file")
+        .arg(&photo_path)
+        .arg("--body")
+        .arg("Sunset over the river")
+        .arg("--no-input")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut add, &config_home, &data_home);
+    let add = add.output().expect("run add photo");
+    assert!(
+        add.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&add.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&add.stdout);
+    assert!(stdout.contains("attachment_count=1"), "stdout: {}", stdout);
+    let entry_id = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("entry_id="))
+        .expect("entry id")
+        .to_string();
+
+    let mut show = Command::new(bin());
+    show.arg("show")
+        .arg(&entry_id)
+        .arg("--json")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut show, &config_home, &data_home);
+    let show = show.output().expect("run show");
+    let value: serde_json::Value = serde_json::from_slice(&show.stdout).expect("parse show");
+    assert_eq!(value["data"]["file"], "sunset.jpg");
+    assert_eq!(value["data"]["caption"], "Sunset over the river");
+    assert!(value["created_at"]
+        .as_str()
+        .expect("created_at")
+        .starts_with("2023-08-14T18:30:00"));
+    let lat = value["data"]["latitude"].as_f64().expect("latitude");
+    let lon = value["data"]["longitude"].as_f64().expect("longitude");
+    assert!((lat - 51.5).abs() < 1e-9, "lat={}", lat);
+    assert!((lon + 0.125).abs() < 1e-9, "lon={}", lon);
+    assert_eq!(value["attachments"][0]["mime_type"], "image/jpeg");
+
+    // --file is required for photo entries
+    let mut missing = Command::new(bin());
+    missing
+        .arg("add")
+        .arg("photo")
+        .arg("--no-input")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut missing, &config_home, &data_home);
+    let missing = missing.output().expect("run add photo without file");
+    assert!(!missing.status.success());
+}
+
+#[test]
+fn test_cli_collect_calendar_activity() {
+    let ledger_path = temp_ledger_path("ledger_cli_collect");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_collect");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    let init = init.output().expect("run init");
+    assert!(init.status.success());
+
+    let ics_path = data_home.join("work.ics");
+    std::fs::write(
+        &ics_path,
+        "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Design review\r\n\
+DTSTART:20240305T120000\r\nDTEND:20240305T130000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+    )
+    .expect("write ics");
+
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str(&format!(
+        "\n[collectors]\nenabled = [\"calendar\"]\ncalendar_files = [\"{}\"]\n",
+        ics_path.display()
+    ));
+    std::fs::write(&config_path, config).expect("write config");
+
+    let collect = || {
+        run_ledger_ok(
+            &ledger_path,
+            (&config_home, &data_home),
+            &["collect", "--date", "2024-03-05", "--no-input"],
+        )
+    };
+
+    let stdout = collect();
+    assert!(stdout.contains("status=added"), "stdout: {}", stdout);
+    assert!(
+        stdout.contains("summary=1 calendar event"),
+        "stdout: {}",
+        stdout
+    );
+    let entry_id = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("activity="))
+        .and_then(|rest| rest.split_whitespace().next())
+        .expect("activity id")
+        .to_string();
+
+    // Re-running the same day does not duplicate entries
+    let stdout = collect();
+    assert!(stdout.contains("status=unchanged"), "stdout: {}", stdout);
+
+    let mut show = Command::new(bin());
+    show.arg("show")
+        .arg(&entry_id)
+        .arg("--json")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut show, &config_home, &data_home);
+    let show = show.output().expect("run show");
+    let value: serde_json::Value = serde_json::from_slice(&show.stdout).expect("parse show json");
+    assert_eq!(value["entry_type_name"], "activity");
+    assert_eq!(value["data"]["source"], "calendar");
+    assert_eq!(value["data"]["date"], "2024-03-05");
+    assert_eq!(value["data"]["details"], "12:00 Design review");
+    assert!(value["tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|t| t == "collector:calendar"));
+}
+
+#[test]
+fn test_cli_report_standup_groups_entries() {
+    let ledger_path = temp_ledger_path("ledger_cli_standup");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_standup");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    run(&["compositions", "create", "billing"]);
+    run(&[
+        "add",
+        "journal",
+        "--body",
+        "Fixed invoice rounding",
+        "--tag",
+        "work",
+        "--compose",
+        "billing",
+        "--no-input",
+    ]);
+    run(&[
+        "add",
+        "journal",
+        "--body",
+        "Waiting on API keys",
+        "--tag",
+        "work",
+        "--tag",
+        "blocker",
+        "--no-input",
+    ]);
+    run(&["add", "journal", "--body", "Went for a run", "--no-input"]);
+
+    let report = run(&["report", "standup", "--last", "1d", "--tag", "work"]);
+    assert!(report.contains("## Yesterday\n\n### billing\n\n- Fixed invoice rounding"));
+    assert!(report.contains("## Blockers\n\n- Waiting on API keys"));
+    assert!(!report.contains("Went for a run"), "report: {}", report);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&run(&["report", "standup", "--json"])).expect("parse json");
+    assert_eq!(json["yesterday"].as_array().unwrap().len(), 2);
+    assert_eq!(json["blockers"][0]["summary"], "Waiting on API keys");
+}
+
+#[test]
+fn test_cli_hierarchical_tags_filter_and_tree() {
+    let ledger_path = temp_ledger_path("ledger_cli_tag_tree");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_tag_tree");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let add = |body: &str, tag: &str| {
+        run(&["add", "journal", "--body", body, "--tag", tag, "--no-input"]);
+    };
+    add("Sprint planning notes", "work/project-x/meetings");
+    add("Inbox zero", "work");
+    add("Pottery class notes", "workshop");
+
+    let list = run(&["list", "--tag", "work/", "--format", "plain"]);
+    assert!(list.contains("Sprint planning notes"), "{}", list);
+    assert!(list.contains("Inbox zero"));
+    assert!(!list.contains("Pottery"));
+
+    let exact = run(&["list", "--tag", "work", "--format", "plain"]);
+    assert!(exact.contains("Inbox zero"));
+    assert!(!exact.contains("Sprint planning"));
+
+    let search = run(&["search", "notes", "--tag", "work/", "--format", "plain"]);
+    assert!(search.contains("Sprint planning notes"), "{}", search);
+    assert!(!search.contains("Pottery"));
+
+    let export = run(&["export", "--format", "jsonl", "--tag", "work/project-x/"]);
+    assert_eq!(export.lines().count(), 1, "{}", export);
+
+    let tree = run(&["tags", "list", "--tree"]);
+    assert_eq!(
+        tree,
+        "count=4\n\
+tag=work depth=0 count=1 total=2\n\
+tag=work/project-x depth=1 count=0 total=1\n\
+tag=work/project-x/meetings depth=2 count=1 total=1\n\
+tag=workshop depth=0 count=1 total=1\n"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&run(&["tags", "list", "--json"])).expect("parse json");
+    assert_eq!(json[0]["tag"], "work");
+    assert_eq!(json[0]["total"], 2);
+}
+
+#[test]
+fn test_cli_add_suggest_tags_lists_without_tty() {
+    let ledger_path = temp_ledger_path("ledger_cli_suggest_tags");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_suggest_tags");
+
+    let run = |args: &[&str]| {
+        let output = run_ledger(&ledger_path, (&config_home, &data_home), args);
+        assert!(
+            output.status.success(),
+            "{:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output
+    };
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    run(&[
+        "add",
+        "journal",
+        "--body",
+        "Sourdough loaf came out dense",
+        "--tag",
+        "baking",
+        "--no-input",
+    ]);
+    run(&[
+        "add",
+        "journal",
+        "--body",
+        "Standup ran long again",
+        "--tag",
+        "work",
+        "--no-input",
+    ]);
+
+    let output = run(&[
+        "add",
+        "journal",
+        "--body",
+        "Second sourdough loaf, better crumb",
+        "--suggest-tags",
+        "--no-input",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("suggested_tags=baking\n"), "{}", stderr);
+
+    // Listed only: the entry is saved without them
+    let tags = run(&["tags", "list"]);
+    let tags = String::from_utf8_lossy(&tags.stdout);
+    assert!(tags.contains("tag=baking count=1 total=1"), "{}", tags);
+
+    // Already chosen tags aren't suggested again
+    let output = run(&[
+        "add",
+        "journal",
+        "--body",
+        "More sourdough",
+        "--tag",
+        "baking",
+        "--suggest-tags",
+        "--no-input",
+    ]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("suggested_tags"));
+}
+
+#[test]
+fn test_cli_show_lists_related_entries() {
+    let ledger_path = temp_ledger_path("ledger_cli_related");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_related");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let add = |body: &str, tags: &[&str]| {
+        let mut args = vec!["add", "journal", "--body", body, "--json", "--no-input"];
+        for tag in tags {
+            args.extend(["--tag", tag]);
+        }
+        let receipt: serde_json::Value = serde_json::from_str(&run(&args)).expect("receipt");
+        receipt["id"].as_str().expect("id").to_string()
+    };
+    let subject = add("Repotted the fiddle leaf fig", &["plants"]);
+    let sibling = add("Watered the ferns", &["plants"]);
+    add("Paid the electricity bill", &[]);
+    add("Paid the water bill", &[]);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&run(&["show", &subject, "--json"])).expect("parse show");
+    let related = json["related"].as_array().expect("related array");
+    assert_eq!(related.len(), 1, "{}", json);
+    assert_eq!(related[0]["id"], sibling.as_str());
+    assert_eq!(related[0]["summary"], "Watered the ferns");
+    assert_eq!(
+        related[0]["reasons"][0],
+        serde_json::json!({"kind": "tag", "value": "plants"})
+    );
+
+    let plain = run(&["show", &subject]);
+    assert!(
+        plain.contains(&format!("related={} reasons=tag:plants\n", sibling)),
+        "{}",
+        plain
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&run(&["show", &subject, "--json", "--related", "0"]))
+            .expect("parse show");
+    assert_eq!(json["related"], serde_json::json!([]));
+}
+
+#[test]
+fn test_cli_list_compositions_and_templates_with_counts() {
+    let ledger_path = temp_ledger_path("ledger_cli_list_counts");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_list_counts");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    // Before any template exists, so it counts towards none
+    run(&["add", "journal", "--body", "Planning", "--no-input"]);
+    run(&[
+        "templates",
+        "create",
+        "daily",
+        "--entry-type",
+        "journal",
+        "--set-default",
+    ]);
+    run(&["templates", "create", "quick", "--entry-type", "journal"]);
+    run(&["compositions", "create", "trip"]);
+    run(&["compositions", "create", "someday"]);
+    run(&[
+        "add",
+        "journal",
+        "--body",
+        "Packed",
+        "--compose",
+        "trip",
+        "--no-input",
+    ]);
+    run(&[
+        "add",
+        "journal",
+        "--body",
+        "Landed",
+        "--compose",
+        "trip",
+        "--template",
+        "quick",
+        "--no-input",
+    ]);
+
+    let compositions: serde_json::Value =
+        serde_json::from_str(&run(&["compositions", "list", "--json"])).expect("parse json");
+    let composition = |name: &str| {
+        compositions
+            .as_array()
+            .expect("array")
+            .iter()
+            .find(|c| c["name"] == name)
+            .cloned()
+            .expect("composition listed")
+    };
+    assert_eq!(composition("trip")["entry_count"], 2);
+    assert!(composition("trip")["last_activity"].is_string());
+    assert_eq!(composition("someday")["entry_count"], 0);
+    assert!(composition("someday")["last_activity"].is_null());
+
+    let plain = run(&["compositions", "list"]);
+    assert!(plain.lines().any(|line| line.contains(" someday 0 - ")));
+
+    let templates: serde_json::Value =
+        serde_json::from_str(&run(&["templates", "list", "--json"])).expect("parse json");
+    let template = |name: &str| {
+        templates
+            .as_array()
+            .expect("array")
+            .iter()
+            .find(|t| t["name"] == name)
+            .cloned()
+            .expect("template listed")
+    };
+    // The first entry used the default template, the second picked one
+    assert_eq!(template("daily")["usage_count"], 1);
+    assert_eq!(template("daily")["is_default"], true);
+    assert_eq!(template("quick")["usage_count"], 1);
+    assert_eq!(template("quick")["is_default"], false);
+}
+
+#[test]
+fn test_cli_template_usage_in_show_and_stats() {
+    let ledger_path = temp_ledger_path("ledger_cli_template_stats");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_template_stats");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let add = |body: &str| {
+        let receipt: serde_json::Value = serde_json::from_str(&run(&[
+            "add",
+            "journal",
+            "--body",
+            body,
+            "--json",
+            "--no-input",
+        ]))
+        .expect("receipt");
+        receipt["id"].as_str().expect("id").to_string()
+    };
+    let untemplated = add("Planning");
+    run(&[
+        "templates",
+        "create",
+        "daily",
+        "--entry-type",
+        "journal",
+        "--set-default",
+    ]);
+    run(&["templates", "create", "unused", "--entry-type", "journal"]);
+    let first = add("Morning pages");
+    run(&[
+        "templates",
+        "update",
+        "daily",
+        "--defaults",
+        r#"{"body": "Gratitude"}"#,
+    ]);
+    let second = add("Evening pages");
+
+    let shown: serde_json::Value =
+        serde_json::from_str(&run(&["show", &first, "--json"])).expect("parse show");
+    assert_eq!(shown["template"]["name"], "daily");
+    assert_eq!(shown["template"]["version"], 1);
+    let shown: serde_json::Value =
+        serde_json::from_str(&run(&["show", &untemplated, "--json"])).expect("parse show");
+    assert!(shown["template"].is_null());
+    let plain = run(&["show", &second]);
+    assert!(
+        plain.contains("template=daily\ntemplate_version=2\n"),
+        "{}",
+        plain
+    );
+
+    let stats: serde_json::Value =
+        serde_json::from_str(&run(&["templates", "stats", "--json"])).expect("parse stats");
+    assert_eq!(stats[0]["name"], "daily");
+    assert_eq!(stats[0]["usage_count"], 2);
+    assert_eq!(stats[0]["by_version"], serde_json::json!({"1": 1, "2": 1}));
+    assert_eq!(stats[0]["is_default"], true);
+    assert_eq!(stats[1]["name"], "unused");
+    assert_eq!(stats[1]["usage_count"], 0);
+    assert!(stats[1]["last_used"].is_null());
+
+    let plain = run(&["templates", "stats"]);
+    assert!(plain.starts_with("count=2\n"), "{}", plain);
+    assert!(plain.contains("template=daily type=journal default=true uses=2 by_version=1:1,2:1"));
+    assert!(plain
+        .contains("template=unused type=journal default=false uses=0 by_version=- last_used=-"));
+}
+
+#[test]
+fn test_cli_retire_type_and_refuse_used_template_delete() {
+    let ledger_path = temp_ledger_path("ledger_cli_retire_type");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_retire_type");
+
+    let command = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    run(&["add", "journal", "--body", "Setup", "--no-input"]);
+    run(&["templates", "create", "daily", "--entry-type", "journal"]);
+    let receipt: serde_json::Value = serde_json::from_str(&run(&[
+        "add",
+        "journal",
+        "--body",
+        "From template",
+        "--template",
+        "daily",
+        "--json",
+        "--no-input",
+    ]))
+    .expect("receipt");
+    let entry_id = receipt["id"].as_str().expect("id").to_string();
+
+    let refused = command(&["templates", "delete", "daily", "--force"]);
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(stderr.contains("used to create 1 entries"), "{}", stderr);
+    assert!(stderr.contains(&entry_id[..8]), "{}", stderr);
+    assert!(run(&["templates", "list"]).contains(" daily "));
+
+    run(&["types", "retire", "journal"]);
+    assert!(run(&["types", "list"]).contains("type=journal version=1 retired=true"));
+    let refused = command(&["add", "journal", "--body", "Too late", "--no-input"]);
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(stderr.contains("is retired"), "{}", stderr);
+    assert!(
+        stderr.contains("ledger types retire <type> --undo"),
+        "{}",
+        stderr
+    );
+    // Existing entries stay editable
+    run(&["edit", &entry_id, "--body", "Revised", "--no-input"]);
+
+    let undo = run(&["types", "retire", "journal", "--undo"]);
+    assert!(undo.contains("changed=true"), "{}", undo);
+    run(&["add", "journal", "--body", "Back again", "--no-input"]);
+}
+
+#[test]
+fn test_cli_types_show_lists_fields() {
+    let ledger_path = temp_ledger_path("ledger_cli_types_show");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_types_show");
+
+    let command = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let output = command(&[
+        "add",
+        "expense",
+        "--field",
+        "amount=4.50",
+        "--field",
+        "currency=USD",
+        "--field",
+        "category=coffee",
+        "--no-input",
+    ]);
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = command(&["types", "show", "expense"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("name=expense"), "{}", stdout);
+    assert!(
+        stdout.contains("field=amount type=number required=true"),
+        "{}",
+        stdout
+    );
+
+    let output = command(&["types", "show", "expense", "--json"]);
+    let shown: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(shown["name"], "expense");
+    assert_eq!(shown["version"], 1);
+    assert!(shown["retired_at"].is_null());
+    assert_eq!(shown["schema"]["fields"][0]["name"], "amount");
+
+    let missing = command(&["types", "show", "nope"]);
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("not found"));
+}
+
+#[test]
+fn test_cli_export_diff_emits_only_new_entries() {
+    let ledger_path = temp_ledger_path("ledger_cli_export_diff");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_export_diff");
+
+    let run = |args: &[&str]| {
+        let output = run_ledger(&ledger_path, (&config_home, &data_home), args);
+        assert!(
+            output.status.success(),
+            "{:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output
+    };
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let receipt: serde_json::Value = serde_json::from_slice(
+        &run(&[
+            "add",
+            "journal",
+            "--body",
+            "Archived",
+            "--json",
+            "--no-input",
+        ])
+        .stdout,
+    )
+    .expect("receipt");
+    let archived_id = receipt["id"].as_str().expect("id").to_string();
+    let old_export = ledger_path.with_extension("old.jsonl");
+    let old_export = old_export.to_str().expect("path");
+    run(&["export", "--format", "jsonl", "--out", old_export]);
+
+    run(&["add", "journal", "--body", "Fresh", "--no-input"]);
+    run(&[
+        "edit",
+        &archived_id,
+        "--body",
+        "Archived, revised",
+        "--no-input",
+    ]);
+
+    let output = run(&["export-diff", old_export]);
+    let lines: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("jsonl"))
+        .collect();
+    assert_eq!(lines.len(), 2, "{:?}", lines);
+    assert!(lines
+        .iter()
+        .all(|entry| entry["id"] != archived_id.as_str()));
+    assert!(lines.iter().any(|entry| entry["data"]["body"] == "Fresh"));
+    assert!(lines
+        .iter()
+        .any(|entry| entry["supersedes"] == archived_id.as_str()));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("export_count=2"), "{}", stderr);
+    assert!(stderr.contains("already_exported=1"), "{}", stderr);
+
+    let output = run(&["export-diff", old_export, "--format", "json"]);
+    let array: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(array.as_array().map(Vec::len), Some(2));
+}
+
+#[test]
+fn test_cli_show_and_edit_accept_day_numbers() {
+    let ledger_path = temp_ledger_path("ledger_cli_day_numbers");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_day_numbers");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let mut ids = Vec::new();
+    for body in ["First entry", "Second entry"] {
+        let mut add = Command::new(bin());
+        add.arg("add")
+            .arg("journal")
+            .arg("--body")
+            .arg(body)
+            .arg("--json")
+            .arg("--ledger")
+            .arg(&ledger_path)
+            .env("LEDGER_PASSPHRASE", passphrase);
+        apply_xdg_env(&mut add, &config_home, &data_home);
+        let add = add.output().expect("run add");
+        assert!(add.status.success());
+        let value: serde_json::Value = serde_json::from_slice(&add.stdout).expect("parse add json");
+        ids.push(value["id"].as_str().expect("entry id").to_string());
+    }
+
+    let show = |selector: &str| {
+        run_ledger(
+            &ledger_path,
+            (&config_home, &data_home),
+            &["show", selector, "--json"],
+        )
+    };
+
+    let second = show(&ids[1]);
+    assert!(second.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&second.stdout).expect("parse show");
+    let log_id = value["log_id"].as_str().expect("log id").to_string();
+    assert!(log_id.ends_with("#2"), "log id: {}", log_id);
+
+    let by_number = show(&log_id);
+    assert!(by_number.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&by_number.stdout).expect("parse show");
+    assert_eq!(value["id"].as_str(), Some(ids[1].as_str()));
+
+    let mut edit = Command::new(bin());
+    edit.arg("edit")
+        .arg(&log_id)
+        .arg("--body")
+        .arg("Second entry, revised")
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut edit, &config_home, &data_home);
+    assert!(edit.output().expect("run edit").status.success());
+
+    // The revision keeps its entry's number
+    let revised = show(&log_id);
+    assert!(revised.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&revised.stdout).expect("parse show");
+    assert_eq!(value["data"]["body"], "Second entry, revised");
+    assert_eq!(value["log_id"].as_str(), Some(log_id.as_str()));
+
+    let missing = show(&log_id.replace("#2", "#9"));
+    assert_eq!(missing.status.code(), Some(3));
+}
+
+#[test]
+fn test_cli_list_log_ids_select_entries() {
+    let ledger_path = temp_ledger_path("ledger_cli_list_log_ids");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_list_log_ids");
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    for body in ["First entry", "Second entry"] {
+        assert!(run(&["add", "journal", "--body", body]).status.success());
+    }
+
+    let output = run(&["list", "--json"]);
+    assert!(output.status.success());
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("list json");
+    let listed = listed.as_array().expect("entries");
+    assert_eq!(listed.len(), 2);
+    for entry in listed {
+        let log_id = entry["log_id"].as_str().expect("log id");
+        let output = run(&["show", log_id, "--json"]);
+        assert!(output.status.success(), "show {}", log_id);
+        let shown: serde_json::Value = serde_json::from_slice(&output.stdout).expect("show json");
+        assert_eq!(shown["id"], entry["id"]);
+        assert_eq!(shown["log_id"], entry["log_id"]);
+    }
+
+    // Plain rows carry the log id right after the entry id
+    let output = run(&["list", "--format", "plain"]);
+    let plain = String::from_utf8_lossy(&output.stdout);
+    for (line, entry) in plain.lines().zip(listed) {
+        let mut fields = line.split_whitespace();
+        assert_eq!(fields.next(), entry["id"].as_str());
+        assert_eq!(fields.next(), entry["log_id"].as_str());
+    }
+
+    let _ = std::fs::remove_file(&ledger_path);
+}
+
+#[test]
+fn test_cli_packs_install_is_idempotent_and_upgrades() {
+    let ledger_path = temp_ledger_path("ledger_cli_packs");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_packs");
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let install = run(&["packs", "install", "fitness"]);
+    assert!(
+        install.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&install.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&install.stdout);
+    assert!(
+        stdout.contains("entry_type=workout action=created"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains("template=run action=created"));
+
+    let again = run(&["packs", "install", "fitness", "--json"]);
+    assert!(again.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&again.stdout).expect("parse json");
+    assert!(value["entry_types"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|item| item["action"] == "unchanged"));
+
+    // The default template brings the pack's tags
+    let add = run(&["add", "workout", "--field", "duration=30", "--no-input"]);
+    assert!(
+        add.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&add.stderr)
+    );
+    let list = run(&["list", "workout", "--json"]);
+    let value: serde_json::Value = serde_json::from_slice(&list.stdout).expect("parse list");
+    let entry = &value.as_array().expect("array")[0];
+    assert_eq!(entry["data"]["activity"], "run");
+    assert_eq!(entry["tags"], serde_json::json!(["fitness", "run"]));
+
+    // A newer manifest adds a schema version to the type it owns
+    let manifest_path = ledger_path.with_extension("pack.json");
+    let manifest = |version: &str, note_field: &str| {
+        serde_json::json!({
+            "name": "reading",
+            "version": version,
+            "entry_types": [{"name": "book", "schema": {"fields": [
+                {"name": "title", "type": "string", "required": true},
+                {"name": note_field, "type": "text", "required": false}
+            ]}}]
+        })
+        .to_string()
+    };
+    std::fs::write(&manifest_path, manifest("1", "notes")).expect("write manifest");
+    assert!(run(&["packs", "install", manifest_path.to_str().unwrap()])
+        .status
+        .success());
+    std::fs::write(&manifest_path, manifest("2", "review")).expect("write manifest");
+    let upgrade = run(&["packs", "install", manifest_path.to_str().unwrap()]);
+    let stdout = String::from_utf8_lossy(&upgrade.stdout);
+    assert!(stdout.contains("previous_version=1"), "stdout: {}", stdout);
+    assert!(stdout.contains("entry_type=book action=updated"));
+
+    let list = run(&["packs", "list", "--json"]);
+    let value: serde_json::Value = serde_json::from_slice(&list.stdout).expect("parse packs");
+    let packs: Vec<(&str, &str)> = value
+        .as_array()
+        .expect("array")
+        .iter()
+        .map(|pack| {
+            (
+                pack["name"].as_str().unwrap(),
+                pack["version"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(packs, vec![("fitness", "1.0"), ("reading", "2")]);
+
+    // Types the pack does not own are never changed
+    std::fs::write(
+        &manifest_path,
+        serde_json::json!({
+            "name": "clash",
+            "version": "1",
+            "entry_types": [{"name": "book", "schema": {"fields": [
+                {"name": "isbn", "type": "string", "required": true}
+            ]}}]
+        })
+        .to_string(),
+    )
+    .expect("write manifest");
+    let clash = run(&["packs", "install", manifest_path.to_str().unwrap()]);
+    assert!(!clash.status.success());
+    assert!(String::from_utf8_lossy(&clash.stderr).contains("does not belong to pack 'clash'"));
+    assert!(!run(&["packs", "install", "cooking"]).status.success());
+}
+
+#[test]
+fn test_cli_attachments_are_deduplicated_and_reported() {
+    let ledger_path = temp_ledger_path("ledger_cli_attachment_dedup");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_attachment_dedup");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    let photo_path = data_home.join("sunset.jpg");
+    std::fs::write(&photo_path, jpeg_with_exif()).expect("write photo");
+    let photo_size = std::fs::metadata(&photo_path)
+        .expect("photo metadata")
+        .len();
+    for caption in ["Sunset", "Same sunset, again"] {
+        run(&[
+            "add",
+            "photo",
+            "--file",
+            photo_path.to_str().unwrap(),
+            "--body",
+            caption,
+            "--no-input",
+        ]);
+    }
+
+    let status: serde_json::Value =
+        serde_json::from_str(&run(&["status", "--json"])).expect("parse status");
+    assert_eq!(status["attachments"]["attachments"], 2);
+    assert_eq!(status["attachments"]["blobs"], 1);
+    assert_eq!(status["attachments"]["logical_bytes"], photo_size * 2);
+    assert_eq!(status["attachments"]["stored_bytes"], photo_size);
+
+    let gc: serde_json::Value =
+        serde_json::from_str(&run(&["attachments", "gc", "--json", "--no-input"]))
+            .expect("parse gc");
+    assert_eq!(gc["blobs_removed"], 0);
+    assert_eq!(gc["usage"]["stored_bytes"], photo_size);
+    let plain = run(&["attachments", "gc", "--dry-run", "--no-input"]);
+    assert!(plain.contains("dry_run=true"), "{}", plain);
+    assert!(plain.contains("blobs_removed=0"), "{}", plain);
+}
+
+#[test]
+fn test_cli_sync_export_applies_peer_rules() {
+    let home_path = temp_ledger_path("ledger_cli_sync_home");
+    let work_path = temp_ledger_path("ledger_cli_sync_work");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_sync");
+    let bundle_path = data_home.join("work.bundle.json");
+
+    let run =
+        |args: &[&str], ledger: &PathBuf| run_ledger_ok(ledger, (&config_home, &data_home), args);
+
+    for path in [&work_path, &home_path] {
+        let mut init = Command::new(bin());
+        init.arg("init")
+            .arg(path)
+            .arg("--no-input")
+            .env("LEDGER_PASSPHRASE", passphrase);
+        apply_xdg_env(&mut init, &config_home, &data_home);
+        assert!(init.output().expect("run init").status.success());
+    }
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str(
+        "\n[sync.peers.work]\ninclude_tags = [\"work\"]\nexclude_compositions = [\"reviews\"]\n",
+    );
+    std::fs::write(&config_path, config).expect("write config");
+    let peers: serde_json::Value =
+        serde_json::from_str(&run(&["sync", "peers", "list", "--json"], &work_path))
+            .expect("parse peers");
+    let work_fingerprint = peers["fingerprint"].as_str().expect("fingerprint");
+    run(
+        &[
+            "sync",
+            "peers",
+            "add",
+            "work",
+            "--fingerprint",
+            work_fingerprint,
+        ],
+        &home_path,
+    );
+
+    for (body, tag) in [
+        ("Standup notes", "work/meetings"),
+        ("Dinner plans", "home"),
+        ("Salary review", "work"),
+    ] {
+        run(
+            &["add", "journal", "--body", body, "--tag", tag, "--no-input"],
+            &home_path,
+        );
+    }
+    run(&["compositions", "create", "reviews"], &home_path);
+    let list: serde_json::Value =
+        serde_json::from_str(&run(&["list", "--json"], &home_path)).expect("parse list");
+    let review_id = list
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["data"]["body"] == "Salary review")
+        .and_then(|entry| entry["id"].as_str())
+        .expect("review entry")
+        .to_string();
+    run(&["attach", &review_id, "reviews"], &home_path);
+
+    run(
+        &[
+            "sync",
+            "export",
+            "--peer",
+            "work",
+            "--out",
+            bundle_path.to_str().unwrap(),
+        ],
+        &home_path,
+    );
+    let bundle: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&bundle_path).expect("read bundle"))
+            .expect("parse bundle");
+    assert_eq!(bundle["entries"].as_array().unwrap().len(), 1);
+    assert!(bundle.get("compositions").is_none(), "{}", bundle);
+
+    let imported = run(&["import", bundle_path.to_str().unwrap()], &work_path);
+    assert!(imported.contains("entries=1"), "stdout: {}", imported);
+    let list: serde_json::Value =
+        serde_json::from_str(&run(&["list", "--json"], &work_path)).expect("parse list");
+    let bodies: Vec<&str> = list
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|entry| entry["data"]["body"].as_str())
+        .collect();
+    assert_eq!(bodies, vec!["Standup notes"]);
+
+    // Without a peer everything is sent; an unknown peer is an error
+    let everything: serde_json::Value =
+        serde_json::from_str(&run(&["sync", "export"], &home_path)).expect("parse bundle");
+    assert_eq!(everything["entries"].as_array().unwrap().len(), 3);
+    let mut unknown = Command::new(bin());
+    unknown
+        .args(["sync", "export", "--peer", "laptop", "--ledger"])
+        .arg(&home_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut unknown, &config_home, &data_home);
+    let unknown = unknown.output().expect("run sync export");
+    assert!(!unknown.status.success());
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("ledger sync peers add laptop"));
+}
+
+#[test]
+fn test_cli_sync_export_leaves_out_other_compositions() {
+    let ledger_path = temp_ledger_path("ledger_cli_sync_memberships");
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_sync_memberships");
+    create_ledger_with_passphrase(&ledger_path, TEST_PASSPHRASE);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str("\n[sync.peers.work]\ninclude_compositions = [\"standups\"]\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+    run(&[
+        "sync",
+        "peers",
+        "add",
+        "work",
+        "--fingerprint",
+        "1a2b-3c4d-5e6f-7a8b",
+    ]);
+    for name in ["standups", "private", "archive"] {
+        run(&["compositions", "create", name]);
+    }
+    run(&["compositions", "edit", "standups", "--body", "Daily notes"]);
+    run(&["compositions", "edit", "private", "--body", "Not for work"]);
+    let entry_id = run(&["add", "journal", "--body", "Standup", "--no-input"])
+        .lines()
+        .find_map(|line| line.strip_prefix("entry_id="))
+        .expect("entry id")
+        .to_string();
+    run(&["attach", &entry_id, "standups"]);
+    run(&["attach", &entry_id, "private"]);
+    run(&["attach", &entry_id, "archive"]);
+    run(&["detach", &entry_id, "archive"]);
+
+    // The entry goes, with its one membership the rules admit
+    let bundle: serde_json::Value =
+        serde_json::from_str(&run(&["sync", "export", "--peer", "work"])).expect("parse bundle");
+    assert_eq!(bundle["entries"].as_array().unwrap().len(), 1);
+    let names: Vec<&str> = bundle["compositions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|composition| composition["name"].as_str())
+        .collect();
+    assert_eq!(names, vec!["standups"]);
+    let memberships = bundle["entry_compositions"].as_array().unwrap();
+    assert_eq!(memberships.len(), 1, "{}", bundle);
+    assert!(memberships[0]["removed_at"].is_null());
+    let overviews: Vec<&str> = bundle["composition_overviews"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|overview| overview["body"].as_str())
+        .collect();
+    assert_eq!(overviews, vec!["Daily notes"]);
+}
+
+#[test]
+fn test_cli_sync_peers_track_since_and_check_sender() {
+    let home_path = temp_ledger_path("ledger_cli_sync_peers_home");
+    let laptop_path = temp_ledger_path("ledger_cli_sync_peers_laptop");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_sync_peers");
+    let bundle_path = data_home.join("laptop.bundle.json");
+    let bundle = bundle_path.to_str().unwrap();
+
+    let command =
+        |args: &[&str], ledger: &PathBuf| run_ledger(ledger, (&config_home, &data_home), args);
+    let run =
+        |args: &[&str], ledger: &PathBuf| run_ledger_ok(ledger, (&config_home, &data_home), args);
+
+    for path in [&home_path, &laptop_path] {
+        let mut init = Command::new(bin());
+        init.arg("init")
+            .arg(path)
+            .arg("--no-input")
+            .env("LEDGER_PASSPHRASE", passphrase);
+        apply_xdg_env(&mut init, &config_home, &data_home);
+        assert!(init.output().expect("run init").status.success());
+    }
+    let fingerprint = |ledger: &PathBuf| {
+        let peers: serde_json::Value =
+            serde_json::from_str(&run(&["sync", "peers", "list", "--json"], ledger))
+                .expect("parse peers");
+        peers["fingerprint"]
+            .as_str()
+            .expect("fingerprint")
+            .to_string()
+    };
+    let home_fingerprint = fingerprint(&home_path);
+    let laptop_fingerprint = fingerprint(&laptop_path);
+    assert_ne!(home_fingerprint, laptop_fingerprint);
+    run(
+        &[
+            "sync",
+            "peers",
+            "add",
+            "laptop",
+            "--fingerprint",
+            &laptop_fingerprint,
+        ],
+        &home_path,
+    );
+    run(
+        &[
+            "sync",
+            "peers",
+            "add",
+            "home",
+            "--fingerprint",
+            &home_fingerprint,
+        ],
+        &laptop_path,
+    );
+    let own = command(
+        &[
+            "sync",
+            "peers",
+            "add",
+            "me",
+            "--fingerprint",
+            &home_fingerprint,
+        ],
+        &home_path,
+    );
+    assert!(!own.status.success());
+
+    let synced = |ledger: &PathBuf| {
+        let output = command(
+            &["sync", "export", "--peer", "laptop", "--out", bundle],
+            ledger,
+        );
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let bundle: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&bundle_path).expect("read bundle"))
+                .expect("parse bundle");
+        (bundle, stderr)
+    };
+
+    // The first bundle holds everything; the next only what came after it
+    run(
+        &["add", "journal", "--body", "First", "--no-input"],
+        &home_path,
+    );
+    let (first, stderr) = synced(&home_path);
+    assert_eq!(first["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(first["sync"]["from"], home_fingerprint.as_str());
+    assert!(!stderr.contains("since="), "{}", stderr);
+    let imported = run(&["sync", "import", bundle, "--peer", "home"], &laptop_path);
+    assert!(imported.contains("entries=1"), "{}", imported);
+
+    run(
+        &["add", "journal", "--body", "Second", "--no-input"],
+        &home_path,
+    );
+    let (second, stderr) = synced(&home_path);
+    let bodies: Vec<&str> = second["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|entry| entry["data"]["body"].as_str())
+        .collect();
+    assert_eq!(bodies, vec!["Second"]);
+    assert!(stderr.contains("after_seq="), "{}", stderr);
+    run(&["sync", "import", bundle, "--peer", "home"], &laptop_path);
+
+    // An entry dated before the last export was still added after it
+    run(
+        &[
+            "add",
+            "journal",
+            "--body",
+            "Backdated",
+            "--date",
+            "2020-01-01T09:00:00Z",
+            "--no-input",
+        ],
+        &home_path,
+    );
+    let (third, _) = synced(&home_path);
+    let bodies: Vec<&str> = third["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|entry| entry["data"]["body"].as_str())
+        .collect();
+    assert_eq!(bodies, vec!["Backdated"]);
+    run(&["sync", "import", bundle, "--peer", "home"], &laptop_path);
+
+    let peers = run(&["sync", "peers", "list"], &laptop_path);
+    assert!(
+        peers.contains(&format!("fingerprint={}", laptop_fingerprint)),
+        "{}",
+        peers
+    );
+    assert!(peers.contains("peer=home"), "{}", peers);
+    assert!(!peers.contains("last_received=never"), "{}", peers);
+    let list: serde_json::Value =
+        serde_json::from_str(&run(&["list", "--json"], &laptop_path)).expect("parse list");
+    assert_eq!(list.as_array().unwrap().len(), 3);
+
+    // A bundle is only accepted from the peer whose fingerprint it carries
+    run(
+        &[
+            "sync",
+            "peers",
+            "add",
+            "phone",
+            "--fingerprint",
+            "0000-0000-0000-0001",
+        ],
+        &laptop_path,
+    );
+    let wrong = command(&["sync", "import", bundle, "--peer", "phone"], &laptop_path);
+    assert!(!wrong.status.success());
+    assert!(String::from_utf8_lossy(&wrong.stderr).contains("not peer 'phone'"));
+
+    run(&["sync", "peers", "remove", "phone"], &laptop_path);
+    let removed = command(&["sync", "peers", "remove", "phone"], &laptop_path);
+    assert!(!removed.status.success());
+}
+
+#[test]
+fn test_cli_digest_renders_markdown_and_html() {
+    let ledger_path = temp_ledger_path("ledger_cli_digest");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_digest");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    run(&[
+        "add",
+        "journal",
+        "--body",
+        "Shipped <search> & filters",
+        "--tag",
+        "work",
+        "--no-input",
+    ]);
+    run(&["add", "journal", "--body", "Went for a run", "--no-input"]);
+
+    let markdown = run(&["digest", "--tag", "work"]);
+    assert!(markdown.starts_with("# Weekly digest"), "{}", markdown);
+    assert!(markdown.contains("- 2 entries\n- journal: 2"));
+    assert!(markdown.contains("## #work\n\n"));
+    assert!(markdown.contains("Shipped <search> & filters"));
+    assert!(!markdown.contains("Went for a run"));
+
+    let out = ledger_path.with_extension("html");
+    run(&[
+        "digest",
+        "--period",
+        "month",
+        "--format",
+        "html",
+        "--out",
+        out.to_str().unwrap(),
+    ]);
+    let html = std::fs::read_to_string(&out).expect("read digest");
+    assert!(html.contains("<h1>Monthly digest</h1>"));
+    assert!(html.contains("<h2>Recent entries</h2>"));
+    assert!(html.contains("Shipped &lt;search&gt; &amp; filters"));
+    assert!(html.contains("Went for a run"));
+    let _ = std::fs::remove_file(&out);
+}
+
+#[test]
+fn test_cli_secrets_resolve_config_references() {
+    let ledger_path = temp_ledger_path("ledger_cli_secrets");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_secrets");
+
+    let command = |args: &[&str]| ledger_command(&ledger_path, (&config_home, &data_home), args);
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    // Value piped on stdin
+    let mut set = command(&["secret", "set", "transcriber", "--no-input"]);
+    set.stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped());
+    let mut child = set.spawn().expect("spawn secret set");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all(b"echo transcript of\n")
+        .expect("write stdin");
+    let output = child.wait_with_output().expect("wait secret set");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("updated=false"));
+
+    run(&["secret", "set", "unused", "--value", "x", "--no-input"]);
+    assert_eq!(
+        run(&["secret", "get", "transcriber"]),
+        "echo transcript of\n"
+    );
+    assert_eq!(run(&["secret", "list"]), "transcriber\nunused\n");
+
+    // The secret value never appears in the ledger file
+    let on_disk = std::fs::read(&ledger_path).expect("read ledger");
+    assert!(!on_disk
+        .windows(b"echo transcript of".len())
+        .any(|window| window == b"echo transcript of"));
+
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str("\n[capture]\ntranscriber = \"secret://transcriber\"\n");
+    std::fs::write(&config_path, &config).expect("write config");
+
+    let audio_path = data_home.join("memo.m4a");
+    std::fs::write(&audio_path, b"fake audio").expect("write audio");
+    let audio_arg = audio_path.to_string_lossy().to_string();
+    let stdout = run(&["add", "journal", "--from-audio", &audio_arg, "--no-input"]);
+    assert!(stdout.contains("transcribed=true"), "stdout: {}", stdout);
+
+    run(&["secret", "delete", "transcriber", "--force"]);
+    let output = command(&["add", "journal", "--from-audio", &audio_arg, "--no-input"])
+        .output()
+        .expect("run add");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Secret 'transcriber'"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_audit_security_reports_findings() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ledger_path = temp_ledger_path("ledger_cli_audit");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_audit");
+    let history = data_home.join("history");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let audit = |with_env: bool| {
+        let mut cmd = Command::new(bin());
+        cmd.arg("audit-security")
+            .arg("--json")
+            .arg("--ledger")
+            .arg(&ledger_path)
+            .env("HOME", &data_home)
+            .env("HISTFILE", &history)
+            .env_remove("LEDGER_PASSPHRASE");
+        if with_env {
+            cmd.env("LEDGER_PASSPHRASE", passphrase);
+        }
+        apply_xdg_env(&mut cmd, &config_home, &data_home);
+        let output = cmd.output().expect("run audit");
+        let value: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("parse audit json");
+        (output.status.success(), value)
+    };
+
+    std::fs::set_permissions(&ledger_path, std::fs::Permissions::from_mode(0o600))
+        .expect("chmod ledger");
+    std::fs::write(&history, "ledger list\n").expect("write history");
+    let checks_of = |value: &serde_json::Value| -> Vec<String> {
+        value["findings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|f| f["check"].as_str().map(String::from))
+            .collect()
+    };
+    let (ok, value) = audit(false);
+    assert!(ok, "audit: {}", value);
+    let checks = checks_of(&value);
+    assert!(
+        !checks.iter().any(|c| c == "ledger_permissions"),
+        "checks: {:?}",
+        checks
+    );
+    assert!(
+        !checks.iter().any(|c| c == "passphrase_env"),
+        "checks: {:?}",
+        checks
+    );
+
+    std::fs::set_permissions(&ledger_path, std::fs::Permissions::from_mode(0o644))
+        .expect("chmod ledger");
+    let (ok, value) = audit(true);
+    assert!(ok, "medium findings should not fail: {}", value);
+    let checks = checks_of(&value);
+    assert!(
+        checks.iter().any(|c| c == "ledger_permissions"),
+        "checks: {:?}",
+        checks
+    );
+    assert!(
+        checks.iter().any(|c| c == "passphrase_env"),
+        "checks: {:?}",
+        checks
+    );
+
+    std::fs::write(&history, "LEDGER_PASSPHRASE=hunter2 ledger list\n").expect("write history");
+    let (ok, value) = audit(false);
+    assert!(!ok, "high findings should fail");
+    assert_eq!(value["findings"][0]["severity"], "high");
+    assert_eq!(value["findings"][0]["check"], "shell_history");
+}
+
+#[test]
+fn test_cli_lockout_after_failed_unlocks() {
+    let ledger_path = temp_ledger_path("ledger_cli_lockout");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_lockout");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let config_path = config_home.join("ledger").join("config.toml");
+    let config = std::fs::read_to_string(&config_path).expect("read config");
+    let config = config.replace("[security]\n", "[security]\nlockout = true\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    let run = |args: &[&str], pass: &str| {
+        ledger_command(&ledger_path, (&config_home, &data_home), args)
+            .env("LEDGER_PASSPHRASE", pass)
+            .output()
+            .expect("run ledger")
+    };
+
+    for _ in 0..3 {
+        let output = run(&["list"], "wrong-passphrase-123456");
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Incorrect passphrase"));
+    }
+
+    // Even the right passphrase waits out the backoff
+    let output = run(&["list"], passphrase);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Too many failed unlock attempts (3)"),
+        "stderr: {}",
+        stderr
+    );
+
+    let output = run(&["doctor", "--no-input"], passphrase);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("check=lockout locked"));
+
+    // The escape hatch unlocks and resets the counter
+    let output = run(&["--no-lockout", "list"], passphrase);
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let lockout_file = PathBuf::from(format!("{}.lockout", ledger_path.display()));
+    assert!(!lockout_file.exists());
+
+    let output = run(&["doctor", "--no-input"], passphrase);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("lockout=on"), "stdout: {}", stdout);
+    assert!(stdout.contains("failed_attempts=0"), "stdout: {}", stdout);
+}
+
+#[test]
+fn test_cli_export_include_and_import_keep_compositions() {
+    let source_path = temp_ledger_path("ledger_cli_export_include_src");
+    let target_path = temp_ledger_path("ledger_cli_export_include_dst");
+    let export_path = std::env::temp_dir().join(format!(
+        "ledger_cli_export_include_{}.json",
+        std::process::id()
+    ));
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_export_include");
+
+    let run =
+        |args: &[&str], ledger: &PathBuf| run_ledger_ok(ledger, (&config_home, &data_home), args);
+
+    for path in [&source_path, &target_path] {
+        let mut init = Command::new(bin());
+        init.arg("init")
+            .arg(path)
+            .arg("--no-input")
+            .env("LEDGER_PASSPHRASE", passphrase);
+        apply_xdg_env(&mut init, &config_home, &data_home);
+        assert!(init.output().expect("run init").status.success());
+    }
+
+    run(&["compositions", "create", "research"], &source_path);
+    run(
+        &[
+            "compositions",
+            "edit",
+            "research",
+            "--body",
+            "# Research\n\nReading list",
+        ],
+        &source_path,
+    );
+    run(
+        &["add", "journal", "--body", "Portable notes"],
+        &source_path,
+    );
+    let list: serde_json::Value =
+        serde_json::from_str(&run(&["list", "--json"], &source_path)).expect("parse list");
+    let entry_id = list[0]["id"].as_str().unwrap().to_string();
+    run(&["attach", &entry_id, "research"], &source_path);
+
+    let exported = run(
+        &["export", "--include", "compositions,templates,types"],
+        &source_path,
+    );
+    let bundle: serde_json::Value = serde_json::from_str(&exported).expect("parse export");
+    assert_eq!(bundle["compositions"][0]["name"], "research");
+    assert_eq!(
+        bundle["composition_overviews"][0]["body"],
+        "# Research\n\nReading list"
+    );
+    assert_eq!(
+        bundle["entry_compositions"][0]["entry_id"],
+        entry_id.as_str()
+    );
+    assert!(bundle["entry_types"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|t| t["name"] == "journal"));
+    assert_eq!(bundle["entries"][0]["id"], entry_id.as_str());
+    std::fs::write(&export_path, &exported).expect("write export");
+
+    let imported = run(&["import", export_path.to_str().unwrap()], &target_path);
+    assert!(imported.contains("entries=1"), "stdout: {}", imported);
+    assert!(imported.contains("compositions=1"));
+    assert!(imported.contains("memberships=1"));
+    assert!(imported.contains("overviews=1"));
+
+    let show = run(&["compositions", "show", "research"], &target_path);
+    assert!(show.contains("entry_count=1"), "stdout: {}", show);
+    assert!(show.contains("overview_lines=3"), "stdout: {}", show);
+    let show: serde_json::Value = serde_json::from_str(&run(
+        &["compositions", "show", "research", "--json"],
+        &target_path,
+    ))
+    .expect("parse show");
+    assert_eq!(show["overview"]["body"], "# Research\n\nReading list");
+
+    let mut jsonl = Command::new(bin());
+    jsonl
+        .args(["export", "--format", "jsonl", "--include", "compositions"])
+        .arg("--ledger")
+        .arg(&source_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut jsonl, &config_home, &data_home);
+    let jsonl = jsonl.output().expect("run export");
+    assert!(!jsonl.status.success());
+    assert!(String::from_utf8_lossy(&jsonl.stderr).contains("--include requires --format json"));
+
+    let _ = std::fs::remove_file(&export_path);
+}
+
+#[test]
+fn test_cli_yaml_and_toml_documents_round_trip() {
+    let source_path = temp_ledger_path("ledger_cli_yaml_src");
+    let target_path = temp_ledger_path("ledger_cli_yaml_dst");
+    let export_path =
+        std::env::temp_dir().join(format!("ledger_cli_yaml_{}.yaml", std::process::id()));
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_yaml");
+
+    let output =
+        |args: &[&str], ledger: &PathBuf| run_ledger(ledger, (&config_home, &data_home), args);
+    let run =
+        |args: &[&str], ledger: &PathBuf| run_ledger_ok(ledger, (&config_home, &data_home), args);
+
+    for path in [&source_path, &target_path] {
+        let mut init = Command::new(bin());
+        init.arg("init")
+            .arg(path)
+            .arg("--no-input")
+            .env("LEDGER_PASSPHRASE", passphrase);
+        apply_xdg_env(&mut init, &config_home, &data_home);
+        assert!(init.output().expect("run init").status.success());
+    }
+
+    let body = "Walked home\nkey: not yaml\n- nor a list";
+    run(
+        &["add", "journal", "--body", body, "--tag", "walk"],
+        &source_path,
+    );
+    let list: serde_json::Value =
+        serde_json::from_str(&run(&["list", "--json"], &source_path)).expect("parse list");
+    let entry_id = list[0]["id"].as_str().unwrap().to_string();
+
+    let yaml: serde_json::Value =
+        serde_yaml::from_str(&run(&["show", &entry_id, "--format", "yaml"], &source_path))
+            .expect("parse yaml");
+    assert_eq!(yaml["id"], entry_id.as_str());
+    assert_eq!(yaml["data"]["body"], body);
+    assert!(yaml["supersedes"].is_null());
+
+    let toml_doc: toml::Value =
+        toml::from_str(&run(&["show", &entry_id, "--format", "toml"], &source_path))
+            .expect("parse toml");
+    assert_eq!(toml_doc["data"]["body"].as_str(), Some(body));
+    assert!(toml_doc.get("supersedes").is_none());
+
+    let exported: toml::Value =
+        toml::from_str(&run(&["export", "--format", "toml"], &source_path)).expect("parse toml");
+    assert_eq!(
+        exported["entries"][0]["id"].as_str(),
+        Some(entry_id.as_str())
+    );
+
+    let exported = run(
+        &["export", "--format", "yaml", "--include", "types"],
+        &source_path,
+    );
+    std::fs::write(&export_path, &exported).expect("write export");
+    let imported = run(
+        &["import", export_path.to_str().unwrap(), "--from", "yaml"],
+        &target_path,
+    );
+    assert!(imported.contains("entries=1"), "stdout: {}", imported);
+    let copied: serde_json::Value =
+        serde_json::from_str(&run(&["show", &entry_id, "--json"], &target_path))
+            .expect("parse show");
+    assert_eq!(copied["data"]["body"], body);
+    assert_eq!(copied["tags"], serde_json::json!(["walk"]));
+
+    let unsupported = output(&["show", &entry_id, "--format", "xml"], &source_path);
+    assert!(!unsupported.status.success());
+    assert!(String::from_utf8_lossy(&unsupported.stderr).contains("Unsupported show format"));
+
+    let _ = std::fs::remove_file(&export_path);
+}
+
+#[test]
+fn test_cli_vault_sync_round_trips_edits() {
+    let ledger_path = temp_ledger_path("ledger_cli_vault");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_vault");
+    let vault_dir = config_home.join("vault");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+    assert!(
+        run(&["add", "journal", "--body", "Vault draft", "--tag", "work"])
+            .status
+            .success()
+    );
+
+    let vault = vault_dir.to_str().unwrap();
+    let refused = run(&["vault", "sync", vault]);
+    assert!(!refused.status.success());
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("--plaintext"));
+
+    let first = run(&["vault", "sync", vault, "--plaintext"]);
+    assert!(
+        first.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&first.stderr)
+    );
+    assert!(String::from_utf8_lossy(&first.stdout).contains("written=1"));
+    assert!(String::from_utf8_lossy(&first.stderr).contains("plaintext"));
+
+    let journal_dir = vault_dir.join("journal");
+    let month_dir = std::fs::read_dir(&journal_dir)
+        .expect("journal folder")
+        .next()
+        .expect("month folder")
+        .unwrap()
+        .path();
+    let note_path = std::fs::read_dir(&month_dir)
+        .expect("read month folder")
+        .next()
+        .expect("note file")
+        .unwrap()
+        .path();
+    let note = std::fs::read_to_string(&note_path).expect("read note");
+    assert!(note.starts_with("---\nid: "), "note: {}", note);
+    assert!(note.contains("type: journal\n"));
+    assert!(note.contains("tags: [work]\n"));
+    assert!(note.ends_with("---\nVault draft\n"));
+
+    let edited = note
+        .replace("Vault draft", "Vault final")
+        .replace("tags: [work]", "tags: [work, done]");
+    std::fs::write(&note_path, edited).expect("edit note");
+
+    let second = run(&["vault", "sync", vault, "--plaintext"]);
+    assert!(second.status.success());
+    let stdout = String::from_utf8_lossy(&second.stdout);
+    assert!(stdout.contains("pulled=1"), "stdout: {}", stdout);
+    assert!(stdout.contains("written=1"), "stdout: {}", stdout);
+
+    let search = run(&["search", "final", "--format", "plain"]);
+    assert!(String::from_utf8_lossy(&search.stdout).contains("Vault final"));
+    let resynced = std::fs::read_to_string(&note_path).expect("reread note");
+    assert!(resynced.contains("tags: [work, done]\n") || resynced.contains("tags: [done, work]\n"));
+    assert_ne!(resynced.lines().nth(1), note.lines().nth(1));
+
+    let third = run(&["vault", "sync", vault, "--plaintext"]);
+    let stdout = String::from_utf8_lossy(&third.stdout);
+    assert!(stdout.contains("pulled=0") && stdout.contains("unchanged=1"));
+
+    let encrypted_dir = config_home.join("vault-age");
+    let sealed = run(&[
+        "vault",
+        "sync",
+        encrypted_dir.to_str().unwrap(),
+        "--encrypt",
+    ]);
+    assert!(sealed.status.success());
+    assert!(String::from_utf8_lossy(&sealed.stdout).contains("written=1"));
+}
+
+#[test]
+fn test_cli_export_chunks_by_month_and_resumes() {
+    let ledger_path = temp_ledger_path("ledger_cli_export_chunks");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_export_chunks");
+    let out_dir = ledger_path.with_extension("chunks");
+
+    let command = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let run = |args: &[&str]| {
+        let output = command(args);
+        assert!(
+            output.status.success(),
+            "{:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stderr).to_string()
+    };
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    for (body, date) in [
+        ("January one", "2024-01-10"),
+        ("January two", "2024-01-20"),
+        ("February", "2024-02-14"),
+        ("March", "2024-03-15"),
+    ] {
+        run(&[
+            "add",
+            "journal",
+            "--body",
+            body,
+            "--date",
+            date,
+            "--no-input",
+        ]);
+    }
+
+    let out = out_dir.to_str().unwrap();
+    let export = ["export", "--chunk-by", "month", "--out-dir", out];
+    let stderr = run(&export);
+    assert!(stderr.contains("export_count=4"), "{}", stderr);
+    assert!(stderr.contains("chunk_count=3"));
+
+    let read_json = |name: &str| -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(out_dir.join(name)).expect("read file"))
+            .expect("parse json")
+    };
+    let manifest = read_json("manifest.json");
+    assert_eq!(manifest["complete"], true);
+    let chunks = manifest["chunks"].as_array().unwrap();
+    let files: Vec<&str> = chunks.iter().map(|c| c["file"].as_str().unwrap()).collect();
+    assert_eq!(files, ["2024-01.json", "2024-02.json", "2024-03.json"]);
+    let january = read_json("2024-01.json");
+    assert_eq!(january[0]["data"]["body"], "January one");
+    assert_eq!(january[1]["data"]["body"], "January two");
+
+    // An existing export is never overwritten without --resume
+    let output = command(&export);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--resume"));
+
+    // A damaged chunk is rewritten along with everything after it
+    std::fs::write(out_dir.join("2024-02.json"), "[").expect("truncate chunk");
+    let stderr = run(&[&export[..], &["--resume"]].concat());
+    assert!(stderr.contains("skipped_chunks=1"), "{}", stderr);
+    assert!(stderr.contains("export_count=2"));
+    assert_eq!(read_json("2024-02.json")[0]["data"]["body"], "February");
+    assert_eq!(
+        read_json("manifest.json")["chunks"]
+            .as_array()
+            .unwrap()
+            .len(),
+        3
+    );
+
+    let output = command(&[&export[..], &["--resume", "--format", "jsonl"]].concat());
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("different options"));
+
+    let _ = std::fs::remove_dir_all(&out_dir);
+}
+
+#[test]
+fn test_cli_todotxt_import_and_export() {
+    let ledger_path = temp_ledger_path("ledger_cli_todotxt");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_todotxt");
+    let todo_path = config_home.join("todo.txt");
+    std::fs::write(
+        &todo_path,
+        "(A) 2024-03-01 Call Mom +family @phone due:2024-03-05\n\nx 2024-03-04 2024-03-02 File taxes\n",
+    )
+    .expect("write todo.txt");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let todo = todo_path.to_str().unwrap();
+    let imported = run(&["import", "--from", "todotxt", todo]);
+    assert!(
+        imported.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&imported.stderr)
+    );
+    assert!(String::from_utf8_lossy(&imported.stdout).contains("entries=2"));
+
+    let again = run(&["import", "--from", "todotxt", todo]);
+    let stdout = String::from_utf8_lossy(&again.stdout);
+    assert!(stdout.contains("entries=0") && stdout.contains("skipped=2"));
+
+    let exported = run(&["export", "--format", "todotxt", "--type", "task"]);
+    assert!(exported.status.success());
+    let stdout = String::from_utf8_lossy(&exported.stdout);
+    assert!(stdout
+        .lines()
+        .any(|line| line == "(A) 2024-03-01 Call Mom +family @phone due:2024-03-05"));
+    assert!(stdout
+        .lines()
+        .any(|line| line == "x 2024-03-04 2024-03-02 File taxes"));
+
+    let wrong_type = run(&["export", "--format", "todotxt", "journal"]);
+    assert!(!wrong_type.status.success());
+    assert!(String::from_utf8_lossy(&wrong_type.stderr).contains("--type task"));
+}
+
+#[test]
+fn test_cli_export_atom_feed_for_tag() {
+    let ledger_path = temp_ledger_path("ledger_cli_atom");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_atom");
+    let feed_path = config_home.join("feed.xml");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+    let body = "Shipped the <feed> & more";
+    assert!(run(&["add", "journal", "--body", body, "--tag", "public"])
+        .status
+        .success());
+    assert!(run(&["add", "journal", "--body", "Private thoughts"])
+        .status
+        .success());
+
+    let export = run(&[
+        "export",
+        "--format",
+        "atom",
+        "--tag",
+        "public",
+        "--out",
+        feed_path.to_str().unwrap(),
+    ]);
+    assert!(
+        export.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&export.stderr)
+    );
+    assert!(export.stdout.is_empty());
+
+    let feed = std::fs::read_to_string(&feed_path).expect("read feed");
+    assert!(feed.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>"));
+    assert!(feed.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+    assert!(feed.contains("<title>Ledger: public</title>"));
+    assert!(feed.contains("<title>Shipped the &lt;feed&gt; &amp; more</title>"));
+    assert_eq!(feed.matches("<entry>").count(), 1);
+    assert!(!feed.contains("Private thoughts"));
+}
+
+#[test]
+fn test_cli_share_bundle_opens_with_recipient_key() {
+    use age::secrecy::ExposeSecret;
+
+    let ledger_path = temp_ledger_path("ledger_cli_share");
+    let dir = std::env::temp_dir().join(format!("ledger_cli_share_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create share dir");
+    let bundle_path = dir.join("trip.bundle");
+    let key_path = dir.join("friend.key");
+    let wrong_key_path = dir.join("stranger.key");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_share");
+
+    let friend = age::x25519::Identity::generate();
+    let stranger = age::x25519::Identity::generate();
+    std::fs::write(&key_path, friend.to_string().expose_secret()).expect("write key");
+    std::fs::write(&wrong_key_path, stranger.to_string().expose_secret()).expect("write key");
+    let recipient = friend.to_public().to_string();
+
+    let command = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    run(&["compositions", "create", "trip-2024"]);
+    run(&["add", "journal", "--body", "Arrived in Lisbon"]);
+    run(&["add", "journal", "--body", "Private thoughts"]);
+    let list: serde_json::Value = serde_json::from_str(&run(&["list", "--json"])).unwrap();
+    let shared_id = list
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["data"]["body"] == "Arrived in Lisbon")
+        .map(|e| e["id"].as_str().unwrap().to_string())
+        .unwrap();
+    run(&["attach", &shared_id, "trip-2024"]);
+    run(&["compositions", "edit", "trip-2024", "--body", "Itinerary"]);
+    run(&["compositions", "create", "therapy"]);
+    run(&["compositions", "edit", "therapy", "--body", "Session notes"]);
+
+    let shared = run(&[
+        "share",
+        "--where",
+        "composition=trip-2024",
+        "--to-age-recipient",
+        &recipient,
+        "--out",
+        bundle_path.to_str().unwrap(),
+    ]);
+    assert!(shared.contains("entries=1"), "stdout: {}", shared);
+    let raw = std::fs::read(&bundle_path).expect("read bundle");
+    assert!(!String::from_utf8_lossy(&raw).contains("Lisbon"));
+
+    let opened = run(&[
+        "share",
+        "open",
+        bundle_path.to_str().unwrap(),
+        "--identity",
+        key_path.to_str().unwrap(),
+        "--json",
+    ]);
+    let opened: serde_json::Value = serde_json::from_str(&opened).expect("parse share");
+    let entries = opened["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["id"], shared_id.as_str());
+    assert_eq!(entries[0]["entry_type_name"], "journal");
+    assert_eq!(opened["compositions"][0]["name"], "trip-2024");
+
+    // Only the overviews of compositions in the bundle travel with it
+    let overview_bodies = |path: &Path| {
+        let plaintext = ledger_core::storage::encryption::decrypt_with_identities(
+            &std::fs::read(path).expect("read bundle"),
+            friend.to_string().expose_secret(),
+        )
+        .expect("decrypt bundle");
+        let bundle: serde_json::Value = serde_json::from_slice(&plaintext).expect("parse bundle");
+        bundle["composition_overviews"]
+            .as_array()
+            .map(|overviews| {
+                overviews
+                    .iter()
+                    .filter_map(|overview| overview["body"].as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    assert_eq!(overview_bodies(&bundle_path), vec!["Itinerary"]);
+    run(&["add", "journal", "--body", "Tram 28", "--tag", "lisbon"]);
+    let tagged_path = dir.join("tagged.bundle");
+    run(&[
+        "share",
+        "--where",
+        "tag=lisbon",
+        "--to-age-recipient",
+        &recipient,
+        "--out",
+        tagged_path.to_str().unwrap(),
+    ]);
+    assert!(overview_bodies(&tagged_path).is_empty());
+
+    let denied = command(&[
+        "share",
+        "open",
+        bundle_path.to_str().unwrap(),
+        "--identity",
+        wrong_key_path.to_str().unwrap(),
+    ]);
+    assert!(!denied.status.success());
+    assert!(String::from_utf8_lossy(&denied.stderr).contains("None of the identities"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_cli_upcoming_lists_recurring_dates() {
+    use chrono::{Datelike, Duration, Local};
+
+    let ledger_path = temp_ledger_path("ledger_cli_upcoming");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_upcoming");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    // Pick dates a few years back whose anniversaries are 3 and 40 days out
+    let today = Local::now().date_naive();
+    let years_back = |date: chrono::NaiveDate| {
+        date.with_year(date.year() - 4)
+            .unwrap_or_else(|| date - Duration::days(4 * 365))
+    };
+    let soon = years_back(today + Duration::days(3));
+    let later = years_back(today + Duration::days(40));
+    let soon_field = format!("date={}", soon.format("%Y-%m-%d"));
+    let later_field = format!("date={}", later.format("%Y-%m-%d"));
+    run(&[
+        "add",
+        "anniversary",
+        "-f",
+        "title=Wedding",
+        "-f",
+        &soon_field,
+        "--no-input",
+    ]);
+    run(&[
+        "add",
+        "anniversary",
+        "-f",
+        "title=Moved in",
+        "-f",
+        &later_field,
+        "--no-input",
+    ]);
+
+    let upcoming: serde_json::Value =
+        serde_json::from_str(&run(&["upcoming", "--within", "14d", "--json"]))
+            .expect("parse upcoming");
+    let items = upcoming.as_array().unwrap();
+    assert_eq!(items.len(), 1, "upcoming: {}", upcoming);
+    assert_eq!(items[0]["entry"]["data"]["title"], "Wedding");
+    assert_eq!(items[0]["field"], "date");
+    assert_eq!(items[0]["occurrence"], 4);
+
+    let wide = run(&["upcoming", "--within", "60d"]);
+    assert!(wide.starts_with("count=2"), "stdout: {}", wide);
+    assert!(wide.contains("Moved in"));
+}
+
+#[test]
+fn test_cli_upcoming_notify_sends_once_a_day() {
+    use chrono::{Datelike, Local};
+
+    let ledger_path = temp_ledger_path("ledger_cli_notify");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_notify");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let script = ledger_path.with_extension("notify.sh");
+    let sent_log = ledger_path.with_extension("sent");
+    std::fs::write(
+        &script,
+        format!(
+            "printf '%s|%s\\n' \"$1\" \"$2\" >> {}\n",
+            sent_log.display()
+        ),
+    )
+    .expect("write script");
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str(&format!(
+        "\n[reminders]\nnotify_command = \"sh {}\"\n",
+        script.display()
+    ));
+    std::fs::write(&config_path, config).expect("write config");
+
+    let today = Local::now().date_naive();
+    let years_ago = today.with_year(today.year() - 3).unwrap_or(today);
+    let date_field = format!("date={}", years_ago.format("%Y-%m-%d"));
+    run(&[
+        "add",
+        "anniversary",
+        "-f",
+        "title=Wedding",
+        "-f",
+        &date_field,
+        "--no-input",
+    ]);
+
+    let first = run(&["upcoming", "--notify"]);
+    assert!(first.contains("notified=1"), "{}", first);
+    let second = run(&["upcoming", "--notify"]);
+    assert!(second.contains("notified=0"), "{}", second);
+
+    let sent = std::fs::read_to_string(&sent_log).expect("notification sent");
+    assert_eq!(sent.lines().count(), 1, "{}", sent);
+    assert!(
+        sent.starts_with("Wedding|anniversary date today"),
+        "{}",
+        sent
+    );
+}
+
+#[test]
+fn test_cli_export_markdown_formats_fields_for_locale() {
+    let ledger_path = temp_ledger_path("ledger_cli_locale");
+    let bundle_path =
+        std::env::temp_dir().join(format!("ledger_cli_locale_{}.json", std::process::id()));
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_locale");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str("\n[ui]\nlocale = \"de-DE\"\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    let bundle = serde_json::json!({
+        "entry_types": [{
+            "id": "6f0c1f7e-4c1b-4d0e-9a57-0f3b7d1f2a10",
+            "name": "weigh-in",
+            "created_at": "2024-01-01T00:00:00Z",
+            "device_id": "00000000-0000-0000-0000-000000000001",
+            "versions": [{
+                "version": 1,
+                "created_at": "2024-01-01T00:00:00Z",
+                "active": true,
+                "body": {"fields": [
+                    {"name": "weight", "type": "number", "required": true, "unit": "kg", "order": 1},
+                    {"name": "fee", "type": "number", "currency": "EUR", "order": 2},
+                    {"name": "on", "type": "date", "order": 3}
+                ]}
+            }]
+        }],
+        "entries": [{
+            "id": "3b8f7a2e-9d41-4c55-8e0a-2f6d1c9b7e11",
+            "entry_type_id": "6f0c1f7e-4c1b-4d0e-9a57-0f3b7d1f2a10",
+            "schema_version": 1,
+            "data": {"weight": 1234.5, "fee": 12, "on": "2024-03-09"},
+            "tags": ["health"],
+            "created_at": "2024-03-09T07:30:00Z",
+            "device_id": "00000000-0000-0000-0000-000000000001",
+            "supersedes": null
+        }]
+    });
+    std::fs::write(&bundle_path, bundle.to_string()).expect("write bundle");
+    run(&["import", bundle_path.to_str().unwrap()]);
+
+    let markdown = run(&["export", "--format", "markdown"]);
+    assert!(markdown.starts_with("# Ledger export"), "{}", markdown);
+    assert!(markdown.contains("## 09.03.2024 07:30 \u{00B7} weigh-in"));
+    assert!(markdown.contains("- **tags:** health"));
+    assert!(
+        markdown.contains("- **weight:** 1.234,5 kg"),
+        "{}",
+        markdown
+    );
+    assert!(markdown.contains("- **fee:** 12,00 \u{20AC}"));
+    assert!(markdown.contains("- **on:** 09.03.2024"));
+
+    // JSON keeps the raw values
+    let json = run(&["export"]);
+    assert!(json.contains("1234.5"));
+
+    let _ = std::fs::remove_file(&bundle_path);
+}
+
+#[test]
+fn test_cli_summary_template_drives_list_and_exports() {
+    let ledger_path = temp_ledger_path("ledger_cli_summary_template");
+    let bundle_path = std::env::temp_dir().join(format!(
+        "ledger_cli_summary_template_{}.json",
+        std::process::id()
+    ));
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_summary_template");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let bundle = serde_json::json!({
+        "entry_types": [{
+            "id": "6f0c1f7e-4c1b-4d0e-9a57-0f3b7d1f2a11",
+            "name": "weigh-in",
+            "created_at": "2024-01-01T00:00:00Z",
+            "device_id": "00000000-0000-0000-0000-000000000001",
+            "versions": [{
+                "version": 1,
+                "created_at": "2024-01-01T00:00:00Z",
+                "active": true,
+                "body": {
+                    "summary_template": "{weight} kg \u{2014} {notes}",
+                    "fields": [
+                        {"name": "weight", "type": "number", "required": true, "order": 1},
+                        {"name": "notes", "type": "string", "order": 2}
+                    ]
+                }
+            }]
+        }],
+        "entries": [{
+            "id": "3b8f7a2e-9d41-4c55-8e0a-2f6d1c9b7e12",
+            "entry_type_id": "6f0c1f7e-4c1b-4d0e-9a57-0f3b7d1f2a11",
+            "schema_version": 1,
+            "data": {"weight": 80, "notes": "after run"},
+            "tags": [],
+            "created_at": "2024-03-09T07:30:00Z",
+            "device_id": "00000000-0000-0000-0000-000000000001",
+            "supersedes": null
+        }]
+    });
+    std::fs::write(&bundle_path, bundle.to_string()).expect("write bundle");
+    run(&["import", bundle_path.to_str().unwrap()]);
+
+    let list = run(&["list"]);
+    assert!(list.contains("80 kg \u{2014} after run"), "{}", list);
+
+    let markdown = run(&["export", "--format", "markdown"]);
+    assert!(
+        markdown.contains("\u{00B7} weigh-in \u{2014} 80 kg \u{2014} after run\n"),
+        "{}",
+        markdown
+    );
+
+    let atom = run(&["export", "--format", "atom"]);
+    assert!(
+        atom.contains("<title>80 kg \u{2014} after run</title>"),
+        "{}",
+        atom
+    );
+
+    let _ = std::fs::remove_file(&bundle_path);
+}
+
+#[test]
+fn test_cli_expenses_report_totals_categories_against_budgets() {
+    let ledger_path = temp_ledger_path("ledger_cli_expenses");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_expenses");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let ok = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str("\n[budgets]\ngroceries = 200\ntravel = 100\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    let add = |amount: &str, currency: &str, category: &str, date: &str| {
+        let amount = format!("amount={}", amount);
+        let currency = format!("currency={}", currency);
+        let category = format!("category={}", category);
+        ok(&[
+            "add",
+            "expense",
+            "-f",
+            &amount,
+            "-f",
+            &currency,
+            "-f",
+            &category,
+            "--date",
+            date,
+            "--no-input",
+        ])
+    };
+    add("45.20", "usd", "Groceries", "2025-01-10");
+    add("30", "USD", " groceries ", "2025-01-15");
+    add("12.5", "USD", "coffee", "2025-01-20");
+    add("99", "USD", "groceries", "2025-02-12");
+
+    let bad = run(&[
+        "add",
+        "expense",
+        "-f",
+        "amount=5",
+        "-f",
+        "currency=dollars",
+        "-f",
+        "category=misc",
+        "--no-input",
+    ]);
+    assert!(!bad.status.success());
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("Invalid currency"));
+
+    let report = ok(&["expenses", "report", "--month", "2025-01"]);
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "month=2025-01",
+            "category=coffee currency=USD count=1 spent=12.50",
+            "category=groceries currency=USD count=2 spent=75.20 budget=200.00 remaining=124.80",
+            "category=travel currency=USD count=0 spent=0.00 budget=100.00 remaining=100.00",
+            "total currency=USD count=3 spent=87.70",
+        ]
+    );
+
+    let json = ok(&["expenses", "report", "--month", "2025-02", "--json"]);
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["month"], "2025-02");
+    assert_eq!(value["totals"][0]["spent"], 99.0);
+    assert_eq!(value["categories"][0]["remaining"], 101.0);
+
+    let invalid = run(&["expenses", "report", "--month", "2025-13"]);
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_cli_habit_done_and_status_matrix() {
+    use chrono::{Duration, Local};
+
+    let ledger_path = temp_ledger_path("ledger_cli_habits");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_habits");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let ok = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    ok(&["habit", "add", "stretch", "--no-input"]);
+    ok(&["habit", "add", "run", "--schedule", "3/week", "--no-input"]);
+    assert!(!run(&["habit", "add", "swim", "--schedule", "often"])
+        .status
+        .success());
+    assert!(!run(&["habit", "done", "swim", "--no-input"])
+        .status
+        .success());
+
+    let today = Local::now().date_naive();
+    for days_ago in [2, 1] {
+        let date = (today - Duration::days(days_ago))
+            .format("%Y-%m-%d")
+            .to_string();
+        ok(&["habit", "done", "stretch", "--date", &date, "--no-input"]);
+    }
+    let done = ok(&["habit", "done", "stretch", "--no-input"]);
+    assert!(done.contains("id="));
+    let again = ok(&["habit", "done", "stretch", "--no-input"]);
+    assert!(again.contains("status=already_done"));
+
+    let status = ok(&["habit", "status", "--days", "5"]);
+    let lines: Vec<&str> = status.lines().collect();
+    assert_eq!(lines[0], "count=2");
+    assert_eq!(
+        lines[1],
+        "habit=run schedule=3/week current=0 longest=0 unit=weeks matrix=-----"
+    );
+    assert_eq!(
+        lines[2],
+        "habit=stretch schedule=daily current=3 longest=3 unit=days matrix=--xxx"
+    );
+
+    let json = ok(&["habit", "status", "--json"]);
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value[1]["name"], "stretch");
+    assert_eq!(value[1]["days"].as_array().unwrap().len(), 30);
+
+    ok(&["habit", "remove", "run", "--no-input"]);
+    let status = ok(&["habit", "status"]);
+    assert!(status.starts_with("count=1\n"));
+}
+
+#[test]
+fn test_cli_correlate_metric_series_with_lag() {
+    use chrono::{Duration, Local};
+
+    let ledger_path = temp_ledger_path("ledger_cli_correlate");
+    let bundle_path =
+        std::env::temp_dir().join(format!("ledger_cli_correlate_{}.json", std::process::id()));
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_correlate");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let ok = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    let entry_type = |id: &str, name: &str, field: &str| {
+        serde_json::json!({
+            "id": id,
+            "name": name,
+            "created_at": "2024-01-01T00:00:00Z",
+            "device_id": "00000000-0000-0000-0000-000000000001",
+            "versions": [{
+                "version": 1,
+                "created_at": "2024-01-01T00:00:00Z",
+                "active": true,
+                "body": {"fields": [
+                    {"name": field, "type": "number", "required": true},
+                    {"name": "date", "type": "date", "required": true}
+                ]}
+            }]
+        })
+    };
+    let sleep_id = "6f0c1f7e-4c1b-4d0e-9a57-0f3b7d1f2a21";
+    let mood_id = "6f0c1f7e-4c1b-4d0e-9a57-0f3b7d1f2a22";
+    let today = Local::now().date_naive();
+    let hours = [6.0, 8.0, 5.0, 7.5, 9.0, 6.5];
+    let mut entries = Vec::new();
+    for (i, value) in hours.iter().enumerate() {
+        let date = (today - Duration::days(5 - i as i64))
+            .format("%Y-%m-%d")
+            .to_string();
+        for (type_id, field, value) in
+            [(sleep_id, "hours", *value), (mood_id, "score", value * 2.0)]
+        {
+            entries.push(serde_json::json!({
+                "id": uuid::Uuid::new_v4(),
+                "entry_type_id": type_id,
+                "schema_version": 1,
+                "data": {field: value, "date": date},
+                "tags": [],
+                "created_at": "2024-03-09T07:30:00Z",
+                "device_id": "00000000-0000-0000-0000-000000000001",
+                "supersedes": null
+            }));
+        }
+    }
+    let bundle = serde_json::json!({
+        "entry_types": [
+            entry_type(sleep_id, "sleep", "hours"),
+            entry_type(mood_id, "mood", "score")
+        ],
+        "entries": entries,
+    });
+    std::fs::write(&bundle_path, bundle.to_string()).expect("write bundle");
+    ok(&["import", bundle_path.to_str().unwrap()]);
+
+    let report = ok(&[
+        "correlate",
+        "metric:sleep",
+        "metric:mood.score",
+        "--last",
+        "7d",
+        "--max-lag",
+        "1",
+    ]);
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines[0], "first=metric:sleep");
+    assert_eq!(lines[4], "lag=0 pairs=6 r=1.000");
+    assert!(lines[5].starts_with("lag=1 pairs=5 r="), "{}", report);
+
+    let json = ok(&["correlate", "metric:sleep", "metric:mood", "--json"]);
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["results"][0]["r"], 1.0);
+    assert_eq!(value["series"]["first"].as_array().unwrap().len(), 90);
+
+    let bad = run(&["correlate", "metric:sleep.date", "metric:mood"]);
+    assert!(!bad.status.success());
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("not a numeric field"));
+
+    let _ = std::fs::remove_file(&bundle_path);
+}
+
+#[test]
+fn test_cli_metrics_anomalies_flags_typo() {
+    use chrono::{Duration, Local};
+
+    let ledger_path = temp_ledger_path("ledger_cli_anomalies");
+    let bundle_path =
+        std::env::temp_dir().join(format!("ledger_cli_anomalies_{}.json", std::process::id()));
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_anomalies");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let ok = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    let weight_id = "6f0c1f7e-4c1b-4d0e-9a57-0f3b7d1f2a31";
+    let today = Local::now().date_naive();
+    let kilograms = [85.2, 84.9, 85.4, 85.0, 84.7, 85.1, 8.5, 85.3];
+    let typo_id = uuid::Uuid::new_v4();
+    let mut entries = Vec::new();
+    for (i, value) in kilograms.iter().enumerate() {
+        let date = (today - Duration::days(7 - i as i64))
+            .format("%Y-%m-%d")
+            .to_string();
+        let id = if *value < 10.0 {
+            typo_id
+        } else {
+            uuid::Uuid::new_v4()
+        };
+        entries.push(serde_json::json!({
+            "id": id,
+            "entry_type_id": weight_id,
+            "schema_version": 1,
+            "data": {"kg": value, "date": date},
+            "tags": [],
+            "created_at": "2024-03-09T07:30:00Z",
+            "device_id": "00000000-0000-0000-0000-000000000001",
+            "supersedes": null
+        }));
+    }
+    let bundle = serde_json::json!({
+        "entry_types": [{
+            "id": weight_id,
+            "name": "weight",
+            "created_at": "2024-01-01T00:00:00Z",
+            "device_id": "00000000-0000-0000-0000-000000000001",
+            "versions": [{
+                "version": 1,
+                "created_at": "2024-01-01T00:00:00Z",
+                "active": true,
+                "body": {"fields": [
+                    {"name": "kg", "type": "number", "required": true},
+                    {"name": "date", "type": "date", "required": true}
+                ]}
+            }]
+        }],
+        "entries": entries,
+    });
+    std::fs::write(&bundle_path, bundle.to_string()).expect("write bundle");
+    ok(&["import", bundle_path.to_str().unwrap()]);
+
+    let report = ok(&[
+        "metrics",
+        "anomalies",
+        "--metric",
+        "weight",
+        "--window",
+        "30d",
+        "--json",
+    ]);
+    let value: serde_json::Value = serde_json::from_str(&report).expect("json");
+    assert_eq!(value["field"], "kg");
+    assert_eq!(value["checked"], 8);
+    let anomalies = value["anomalies"].as_array().expect("anomalies");
+    assert_eq!(anomalies.len(), 1, "{}", report);
+    assert_eq!(anomalies[0]["id"], typo_id.to_string());
+    assert_eq!(anomalies[0]["value"], 8.5);
+    let reasons = anomalies[0]["reasons"].as_array().expect("reasons");
+    assert!(reasons.contains(&serde_json::json!("sigma")));
+    assert!(reasons.contains(&serde_json::json!("jump")));
+
+    // Without enough history only the jump is reported
+    let plain = ok(&[
+        "metrics",
+        "anomalies",
+        "--metric",
+        "weight.kg",
+        "--window",
+        "2d",
+    ]);
+    assert!(plain.contains("checked=2"), "{}", plain);
+    assert!(
+        plain.contains(&format!("anomaly={} ", typo_id)) && plain.contains("reasons=jump"),
+        "{}",
+        plain
+    );
+
+    let output = run(&["metrics", "anomalies", "--metric", "weight.date"]);
+    assert!(!output.status.success());
+
+    let _ = std::fs::remove_file(&bundle_path);
+}
+
+#[test]
+fn test_cli_today_dashboard_sections() {
+    use chrono::{Duration, Local};
+
+    let ledger_path = temp_ledger_path("ledger_cli_today");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_today");
+
+    let ok = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    let today = Local::now().date_naive();
+    let day = |offset: i64| {
+        (today + Duration::days(offset))
+            .format("%Y-%m-%d")
+            .to_string()
+    };
+    ok(&["add", "journal", "--body", "Morning pages", "--no-input"]);
+    for (title, due) in [
+        ("Pay rent", day(-2)),
+        ("Call mom", day(0)),
+        ("Later", day(3)),
+    ] {
+        let title_field = format!("title={}", title);
+        let due_field = format!("due={}", due);
+        ok(&[
+            "add",
+            "task",
+            "-f",
+            &title_field,
+            "-f",
+            &due_field,
+            "--no-input",
+        ]);
+    }
+    let birthday = format!(
+        "date={}",
+        day(2).replacen(&today.format("%Y").to_string(), "2000", 1)
+    );
+    ok(&[
+        "add",
+        "anniversary",
+        "-f",
+        "title=Birthday",
+        "-f",
+        &birthday,
+        "--no-input",
+    ]);
+    ok(&["habit", "add", "stretch", "--no-input"]);
+    ok(&["habit", "done", "stretch", "--no-input"]);
+
+    let report = ok(&["today", "--json"]);
+    let value: serde_json::Value = serde_json::from_str(&report).expect("json");
+    assert_eq!(value["date"], day(0));
+    let tasks = value["tasks"].as_array().expect("tasks");
+    let titles: Vec<&str> = tasks
+        .iter()
+        .map(|task| task["entry"]["data"]["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, vec!["Pay rent", "Call mom"]);
+    assert_eq!(tasks[0]["overdue_days"], 2);
+    let habits = value["habits"].as_array().expect("habits");
+    assert_eq!(habits.len(), 1);
+    assert_eq!(habits[0]["done"], true);
+    assert_eq!(habits[0]["current_streak"], 1);
+    let reminders = value["reminders"].as_array().expect("reminders");
+    assert_eq!(reminders.len(), 1);
+    assert_eq!(reminders[0]["in_days"], 2);
+    // Journal, tasks, anniversary and the habit completion were all written today
+    assert_eq!(value["entries"].as_array().expect("entries").len(), 6);
+
+    let plain = ok(&["today", "--within", "1d"]);
+    assert!(
+        plain.starts_with(&format!("date={}\n", day(0))),
+        "{}",
+        plain
+    );
+    assert!(plain.contains("title=Call mom"), "{}", plain);
+    assert!(!plain.contains("title=Later"), "{}", plain);
+    assert!(
+        plain.contains("habit=stretch done=true streak=1"),
+        "{}",
+        plain
+    );
+    assert!(!plain.contains("reminder="), "{}", plain);
+}
+
+#[test]
+fn test_cli_prompts_rotate_into_today_and_editor() {
+    let ledger_path = temp_ledger_path("ledger_cli_prompts");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_prompts");
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str("\n[prompts]\nenabled = true\nbuiltin = false\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    let ok = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    ok(&["prompts", "add", "How was the walk?"]);
+    ok(&["prompts", "add", "What went well?"]);
+    let added = ok(&["prompts", "add", "What went well?"]);
+    assert!(added.contains("changed=false"), "{}", added);
+    assert!(config_home.join("ledger").join("prompts.txt").exists());
+    ok(&["prompts", "disable", "1"]);
+
+    let list: serde_json::Value =
+        serde_json::from_str(&ok(&["prompts", "list", "--json"])).expect("parse list");
+    assert_eq!(list.as_array().map(Vec::len), Some(2));
+    assert_eq!(list[0]["disabled"], true);
+    assert_eq!(list[1]["today"], true);
+    let today: serde_json::Value =
+        serde_json::from_str(&ok(&["today", "--json"])).expect("parse today");
+    assert_eq!(today["prompt"], "What went well?");
+
+    // The prompt opens the editor as a placeholder and is not saved
+    let captured = data_home.join("captured.md");
+    let editor = format!(
+        "sh -c 'cp \"$1\" \"{}\"; printf \"Finished the draft\" >> \"$1\"' editor",
+        captured.display()
+    );
+    ok(&["add", "journal", "--editor", &editor]);
+    let shown = std::fs::read_to_string(&captured).expect("read captured draft");
+    assert!(shown.contains("What went well?"), "{}", shown);
+    let entries: serde_json::Value =
+        serde_json::from_str(&ok(&["list", "--json"])).expect("parse list");
+    assert_eq!(entries[0]["data"]["body"], "Finished the draft");
+
+    ok(&["prompts", "disable", "How was the walk?", "--undo"]);
+    let plain = ok(&["prompts", "list"]);
+    assert!(
+        plain.contains("prompt=1 source=file disabled=false"),
+        "{}",
+        plain
+    );
+}
+
+#[test]
+fn test_cli_add_saves_crashed_editor_draft_encrypted() {
+    let ledger_path = temp_ledger_path("ledger_cli_crash_draft");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_crash_draft");
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    // The editor writes, then dies before ledger can read the body back
+    let editor = "sh -c 'printf \"Halfway through a secret thought\" > \"$1\"; exit 1' editor";
+    let output = run(&["add", "journal", "--editor", editor]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("saved encrypted"), "{}", stderr);
+
+    let recovery = data_home.parent().unwrap().join("runtime/ledger/recovery");
+    let saved: Vec<_> = std::fs::read_dir(&recovery)
+        .expect("read recovery dir")
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "age"))
+        .collect();
+    assert_eq!(saved.len(), 1);
+    let sealed = std::fs::read(&saved[0]).expect("read saved draft");
+    assert!(!String::from_utf8_lossy(&sealed).contains("secret thought"));
+
+    // Without a terminal the next add only points at the draft
+    let output = run(&["add", "journal", "--body", "Something else"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("1 unsaved draft"), "{}", stderr);
+    assert!(saved[0].exists());
+}
+
+#[test]
+fn test_cli_drafts_publish_into_entries() {
+    let ledger_path = temp_ledger_path("ledger_cli_drafts");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_drafts");
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let ok = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+    let json = |args: &[&str]| -> serde_json::Value {
+        serde_json::from_str(&ok(args)).expect("parse json")
+    };
+
+    let saved = json(&["draft", "save", "--body", "Unfinished idea", "--json"]);
+    let draft_id = saved["id"].as_str().expect("draft id").to_string();
+    let other = json(&["draft", "save", "--body", "Throwaway", "--json"]);
+    ok(&[
+        "draft",
+        "edit",
+        &draft_id[..8],
+        "--body",
+        "Finished idea",
+        "-t",
+        "ideas",
+    ]);
+
+    // Drafts stay out of list, search and export
+    assert_eq!(json(&["list", "--json"]).as_array().map(Vec::len), Some(0));
+    assert_eq!(
+        json(&["search", "idea", "--json"]).as_array().map(Vec::len),
+        Some(0)
+    );
+    let exported = ok(&["export", "--format", "jsonl"]);
+    assert!(!exported.contains("Finished idea"), "{}", exported);
+    let drafts = json(&["draft", "list", "--json"]);
+    assert_eq!(drafts.as_array().map(Vec::len), Some(2));
+
+    let published = json(&["draft", "publish", &draft_id, "--json"]);
+    assert_eq!(published["affected"][1]["kind"], "draft");
+    let entries = json(&["list", "--json"]);
+    assert_eq!(entries[0]["data"]["body"], "Finished idea");
+    assert_eq!(entries[0]["tags"][0], "ideas");
+    let drafted_at = drafts
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|draft| draft["id"] == draft_id.as_str())
+        .map(|draft| draft["created_at"].clone())
+        .expect("draft listed");
+    assert_eq!(published["created_at"], drafted_at);
+
+    ok(&["draft", "discard", other["id"].as_str().expect("draft id")]);
+    assert_eq!(
+        json(&["draft", "list", "--json"]).as_array().map(Vec::len),
+        Some(0)
+    );
+}
+
+#[test]
+fn test_cli_destructive_commands_need_yes_without_tty() {
+    let ledger_path = temp_ledger_path("ledger_cli_confirm_yes");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_confirm_yes");
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let run = |args: &[&str]| {
+        ledger_command(&ledger_path, (&config_home, &data_home), args)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .expect("run ledger")
+    };
+
+    assert!(
+        run(&["secret", "set", "token", "--value", "x", "--no-input"])
+            .status
+            .success()
+    );
+    assert!(run(&["scratch", "edit", "--body", "note"]).status.success());
+
+    // Nobody can answer the prompt, so the command fails and names the flag
+    for args in [
+        &["secret", "delete", "token"][..],
+        &["scratch", "clear"][..],
+    ] {
+        let output = run(args);
+        assert!(!output.status.success(), "{:?} should need --yes", args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Confirmation required"), "{}", stderr);
+        assert!(stderr.contains("--yes"), "{}", stderr);
+    }
+
+    assert!(run(&["secret", "delete", "token", "--yes"])
+        .status
+        .success());
+    assert!(run(&["-y", "scratch", "clear"]).status.success());
+    let list = run(&["secret", "list"]);
+    assert!(String::from_utf8_lossy(&list.stdout).trim().is_empty());
+}
+
+#[test]
+fn test_cli_json_output_fits_published_schemas() {
+    let ledger_path = temp_ledger_path("ledger_cli_schema");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_schema");
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let json = |args: &[&str]| -> serde_json::Value {
+        serde_json::from_str(&run_ledger_ok(
+            &ledger_path,
+            (&config_home, &data_home),
+            args,
+        ))
+        .expect("parse json")
+    };
+    let assert_fits = |value: &serde_json::Value, schema: &serde_json::Value| {
+        let properties = schema["properties"].as_object().expect("properties");
+        let object = value.as_object().expect("object");
+        for key in object.keys() {
+            assert!(properties.contains_key(key), "{} is not in the schema", key);
+        }
+        for required in schema["required"].as_array().expect("required") {
+            let required = required.as_str().unwrap();
+            assert!(object.contains_key(required), "{} is missing", required);
+        }
+    };
+
+    let schemas = json(&["schema"]);
+    for view in ["entry", "composition", "template", "receipt"] {
+        assert_eq!(schemas[view], json(&["schema", view]));
+    }
+
+    let receipt = json(&["add", "journal", "--body", "Schema day", "--json"]);
+    assert_fits(&receipt, &schemas["receipt"]);
+    json(&["compositions", "create", "trip", "--json"]);
+    json(&[
+        "templates",
+        "create",
+        "daily",
+        "--entry-type",
+        "journal",
+        "--json",
+    ]);
+
+    for (args, view) in [
+        (&["list", "--json"][..], "entry"),
+        (&["compositions", "list", "--json"][..], "composition"),
+        (&["templates", "list", "--json"][..], "template"),
+    ] {
+        let items = json(args);
+        assert_eq!(items.as_array().map(Vec::len), Some(1), "{:?}", args);
+        assert_fits(&items[0], &schemas[view]);
+    }
+    assert_fits(
+        &json(&["compositions", "show", "trip", "--json"]),
+        &schemas["composition"],
+    );
+    assert_fits(
+        &json(&["templates", "show", "daily", "--json"]),
+        &schemas["template"],
+    );
+}
+
+#[test]
+fn test_cli_status_summarizes_what_needs_attention() {
+    let ledger_path = temp_ledger_path("ledger_cli_status");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_status");
+
+    let run = |args: &[&str], unlock: Option<&str>| {
+        let mut cmd = ledger_command(&ledger_path, (&config_home, &data_home), args);
+        match unlock {
+            Some(value) => cmd.env("LEDGER_PASSPHRASE", value),
+            None => cmd.env_remove("LEDGER_PASSPHRASE"),
+        };
+        let output = cmd.output().expect("run ledger");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    run(
+        &["add", "journal", "--body", "Hello", "--no-input"],
+        Some(passphrase),
+    );
+    run(&["draft", "save", "--body", "Later"], Some(passphrase));
+
+    // Locked: only what can be read without the passphrase
+    let plain = run(&["status"], None);
+    assert!(plain.contains("lock=locked"), "{}", plain);
+    assert!(!plain.contains("drafts="));
+    assert!(plain.contains("check=never"));
+    assert!(plain.contains("attention=No backup recorded"));
+
+    run(&["check"], Some(passphrase));
+    let dest = ledger_path.with_extension("bak");
+    run(&["backup", dest.to_str().unwrap()], Some(passphrase));
+    let status: serde_json::Value =
+        serde_json::from_str(&run(&["status", "--json"], Some(passphrase))).expect("parse json");
+    assert_eq!(status["lock"]["state"], "unlocked");
+    assert_eq!(status["lock"]["source"], "environment");
+    assert_eq!(status["drafts"], 1);
+    assert!(status["last_entry"].is_string());
+    assert!(status["last_backup"].is_string());
+    assert_eq!(status["last_check"]["errors"], 0);
+    assert_eq!(status["state"], "dirty");
+    assert_eq!(
+        status["attention"],
+        serde_json::json!(["1 draft(s) not published"])
+    );
+}
+
+#[test]
+fn test_cli_replica_follows_every_save() {
+    let ledger_path = temp_ledger_path("ledger_cli_replica");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_replica");
+    let replica_dir = data_home.join("mirror");
+    std::fs::create_dir_all(&replica_dir).expect("create replica dir");
+    let replica_path = replica_dir.join("ledger.replica");
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str(&format!(
+        "\n[replica]\npath = \"{}\"\n",
+        replica_path.display()
+    ));
+    std::fs::write(&config_path, config).expect("write config");
+
+    let mut cmd = Command::new(bin());
+    cmd.args(["add", "journal", "--body", "Mirrored", "--no-input"])
+        .arg("--ledger")
+        .arg(&ledger_path)
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut cmd, &config_home, &data_home);
+    let output = cmd.output().expect("run ledger");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The replica is written in the background; wait for its record
+    let mut record_path = ledger_path.as_os_str().to_os_string();
+    record_path.push(".replica");
+    let record = (0..100)
+        .find_map(|_| {
+            let record = std::fs::read_to_string(&record_path).ok();
+            if record.is_none() {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            record
+        })
+        .expect("replica record written");
+    let record: serde_json::Value = serde_json::from_str(&record).expect("parse record");
+    assert_eq!(record["ok"], true, "{}", record);
+    assert_eq!(
+        std::fs::read(&replica_path).expect("read replica"),
+        std::fs::read(&ledger_path).expect("read ledger")
+    );
+
+    // Another ledger saved with --ledger leaves the replica alone
+    let other_path = temp_ledger_path("ledger_cli_replica_other");
+    create_ledger_with_passphrase(&other_path, passphrase);
+    run_ledger_ok(
+        &other_path,
+        (&config_home, &data_home),
+        &["add", "journal", "--body", "Elsewhere", "--no-input"],
+    );
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let mut other_record = other_path.as_os_str().to_os_string();
+    other_record.push(".replica");
+    assert!(!Path::new(&other_record).exists());
+    assert_eq!(
+        std::fs::read(&replica_path).expect("read replica"),
+        std::fs::read(&ledger_path).expect("read ledger")
+    );
+}
+
+#[test]
+fn test_cli_reencrypt_records_rotation() {
+    let ledger_path = temp_ledger_path("ledger_cli_reencrypt");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_reencrypt");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let config = std::fs::read_to_string(&config_path)
+        .expect("read config")
+        .replace("[security]\n", "[security]\nreencrypt_after_days = 365\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    let before = std::fs::read(&ledger_path).expect("read ledger");
+    let first: serde_json::Value =
+        serde_json::from_str(&run(&["reencrypt", "--json"])).expect("parse json");
+    assert!(first["previous_reencrypted_at"].is_null());
+    assert!(first["work_factor"].is_u64());
+    assert_ne!(
+        std::fs::read(&ledger_path).expect("read ledger"),
+        before,
+        "fresh salt and nonce"
+    );
+
+    let second: serde_json::Value =
+        serde_json::from_str(&run(&["reencrypt", "--json"])).expect("parse json");
+    assert_eq!(second["previous_reencrypted_at"], first["reencrypted_at"]);
+
+    let doctor = run(&["doctor"]);
+    assert!(doctor.contains("check=encryption ok"), "{}", doctor);
+    assert!(doctor.contains("encryption_age_days=0"));
+}
+
+#[test]
+fn test_cli_week_columns_and_offset() {
+    use chrono::{Datelike, Duration, Local};
+
+    let ledger_path = temp_ledger_path("ledger_cli_week");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_week");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let ok = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    let today = Local::now().date_naive();
+    let monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+    let sunday = monday + Duration::days(6);
+    ok(&["add", "journal", "--body", "Planning notes", "--no-input"]);
+    let due = format!("due={}", sunday.format("%Y-%m-%d"));
+    ok(&[
+        "add",
+        "task",
+        "-f",
+        "title=Ship it",
+        "-f",
+        &due,
+        "--no-input",
+    ]);
+    ok(&["habit", "add", "stretch", "--no-input"]);
+    ok(&["habit", "done", "stretch", "--no-input"]);
+
+    let report = ok(&["week", "--json"]);
+    let value: serde_json::Value = serde_json::from_str(&report).expect("json");
+    assert_eq!(value["start"], monday.format("%Y-%m-%d").to_string());
+    let days = value["days"].as_array().expect("days");
+    assert_eq!(days.len(), 7);
+    let index = today.weekday().num_days_from_monday() as usize;
+    let day = &days[index];
+    // The habit completion shows under habits rather than as an entry
+    assert_eq!(day["count"], 2, "{}", report);
+    assert_eq!(day["by_type"]["journal"], 1);
+    assert_eq!(day["habits_done"], serde_json::json!(["stretch"]));
+    assert_eq!(day["habits_scheduled"], 1);
+    assert_eq!(days[6]["tasks_due"][0]["title"], "Ship it");
+
+    let last_week = ok(&["week", "--offset", "-1"]);
+    let lines: Vec<&str> = last_week.lines().collect();
+    assert_eq!(
+        lines[0],
+        format!("start={}", (monday - Duration::weeks(1)).format("%Y-%m-%d"))
+    );
+    assert_eq!(lines.len(), 9);
+    assert!(lines[2..]
+        .iter()
+        .all(|line| line.contains(" entries=0 tasks_due=0 habits_done=0 ")));
+
+    let by_date = ok(&["week", "--date", &today.format("%Y-%m-%d").to_string()]);
+    assert!(by_date.contains(&format!(
+        "day={} entries=2 tasks_due=0 habits_done=1 habits_scheduled=1",
+        today.format("%Y-%m-%d")
+    )));
+    assert!(!run(&["week", "--date", "next tuesday"]).status.success());
+}
+
+#[test]
+fn test_cli_track_access_sorts_and_finds_unread() {
+    let ledger_path = temp_ledger_path("ledger_cli_access");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_access");
+
+    let run = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+    let add = |body: &str| {
+        run(&["add", "journal", "--body", body, "--no-input"])
+            .lines()
+            .find_map(|line| line.strip_prefix("entry_id="))
+            .expect("entry id")
+            .to_string()
+    };
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let older = add("First idea");
+    let newer = add("Second idea");
+    add("Third idea");
+
+    // Views are ignored until tracking is turned on
+    run(&["show", &newer]);
+    assert!(run(&["stats"]).contains("unread=3\n"));
+
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str("\n[privacy]\ntrack_access = true\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    run(&["show", &older]);
+    let stats = run(&["stats"]);
+    assert!(stats.starts_with("entries=3\nrevisions=0\nviewed=1\nunread=2\n"));
+    assert!(stats.contains("type=journal entries=3 unread=2"));
+
+    let unread = run(&["stats", "--unread"]);
+    let lines: Vec<&str> = unread.lines().collect();
+    assert_eq!(lines[0], "count=2");
+    assert!(lines[1].starts_with(&newer), "{}", unread);
+    assert!(!unread.contains(&older));
+
+    let sorted = run(&["list", "--sort", "last-viewed"]);
+    assert!(sorted.starts_with(&older), "{}", sorted);
+
+    let json = run(&["list", "--sort", "last-viewed", "--json"]);
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert!(value[0]["last_viewed"].is_string());
+    assert!(value[1]["last_viewed"].is_null());
+}
+
+#[test]
+fn test_cli_api_stdin_json() {
+    let ledger_path = temp_ledger_path("ledger_cli_api");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_api");
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let call = |request: serde_json::Value| {
+        let mut cmd = Command::new(bin());
+        cmd.args(["api", "--stdin-json", "--ledger"])
+            .arg(&ledger_path)
+            .env("LEDGER_PASSPHRASE", passphrase)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        apply_xdg_env(&mut cmd, &config_home, &data_home);
+        let mut child = cmd.spawn().expect("spawn api");
+        child
+            .stdin
+            .take()
+            .expect("stdin")
+            .write_all(request.to_string().as_bytes())
+            .expect("write request");
+        let output = child.wait_with_output().expect("wait api");
+        let response: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("parse response");
+        (output.status.code(), response)
+    };
+
+    let (code, added) = call(serde_json::json!({
+        "op": "add",
+        "id": 1,
+        "params": {"body": "Coffee with Sam", "tags": ["friends"]},
+    }));
+    assert_eq!(code, Some(0));
+    assert_eq!(added["ok"], true);
+    assert_eq!(added["id"], 1);
+    assert_eq!(added["result"]["entry_type_name"], "journal");
+    let entry_id = added["result"]["id"].as_str().expect("id").to_string();
+
+    let (_, edited) = call(serde_json::json!({
+        "op": "edit",
+        "params": {"id": entry_id, "body": "Coffee with Sam and Alex"},
+    }));
+    assert_eq!(edited["result"]["supersedes"], entry_id.as_str());
+    assert_eq!(edited["result"]["tags"], serde_json::json!(["friends"]));
+
+    let (_, found) = call(serde_json::json!({
+        "op": "search",
+        "params": {"query": "Alex"},
+    }));
+    let found = found["result"].as_array().expect("entries");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0]["data"]["body"], "Coffee with Sam and Alex");
+
+    let (_, listed) = call(serde_json::json!({"op": "list", "params": {"history": true}}));
+    assert_eq!(listed["result"].as_array().expect("entries").len(), 2);
+
+    let (code, missing) = call(serde_json::json!({
+        "op": "show",
+        "id": "req-2",
+        "params": {"id": "00000000-0000-4000-8000-000000000000"},
+    }));
+    assert_eq!(code, Some(3));
+    assert_eq!(missing["ok"], false);
+    assert_eq!(missing["id"], "req-2");
+    assert_eq!(missing["error"]["code"], "not_found");
+
+    let (code, unknown) = call(serde_json::json!({"op": "delete"}));
+    assert_eq!(code, Some(4));
+    assert_eq!(unknown["error"]["code"], "invalid_input");
+}
+
+#[test]
+fn test_cli_org_export_and_import() {
+    let source_path = temp_ledger_path("ledger_cli_org_src");
+    let target_path = temp_ledger_path("ledger_cli_org_dst");
+    let org_path = std::env::temp_dir().join(format!("ledger_cli_org_{}.org", std::process::id()));
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_org");
+
+    let run =
+        |args: &[&str], ledger: &PathBuf| run_ledger_ok(ledger, (&config_home, &data_home), args);
+
+    for path in [&source_path, &target_path] {
+        let mut init = Command::new(bin());
+        init.arg("init")
+            .arg(path)
+            .arg("--no-input")
+            .env("LEDGER_PASSPHRASE", passphrase);
+        apply_xdg_env(&mut init, &config_home, &data_home);
+        assert!(init.output().expect("run init").status.success());
+    }
+
+    run(
+        &[
+            "add",
+            "journal",
+            "--body",
+            "Walked home\n* not a headline",
+            "--tag",
+            "walk",
+        ],
+        &source_path,
+    );
+    let org = run(&["export", "--format", "org"], &source_path);
+    assert!(org.contains(":PROPERTIES:\n:ID: "));
+    assert!(org.contains(":TYPE: journal\n:TAGS: walk\n"));
+    assert!(org.contains("\n,* not a headline\n"));
+
+    // A fresh ledger keeps the ids, so importing twice adds nothing
+    std::fs::write(&org_path, &org).expect("write org");
+    let org_file = org_path.to_str().expect("path");
+    let first = run(&["import", "--from", "org", org_file], &target_path);
+    assert!(first.contains("entries=1"), "{}", first);
+    let again = run(&["import", "--from", "org", org_file], &target_path);
+    assert!(again.contains("entries=0"), "{}", again);
+    assert!(again.contains("skipped=1"), "{}", again);
+
+    // Edits become revisions; a headline without an id is a new entry
+    let edited =
+        org.replace("Walked home", "Walked home in the rain") + "\n* Idea\nWrite it down.\n";
+    std::fs::write(&org_path, edited).expect("write org");
+    let summary = run(&["import", "--from", "org", org_file], &source_path);
+    assert!(summary.contains("entries=2"), "{}", summary);
+
+    let json = run(&["list", "--json"], &source_path);
+    let entries: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    let bodies: Vec<&str> = entries
+        .as_array()
+        .expect("entries")
+        .iter()
+        .filter_map(|entry| entry["data"]["body"].as_str())
+        .collect();
+    assert!(bodies.contains(&"Walked home in the rain\n* not a headline"));
+    assert!(bodies.contains(&"Write it down."));
+    assert_eq!(bodies.len(), 2);
+}
+
+#[test]
+fn test_cli_export_profile_applies_settings_and_redaction() {
+    let ledger_path = temp_ledger_path("ledger_cli_export_profile");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_export_profile");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+    let ok = |args: &[&str]| run_ledger_ok(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str(
+        "\n[export.profiles.blog]\nformat = \"markdown\"\ntag = \"public\"\nredact = [\"private\"]\n",
+    );
+    std::fs::write(&config_path, config).expect("write config");
+
+    for (body, tags) in [
+        ("Launch day", vec!["public"]),
+        ("Therapy notes", vec!["public", "private/health"]),
+        ("Grocery list", vec!["errands"]),
+    ] {
+        let mut args = vec!["add", "journal", "--body", body, "--no-input"];
+        for tag in tags {
+            args.extend(["--tag", tag]);
+        }
+        ok(&args);
+    }
+
+    let blog = ok(&["export", "--profile", "blog"]);
+    assert!(blog.starts_with("# Ledger export"), "{}", blog);
+    assert!(blog.contains("Launch day"), "{}", blog);
+    assert!(!blog.contains("Therapy notes"), "{}", blog);
+    assert!(!blog.contains("Grocery list"), "{}", blog);
+
+    // Flags override the profile, but its redaction still applies
+    let json = ok(&[
+        "export",
+        "--profile",
+        "blog",
+        "--format",
+        "json",
+        "--tag",
+        "errands",
+    ]);
+    let entries: serde_json::Value = serde_json::from_str(&json).expect("json");
+    assert_eq!(entries.as_array().expect("entries").len(), 1);
+    let everything = ok(&["export", "--profile", "blog", "--format", "jsonl"]);
+    assert_eq!(everything.lines().count(), 1);
+
+    let redacted = ok(&["export", "--format", "jsonl", "--redact", "private"]);
+    assert_eq!(redacted.lines().count(), 2);
+
+    let output = run(&["export", "--profile", "zine"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[export.profiles.zine]"));
+}
+
+#[test]
+fn test_cli_export_encrypt_output() {
+    let ledger_path = temp_ledger_path("ledger_cli_export_encrypted");
+    let export_path = std::env::temp_dir().join(format!(
+        "ledger_cli_export_encrypted_{}.age",
+        std::process::id()
+    ));
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_export_enc");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+    assert!(run(&["add", "journal", "--body", "Private thoughts"])
+        .status
+        .success());
+
+    let out = export_path.to_str().expect("path");
+    let output = run(&[
+        "export",
+        "--format",
+        "jsonl",
+        "--out",
+        out,
+        "--encrypt-output",
+    ]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let sealed = std::fs::read(&export_path).expect("read export");
+    assert!(!String::from_utf8_lossy(&sealed).contains("Private thoughts"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&export_path)
+            .expect("stat export")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    let plaintext =
+        ledger_core::storage::encryption::decrypt(&sealed, passphrase).expect("decrypt export");
+    assert!(String::from_utf8_lossy(&plaintext).contains("Private thoughts"));
+
+    // Encrypting stdout is refused
+    let output = run(&["export", "--encrypt-output"]);
+    assert!(!output.status.success());
+    let _ = std::fs::remove_file(&export_path);
+}
+
+#[test]
+fn test_cli_scratch_edit_show_and_promote() {
+    let ledger_path = temp_ledger_path("ledger_cli_scratch");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_scratch");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    // Promoting an empty scratchpad fails
+    assert!(!run(&["scratch", "promote"]).status.success());
+
+    let output = run(&[
+        "scratch",
+        "edit",
+        "--body",
+        "Call the plumber",
+        "--no-input",
+    ]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let output = run(&[
+        "scratch",
+        "edit",
+        "--append",
+        "--body",
+        "and the bank",
+        "--no-input",
+    ]);
+    assert!(output.status.success());
+
+    let output = run(&["scratch", "show", "--json"]);
+    let shown: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(shown["text"], "Call the plumber\nand the bank");
+
+    let output = run(&["scratch", "promote", "--tag", "todo", "--json"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let receipt: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    let entry_id = receipt["id"].as_str().expect("entry id").to_string();
+
+    let output = run(&["show", &entry_id, "--json"]);
+    let entry: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(entry["data"]["body"], "Call the plumber\nand the bank");
+    assert_eq!(entry["tags"], serde_json::json!(["todo"]));
+
+    // The scratchpad is emptied once promoted
+    let output = run(&["scratch", "show", "--json"]);
+    let shown: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert!(shown["text"].is_null());
+
+    let _ = std::fs::remove_file(&ledger_path);
+}
+
+#[test]
+fn test_cli_backfill_fills_missing_days() {
+    let ledger_path = temp_ledger_path("ledger_cli_backfill");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_backfill");
+
+    let run = |args: &[&str]| {
+        ledger_command(&ledger_path, (&config_home, &data_home), args)
+            .env("TZ", "UTC")
+            .output()
+            .expect("run ledger")
+    };
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    assert!(run(&[
+        "add",
+        "journal",
+        "--body",
+        "Back home",
+        "--date",
+        "2024-05-02T18:00:00Z",
+        "--no-input",
+    ])
+    .status
+    .success());
+    assert!(run(&[
+        "templates",
+        "create",
+        "daily-journal",
+        "--entry-type",
+        "journal",
+        "--defaults",
+        r#"{"body": "Away"}"#,
+    ])
+    .status
+    .success());
+
+    // A template without a body default can't fill days unattended
+    let output = run(&["backfill", "--date", "2024-05-01..2024-05-03", "--no-input"]);
+    assert!(!output.status.success());
+
+    let output = run(&[
+        "backfill",
+        "--date",
+        "2024-05-01..2024-05-03",
+        "--template",
+        "daily-journal",
+        "--tag",
+        "vacation",
+        "--no-input",
+        "--json",
+    ]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    let statuses: Vec<&str> = summary["days"]
+        .as_array()
+        .expect("days")
+        .iter()
+        .map(|day| day["status"].as_str().expect("status"))
+        .collect();
+    assert_eq!(statuses, vec!["added", "present", "added"]);
+
+    let output = run(&["list", "--since", "2024-05-01T00:00:00Z", "--json"]);
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    let entries = entries.as_array().expect("entries");
+    assert_eq!(entries.len(), 3);
+    let backfilled: Vec<_> = entries
+        .iter()
+        .filter(|entry| entry["data"]["body"] == "Away")
+        .collect();
+    assert_eq!(backfilled.len(), 2);
+    assert!(backfilled
+        .iter()
+        .all(|entry| entry["tags"] == serde_json::json!(["vacation"])));
+
+    // Running it again finds nothing missing
+    let output = run(&[
+        "backfill",
+        "--date",
+        "2024-05-01..2024-05-03",
+        "--template",
+        "daily-journal",
+        "--no-input",
+        "--json",
+    ]);
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert!(summary["days"]
+        .as_array()
+        .expect("days")
+        .iter()
+        .all(|day| day["status"] == "present"));
+
+    assert!(!run(&["backfill", "--date", "2024-05-03..2024-05-01"])
+        .status
+        .success());
+
+    let _ = std::fs::remove_file(&ledger_path);
+}
+
+#[test]
+fn test_cli_entry_origin_show_verbose_and_filter() {
+    let ledger_path = temp_ledger_path("ledger_cli_origin");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_origin");
+
+    let run = |args: &[&str], stdin: Option<&str>| {
+        let mut child = ledger_command(&ledger_path, (&config_home, &data_home), args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .expect("spawn ledger");
+        let mut pipe = child.stdin.take().expect("stdin");
+        if let Some(input) = stdin {
+            pipe.write_all(input.as_bytes()).expect("write stdin");
+        }
+        drop(pipe);
+        child.wait_with_output().expect("run ledger")
+    };
+
+    let mut init = Command::new(bin());
+    init.arg("init")
+        .arg(&ledger_path)
+        .arg("--no-input")
+        .env("LEDGER_PASSPHRASE", passphrase);
+    apply_xdg_env(&mut init, &config_home, &data_home);
+    assert!(init.output().expect("run init").status.success());
+
+    let output = run(
+        &["add", "journal", "--body", "Typed by hand", "--json"],
+        None,
+    );
+    let receipt: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    let cli_id = receipt["id"].as_str().expect("id").to_string();
+    let output = run(
+        &["api", "--stdin-json"],
+        Some(r#"{"op": "add", "params": {"body": "From the plugin"}, "app": "vim-ledger"}"#),
+    );
+    let response: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    let plugin_id = response["result"]["id"].as_str().expect("id").to_string();
+
+    let output = run(&["show", &cli_id, "--verbose", "--json"], None);
+    let shown: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(shown["origin"]["app"], "ledger-cli");
+    assert_eq!(shown["origin"]["command"], "add");
+    assert!(shown["origin"]["version"].is_string());
+
+    // Without --verbose the origin stays out of the way
+    let output = run(&["show", &cli_id, "--json"], None);
+    let shown: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert!(shown.get("origin").is_none());
+
+    let output = run(&["show", &plugin_id, "--verbose"], None);
+    let plain = String::from_utf8_lossy(&output.stdout);
+    assert!(plain.contains("origin_app=vim-ledger"));
+    assert!(plain.contains("origin_command=api add"));
+
+    let output = run(&["list", "--origin", "vim-ledger", "--json"], None);
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    let ids: Vec<&str> = listed
+        .as_array()
+        .expect("entries")
+        .iter()
+        .map(|entry| entry["id"].as_str().expect("id"))
+        .collect();
+    assert_eq!(ids, vec![plugin_id.as_str()]);
+
+    let _ = std::fs::remove_file(&ledger_path);
+}
+
+#[test]
+fn test_cli_config_version_checked() {
+    let ledger_path = temp_ledger_path("ledger_cli_config_version");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_config_version");
+
+    let run = |args: &[&str]| run_ledger(&ledger_path, (&config_home, &data_home), args);
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let current = std::fs::read_to_string(&config_path).expect("read config");
+    let legacy = current.replace("config_version = 1\n\n", "");
+    std::fs::write(&config_path, &legacy).expect("write config");
+
+    // A dry run reports the migration of a versionless config without writing
+    let output = run(&["--dry-run", "list"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Would migrate config"), "{}", stderr);
+    assert!(stderr.contains("from version 0 to 1"));
+    assert_eq!(
+        std::fs::read_to_string(&config_path).expect("read config"),
+        legacy
+    );
+    let leftovers = std::fs::read_dir(config_home.join("ledger"))
+        .expect("read config dir")
+        .filter(|entry| {
+            let name = entry.as_ref().expect("entry").file_name();
+            name.to_string_lossy().ends_with(".bak")
+        })
+        .count();
+    assert_eq!(leftovers, 0);
+
+    // A real run keeps the old file as a backup and stamps the version
+    let output = run(&["list"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Migrated config"), "{}", stderr);
+    assert!(!stderr.contains("Warning"));
+    let backup = stderr
+        .split("(backup: ")
+        .nth(1)
+        .and_then(|rest| rest.split(')').next())
+        .expect("backup path");
+    assert_eq!(
+        std::fs::read_to_string(backup).expect("read backup"),
+        legacy
+    );
+    let migrated = std::fs::read_to_string(&config_path).expect("read config");
+    assert!(migrated.contains("config_version = 1"));
+
+    // Once current, the file is left alone
+    let output = run(&["list"]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("igrate"));
+    assert_eq!(
+        std::fs::read_to_string(&config_path).expect("read config"),
+        migrated
+    );
+
+    std::fs::write(&config_path, format!("config_version = 99\n{}", legacy)).expect("write config");
+    let output = run(&["list"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("config_version 99, newer than this ledger supports"));
+
+    let output = run(&["--strict", "list"]);
+    assert_eq!(output.status.code(), Some(7));
+}
+
+#[test]
+fn test_cli_adopt_writes_config_for_existing_ledger() {
+    let ledger_path = temp_ledger_path("ledger_cli_adopt");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_adopt");
+    let config_path = config_home.join("ledger").join("config.toml");
+
+    let run = |args: &[&str], unlock: &str| {
+        let mut cmd = Command::new(bin());
+        cmd.args(args).env("LEDGER_PASSPHRASE", unlock);
+        apply_xdg_env(&mut cmd, &config_home, &data_home);
+        cmd.output().expect("run ledger")
+    };
+    let path = ledger_path.to_str().unwrap();
+
+    let output = run(&["adopt", path], passphrase);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("ledger init"));
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    let output = run(&["adopt", path, "--no-input"], "wrong-passphrase");
+    assert_eq!(output.status.code(), Some(5));
+    assert!(!config_path.exists());
+
+    let output = run(&["adopt", path, "--no-input", "--json"], passphrase);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let adopted: serde_json::Value = serde_json::from_slice(&output.stdout).expect("parse adopt");
+    assert_eq!(adopted["tier"], "passphrase");
+    assert_eq!(adopted["entries"], 0);
+    let config = std::fs::read_to_string(&config_path).expect("read config");
+    assert!(config.contains("tier = \"passphrase\""));
+
+    // The config now resolves the ledger without --ledger
+    let output = run(
+        &["add", "journal", "--body", "Restored", "--no-input"],
+        passphrase,
+    );
+    assert!(output.status.success());
+    let output = run(&["list", "--json"], passphrase);
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).expect("parse list");
+    assert_eq!(entries.as_array().map(Vec::len), Some(1));
+
+    let output = run(&["adopt", path, "--no-input"], passphrase);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+    let output = run(
+        &["adopt", path, "--no-input", "--force", "--timezone", "UTC"],
+        passphrase,
+    );
+    assert!(output.status.success());
+    let config = std::fs::read_to_string(&config_path).expect("read config");
+    assert!(config.contains("timezone = \"UTC\""));
+}
+
+#[test]
+fn test_cli_check_healthcheck_reports_backup_age() {
+    let ledger_path = temp_ledger_path("ledger_cli_healthcheck");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_healthcheck");
+
+    let run = |args: &[&str], unlock: Option<&str>| {
+        let mut cmd = ledger_command(&ledger_path, (&config_home, &data_home), args);
+        match unlock {
+            Some(value) => cmd.env("LEDGER_PASSPHRASE", value),
+            None => cmd.env_remove("LEDGER_PASSPHRASE"),
+        };
+        cmd.output().expect("run ledger")
+    };
+    let stdout =
+        |output: &std::process::Output| String::from_utf8_lossy(&output.stdout).to_string();
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+    let config_path = config_home.join("ledger").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).expect("read config");
+    config.push_str("\n[backup]\nmax_age_hours = 24\n");
+    std::fs::write(&config_path, config).expect("write config");
+
+    let health = ["--quiet", "check", "--healthcheck"];
+    let output = run(&health, Some(passphrase));
+    assert_eq!(output.status.code(), Some(8));
+    let line = stdout(&output);
+    assert_eq!(line.lines().count(), 1);
+    assert!(line.starts_with("status=failed reason=no_backup integrity=ok last_backup=none"));
+
+    let dest = ledger_path.with_extension("bak");
+    let output = run(&["backup", dest.to_str().unwrap()], Some(passphrase));
+    assert!(output.status.success());
+    let output = run(&health, Some(passphrase));
+    assert!(output.status.success(), "{}", stdout(&output));
+    let line = stdout(&output);
+    assert!(line.starts_with("status=ok integrity=ok last_backup="));
+    assert!(line.contains("backup_age_hours=0 max_age_hours=24"));
+
+    // Never prompts: without cached credentials it fails at once
+    let output = run(&health, None);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output).trim(), "status=failed reason=unlock");
+
+    let mut record_path = ledger_path.as_os_str().to_os_string();
+    record_path.push(".backup");
+    std::fs::write(
+        &record_path,
+        r#"{"at":"2020-01-01T00:00:00Z","destination":"/old","bytes":1}"#,
+    )
+    .expect("write backup record");
+    let output = run(&["check", "--healthcheck", "--json"], Some(passphrase));
+    assert_eq!(output.status.code(), Some(8));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("parse json");
+    assert_eq!(report["reason"], "stale_backup");
+    assert_eq!(report["max_age_hours"], 24);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("ledger backup"));
+}
+
+#[test]
+fn test_cli_launchd_install_writes_plists() {
+    let ledger_path = temp_ledger_path("ledger_cli_launchd");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_launchd");
+    let agent_dir = data_home.join("agents");
+    let home = data_home.join("home");
+    // apply_xdg_env points TMPDIR here, as on macOS
+    let tmpdir = data_home.parent().unwrap().join("runtime");
+
+    let run = |args: &[&str]| {
+        ledger_command(&ledger_path, (&config_home, &data_home), args)
+            .env("HOME", &home)
+            .env_remove("LEDGER_PASSPHRASE")
+            .output()
+            .expect("run ledger")
+    };
+    let dir = agent_dir.to_str().unwrap();
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 0);
+
+    let output = run(&[
+        "launchd",
+        "install",
+        "--dir",
+        dir,
+        "--no-load",
+        "--backup-hour",
+        "4",
+    ]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut names: Vec<String> = std::fs::read_dir(&agent_dir)
+        .expect("read agent dir")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    // Caching is off, so there is no cache agent
+    assert_eq!(
+        names,
+        vec![
+            "local.ledger.backup.plist",
+            "local.ledger.healthcheck.plist"
+        ]
+    );
+    let backup = std::fs::read_to_string(agent_dir.join("local.ledger.backup.plist")).unwrap();
+    assert!(backup.contains("<key>Hour</key>\n\t\t<integer>4</integer>"));
+    assert!(backup.contains(&format!(
+        "<key>TMPDIR</key>\n\t\t<string>{}</string>",
+        tmpdir.display()
+    )));
+    assert!(home.join("Library/Logs/ledger").is_dir());
+
+    let output = run(&["launchd", "install", "--dir", dir, "--backup-hour", "24"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_systemd_install_writes_units() {
+    let ledger_path = temp_ledger_path("ledger_cli_systemd");
+    let passphrase = "test-passphrase-secure-123";
+    let (config_home, data_home) = temp_xdg_dirs("ledger_cli_systemd");
+    let unit_dir = data_home.join("units");
+
+    let run = |args: &[&str]| {
+        ledger_command(&ledger_path, (&config_home, &data_home), args)
+            .env_remove("LEDGER_PASSPHRASE")
+            .output()
+            .expect("run ledger")
+    };
+    let dir = unit_dir.to_str().unwrap();
+
+    create_ledger_with_passphrase(&ledger_path, passphrase);
+    write_config_file(&config_home, &ledger_path, "passphrase", "none", None, 300);
+
+    let output = run(&["--dry-run", "systemd", "install", "--user", "--dir", dir]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("[Timer]"));
+    assert!(!unit_dir.exists());
+
+    let output = run(&["systemd", "install", "--user", "--dir", dir, "--no-enable"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut names: Vec<String> = std::fs::read_dir(&unit_dir)
+        .expect("read unit dir")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            "ledger-backup.service",
+            "ledger-backup.timer",
+            "ledger-cache.service",
+            "ledger-healthcheck.service",
+            "ledger-healthcheck.timer",
+        ]
+    );
+    let backup = std::fs::read_to_string(unit_dir.join("ledger-backup.service")).unwrap();
+    let ledger = ledger_path.canonicalize().unwrap();
+    assert!(backup.contains(&format!("--ledger {} backup", ledger.display())));
+    assert!(backup.contains("LEDGER_CONFIG="));
+    let cache = std::fs::read_to_string(unit_dir.join("ledger-cache.service")).unwrap();
+    assert!(cache.contains("internal-cache-daemon --ttl 300"));
+    assert!(cache.contains("--keep-alive"));
+
+    // Rerunning replaces generated units, but never hand-written ones
+    let output = run(&["systemd", "install", "--user", "--dir", dir, "--no-enable"]);
+    assert!(output.status.success());
+    std::fs::write(unit_dir.join("ledger-backup.timer"), "[Timer]\n").unwrap();
+    let output = run(&["systemd", "install", "--user", "--dir", dir, "--no-enable"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+}
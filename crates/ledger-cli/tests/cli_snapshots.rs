@@ -0,0 +1,277 @@
+//! Snapshot tests for CLI output.
+//!
+//! Each case runs the CLI against the same small ledger with the environment
+//! pinned (width, color, timezone, locale) and compares stdout with
+//! `tests/snapshots/<name>.snap`. Ids generated at runtime are replaced by
+//! stable placeholders of the same length, so table alignment is kept.
+//!
+//! After an intended formatting change, rewrite the snapshots with
+//! `UPDATE_SNAPSHOTS=1 cargo test --test cli_snapshots` and review the diff.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ledger_core::storage::{AgeSqliteStorage, StorageEngine};
+
+const PASSPHRASE: &str = "test-passphrase-secure-123";
+
+/// Widths every table snapshot is taken at.
+const WIDTHS: [usize; 3] = [60, 100, 140];
+
+fn bin() -> PathBuf {
+    PathBuf::from(env!("CARGO_BIN_EXE_ledger"))
+}
+
+fn snapshot_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+}
+
+/// A ledger with fixed entries, and the ids it was given mapped to
+/// placeholders in creation order.
+struct Fixture {
+    ledger_path: PathBuf,
+    config_home: PathBuf,
+    data_home: PathBuf,
+    ids: Vec<(String, String)>,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time")
+            .as_nanos();
+        // Short base to stay under the Unix socket path limit
+        let base = std::env::temp_dir().join(format!("lsnap_{}", nanos % 1_000_000_000));
+        let config_home = base.join("c");
+        let data_home = base.join("d");
+        std::fs::create_dir_all(config_home.join("ledger")).expect("create config dir");
+        std::fs::create_dir_all(&data_home).expect("create data dir");
+        std::fs::create_dir_all(base.join("runtime")).expect("create runtime dir");
+        let ledger_path = base.join("snap.ledger");
+        AgeSqliteStorage::create(&ledger_path, PASSPHRASE).expect("create ledger");
+        std::fs::write(
+            config_home.join("ledger").join("config.toml"),
+            format!(
+                "config_version = 1\n\n[ledger]\npath = \"{}\"\n\n[security]\ntier = \"passphrase\"\n\
+                 passphrase_cache_ttl_seconds = 0\n\n[keychain]\nenabled = false\n\n\
+                 [keyfile]\nmode = \"none\"\n\n[ui]\ntimezone = \"UTC\"\nlocale = \"en-US\"\n",
+                ledger_path.display()
+            ),
+        )
+        .expect("write config");
+
+        let mut fixture = Self {
+            ledger_path,
+            config_home,
+            data_home,
+            ids: Vec::new(),
+        };
+        for (date, tags, body) in [
+            (
+                "2024-03-04T09:00:00Z",
+                &["work"][..],
+                "Planned the quarter with the team",
+            ),
+            (
+                "2024-03-05T18:30:00Z",
+                &["home", "garden"][..],
+                "Repotted the tomatoes and watered the herbs before the rain came in",
+            ),
+            (
+                "2024-03-06T07:15:00Z",
+                &[][..],
+                "Morning run along the river",
+            ),
+        ] {
+            let mut args = vec!["add", "journal", "--body", body, "--date", date];
+            for tag in tags {
+                args.extend(["--tag", tag]);
+            }
+            fixture.run_raw(&args, 80);
+        }
+
+        let list = fixture.run_raw(&["list", "--json"], 80);
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&list).expect("parse list");
+        let mut created: Vec<(String, String)> = entries
+            .iter()
+            .map(|e| {
+                (
+                    e["created_at"].as_str().expect("created_at").to_string(),
+                    e["id"].as_str().expect("id").to_string(),
+                )
+            })
+            .collect();
+        created.sort();
+        fixture.ids = created
+            .into_iter()
+            .enumerate()
+            .map(|(n, (_, id))| {
+                (
+                    id,
+                    format!("0000000{}-0000-4000-8000-00000000000{}", n + 1, n + 1),
+                )
+            })
+            .collect();
+        fixture
+    }
+
+    /// Id of the `n`th entry created (from 1).
+    fn id(&self, n: usize) -> &str {
+        &self.ids[n - 1].0
+    }
+
+    /// Run the CLI at terminal width `width` and return redacted stdout.
+    fn run(&self, args: &[&str], width: usize) -> String {
+        self.redact(&self.run_raw(args, width))
+    }
+
+    fn run_raw(&self, args: &[&str], width: usize) -> String {
+        let runtime = self.data_home.parent().unwrap().join("runtime");
+        let output = Command::new(bin())
+            .args(args)
+            .arg("--ledger")
+            .arg(&self.ledger_path)
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", self.data_home.parent().unwrap())
+            .env("XDG_CONFIG_HOME", &self.config_home)
+            .env("XDG_DATA_HOME", &self.data_home)
+            .env("XDG_RUNTIME_DIR", &runtime)
+            .env("TMPDIR", &runtime)
+            .env("LEDGER_PASSPHRASE", PASSPHRASE)
+            .env("COLUMNS", width.to_string())
+            .env("NO_COLOR", "1")
+            .env("TERM", "xterm")
+            .env("TZ", "UTC")
+            .env("LANG", "C")
+            .output()
+            .expect("run ledger");
+        assert!(
+            output.status.success(),
+            "ledger {}: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).expect("utf-8 stdout")
+    }
+
+    fn redact(&self, output: &str) -> String {
+        let mut out = output.replace(&self.ledger_path.display().to_string(), "<ledger>");
+        for (id, placeholder) in &self.ids {
+            out = out
+                .replace(id, placeholder)
+                .replace(&id[..8], &placeholder[..8]);
+        }
+        // Device ids and anything else generated per run
+        let mut redacted = String::with_capacity(out.len());
+        let mut rest = out.as_str();
+        while let Some(at) = rest
+            .char_indices()
+            .map(|(i, _)| i)
+            .find(|&i| is_uuid(&rest[i..]))
+        {
+            redacted.push_str(&rest[..at]);
+            redacted.push_str("ffffffff-ffff-4fff-8fff-ffffffffffff");
+            rest = &rest[at + 36..];
+        }
+        redacted.push_str(rest);
+        redacted
+            .lines()
+            .map(|line| match line.trim_start().strip_prefix("Path: ") {
+                Some(_) => "Path: <ledger>",
+                None => line,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        if let Some(base) = self.ledger_path.parent() {
+            let _ = std::fs::remove_dir_all(base);
+        }
+    }
+}
+
+/// Whether `text` starts with a hyphenated UUID that isn't a placeholder.
+fn is_uuid(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    if bytes.len() < 36 || text.starts_with("0000000") || text.starts_with("ffffffff-") {
+        return false;
+    }
+    bytes[..36].iter().enumerate().all(|(i, b)| match i {
+        8 | 13 | 18 | 23 => *b == b'-',
+        _ => b.is_ascii_hexdigit(),
+    })
+}
+
+/// Compare `actual` with the stored snapshot, or store it when
+/// `UPDATE_SNAPSHOTS` is set or none exists yet.
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = snapshot_dir().join(format!("{}.snap", name));
+    let actual = format!("{}\n", actual.trim_end());
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() || !path.exists() {
+        std::fs::create_dir_all(snapshot_dir()).expect("create snapshot dir");
+        std::fs::write(&path, &actual).expect("write snapshot");
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).expect("read snapshot");
+    if expected != actual {
+        let diff: Vec<String> = expected
+            .lines()
+            .zip(actual.lines().chain(std::iter::repeat("<missing>")))
+            .enumerate()
+            .filter(|(_, (e, a))| e != a)
+            .take(5)
+            .map(|(n, (e, a))| format!("line {}:\n  - {}\n  + {}", n + 1, e, a))
+            .collect();
+        panic!(
+            "snapshot {} differs:\n{}\n--- actual ---\n{}\n\
+             Run with UPDATE_SNAPSHOTS=1 if the change is intended.",
+            name,
+            diff.join("\n"),
+            actual
+        );
+    }
+}
+
+#[test]
+fn test_snapshots_list_and_search() {
+    let fixture = Fixture::new();
+
+    for width in WIDTHS {
+        assert_snapshot(
+            &format!("list_table_{}", width),
+            &fixture.run(&["list", "--format", "table"], width),
+        );
+        assert_snapshot(
+            &format!("search_table_{}", width),
+            &fixture.run(&["search", "the", "--format", "table"], width),
+        );
+    }
+    assert_snapshot(
+        "list_table_ascii",
+        &fixture.run(&["list", "--format", "table", "--ascii"], 80),
+    );
+    assert_snapshot("list_plain", &fixture.run(&["list"], 80));
+    assert_snapshot("list_json", &fixture.run(&["list", "--json"], 80));
+    assert_snapshot("search_plain", &fixture.run(&["search", "the"], 80));
+    assert_snapshot(
+        "search_json",
+        &fixture.run(&["search", "the", "--json"], 80),
+    );
+}
+
+#[test]
+fn test_snapshots_show() {
+    let fixture = Fixture::new();
+    let id = fixture.id(2).to_string();
+
+    assert_snapshot("show_plain", &fixture.run(&["show", &id], 80));
+    assert_snapshot("show_json", &fixture.run(&["show", &id, "--json"], 80));
+}
@@ -21,6 +21,19 @@ pub enum LedgerError {
     #[error("Ledger file not found")]
     LedgerNotFound,
 
+    /// Encrypted data is damaged: an unreadable age header, a bad MAC or a
+    /// truncated payload
+    #[error("Encryption error: {0}")]
+    Corrupted(String),
+
+    /// The ledger was written by a newer format than this build reads
+    #[error("Unsupported ledger format {found} (this build reads up to {supported})")]
+    VersionMismatch { found: String, supported: String },
+
+    /// Another process holds the ledger's write lock
+    #[error("Storage error: Write lock failed: {0}")]
+    LockHeld(String),
+
     /// Encryption or decryption error
     #[error("Encryption error: {0}")]
     Crypto(String),
@@ -33,6 +46,11 @@ pub enum LedgerError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    /// Entry data does not fit the schema of its type; `field` names the
+    /// offending field
+    #[error("Validation error: {message}")]
+    SchemaViolation { field: String, message: String },
+
     /// Storage backend error (generic)
     #[error("Storage error: {0}")]
     Storage(String),
@@ -52,6 +70,14 @@ pub enum LedgerError {
     #[error("Entry type not found: {0}")]
     EntryTypeNotFound(String),
 
+    /// Template not found by ID
+    #[error("Not found: Template {0} not found")]
+    TemplateNotFound(Uuid),
+
+    /// Composition not found by ID
+    #[error("Not found: Composition {0} not found")]
+    CompositionNotFound(Uuid),
+
     /// Generic resource not found
     #[error("Not found: {0}")]
     NotFound(String),
@@ -1,8 +1,10 @@
-//! Filesystem utilities for atomic operations.
+//! Filesystem utilities for atomic operations and ledger location checks.
 
+use std::fmt;
 use std::fs;
-use std::io;
-use std::path::Path;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 /// Atomically rename a file, with fallback for platforms where rename fails if target exists.
 ///
@@ -33,6 +35,257 @@ pub fn rename_with_fallback(temp_path: &Path, destination: &Path) -> io::Result<
     Ok(())
 }
 
+/// Filesystem types where atomic rename and locking are not reliable across clients.
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb",
+    "smb2",
+    "smb3",
+    "smbfs",
+    "afs",
+    "9p",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "davfs",
+    "fuse.davfs2",
+    "fuse.sshfs",
+    "fuse.rclone",
+    "fuse.s3fs",
+];
+
+/// Folder names created by file-sync clients.
+const SYNCED_FOLDERS: &[&str] = &[
+    "Dropbox",
+    "OneDrive",
+    "Google Drive",
+    "iCloud Drive",
+    "Mobile Documents",
+    "Nextcloud",
+    "ownCloud",
+    "pCloud Drive",
+];
+
+/// Marker files that sync clients leave in the folders they manage.
+const SYNC_MARKERS: &[(&str, &str)] = &[(".dropbox", "Dropbox"), (".stfolder", "Syncthing")];
+
+/// Lockfiles older than this are assumed to be left over from a crash.
+const STALE_LOCK_SECS: u64 = 60;
+
+/// Something about a ledger's location that makes writes less safe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathRisk {
+    /// The ledger path is a symlink; writes go to the target.
+    Symlink(PathBuf),
+    /// The ledger lives on a network filesystem of the given type.
+    NetworkFilesystem(String),
+    /// The ledger lives in a folder managed by the named sync client.
+    SyncedFolder(String),
+}
+
+impl PathRisk {
+    /// Whether another machine or a sync client may touch the file mid-write.
+    pub fn is_concurrent_write_risk(&self) -> bool {
+        !matches!(self, PathRisk::Symlink(_))
+    }
+}
+
+impl fmt::Display for PathRisk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathRisk::Symlink(target) => write!(
+                f,
+                "Ledger path is a symlink to {}; writes go to the target",
+                target.display()
+            ),
+            PathRisk::NetworkFilesystem(fs_type) => write!(
+                f,
+                "Ledger is on a network filesystem ({}); writes from two machines at once can corrupt it",
+                fs_type
+            ),
+            PathRisk::SyncedFolder(client) => write!(
+                f,
+                "Ledger is in a {} folder; the sync client can upload a half-written file or create conflicting copies",
+                client
+            ),
+        }
+    }
+}
+
+/// Inspect a ledger path for symlinks, network filesystems, and sync folders.
+///
+/// Network filesystems are detected from `/proc/self/mounts`, so only on Linux.
+pub fn path_risks(path: &Path) -> Vec<PathRisk> {
+    let mut risks = Vec::new();
+    if fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink()) {
+        risks.push(PathRisk::Symlink(
+            fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()),
+        ));
+    }
+
+    let real = write_target(path);
+    let dir = real
+        .parent()
+        .and_then(|parent| fs::canonicalize(parent).ok())
+        .unwrap_or_else(|| real.clone());
+
+    if let Ok(mounts) = fs::read_to_string("/proc/self/mounts") {
+        if let Some(fs_type) = filesystem_type(&mounts, &dir) {
+            if NETWORK_FILESYSTEMS.contains(&fs_type.as_str()) {
+                risks.push(PathRisk::NetworkFilesystem(fs_type));
+            }
+        }
+    }
+
+    if let Some(client) = synced_folder(&dir) {
+        risks.push(PathRisk::SyncedFolder(client));
+    }
+    risks
+}
+
+/// The file a write to `path` should replace: the symlink target if `path` is a symlink.
+///
+/// Renaming a temp file over a symlink would replace the link with a regular
+/// file and leave the real ledger stale.
+pub fn write_target(path: &Path) -> PathBuf {
+    if fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink()) {
+        if let Ok(target) = fs::canonicalize(path) {
+            return target;
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Flush a directory entry so a completed rename survives a crash.
+pub fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        fs::File::open(dir)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Exclusive `<ledger>.lock` file held while a ledger is being written.
+///
+/// Removed on drop. A lock older than a minute is taken over, since writes
+/// finish in well under that.
+#[derive(Debug)]
+pub struct WriteLock {
+    path: PathBuf,
+}
+
+impl WriteLock {
+    /// Create the lockfile next to `ledger_path`, failing if another writer holds it.
+    pub fn acquire(ledger_path: &Path) -> io::Result<Self> {
+        let mut name = ledger_path.as_os_str().to_os_string();
+        name.push(".lock");
+        let path = PathBuf::from(name);
+
+        for _ in 0..2 {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    let _ = writeln!(file, "{}", std::process::id());
+                    let _ = file.sync_all();
+                    return Ok(Self { path });
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|meta| meta.modified())
+                        .ok()
+                        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                        .is_some_and(|age| age.as_secs() > STALE_LOCK_SECS);
+                    if !stale {
+                        break;
+                    }
+                    let _ = fs::remove_file(&path);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::WouldBlock,
+            format!(
+                "Another process is writing this ledger (lockfile {}); remove it if no ledger command is running",
+                path.display()
+            ),
+        ))
+    }
+}
+
+impl Drop for WriteLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Filesystem type of the longest mount point containing `dir`, from a
+/// `/proc/self/mounts`-style listing.
+fn filesystem_type(mounts: &str, dir: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = unescape_mount_field(fields.next()?);
+            let fs_type = fields.next()?;
+            Some((PathBuf::from(mount_point), fs_type))
+        })
+        .filter(|(mount_point, _)| dir.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, fs_type)| fs_type.to_string())
+}
+
+/// Undo the octal escapes (`\040` for space, ...) used in mount listings.
+fn unescape_mount_field(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(pos) = rest.find('\\') {
+        out.push_str(&rest[..pos]);
+        let code = rest.get(pos + 1..pos + 4);
+        match code.and_then(|digits| u8::from_str_radix(digits, 8).ok()) {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[pos + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[pos + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Name of the sync client managing `dir` or one of its ancestors, if any.
+fn synced_folder(dir: &Path) -> Option<String> {
+    for ancestor in dir.ancestors() {
+        if let Some(name) = ancestor.file_name().and_then(|name| name.to_str()) {
+            if let Some(client) = SYNCED_FOLDERS
+                .iter()
+                .find(|client| name.starts_with(*client))
+            {
+                return Some(client.to_string());
+            }
+        }
+        if let Some((_, client)) = SYNC_MARKERS
+            .iter()
+            .find(|(marker, _)| ancestor.join(marker).exists())
+        {
+            return Some(client.to_string());
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +323,69 @@ mod tests {
         assert!(dest.exists());
         assert_eq!(fs::read_to_string(&dest).unwrap(), "new");
     }
+
+    #[test]
+    fn test_filesystem_type_uses_longest_mount() {
+        let mounts = "\
+/dev/sda1 / ext4 rw 0 0
+server:/export /home/me/net nfs4 rw 0 0
+//nas/share /mnt/my\\040share cifs rw 0 0
+";
+        assert_eq!(
+            filesystem_type(mounts, Path::new("/home/me/net/ledgers")).as_deref(),
+            Some("nfs4")
+        );
+        assert_eq!(
+            filesystem_type(mounts, Path::new("/home/me/network")).as_deref(),
+            Some("ext4")
+        );
+        assert_eq!(
+            filesystem_type(mounts, Path::new("/mnt/my share")).as_deref(),
+            Some("cifs")
+        );
+    }
+
+    #[test]
+    fn test_synced_folder_by_name_and_marker() {
+        let dir = tempdir().unwrap();
+        let dropbox = dir.path().join("Dropbox (Personal)").join("notes");
+        fs::create_dir_all(&dropbox).unwrap();
+        assert_eq!(synced_folder(&dropbox).as_deref(), Some("Dropbox"));
+
+        let synced = dir.path().join("sync");
+        fs::create_dir_all(synced.join(".stfolder")).unwrap();
+        assert_eq!(synced_folder(&synced).as_deref(), Some("Syncthing"));
+
+        let plain = dir.path().join("plain");
+        fs::create_dir_all(&plain).unwrap();
+        assert_eq!(synced_folder(&plain), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_risk_and_write_target() {
+        let dir = tempdir().unwrap();
+        let real = dir.path().join("real.ledger");
+        let link = dir.path().join("link.ledger");
+        File::create(&real).unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let real = fs::canonicalize(&real).unwrap();
+        assert_eq!(write_target(&link), real);
+        assert!(path_risks(&link).contains(&PathRisk::Symlink(real.clone())));
+        assert_eq!(write_target(&real), real);
+    }
+
+    #[test]
+    fn test_write_lock_is_exclusive() {
+        let dir = tempdir().unwrap();
+        let ledger = dir.path().join("test.ledger");
+
+        let lock = WriteLock::acquire(&ledger).unwrap();
+        let err = WriteLock::acquire(&ledger).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        drop(lock);
+        assert!(!dir.path().join("test.ledger.lock").exists());
+        WriteLock::acquire(&ledger).unwrap();
+    }
 }
@@ -13,6 +13,7 @@
 //! - **search**: Full-text search and querying
 //! - **tags**: Tag normalization and filtering
 //! - **export**: Export formats (JSON, JSONL)
+//! - **views**: JSON output shapes shared by frontends, with their JSON Schemas
 //!
 //! ## Milestones
 //!
@@ -26,6 +27,7 @@ pub mod crypto;
 pub mod error;
 pub mod fs;
 pub mod storage;
+pub mod views;
 
 pub use error::{LedgerError, Result};
 pub use storage::StorageEngine;
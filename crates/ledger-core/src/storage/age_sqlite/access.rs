@@ -0,0 +1,68 @@
+//! Per-entry access timestamps.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// Record that an entry was viewed at `at`.
+    ///
+    /// Only the latest view is kept. Returns `LedgerError::NotFound` if the
+    /// entry doesn't exist.
+    pub fn record_access(&mut self, entry_id: &Uuid, at: DateTime<Utc>) -> Result<()> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let exists: Option<String> = tx
+            .query_row(
+                "SELECT id FROM entries WHERE id = ?",
+                [entry_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if exists.is_none() {
+            return Err(LedgerError::NotFound(format!(
+                "Entry {} not found",
+                entry_id
+            )));
+        }
+
+        let at = at.to_rfc3339();
+        tx.execute(
+            "INSERT INTO entry_access (entry_id, last_accessed) VALUES (?, ?) ON CONFLICT(entry_id) DO UPDATE SET last_accessed = excluded.last_accessed",
+            rusqlite::params![entry_id.to_string(), at],
+        )?;
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&self.now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Last view time of every entry that has been viewed.
+    pub fn last_accessed(&self) -> Result<HashMap<Uuid, DateTime<Utc>>> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached("SELECT entry_id, last_accessed FROM entry_access")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut accessed = HashMap::new();
+        for row in rows {
+            let (entry_id, at) = row?;
+            let entry_id = Uuid::parse_str(&entry_id)
+                .map_err(|e| LedgerError::Storage(format!("Invalid entry ID: {}", e)))?;
+            let at = DateTime::parse_from_rfc3339(&at)
+                .map_err(|e| LedgerError::Storage(format!("Invalid timestamp: {}", e)))?
+                .with_timezone(&Utc);
+            accessed.insert(entry_id, at);
+        }
+        Ok(accessed)
+    }
+}
@@ -0,0 +1,159 @@
+//! Content-addressed attachment blobs.
+//!
+//! Attachment rows carry metadata and the BLAKE3 hash of their bytes; the
+//! bytes themselves live once per hash in `attachment_blobs`, which counts
+//! the attachments referring to each blob. Removing the last reference leaves
+//! the blob in place until [`AgeSqliteStorage::gc_attachment_blobs`] runs.
+
+use rusqlite::{Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::storage::types::{AttachmentGc, AttachmentUsage};
+
+use super::AgeSqliteStorage;
+
+/// Store `data` as a blob, or take another reference to an identical one.
+///
+/// Returns the blob's hex BLAKE3 hash.
+pub(super) fn store_blob(conn: &Connection, data: &[u8]) -> Result<String> {
+    let hash = blake3::hash(data).to_hex().to_string();
+    conn.execute(
+        "INSERT INTO attachment_blobs (hash, size, data, refcount) VALUES (?, ?, ?, 1) \
+         ON CONFLICT(hash) DO UPDATE SET refcount = refcount + 1",
+        rusqlite::params![hash, data.len() as i64, data],
+    )?;
+    Ok(hash)
+}
+
+/// Reset every blob's reference count from the attachments that point at it.
+pub(super) fn recount_blob_refs(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "UPDATE attachment_blobs SET refcount = \
+         (SELECT COUNT(*) FROM attachments a WHERE a.blob_hash = attachment_blobs.hash)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Move bytes stored inline on attachments from before blobs existed into
+/// `attachment_blobs`, giving older ledgers a `blob_hash` column first.
+///
+/// `data` stays `NOT NULL` on those ledgers, so moved rows keep an empty
+/// value there.
+pub(super) fn migrate_attachment_blobs(conn: &Connection) -> Result<()> {
+    let has_blob_hash: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('attachments') WHERE name = 'blob_hash'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_blob_hash {
+        conn.execute_batch(
+            "ALTER TABLE attachments ADD COLUMN blob_hash TEXT REFERENCES attachment_blobs(hash);",
+        )?;
+    }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS attachments_blob ON attachments (blob_hash);")?;
+
+    let inline: Vec<(String, Vec<u8>)> = {
+        let mut stmt = conn.prepare("SELECT id, data FROM attachments WHERE blob_hash IS NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()?
+    };
+    for (id, data) in inline {
+        let hash = store_blob(conn, &data)?;
+        conn.execute(
+            "UPDATE attachments SET blob_hash = ?, data = x'' WHERE id = ?",
+            rusqlite::params![hash, id],
+        )?;
+    }
+    Ok(())
+}
+
+impl AgeSqliteStorage {
+    /// Remove an attachment record and release its reference to the blob.
+    ///
+    /// The blob is kept until [`Self::gc_attachment_blobs`], even when this
+    /// was its last reference. Returns `false` if no such attachment exists.
+    pub fn remove_attachment(&mut self, id: &Uuid) -> Result<bool> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let hash: Option<Option<String>> = tx
+            .query_row(
+                "SELECT blob_hash FROM attachments WHERE id = ?",
+                [id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(hash) = hash else {
+            return Ok(false);
+        };
+        tx.execute("DELETE FROM attachments WHERE id = ?", [id.to_string()])?;
+        if let Some(hash) = hash {
+            tx.execute(
+                "UPDATE attachment_blobs SET refcount = MAX(refcount - 1, 0) WHERE hash = ?",
+                [hash],
+            )?;
+        }
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&self.now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// How much space attachments take, and how much deduplication saves.
+    pub fn attachment_usage(&self) -> Result<AttachmentUsage> {
+        let conn = self.lock_conn()?;
+        let (attachments, logical_bytes): (i64, i64) = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(size), 0) FROM attachments",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let (blobs, stored_bytes, unreferenced_blobs, unreferenced_bytes): (i64, i64, i64, i64) =
+            conn.query_row(
+                "SELECT COUNT(*), COALESCE(SUM(size), 0), \
+                 COALESCE(SUM(refcount <= 0), 0), \
+                 COALESCE(SUM(CASE WHEN refcount <= 0 THEN size ELSE 0 END), 0) \
+                 FROM attachment_blobs",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )?;
+        Ok(AttachmentUsage {
+            attachments: attachments as u64,
+            logical_bytes: logical_bytes as u64,
+            blobs: blobs as u64,
+            stored_bytes: stored_bytes as u64,
+            unreferenced_blobs: unreferenced_blobs as u64,
+            unreferenced_bytes: unreferenced_bytes as u64,
+        })
+    }
+
+    /// Delete blobs no attachment refers to.
+    ///
+    /// Reference counts are recomputed from the attachments first, so a
+    /// count that drifted (say, after recovery dropped rows) cannot keep a
+    /// blob alive or delete one still in use.
+    pub fn gc_attachment_blobs(&mut self) -> Result<AttachmentGc> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        recount_blob_refs(&tx)?;
+        let (blobs_removed, bytes_freed): (i64, i64) = tx.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(size), 0) FROM attachment_blobs WHERE refcount = 0",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        if blobs_removed > 0 {
+            tx.execute("DELETE FROM attachment_blobs WHERE refcount = 0", [])?;
+            tx.execute(
+                "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+                [&self.now().to_rfc3339()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(AttachmentGc {
+            blobs_removed: blobs_removed as u64,
+            bytes_freed: bytes_freed as u64,
+        })
+    }
+}
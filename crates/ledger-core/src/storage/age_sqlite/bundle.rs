@@ -0,0 +1,614 @@
+//! Full-fidelity export and import of ledger structure.
+//!
+//! Unlike the trait's create methods, import keeps the ids, timestamps and
+//! version history from the bundle, so compositions, memberships and template
+//! defaults survive a move between ledgers. Rows that already exist (by id or
+//! unique name) are left alone and later rows are pointed at the existing id.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+use crate::storage::clock::IdGen;
+use crate::storage::types::{
+    Composition, CompositionOverview, Entry, EntryComposition, EntryTypeRecord, ExportBundle,
+    ImportSummary, TemplateRecord, VersionRecord,
+};
+
+use super::day_numbers;
+use super::search;
+use super::validation::{fts_content_for_entry, normalize_tags, validate_schema_annotations};
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// Export entry types, templates, compositions, memberships and
+    /// composition overviews.
+    ///
+    /// Entries are left out; callers add the (possibly filtered) entries they
+    /// want alongside the structure.
+    pub fn export_structure(&self) -> Result<ExportBundle> {
+        let conn = self.lock_conn()?;
+        Ok(ExportBundle {
+            entry_types: export_entry_types(&conn)?,
+            templates: export_templates(&conn)?,
+            compositions: export_compositions(&conn)?,
+            entry_compositions: export_memberships(&conn)?,
+            composition_overviews: export_overviews(&conn)?,
+            entries: Vec::new(),
+        })
+    }
+
+    /// Import a bundle, preserving ids, timestamps and version history.
+    ///
+    /// Runs in a single transaction: either the whole bundle is applied or
+    /// nothing is. Fails if an entry or template references an entry type, or
+    /// a membership references an entry or composition, that is neither in
+    /// the bundle nor already in the ledger.
+    pub fn import_bundle(&mut self, bundle: &ExportBundle) -> Result<ImportSummary> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let mut summary = ImportSummary::default();
+
+        let mut type_ids = HashMap::new();
+        for record in &bundle.entry_types {
+            let target = match existing_id(&tx, "entry_types", &record.id, &record.name)? {
+                Some(id) => {
+                    summary.skipped += 1;
+                    id
+                }
+                None => {
+                    insert_entry_type(&tx, record, &*self.ids)?;
+                    summary.entry_types += 1;
+                    record.id
+                }
+            };
+            type_ids.insert(record.id, target);
+        }
+
+        for record in &bundle.templates {
+            if existing_id(&tx, "templates", &record.id, &record.name)?.is_some() {
+                summary.skipped += 1;
+                continue;
+            }
+            let entry_type_id = resolve_entry_type(&tx, &type_ids, &record.entry_type_id)?;
+            insert_template(&tx, record, &entry_type_id, &*self.ids)?;
+            summary.templates += 1;
+        }
+
+        let mut composition_ids = HashMap::new();
+        for composition in &bundle.compositions {
+            let target = match existing_id(&tx, "compositions", &composition.id, &composition.name)?
+            {
+                Some(id) => {
+                    summary.skipped += 1;
+                    id
+                }
+                None => {
+                    insert_composition(&tx, composition)?;
+                    summary.compositions += 1;
+                    composition.id
+                }
+            };
+            composition_ids.insert(composition.id, target);
+        }
+
+        // Insert oldest first so imported entries get fresh `seq` values in
+        // their original order; bundles without `seq` keep file order
+        let mut entries: Vec<&Entry> = bundle.entries.iter().collect();
+        entries.sort_by_key(|entry| (entry.created_at, entry.seq));
+        for entry in entries {
+            let exists: Option<String> = tx
+                .query_row(
+                    "SELECT id FROM entries WHERE id = ?",
+                    [entry.id.to_string()],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if exists.is_some() {
+                summary.skipped += 1;
+                continue;
+            }
+            let entry_type_id = resolve_entry_type(&tx, &type_ids, &entry.entry_type_id)?;
+            let tags = normalize_tags(&entry.tags)?;
+            let tags_json = if tags.is_empty() {
+                None
+            } else {
+                Some(serde_json::to_string(&tags).map_err(|e| {
+                    LedgerError::Storage(format!("Failed to serialize tags: {}", e))
+                })?)
+            };
+            tx.execute(
+                r#"
+                INSERT INTO entries (
+                    id, entry_type_id, schema_version, data_json, tags_json,
+                    created_at, device_id, supersedes, seq
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, (SELECT COALESCE(MAX(seq), 0) + 1 FROM entries))
+                "#,
+                (
+                    entry.id.to_string(),
+                    entry_type_id.to_string(),
+                    entry.schema_version,
+                    entry.data.to_string(),
+                    tags_json,
+                    entry.created_at.to_rfc3339(),
+                    entry.device_id.to_string(),
+                    entry.supersedes.map(|id| id.to_string()),
+                ),
+            )?;
+            let schema = search::schema_for(&tx, &entry_type_id.to_string(), entry.schema_version)?;
+            tx.execute(
+                "INSERT INTO entries_fts (entry_id, content) VALUES (?, ?)",
+                (
+                    entry.id.to_string(),
+                    fts_content_for_entry(&schema, &entry.data),
+                ),
+            )?;
+            self.record_origin(&tx, &entry.id)?;
+            summary.entries += 1;
+        }
+        day_numbers::assign_missing_day_numbers(&tx, self.day_offset)?;
+
+        for membership in &bundle.entry_compositions {
+            let entry_exists: Option<String> = tx
+                .query_row(
+                    "SELECT id FROM entries WHERE id = ?",
+                    [membership.entry_id.to_string()],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if entry_exists.is_none() {
+                return Err(LedgerError::Validation(format!(
+                    "Membership references unknown entry {}",
+                    membership.entry_id
+                )));
+            }
+            let composition_id = match composition_ids.get(&membership.composition_id) {
+                Some(id) => *id,
+                None if row_exists(&tx, "compositions", &membership.composition_id)? => {
+                    membership.composition_id
+                }
+                None => {
+                    return Err(LedgerError::Validation(format!(
+                        "Membership references unknown composition {}",
+                        membership.composition_id
+                    )));
+                }
+            };
+            // Memberships already recorded, and current ones clashing with a
+            // current local membership, are skipped
+            let inserted = tx.execute(
+                r#"
+                INSERT OR IGNORE INTO entry_compositions
+                    (entry_id, composition_id, added_at, removed_at)
+                SELECT ?1, ?2, ?3, ?4
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM entry_compositions
+                    WHERE entry_id = ?1 AND composition_id = ?2 AND added_at = ?3
+                )
+                "#,
+                (
+                    membership.entry_id.to_string(),
+                    composition_id.to_string(),
+                    membership.added_at.to_rfc3339(),
+                    membership.removed_at.map(|at| at.to_rfc3339()),
+                ),
+            )?;
+            if inserted == 0 {
+                summary.skipped += 1;
+            } else {
+                summary.memberships += 1;
+            }
+        }
+
+        // Overviews already present are kept, like other existing rows
+        for overview in &bundle.composition_overviews {
+            let composition_id = match composition_ids.get(&overview.composition_id) {
+                Some(id) => *id,
+                None if row_exists(&tx, "compositions", &overview.composition_id)? => {
+                    overview.composition_id
+                }
+                None => {
+                    return Err(LedgerError::Validation(format!(
+                        "Overview references unknown composition {}",
+                        overview.composition_id
+                    )));
+                }
+            };
+            let inserted = tx.execute(
+                r#"
+                INSERT OR IGNORE INTO composition_overviews (composition_id, body, updated_at)
+                VALUES (?, ?, ?)
+                "#,
+                (
+                    composition_id.to_string(),
+                    &overview.body,
+                    overview.updated_at.to_rfc3339(),
+                ),
+            )?;
+            if inserted == 0 {
+                summary.skipped += 1;
+            } else {
+                summary.overviews += 1;
+            }
+        }
+
+        let written = summary.entry_types
+            + summary.templates
+            + summary.compositions
+            + summary.entries
+            + summary.memberships
+            + summary.overviews;
+        if written > 0 {
+            tx.execute(
+                "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+                [self.now().to_rfc3339()],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(summary)
+    }
+}
+
+fn parse_uuid(value: &str) -> Result<Uuid> {
+    Uuid::parse_str(value).map_err(|e| LedgerError::Storage(format!("Invalid UUID: {}", e)))
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(value)
+        .map_err(|e| LedgerError::Storage(format!("Invalid timestamp: {}", e)))?
+        .with_timezone(&Utc))
+}
+
+fn parse_json(value: &str) -> Result<serde_json::Value> {
+    serde_json::from_str(value).map_err(|e| LedgerError::Storage(format!("Invalid JSON: {}", e)))
+}
+
+/// Id of the row in `table` matching `id`, or else `name`.
+fn existing_id(conn: &Connection, table: &str, id: &Uuid, name: &str) -> Result<Option<Uuid>> {
+    let found: Option<String> = conn
+        .query_row(
+            &format!(
+                "SELECT id FROM {} WHERE id = ? OR name = ? ORDER BY id = ? DESC LIMIT 1",
+                table
+            ),
+            [id.to_string(), name.to_string(), id.to_string()],
+            |row| row.get(0),
+        )
+        .optional()?;
+    found.as_deref().map(parse_uuid).transpose()
+}
+
+fn row_exists(conn: &Connection, table: &str, id: &Uuid) -> Result<bool> {
+    let found: Option<String> = conn
+        .query_row(
+            &format!("SELECT id FROM {} WHERE id = ?", table),
+            [id.to_string()],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(found.is_some())
+}
+
+fn resolve_entry_type(
+    conn: &Connection,
+    type_ids: &HashMap<Uuid, Uuid>,
+    entry_type_id: &Uuid,
+) -> Result<Uuid> {
+    if let Some(id) = type_ids.get(entry_type_id) {
+        return Ok(*id);
+    }
+    if row_exists(conn, "entry_types", entry_type_id)? {
+        return Ok(*entry_type_id);
+    }
+    Err(LedgerError::Validation(format!(
+        "Import references unknown entry type {}",
+        entry_type_id
+    )))
+}
+
+fn insert_entry_type(conn: &Connection, record: &EntryTypeRecord, ids: &dyn IdGen) -> Result<()> {
+    if record.versions.is_empty() {
+        return Err(LedgerError::Validation(format!(
+            "Entry type '{}' has no versions",
+            record.name
+        )));
+    }
+    for version in &record.versions {
+        validate_schema_annotations(&version.body)?;
+    }
+    conn.execute(
+        "INSERT INTO entry_types (id, name, created_at, device_id) VALUES (?, ?, ?, ?)",
+        (
+            record.id.to_string(),
+            &record.name,
+            record.created_at.to_rfc3339(),
+            record.device_id.to_string(),
+        ),
+    )?;
+    for version in &record.versions {
+        conn.execute(
+            r#"
+            INSERT INTO entry_type_versions (id, entry_type_id, version, schema_json, created_at, active)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            (
+                ids.new_id().to_string(),
+                record.id.to_string(),
+                version.version,
+                version.body.to_string(),
+                version.created_at.to_rfc3339(),
+                version.active,
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+fn insert_template(
+    conn: &Connection,
+    record: &TemplateRecord,
+    entry_type_id: &Uuid,
+    ids: &dyn IdGen,
+) -> Result<()> {
+    if record.versions.is_empty() {
+        return Err(LedgerError::Validation(format!(
+            "Template '{}' has no versions",
+            record.name
+        )));
+    }
+    conn.execute(
+        r#"
+        INSERT INTO templates (id, name, entry_type_id, description, created_at, device_id)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        (
+            record.id.to_string(),
+            &record.name,
+            entry_type_id.to_string(),
+            &record.description,
+            record.created_at.to_rfc3339(),
+            record.device_id.to_string(),
+        ),
+    )?;
+    for version in &record.versions {
+        conn.execute(
+            r#"
+            INSERT INTO template_versions (id, template_id, version, template_json, created_at, active)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            (
+                ids.new_id().to_string(),
+                record.id.to_string(),
+                version.version,
+                version.body.to_string(),
+                version.created_at.to_rfc3339(),
+                version.active,
+            ),
+        )?;
+    }
+    if record.default {
+        // Keep an existing default for this entry type rather than replace it
+        conn.execute(
+            r#"
+            INSERT OR IGNORE INTO entry_type_templates (entry_type_id, template_id, active)
+            VALUES (?, ?, 1)
+            "#,
+            (entry_type_id.to_string(), record.id.to_string()),
+        )?;
+    }
+    Ok(())
+}
+
+fn insert_composition(conn: &Connection, composition: &Composition) -> Result<()> {
+    let metadata_json = composition.metadata.as_ref().map(|m| m.to_string());
+    conn.execute(
+        r#"
+        INSERT INTO compositions (id, name, description, created_at, device_id, metadata_json)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        (
+            composition.id.to_string(),
+            &composition.name,
+            &composition.description,
+            composition.created_at.to_rfc3339(),
+            composition.device_id.to_string(),
+            metadata_json,
+        ),
+    )?;
+    Ok(())
+}
+
+/// Versions of one parent row, oldest first.
+fn export_versions(conn: &Connection, sql: &str, parent_id: &str) -> Result<Vec<VersionRecord>> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let rows = stmt.query_map([parent_id], |row| {
+        Ok((
+            row.get::<_, i32>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, bool>(3)?,
+        ))
+    })?;
+    let mut versions = Vec::new();
+    for row in rows {
+        let (version, body, created_at, active) = row?;
+        versions.push(VersionRecord {
+            version,
+            body: parse_json(&body)?,
+            created_at: parse_timestamp(&created_at)?,
+            active,
+        });
+    }
+    Ok(versions)
+}
+
+fn export_entry_types(conn: &Connection) -> Result<Vec<EntryTypeRecord>> {
+    let mut stmt = conn
+        .prepare_cached("SELECT id, name, created_at, device_id FROM entry_types ORDER BY name")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+    let mut records = Vec::new();
+    for row in rows {
+        let (id, name, created_at, device_id) = row?;
+        let versions = export_versions(
+            conn,
+            r#"
+            SELECT version, schema_json, created_at, active
+            FROM entry_type_versions WHERE entry_type_id = ? ORDER BY version
+            "#,
+            &id,
+        )?;
+        records.push(EntryTypeRecord {
+            id: parse_uuid(&id)?,
+            name,
+            created_at: parse_timestamp(&created_at)?,
+            device_id: parse_uuid(&device_id)?,
+            versions,
+        });
+    }
+    Ok(records)
+}
+
+fn export_templates(conn: &Connection) -> Result<Vec<TemplateRecord>> {
+    let mut stmt = conn.prepare_cached(
+        r#"
+        SELECT t.id, t.name, t.entry_type_id, t.description, t.created_at, t.device_id,
+               EXISTS (
+                   SELECT 1 FROM entry_type_templates ett
+                   WHERE ett.template_id = t.id AND ett.active = 1
+               )
+        FROM templates t
+        ORDER BY t.name
+        "#,
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, String>(5)?,
+            row.get::<_, bool>(6)?,
+        ))
+    })?;
+    let mut records = Vec::new();
+    for row in rows {
+        let (id, name, entry_type_id, description, created_at, device_id, default) = row?;
+        let versions = export_versions(
+            conn,
+            r#"
+            SELECT version, template_json, created_at, active
+            FROM template_versions WHERE template_id = ? ORDER BY version
+            "#,
+            &id,
+        )?;
+        records.push(TemplateRecord {
+            id: parse_uuid(&id)?,
+            name,
+            entry_type_id: parse_uuid(&entry_type_id)?,
+            description,
+            created_at: parse_timestamp(&created_at)?,
+            device_id: parse_uuid(&device_id)?,
+            versions,
+            default,
+        });
+    }
+    Ok(records)
+}
+
+fn export_compositions(conn: &Connection) -> Result<Vec<Composition>> {
+    let mut stmt = conn.prepare_cached(
+        r#"
+        SELECT id, name, description, created_at, device_id, metadata_json
+        FROM compositions ORDER BY name
+        "#,
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, Option<String>>(5)?,
+        ))
+    })?;
+    let mut compositions = Vec::new();
+    for row in rows {
+        let (id, name, description, created_at, device_id, metadata_json) = row?;
+        compositions.push(Composition {
+            id: parse_uuid(&id)?,
+            name,
+            description,
+            created_at: parse_timestamp(&created_at)?,
+            device_id: parse_uuid(&device_id)?,
+            metadata: metadata_json.as_deref().map(parse_json).transpose()?,
+        });
+    }
+    Ok(compositions)
+}
+
+fn export_memberships(conn: &Connection) -> Result<Vec<EntryComposition>> {
+    let mut stmt = conn.prepare_cached(
+        r#"
+        SELECT entry_id, composition_id, added_at, removed_at
+        FROM entry_compositions ORDER BY added_at, entry_id
+        "#,
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+    let mut memberships = Vec::new();
+    for row in rows {
+        let (entry_id, composition_id, added_at, removed_at) = row?;
+        memberships.push(EntryComposition {
+            entry_id: parse_uuid(&entry_id)?,
+            composition_id: parse_uuid(&composition_id)?,
+            added_at: parse_timestamp(&added_at)?,
+            removed_at: removed_at.as_deref().map(parse_timestamp).transpose()?,
+        });
+    }
+    Ok(memberships)
+}
+
+fn export_overviews(conn: &Connection) -> Result<Vec<CompositionOverview>> {
+    let mut stmt = conn.prepare_cached(
+        r#"
+        SELECT composition_id, body, updated_at
+        FROM composition_overviews ORDER BY composition_id
+        "#,
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut overviews = Vec::new();
+    for row in rows {
+        let (composition_id, body, updated_at) = row?;
+        overviews.push(CompositionOverview {
+            composition_id: parse_uuid(&composition_id)?,
+            body,
+            updated_at: parse_timestamp(&updated_at)?,
+        });
+    }
+    Ok(overviews)
+}
@@ -0,0 +1,59 @@
+//! Entry counts per calendar day.
+
+use chrono::{FixedOffset, NaiveDate};
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::DayCount;
+
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// Count current entries per day and entry type from `from` to `to`
+    /// (inclusive), ordered by day.
+    ///
+    /// Creation times are shifted by `offset` before taking the date, so
+    /// passing the local UTC offset buckets entries by local day.
+    pub fn day_counts(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        offset: FixedOffset,
+    ) -> Result<Vec<DayCount>> {
+        let shift = format!("{:+} seconds", offset.local_minus_utc());
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT date(created_at, ?1) AS day, entry_type_id, COUNT(*) FROM entries \
+             WHERE id NOT IN (SELECT supersedes FROM entries WHERE supersedes IS NOT NULL) \
+             AND day BETWEEN ?2 AND ?3 \
+             GROUP BY day, entry_type_id ORDER BY day, entry_type_id",
+        )?;
+        let rows = stmt.query_map(
+            rusqlite::params![
+                shift,
+                from.format("%Y-%m-%d").to_string(),
+                to.format("%Y-%m-%d").to_string()
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            },
+        )?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (day, entry_type_id, count) = row?;
+            out.push(DayCount {
+                date: NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+                    .map_err(|e| LedgerError::Storage(format!("Invalid entry date: {}", e)))?,
+                entry_type_id: Uuid::parse_str(&entry_type_id)
+                    .map_err(|e| LedgerError::Storage(format!("Invalid entry type ID: {}", e)))?,
+                count: count as usize,
+            });
+        }
+        Ok(out)
+    }
+}
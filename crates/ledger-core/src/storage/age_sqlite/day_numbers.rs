@@ -0,0 +1,198 @@
+//! Per-day entry numbers, such as `2025-02-03#4` for the fourth entry of a
+//! day.
+//!
+//! A number is assigned once and never changes: an entry backfilled onto an
+//! earlier day takes that day's next free number rather than its place in
+//! time. A revision keeps the number of the entry it supersedes, so a number
+//! names the whole chain. Entries are numbered as they are inserted or
+//! imported; entries written before numbering are numbered when the ledger is
+//! opened, in `(created_at, seq)` order. Reading never assigns a number.
+//!
+//! Days are taken under one UTC offset recorded in `meta` the first time the
+//! ledger is numbered, so a ledger opened from another time zone keeps its
+//! numbers.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Offset};
+use rusqlite::{Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::DayNumber;
+
+use super::AgeSqliteStorage;
+
+/// `meta` key holding the numbering offset, in seconds east of UTC.
+const DAY_OFFSET_KEY: &str = "day_offset";
+
+impl AgeSqliteStorage {
+    /// Record `offset` as the one entries numbered from now on take their
+    /// day under. Numbers already assigned keep their day.
+    pub fn set_day_offset(&mut self, offset: FixedOffset) -> Result<()> {
+        let conn = self.lock_conn()?;
+        store_day_offset(&conn, offset)?;
+        drop(conn);
+        self.day_offset = offset;
+        Ok(())
+    }
+
+    /// Day number of an entry.
+    pub fn day_number(&self, id: &Uuid) -> Result<Option<DayNumber>> {
+        let conn = self.lock_conn()?;
+        conn.query_row(
+            "SELECT day, number FROM entry_day_numbers WHERE entry_id = ?",
+            [id.to_string()],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)),
+        )
+        .optional()?
+        .map(|(day, number)| day_number(&day, number))
+        .transpose()
+    }
+
+    /// Day numbers of every entry, by entry id.
+    pub fn day_numbers(&self) -> Result<HashMap<Uuid, DayNumber>> {
+        let conn = self.lock_conn()?;
+        let mut stmt =
+            conn.prepare_cached("SELECT entry_id, day, number FROM entry_day_numbers")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+            ))
+        })?;
+        let mut out = HashMap::new();
+        for row in rows {
+            let (id, day, number) = row?;
+            let id = Uuid::parse_str(&id)
+                .map_err(|e| LedgerError::Storage(format!("Invalid entry UUID: {}", e)))?;
+            out.insert(id, day_number(&day, number)?);
+        }
+        Ok(out)
+    }
+
+    /// The current revision of the entry numbered `number` on its day.
+    pub fn entry_by_day_number(&self, number: &DayNumber) -> Result<Option<Uuid>> {
+        let conn = self.lock_conn()?;
+        let id: Option<String> = conn
+            .query_row(
+                "SELECT d.entry_id FROM entry_day_numbers d \
+                 WHERE d.day = ? AND d.number = ? \
+                 AND d.entry_id NOT IN (SELECT supersedes FROM entries WHERE supersedes IS NOT NULL)",
+                rusqlite::params![number.day.format("%Y-%m-%d").to_string(), number.number],
+                |row| row.get(0),
+            )
+            .optional()?;
+        id.map(|id| {
+            Uuid::parse_str(&id)
+                .map_err(|e| LedgerError::Storage(format!("Invalid entry UUID: {}", e)))
+        })
+        .transpose()
+    }
+}
+
+/// Load the recorded numbering offset, recording this machine's current one
+/// for a ledger that has none, and number entries written before numbering.
+pub(super) fn migrate_day_numbers(conn: &Connection) -> Result<FixedOffset> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = ?",
+            [DAY_OFFSET_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let offset = match stored {
+        Some(seconds) => seconds
+            .parse::<i32>()
+            .ok()
+            .and_then(FixedOffset::east_opt)
+            .ok_or_else(|| LedgerError::Storage(format!("Invalid day offset: {}", seconds)))?,
+        None => {
+            let offset = Local::now().offset().fix();
+            store_day_offset(conn, offset)?;
+            offset
+        }
+    };
+    assign_missing_day_numbers(conn, offset)?;
+    Ok(offset)
+}
+
+fn store_day_offset(conn: &Connection, offset: FixedOffset) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES (?, ?)",
+        rusqlite::params![DAY_OFFSET_KEY, offset.local_minus_utc().to_string()],
+    )?;
+    Ok(())
+}
+
+/// Number every entry that has no day number yet.
+pub(super) fn assign_missing_day_numbers(conn: &Connection, offset: FixedOffset) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT e.id, e.created_at, e.supersedes FROM entries e \
+         WHERE NOT EXISTS (SELECT 1 FROM entry_day_numbers d WHERE d.entry_id = e.id) \
+         ORDER BY e.created_at, e.seq",
+    )?;
+    let missing = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (id, created_at, supersedes) in missing {
+        assign_day_number(conn, &id, &created_at, supersedes.as_deref(), offset)?;
+    }
+    Ok(())
+}
+
+/// Number one entry: its parent's number for a revision, otherwise the next
+/// free number of its day.
+fn assign_day_number(
+    conn: &Connection,
+    id: &str,
+    created_at: &str,
+    supersedes: Option<&str>,
+    offset: FixedOffset,
+) -> Result<()> {
+    let inherited: Option<(String, u32)> = match supersedes {
+        Some(parent) => conn
+            .query_row(
+                "SELECT day, number FROM entry_day_numbers WHERE entry_id = ?",
+                [parent],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?,
+        None => None,
+    };
+    let (day, number) = match inherited {
+        Some(inherited) => inherited,
+        None => {
+            let day = DateTime::parse_from_rfc3339(created_at)
+                .map_err(|e| LedgerError::Storage(format!("Invalid timestamp: {}", e)))?
+                .with_timezone(&offset)
+                .date_naive()
+                .format("%Y-%m-%d")
+                .to_string();
+            let number: u32 = conn.query_row(
+                "SELECT COALESCE(MAX(number), 0) + 1 FROM entry_day_numbers WHERE day = ?",
+                [&day],
+                |row| row.get(0),
+            )?;
+            (day, number)
+        }
+    };
+    conn.execute(
+        "INSERT INTO entry_day_numbers (entry_id, day, number) VALUES (?, ?, ?)",
+        rusqlite::params![id, day, number],
+    )?;
+    Ok(())
+}
+
+fn day_number(day: &str, number: u32) -> Result<DayNumber> {
+    let day = NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .map_err(|e| LedgerError::Storage(format!("Invalid entry day: {}", e)))?;
+    Ok(DayNumber { day, number })
+}
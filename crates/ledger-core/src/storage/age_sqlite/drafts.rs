@@ -0,0 +1,236 @@
+//! Drafts: entries saved before they are ready, published later.
+//!
+//! A draft is encrypted with the rest of the ledger but is not an entry, so
+//! listing, search and export leave it out. Its data is only validated
+//! against the entry type's schema when it is published.
+
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+use crate::storage::traits::StorageEngine;
+use crate::storage::types::{Draft, NewEntry};
+
+use super::validation::{normalize_tags, MAX_DATA_BYTES};
+use super::AgeSqliteStorage;
+
+const DRAFT_COLUMNS: &str =
+    "id, entry_type_id, data_json, tags_json, created_at, updated_at, device_id";
+
+type DraftRow = (
+    String,
+    String,
+    String,
+    Option<String>,
+    String,
+    String,
+    String,
+);
+
+impl AgeSqliteStorage {
+    /// Save `draft` as a new draft. Only the entry type, data, tags, device
+    /// and `created_at` are used.
+    ///
+    /// Returns `LedgerError::Validation` if the entry type does not exist or
+    /// the data is too large.
+    pub fn insert_draft(&mut self, draft: &NewEntry) -> Result<Uuid> {
+        let (data_json, tags_json) = draft_columns(&draft.data, &draft.tags)?;
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let exists: bool = tx.query_row(
+            "SELECT COUNT(*) > 0 FROM entry_types WHERE id = ?",
+            [draft.entry_type_id.to_string()],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(LedgerError::Validation(
+                "Entry type does not exist".to_string(),
+            ));
+        }
+
+        let id = self.new_id();
+        let now = self.now();
+        let created_at = self
+            .timestamp_precision
+            .truncate(draft.created_at.unwrap_or(now))
+            .to_rfc3339();
+        let now = now.to_rfc3339();
+        tx.execute(
+            "INSERT INTO drafts (id, entry_type_id, data_json, tags_json, created_at, updated_at, device_id) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            (
+                id.to_string(),
+                draft.entry_type_id.to_string(),
+                data_json,
+                tags_json,
+                &created_at,
+                &now,
+                draft.device_id.to_string(),
+            ),
+        )?;
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&now],
+        )?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Replace the data and tags of a draft.
+    ///
+    /// Returns `LedgerError::NotFound` if there is no such draft.
+    pub fn update_draft(
+        &mut self,
+        id: &Uuid,
+        data: &serde_json::Value,
+        tags: &[String],
+    ) -> Result<()> {
+        let (data_json, tags_json) = draft_columns(data, tags)?;
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let now = self.now().to_rfc3339();
+        let updated = tx.execute(
+            "UPDATE drafts SET data_json = ?, tags_json = ?, updated_at = ? WHERE id = ?",
+            (data_json, tags_json, &now, id.to_string()),
+        )?;
+        if updated == 0 {
+            return Err(LedgerError::NotFound(format!("Draft {} not found", id)));
+        }
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&now],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// A draft by ID, or `None` if there is no such draft.
+    pub fn get_draft(&self, id: &Uuid) -> Result<Option<Draft>> {
+        let conn = self.lock_conn()?;
+        let row: Option<DraftRow> = conn
+            .query_row(
+                &format!("SELECT {} FROM drafts WHERE id = ?", DRAFT_COLUMNS),
+                [id.to_string()],
+                draft_row,
+            )
+            .optional()?;
+        row.map(parse_draft).transpose()
+    }
+
+    /// All drafts, most recently saved first.
+    pub fn list_drafts(&self) -> Result<Vec<Draft>> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM drafts ORDER BY updated_at DESC, id",
+            DRAFT_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], draft_row)?;
+        let mut drafts = Vec::new();
+        for row in rows {
+            drafts.push(parse_draft(row?)?);
+        }
+        Ok(drafts)
+    }
+
+    /// Delete a draft. Returns whether it existed.
+    pub fn delete_draft(&mut self, id: &Uuid) -> Result<bool> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let removed = tx.execute("DELETE FROM drafts WHERE id = ?", [id.to_string()])?;
+        if removed > 0 {
+            tx.execute(
+                "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+                [&self.now().to_rfc3339()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(removed > 0)
+    }
+
+    /// Turn a draft into an entry of the current schema version and delete
+    /// the draft. The entry keeps the draft's creation time unless
+    /// `created_at` is given.
+    ///
+    /// Returns `LedgerError::NotFound` if there is no such draft, and the
+    /// errors of [`StorageEngine::insert_entry`] if the data does not fit
+    /// the schema; the draft is kept in that case.
+    pub fn publish_draft(&mut self, id: &Uuid, created_at: Option<DateTime<Utc>>) -> Result<Uuid> {
+        let draft = self
+            .get_draft(id)?
+            .ok_or_else(|| LedgerError::NotFound(format!("Draft {} not found", id)))?;
+        let version: Option<i32> = self.lock_conn()?.query_row(
+            "SELECT MAX(version) FROM entry_type_versions WHERE entry_type_id = ?",
+            [draft.entry_type_id.to_string()],
+            |row| row.get(0),
+        )?;
+        let version = version.ok_or_else(|| {
+            LedgerError::Validation("Entry schema version does not exist".to_string())
+        })?;
+
+        let entry = NewEntry::new(draft.entry_type_id, version, draft.data, draft.device_id)
+            .with_tags(draft.tags)
+            .with_created_at(created_at.unwrap_or(draft.created_at));
+        let entry_id = self.insert_entry(&entry)?;
+        self.delete_draft(id)?;
+        Ok(entry_id)
+    }
+}
+
+/// Serialized data and tags for a draft row.
+fn draft_columns(data: &serde_json::Value, tags: &[String]) -> Result<(String, Option<String>)> {
+    let data_json = serde_json::to_string(data)
+        .map_err(|e| LedgerError::Storage(format!("Failed to serialize draft data: {}", e)))?;
+    if data_json.len() > MAX_DATA_BYTES {
+        return Err(LedgerError::Validation(format!(
+            "Draft data too large (max {} bytes)",
+            MAX_DATA_BYTES
+        )));
+    }
+    let tags = normalize_tags(tags)?;
+    let tags_json = if tags.is_empty() {
+        None
+    } else {
+        Some(
+            serde_json::to_string(&tags)
+                .map_err(|e| LedgerError::Storage(format!("Failed to serialize tags: {}", e)))?,
+        )
+    };
+    Ok((data_json, tags_json))
+}
+
+fn draft_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<DraftRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+    ))
+}
+
+fn parse_draft(row: DraftRow) -> Result<Draft> {
+    let (id, entry_type_id, data_json, tags_json, created_at, updated_at, device_id) = row;
+    let parse_uuid = |value: &str| {
+        Uuid::parse_str(value).map_err(|e| LedgerError::Storage(format!("Invalid UUID: {}", e)))
+    };
+    let parse_time = |value: &str| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|time| time.with_timezone(&Utc))
+            .map_err(|e| LedgerError::Storage(format!("Invalid timestamp: {}", e)))
+    };
+    Ok(Draft {
+        id: parse_uuid(&id)?,
+        entry_type_id: parse_uuid(&entry_type_id)?,
+        data: serde_json::from_str(&data_json)?,
+        tags: match tags_json {
+            Some(json) => serde_json::from_str(&json)?,
+            None => Vec::new(),
+        },
+        created_at: parse_time(&created_at)?,
+        updated_at: parse_time(&updated_at)?,
+        device_id: parse_uuid(&device_id)?,
+    })
+}
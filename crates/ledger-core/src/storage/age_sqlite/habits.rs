@@ -0,0 +1,123 @@
+//! Habit definitions.
+
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::{Habit, HabitSchedule};
+
+use super::validation::validate_habit_name;
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// Define a new habit.
+    ///
+    /// Returns `LedgerError::Validation` if a habit with the name exists.
+    pub fn create_habit(
+        &mut self,
+        name: &str,
+        schedule: &HabitSchedule,
+        device_id: Uuid,
+    ) -> Result<Habit> {
+        validate_habit_name(name)?;
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let exists: Option<String> = tx
+            .query_row("SELECT name FROM habits WHERE name = ?", [name], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        if exists.is_some() {
+            return Err(LedgerError::Validation(format!(
+                "Habit '{}' already exists",
+                name
+            )));
+        }
+
+        let created_at = self.now();
+        tx.execute(
+            "INSERT INTO habits (name, schedule, created_at, device_id) VALUES (?, ?, ?, ?)",
+            rusqlite::params![
+                name,
+                schedule.to_string(),
+                created_at.to_rfc3339(),
+                device_id.to_string()
+            ],
+        )?;
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&created_at.to_rfc3339()],
+        )?;
+        tx.commit()?;
+
+        Ok(Habit {
+            name: name.to_string(),
+            schedule: schedule.clone(),
+            created_at,
+            device_id,
+        })
+    }
+
+    /// Look up a habit by name.
+    pub fn get_habit(&self, name: &str) -> Result<Option<Habit>> {
+        Ok(self
+            .list_habits()?
+            .into_iter()
+            .find(|habit| habit.name == name))
+    }
+
+    /// All habits, sorted by name.
+    pub fn list_habits(&self) -> Result<Vec<Habit>> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT name, schedule, created_at, device_id FROM habits ORDER BY name ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        let mut habits = Vec::new();
+        for row in rows {
+            let (name, schedule, created_at, device_id) = row?;
+            let schedule = HabitSchedule::parse(&schedule).ok_or_else(|| {
+                LedgerError::Storage(format!("Invalid schedule for habit '{}'", name))
+            })?;
+            let created_at = DateTime::parse_from_rfc3339(&created_at)
+                .map_err(|e| LedgerError::Storage(format!("Invalid habit timestamp: {}", e)))?
+                .with_timezone(&Utc);
+            let device_id = Uuid::parse_str(&device_id)
+                .map_err(|e| LedgerError::Storage(format!("Invalid device ID: {}", e)))?;
+            habits.push(Habit {
+                name,
+                schedule,
+                created_at,
+                device_id,
+            });
+        }
+        Ok(habits)
+    }
+
+    /// Remove a habit definition. Completion entries are kept.
+    ///
+    /// Returns `LedgerError::NotFound` if the habit doesn't exist.
+    pub fn delete_habit(&mut self, name: &str) -> Result<()> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let deleted = tx.execute("DELETE FROM habits WHERE name = ?", [name])?;
+        if deleted == 0 {
+            return Err(LedgerError::NotFound(format!("Habit '{}' not found", name)));
+        }
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&self.now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
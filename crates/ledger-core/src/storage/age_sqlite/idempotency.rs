@@ -0,0 +1,137 @@
+//! Idempotency keys: scripted operations recorded under a caller-chosen key
+//! so a retried command replays the first result instead of writing twice.
+
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::IdempotencyRecord;
+
+use super::AgeSqliteStorage;
+
+/// Longest accepted key, in bytes.
+const MAX_KEY_BYTES: usize = 256;
+
+impl AgeSqliteStorage {
+    /// The record of `operation` applied under `key`, or `None` if the key
+    /// is unused.
+    ///
+    /// Returns `LedgerError::Validation` if the key is invalid or was used
+    /// for a different operation.
+    pub fn idempotency_record(
+        &self,
+        key: &str,
+        operation: &str,
+    ) -> Result<Option<IdempotencyRecord>> {
+        validate_key(key)?;
+        let conn = self.lock_conn()?;
+        let row: Option<(String, Option<String>, Option<String>, String)> = conn
+            .query_row(
+                "SELECT operation, entry_id, result_json, created_at \
+                 FROM idempotency_keys WHERE key = ?",
+                [key],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()?;
+        let Some((recorded, entry_id, result_json, created_at)) = row else {
+            return Ok(None);
+        };
+        if recorded != operation {
+            return Err(LedgerError::Validation(format!(
+                "Idempotency key '{}' was already used for {}",
+                key, recorded
+            )));
+        }
+        let entry_id = entry_id
+            .map(|id| Uuid::parse_str(&id))
+            .transpose()
+            .map_err(|e| LedgerError::Storage(format!("Invalid UUID: {}", e)))?;
+        let result = result_json
+            .map(|json| serde_json::from_str(&json))
+            .transpose()?;
+        let created_at = DateTime::parse_from_rfc3339(&created_at)
+            .map_err(|e| LedgerError::Storage(format!("Invalid timestamp: {}", e)))?
+            .with_timezone(&Utc);
+        Ok(Some(IdempotencyRecord {
+            key: key.to_string(),
+            operation: recorded,
+            entry_id,
+            result,
+            created_at,
+        }))
+    }
+
+    /// Record that `operation` was applied under `key`, with the entry it
+    /// created and a result to replay.
+    ///
+    /// Returns `LedgerError::Validation` if the key is invalid or already
+    /// recorded, and `LedgerError::EntryNotFound` if the entry doesn't exist.
+    pub fn record_idempotency_key(
+        &mut self,
+        key: &str,
+        operation: &str,
+        entry_id: Option<&Uuid>,
+        result: Option<&serde_json::Value>,
+    ) -> Result<()> {
+        validate_key(key)?;
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+
+        let used: bool = tx.query_row(
+            "SELECT COUNT(*) > 0 FROM idempotency_keys WHERE key = ?",
+            [key],
+            |row| row.get(0),
+        )?;
+        if used {
+            return Err(LedgerError::Validation(format!(
+                "Idempotency key '{}' is already recorded",
+                key
+            )));
+        }
+        if let Some(entry_id) = entry_id {
+            let exists: bool = tx.query_row(
+                "SELECT COUNT(*) > 0 FROM entries WHERE id = ?",
+                [entry_id.to_string()],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                return Err(LedgerError::EntryNotFound(*entry_id));
+            }
+        }
+
+        let now = self.now().to_rfc3339();
+        tx.execute(
+            "INSERT INTO idempotency_keys (key, operation, entry_id, result_json, created_at) \
+             VALUES (?, ?, ?, ?, ?)",
+            (
+                key,
+                operation,
+                entry_id.map(|id| id.to_string()),
+                result.map(serde_json::Value::to_string),
+                &now,
+            ),
+        )?;
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&now],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn validate_key(key: &str) -> Result<()> {
+    if key.trim().is_empty() {
+        return Err(LedgerError::Validation(
+            "Idempotency key cannot be empty".to_string(),
+        ));
+    }
+    if key.len() > MAX_KEY_BYTES {
+        return Err(LedgerError::Validation(format!(
+            "Idempotency key is longer than {} bytes",
+            MAX_KEY_BYTES
+        )));
+    }
+    Ok(())
+}
@@ -4,7 +4,30 @@
 //! passphrase encryption. The database is held in memory and serialized
 //! to disk with encryption on close.
 
+mod access;
+mod aggregate;
+mod attachments;
+mod bundle;
+mod daily;
+mod day_numbers;
+mod drafts;
+mod habits;
+mod idempotency;
+mod origin;
+mod overview;
+mod packs;
+mod peers;
+mod recover;
+mod related;
+mod retire;
+mod rotation;
 mod row;
+mod scratch;
+mod search;
+mod stream;
+mod tags;
+mod upcoming;
+mod usage;
 mod validation;
 
 use std::collections::HashSet;
@@ -12,35 +35,400 @@ use std::fs;
 use std::fs::OpenOptions;
 use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
-use std::sync::{Mutex, MutexGuard};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
 use rusqlite::serialize::OwnedData;
 use rusqlite::{Connection, DatabaseName, OptionalExtension};
 use uuid::Uuid;
 
 use crate::crypto::validate_passphrase;
 use crate::error::{LedgerError, Result};
+use crate::storage::clock::{Clock, IdGen, RandomIds, SystemClock};
 use crate::storage::encryption::{decrypt, encrypt};
 use crate::storage::traits::StorageEngine;
 use crate::storage::types::{
-    Composition, CompositionFilter, Entry, EntryComposition, EntryFilter, EntryType,
-    LedgerMetadata, NewComposition, NewEntry, NewEntryType, NewTemplate, Template,
+    Attachment, Composition, CompositionFilter, Entry, EntryComposition, EntryFilter, EntryOrigin,
+    EntryType, IntegrityIssue, IntegritySeverity, LedgerMetadata, NewAttachment, NewComposition,
+    NewEntry, NewEntryType, NewTemplate, SearchTokenizer, Template, TimestampPrecision,
 };
 
-use row::EntryRow;
-use validation::{fts_content_for_entry, normalize_tags, validate_entry_data, MAX_DATA_BYTES};
+use row::{EntryRow, ENTRY_COLUMNS};
+pub use stream::EntryStream;
+use validation::{
+    fts_content_for_entry, normalize_tags, validate_entry_data, validate_schema_annotations,
+    validate_secret_name, MAX_ATTACHMENT_BYTES, MAX_DATA_BYTES, MAX_SECRET_BYTES,
+};
+
+/// Prepared statements kept per connection; enough for every query this
+/// backend issues, so repeated calls in one session never re-prepare.
+const STATEMENT_CACHE_CAPACITY: usize = 128;
+
+/// Ledger format this build writes and the newest it can open.
+const FORMAT_VERSION: &str = "0.1";
+
+/// Tables added after format 0.1 shipped.
+///
+/// Created with `IF NOT EXISTS` so older ledgers pick them up on open.
+const ADDITIVE_SCHEMA: &str = r#"
+    -- Attachment blobs: bytes stored once per BLAKE3 hash
+    CREATE TABLE IF NOT EXISTS attachment_blobs (
+        hash TEXT PRIMARY KEY,
+        size INTEGER NOT NULL,
+        data BLOB NOT NULL,
+        refcount INTEGER NOT NULL
+    );
+
+    -- Attachments: files owned by an entry, pointing at their blob
+    CREATE TABLE IF NOT EXISTS attachments (
+        id TEXT PRIMARY KEY,
+        entry_id TEXT NOT NULL,
+        name TEXT NOT NULL,
+        mime_type TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        data BLOB NOT NULL,
+        created_at TEXT NOT NULL,
+        device_id TEXT NOT NULL,
+        blob_hash TEXT,
+
+        FOREIGN KEY (entry_id) REFERENCES entries(id),
+        FOREIGN KEY (blob_hash) REFERENCES attachment_blobs(hash)
+    );
+
+    CREATE INDEX IF NOT EXISTS attachments_entry ON attachments (entry_id);
+
+    -- Keep "latest revision only" queries off full scans
+    CREATE INDEX IF NOT EXISTS entries_supersedes ON entries (supersedes);
+
+    -- Secrets: named values referenced from config as secret://name
+    CREATE TABLE IF NOT EXISTS secrets (
+        name TEXT PRIMARY KEY,
+        value TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+
+    -- Entry access: when each entry was last shown (opt-in)
+    CREATE TABLE IF NOT EXISTS entry_access (
+        entry_id TEXT PRIMARY KEY,
+        last_accessed TEXT NOT NULL,
+
+        FOREIGN KEY (entry_id) REFERENCES entries(id)
+    );
+
+    -- Habits: names and schedules; completions are `habit` entries
+    CREATE TABLE IF NOT EXISTS habits (
+        name TEXT PRIMARY KEY,
+        schedule TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        device_id TEXT NOT NULL
+    );
+
+    -- Entry type packs: what each installed manifest defined
+    CREATE TABLE IF NOT EXISTS packs (
+        name TEXT PRIMARY KEY,
+        version TEXT NOT NULL,
+        description TEXT,
+        entry_types_json TEXT NOT NULL,
+        templates_json TEXT NOT NULL,
+        installed_at TEXT NOT NULL,
+        device_id TEXT NOT NULL
+    );
+
+    -- Template each entry was created from, for usage counts
+    CREATE TABLE IF NOT EXISTS entry_templates (
+        entry_id TEXT PRIMARY KEY,
+        template_id TEXT NOT NULL,
+        template_version INTEGER,
+
+        FOREIGN KEY (entry_id) REFERENCES entries(id),
+        FOREIGN KEY (template_id) REFERENCES templates(id)
+    );
+
+    CREATE INDEX IF NOT EXISTS entry_templates_template ON entry_templates (template_id);
+
+    -- Retired entry types: no new entries, history kept
+    CREATE TABLE IF NOT EXISTS retired_entry_types (
+        entry_type_id TEXT PRIMARY KEY,
+        retired_at TEXT NOT NULL,
+
+        FOREIGN KEY (entry_type_id) REFERENCES entry_types(id)
+    );
+
+    -- Program, command and host that wrote each entry
+    CREATE TABLE IF NOT EXISTS entry_origins (
+        entry_id TEXT PRIMARY KEY,
+        app TEXT NOT NULL,
+        version TEXT,
+        command TEXT,
+        hostname TEXT,
+
+        FOREIGN KEY (entry_id) REFERENCES entries(id)
+    );
+
+    -- Composition overviews: one markdown page per composition
+    CREATE TABLE IF NOT EXISTS composition_overviews (
+        composition_id TEXT PRIMARY KEY,
+        body TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+
+        FOREIGN KEY (composition_id) REFERENCES compositions(id)
+    );
+
+    -- Idempotency keys: operations already applied, by caller-chosen key
+    CREATE TABLE IF NOT EXISTS idempotency_keys (
+        key TEXT PRIMARY KEY,
+        operation TEXT NOT NULL,
+        entry_id TEXT,
+        result_json TEXT,
+        created_at TEXT NOT NULL,
+
+        FOREIGN KEY (entry_id) REFERENCES entries(id)
+    );
+
+    -- Per-day entry numbers (2025-02-03#4); revisions share their chain's number
+    CREATE TABLE IF NOT EXISTS entry_day_numbers (
+        entry_id TEXT PRIMARY KEY,
+        day TEXT NOT NULL,
+        number INTEGER NOT NULL,
+
+        FOREIGN KEY (entry_id) REFERENCES entries(id)
+    );
+
+    CREATE INDEX IF NOT EXISTS entry_day_numbers_day ON entry_day_numbers (day, number);
+
+    -- Drafts: unpublished entries, kept out of list, search and export
+    CREATE TABLE IF NOT EXISTS drafts (
+        id TEXT PRIMARY KEY,
+        entry_type_id TEXT NOT NULL,
+        data_json TEXT NOT NULL,
+        tags_json TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        device_id TEXT NOT NULL,
+
+        FOREIGN KEY (entry_type_id) REFERENCES entry_types(id)
+    );
+
+    -- Sync peers: ledgers this one exchanges change bundles with
+    CREATE TABLE IF NOT EXISTS sync_peers (
+        name TEXT PRIMARY KEY,
+        fingerprint TEXT NOT NULL,
+        added_at TEXT NOT NULL,
+        last_sent_at TEXT,
+        last_received_at TEXT,
+        last_sent_seq INTEGER
+    );
+"#;
+
+/// Refuse ledgers written by a newer format than [`FORMAT_VERSION`], before
+/// anything is added to their schema.
+fn check_format_version(conn: &Connection) -> Result<()> {
+    let found: String = conn.query_row(
+        "SELECT value FROM meta WHERE key = 'format_version'",
+        [],
+        |row| row.get(0),
+    )?;
+    let parse = |version: &str| -> Option<(u32, u32)> {
+        let (major, minor) = version.trim().split_once('.')?;
+        Some((major.parse().ok()?, minor.parse().ok()?))
+    };
+    match (parse(&found), parse(FORMAT_VERSION)) {
+        (Some(found), Some(supported)) if found <= supported => Ok(()),
+        _ => Err(LedgerError::VersionMismatch {
+            found,
+            supported: FORMAT_VERSION.to_string(),
+        }),
+    }
+}
+
+/// Give ledgers written before `entries.seq` existed an insertion order.
+///
+/// Existing rows take their rowid, which is the order they were inserted in.
+fn migrate_entry_seq(conn: &Connection) -> Result<()> {
+    let has_seq: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('entries') WHERE name = 'seq'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_seq {
+        conn.execute_batch(
+            "ALTER TABLE entries ADD COLUMN seq INTEGER; UPDATE entries SET seq = rowid;",
+        )?;
+    }
+    // Keeps "newest N" queries off full scans
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS entries_created_at;
+         CREATE INDEX IF NOT EXISTS entries_order ON entries (created_at, seq);",
+    )?;
+    Ok(())
+}
+
+/// Give `entry_templates` from before template versions were recorded a
+/// `template_version` column; those rows keep no version.
+fn migrate_entry_template_version(conn: &Connection) -> Result<()> {
+    let has_version: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('entry_templates') \
+         WHERE name = 'template_version'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_version {
+        conn.execute_batch("ALTER TABLE entry_templates ADD COLUMN template_version INTEGER;")?;
+    }
+    Ok(())
+}
+
+/// Turn the `entry_compositions` join table from before membership history
+/// into the append-only shape: memberships gain `removed_at`, and the
+/// primary key becomes a unique index over current memberships only.
+fn migrate_membership_history(conn: &Connection) -> Result<()> {
+    let has_removed_at: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('entry_compositions') \
+         WHERE name = 'removed_at'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_removed_at {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE entry_compositions_history (
+                entry_id TEXT NOT NULL,
+                composition_id TEXT NOT NULL,
+                added_at TEXT NOT NULL,
+                removed_at TEXT,
+
+                FOREIGN KEY (entry_id) REFERENCES entries(id),
+                FOREIGN KEY (composition_id) REFERENCES compositions(id)
+            );
+            INSERT INTO entry_compositions_history (entry_id, composition_id, added_at)
+            SELECT entry_id, composition_id, added_at FROM entry_compositions;
+            DROP TABLE entry_compositions;
+            ALTER TABLE entry_compositions_history RENAME TO entry_compositions;
+            CREATE UNIQUE INDEX entry_compositions_active
+            ON entry_compositions (entry_id, composition_id)
+            WHERE removed_at IS NULL;
+            "#,
+        )?;
+    }
+    Ok(())
+}
+
+/// Dependents named when a delete is refused; the rest are only counted.
+const LISTED_DEPENDENTS: usize = 5;
+
+/// The first few of `ids`, then how many more there are.
+fn listed(ids: &[String]) -> String {
+    let mut list = ids[..ids.len().min(LISTED_DEPENDENTS)].join(", ");
+    if ids.len() > LISTED_DEPENDENTS {
+        list.push_str(&format!(" and {} more", ids.len() - LISTED_DEPENDENTS));
+    }
+    list
+}
 
 /// Age-encrypted SQLite storage engine.
 pub struct AgeSqliteStorage {
     path: PathBuf,
     conn: Mutex<Connection>,
-    #[allow(dead_code)]
     device_id: Uuid,
+    verify_after_write: bool,
+    timestamp_precision: TimestampPrecision,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGen>,
+    origin: Option<EntryOrigin>,
+    day_offset: FixedOffset,
 }
 
 impl AgeSqliteStorage {
+    /// Re-read and verify the ledger file after every `close()`.
+    ///
+    /// The written file is decrypted again and compared byte-for-byte with the
+    /// serialized database, then loaded to confirm the entry count and
+    /// `last_modified` survived, so a silently failed write surfaces at once.
+    pub fn set_verify_after_write(&mut self, enabled: bool) {
+        self.verify_after_write = enabled;
+    }
+
+    /// Truncate the timestamps of entries inserted from now on.
+    ///
+    /// Entries that end up with equal timestamps keep their insertion order.
+    pub fn set_timestamp_precision(&mut self, precision: TimestampPrecision) {
+        self.timestamp_precision = precision;
+    }
+
+    /// Read the time from `clock` from now on, for timestamps and
+    /// `last_modified`.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Take the ids of records created from now on from `ids`.
+    pub fn set_id_gen(&mut self, ids: Arc<dyn IdGen>) {
+        self.ids = ids;
+    }
+
+    /// Current time from the storage clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    fn new_id(&self) -> Uuid {
+        self.ids.new_id()
+    }
+
+    /// Entry count and `last_modified`, used to confirm a written file reloads.
+    fn write_snapshot(conn: &Connection) -> Result<(i64, String)> {
+        let entries = conn.query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))?;
+        let last_modified = conn.query_row(
+            "SELECT value FROM meta WHERE key = 'last_modified'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok((entries, last_modified))
+    }
+
+    /// Check that the file at `path` decrypts to `expected` and reloads with
+    /// the same entry count and modification time.
+    fn verify_written(
+        path: &Path,
+        passphrase: &str,
+        expected: &[u8],
+        (entries, last_modified): (i64, String),
+    ) -> Result<()> {
+        let fail = |reason: String| {
+            LedgerError::Storage(format!("Verification after write failed: {}", reason))
+        };
+
+        let encrypted = fs::read(path).map_err(|e| fail(format!("re-read failed: {}", e)))?;
+        let plaintext = decrypt(&encrypted, passphrase)
+            .map_err(|e| fail(format!("re-decrypt failed: {}", e)))?;
+        if plaintext != expected {
+            return Err(fail("file contents differ from the database".to_string()));
+        }
+
+        let mut written = Connection::open_in_memory()?;
+        written.deserialize(
+            DatabaseName::Main,
+            Self::owned_data_from_bytes(&plaintext)?,
+            false,
+        )?;
+        let (written_entries, written_modified) =
+            Self::write_snapshot(&written).map_err(|e| fail(format!("reload failed: {}", e)))?;
+        if written_entries != entries {
+            return Err(fail(format!(
+                "expected {} entries, found {}",
+                entries, written_entries
+            )));
+        }
+        if written_modified != last_modified {
+            return Err(fail(format!(
+                "expected last_modified {}, found {}",
+                last_modified, written_modified
+            )));
+        }
+        Ok(())
+    }
+
     /// Lock the database connection, returning an error if the mutex is poisoned.
     fn lock_conn(&self) -> Result<MutexGuard<'_, Connection>> {
         self.conn
@@ -88,7 +476,25 @@ impl AgeSqliteStorage {
         }
     }
 
+    /// Write `data` via a synced temp file renamed over the ledger.
+    ///
+    /// Symlinks are written through to their target, the parent directory is
+    /// synced after the rename, and ledgers on network or synced folders take
+    /// a `<ledger>.lock` file for the duration of the write.
     fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+        let path = &crate::fs::write_target(path);
+        let _lock = if crate::fs::path_risks(path)
+            .iter()
+            .any(|risk| risk.is_concurrent_write_risk())
+        {
+            Some(
+                crate::fs::WriteLock::acquire(path)
+                    .map_err(|e| LedgerError::LockHeld(e.to_string()))?,
+            )
+        } else {
+            None
+        };
+
         let parent = path
             .parent()
             .ok_or_else(|| LedgerError::Storage("Invalid ledger path".to_string()))?;
@@ -116,11 +522,118 @@ impl AgeSqliteStorage {
 
         crate::fs::rename_with_fallback(&temp_path, path)
             .map_err(|e| LedgerError::Storage(format!("Atomic rename failed: {}", e)))?;
+        crate::fs::sync_dir(parent)
+            .map_err(|e| LedgerError::Storage(format!("Directory sync failed: {}", e)))?;
 
         Ok(())
     }
 }
 
+/// Boxed positional parameters for a dynamically built query.
+type SqlParams = Vec<Box<dyn rusqlite::ToSql>>;
+
+/// SQL conditions (over `entries e`) and parameters for an entry filter.
+///
+/// `filter.limit` is left to the caller, which knows where `LIMIT` goes.
+fn entry_filter_conditions(filter: &EntryFilter) -> Result<(Vec<String>, SqlParams)> {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut params: SqlParams = Vec::new();
+
+    if let Some(entry_type_id) = filter.entry_type_id {
+        conditions.push("e.entry_type_id = ?".to_string());
+        params.push(Box::new(entry_type_id.to_string()));
+    }
+
+    if let Some(ref tag) = filter.tag {
+        // `work/` selects `work` and everything nested under it
+        let (tag, nested) = match tag.trim().strip_suffix('/') {
+            Some(parent) => (parent.to_string(), true),
+            None => (tag.clone(), false),
+        };
+        let (condition, tag_params) = tag_condition(&tag, nested)?;
+        conditions.push(format!(
+            "e.tags_json IS NOT NULL AND EXISTS (SELECT 1 FROM json_each(e.tags_json) WHERE {})",
+            condition
+        ));
+        params.extend(tag_params);
+    }
+
+    for tag in &filter.exclude_tags {
+        // A later revision that adds the tag hides the earlier ones too
+        let (condition, tag_params) = tag_condition(tag.trim().trim_end_matches('/'), true)?;
+        conditions.push(format!(
+            "NOT EXISTS (WITH RECURSIVE chain(id, tags_json) AS (\
+             SELECT e.id, e.tags_json \
+             UNION SELECT s.id, s.tags_json FROM entries s JOIN chain c ON s.supersedes = c.id) \
+             SELECT 1 FROM chain, json_each(chain.tags_json) WHERE {})",
+            condition
+        ));
+        params.extend(tag_params);
+    }
+
+    if let Some(since) = filter.since {
+        conditions.push("e.created_at >= ?".to_string());
+        params.push(Box::new(since.to_rfc3339()));
+    }
+
+    if let Some(until) = filter.until {
+        conditions.push("e.created_at <= ?".to_string());
+        params.push(Box::new(until.to_rfc3339()));
+    }
+
+    if let Some(composition_id) = filter.composition_id {
+        conditions.push(
+            "EXISTS (SELECT 1 FROM entry_compositions ec WHERE ec.entry_id = e.id AND ec.composition_id = ? AND ec.removed_at IS NULL)"
+                .to_string(),
+        );
+        params.push(Box::new(composition_id.to_string()));
+    }
+
+    if let Some(ref origin) = filter.origin {
+        conditions.push(
+            "EXISTS (SELECT 1 FROM entry_origins o WHERE o.entry_id = e.id AND ? IN (o.app, o.command, o.hostname))"
+                .to_string(),
+        );
+        params.push(Box::new(origin.clone()));
+    }
+
+    if filter.latest_only {
+        conditions
+            .push("NOT EXISTS (SELECT 1 FROM entries s WHERE s.supersedes = e.id)".to_string());
+    }
+
+    Ok((conditions, params))
+}
+
+/// A condition on a `json_each` `value` matching a tag, or with `nested`
+/// also the tags beneath it.
+fn tag_condition(tag: &str, nested: bool) -> Result<(String, SqlParams)> {
+    let normalized = normalize_tags(&[tag.to_string()])?;
+    let normalized_tag = normalized
+        .first()
+        .ok_or_else(|| LedgerError::Validation("Invalid tag filter".to_string()))?
+        .clone();
+    if !nested {
+        return Ok(("value = ?".to_string(), vec![Box::new(normalized_tag)]));
+    }
+    let prefix = format!("{}/", normalized_tag);
+    Ok((
+        "(value = ? OR substr(value, 1, ?) = ?)".to_string(),
+        vec![
+            Box::new(normalized_tag),
+            Box::new(prefix.len() as i64),
+            Box::new(prefix),
+        ],
+    ))
+}
+
+/// Collect the first column of every row as strings.
+fn query_strings(conn: &Connection, sql: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
 impl StorageEngine for AgeSqliteStorage {
     fn create(path: &Path, passphrase: &str) -> Result<Uuid> {
         if path.exists() {
@@ -171,16 +684,11 @@ impl StorageEngine for AgeSqliteStorage {
                 created_at TEXT NOT NULL,
                 device_id TEXT NOT NULL,
                 supersedes TEXT,
+                seq INTEGER,
 
                 FOREIGN KEY(entry_type_id) REFERENCES entry_types(id)
             );
 
-            CREATE VIRTUAL TABLE entries_fts USING fts5(
-                entry_id UNINDEXED,
-                content,
-                tokenize = 'porter'
-            );
-
             -- Compositions: semantic grouping of entries
             CREATE TABLE compositions (
                 id TEXT PRIMARY KEY,
@@ -191,17 +699,22 @@ impl StorageEngine for AgeSqliteStorage {
                 metadata_json TEXT
             );
 
-            -- Entry-Composition join table (many-to-many)
+            -- Entry-Composition memberships (many-to-many, append-only)
             CREATE TABLE entry_compositions (
                 entry_id TEXT NOT NULL,
                 composition_id TEXT NOT NULL,
                 added_at TEXT NOT NULL,
+                removed_at TEXT,
 
-                PRIMARY KEY (entry_id, composition_id),
                 FOREIGN KEY (entry_id) REFERENCES entries(id),
                 FOREIGN KEY (composition_id) REFERENCES compositions(id)
             );
 
+            -- An entry is a current member of a composition at most once
+            CREATE UNIQUE INDEX entry_compositions_active
+            ON entry_compositions (entry_id, composition_id)
+            WHERE removed_at IS NULL;
+
             -- Templates: reusable defaults for entry creation
             CREATE TABLE templates (
                 id TEXT PRIMARY KEY,
@@ -244,12 +757,20 @@ impl StorageEngine for AgeSqliteStorage {
             WHERE active = 1;
             "#,
         )?;
+        conn.execute_batch(&search::fts_table_sql(SearchTokenizer::default()))?;
+        conn.execute_batch(ADDITIVE_SCHEMA)?;
+        migrate_entry_seq(&conn)?;
+        migrate_entry_template_version(&conn)?;
+        migrate_membership_history(&conn)?;
+        attachments::migrate_attachment_blobs(&conn)?;
+        peers::migrate_peer_sent_seq(&conn)?;
+        day_numbers::migrate_day_numbers(&conn)?;
 
         // Insert metadata
         let created_at = chrono::Utc::now().to_rfc3339();
         conn.execute(
             "INSERT INTO meta (key, value) VALUES (?, ?)",
-            ["format_version", "0.1"],
+            ["format_version", FORMAT_VERSION],
         )?;
         conn.execute(
             "INSERT INTO meta (key, value) VALUES (?, ?)",
@@ -263,6 +784,10 @@ impl StorageEngine for AgeSqliteStorage {
             "INSERT INTO meta (key, value) VALUES (?, ?)",
             ["last_modified", &created_at],
         )?;
+        conn.execute(
+            "INSERT INTO meta (key, value) VALUES (?, ?)",
+            ["search_tokenizer", SearchTokenizer::default().as_str()],
+        )?;
 
         // Serialize and encrypt
         let plaintext = Self::empty_payload(&conn)?;
@@ -282,9 +807,18 @@ impl StorageEngine for AgeSqliteStorage {
         let encrypted = fs::read(path)?;
         let plaintext = decrypt(&encrypted, passphrase)?;
         let mut conn = Connection::open_in_memory()?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
         let owned_data = Self::owned_data_from_bytes(&plaintext)?;
         conn.deserialize(DatabaseName::Main, owned_data, false)?;
+        check_format_version(&conn)?;
+        conn.execute_batch(ADDITIVE_SCHEMA)?;
+        migrate_entry_seq(&conn)?;
+        migrate_entry_template_version(&conn)?;
+        migrate_membership_history(&conn)?;
+        attachments::migrate_attachment_blobs(&conn)?;
+        peers::migrate_peer_sent_seq(&conn)?;
+        let day_offset = day_numbers::migrate_day_numbers(&conn)?;
 
         // Read device_id from metadata
         let device_id_str: String = conn.query_row(
@@ -299,18 +833,47 @@ impl StorageEngine for AgeSqliteStorage {
             path: path.to_path_buf(),
             conn: Mutex::new(conn),
             device_id,
+            verify_after_write: false,
+            timestamp_precision: TimestampPrecision::default(),
+            clock: Arc::new(SystemClock),
+            ids: Arc::new(RandomIds),
+            origin: None,
+            day_offset,
         })
     }
 
+    fn batch<T, F>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        // Each operation opens its own savepoint, which nests inside this one
+        self.lock_conn()?.execute_batch("SAVEPOINT batch")?;
+        let result = f(self);
+        let end = match result {
+            Ok(_) => "RELEASE batch",
+            Err(_) => "ROLLBACK TO batch; RELEASE batch",
+        };
+        self.lock_conn()?.execute_batch(end)?;
+        result
+    }
+
     fn close(self, passphrase: &str) -> Result<()> {
         validate_passphrase(passphrase)?;
         let conn = self
             .conn
             .into_inner()
             .map_err(|_| LedgerError::Storage("SQLite connection poisoned".to_string()))?;
+        let snapshot = if self.verify_after_write {
+            Some(Self::write_snapshot(&conn)?)
+        } else {
+            None
+        };
         let data = conn.serialize(DatabaseName::Main)?;
         let encrypted = encrypt(data.as_ref(), passphrase)?;
         Self::write_atomic(&self.path, &encrypted)?;
+        if let Some(snapshot) = snapshot {
+            Self::verify_written(&self.path, passphrase, data.as_ref(), snapshot)?;
+        }
         Ok(())
     }
 
@@ -352,19 +915,27 @@ impl StorageEngine for AgeSqliteStorage {
     fn insert_entry(&mut self, entry: &NewEntry) -> Result<Uuid> {
         let mut conn = self.lock_conn()?;
 
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
-        let exists: Option<String> = tx
+        let entry_type: Option<(String, bool)> = tx
             .query_row(
-                "SELECT id FROM entry_types WHERE id = ?",
+                "SELECT name, id IN (SELECT entry_type_id FROM retired_entry_types) \
+                 FROM entry_types WHERE id = ?",
                 [entry.entry_type_id.to_string()],
-                |row| row.get(0),
+                |row| Ok((row.get(0)?, row.get(1)?)),
             )
             .optional()?;
-        if exists.is_none() {
+        let Some((entry_type_name, retired)) = entry_type else {
             return Err(LedgerError::Validation(
                 "Entry type does not exist".to_string(),
             ));
+        };
+        // Revisions keep retired history editable
+        if retired && entry.supersedes.is_none() {
+            return Err(LedgerError::Validation(format!(
+                "Entry type '{}' is retired and takes no new entries",
+                entry_type_name
+            )));
         }
 
         let schema_json: Option<String> = tx
@@ -404,10 +975,12 @@ impl StorageEngine for AgeSqliteStorage {
             )));
         }
 
-        let id = Uuid::new_v4();
-        let created_at = entry.created_at.unwrap_or_else(Utc::now);
+        let id = self.new_id();
+        let created_at = self
+            .timestamp_precision
+            .truncate(entry.created_at.unwrap_or_else(|| self.now()));
         let created_at_str = created_at.to_rfc3339();
-        let last_modified = Utc::now().to_rfc3339();
+        let last_modified = self.now().to_rfc3339();
 
         tx.execute(
             r#"
@@ -419,9 +992,10 @@ impl StorageEngine for AgeSqliteStorage {
                 tags_json,
                 created_at,
                 device_id,
-                supersedes
+                supersedes,
+                seq
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, (SELECT COALESCE(MAX(seq), 0) + 1 FROM entries))
             "#,
             (
                 id.to_string(),
@@ -435,11 +1009,13 @@ impl StorageEngine for AgeSqliteStorage {
             ),
         )?;
 
-        let fts_content = fts_content_for_entry(&entry.data);
+        let fts_content = fts_content_for_entry(&schema_value, &entry.data);
         tx.execute(
             "INSERT INTO entries_fts (entry_id, content) VALUES (?, ?)",
             (id.to_string(), fts_content),
         )?;
+        self.record_origin(&tx, &id)?;
+        day_numbers::assign_missing_day_numbers(&tx, self.day_offset)?;
 
         tx.execute(
             "UPDATE meta SET value = ? WHERE key = 'last_modified'",
@@ -454,212 +1030,113 @@ impl StorageEngine for AgeSqliteStorage {
     fn get_entry(&self, id: &Uuid) -> Result<Option<Entry>> {
         let conn = self.lock_conn()?;
 
-        let result = conn.query_row(
-            r#"
-            SELECT id, entry_type_id, schema_version, data_json, tags_json, created_at, device_id, supersedes
-            FROM entries
-            WHERE id = ?
-            "#,
-            [id.to_string()],
-            |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, i32>(2)?,
-                    row.get::<_, String>(3)?,
-                    row.get::<_, Option<String>>(4)?,
-                    row.get::<_, String>(5)?,
-                    row.get::<_, String>(6)?,
-                    row.get::<_, Option<String>>(7)?,
-                ))
-            },
-        );
-
-        match result {
-            Ok((
-                id,
-                entry_type_id,
-                schema_version,
-                data_json,
-                tags_json,
-                created_at,
-                device_id,
-                supersedes,
-            )) => {
-                let row = EntryRow {
-                    id,
-                    entry_type_id,
-                    schema_version,
-                    data_json,
-                    tags_json,
-                    created_at,
-                    device_id,
-                    supersedes,
-                };
-                Ok(Some(row.try_into()?))
-            }
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e.into()),
-        }
+        let row = conn
+            .query_row(
+                &format!("SELECT {} FROM entries e WHERE e.id = ?", ENTRY_COLUMNS),
+                [id.to_string()],
+                EntryRow::from_row,
+            )
+            .optional()?;
+        row.map(Entry::try_from).transpose()
     }
 
     fn list_entries(&self, filter: &EntryFilter) -> Result<Vec<Entry>> {
         let conn = self.lock_conn()?;
+        let (conditions, mut params) = entry_filter_conditions(filter)?;
 
-        let mut conditions: Vec<String> = Vec::new();
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-        if let Some(entry_type_id) = filter.entry_type_id {
-            conditions.push("e.entry_type_id = ?".to_string());
-            params.push(Box::new(entry_type_id.to_string()));
-        }
-
-        if let Some(ref tag) = filter.tag {
-            let normalized = normalize_tags(std::slice::from_ref(tag))?;
-            let normalized_tag = normalized
-                .first()
-                .ok_or_else(|| LedgerError::Validation("Invalid tag filter".to_string()))?
-                .clone();
-            conditions.push(
-                "e.tags_json IS NOT NULL AND EXISTS (SELECT 1 FROM json_each(e.tags_json) WHERE value = ?)"
-                    .to_string(),
-            );
-            params.push(Box::new(normalized_tag));
-        }
-
-        if let Some(since) = filter.since {
-            conditions.push("e.created_at >= ?".to_string());
-            params.push(Box::new(since.to_rfc3339()));
-        }
-
-        if let Some(until) = filter.until {
-            conditions.push("e.created_at <= ?".to_string());
-            params.push(Box::new(until.to_rfc3339()));
-        }
-
-        if let Some(composition_id) = filter.composition_id {
-            conditions.push(
-                "EXISTS (SELECT 1 FROM entry_compositions ec WHERE ec.entry_id = e.id AND ec.composition_id = ?)"
-                    .to_string(),
-            );
-            params.push(Box::new(composition_id.to_string()));
-        }
-
-        let mut query = String::from(
-            "SELECT e.id, e.entry_type_id, e.schema_version, e.data_json, e.tags_json, e.created_at, e.device_id, e.supersedes FROM entries e",
-        );
+        let mut query = format!("SELECT {} FROM entries e", ENTRY_COLUMNS);
         if !conditions.is_empty() {
             query.push_str(" WHERE ");
             query.push_str(&conditions.join(" AND "));
         }
-        query.push_str(" ORDER BY e.created_at DESC");
+        query.push_str(if filter.oldest_first {
+            " ORDER BY e.created_at ASC, e.seq ASC"
+        } else {
+            " ORDER BY e.created_at DESC, e.seq DESC"
+        });
 
         if let Some(limit) = filter.limit {
             query.push_str(" LIMIT ?");
             params.push(Box::new(limit as i64));
         }
 
-        let mut stmt = conn.prepare(&query)?;
-        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, i32>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, Option<String>>(4)?,
-                row.get::<_, String>(5)?,
-                row.get::<_, String>(6)?,
-                row.get::<_, Option<String>>(7)?,
-            ))
-        })?;
-
-        let mut entries = Vec::new();
-        for row in rows {
-            let (
-                id,
-                entry_type_id,
-                schema_version,
-                data_json,
-                tags_json,
-                created_at,
-                device_id,
-                supersedes,
-            ) = row?;
-            let entry_row = EntryRow {
-                id,
-                entry_type_id,
-                schema_version,
-                data_json,
-                tags_json,
-                created_at,
-                device_id,
-                supersedes,
-            };
-            entries.push(entry_row.try_into()?);
-        }
+        let mut stmt = conn.prepare_cached(&query)?;
+        let rows = stmt.query_map(
+            rusqlite::params_from_iter(params.iter()),
+            EntryRow::from_row,
+        )?;
+        rows.map(|row| Entry::try_from(row?)).collect()
+    }
 
-        Ok(entries)
+    fn iter_entries(&self, filter: &EntryFilter) -> impl Iterator<Item = Result<Entry>> + '_ {
+        EntryStream::new(self, filter)
     }
 
     fn search_entries(&self, query: &str) -> Result<Vec<Entry>> {
+        self.search_entries_filtered(query, &EntryFilter::new())
+    }
+
+    fn search_entries_filtered(&self, query: &str, filter: &EntryFilter) -> Result<Vec<Entry>> {
         let conn = self.lock_conn()?;
 
-        let mut stmt = conn.prepare(
+        // Trigram can't match terms shorter than three characters, which
+        // covers most two-character CJK words; scan the indexed text instead
+        let short_terms = match search::stored_tokenizer(&conn)? {
+            SearchTokenizer::Trigram => search::short_trigram_terms(query),
+            _ => None,
+        };
+        let (condition, order, terms) = match short_terms {
+            Some(terms) => (
+                vec!["f.content LIKE ? ESCAPE '\\'"; terms.len()].join(" AND "),
+                "e.created_at DESC, e.seq DESC",
+                terms
+                    .iter()
+                    .map(|term| search::like_pattern(term))
+                    .collect(),
+            ),
+            None => (
+                "entries_fts MATCH ?".to_string(),
+                "bm25(entries_fts), e.created_at DESC, e.seq DESC",
+                vec![query.to_string()],
+            ),
+        };
+        let (mut conditions, filter_params) = entry_filter_conditions(filter)?;
+        conditions.insert(0, condition);
+        let mut params: SqlParams = terms
+            .into_iter()
+            .map(|term| Box::new(term) as Box<dyn rusqlite::ToSql>)
+            .collect();
+        params.extend(filter_params);
+        let mut limit = String::new();
+        if let Some(max) = filter.limit {
+            limit.push_str(" LIMIT ?");
+            params.push(Box::new(max as i64));
+        }
+        let mut stmt = conn.prepare_cached(&format!(
             r#"
-                SELECT e.id, e.entry_type_id, e.schema_version, e.data_json, e.tags_json,
-                       e.created_at, e.device_id, e.supersedes
+                SELECT {}
                 FROM entries_fts f
                 JOIN entries e ON e.id = f.entry_id
-                WHERE entries_fts MATCH ?
-                ORDER BY bm25(entries_fts), e.created_at DESC
+                WHERE {}
+                ORDER BY {}{}
                 "#,
+            ENTRY_COLUMNS,
+            conditions.join(" AND "),
+            order,
+            limit
+        ))?;
+        let rows = stmt.query_map(
+            rusqlite::params_from_iter(params.iter()),
+            EntryRow::from_row,
         )?;
-
-        let rows = stmt.query_map([query], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, i32>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, Option<String>>(4)?,
-                row.get::<_, String>(5)?,
-                row.get::<_, String>(6)?,
-                row.get::<_, Option<String>>(7)?,
-            ))
-        })?;
-
-        let mut entries = Vec::new();
-        for row in rows {
-            let (
-                id,
-                entry_type_id,
-                schema_version,
-                data_json,
-                tags_json,
-                created_at,
-                device_id,
-                supersedes,
-            ) = row?;
-            let entry_row = EntryRow {
-                id,
-                entry_type_id,
-                schema_version,
-                data_json,
-                tags_json,
-                created_at,
-                device_id,
-                supersedes,
-            };
-            entries.push(entry_row.try_into()?);
-        }
-
-        Ok(entries)
+        rows.map(|row| Entry::try_from(row?)).collect()
     }
 
     fn superseded_entry_ids(&self) -> Result<HashSet<Uuid>> {
         let conn = self.lock_conn()?;
-        let mut stmt =
-            conn.prepare("SELECT DISTINCT supersedes FROM entries WHERE supersedes IS NOT NULL")?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT DISTINCT supersedes FROM entries WHERE supersedes IS NOT NULL",
+        )?;
         let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
         let mut ids = HashSet::new();
         for row in rows {
@@ -730,9 +1207,10 @@ impl StorageEngine for AgeSqliteStorage {
     }
 
     fn create_entry_type(&mut self, entry_type: &NewEntryType) -> Result<Uuid> {
+        validate_schema_annotations(&entry_type.schema_json)?;
         let mut conn = self.lock_conn()?;
 
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
         // Check if entry type with this name already exists
         let base_type_id: Option<String> = tx
@@ -755,8 +1233,8 @@ impl StorageEngine for AgeSqliteStorage {
             (base_id, max_version + 1)
         } else {
             // New entry type, create base record
-            let base_id = Uuid::new_v4();
-            let created_at = Utc::now().to_rfc3339();
+            let base_id = self.new_id();
+            let created_at = self.now().to_rfc3339();
             tx.execute(
                 "INSERT INTO entry_types (id, name, created_at, device_id) VALUES (?, ?, ?, ?)",
                 (
@@ -776,8 +1254,8 @@ impl StorageEngine for AgeSqliteStorage {
         )?;
 
         // Create version record
-        let version_id = Uuid::new_v4();
-        let created_at = Utc::now().to_rfc3339();
+        let version_id = self.new_id();
+        let created_at = self.now().to_rfc3339();
         let schema_json_str = serde_json::to_string(&entry_type.schema_json)
             .map_err(|e| LedgerError::Storage(format!("Failed to serialize schema: {}", e)))?;
 
@@ -809,7 +1287,7 @@ impl StorageEngine for AgeSqliteStorage {
     fn list_entry_types(&self) -> Result<Vec<EntryType>> {
         let conn = self.lock_conn()?;
 
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             r#"
                 SELECT et.id, et.name, etv.version, etv.created_at, et.device_id, etv.schema_json
                 FROM entry_type_versions etv
@@ -868,69 +1346,363 @@ impl StorageEngine for AgeSqliteStorage {
         Ok(entry_types)
     }
 
-    fn check_integrity(&self) -> Result<()> {
+    // --- Attachment operations ---
+
+    fn add_attachment(&mut self, attachment: &NewAttachment) -> Result<Uuid> {
+        if attachment.name.trim().is_empty() {
+            return Err(LedgerError::Validation(
+                "Attachment name cannot be empty".to_string(),
+            ));
+        }
+        if attachment.data.len() > MAX_ATTACHMENT_BYTES {
+            return Err(LedgerError::Validation(format!(
+                "Attachment exceeds max size ({} bytes)",
+                MAX_ATTACHMENT_BYTES
+            )));
+        }
+
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+
+        let entry_exists: Option<String> = tx
+            .query_row(
+                "SELECT id FROM entries WHERE id = ?",
+                [attachment.entry_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if entry_exists.is_none() {
+            return Err(LedgerError::NotFound(format!(
+                "Entry {} not found",
+                attachment.entry_id
+            )));
+        }
+
+        let id = self.new_id();
+        let created_at = self.now().to_rfc3339();
+        let blob_hash = attachments::store_blob(&tx, &attachment.data)?;
+
+        tx.execute(
+            "INSERT INTO attachments (id, entry_id, name, mime_type, size, data, created_at, device_id, blob_hash) VALUES (?, ?, ?, ?, ?, x'', ?, ?, ?)",
+            rusqlite::params![
+                id.to_string(),
+                attachment.entry_id.to_string(),
+                attachment.name,
+                attachment.mime_type,
+                attachment.data.len() as i64,
+                created_at,
+                attachment.device_id.to_string(),
+                blob_hash,
+            ],
+        )?;
+
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&created_at],
+        )?;
+
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn list_attachments(&self, entry_id: &Uuid) -> Result<Vec<Attachment>> {
         let conn = self.lock_conn()?;
 
-        let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
-        let mut rows = stmt.query([])?;
-        if rows.next()?.is_some() {
-            return Err(LedgerError::Storage(
-                "Foreign key integrity check failed".to_string(),
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT id, entry_id, name, mime_type, size, created_at, device_id
+            FROM attachments
+            WHERE entry_id = ?
+            ORDER BY created_at ASC, name ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map([entry_id.to_string()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })?;
+
+        let mut attachments = Vec::new();
+        for row in rows {
+            let (id, entry_id, name, mime_type, size, created_at, device_id) = row?;
+            let id = Uuid::parse_str(&id)
+                .map_err(|e| LedgerError::Storage(format!("Invalid UUID: {}", e)))?;
+            let entry_id = Uuid::parse_str(&entry_id)
+                .map_err(|e| LedgerError::Storage(format!("Invalid entry UUID: {}", e)))?;
+            let device_id = Uuid::parse_str(&device_id)
+                .map_err(|e| LedgerError::Storage(format!("Invalid device_id: {}", e)))?;
+            let created_at = DateTime::parse_from_rfc3339(&created_at)
+                .map_err(|e| LedgerError::Storage(format!("Invalid timestamp: {}", e)))?
+                .with_timezone(&Utc);
+
+            attachments.push(Attachment {
+                id,
+                entry_id,
+                name,
+                mime_type,
+                size: size.max(0) as u64,
+                created_at,
+                device_id,
+            });
+        }
+
+        Ok(attachments)
+    }
+
+    fn get_attachment_data(&self, id: &Uuid) -> Result<Option<Vec<u8>>> {
+        let conn = self.lock_conn()?;
+        let data: Option<Vec<u8>> = conn
+            .query_row(
+                "SELECT b.data FROM attachments a \
+                 JOIN attachment_blobs b ON b.hash = a.blob_hash WHERE a.id = ?",
+                [id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(data)
+    }
+
+    // --- Secret operations ---
+
+    fn set_secret(&mut self, name: &str, value: &str) -> Result<()> {
+        validate_secret_name(name)?;
+        if value.is_empty() {
+            return Err(LedgerError::Validation(
+                "Secret value cannot be empty".to_string(),
             ));
         }
+        if value.len() > MAX_SECRET_BYTES {
+            return Err(LedgerError::Validation(format!(
+                "Secret exceeds max size ({} bytes)",
+                MAX_SECRET_BYTES
+            )));
+        }
 
-        let missing_fts: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM entries e LEFT JOIN entries_fts f ON e.id = f.entry_id WHERE f.entry_id IS NULL",
-            [],
-            |row| row.get(0),
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let updated_at = self.now().to_rfc3339();
+
+        tx.execute(
+            "INSERT INTO secrets (name, value, updated_at) VALUES (?, ?, ?) ON CONFLICT(name) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            rusqlite::params![name, value, updated_at],
         )?;
-        if missing_fts > 0 {
-            return Err(LedgerError::Storage(
-                "FTS index missing entries".to_string(),
+
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&updated_at],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_secret(&self, name: &str) -> Result<Option<String>> {
+        let conn = self.lock_conn()?;
+        let value: Option<String> = conn
+            .query_row("SELECT value FROM secrets WHERE name = ?", [name], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        Ok(value)
+    }
+
+    fn list_secret_names(&self) -> Result<Vec<String>> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached("SELECT name FROM secrets ORDER BY name ASC")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut names = Vec::new();
+        for row in rows {
+            names.push(row?);
+        }
+        Ok(names)
+    }
+
+    fn delete_secret(&mut self, name: &str) -> Result<()> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+
+        let deleted = tx.execute("DELETE FROM secrets WHERE name = ?", [name])?;
+        if deleted == 0 {
+            return Err(LedgerError::NotFound(format!(
+                "Secret '{}' not found",
+                name
+            )));
+        }
+
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&self.now().to_rfc3339()],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn check_integrity(&self) -> Result<Vec<IntegrityIssue>> {
+        let conn = self.lock_conn()?;
+        let mut issues = Vec::new();
+
+        let violations = query_strings(
+            &conn,
+            "SELECT \"table\" || ':' || COALESCE(rowid, '?') FROM pragma_foreign_key_check",
+        )?;
+        if !violations.is_empty() {
+            issues.push(IntegrityIssue::new(
+                "foreign_key_violation",
+                IntegritySeverity::Error,
+                format!("{} row(s) reference missing parents", violations.len()),
+                violations,
             ));
         }
 
-        let orphaned_fts: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM entries_fts f LEFT JOIN entries e ON f.entry_id = e.id WHERE e.id IS NULL",
-            [],
-            |row| row.get(0),
+        let missing_fts = query_strings(
+            &conn,
+            "SELECT e.id FROM entries e LEFT JOIN entries_fts f ON e.id = f.entry_id WHERE f.entry_id IS NULL",
         )?;
-        if orphaned_fts > 0 {
-            return Err(LedgerError::Storage(
-                "FTS index has orphaned rows".to_string(),
+        // An interrupted chunked rebuild leaves entries unindexed on purpose
+        if !missing_fts.is_empty() && search::rebuild_progress(&conn)?.is_some() {
+            issues.push(IntegrityIssue::new(
+                "fts_rebuild_pending",
+                IntegritySeverity::Warning,
+                format!(
+                    "Search index rebuild interrupted with {} entries left to index",
+                    missing_fts.len()
+                ),
+                missing_fts,
+            ));
+        } else if !missing_fts.is_empty() {
+            issues.push(IntegrityIssue::new(
+                "fts_missing",
+                IntegritySeverity::Error,
+                format!("FTS index missing {} entries", missing_fts.len()),
+                missing_fts,
             ));
         }
 
-        let invalid_active: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM (SELECT 1 FROM entry_type_versions GROUP BY entry_type_id HAVING SUM(active) != 1)",
-            [],
-            |row| row.get(0),
+        // Orphaned index rows only produce stale search hits; no data is lost
+        let orphaned_fts = query_strings(
+            &conn,
+            "SELECT f.entry_id FROM entries_fts f LEFT JOIN entries e ON f.entry_id = e.id WHERE e.id IS NULL",
         )?;
-        if invalid_active > 0 {
-            return Err(LedgerError::Storage(
-                "Entry type versions have invalid active state".to_string(),
+        if !orphaned_fts.is_empty() {
+            issues.push(IntegrityIssue::new(
+                "fts_orphaned",
+                IntegritySeverity::Warning,
+                format!("FTS index has {} orphaned rows", orphaned_fts.len()),
+                orphaned_fts,
             ));
         }
 
-        let metadata_count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM meta WHERE key IN ('format_version', 'device_id', 'created_at', 'last_modified')",
-            [],
-            |row| row.get(0),
+        // Named separately from the generic foreign key check so a dangling
+        // membership points at the composition history it would corrupt
+        let dangling_memberships = query_strings(
+            &conn,
+            r#"
+            SELECT ec.entry_id || ':' || ec.composition_id
+            FROM entry_compositions ec
+            WHERE NOT EXISTS (SELECT 1 FROM entries e WHERE e.id = ec.entry_id)
+               OR NOT EXISTS (SELECT 1 FROM compositions c WHERE c.id = ec.composition_id)
+            "#,
         )?;
-        if metadata_count < 4 {
-            return Err(LedgerError::Storage(
-                "Metadata table missing required keys".to_string(),
+        if !dangling_memberships.is_empty() {
+            issues.push(IntegrityIssue::new(
+                "membership_dangling",
+                IntegritySeverity::Error,
+                format!(
+                    "{} composition membership row(s) reference a missing entry or composition",
+                    dangling_memberships.len()
+                ),
+                dangling_memberships,
             ));
         }
 
-        Ok(())
+        let inverted_memberships = query_strings(
+            &conn,
+            r#"
+            SELECT entry_id || ':' || composition_id FROM entry_compositions
+            WHERE removed_at IS NOT NULL AND julianday(removed_at) < julianday(added_at)
+            "#,
+        )?;
+        if !inverted_memberships.is_empty() {
+            issues.push(IntegrityIssue::new(
+                "membership_removed_before_added",
+                IntegritySeverity::Warning,
+                format!(
+                    "{} composition membership row(s) end before they start",
+                    inverted_memberships.len()
+                ),
+                inverted_memberships,
+            ));
+        }
+
+        // gc recounts before deleting, so a stale count loses no data
+        let miscounted_blobs = query_strings(
+            &conn,
+            r#"
+            SELECT b.hash FROM attachment_blobs b
+            WHERE b.refcount != (SELECT COUNT(*) FROM attachments a WHERE a.blob_hash = b.hash)
+            "#,
+        )?;
+        if !miscounted_blobs.is_empty() {
+            issues.push(IntegrityIssue::new(
+                "attachment_blob_refcount",
+                IntegritySeverity::Warning,
+                format!(
+                    "{} attachment blob(s) have a stale reference count; `ledger attachments gc` recounts them",
+                    miscounted_blobs.len()
+                ),
+                miscounted_blobs,
+            ));
+        }
+
+        let invalid_active = query_strings(
+            &conn,
+            "SELECT entry_type_id FROM entry_type_versions GROUP BY entry_type_id HAVING SUM(active) != 1",
+        )?;
+        if !invalid_active.is_empty() {
+            issues.push(IntegrityIssue::new(
+                "entry_type_active_version",
+                IntegritySeverity::Error,
+                "Entry type versions have invalid active state",
+                invalid_active,
+            ));
+        }
+
+        let present: HashSet<String> = query_strings(&conn, "SELECT key FROM meta")?
+            .into_iter()
+            .collect();
+        let missing_keys: Vec<String> =
+            ["format_version", "device_id", "created_at", "last_modified"]
+                .iter()
+                .filter(|key| !present.contains(**key))
+                .map(|key| key.to_string())
+                .collect();
+        if !missing_keys.is_empty() {
+            issues.push(IntegrityIssue::new(
+                "metadata_missing_keys",
+                IntegritySeverity::Error,
+                "Metadata table missing required keys",
+                missing_keys,
+            ));
+        }
+
+        Ok(issues)
     }
 
     // --- Composition operations ---
 
     fn create_composition(&mut self, composition: &NewComposition) -> Result<Uuid> {
         let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
         // Check if composition with this name already exists
         let exists: Option<String> = tx
@@ -948,8 +1720,8 @@ impl StorageEngine for AgeSqliteStorage {
             )));
         }
 
-        let id = Uuid::new_v4();
-        let created_at = Utc::now().to_rfc3339();
+        let id = self.new_id();
+        let created_at = self.now().to_rfc3339();
         let metadata_json = composition
             .metadata
             .as_ref()
@@ -1091,7 +1863,7 @@ impl StorageEngine for AgeSqliteStorage {
             query.push_str(&format!(" LIMIT {}", limit));
         }
 
-        let mut stmt = conn.prepare(&query)?;
+        let mut stmt = conn.prepare_cached(&query)?;
         let rows = stmt.query_map([], |row| {
             Ok((
                 row.get::<_, String>(0)?,
@@ -1133,7 +1905,7 @@ impl StorageEngine for AgeSqliteStorage {
 
     fn rename_composition(&mut self, id: &Uuid, new_name: &str) -> Result<()> {
         let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
         // Check composition exists
         let exists: Option<String> = tx
@@ -1145,10 +1917,7 @@ impl StorageEngine for AgeSqliteStorage {
             .optional()?;
 
         if exists.is_none() {
-            return Err(LedgerError::NotFound(format!(
-                "Composition {} not found",
-                id
-            )));
+            return Err(LedgerError::CompositionNotFound(*id));
         }
 
         // Check new name doesn't exist (for a different composition)
@@ -1167,7 +1936,7 @@ impl StorageEngine for AgeSqliteStorage {
             )));
         }
 
-        let last_modified = Utc::now().to_rfc3339();
+        let last_modified = self.now().to_rfc3339();
 
         tx.execute(
             "UPDATE compositions SET name = ? WHERE id = ?",
@@ -1185,7 +1954,7 @@ impl StorageEngine for AgeSqliteStorage {
 
     fn delete_composition(&mut self, id: &Uuid) -> Result<()> {
         let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
         // Check composition exists
         let exists: Option<String> = tx
@@ -1197,19 +1966,20 @@ impl StorageEngine for AgeSqliteStorage {
             .optional()?;
 
         if exists.is_none() {
-            return Err(LedgerError::NotFound(format!(
-                "Composition {} not found",
-                id
-            )));
+            return Err(LedgerError::CompositionNotFound(*id));
         }
 
-        let last_modified = Utc::now().to_rfc3339();
+        let last_modified = self.now().to_rfc3339();
 
         // Remove all entry associations
         tx.execute(
             "DELETE FROM entry_compositions WHERE composition_id = ?",
             [id.to_string()],
         )?;
+        tx.execute(
+            "DELETE FROM composition_overviews WHERE composition_id = ?",
+            [id.to_string()],
+        )?;
 
         // Delete the composition
         tx.execute("DELETE FROM compositions WHERE id = ?", [id.to_string()])?;
@@ -1229,7 +1999,7 @@ impl StorageEngine for AgeSqliteStorage {
         composition_id: &Uuid,
     ) -> Result<()> {
         let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
         // Check entry exists
         let entry_exists: Option<String> = tx
@@ -1257,16 +2027,14 @@ impl StorageEngine for AgeSqliteStorage {
             .optional()?;
 
         if comp_exists.is_none() {
-            return Err(LedgerError::NotFound(format!(
-                "Composition {} not found",
-                composition_id
-            )));
+            return Err(LedgerError::CompositionNotFound(*composition_id));
         }
 
         // Check if already attached
         let already_attached: Option<String> = tx
             .query_row(
-                "SELECT entry_id FROM entry_compositions WHERE entry_id = ? AND composition_id = ?",
+                "SELECT entry_id FROM entry_compositions \
+                 WHERE entry_id = ? AND composition_id = ? AND removed_at IS NULL",
                 (entry_id.to_string(), composition_id.to_string()),
                 |row| row.get(0),
             )
@@ -1277,7 +2045,7 @@ impl StorageEngine for AgeSqliteStorage {
             return Ok(());
         }
 
-        let added_at = Utc::now().to_rfc3339();
+        let added_at = self.now().to_rfc3339();
 
         tx.execute(
             "INSERT INTO entry_compositions (entry_id, composition_id, added_at) VALUES (?, ?, ?)",
@@ -1299,21 +2067,27 @@ impl StorageEngine for AgeSqliteStorage {
         composition_id: &Uuid,
     ) -> Result<()> {
         let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
-        let deleted = tx.execute(
-            "DELETE FROM entry_compositions WHERE entry_id = ? AND composition_id = ?",
-            (entry_id.to_string(), composition_id.to_string()),
+        // Close the membership rather than deleting it, so history survives
+        let last_modified = self.now().to_rfc3339();
+        let detached = tx.execute(
+            "UPDATE entry_compositions SET removed_at = ? \
+             WHERE entry_id = ? AND composition_id = ? AND removed_at IS NULL",
+            (
+                &last_modified,
+                entry_id.to_string(),
+                composition_id.to_string(),
+            ),
         )?;
 
-        if deleted == 0 {
+        if detached == 0 {
             return Err(LedgerError::NotFound(format!(
                 "Entry {} is not attached to composition {}",
                 entry_id, composition_id
             )));
         }
 
-        let last_modified = Utc::now().to_rfc3339();
         tx.execute(
             "UPDATE meta SET value = ? WHERE key = 'last_modified'",
             [&last_modified],
@@ -1326,12 +2100,12 @@ impl StorageEngine for AgeSqliteStorage {
     fn get_entry_compositions(&self, entry_id: &Uuid) -> Result<Vec<Composition>> {
         let conn = self.lock_conn()?;
 
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             r#"
             SELECT c.id, c.name, c.description, c.created_at, c.device_id, c.metadata_json
             FROM compositions c
             JOIN entry_compositions ec ON c.id = ec.composition_id
-            WHERE ec.entry_id = ?
+            WHERE ec.entry_id = ? AND ec.removed_at IS NULL
             ORDER BY c.name
             "#,
         )?;
@@ -1378,11 +2152,11 @@ impl StorageEngine for AgeSqliteStorage {
     fn get_composition_entries(&self, composition_id: &Uuid) -> Result<Vec<EntryComposition>> {
         let conn = self.lock_conn()?;
 
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             r#"
             SELECT entry_id, composition_id, added_at
             FROM entry_compositions
-            WHERE composition_id = ?
+            WHERE composition_id = ? AND removed_at IS NULL
             ORDER BY added_at DESC
             "#,
         )?;
@@ -1410,6 +2184,7 @@ impl StorageEngine for AgeSqliteStorage {
                 entry_id,
                 composition_id,
                 added_at,
+                removed_at: None,
             });
         }
 
@@ -1420,7 +2195,7 @@ impl StorageEngine for AgeSqliteStorage {
 
     fn create_template(&mut self, template: &NewTemplate) -> Result<Uuid> {
         let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
         // Check if template with this name already exists
         let exists: Option<String> = tx
@@ -1454,8 +2229,8 @@ impl StorageEngine for AgeSqliteStorage {
             )));
         }
 
-        let id = Uuid::new_v4();
-        let created_at = Utc::now().to_rfc3339();
+        let id = self.new_id();
+        let created_at = self.now().to_rfc3339();
 
         // Create base template record
         tx.execute(
@@ -1474,7 +2249,7 @@ impl StorageEngine for AgeSqliteStorage {
         )?;
 
         // Create first version
-        let version_id = Uuid::new_v4();
+        let version_id = self.new_id();
         let template_json_str = serde_json::to_string(&template.template_json)
             .map_err(|e| LedgerError::Storage(format!("Failed to serialize template: {}", e)))?;
 
@@ -1630,7 +2405,7 @@ impl StorageEngine for AgeSqliteStorage {
     fn list_templates(&self) -> Result<Vec<Template>> {
         let conn = self.lock_conn()?;
 
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             r#"
             SELECT t.id, t.name, t.entry_type_id, tv.version, tv.created_at, t.device_id, t.description, tv.template_json
             FROM template_versions tv
@@ -1699,7 +2474,7 @@ impl StorageEngine for AgeSqliteStorage {
 
     fn update_template(&mut self, id: &Uuid, template_json: serde_json::Value) -> Result<i32> {
         let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
         // Check template exists and get max version
         let max_version: Option<i32> = tx
@@ -1711,12 +2486,11 @@ impl StorageEngine for AgeSqliteStorage {
             .optional()?
             .flatten();
 
-        let max_version = max_version
-            .ok_or_else(|| LedgerError::NotFound(format!("Template {} not found", id)))?;
+        let max_version = max_version.ok_or_else(|| LedgerError::TemplateNotFound(*id))?;
 
         let new_version = max_version + 1;
-        let created_at = Utc::now().to_rfc3339();
-        let version_id = Uuid::new_v4();
+        let created_at = self.now().to_rfc3339();
+        let version_id = self.new_id();
 
         // Deactivate old versions
         tx.execute(
@@ -1753,22 +2527,38 @@ impl StorageEngine for AgeSqliteStorage {
 
     fn delete_template(&mut self, id: &Uuid) -> Result<()> {
         let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
         // Check template exists
-        let exists: Option<String> = tx
+        let name: Option<String> = tx
             .query_row(
-                "SELECT id FROM templates WHERE id = ?",
+                "SELECT name FROM templates WHERE id = ?",
                 [id.to_string()],
                 |row| row.get(0),
             )
             .optional()?;
 
-        if exists.is_none() {
-            return Err(LedgerError::NotFound(format!("Template {} not found", id)));
+        let Some(name) = name else {
+            return Err(LedgerError::TemplateNotFound(*id));
+        };
+
+        let created: Vec<String> = {
+            let mut stmt = tx.prepare_cached(
+                "SELECT entry_id FROM entry_templates WHERE template_id = ? ORDER BY entry_id",
+            )?;
+            let rows = stmt.query_map([id.to_string()], |row| row.get(0))?;
+            rows.collect::<std::result::Result<_, _>>()?
+        };
+        if !created.is_empty() {
+            return Err(LedgerError::Validation(format!(
+                "Template '{}' was used to create {} entries: {}",
+                name,
+                created.len(),
+                listed(&created)
+            )));
         }
 
-        let last_modified = Utc::now().to_rfc3339();
+        let last_modified = self.now().to_rfc3339();
 
         // Remove default template mappings
         tx.execute(
@@ -1796,7 +2586,7 @@ impl StorageEngine for AgeSqliteStorage {
 
     fn set_default_template(&mut self, entry_type_id: &Uuid, template_id: &Uuid) -> Result<()> {
         let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
         // Check entry type exists
         let entry_type_exists: Option<String> = tx
@@ -1823,8 +2613,8 @@ impl StorageEngine for AgeSqliteStorage {
             )
             .optional()?;
 
-        let template_entry_type_id = template_entry_type_id
-            .ok_or_else(|| LedgerError::NotFound(format!("Template {} not found", template_id)))?;
+        let template_entry_type_id =
+            template_entry_type_id.ok_or_else(|| LedgerError::TemplateNotFound(*template_id))?;
 
         if template_entry_type_id != entry_type_id.to_string() {
             return Err(LedgerError::Validation(format!(
@@ -1833,7 +2623,7 @@ impl StorageEngine for AgeSqliteStorage {
             )));
         }
 
-        let last_modified = Utc::now().to_rfc3339();
+        let last_modified = self.now().to_rfc3339();
 
         // Deactivate existing default for this entry type
         tx.execute(
@@ -1862,7 +2652,7 @@ impl StorageEngine for AgeSqliteStorage {
 
     fn clear_default_template(&mut self, entry_type_id: &Uuid) -> Result<()> {
         let mut conn = self.lock_conn()?;
-        let tx = conn.transaction()?;
+        let tx = conn.savepoint()?;
 
         // Check entry type exists
         let entry_type_exists: Option<String> = tx
@@ -1880,7 +2670,7 @@ impl StorageEngine for AgeSqliteStorage {
             )));
         }
 
-        let last_modified = Utc::now().to_rfc3339();
+        let last_modified = self.now().to_rfc3339();
 
         tx.execute(
             "UPDATE entry_type_templates SET active = 0 WHERE entry_type_id = ? AND active = 1",
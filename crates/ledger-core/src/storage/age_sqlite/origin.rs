@@ -0,0 +1,60 @@
+//! Entry provenance: which program, command and host wrote each entry.
+
+use rusqlite::{Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::storage::types::EntryOrigin;
+
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// Record `origin` against every entry inserted from now on, including
+    /// imported ones. `None` stops recording.
+    pub fn set_origin(&mut self, origin: Option<EntryOrigin>) {
+        self.origin = origin;
+    }
+
+    /// Where the entry was written, if that was recorded.
+    ///
+    /// Each revision has its own origin; entries written before origins
+    /// were recorded have none.
+    pub fn entry_origin(&self, entry_id: &Uuid) -> Result<Option<EntryOrigin>> {
+        let conn = self.lock_conn()?;
+        let origin = conn
+            .query_row(
+                "SELECT app, version, command, hostname FROM entry_origins WHERE entry_id = ?",
+                [entry_id.to_string()],
+                |row| {
+                    Ok(EntryOrigin {
+                        app: row.get(0)?,
+                        version: row.get(1)?,
+                        command: row.get(2)?,
+                        hostname: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(origin)
+    }
+
+    /// Store the current origin for a just-inserted entry, inside the
+    /// caller's savepoint.
+    pub(super) fn record_origin(&self, conn: &Connection, entry_id: &Uuid) -> Result<()> {
+        let Some(ref origin) = self.origin else {
+            return Ok(());
+        };
+        conn.execute(
+            "INSERT OR REPLACE INTO entry_origins (entry_id, app, version, command, hostname) \
+             VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![
+                entry_id.to_string(),
+                origin.app,
+                origin.version,
+                origin.command,
+                origin.hostname
+            ],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+//! Composition overviews: one markdown page per composition.
+//!
+//! Like the scratchpad, an overview is encrypted with the rest of the ledger
+//! but is not an entry: it has no revisions and is overwritten in place.
+
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::CompositionOverview;
+
+use super::validation::MAX_DATA_BYTES;
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// The overview of a composition, or `None` if it has none.
+    pub fn composition_overview(
+        &self,
+        composition_id: &Uuid,
+    ) -> Result<Option<CompositionOverview>> {
+        let conn = self.lock_conn()?;
+        let row: Option<(String, String)> = conn
+            .query_row(
+                "SELECT body, updated_at FROM composition_overviews WHERE composition_id = ?",
+                [composition_id.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        row.map(|(body, updated_at)| {
+            Ok(CompositionOverview {
+                composition_id: *composition_id,
+                body,
+                updated_at: parse_updated_at(&updated_at)?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Replace a composition's overview; empty text removes it.
+    ///
+    /// Returns `LedgerError::CompositionNotFound` if the composition does not exist.
+    pub fn set_composition_overview(&mut self, composition_id: &Uuid, body: &str) -> Result<()> {
+        if body.len() > MAX_DATA_BYTES {
+            return Err(LedgerError::Validation(format!(
+                "Composition overview exceeds max size ({} bytes)",
+                MAX_DATA_BYTES
+            )));
+        }
+
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let exists: Option<String> = tx
+            .query_row(
+                "SELECT id FROM compositions WHERE id = ?",
+                [composition_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if exists.is_none() {
+            return Err(LedgerError::CompositionNotFound(*composition_id));
+        }
+
+        let now = self.now().to_rfc3339();
+        if body.is_empty() {
+            tx.execute(
+                "DELETE FROM composition_overviews WHERE composition_id = ?",
+                [composition_id.to_string()],
+            )?;
+        } else {
+            tx.execute(
+                r#"
+                INSERT INTO composition_overviews (composition_id, body, updated_at)
+                VALUES (?, ?, ?)
+                ON CONFLICT(composition_id) DO UPDATE
+                SET body = excluded.body, updated_at = excluded.updated_at
+                "#,
+                (composition_id.to_string(), body, &now),
+            )?;
+        }
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&now],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn parse_updated_at(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|value| value.with_timezone(&Utc))
+        .map_err(|e| LedgerError::Storage(format!("Invalid overview time: {}", e)))
+}
@@ -0,0 +1,115 @@
+//! Installed entry type packs.
+//!
+//! A pack is a set of entry types and templates installed together from a
+//! manifest. Only the record of what was installed lives here; the types and
+//! templates are ordinary rows.
+
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::InstalledPack;
+
+use super::validation::validate_pack_name;
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// Record a pack install, replacing an earlier record of the same pack.
+    pub fn record_pack(&mut self, pack: &InstalledPack) -> Result<()> {
+        validate_pack_name(&pack.name)?;
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        tx.execute(
+            "INSERT INTO packs (name, version, description, entry_types_json, templates_json, installed_at, device_id) \
+             VALUES (?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT(name) DO UPDATE SET version = excluded.version, description = excluded.description, \
+             entry_types_json = excluded.entry_types_json, templates_json = excluded.templates_json, \
+             installed_at = excluded.installed_at, device_id = excluded.device_id",
+            rusqlite::params![
+                pack.name,
+                pack.version,
+                pack.description,
+                serde_json::to_string(&pack.entry_types)?,
+                serde_json::to_string(&pack.templates)?,
+                pack.installed_at.to_rfc3339(),
+                pack.device_id.to_string()
+            ],
+        )?;
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&self.now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Look up an installed pack by name.
+    pub fn get_pack(&self, name: &str) -> Result<Option<InstalledPack>> {
+        let conn = self.lock_conn()?;
+        let row = conn
+            .query_row(
+                "SELECT name, version, description, entry_types_json, templates_json, installed_at, device_id \
+                 FROM packs WHERE name = ?",
+                [name],
+                pack_row,
+            )
+            .optional()?;
+        row.map(parse_pack).transpose()
+    }
+
+    /// All installed packs, sorted by name.
+    pub fn list_packs(&self) -> Result<Vec<InstalledPack>> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT name, version, description, entry_types_json, templates_json, installed_at, device_id \
+             FROM packs ORDER BY name ASC",
+        )?;
+        let rows = stmt.query_map([], pack_row)?;
+        let mut packs = Vec::new();
+        for row in rows {
+            packs.push(parse_pack(row?)?);
+        }
+        Ok(packs)
+    }
+}
+
+type PackRow = (
+    String,
+    String,
+    Option<String>,
+    String,
+    String,
+    String,
+    String,
+);
+
+fn pack_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<PackRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+    ))
+}
+
+fn parse_pack(row: PackRow) -> Result<InstalledPack> {
+    let (name, version, description, entry_types, templates, installed_at, device_id) = row;
+    let installed_at = DateTime::parse_from_rfc3339(&installed_at)
+        .map_err(|e| LedgerError::Storage(format!("Invalid pack timestamp: {}", e)))?
+        .with_timezone(&Utc);
+    let device_id = Uuid::parse_str(&device_id)
+        .map_err(|e| LedgerError::Storage(format!("Invalid device ID: {}", e)))?;
+    Ok(InstalledPack {
+        name,
+        version,
+        description,
+        entry_types: serde_json::from_str(&entry_types)?,
+        templates: serde_json::from_str(&templates)?,
+        installed_at,
+        device_id,
+    })
+}
@@ -0,0 +1,247 @@
+//! Sync peers: other ledgers this one exchanges change bundles with.
+//!
+//! A peer is known by name and by the device fingerprint of its ledger,
+//! which change bundles carry so an import can tell whose bundle it is. Each
+//! peer remembers when a bundle last went each way, and how far into the
+//! ledger's insertion order (`entries.seq`) the last one sent reached, so the
+//! next export only needs entries inserted since, whatever their dates.
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::{SyncDirection, SyncPeer};
+
+use super::validation::validate_peer_name;
+use super::AgeSqliteStorage;
+
+/// Hex digits in a device fingerprint, grouped in fours.
+const FINGERPRINT_DIGITS: usize = 16;
+
+/// Format 16 hex digits as `xxxx-xxxx-xxxx-xxxx`.
+fn group_fingerprint(digits: &str) -> String {
+    digits
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Canonical form of a fingerprint typed by a user: case, dashes, colons and
+/// spaces are ignored.
+fn normalize_fingerprint(fingerprint: &str) -> Result<String> {
+    let digits: String = fingerprint
+        .chars()
+        .filter(|c| !matches!(c, '-' | ':' | ' '))
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if digits.len() != FINGERPRINT_DIGITS || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(LedgerError::Validation(format!(
+            "Invalid device fingerprint: {} (expected {} hex digits, e.g. 1a2b-3c4d-5e6f-7a8b)",
+            fingerprint, FINGERPRINT_DIGITS
+        )));
+    }
+    Ok(group_fingerprint(&digits))
+}
+
+fn parse_timestamp(value: Option<String>) -> Result<Option<DateTime<Utc>>> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(&value)
+                .map(|at| at.with_timezone(&Utc))
+                .map_err(|e| LedgerError::Storage(format!("Invalid peer timestamp: {}", e)))
+        })
+        .transpose()
+}
+
+type PeerRow = (
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<i64>,
+);
+
+const PEER_COLUMNS: &str =
+    "name, fingerprint, added_at, last_sent_at, last_received_at, last_sent_seq";
+
+fn parse_peer(
+    (name, fingerprint, added_at, last_sent_at, last_received_at, last_sent_seq): PeerRow,
+) -> Result<SyncPeer> {
+    Ok(SyncPeer {
+        name,
+        fingerprint,
+        added_at: parse_timestamp(Some(added_at))?
+            .ok_or_else(|| LedgerError::Storage("Missing peer timestamp".to_string()))?,
+        last_sent_at: parse_timestamp(last_sent_at)?,
+        last_received_at: parse_timestamp(last_received_at)?,
+        last_sent_seq,
+    })
+}
+
+fn peer_row(row: &rusqlite::Row) -> rusqlite::Result<PeerRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+    ))
+}
+
+/// Give `sync_peers` from before the sent high-water mark a `last_sent_seq`
+/// column. Those peers are sent everything once more; their import skips
+/// what they already have.
+pub(super) fn migrate_peer_sent_seq(conn: &Connection) -> Result<()> {
+    let has_seq: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('sync_peers') WHERE name = 'last_sent_seq'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_seq {
+        conn.execute_batch("ALTER TABLE sync_peers ADD COLUMN last_sent_seq INTEGER;")?;
+    }
+    Ok(())
+}
+
+impl AgeSqliteStorage {
+    /// This ledger's device fingerprint, for peers to record.
+    ///
+    /// Derived from the device id, so it never changes for a ledger file.
+    pub fn device_fingerprint(&self) -> String {
+        let hash = blake3::hash(self.device_id.as_bytes()).to_hex();
+        group_fingerprint(&hash[..FINGERPRINT_DIGITS])
+    }
+
+    /// Add a peer, returning the stored record.
+    ///
+    /// Returns `LedgerError::Validation` if a peer with the name exists, the
+    /// fingerprint is malformed or it is this ledger's own.
+    pub fn add_sync_peer(&mut self, name: &str, fingerprint: &str) -> Result<SyncPeer> {
+        validate_peer_name(name)?;
+        let fingerprint = normalize_fingerprint(fingerprint)?;
+        if fingerprint == self.device_fingerprint() {
+            return Err(LedgerError::Validation(format!(
+                "Fingerprint {} is this ledger's own",
+                fingerprint
+            )));
+        }
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let exists: Option<String> = tx
+            .query_row(
+                "SELECT name FROM sync_peers WHERE name = ?",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if exists.is_some() {
+            return Err(LedgerError::Validation(format!(
+                "Peer '{}' already exists",
+                name
+            )));
+        }
+
+        let added_at = self.now();
+        tx.execute(
+            "INSERT INTO sync_peers (name, fingerprint, added_at) VALUES (?, ?, ?)",
+            rusqlite::params![name, fingerprint, added_at.to_rfc3339()],
+        )?;
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&added_at.to_rfc3339()],
+        )?;
+        tx.commit()?;
+
+        Ok(SyncPeer {
+            name: name.to_string(),
+            fingerprint,
+            added_at,
+            last_sent_at: None,
+            last_received_at: None,
+            last_sent_seq: None,
+        })
+    }
+
+    /// Look up a peer by name.
+    pub fn get_sync_peer(&self, name: &str) -> Result<Option<SyncPeer>> {
+        let conn = self.lock_conn()?;
+        let row = conn
+            .query_row(
+                &format!("SELECT {} FROM sync_peers WHERE name = ?", PEER_COLUMNS),
+                [name],
+                peer_row,
+            )
+            .optional()?;
+        row.map(parse_peer).transpose()
+    }
+
+    /// All peers, sorted by name.
+    pub fn list_sync_peers(&self) -> Result<Vec<SyncPeer>> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM sync_peers ORDER BY name ASC",
+            PEER_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], peer_row)?;
+        let mut peers = Vec::new();
+        for row in rows {
+            peers.push(parse_peer(row?)?);
+        }
+        Ok(peers)
+    }
+
+    /// Forget a peer.
+    ///
+    /// Returns `LedgerError::NotFound` if the peer doesn't exist.
+    pub fn remove_sync_peer(&mut self, name: &str) -> Result<()> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let deleted = tx.execute("DELETE FROM sync_peers WHERE name = ?", [name])?;
+        if deleted == 0 {
+            return Err(LedgerError::NotFound(format!("Peer '{}' not found", name)));
+        }
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&self.now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record that a change bundle went to or came from a peer at `at`.
+    ///
+    /// A bundle sent also records the highest entry `seq` now in the ledger,
+    /// which the next bundle for the peer starts after.
+    ///
+    /// Returns `LedgerError::NotFound` if the peer doesn't exist.
+    pub fn mark_peer_synced(
+        &mut self,
+        name: &str,
+        direction: SyncDirection,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        let sql = match direction {
+            SyncDirection::Sent => {
+                "UPDATE sync_peers SET last_sent_at = ?, \
+                 last_sent_seq = (SELECT COALESCE(MAX(seq), 0) FROM entries) WHERE name = ?"
+            }
+            SyncDirection::Received => "UPDATE sync_peers SET last_received_at = ? WHERE name = ?",
+        };
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let updated = tx.execute(sql, rusqlite::params![at.to_rfc3339(), name])?;
+        if updated == 0 {
+            return Err(LedgerError::NotFound(format!("Peer '{}' not found", name)));
+        }
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&self.now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+//! Salvage readable rows from a damaged ledger into a fresh one.
+//!
+//! Recovery reads the decrypted database table by table in rowid order. When
+//! a read fails, it probes forward with a growing stride until rows become
+//! readable again and records the skipped rowids as an unreadable range. Rows
+//! that no longer satisfy foreign keys are dropped, and the FTS index is
+//! rebuilt from the salvaged entries. The source file is only ever read.
+
+use std::fs;
+use std::path::Path;
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, DatabaseName};
+
+use crate::error::{LedgerError, Result};
+use crate::storage::encryption::decrypt;
+use crate::storage::traits::StorageEngine;
+use crate::storage::types::{RecoveryReport, RowRange, TableRecovery};
+
+use super::attachments::{migrate_attachment_blobs, recount_blob_refs};
+use super::day_numbers::migrate_day_numbers;
+use super::search::{rebuild_fts, stored_tokenizer};
+use super::AgeSqliteStorage;
+
+/// Tables copied during recovery, parents before children.
+const RECOVERABLE_TABLES: &[&str] = &[
+    "meta",
+    "entry_types",
+    "entry_type_versions",
+    "entries",
+    "compositions",
+    "entry_compositions",
+    "templates",
+    "template_versions",
+    "entry_type_templates",
+    "attachment_blobs",
+    "attachments",
+    "secrets",
+    "habits",
+    "packs",
+    "entry_access",
+    "entry_templates",
+    "retired_entry_types",
+    "entry_origins",
+    "composition_overviews",
+    "idempotency_keys",
+    "drafts",
+    "entry_day_numbers",
+    "sync_peers",
+];
+
+/// Tables from `ADDITIVE_SCHEMA`; older ledgers legitimately lack them.
+const ADDITIVE_TABLES: &[&str] = &[
+    "attachment_blobs",
+    "attachments",
+    "secrets",
+    "habits",
+    "packs",
+    "entry_access",
+    "entry_templates",
+    "retired_entry_types",
+    "entry_origins",
+    "composition_overviews",
+    "idempotency_keys",
+    "drafts",
+    "entry_day_numbers",
+    "sync_peers",
+];
+
+/// Largest stride used when probing past a damaged region.
+const MAX_PROBE_STRIDE: i64 = 1 << 40;
+
+impl AgeSqliteStorage {
+    /// Salvage readable rows from `source` into a new ledger at `out`.
+    ///
+    /// `out` is created with a fresh schema and encrypted with the same
+    /// passphrase. `source` is never modified. Fails if the source cannot be
+    /// decrypted or its schema is unreadable; row-level damage is reported in
+    /// the returned [`RecoveryReport`] instead.
+    pub fn recover(source: &Path, out: &Path, passphrase: &str) -> Result<RecoveryReport> {
+        if !source.exists() {
+            return Err(LedgerError::LedgerNotFound);
+        }
+        if out.exists() {
+            return Err(LedgerError::Storage(format!(
+                "Recovery output already exists: {}",
+                out.display()
+            )));
+        }
+
+        let plaintext = decrypt(&fs::read(source)?, passphrase)?;
+        let mut src = Connection::open_in_memory()?;
+        src.deserialize(
+            DatabaseName::Main,
+            Self::owned_data_from_bytes(&plaintext)?,
+            true,
+        )?;
+        let source_tables = read_table_names(&src).map_err(|e| {
+            LedgerError::Storage(format!(
+                "Database schema is unreadable, nothing can be salvaged: {}",
+                e
+            ))
+        })?;
+
+        Self::create(out, passphrase)?;
+        let result = Self::open(out, passphrase).and_then(|storage| {
+            let report = salvage_into(&storage, &src, &source_tables)?;
+            storage.close(passphrase)?;
+            Ok(report)
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(out);
+        }
+        result
+    }
+}
+
+fn read_table_names(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type = 'table'")?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    Ok(names.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+fn column_names(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info(\"{}\")", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    Ok(names.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+fn salvage_into(
+    storage: &AgeSqliteStorage,
+    src: &Connection,
+    source_tables: &[String],
+) -> Result<RecoveryReport> {
+    let mut conn = storage.lock_conn()?;
+    conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+    let tx = conn.transaction()?;
+
+    let mut tables = Vec::new();
+    for &table in RECOVERABLE_TABLES {
+        let mut recovery = TableRecovery::new(table);
+        if !source_tables.iter().any(|name| name == table) {
+            recovery.missing = !ADDITIVE_TABLES.contains(&table);
+            tables.push(recovery);
+            continue;
+        }
+        let dest_columns = column_names(&tx, table)?;
+        let columns: Vec<String> = match column_names(src, table) {
+            Ok(source_columns) => dest_columns
+                .into_iter()
+                .filter(|column| source_columns.contains(column))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        if columns.is_empty() {
+            recovery.missing = true;
+            tables.push(recovery);
+            continue;
+        }
+        copy_table(src, &tx, table, &columns, &mut recovery)?;
+        tables.push(recovery);
+    }
+
+    // Drop rows whose parents were lost
+    let orphans: Vec<(String, i64)> = {
+        let mut stmt = tx.prepare("SELECT \"table\", rowid FROM pragma_foreign_key_check")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()?
+    };
+    for (table, rowid) in orphans {
+        tx.execute(
+            &format!("DELETE FROM \"{}\" WHERE rowid = ?", table),
+            [rowid],
+        )?;
+        if let Some(recovery) = tables.iter_mut().find(|t| t.table == table) {
+            recovery.recovered -= 1;
+            recovery.orphaned += 1;
+        }
+    }
+
+    // Rebuild the search index from whatever entries survived, with the
+    // tokenizer the source ledger chose
+    let tokenizer = stored_tokenizer(&tx).unwrap_or_default();
+    rebuild_fts(&tx, tokenizer)?;
+    // Sources older than `entries.seq` salvage without one
+    tx.execute("UPDATE entries SET seq = rowid WHERE seq IS NULL", [])?;
+    // Sources older than attachment blobs salvage their bytes inline, and
+    // dropped attachments leave blob reference counts too high
+    migrate_attachment_blobs(&tx)?;
+    recount_blob_refs(&tx)?;
+    // Sources older than day numbers, or that lost some, number what survived
+    migrate_day_numbers(&tx)?;
+
+    tx.execute(
+        "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+        [storage.now().to_rfc3339()],
+    )?;
+    tx.commit()?;
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+    drop(conn);
+
+    let issues = storage.check_integrity()?;
+    Ok(RecoveryReport { tables, issues })
+}
+
+/// Copy every readable row of `table`, recording unreadable rowid ranges.
+fn copy_table(
+    src: &Connection,
+    dest: &Connection,
+    table: &str,
+    columns: &[String],
+    recovery: &mut TableRecovery,
+) -> Result<()> {
+    let column_list = columns
+        .iter()
+        .map(|column| format!("\"{}\"", column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let select = format!(
+        "SELECT rowid, {} FROM \"{}\" WHERE rowid > ? ORDER BY rowid",
+        column_list, table
+    );
+    let insert = format!(
+        "INSERT OR REPLACE INTO \"{}\" ({}) VALUES ({})",
+        table,
+        column_list,
+        vec!["?"; columns.len()].join(", ")
+    );
+    let max_rowid: Option<i64> = src
+        .query_row(
+            &format!("SELECT MAX(rowid) FROM \"{}\"", table),
+            [],
+            |row| row.get(0),
+        )
+        .ok()
+        .flatten();
+
+    let mut cursor = i64::MIN;
+    loop {
+        match read_rows_after(
+            src,
+            &select,
+            columns.len(),
+            &mut cursor,
+            |values| match dest.execute(&insert, params_from_iter(values.iter())) {
+                Ok(_) => recovery.recovered += 1,
+                Err(_) => recovery.rejected += 1,
+            },
+        ) {
+            Ok(()) => return Ok(()),
+            Err(_) => {
+                // Rowids start at 1; nothing read yet means the damage starts there
+                let last_good = if cursor == i64::MIN { 0 } else { cursor };
+                let resume = probe_past_damage(src, table, last_good, max_rowid);
+                recovery.add_unreadable(RowRange {
+                    start: last_good.saturating_add(1),
+                    end: resume,
+                });
+                match resume {
+                    Some(end) => cursor = end,
+                    None => return Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Stream rows with rowid greater than `cursor`, advancing it per row.
+fn read_rows_after(
+    src: &Connection,
+    select: &str,
+    column_count: usize,
+    cursor: &mut i64,
+    mut on_row: impl FnMut(Vec<Value>),
+) -> Result<()> {
+    let mut stmt = src.prepare(select)?;
+    let mut rows = stmt.query([*cursor])?;
+    while let Some(row) = rows.next()? {
+        let rowid: i64 = row.get(0)?;
+        let values = (1..=column_count)
+            .map(|index| row.get::<_, Value>(index))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        *cursor = rowid;
+        on_row(values);
+    }
+    Ok(())
+}
+
+/// Find a rowid past the damaged region that reads cleanly again.
+///
+/// Returns the last skipped rowid, or `None` when nothing after the damage is
+/// readable.
+fn probe_past_damage(
+    src: &Connection,
+    table: &str,
+    last_good: i64,
+    max_rowid: Option<i64>,
+) -> Option<i64> {
+    let probe_sql = format!(
+        "SELECT rowid FROM \"{}\" WHERE rowid > ? ORDER BY rowid LIMIT 1",
+        table
+    );
+    let mut stride: i64 = 1;
+    while stride <= MAX_PROBE_STRIDE {
+        let probe = last_good.saturating_add(stride);
+        if max_rowid.is_some_and(|max| probe >= max) {
+            return None;
+        }
+        match src.query_row(&probe_sql, [probe], |row| row.get::<_, i64>(0)) {
+            Ok(_) => return Some(probe),
+            Err(rusqlite::Error::QueryReturnedNoRows) => return None,
+            Err(_) => stride = stride.saturating_mul(2),
+        }
+    }
+    None
+}
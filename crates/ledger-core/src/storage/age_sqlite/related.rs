@@ -0,0 +1,184 @@
+//! Entries related to a given entry, by shared tags, compositions and text.
+
+use std::collections::HashMap;
+
+use rusqlite::OptionalExtension;
+
+use crate::error::Result;
+use crate::storage::types::{Entry, RelatedEntry, RelatedReason};
+
+use super::row::{EntryRow, ENTRY_COLUMNS};
+use super::search::{any_term_query, salient_terms};
+use super::AgeSqliteStorage;
+
+/// Salient terms of the entry matched against the search index.
+const TEXT_TERMS: usize = 16;
+
+/// Closest text matches considered.
+const TEXT_CANDIDATES: usize = 50;
+
+/// Text similarity, relative to the entry matching itself, below which
+/// overlap is treated as incidental.
+const MIN_TEXT_SIMILARITY: f64 = 0.25;
+
+/// Score for each composition both entries belong to.
+const COMPOSITION_WEIGHT: f64 = 1.0;
+
+/// Current entries only; superseded revisions are never suggested.
+const CURRENT: &str = "e.id NOT IN (SELECT supersedes FROM entries WHERE supersedes IS NOT NULL)";
+
+#[derive(Default)]
+struct Candidate {
+    score: f64,
+    reasons: Vec<RelatedReason>,
+}
+
+impl AgeSqliteStorage {
+    /// Up to `limit` current entries most related to `entry`, best first.
+    ///
+    /// Three signals add up: each shared tag, weighted by how rare it is
+    /// across the ledger (a tag on every entry barely counts); each shared
+    /// composition; and text similarity, BM25 against the entry's salient
+    /// terms scaled so the entry matching itself would score 1. The entry,
+    /// the revision it replaces and revisions replacing it are left out.
+    pub fn related_entries(&self, entry: &Entry, limit: usize) -> Result<Vec<RelatedEntry>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let own_id = entry.id.to_string();
+        let mut skip = vec![own_id.clone()];
+        skip.extend(entry.supersedes.map(|id| id.to_string()));
+
+        let conn = self.lock_conn()?;
+        {
+            let mut stmt = conn.prepare_cached("SELECT id FROM entries WHERE supersedes = ?")?;
+            let rows = stmt.query_map([&own_id], |row| row.get::<_, String>(0))?;
+            for row in rows {
+                skip.push(row?);
+            }
+        }
+        let mut candidates: HashMap<String, Candidate> = HashMap::new();
+
+        let total: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM entries e WHERE {}", CURRENT),
+            [],
+            |row| row.get(0),
+        )?;
+        for tag in &entry.tags {
+            let ids: Vec<String> = {
+                let mut stmt = conn.prepare_cached(&format!(
+                    "SELECT e.id FROM entries e WHERE e.tags_json IS NOT NULL \
+                     AND EXISTS (SELECT 1 FROM json_each(e.tags_json) WHERE value = ?) AND {}",
+                    CURRENT
+                ))?;
+                let rows = stmt.query_map([tag], |row| row.get::<_, String>(0))?;
+                rows.collect::<std::result::Result<_, _>>()?
+            };
+            // Inverse document frequency: rare tags say more
+            let weight = ((total + 1) as f64 / ids.len().max(1) as f64).ln();
+            for id in ids {
+                let candidate = candidates.entry(id).or_default();
+                candidate.score += weight;
+                candidate.reasons.push(RelatedReason::Tag(tag.clone()));
+            }
+        }
+
+        {
+            let mut stmt = conn.prepare_cached(&format!(
+                r#"
+                SELECT e.id, c.name
+                FROM entry_compositions mine
+                JOIN entry_compositions ec ON ec.composition_id = mine.composition_id
+                JOIN compositions c ON c.id = mine.composition_id
+                JOIN entries e ON e.id = ec.entry_id
+                WHERE mine.entry_id = ? AND mine.removed_at IS NULL
+                  AND ec.removed_at IS NULL AND {}
+                ORDER BY c.name
+                "#,
+                CURRENT
+            ))?;
+            let rows = stmt.query_map([&own_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+            for row in rows {
+                let (id, name) = row?;
+                let candidate = candidates.entry(id).or_default();
+                candidate.score += COMPOSITION_WEIGHT;
+                candidate.reasons.push(RelatedReason::Composition(name));
+            }
+        }
+
+        // What the search index holds, so unindexed fields never match
+        let content: Option<String> = conn
+            .query_row(
+                "SELECT content FROM entries_fts WHERE entry_id = ?",
+                [&own_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let terms = salient_terms(&content.unwrap_or_default(), TEXT_TERMS);
+        if !terms.is_empty() {
+            let query = any_term_query(&terms);
+            let own_rank: Option<f64> = conn
+                .query_row(
+                    "SELECT bm25(entries_fts) FROM entries_fts \
+                     WHERE entries_fts MATCH ? AND entry_id = ?",
+                    (&query, &own_id),
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let mut stmt = conn.prepare_cached(&format!(
+                r#"
+                SELECT f.entry_id, bm25(entries_fts)
+                FROM entries_fts f
+                JOIN entries e ON e.id = f.entry_id
+                WHERE entries_fts MATCH ? AND {}
+                ORDER BY bm25(entries_fts)
+                LIMIT ?
+                "#,
+                CURRENT
+            ))?;
+            let rows = stmt.query_map((&query, TEXT_CANDIDATES as i64), |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+            })?;
+            let matches: Vec<(String, f64)> = rows.collect::<std::result::Result<_, _>>()?;
+            // bm25() is negative, lower meaning more relevant
+            let best = own_rank
+                .or_else(|| matches.first().map(|(_, rank)| *rank))
+                .filter(|rank| *rank < 0.0);
+            if let Some(best) = best {
+                for (id, rank) in matches {
+                    let similarity = (rank / best).min(1.0);
+                    if similarity < MIN_TEXT_SIMILARITY {
+                        continue;
+                    }
+                    let candidate = candidates.entry(id).or_default();
+                    candidate.score += similarity;
+                    candidate.reasons.push(RelatedReason::Text);
+                }
+            }
+        }
+
+        let mut ranked: Vec<(String, Candidate)> = candidates
+            .into_iter()
+            .filter(|(id, _)| !skip.contains(id))
+            .collect();
+        ranked.sort_by(|a, b| b.1.score.total_cmp(&a.1.score).then_with(|| a.0.cmp(&b.0)));
+        ranked.truncate(limit);
+
+        let mut related = Vec::with_capacity(ranked.len());
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM entries e WHERE e.id = ?",
+            ENTRY_COLUMNS
+        ))?;
+        for (id, candidate) in ranked {
+            let row = stmt.query_row([&id], EntryRow::from_row)?;
+            related.push(RelatedEntry {
+                entry: Entry::try_from(row)?,
+                score: candidate.score,
+                reasons: candidate.reasons,
+            });
+        }
+        Ok(related)
+    }
+}
@@ -0,0 +1,86 @@
+//! Retiring entry types: no new entries, existing history kept.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// Retire an entry type as of `at`.
+    ///
+    /// Retired types take no new entries; existing ones can still be read,
+    /// searched and revised. Retiring again keeps the original time.
+    /// Returns `LedgerError::NotFound` if the entry type doesn't exist.
+    pub fn retire_entry_type(&mut self, entry_type_id: &Uuid, at: DateTime<Utc>) -> Result<()> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let exists: bool = tx.query_row(
+            "SELECT COUNT(*) > 0 FROM entry_types WHERE id = ?",
+            [entry_type_id.to_string()],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(LedgerError::NotFound(format!(
+                "Entry type {} not found",
+                entry_type_id
+            )));
+        }
+
+        tx.execute(
+            "INSERT OR IGNORE INTO retired_entry_types (entry_type_id, retired_at) VALUES (?, ?)",
+            [entry_type_id.to_string(), at.to_rfc3339()],
+        )?;
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&self.now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Let a retired entry type take new entries again.
+    ///
+    /// Returns whether it was retired.
+    pub fn unretire_entry_type(&mut self, entry_type_id: &Uuid) -> Result<bool> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let removed = tx.execute(
+            "DELETE FROM retired_entry_types WHERE entry_type_id = ?",
+            [entry_type_id.to_string()],
+        )?;
+        if removed > 0 {
+            tx.execute(
+                "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+                [&self.now().to_rfc3339()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(removed > 0)
+    }
+
+    /// Retirement time of every retired entry type.
+    pub fn retired_entry_types(&self) -> Result<HashMap<Uuid, DateTime<Utc>>> {
+        let conn = self.lock_conn()?;
+        let mut stmt =
+            conn.prepare_cached("SELECT entry_type_id, retired_at FROM retired_entry_types")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut retired = HashMap::new();
+        for row in rows {
+            let (entry_type_id, at) = row?;
+            let entry_type_id = Uuid::parse_str(&entry_type_id)
+                .map_err(|e| LedgerError::Storage(format!("Invalid entry type ID: {}", e)))?;
+            let at = DateTime::parse_from_rfc3339(&at)
+                .map_err(|e| LedgerError::Storage(format!("Invalid timestamp: {}", e)))?
+                .with_timezone(&Utc);
+            retired.insert(entry_type_id, at);
+        }
+        Ok(retired)
+    }
+}
@@ -0,0 +1,49 @@
+//! Re-encryption bookkeeping.
+//!
+//! Every save encrypts the whole database afresh, with a new scrypt salt,
+//! payload nonce and a work factor calibrated on the machine doing it. An
+//! archive that is rarely written keeps whatever parameters it was last saved
+//! with, so the time of the last deliberate re-encryption is kept in `meta`
+//! for `doctor` to measure against a rotation policy.
+
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+
+use crate::error::{LedgerError, Result};
+
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// When the ledger was last re-encrypted on purpose, if ever.
+    pub fn last_reencrypted(&self) -> Result<Option<DateTime<Utc>>> {
+        let conn = self.lock_conn()?;
+        let value: Option<String> = conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'last_reencrypted'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        value
+            .map(|value| {
+                DateTime::parse_from_rfc3339(&value)
+                    .map(|at| at.with_timezone(&Utc))
+                    .map_err(|e| {
+                        LedgerError::Storage(format!("Invalid last_reencrypted timestamp: {}", e))
+                    })
+            })
+            .transpose()
+    }
+
+    /// Record a re-encryption now; the next `close` writes the fresh file.
+    ///
+    /// Does not touch `last_modified`: the contents are unchanged.
+    pub fn record_reencryption(&mut self) -> Result<DateTime<Utc>> {
+        let now = self.now();
+        self.lock_conn()?.execute(
+            "INSERT INTO meta (key, value) VALUES ('last_reencrypted', ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            [now.to_rfc3339()],
+        )?;
+        Ok(now)
+    }
+}
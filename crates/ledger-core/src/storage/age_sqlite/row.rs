@@ -6,6 +6,12 @@ use uuid::Uuid;
 use crate::error::{LedgerError, Result};
 use crate::storage::types::Entry;
 
+/// Columns selected for an [`EntryRow`], in the order `from_row` reads them.
+///
+/// Queries alias the entries table as `e`.
+pub const ENTRY_COLUMNS: &str = "e.id, e.entry_type_id, e.schema_version, e.data_json, \
+     e.tags_json, e.created_at, e.device_id, e.supersedes, e.seq";
+
 /// Raw row data from the entries table, before parsing into domain types.
 #[derive(Debug)]
 pub struct EntryRow {
@@ -17,6 +23,24 @@ pub struct EntryRow {
     pub created_at: String,
     pub device_id: String,
     pub supersedes: Option<String>,
+    pub seq: i64,
+}
+
+impl EntryRow {
+    /// Read a row selected with [`ENTRY_COLUMNS`].
+    pub fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            entry_type_id: row.get(1)?,
+            schema_version: row.get(2)?,
+            data_json: row.get(3)?,
+            tags_json: row.get(4)?,
+            created_at: row.get(5)?,
+            device_id: row.get(6)?,
+            supersedes: row.get(7)?,
+            seq: row.get(8)?,
+        })
+    }
 }
 
 impl TryFrom<EntryRow> for Entry {
@@ -55,6 +79,7 @@ impl TryFrom<EntryRow> for Entry {
             data,
             tags,
             created_at,
+            seq: row.seq,
             device_id,
             supersedes,
         })
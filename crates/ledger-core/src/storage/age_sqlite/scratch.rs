@@ -0,0 +1,90 @@
+//! The scratchpad: one free-form text buffer per ledger, kept in `meta`.
+//!
+//! It is encrypted with the rest of the ledger but is not an entry: it has
+//! no type, tags or revisions, and is overwritten in place.
+
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::Scratchpad;
+
+use super::validation::MAX_DATA_BYTES;
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// The scratchpad, or `None` if it is empty.
+    pub fn scratchpad(&self) -> Result<Option<Scratchpad>> {
+        let conn = self.lock_conn()?;
+        let text: Option<String> = conn
+            .query_row("SELECT value FROM meta WHERE key = 'scratch'", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        let Some(text) = text else {
+            return Ok(None);
+        };
+        let updated_at: Option<String> = conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'scratch_updated_at'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let updated_at = match updated_at {
+            Some(value) => DateTime::parse_from_rfc3339(&value)
+                .map_err(|e| LedgerError::Storage(format!("Invalid scratchpad time: {}", e)))?
+                .with_timezone(&Utc),
+            None => self.now(),
+        };
+        Ok(Some(Scratchpad { text, updated_at }))
+    }
+
+    /// Replace the scratchpad text; empty text clears it.
+    pub fn set_scratchpad(&mut self, text: &str) -> Result<()> {
+        if text.is_empty() {
+            self.clear_scratchpad()?;
+            return Ok(());
+        }
+        if text.len() > MAX_DATA_BYTES {
+            return Err(LedgerError::Validation(format!(
+                "Scratchpad exceeds max size ({} bytes)",
+                MAX_DATA_BYTES
+            )));
+        }
+
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let now = self.now().to_rfc3339();
+        for (key, value) in [("scratch", text), ("scratch_updated_at", now.as_str())] {
+            tx.execute(
+                "INSERT INTO meta (key, value) VALUES (?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                [key, value],
+            )?;
+        }
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&now],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Empty the scratchpad. Returns whether it held anything.
+    pub fn clear_scratchpad(&mut self) -> Result<bool> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let removed = tx.execute(
+            "DELETE FROM meta WHERE key IN ('scratch', 'scratch_updated_at')",
+            [],
+        )?;
+        if removed > 0 {
+            tx.execute(
+                "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+                [&self.now().to_rfc3339()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(removed > 0)
+    }
+}
@@ -0,0 +1,330 @@
+//! Full-text search index tokenizer.
+//!
+//! The tokenizer is fixed when the `entries_fts` table is created, so changing
+//! it means recreating the table and re-indexing every entry.
+//!
+//! Large ledgers re-index in chunks. `meta` keeps the last indexed `rowid`
+//! (`search_rebuild_cursor`) and the highest `rowid` that existed when the
+//! rebuild began (`search_rebuild_end`); entries added later are indexed on
+//! insert as usual. A saved ledger with a cursor resumes where it stopped.
+
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::{SearchRebuild, SearchTokenizer};
+
+use super::validation::fts_content_for_entry;
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// Tokenizer the search index was built with.
+    ///
+    /// Ledgers created before the choice was recorded use `porter`.
+    pub fn search_tokenizer(&self) -> Result<SearchTokenizer> {
+        let conn = self.lock_conn()?;
+        stored_tokenizer(&conn)
+    }
+
+    /// Recreate the search index with `tokenizer` and re-index every entry.
+    ///
+    /// Returns the number of entries indexed.
+    pub fn rebuild_search_index(&mut self, tokenizer: SearchTokenizer) -> Result<usize> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let indexed = rebuild_fts(&tx, tokenizer)?;
+        set_meta(&tx, "search_tokenizer", tokenizer.as_str())?;
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [self.now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(indexed)
+    }
+
+    /// The unfinished chunked rebuild, if one was interrupted.
+    pub fn pending_search_rebuild(&self) -> Result<Option<SearchRebuild>> {
+        let conn = self.lock_conn()?;
+        rebuild_progress(&conn)
+    }
+
+    /// Start a chunked rebuild with `tokenizer`, or resume the pending one if
+    /// it uses the same tokenizer.
+    ///
+    /// Starting empties the index, so search only finds entries indexed so
+    /// far until the rebuild completes.
+    pub fn begin_search_rebuild(&mut self, tokenizer: SearchTokenizer) -> Result<SearchRebuild> {
+        let mut conn = self.lock_conn()?;
+        if let Some(pending) = rebuild_progress(&conn)? {
+            if pending.tokenizer == tokenizer {
+                return Ok(pending);
+            }
+        }
+        let tx = conn.savepoint()?;
+        tx.execute_batch("DROP TABLE IF EXISTS entries_fts;")?;
+        tx.execute_batch(&fts_table_sql(tokenizer))?;
+        let end: i64 = tx.query_row("SELECT COALESCE(MAX(rowid), 0) FROM entries", [], |row| {
+            row.get(0)
+        })?;
+        set_meta(&tx, "search_tokenizer", tokenizer.as_str())?;
+        set_meta(&tx, "search_rebuild_cursor", "0")?;
+        set_meta(&tx, "search_rebuild_end", &end.to_string())?;
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [self.now().to_rfc3339()],
+        )?;
+        let progress = rebuild_progress(&tx)?;
+        tx.commit()?;
+        progress.ok_or_else(|| LedgerError::Storage("Search rebuild did not start".to_string()))
+    }
+
+    /// Index up to `limit` more entries of the pending rebuild.
+    ///
+    /// Each chunk commits together with the cursor, so stopping between
+    /// chunks never indexes an entry twice. The cursor is cleared once the
+    /// last chunk is done.
+    pub fn continue_search_rebuild(&mut self, limit: usize) -> Result<SearchRebuild> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let cursor = meta_rowid(&tx, "search_rebuild_cursor")?.ok_or_else(|| {
+            LedgerError::Storage("No search index rebuild in progress".to_string())
+        })?;
+        let end = meta_rowid(&tx, "search_rebuild_end")?.unwrap_or(0);
+        let chunk: Vec<(i64, String, String, Option<String>)> = {
+            let mut stmt = tx.prepare_cached(
+                "SELECT e.rowid, e.id, e.data_json, v.schema_json FROM entries e \
+                 LEFT JOIN entry_type_versions v \
+                   ON v.entry_type_id = e.entry_type_id AND v.version = e.schema_version \
+                 WHERE e.rowid > ? AND e.rowid <= ? ORDER BY e.rowid LIMIT ?",
+            )?;
+            let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+            let rows = stmt.query_map((cursor, end, limit), |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })?;
+            rows.collect::<std::result::Result<Vec<_>, _>>()?
+        };
+        let mut last = cursor;
+        for (rowid, id, data_json, schema_json) in &chunk {
+            index_entry(&tx, id, data_json, schema_json.as_deref())?;
+            last = *rowid;
+        }
+        if chunk.is_empty() || last >= end {
+            clear_rebuild_cursor(&tx)?;
+        } else {
+            set_meta(&tx, "search_rebuild_cursor", &last.to_string())?;
+        }
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [self.now().to_rfc3339()],
+        )?;
+        let tokenizer = stored_tokenizer(&tx)?;
+        let progress = match rebuild_progress(&tx)? {
+            Some(progress) => progress,
+            None => {
+                let total = count_entries_through(&tx, end)?;
+                SearchRebuild {
+                    tokenizer,
+                    indexed: total,
+                    total,
+                }
+            }
+        };
+        tx.commit()?;
+        Ok(progress)
+    }
+}
+
+/// `CREATE VIRTUAL TABLE` statement for the search index.
+pub(super) fn fts_table_sql(tokenizer: SearchTokenizer) -> String {
+    format!(
+        "CREATE VIRTUAL TABLE entries_fts USING fts5(entry_id UNINDEXED, content, tokenize = '{}');",
+        tokenizer.fts5_options()
+    )
+}
+
+pub(super) fn stored_tokenizer(conn: &Connection) -> Result<SearchTokenizer> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = 'search_tokenizer'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match value {
+        Some(value) => SearchTokenizer::parse(&value).ok_or_else(|| {
+            LedgerError::Storage(format!("Unknown search tokenizer in metadata: {}", value))
+        }),
+        None => Ok(SearchTokenizer::Porter),
+    }
+}
+
+/// Query terms when any is too short for the trigram index, else `None`.
+///
+/// Quotes are dropped; the terms are matched as plain substrings.
+pub(super) fn short_trigram_terms(query: &str) -> Option<Vec<String>> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| term.trim_matches('"').to_string())
+        .filter(|term| !term.is_empty())
+        .collect();
+    terms
+        .iter()
+        .any(|term| term.chars().count() < 3)
+        .then_some(terms)
+}
+
+/// Common English words never worth matching on by themselves.
+const STOPWORDS: &[&str] = &[
+    "about", "after", "again", "all", "and", "are", "been", "but", "for", "from", "had", "has",
+    "have", "her", "him", "his", "into", "its", "just", "not", "our", "out", "she", "that", "the",
+    "them", "then", "there", "they", "this", "was", "were", "what", "when", "which", "will",
+    "with", "would", "you", "your",
+];
+
+/// Up to `max` distinct words of `text` worth matching on: lower-cased, at
+/// least three characters, stopwords dropped, most frequent first (then
+/// longest, then first seen).
+pub(super) fn salient_terms(text: &str, max: usize) -> Vec<String> {
+    let mut terms: Vec<(String, usize)> = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let word = word.to_lowercase();
+        if word.chars().count() < 3 || STOPWORDS.contains(&word.as_str()) {
+            continue;
+        }
+        match terms.iter_mut().find(|(term, _)| *term == word) {
+            Some((_, count)) => *count += 1,
+            None => terms.push((word, 1)),
+        }
+    }
+    // Stable sort keeps first-seen order among equals
+    terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.0.len().cmp(&a.0.len())));
+    terms.into_iter().take(max).map(|(term, _)| term).collect()
+}
+
+/// FTS5 query matching entries that contain any of `terms`.
+pub(super) fn any_term_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("\"{}\"", term))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+/// `LIKE` pattern matching `term` anywhere, with wildcards escaped.
+pub(super) fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Schema of an entry type version, or `null` if it is missing.
+pub(super) fn schema_for(
+    conn: &Connection,
+    entry_type_id: &str,
+    version: i32,
+) -> Result<serde_json::Value> {
+    let schema_json: Option<String> = conn
+        .query_row(
+            "SELECT schema_json FROM entry_type_versions WHERE entry_type_id = ? AND version = ?",
+            (entry_type_id, version),
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(schema_json
+        .and_then(|schema| serde_json::from_str(&schema).ok())
+        .unwrap_or(serde_json::Value::Null))
+}
+
+/// Drop and recreate `entries_fts`, then index every entry.
+///
+/// Abandons any pending chunked rebuild.
+pub(super) fn rebuild_fts(conn: &Connection, tokenizer: SearchTokenizer) -> Result<usize> {
+    clear_rebuild_cursor(conn)?;
+    conn.execute_batch("DROP TABLE IF EXISTS entries_fts;")?;
+    conn.execute_batch(&fts_table_sql(tokenizer))?;
+    let entries: Vec<(String, String, Option<String>)> = {
+        let mut stmt = conn.prepare_cached(
+            "SELECT e.id, e.data_json, v.schema_json FROM entries e \
+             LEFT JOIN entry_type_versions v \
+               ON v.entry_type_id = e.entry_type_id AND v.version = e.schema_version",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()?
+    };
+    for (id, data_json, schema_json) in &entries {
+        index_entry(conn, id, data_json, schema_json.as_deref())?;
+    }
+    Ok(entries.len())
+}
+
+fn index_entry(
+    conn: &Connection,
+    id: &str,
+    data_json: &str,
+    schema_json: Option<&str>,
+) -> Result<()> {
+    let data: serde_json::Value =
+        serde_json::from_str(data_json).unwrap_or(serde_json::Value::Null);
+    let schema: serde_json::Value = schema_json
+        .and_then(|schema| serde_json::from_str(schema).ok())
+        .unwrap_or(serde_json::Value::Null);
+    conn.execute(
+        "INSERT INTO entries_fts (entry_id, content) VALUES (?, ?)",
+        (id, fts_content_for_entry(&schema, &data)),
+    )?;
+    Ok(())
+}
+
+/// Progress of the pending chunked rebuild, if any.
+pub(super) fn rebuild_progress(conn: &Connection) -> Result<Option<SearchRebuild>> {
+    let Some(cursor) = meta_rowid(conn, "search_rebuild_cursor")? else {
+        return Ok(None);
+    };
+    let end = meta_rowid(conn, "search_rebuild_end")?.unwrap_or(0);
+    Ok(Some(SearchRebuild {
+        tokenizer: stored_tokenizer(conn)?,
+        indexed: count_entries_through(conn, cursor)?,
+        total: count_entries_through(conn, end)?,
+    }))
+}
+
+fn count_entries_through(conn: &Connection, rowid: i64) -> Result<usize> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM entries WHERE rowid <= ?",
+        [rowid],
+        |row| row.get(0),
+    )?;
+    Ok(count as usize)
+}
+
+fn meta_rowid(conn: &Connection, key: &str) -> Result<Option<i64>> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM meta WHERE key = ?", [key], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    value
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| LedgerError::Storage(format!("Invalid {} in meta: {}", key, value)))
+        })
+        .transpose()
+}
+
+fn set_meta(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES (?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        (key, value),
+    )?;
+    Ok(())
+}
+
+fn clear_rebuild_cursor(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "DELETE FROM meta WHERE key IN ('search_rebuild_cursor', 'search_rebuild_end')",
+        [],
+    )?;
+    Ok(())
+}
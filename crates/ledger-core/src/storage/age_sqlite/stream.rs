@@ -0,0 +1,126 @@
+//! Cursor-based entry iteration.
+
+use std::collections::VecDeque;
+
+use crate::error::Result;
+use crate::storage::types::{Entry, EntryFilter};
+
+use super::row::{EntryRow, ENTRY_COLUMNS};
+use super::{entry_filter_conditions, AgeSqliteStorage};
+
+/// Entries fetched per query while streaming.
+const PAGE_SIZE: usize = 256;
+
+impl AgeSqliteStorage {
+    /// Count entries matching `filter`, without reading them.
+    pub fn count_entries(&self, filter: &EntryFilter) -> Result<usize> {
+        let conn = self.lock_conn()?;
+        let (conditions, params) = entry_filter_conditions(filter)?;
+        let mut query = "SELECT COUNT(*) FROM entries e".to_string();
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
+        let count: i64 =
+            conn.query_row(&query, rusqlite::params_from_iter(params.iter()), |row| {
+                row.get(0)
+            })?;
+        let count = count as usize;
+        Ok(filter.limit.map_or(count, |limit| count.min(limit)))
+    }
+}
+
+/// Iterator behind [`StorageEngine::iter_entries`] for [`AgeSqliteStorage`].
+///
+/// Pages are read by keyset on `(created_at, seq)` rather than by offset,
+/// so entries inserted behind the cursor while iterating are neither
+/// repeated nor skipped over.
+///
+/// [`StorageEngine::iter_entries`]: crate::storage::StorageEngine::iter_entries
+pub struct EntryStream<'a> {
+    storage: &'a AgeSqliteStorage,
+    filter: EntryFilter,
+    /// Sort key of the last row read
+    after: Option<(String, i64)>,
+    page: VecDeque<EntryRow>,
+    yielded: usize,
+    exhausted: bool,
+}
+
+impl<'a> EntryStream<'a> {
+    pub(super) fn new(storage: &'a AgeSqliteStorage, filter: &EntryFilter) -> Self {
+        Self {
+            storage,
+            filter: filter.clone(),
+            after: None,
+            page: VecDeque::new(),
+            yielded: 0,
+            exhausted: false,
+        }
+    }
+
+    fn fetch_page(&mut self) -> Result<()> {
+        let conn = self.storage.lock_conn()?;
+        let (mut conditions, mut params) = entry_filter_conditions(&self.filter)?;
+        let (direction, past) = if self.filter.oldest_first {
+            ("ASC", ">")
+        } else {
+            ("DESC", "<")
+        };
+        if let Some((ref created_at, seq)) = self.after {
+            conditions.push(format!(
+                "(e.created_at {} ? OR (e.created_at = ? AND e.seq {} ?))",
+                past, past
+            ));
+            params.push(Box::new(created_at.clone()));
+            params.push(Box::new(created_at.clone()));
+            params.push(Box::new(seq));
+        }
+
+        let mut query = format!("SELECT {} FROM entries e", ENTRY_COLUMNS);
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
+        query.push_str(&format!(
+            " ORDER BY e.created_at {}, e.seq {} LIMIT ?",
+            direction, direction
+        ));
+        params.push(Box::new(PAGE_SIZE as i64));
+
+        let mut stmt = conn.prepare_cached(&query)?;
+        let rows = stmt.query_map(
+            rusqlite::params_from_iter(params.iter()),
+            EntryRow::from_row,
+        )?;
+        for row in rows {
+            self.page.push_back(row?);
+        }
+        if self.page.len() < PAGE_SIZE {
+            self.exhausted = true;
+        }
+        if let Some(last) = self.page.back() {
+            self.after = Some((last.created_at.clone(), last.seq));
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for EntryStream<'_> {
+    type Item = Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.filter.limit.is_some_and(|limit| self.yielded >= limit) {
+            return None;
+        }
+        if self.page.is_empty() && !self.exhausted {
+            if let Err(err) = self.fetch_page() {
+                self.exhausted = true;
+                return Some(Err(err));
+            }
+        }
+        let row = self.page.pop_front()?;
+        self.yielded += 1;
+        Some(Entry::try_from(row))
+    }
+}
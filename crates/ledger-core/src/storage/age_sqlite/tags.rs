@@ -0,0 +1,106 @@
+//! Tag usage counts across the tag hierarchy, and tag suggestions drawn
+//! from the search index.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::TagCount;
+
+use super::search::{any_term_query, salient_terms};
+use super::AgeSqliteStorage;
+
+/// Distinct query terms taken from text to suggest tags for.
+const MAX_SUGGEST_TERMS: usize = 32;
+
+/// Best-matching entries whose tags are weighed for suggestions.
+const MAX_SUGGEST_MATCHES: usize = 100;
+
+impl AgeSqliteStorage {
+    /// Count current entries per tag, parents before their children.
+    ///
+    /// Every parent of a hierarchical tag is listed too, even when no entry
+    /// carries it directly, and its `total` rolls up the entries tagged
+    /// anywhere beneath it. An entry counts once per parent however many of
+    /// its tags share that parent.
+    pub fn tag_counts(&self) -> Result<Vec<TagCount>> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT tags_json FROM entries WHERE tags_json IS NOT NULL \
+             AND id NOT IN (SELECT supersedes FROM entries WHERE supersedes IS NOT NULL)",
+        )?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+        for row in rows {
+            let tags: Vec<String> = serde_json::from_str(&row?)
+                .map_err(|e| LedgerError::Storage(format!("Invalid tags JSON: {}", e)))?;
+            let mut paths = BTreeSet::new();
+            for tag in &tags {
+                counts.entry(tag.clone()).or_default().0 += 1;
+                paths.extend(tag_paths(tag));
+            }
+            for path in paths {
+                counts.entry(path.to_string()).or_default().1 += 1;
+            }
+        }
+
+        let mut counts: Vec<TagCount> = counts
+            .into_iter()
+            .map(|(tag, (count, total))| TagCount { tag, count, total })
+            .collect();
+        // By segment, so children follow their parent (`work/x` before `work-a`)
+        counts.sort_by(|a, b| a.tag.split('/').cmp(b.tag.split('/')));
+        Ok(counts)
+    }
+
+    /// Suggest up to `limit` tags for `text`, best first.
+    ///
+    /// The words of `text` are matched against the search index; each
+    /// matching current entry votes for its tags with its BM25 relevance, so
+    /// tags that co-occur with rare shared terms rank highest. Nothing leaves
+    /// the ledger.
+    pub fn suggest_tags(&self, text: &str, limit: usize) -> Result<Vec<String>> {
+        let terms = salient_terms(text, MAX_SUGGEST_TERMS);
+        if terms.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let query = any_term_query(&terms);
+
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT e.tags_json, bm25(entries_fts)
+            FROM entries_fts f
+            JOIN entries e ON e.id = f.entry_id
+            WHERE entries_fts MATCH ? AND e.tags_json IS NOT NULL
+              AND e.id NOT IN (SELECT supersedes FROM entries WHERE supersedes IS NOT NULL)
+            ORDER BY bm25(entries_fts)
+            LIMIT ?
+            "#,
+        )?;
+        let rows = stmt.query_map((query, MAX_SUGGEST_MATCHES as i64), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        })?;
+
+        let mut scores: HashMap<String, f64> = HashMap::new();
+        for row in rows {
+            let (tags_json, rank) = row?;
+            let tags: Vec<String> = serde_json::from_str(&tags_json)
+                .map_err(|e| LedgerError::Storage(format!("Invalid tags JSON: {}", e)))?;
+            // bm25() is negative, lower meaning more relevant
+            for tag in tags {
+                *scores.entry(tag).or_default() += -rank;
+            }
+        }
+        let mut ranked: Vec<(String, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(ranked.into_iter().take(limit).map(|(tag, _)| tag).collect())
+    }
+}
+
+/// The tag and each of its parents: `a/b/c` gives `a`, `a/b` and `a/b/c`.
+fn tag_paths(tag: &str) -> impl Iterator<Item = &str> {
+    tag.match_indices('/')
+        .map(move |(at, _)| &tag[..at])
+        .chain(std::iter::once(tag))
+}
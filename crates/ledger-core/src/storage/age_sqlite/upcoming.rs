@@ -0,0 +1,59 @@
+//! Due-date queries over recurring date fields.
+
+use chrono::NaiveDate;
+
+use crate::error::Result;
+use crate::storage::traits::StorageEngine;
+use crate::storage::types::{DueEntry, EntryFilter};
+
+use super::validation::recurring_date_fields;
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// Entries whose recurring date fields fall due between `from` and
+    /// `until` (inclusive), soonest first.
+    ///
+    /// Only current revisions are considered, and only fields annotated with
+    /// a `recurrence` in the entry type's active schema.
+    pub fn due_entries(&self, from: NaiveDate, until: NaiveDate) -> Result<Vec<DueEntry>> {
+        let superseded = self.superseded_entry_ids()?;
+        let mut due = Vec::new();
+        for entry_type in self.list_entry_types()? {
+            let fields = recurring_date_fields(&entry_type.schema_json);
+            if fields.is_empty() {
+                continue;
+            }
+            let entries = self.list_entries(&EntryFilter::new().entry_type(entry_type.id))?;
+            for entry in entries.iter().filter(|e| !superseded.contains(&e.id)) {
+                for (field, recurrence, notify) in &fields {
+                    let Some(date) = entry
+                        .data
+                        .get(field)
+                        .and_then(|value| value.as_str())
+                        .and_then(|raw| NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok())
+                    else {
+                        continue;
+                    };
+                    let (next, occurrence) = recurrence.next_on_or_after(date, from);
+                    if next <= until {
+                        due.push(DueEntry {
+                            entry: entry.clone(),
+                            field: field.clone(),
+                            date,
+                            due: next,
+                            occurrence,
+                            recurrence: *recurrence,
+                            notify: *notify,
+                        });
+                    }
+                }
+            }
+        }
+        due.sort_by(|a, b| {
+            a.due
+                .cmp(&b.due)
+                .then_with(|| a.entry.created_at.cmp(&b.entry.created_at))
+        });
+        Ok(due)
+    }
+}
@@ -0,0 +1,280 @@
+//! Per-composition entry counts and membership history, and per-template
+//! usage counts.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+use uuid::Uuid;
+
+use crate::error::{LedgerError, Result};
+use crate::storage::types::{CompositionStats, EntryComposition, EntryTemplate, TemplateUsage};
+
+use super::AgeSqliteStorage;
+
+impl AgeSqliteStorage {
+    /// Record that the entry was created from version `version` of the
+    /// template.
+    ///
+    /// An entry has at most one template; recording again replaces it.
+    /// Returns `LedgerError::NotFound` if the entry doesn't exist and
+    /// `LedgerError::TemplateNotFound` if the template doesn't.
+    pub fn record_template_use(
+        &mut self,
+        entry_id: &Uuid,
+        template_id: &Uuid,
+        version: i32,
+    ) -> Result<()> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn.savepoint()?;
+        let missing = |table: &str, id: &Uuid| -> Result<bool> {
+            let exists: Option<String> = tx
+                .query_row(
+                    &format!("SELECT id FROM {} WHERE id = ?", table),
+                    [id.to_string()],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(exists.is_none())
+        };
+        if missing("entries", entry_id)? {
+            return Err(LedgerError::NotFound(format!(
+                "Entry {} not found",
+                entry_id
+            )));
+        }
+        if missing("templates", template_id)? {
+            return Err(LedgerError::TemplateNotFound(*template_id));
+        }
+
+        tx.execute(
+            "INSERT INTO entry_templates (entry_id, template_id, template_version) VALUES (?, ?, ?) \
+             ON CONFLICT(entry_id) DO UPDATE SET template_id = excluded.template_id, \
+             template_version = excluded.template_version",
+            rusqlite::params![entry_id.to_string(), template_id.to_string(), version],
+        )?;
+        tx.execute(
+            "UPDATE meta SET value = ? WHERE key = 'last_modified'",
+            [&self.now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// The template the entry was created from.
+    ///
+    /// Revisions made by editing inherit the template of the revision they
+    /// replace, so this follows the `supersedes` chain back to the first
+    /// revision that recorded one.
+    pub fn entry_template(&self, entry_id: &Uuid) -> Result<Option<EntryTemplate>> {
+        let conn = self.lock_conn()?;
+        let row = conn
+            .query_row(
+                r#"
+                WITH RECURSIVE chain(id, depth) AS (
+                    SELECT ?, 0
+                    UNION ALL
+                    SELECT e.supersedes, chain.depth + 1
+                    FROM entries e JOIN chain ON e.id = chain.id
+                    WHERE e.supersedes IS NOT NULL
+                )
+                SELECT t.id, t.name, et.template_version
+                FROM chain
+                JOIN entry_templates et ON et.entry_id = chain.id
+                JOIN templates t ON t.id = et.template_id
+                ORDER BY chain.depth
+                LIMIT 1
+                "#,
+                [entry_id.to_string()],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<i32>>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        row.map(|(id, name, version)| {
+            Ok(EntryTemplate {
+                id: parse_uuid(&id)?,
+                name,
+                version,
+            })
+        })
+        .transpose()
+    }
+
+    /// Entries created from the template, as recorded when they were added.
+    pub fn template_entries(&self, template_id: &Uuid) -> Result<Vec<Uuid>> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT entry_id FROM entry_templates WHERE template_id = ? ORDER BY entry_id",
+        )?;
+        let rows = stmt.query_map([template_id.to_string()], |row| row.get::<_, String>(0))?;
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(parse_uuid(&row?)?);
+        }
+        Ok(entries)
+    }
+
+    /// Current member count and latest member entry of every composition
+    /// that has at least one current member.
+    pub fn composition_stats(&self) -> Result<HashMap<Uuid, CompositionStats>> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT ec.composition_id, COUNT(*), MAX(e.created_at)
+            FROM entry_compositions ec
+            JOIN entries e ON e.id = ec.entry_id
+            WHERE ec.removed_at IS NULL
+              AND e.id NOT IN (SELECT supersedes FROM entries WHERE supersedes IS NOT NULL)
+            GROUP BY ec.composition_id
+            "#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        let mut stats = HashMap::new();
+        for row in rows {
+            let (id, count, last) = row?;
+            stats.insert(
+                parse_uuid(&id)?,
+                CompositionStats {
+                    entry_count: count as usize,
+                    last_activity: Some(parse_timestamp(&last)?),
+                },
+            );
+        }
+        Ok(stats)
+    }
+
+    /// Every membership a composition has had, current and detached, oldest
+    /// first.
+    ///
+    /// Returns `LedgerError::CompositionNotFound` if the composition doesn't exist.
+    pub fn composition_history(&self, composition_id: &Uuid) -> Result<Vec<EntryComposition>> {
+        let conn = self.lock_conn()?;
+        let exists: Option<String> = conn
+            .query_row(
+                "SELECT id FROM compositions WHERE id = ?",
+                [composition_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if exists.is_none() {
+            return Err(LedgerError::CompositionNotFound(*composition_id));
+        }
+
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT entry_id, added_at, removed_at
+            FROM entry_compositions
+            WHERE composition_id = ?
+            ORDER BY added_at, rowid
+            "#,
+        )?;
+        let rows = stmt.query_map([composition_id.to_string()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?;
+        let mut history = Vec::new();
+        for row in rows {
+            let (entry_id, added_at, removed_at) = row?;
+            history.push(EntryComposition {
+                entry_id: parse_uuid(&entry_id)?,
+                composition_id: *composition_id,
+                added_at: parse_timestamp(&added_at)?,
+                removed_at: removed_at.as_deref().map(parse_timestamp).transpose()?,
+            });
+        }
+        Ok(history)
+    }
+
+    /// Usage counts and default status of every template.
+    ///
+    /// A use is an entry created from the template, counted once however
+    /// often it was revised afterwards. Entries added before usage was
+    /// recorded aren't counted, and those added before versions were
+    /// recorded count towards `uses` but no version.
+    pub fn template_usage(&self) -> Result<HashMap<Uuid, TemplateUsage>> {
+        let conn = self.lock_conn()?;
+        let mut usage = HashMap::new();
+        {
+            let mut stmt = conn.prepare_cached(
+                r#"
+                SELECT t.id, EXISTS (
+                    SELECT 1 FROM entry_type_templates ett
+                    WHERE ett.template_id = t.id AND ett.active = 1
+                )
+                FROM templates t
+                "#,
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+            })?;
+            for row in rows {
+                let (id, is_default) = row?;
+                usage.insert(
+                    parse_uuid(&id)?,
+                    TemplateUsage {
+                        is_default,
+                        ..TemplateUsage::default()
+                    },
+                );
+            }
+        }
+
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT et.template_id, et.template_version, COUNT(*), MAX(e.created_at)
+            FROM entry_templates et
+            JOIN entries e ON e.id = et.entry_id
+            GROUP BY et.template_id, et.template_version
+            "#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<i32>>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (id, version, count, last) = row?;
+            let Some(template) = usage.get_mut(&parse_uuid(&id)?) else {
+                continue;
+            };
+            let last = parse_timestamp(&last)?;
+            template.uses += count as usize;
+            if let Some(version) = version {
+                template.by_version.insert(version, count as usize);
+            }
+            if template.last_used.is_none_or(|at| at < last) {
+                template.last_used = Some(last);
+            }
+        }
+        Ok(usage)
+    }
+}
+
+fn parse_uuid(id: &str) -> Result<Uuid> {
+    Uuid::parse_str(id).map_err(|e| LedgerError::Storage(format!("Invalid UUID: {}", e)))
+}
+
+fn parse_timestamp(at: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(at)
+        .map_err(|e| LedgerError::Storage(format!("Invalid timestamp: {}", e)))?
+        .with_timezone(&Utc))
+}
@@ -2,9 +2,12 @@
 
 use std::collections::HashSet;
 
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use chrono::{DateTime, NaiveDate};
 
 use crate::error::{LedgerError, Result};
+use crate::storage::types::{Recurrence, SummaryTemplate, ENTRY_TYPE_COLORS};
 
 /// Maximum bytes per tag.
 pub const MAX_TAG_BYTES: usize = 128;
@@ -15,11 +18,135 @@ pub const MAX_TAGS_PER_ENTRY: usize = 100;
 /// Maximum bytes for entry data JSON.
 pub const MAX_DATA_BYTES: usize = 1024 * 1024;
 
+/// Maximum bytes for a single attachment.
+///
+/// The whole ledger is held in memory, so attachments are kept modest.
+pub const MAX_ATTACHMENT_BYTES: usize = 32 * 1024 * 1024;
+
+/// Maximum decoded bytes of a `bytes` field value.
+///
+/// Enough for a thumbnail or QR code; larger files belong in attachments.
+pub const MAX_BYTES_FIELD_BYTES: usize = 256 * 1024;
+
+/// Maximum bytes for a secret value.
+pub const MAX_SECRET_BYTES: usize = 64 * 1024;
+
+/// Maximum `decimals` display annotation on a number field.
+pub const MAX_DISPLAY_DECIMALS: u64 = 10;
+
+/// Maximum characters in an entry type `icon`.
+///
+/// Enough for an emoji built from several code points.
+pub const MAX_TYPE_ICON_CHARS: usize = 8;
+
+/// Maximum bytes for a secret name.
+pub const MAX_SECRET_NAME_BYTES: usize = 128;
+
+/// Maximum bytes of an entry indexed for full-text search.
+///
+/// Search only needs enough text to find an entry; the rest stays unindexed.
+pub const MAX_FTS_CONTENT_BYTES: usize = 256 * 1024;
+
+/// Maximum bytes for a habit name.
+pub const MAX_HABIT_NAME_BYTES: usize = 64;
+
+/// Validate a habit name (lowercase alphanumeric, dash, underscore).
+pub fn validate_habit_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_HABIT_NAME_BYTES {
+        return Err(LedgerError::Validation(format!(
+            "Habit name must be 1-{} bytes",
+            MAX_HABIT_NAME_BYTES
+        )));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_'))
+    {
+        return Err(LedgerError::Validation(format!(
+            "Invalid habit name: {} (use lowercase letters, digits, '-', '_')",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Maximum bytes for a pack name.
+pub const MAX_PACK_NAME_BYTES: usize = 64;
+
+/// Validate a pack name (lowercase alphanumeric, dash, underscore).
+pub fn validate_pack_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_PACK_NAME_BYTES {
+        return Err(LedgerError::Validation(format!(
+            "Pack name must be 1-{} bytes",
+            MAX_PACK_NAME_BYTES
+        )));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_'))
+    {
+        return Err(LedgerError::Validation(format!(
+            "Invalid pack name: {} (use lowercase letters, digits, '-', '_')",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Maximum bytes for a sync peer name.
+pub const MAX_PEER_NAME_BYTES: usize = 64;
+
+/// Validate a sync peer name (lowercase alphanumeric, dash, underscore).
+pub fn validate_peer_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_PEER_NAME_BYTES {
+        return Err(LedgerError::Validation(format!(
+            "Peer name must be 1-{} bytes",
+            MAX_PEER_NAME_BYTES
+        )));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_'))
+    {
+        return Err(LedgerError::Validation(format!(
+            "Invalid peer name: {} (use lowercase letters, digits, '-', '_')",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Validate a secret name (lowercase alphanumeric, dash, underscore, dot).
+pub fn validate_secret_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(LedgerError::Validation(
+            "Secret name cannot be empty".to_string(),
+        ));
+    }
+    if name.len() > MAX_SECRET_NAME_BYTES {
+        return Err(LedgerError::Validation(format!(
+            "Secret name too long (max {} bytes)",
+            MAX_SECRET_NAME_BYTES
+        )));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(LedgerError::Validation(format!(
+            "Invalid secret name: {} (use lowercase letters, digits, '-', '_', '.')",
+            name
+        )));
+    }
+    Ok(())
+}
+
 /// Normalize and validate tags.
 ///
 /// - Trims whitespace and converts to lowercase
 /// - Removes duplicates
-/// - Validates character set (alphanumeric, dash, underscore, colon)
+/// - Validates character set (alphanumeric, dash, underscore, colon, and
+///   `/` between the segments of a hierarchical tag like `work/project-x`)
 /// - Enforces length limits
 pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>> {
     if tags.len() > MAX_TAGS_PER_ENTRY {
@@ -47,12 +174,18 @@ pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>> {
         }
         if !trimmed
             .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == ':')
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '/'))
         {
             return Err(LedgerError::Validation(
                 "Tag contains invalid characters".to_string(),
             ));
         }
+        if trimmed.split('/').any(str::is_empty) {
+            return Err(LedgerError::Validation(format!(
+                "Invalid tag hierarchy: {} (separate non-empty parts with '/', e.g. work/project-x)",
+                trimmed
+            )));
+        }
         // Use HashSet for O(1) duplicate detection instead of Vec::contains O(n)
         if seen.insert(trimmed.clone()) {
             normalized.push(trimmed);
@@ -63,6 +196,9 @@ pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>> {
 }
 
 /// Validate entry data against a schema.
+///
+/// Data that does not fit a declared field, or names an undeclared one,
+/// returns `LedgerError::SchemaViolation` for that field.
 pub fn validate_entry_data(
     schema_json: &serde_json::Value,
     data: &serde_json::Value,
@@ -101,20 +237,20 @@ pub fn validate_entry_data(
             Some(v) => v,
             None => {
                 if required {
-                    return Err(LedgerError::Validation(format!(
-                        "Missing required field: {}",
-                        name
-                    )));
+                    return Err(schema_violation(
+                        name,
+                        format!("Missing required field: {}", name),
+                    ));
                 }
                 continue;
             }
         };
         if value.is_null() {
             if !nullable {
-                return Err(LedgerError::Validation(format!(
-                    "Field {} cannot be null",
-                    name
-                )));
+                return Err(schema_violation(
+                    name,
+                    format!("Field {} cannot be null", name),
+                ));
             }
             continue;
         }
@@ -122,56 +258,74 @@ pub fn validate_entry_data(
         match field_type {
             "string" | "text" => {
                 if !value.is_string() {
-                    return Err(LedgerError::Validation(format!(
-                        "Field {} must be a string",
-                        name
-                    )));
+                    return Err(schema_violation(
+                        name,
+                        format!("Field {} must be a string", name),
+                    ));
                 }
             }
             "number" => {
                 if !value.is_number() {
-                    return Err(LedgerError::Validation(format!(
-                        "Field {} must be a number",
-                        name
-                    )));
+                    return Err(schema_violation(
+                        name,
+                        format!("Field {} must be a number", name),
+                    ));
                 }
             }
             "integer" => {
                 if value.as_i64().is_none() {
-                    return Err(LedgerError::Validation(format!(
-                        "Field {} must be an integer",
-                        name
-                    )));
+                    return Err(schema_violation(
+                        name,
+                        format!("Field {} must be an integer", name),
+                    ));
                 }
             }
             "boolean" => {
                 if !value.is_boolean() {
-                    return Err(LedgerError::Validation(format!(
-                        "Field {} must be a boolean",
-                        name
-                    )));
+                    return Err(schema_violation(
+                        name,
+                        format!("Field {} must be a boolean", name),
+                    ));
                 }
             }
             "date" => {
                 let raw = value.as_str().ok_or_else(|| {
-                    LedgerError::Validation(format!("Field {} must be a date string", name))
+                    schema_violation(name, format!("Field {} must be a date string", name))
                 })?;
                 if NaiveDate::parse_from_str(raw, "%Y-%m-%d").is_err() {
-                    return Err(LedgerError::Validation(format!(
-                        "Field {} must be YYYY-MM-DD",
-                        name
-                    )));
+                    return Err(schema_violation(
+                        name,
+                        format!("Field {} must be YYYY-MM-DD", name),
+                    ));
                 }
             }
             "datetime" => {
                 let raw = value.as_str().ok_or_else(|| {
-                    LedgerError::Validation(format!("Field {} must be an ISO-8601 string", name))
+                    schema_violation(name, format!("Field {} must be an ISO-8601 string", name))
                 })?;
                 if DateTime::parse_from_rfc3339(raw).is_err() {
-                    return Err(LedgerError::Validation(format!(
-                        "Field {} must be ISO-8601",
-                        name
-                    )));
+                    return Err(schema_violation(
+                        name,
+                        format!("Field {} must be ISO-8601", name),
+                    ));
+                }
+            }
+            "bytes" => {
+                let decoded = value
+                    .as_str()
+                    .and_then(|raw| STANDARD.decode(raw).ok())
+                    .ok_or_else(|| {
+                        schema_violation(name, format!("Field {} must be base64", name))
+                    })?;
+                let max = field
+                    .get("max_bytes")
+                    .and_then(|value| value.as_u64())
+                    .map_or(MAX_BYTES_FIELD_BYTES, |max| max as usize);
+                if decoded.len() > max {
+                    return Err(schema_violation(
+                        name,
+                        format!("Field {} is {} bytes (max {})", name, decoded.len(), max),
+                    ));
                 }
             }
             other => {
@@ -185,17 +339,271 @@ pub fn validate_entry_data(
 
     for key in data_obj.keys() {
         if !allowed_fields.contains(key) {
-            return Err(LedgerError::Validation(format!("Unknown field: {}", key)));
+            return Err(schema_violation(key, format!("Unknown field: {}", key)));
         }
     }
 
     Ok(())
 }
 
-/// Extract FTS content from entry data.
-pub fn fts_content_for_entry(data: &serde_json::Value) -> String {
-    data.get("body")
-        .and_then(|value| value.as_str())
-        .map(|value| value.to_string())
-        .unwrap_or_else(|| data.to_string())
+fn schema_violation(field: &str, message: String) -> LedgerError {
+    LedgerError::SchemaViolation {
+        field: field.to_string(),
+        message,
+    }
+}
+
+/// Validate schema field annotations that the storage and display layers act on.
+///
+/// - `recurrence` must sit on a `date` field and parse as a [`Recurrence`];
+///   `notify`, a boolean, only on a field with a `recurrence`
+/// - `unit`, `decimals` and `currency` must sit on `number` or `integer`
+///   fields; `decimals` is at most [`MAX_DISPLAY_DECIMALS`] and `currency` is
+///   a three-letter code
+/// - `index` is a boolean
+/// - `description` (on the schema or a field) is a non-empty string and a
+///   field `example` is a string, number or boolean
+/// - `max_bytes` sits on a `bytes` field and is at most
+///   [`MAX_BYTES_FIELD_BYTES`]
+/// - a top-level `icon` is a short non-empty string and a top-level `color`
+///   is one of [`ENTRY_TYPE_COLORS`]
+pub fn validate_schema_annotations(schema_json: &serde_json::Value) -> Result<()> {
+    if let Some(icon) = schema_json.get("icon") {
+        let valid = icon.as_str().is_some_and(|icon| {
+            !icon.trim().is_empty() && icon.chars().count() <= MAX_TYPE_ICON_CHARS
+        });
+        if !valid {
+            return Err(LedgerError::Validation(format!(
+                "Invalid icon: {} (use a short string or emoji, at most {} characters)",
+                icon, MAX_TYPE_ICON_CHARS
+            )));
+        }
+    }
+    if let Some(color) = schema_json.get("color") {
+        if !color
+            .as_str()
+            .is_some_and(|color| ENTRY_TYPE_COLORS.contains(&color))
+        {
+            return Err(LedgerError::Validation(format!(
+                "Invalid color: {} (use one of {})",
+                color,
+                ENTRY_TYPE_COLORS.join(", ")
+            )));
+        }
+    }
+
+    let fields = schema_json
+        .get("fields")
+        .and_then(|value| value.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    validate_description(schema_json.get("description"), "entry type")?;
+    if let Some(template) = schema_json.get("summary_template") {
+        let parsed = template
+            .as_str()
+            .and_then(SummaryTemplate::parse)
+            .ok_or_else(|| {
+                LedgerError::Validation(format!(
+                    "Invalid summary_template: {} (use text with {{field}} placeholders)",
+                    template
+                ))
+            })?;
+        for name in parsed.fields() {
+            let declared = fields
+                .iter()
+                .any(|field| field.get("name").and_then(|value| value.as_str()) == Some(name));
+            if !declared {
+                return Err(LedgerError::Validation(format!(
+                    "summary_template refers to unknown field: {}",
+                    name
+                )));
+            }
+        }
+    }
+    for field in fields {
+        let name = field
+            .get("name")
+            .and_then(|value| value.as_str())
+            .unwrap_or("?");
+        let field_type = field.get("type").and_then(|value| value.as_str());
+
+        if let Some(recurrence) = field.get("recurrence") {
+            if field_type != Some("date") {
+                return Err(LedgerError::Validation(format!(
+                    "Field {} has a recurrence but is not a date field",
+                    name
+                )));
+            }
+            if recurrence.as_str().and_then(Recurrence::parse).is_none() {
+                return Err(LedgerError::Validation(format!(
+                    "Invalid recurrence for field {}: {} (use yearly, monthly, weekly, daily or an interval like 10d)",
+                    name, recurrence
+                )));
+            }
+        }
+        if let Some(notify) = field.get("notify") {
+            if field.get("recurrence").is_none() {
+                return Err(LedgerError::Validation(format!(
+                    "Field {} has notify but no recurrence",
+                    name
+                )));
+            }
+            if !notify.is_boolean() {
+                return Err(LedgerError::Validation(format!(
+                    "Invalid notify for field {}: {} (use true or false)",
+                    name, notify
+                )));
+            }
+        }
+
+        validate_description(field.get("description"), &format!("field {}", name))?;
+        if let Some(example) = field.get("example") {
+            if !(example.is_string() || example.is_number() || example.is_boolean()) {
+                return Err(LedgerError::Validation(format!(
+                    "Invalid example for field {}: use a string, number or boolean",
+                    name
+                )));
+            }
+        }
+        if field.get("index").is_some_and(|index| !index.is_boolean()) {
+            return Err(LedgerError::Validation(format!(
+                "Invalid index for field {}: use true or false",
+                name
+            )));
+        }
+
+        if let Some(max_bytes) = field.get("max_bytes") {
+            if field_type != Some("bytes") {
+                return Err(LedgerError::Validation(format!(
+                    "Field {} has a max_bytes but is not a bytes field",
+                    name
+                )));
+            }
+            let limit = MAX_BYTES_FIELD_BYTES as u64;
+            if max_bytes.as_u64().is_none_or(|max| max == 0 || max > limit) {
+                return Err(LedgerError::Validation(format!(
+                    "Invalid max_bytes for field {}: use 1 to {}",
+                    name, limit
+                )));
+            }
+        }
+
+        let numeric = matches!(field_type, Some("number" | "integer"));
+        for key in ["unit", "decimals", "currency"] {
+            if field.get(key).is_some() && !numeric {
+                return Err(LedgerError::Validation(format!(
+                    "Field {} has a {} but is not a number field",
+                    name, key
+                )));
+            }
+        }
+        if let Some(unit) = field.get("unit") {
+            if unit.as_str().is_none_or(|u| u.trim().is_empty()) {
+                return Err(LedgerError::Validation(format!(
+                    "Invalid unit for field {}: must be a non-empty string",
+                    name
+                )));
+            }
+        }
+        if let Some(decimals) = field.get("decimals") {
+            if decimals.as_u64().is_none_or(|d| d > MAX_DISPLAY_DECIMALS) {
+                return Err(LedgerError::Validation(format!(
+                    "Invalid decimals for field {}: use 0 to {}",
+                    name, MAX_DISPLAY_DECIMALS
+                )));
+            }
+        }
+        if let Some(currency) = field.get("currency") {
+            let valid = currency
+                .as_str()
+                .is_some_and(|c| c.len() == 3 && c.chars().all(|ch| ch.is_ascii_alphabetic()));
+            if !valid {
+                return Err(LedgerError::Validation(format!(
+                    "Invalid currency for field {}: {} (use a code like USD or EUR)",
+                    name, currency
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_description(description: Option<&serde_json::Value>, owner: &str) -> Result<()> {
+    match description {
+        Some(description) if description.as_str().is_none_or(|d| d.trim().is_empty()) => {
+            Err(LedgerError::Validation(format!(
+                "Invalid description for {}: must be a non-empty string",
+                owner
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Fields a schema keeps out of the search index: those marked
+/// `"index": false`, and every `bytes` field.
+pub fn unindexed_fields(schema_json: &serde_json::Value) -> Vec<&str> {
+    schema_json
+        .get("fields")
+        .and_then(|value| value.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|field| {
+            field.get("index").and_then(|value| value.as_bool()) == Some(false)
+                || field.get("type").and_then(|value| value.as_str()) == Some("bytes")
+        })
+        .filter_map(|field| field.get("name")?.as_str())
+        .collect()
+}
+
+/// Date fields of a schema that carry a `recurrence` annotation, with
+/// whether they want desktop notifications (`notify`, default true).
+pub fn recurring_date_fields(schema_json: &serde_json::Value) -> Vec<(String, Recurrence, bool)> {
+    schema_json
+        .get("fields")
+        .and_then(|value| value.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|field| field.get("type").and_then(|value| value.as_str()) == Some("date"))
+        .filter_map(|field| {
+            let name = field.get("name")?.as_str()?;
+            let recurrence = Recurrence::parse(field.get("recurrence")?.as_str()?)?;
+            let notify = field
+                .get("notify")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(true);
+            Some((name.to_string(), recurrence, notify))
+        })
+        .collect()
+}
+
+/// Extract FTS content from entry data, capped at `MAX_FTS_CONTENT_BYTES`.
+///
+/// Fields the schema marks `"index": false` are left out first; what remains
+/// is the body if there is one, else the rest of the data as JSON.
+pub fn fts_content_for_entry(schema_json: &serde_json::Value, data: &serde_json::Value) -> String {
+    let unindexed = unindexed_fields(schema_json);
+    let data = match data.as_object() {
+        Some(object) if !unindexed.is_empty() => serde_json::Value::Object(
+            object
+                .iter()
+                .filter(|(name, _)| !unindexed.contains(&name.as_str()))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        ),
+        _ => data.clone(),
+    };
+    let mut content = match data.get("body").and_then(|value| value.as_str()) {
+        Some(body) => body.to_string(),
+        None if data.as_object().is_some_and(|object| object.is_empty()) => String::new(),
+        None => data.to_string(),
+    };
+    if content.len() > MAX_FTS_CONTENT_BYTES {
+        let mut end = MAX_FTS_CONTENT_BYTES;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        content.truncate(end);
+    }
+    content
 }
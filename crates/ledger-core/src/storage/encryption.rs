@@ -1,14 +1,22 @@
 //! Age encryption/decryption utilities.
 //!
 //! This module provides wrappers around the Age encryption library for
-//! encrypting and decrypting ledger data using passphrase-based encryption.
+//! encrypting and decrypting ledger data using passphrase-based encryption,
+//! plus recipient-based encryption for sharing data with someone else.
 //!
 //! Note: Age uses scrypt internally for passphrase-based encryption.
 //! While RFC-001 specified Argon2id, we use Age's built-in passphrase support
 //! for simplicity and correctness. The security properties are similar.
+//!
+//! Unlock time is dominated by the scrypt work factor, which age calibrates
+//! to about a second on the machine that encrypted the file. The age format
+//! fixes scrypt's parallelism at 1, and SQLite can only deserialize a complete
+//! image, so neither step can be split across threads; decryption just avoids
+//! regrowing the plaintext buffer.
 
-use std::io::{Read, Write};
+use std::io::{BufReader, Read, Write};
 use std::iter;
+use std::str::FromStr;
 
 use age::secrecy::SecretString;
 
@@ -71,10 +79,9 @@ pub fn encrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
 ///
 /// # Errors
 ///
-/// Returns `LedgerError::Crypto` if:
-/// - The passphrase is incorrect
-/// - The data is corrupted
-/// - Decryption fails for any reason
+/// Returns `LedgerError::IncorrectPassphrase` if the passphrase is wrong,
+/// `LedgerError::Corrupted` if the data is damaged or truncated, and
+/// `LedgerError::Crypto` if decryption fails for any other reason.
 ///
 /// # Examples
 ///
@@ -88,9 +95,10 @@ pub fn encrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
 /// ```
 pub fn decrypt(encrypted_data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
     let decryptor = age::Decryptor::new(encrypted_data)
-        .map_err(|e| LedgerError::Crypto(format!("Failed to create decryptor: {}", e)))?;
+        .map_err(|e| LedgerError::Corrupted(format!("Failed to create decryptor: {}", e)))?;
 
-    let mut decrypted = Vec::new();
+    // Plaintext is always shorter than the ciphertext, so this never regrows
+    let mut decrypted = Vec::with_capacity(encrypted_data.len());
 
     let identity = age::scrypt::Identity::new(SecretString::from(passphrase.to_string()));
     let mut reader = decryptor
@@ -99,12 +107,98 @@ pub fn decrypt(encrypted_data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
             age::DecryptError::NoMatchingKeys
             | age::DecryptError::DecryptionFailed
             | age::DecryptError::KeyDecryptionFailed => LedgerError::IncorrectPassphrase,
+            age::DecryptError::InvalidHeader | age::DecryptError::InvalidMac => {
+                LedgerError::Corrupted(format!("Decryption failed: {}", e))
+            }
             _ => LedgerError::Crypto(format!("Decryption failed: {}", e)),
         })?;
 
     reader
         .read_to_end(&mut decrypted)
-        .map_err(|e| LedgerError::Crypto(format!("Failed to read decrypted data: {}", e)))?;
+        .map_err(|e| LedgerError::Corrupted(format!("Failed to read decrypted data: {}", e)))?;
+
+    Ok(decrypted)
+}
+
+/// The scrypt work factor (log2 of N) in the header of passphrase-encrypted
+/// data, or `None` if the data is not a passphrase-encrypted age file.
+///
+/// Only the plaintext header is read; no passphrase is needed.
+pub fn scrypt_work_factor(encrypted_data: &[u8]) -> Option<u8> {
+    let mut lines = encrypted_data.split(|&byte| byte == b'\n');
+    if lines.next()? != b"age-encryption.org/v1" {
+        return None;
+    }
+    let stanza = std::str::from_utf8(lines.next()?).ok()?;
+    match stanza.split(' ').collect::<Vec<_>>().as_slice() {
+        ["->", "scrypt", _salt, log_n] => log_n.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Encrypt data to one or more age X25519 recipients (`age1...`).
+///
+/// Only holders of a matching identity can decrypt the result; the ledger
+/// passphrase plays no part.
+pub fn encrypt_to_recipients(data: &[u8], recipients: &[String]) -> Result<Vec<u8>> {
+    if recipients.is_empty() {
+        return Err(LedgerError::Crypto(
+            "At least one age recipient is required".to_string(),
+        ));
+    }
+    let parsed = recipients
+        .iter()
+        .map(|recipient| {
+            age::x25519::Recipient::from_str(recipient.trim()).map_err(|e| {
+                LedgerError::Crypto(format!("Invalid age recipient {}: {}", recipient, e))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let encryptor =
+        age::Encryptor::with_recipients(parsed.iter().map(|r| r as &dyn age::Recipient))
+            .map_err(|e| LedgerError::Crypto(format!("Failed to create encryptor: {}", e)))?;
+
+    let mut encrypted = Vec::new();
+    let mut writer = encryptor
+        .wrap_output(&mut encrypted)
+        .map_err(|e| LedgerError::Crypto(format!("Failed to create encryptor: {}", e)))?;
+    writer
+        .write_all(data)
+        .map_err(|e| LedgerError::Crypto(format!("Encryption write failed: {}", e)))?;
+    writer
+        .finish()
+        .map_err(|e| LedgerError::Crypto(format!("Encryption finish failed: {}", e)))?;
+
+    Ok(encrypted)
+}
+
+/// Decrypt recipient-encrypted data with the identities in an age key file.
+///
+/// `identity_file` is the file's contents (`AGE-SECRET-KEY-1...` lines;
+/// comments and blank lines are ignored).
+pub fn decrypt_with_identities(encrypted_data: &[u8], identity_file: &str) -> Result<Vec<u8>> {
+    let identities = age::IdentityFile::from_buffer(BufReader::new(identity_file.as_bytes()))
+        .map_err(|e| LedgerError::Crypto(format!("Invalid age identity file: {}", e)))?
+        .into_identities()
+        .map_err(|e| LedgerError::Crypto(format!("Invalid age identity file: {}", e)))?;
+    let decryptor = age::Decryptor::new(encrypted_data)
+        .map_err(|e| LedgerError::Corrupted(format!("Failed to create decryptor: {}", e)))?;
+
+    let mut reader = decryptor
+        .decrypt(identities.iter().map(|identity| identity.as_ref()))
+        .map_err(|e| match e {
+            age::DecryptError::NoMatchingKeys => LedgerError::Crypto(
+                "None of the identities in the key file can open this data".to_string(),
+            ),
+            age::DecryptError::InvalidHeader | age::DecryptError::InvalidMac => {
+                LedgerError::Corrupted(format!("Decryption failed: {}", e))
+            }
+            _ => LedgerError::Crypto(format!("Decryption failed: {}", e)),
+        })?;
+    let mut decrypted = Vec::with_capacity(encrypted_data.len());
+    reader
+        .read_to_end(&mut decrypted)
+        .map_err(|e| LedgerError::Corrupted(format!("Failed to read decrypted data: {}", e)))?;
 
     Ok(decrypted)
 }
@@ -168,6 +262,34 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_damaged_data_is_corrupted_not_wrong_passphrase() {
+        let passphrase = "test-passphrase-secure-123";
+        let encrypted = encrypt(b"secret data", passphrase).unwrap();
+
+        let truncated = &encrypted[..encrypted.len() - 4];
+        assert!(matches!(
+            decrypt(truncated, passphrase),
+            Err(LedgerError::Corrupted(_))
+        ));
+        assert!(matches!(
+            decrypt(b"not an age file", passphrase),
+            Err(LedgerError::Corrupted(_))
+        ));
+    }
+
+    #[test]
+    fn test_scrypt_work_factor_read_from_header() {
+        let encrypted = encrypt(b"secret data", "test-passphrase-secure-123").unwrap();
+        let log_n = scrypt_work_factor(&encrypted).expect("scrypt stanza");
+        assert!((10..=30).contains(&log_n));
+
+        let recipient = age::x25519::Identity::generate().to_public().to_string();
+        let shared = encrypt_to_recipients(b"x", &[recipient]).unwrap();
+        assert_eq!(scrypt_work_factor(&shared), None);
+        assert_eq!(scrypt_work_factor(b"not an age file"), None);
+    }
+
     #[test]
     fn test_empty_data_encryption() {
         let passphrase = "test-passphrase-secure-123";
@@ -203,4 +325,32 @@ mod tests {
         // Different passphrases should produce different ciphertext
         assert_ne!(encrypted1, encrypted2);
     }
+
+    #[test]
+    fn test_recipient_round_trip() {
+        let identity = age::x25519::Identity::generate();
+        let recipient = identity.to_public().to_string();
+        let key_file = format!(
+            "# created: test\n{}\n",
+            age::secrecy::ExposeSecret::expose_secret(&identity.to_string())
+        );
+
+        let encrypted = encrypt_to_recipients(b"shared", &[recipient]).unwrap();
+        assert_eq!(
+            decrypt_with_identities(&encrypted, &key_file).unwrap(),
+            b"shared"
+        );
+
+        let other = age::x25519::Identity::generate();
+        let other_file = age::secrecy::ExposeSecret::expose_secret(&other.to_string()).to_string();
+        let err = decrypt_with_identities(&encrypted, &other_file).unwrap_err();
+        assert!(err.to_string().contains("None of the identities"));
+    }
+
+    #[test]
+    fn test_invalid_recipient_rejected() {
+        let err = encrypt_to_recipients(b"x", &["age1nope".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("Invalid age recipient"));
+        assert!(encrypt_to_recipients(b"x", &[]).is_err());
+    }
 }
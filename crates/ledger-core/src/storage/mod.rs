@@ -22,14 +22,23 @@
 //! See RFC-001 for the complete storage model.
 
 pub mod age_sqlite;
+pub mod clock;
 pub mod encryption;
 pub mod traits;
 pub mod types;
 
 // Re-export public types
-pub use age_sqlite::AgeSqliteStorage;
+pub use age_sqlite::{AgeSqliteStorage, EntryStream};
+pub use clock::{Clock, FixedClock, IdGen, RandomIds, SequentialIds, SystemClock};
 pub use traits::StorageEngine;
 pub use types::{
-    Composition, CompositionFilter, Entry, EntryComposition, EntryFilter, EntryType,
-    LedgerMetadata, NewComposition, NewEntry, NewEntryType, NewTemplate, Template,
+    AggregateQuery, AggregateRow, Attachment, AttachmentGc, AttachmentUsage, Composition,
+    CompositionFilter, CompositionOverview, CompositionStats, DayCount, DayNumber, Draft, DueEntry,
+    Entry, EntryComposition, EntryFilter, EntryOrigin, EntryTemplate, EntryType, EntryTypeRecord,
+    ExportBundle, Habit, HabitSchedule, IdempotencyRecord, ImportSummary, InstalledPack,
+    IntegrityIssue, IntegritySeverity, LedgerMetadata, NewAttachment, NewComposition, NewEntry,
+    NewEntryType, NewTemplate, RecoveryReport, Recurrence, RelatedEntry, RelatedReason, RowRange,
+    Scratchpad, SearchRebuild, SearchTokenizer, SummaryTemplate, SyncDirection, SyncPeer,
+    TableRecovery, TagCount, Template, TemplateRecord, TemplateUsage, TimestampPrecision,
+    VersionRecord, ENTRY_TYPE_COLORS,
 };
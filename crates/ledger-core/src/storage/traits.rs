@@ -8,8 +8,9 @@ use std::path::Path;
 use uuid::Uuid;
 
 use super::types::{
-    Composition, CompositionFilter, Entry, EntryComposition, EntryFilter, EntryType,
-    LedgerMetadata, NewComposition, NewEntry, NewEntryType, NewTemplate, Template,
+    Attachment, Composition, CompositionFilter, Entry, EntryComposition, EntryFilter, EntryType,
+    IntegrityIssue, LedgerMetadata, NewAttachment, NewComposition, NewEntry, NewEntryType,
+    NewTemplate, Template,
 };
 use crate::error::Result;
 
@@ -53,10 +54,10 @@ pub trait StorageEngine: Send + Sync {
     ///
     /// # Errors
     ///
-    /// Returns `LedgerError::Crypto` if:
-    /// - Passphrase is incorrect
-    /// - File is corrupted
-    /// - Decryption fails
+    /// Returns `LedgerError::IncorrectPassphrase` if the passphrase is wrong,
+    /// `LedgerError::Corrupted` if the file is damaged,
+    /// `LedgerError::VersionMismatch` if a newer format wrote it, and
+    /// `LedgerError::Crypto` if decryption fails otherwise.
     fn open(path: &Path, passphrase: &str) -> Result<Self>
     where
         Self: Sized;
@@ -76,6 +77,15 @@ pub trait StorageEngine: Send + Sync {
     /// Get ledger metadata.
     fn metadata(&self) -> Result<LedgerMetadata>;
 
+    /// Run several operations as one transaction.
+    ///
+    /// Everything `f` does is committed together when it returns `Ok`, and
+    /// rolled back when it returns `Err`. Batches may nest.
+    fn batch<T, F>(&mut self, f: F) -> Result<T>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Result<T>;
+
     // --- Entry operations ---
 
     /// Insert a new entry.
@@ -88,6 +98,7 @@ pub trait StorageEngine: Send + Sync {
     ///
     /// Returns `LedgerError::Validation` if:
     /// - Entry type does not exist
+    /// - Entry type is retired and the entry is not a revision
     /// - Schema version is invalid
     /// - Data does not match schema
     fn insert_entry(&mut self, entry: &NewEntry) -> Result<Uuid>;
@@ -101,9 +112,17 @@ pub trait StorageEngine: Send + Sync {
 
     /// List entries matching the filter.
     ///
-    /// Entries are returned in reverse chronological order (newest first).
+    /// Entries are returned in reverse chronological order (newest first),
+    /// or oldest first if `filter.oldest_first` is set.
     fn list_entries(&self, filter: &EntryFilter) -> Result<Vec<Entry>>;
 
+    /// Iterate over entries matching the filter, in `list_entries` order.
+    ///
+    /// Rows are read through a cursor a page at a time, so only one page is
+    /// held in memory however many entries match. Errors are yielded in
+    /// place of the entry that could not be read.
+    fn iter_entries(&self, filter: &EntryFilter) -> impl Iterator<Item = Result<Entry>> + '_;
+
     /// Search entries using full-text search.
     ///
     /// # Arguments
@@ -115,6 +134,12 @@ pub trait StorageEngine: Send + Sync {
     /// Returns entries ordered by relevance, then recency.
     fn search_entries(&self, query: &str) -> Result<Vec<Entry>>;
 
+    /// Search entries, applying `filter` inside the query.
+    ///
+    /// Rows the filter rejects are never parsed, and `filter.limit` caps the
+    /// results after relevance ordering.
+    fn search_entries_filtered(&self, query: &str, filter: &EntryFilter) -> Result<Vec<Entry>>;
+
     /// List entry IDs that have been superseded by newer revisions.
     fn superseded_entry_ids(&self) -> Result<std::collections::HashSet<Uuid>>;
 
@@ -178,7 +203,7 @@ pub trait StorageEngine: Send + Sync {
     ///
     /// # Errors
     ///
-    /// Returns `LedgerError::NotFound` if composition doesn't exist.
+    /// Returns `LedgerError::CompositionNotFound` if composition doesn't exist.
     /// Returns `LedgerError::Validation` if new name already exists.
     fn rename_composition(&mut self, id: &Uuid, new_name: &str) -> Result<()>;
 
@@ -189,14 +214,15 @@ pub trait StorageEngine: Send + Sync {
     ///
     /// # Errors
     ///
-    /// Returns `LedgerError::NotFound` if composition doesn't exist.
+    /// Returns `LedgerError::CompositionNotFound` if composition doesn't exist.
     fn delete_composition(&mut self, id: &Uuid) -> Result<()>;
 
     /// Attach an entry to a composition.
     ///
     /// # Errors
     ///
-    /// Returns `LedgerError::NotFound` if entry or composition doesn't exist.
+    /// Returns `LedgerError::NotFound` if the entry doesn't exist and
+    /// `LedgerError::CompositionNotFound` if the composition doesn't.
     fn attach_entry_to_composition(&mut self, entry_id: &Uuid, composition_id: &Uuid)
         -> Result<()>;
 
@@ -262,7 +288,7 @@ pub trait StorageEngine: Send + Sync {
     ///
     /// # Errors
     ///
-    /// Returns `LedgerError::NotFound` if template doesn't exist.
+    /// Returns `LedgerError::TemplateNotFound` if template doesn't exist.
     fn update_template(&mut self, id: &Uuid, template_json: serde_json::Value) -> Result<i32>;
 
     /// Delete a template.
@@ -272,14 +298,17 @@ pub trait StorageEngine: Send + Sync {
     ///
     /// # Errors
     ///
-    /// Returns `LedgerError::NotFound` if template doesn't exist.
+    /// Returns `LedgerError::TemplateNotFound` if template doesn't exist.
+    /// Returns `LedgerError::Validation`, listing them, if entries were
+    /// created from the template.
     fn delete_template(&mut self, id: &Uuid) -> Result<()>;
 
     /// Set the default template for an entry type.
     ///
     /// # Errors
     ///
-    /// Returns `LedgerError::NotFound` if entry type or template doesn't exist.
+    /// Returns `LedgerError::NotFound` if entry type doesn't exist.
+    /// Returns `LedgerError::TemplateNotFound` if template doesn't exist.
     /// Returns `LedgerError::Validation` if template is not for this entry type.
     fn set_default_template(&mut self, entry_type_id: &Uuid, template_id: &Uuid) -> Result<()>;
 
@@ -297,6 +326,61 @@ pub trait StorageEngine: Send + Sync {
     /// Returns `Ok(Some(template))` if a default is set, `Ok(None)` otherwise.
     fn get_default_template(&self, entry_type_id: &Uuid) -> Result<Option<Template>>;
 
+    // --- Attachment operations ---
+
+    /// Store a binary attachment for an entry.
+    ///
+    /// The bytes are kept inside the ledger and encrypted with it on close.
+    ///
+    /// # Returns
+    ///
+    /// Returns the UUID of the stored attachment.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LedgerError::NotFound` if the entry doesn't exist.
+    /// Returns `LedgerError::Validation` if the name is empty or the data is too large.
+    fn add_attachment(&mut self, attachment: &NewAttachment) -> Result<Uuid>;
+
+    /// List attachment metadata for an entry, oldest first.
+    fn list_attachments(&self, entry_id: &Uuid) -> Result<Vec<Attachment>>;
+
+    /// Read the bytes of an attachment.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(None)` if the attachment doesn't exist.
+    fn get_attachment_data(&self, id: &Uuid) -> Result<Option<Vec<u8>>>;
+
+    // --- Secret operations ---
+
+    /// Store a named secret, replacing any existing value.
+    ///
+    /// Secrets live inside the ledger and are encrypted with it on close.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LedgerError::Validation` if the name is invalid or the value is
+    /// empty or too large.
+    fn set_secret(&mut self, name: &str, value: &str) -> Result<()>;
+
+    /// Read a secret by name.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(None)` if the secret doesn't exist.
+    fn get_secret(&self, name: &str) -> Result<Option<String>>;
+
+    /// List secret names (never values), sorted by name.
+    fn list_secret_names(&self) -> Result<Vec<String>>;
+
+    /// Delete a secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LedgerError::NotFound` if the secret doesn't exist.
+    fn delete_secret(&mut self, name: &str) -> Result<()>;
+
     // --- Maintenance operations ---
 
     /// Check ledger integrity.
@@ -306,10 +390,13 @@ pub trait StorageEngine: Send + Sync {
     /// - Foreign key relationships
     /// - FTS index synchronization
     ///
+    /// - Entry type version state and required metadata keys
+    ///
     /// # Returns
     ///
-    /// Returns `Ok(())` if ledger is valid, or an error describing the problem.
-    fn check_integrity(&self) -> Result<()>;
+    /// Returns every issue found (empty when the ledger is healthy). Only a
+    /// failure to run the checks themselves is reported as an error.
+    fn check_integrity(&self) -> Result<Vec<IntegrityIssue>>;
 }
 
 #[cfg(test)]
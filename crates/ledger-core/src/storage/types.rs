@@ -2,7 +2,10 @@
 //!
 //! These types represent the stable data model defined in RFC-004.
 
-use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -44,6 +47,35 @@ pub struct EntryType {
     pub schema_json: serde_json::Value,
 }
 
+/// Color names an entry type may declare for its badge.
+///
+/// The CLI theme decides the actual shade for each name.
+pub const ENTRY_TYPE_COLORS: &[&str] =
+    &["red", "green", "yellow", "blue", "magenta", "cyan", "gray"];
+
+impl EntryType {
+    /// Icon or emoji from the schema's top-level `icon`, if declared.
+    pub fn icon(&self) -> Option<&str> {
+        self.schema_json.get("icon").and_then(|icon| icon.as_str())
+    }
+
+    /// Color name from the schema's top-level `color`, if declared.
+    pub fn color(&self) -> Option<&str> {
+        self.schema_json
+            .get("color")
+            .and_then(|color| color.as_str())
+    }
+
+    /// Summary format from the schema's top-level `summary_template`, if
+    /// declared and valid.
+    pub fn summary_template(&self) -> Option<SummaryTemplate> {
+        self.schema_json
+            .get("summary_template")
+            .and_then(|template| template.as_str())
+            .and_then(SummaryTemplate::parse)
+    }
+}
+
 /// An entry instance.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entry {
@@ -65,6 +97,10 @@ pub struct Entry {
     /// When this entry was created
     pub created_at: DateTime<Utc>,
 
+    /// Insertion order in this ledger; breaks ties between equal `created_at`
+    #[serde(default)]
+    pub seq: i64,
+
     /// Device that created this entry
     pub device_id: Uuid,
 
@@ -283,6 +319,9 @@ impl NewTemplate {
 }
 
 /// An entry-composition association.
+///
+/// Memberships are append-only: detaching an entry sets `removed_at`, and
+/// attaching it again starts a new membership.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntryComposition {
     /// Entry ID
@@ -293,6 +332,143 @@ pub struct EntryComposition {
 
     /// When the entry was added to the composition
     pub added_at: DateTime<Utc>,
+
+    /// When the entry was detached; `None` while it is a member
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed_at: Option<DateTime<Utc>>,
+}
+
+/// An operation applied under a caller-chosen idempotency key.
+///
+/// Replaying the key returns this record instead of applying the operation
+/// again, so scripts can retry safely.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    /// Key supplied by the caller
+    pub key: String,
+
+    /// Operation the key was used for, e.g. `add` or `import`
+    pub operation: String,
+
+    /// Entry the operation created, if it created exactly one
+    pub entry_id: Option<Uuid>,
+
+    /// Operation-specific result to replay, such as an import summary
+    pub result: Option<serde_json::Value>,
+
+    /// When the operation was applied
+    pub created_at: DateTime<Utc>,
+}
+
+/// A composition's long-form overview: a markdown page for the project or
+/// theme the composition groups.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompositionOverview {
+    /// Composition the overview belongs to
+    pub composition_id: Uuid,
+
+    /// Markdown body
+    pub body: String,
+
+    /// When the body was last replaced
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Metadata for a binary attachment stored alongside an entry.
+///
+/// Attachment bytes live inside the encrypted ledger, stored once per
+/// distinct content; use `StorageEngine::get_attachment_data` to read them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    /// Unique identifier for this attachment
+    pub id: Uuid,
+
+    /// Entry this attachment belongs to
+    pub entry_id: Uuid,
+
+    /// Original file name
+    pub name: String,
+
+    /// MIME type (best effort, from the file extension)
+    pub mime_type: String,
+
+    /// Size in bytes
+    pub size: u64,
+
+    /// When this attachment was stored
+    pub created_at: DateTime<Utc>,
+
+    /// Device that stored this attachment
+    pub device_id: Uuid,
+}
+
+/// Storage used by attachments, before and after deduplication.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttachmentUsage {
+    /// Attachment records across all entries
+    pub attachments: u64,
+
+    /// Bytes the attachments would take if each were stored separately
+    pub logical_bytes: u64,
+
+    /// Distinct blobs stored
+    pub blobs: u64,
+
+    /// Bytes actually stored in blobs
+    pub stored_bytes: u64,
+
+    /// Blobs no attachment refers to any more
+    pub unreferenced_blobs: u64,
+
+    /// Bytes held by unreferenced blobs
+    pub unreferenced_bytes: u64,
+}
+
+/// What an attachment garbage collection removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttachmentGc {
+    /// Unreferenced blobs deleted
+    pub blobs_removed: u64,
+
+    /// Bytes those blobs held
+    pub bytes_freed: u64,
+}
+
+/// Builder for creating new attachments.
+#[derive(Debug, Clone)]
+pub struct NewAttachment {
+    /// Entry this attachment belongs to
+    pub entry_id: Uuid,
+
+    /// Original file name
+    pub name: String,
+
+    /// MIME type
+    pub mime_type: String,
+
+    /// Raw attachment bytes
+    pub data: Vec<u8>,
+
+    /// Device ID
+    pub device_id: Uuid,
+}
+
+impl NewAttachment {
+    pub fn new(
+        entry_id: Uuid,
+        name: impl Into<String>,
+        mime_type: impl Into<String>,
+        data: Vec<u8>,
+        device_id: Uuid,
+    ) -> Self {
+        Self {
+            entry_id,
+            name: name.into(),
+            mime_type: mime_type.into(),
+            data,
+            device_id,
+        }
+    }
 }
 
 /// Filter for querying entries.
@@ -301,7 +477,8 @@ pub struct EntryFilter {
     /// Filter by entry type ID
     pub entry_type_id: Option<Uuid>,
 
-    /// Filter by tag
+    /// Filter by tag; a trailing `/` (e.g. `work/`) also matches the tags
+    /// nested under it
     pub tag: Option<String>,
 
     /// Start date (inclusive)
@@ -315,6 +492,19 @@ pub struct EntryFilter {
 
     /// Filter by composition ID
     pub composition_id: Option<Uuid>,
+
+    /// Skip entries that a later revision supersedes
+    pub latest_only: bool,
+
+    /// Return entries oldest first instead of newest first
+    pub oldest_first: bool,
+
+    /// Only entries whose origin app, command or hostname equals this
+    pub origin: Option<String>,
+
+    /// Leave out entries carrying any of these tags or the tags nested
+    /// under them, in any revision from the entry on
+    pub exclude_tags: Vec<String>,
 }
 
 impl EntryFilter {
@@ -351,6 +541,288 @@ impl EntryFilter {
         self.composition_id = Some(id);
         self
     }
+
+    pub fn origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    pub fn exclude_tag(mut self, tag: impl Into<String>) -> Self {
+        self.exclude_tags.push(tag.into());
+        self
+    }
+
+    pub fn latest_only(mut self) -> Self {
+        self.latest_only = true;
+        self
+    }
+
+    pub fn oldest_first(mut self) -> Self {
+        self.oldest_first = true;
+        self
+    }
+}
+
+/// A grouped sum over one numeric field of an entry type.
+///
+/// Only current revisions count; superseded entries are left out so edits
+/// do not double a total.
+#[derive(Debug, Clone)]
+pub struct AggregateQuery {
+    /// Entry type to aggregate
+    pub entry_type_id: Uuid,
+
+    /// Numeric data field to sum
+    pub sum_field: String,
+
+    /// Data fields to group by, outermost first
+    pub group_by: Vec<String>,
+
+    /// Start date (inclusive)
+    pub since: Option<DateTime<Utc>>,
+
+    /// End date (inclusive)
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl AggregateQuery {
+    pub fn new(entry_type_id: Uuid, sum_field: impl Into<String>) -> Self {
+        Self {
+            entry_type_id,
+            sum_field: sum_field.into(),
+            group_by: Vec::new(),
+            since: None,
+            until: None,
+        }
+    }
+
+    pub fn group_by(mut self, field: impl Into<String>) -> Self {
+        self.group_by.push(field.into());
+        self
+    }
+
+    pub fn since(mut self, date: DateTime<Utc>) -> Self {
+        self.since = Some(date);
+        self
+    }
+
+    pub fn until(mut self, date: DateTime<Utc>) -> Self {
+        self.until = Some(date);
+        self
+    }
+}
+
+/// One group of an [`AggregateQuery`] result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateRow {
+    /// Group values, in `group_by` order (`None` when the field is missing)
+    pub group: Vec<Option<String>>,
+
+    /// Entries in the group
+    pub count: usize,
+
+    /// Sum of the field; non-numeric values count as zero
+    pub sum: f64,
+}
+
+/// The ledger's scratchpad: free-form text that is not an entry yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scratchpad {
+    /// Current text
+    pub text: String,
+
+    /// When the text was last replaced
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An entry that is not published yet.
+///
+/// Drafts live in the ledger but not among its entries: list, search and
+/// export never see them, and their data is only checked against the entry
+/// type's schema when they are published.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Draft {
+    /// Unique identifier for this draft
+    pub id: Uuid,
+
+    /// Entry type the draft is published as
+    pub entry_type_id: Uuid,
+
+    /// Entry data (JSON)
+    pub data: serde_json::Value,
+
+    /// Tags for the published entry
+    pub tags: Vec<String>,
+
+    /// When the draft was first saved
+    pub created_at: DateTime<Utc>,
+
+    /// When the draft was last saved
+    pub updated_at: DateTime<Utc>,
+
+    /// Device that saved the draft
+    pub device_id: Uuid,
+}
+
+/// Current entries of one type created on one day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayCount {
+    /// Day the entries were created on
+    pub date: NaiveDate,
+
+    /// Entry type of the entries
+    pub entry_type_id: Uuid,
+
+    /// Number of entries
+    pub count: usize,
+}
+
+/// An entry's number within the day it was written, e.g. `2025-02-03#4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DayNumber {
+    /// Day the entry was first written, in the ledger's local time
+    pub day: NaiveDate,
+
+    /// Position among that day's entries, starting at 1
+    pub number: u32,
+}
+
+impl DayNumber {
+    /// Parse a `YYYY-MM-DD#N` string.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (day, number) = value.trim().split_once('#')?;
+        let day = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?;
+        let number = number.parse().ok().filter(|n| *n > 0)?;
+        Some(Self { day, number })
+    }
+}
+
+impl fmt::Display for DayNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}", self.day.format("%Y-%m-%d"), self.number)
+    }
+}
+
+/// Entry counts for one tag in the tag hierarchy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagCount {
+    /// Full tag path, e.g. `work/project-x`
+    pub tag: String,
+
+    /// Entries carrying exactly this tag
+    pub count: usize,
+
+    /// Entries carrying this tag or any tag nested under it
+    pub total: usize,
+}
+
+/// Current members of one composition.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompositionStats {
+    /// Current entries in the composition
+    pub entry_count: usize,
+
+    /// Creation time of the newest of those entries
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+/// How much one template is used.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateUsage {
+    /// Entries created from the template
+    pub uses: usize,
+
+    /// Of those, entries per template version they were created from
+    pub by_version: BTreeMap<i32, usize>,
+
+    /// Creation time of the newest entry created from the template
+    pub last_used: Option<DateTime<Utc>>,
+
+    /// Whether it is the default template for its entry type
+    pub is_default: bool,
+}
+
+/// The template an entry was created from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryTemplate {
+    /// Template ID
+    pub id: Uuid,
+
+    /// Template name
+    pub name: String,
+
+    /// Template version used, if it was recorded
+    pub version: Option<i32>,
+}
+
+/// Which program wrote an entry into the ledger.
+///
+/// Recorded on every insert from the storage's current origin (see
+/// `AgeSqliteStorage::set_origin`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryOrigin {
+    /// Application that wrote the entry (`ledger-cli`, or a plugin's own name)
+    pub app: String,
+
+    /// Application version
+    pub version: Option<String>,
+
+    /// Command or operation that wrote the entry (e.g. `add`, `api add`)
+    pub command: Option<String>,
+
+    /// Host the entry was written on
+    pub hostname: Option<String>,
+}
+
+impl EntryOrigin {
+    pub fn new(app: impl Into<String>) -> Self {
+        Self {
+            app: app.into(),
+            version: None,
+            command: None,
+            hostname: None,
+        }
+    }
+
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    pub fn with_hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = Some(hostname.into());
+        self
+    }
+}
+
+/// Why one entry was judged related to another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum RelatedReason {
+    /// Both carry this tag
+    Tag(String),
+
+    /// Both belong to this composition
+    Composition(String),
+
+    /// Their text shares uncommon terms
+    Text,
+}
+
+/// An entry related to another, with what links them.
+#[derive(Debug, Clone)]
+pub struct RelatedEntry {
+    pub entry: Entry,
+
+    /// Strength of the link; only meaningful for ranking
+    pub score: f64,
+
+    pub reasons: Vec<RelatedReason>,
 }
 
 /// Filter for querying compositions.
@@ -371,6 +843,819 @@ impl CompositionFilter {
     }
 }
 
+/// Severity of an integrity issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegritySeverity {
+    /// The ledger is inconsistent and needs repair.
+    Error,
+    /// Harmless drift (e.g. stale index rows) that does not lose data.
+    Warning,
+}
+
+impl IntegritySeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntegritySeverity::Error => "error",
+            IntegritySeverity::Warning => "warning",
+        }
+    }
+}
+
+/// Tokenizer behind the full-text search index.
+///
+/// Every choice folds case and strips diacritics, so `cafe` finds `café`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchTokenizer {
+    /// Plain word matching; the right choice for most non-English text
+    Unicode61,
+    /// English stemming on top of `unicode61` (`running` finds `run`)
+    #[default]
+    Porter,
+    /// Substring matching on three-character sequences, for languages
+    /// without spaces between words; queries need at least three characters
+    Trigram,
+}
+
+impl SearchTokenizer {
+    pub const ALL: [SearchTokenizer; 3] = [Self::Unicode61, Self::Porter, Self::Trigram];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|tokenizer| tokenizer.as_str() == value.trim().to_ascii_lowercase())
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unicode61 => "unicode61",
+            Self::Porter => "porter",
+            Self::Trigram => "trigram",
+        }
+    }
+
+    /// The FTS5 `tokenize` option for this tokenizer.
+    pub fn fts5_options(&self) -> &'static str {
+        match self {
+            Self::Unicode61 => "unicode61 remove_diacritics 2",
+            Self::Porter => "porter unicode61 remove_diacritics 2",
+            Self::Trigram => "trigram remove_diacritics 1",
+        }
+    }
+}
+
+impl fmt::Display for SearchTokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Progress of a search index rebuild done in chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchRebuild {
+    /// Tokenizer the index is being rebuilt with
+    pub tokenizer: SearchTokenizer,
+
+    /// Entries indexed so far
+    pub indexed: usize,
+
+    /// Entries to index in total
+    pub total: usize,
+}
+
+impl SearchRebuild {
+    /// Whether every entry has been indexed.
+    pub fn is_complete(&self) -> bool {
+        self.indexed >= self.total
+    }
+}
+
+/// Precision kept for new entry timestamps.
+///
+/// Entries with equal timestamps still sort by insertion order (`seq`), so
+/// coarse precision never scrambles a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampPrecision {
+    Seconds,
+    Millis,
+    Micros,
+    /// Whatever the system clock provides
+    #[default]
+    Nanos,
+}
+
+impl TimestampPrecision {
+    pub const ALL: [TimestampPrecision; 4] =
+        [Self::Seconds, Self::Millis, Self::Micros, Self::Nanos];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|precision| precision.as_str() == value.trim().to_ascii_lowercase())
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Seconds => "seconds",
+            Self::Millis => "millis",
+            Self::Micros => "micros",
+            Self::Nanos => "nanos",
+        }
+    }
+
+    /// Drop sub-second digits beyond this precision.
+    pub fn truncate(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let unit = match self {
+            Self::Seconds => 1_000_000_000,
+            Self::Millis => 1_000_000,
+            Self::Micros => 1_000,
+            Self::Nanos => 1,
+        };
+        let nanos = time.timestamp_subsec_nanos();
+        time.with_nanosecond(nanos - nanos % unit).unwrap_or(time)
+    }
+}
+
+impl fmt::Display for TimestampPrecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single problem found by an integrity check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    /// Machine-readable code (e.g. "fts_missing")
+    pub code: String,
+
+    /// How serious the issue is
+    pub severity: IntegritySeverity,
+
+    /// Human-readable description
+    pub message: String,
+
+    /// Affected row ids (entry ids, entry type ids, meta keys, ...)
+    pub ids: Vec<String>,
+}
+
+impl IntegrityIssue {
+    pub fn new(
+        code: impl Into<String>,
+        severity: IntegritySeverity,
+        message: impl Into<String>,
+        ids: Vec<String>,
+    ) -> Self {
+        Self {
+            code: code.into(),
+            severity,
+            message: message.into(),
+            ids,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == IntegritySeverity::Error
+    }
+}
+
+/// An inclusive range of rowids that could not be read during recovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowRange {
+    /// First unreadable rowid
+    pub start: i64,
+
+    /// Last unreadable rowid (`None` means through the end of the table)
+    pub end: Option<i64>,
+}
+
+/// Per-table outcome of a recovery run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableRecovery {
+    /// Table name
+    pub table: String,
+
+    /// Rows copied into the recovered ledger
+    pub recovered: usize,
+
+    /// Rows that were read but violated the schema on insert
+    pub rejected: usize,
+
+    /// Rows dropped because the rows they reference were lost
+    pub orphaned: usize,
+
+    /// Rowid ranges that could not be read
+    pub unreadable: Vec<RowRange>,
+
+    /// Table was absent or unreadable in the source
+    pub missing: bool,
+}
+
+impl TableRecovery {
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            recovered: 0,
+            rejected: 0,
+            orphaned: 0,
+            unreadable: Vec::new(),
+            missing: false,
+        }
+    }
+
+    /// Record an unreadable range, merging it with an adjacent previous one.
+    pub fn add_unreadable(&mut self, range: RowRange) {
+        if let Some(last) = self.unreadable.last_mut() {
+            if last
+                .end
+                .is_some_and(|end| end.saturating_add(1) >= range.start)
+            {
+                last.end = range.end;
+                return;
+            }
+        }
+        self.unreadable.push(range);
+    }
+
+    /// Whether anything in this table was lost.
+    pub fn is_lossy(&self) -> bool {
+        self.missing || self.rejected > 0 || self.orphaned > 0 || !self.unreadable.is_empty()
+    }
+}
+
+/// Result of salvaging a damaged ledger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    /// Per-table results, in copy order
+    pub tables: Vec<TableRecovery>,
+
+    /// Integrity issues remaining in the recovered ledger
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl RecoveryReport {
+    /// Whether every row of every table was recovered.
+    pub fn is_complete(&self) -> bool {
+        !self.tables.iter().any(TableRecovery::is_lossy)
+    }
+}
+
+/// One version of an entry type schema or a template body.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionRecord {
+    /// Version number
+    pub version: i32,
+
+    /// Schema or template JSON for this version
+    pub body: serde_json::Value,
+
+    /// When this version was created
+    pub created_at: DateTime<Utc>,
+
+    /// Whether this version is active
+    pub active: bool,
+}
+
+/// An entry type with its full version history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryTypeRecord {
+    /// Unique identifier for this entry type
+    pub id: Uuid,
+
+    /// User-facing name
+    pub name: String,
+
+    /// When this entry type was created
+    pub created_at: DateTime<Utc>,
+
+    /// Device that created this entry type
+    pub device_id: Uuid,
+
+    /// Every schema version, oldest first
+    pub versions: Vec<VersionRecord>,
+}
+
+/// A template with its full version history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateRecord {
+    /// Unique identifier for this template
+    pub id: Uuid,
+
+    /// User-facing name
+    pub name: String,
+
+    /// Entry type this template applies to
+    pub entry_type_id: Uuid,
+
+    /// Optional description
+    pub description: Option<String>,
+
+    /// When this template was created
+    pub created_at: DateTime<Utc>,
+
+    /// Device that created this template
+    pub device_id: Uuid,
+
+    /// Every template version, oldest first
+    pub versions: Vec<VersionRecord>,
+
+    /// Whether this is the active default template for its entry type
+    #[serde(default)]
+    pub default: bool,
+}
+
+/// Full-fidelity copy of a ledger's entries and organizational structure.
+///
+/// Sections are optional so partial exports round-trip; ids are preserved so
+/// memberships and template links survive an export/import cycle.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportBundle {
+    /// Entry types with version history
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entry_types: Vec<EntryTypeRecord>,
+
+    /// Templates with version history and default flags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<TemplateRecord>,
+
+    /// Compositions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compositions: Vec<Composition>,
+
+    /// Entry-composition memberships (join table rows)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entry_compositions: Vec<EntryComposition>,
+
+    /// Composition overview pages
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub composition_overviews: Vec<CompositionOverview>,
+
+    /// Entries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<Entry>,
+}
+
+/// Rows written by an import, per kind.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// Entry types created
+    pub entry_types: usize,
+
+    /// Templates created
+    pub templates: usize,
+
+    /// Compositions created
+    pub compositions: usize,
+
+    /// Entries created
+    pub entries: usize,
+
+    /// Entry-composition memberships created
+    pub memberships: usize,
+
+    /// Composition overviews written
+    #[serde(default)]
+    pub overviews: usize,
+
+    /// Rows already present (same id or name) and left untouched
+    pub skipped: usize,
+}
+
+/// How a date field repeats, from a schema field's `recurrence` annotation.
+///
+/// Accepted values are `yearly`, `monthly`, `weekly`, `daily`, or an interval
+/// such as `10d` or `2w`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recurrence {
+    /// Same month and day every year (Feb 29 falls on Feb 28 in other years)
+    Yearly,
+    /// Same day every month, clamped to the last day of shorter months
+    Monthly,
+    /// A fixed number of days
+    EveryDays(u32),
+}
+
+impl Recurrence {
+    /// Parse a `recurrence` annotation.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "yearly" => Some(Self::Yearly),
+            "monthly" => Some(Self::Monthly),
+            "weekly" => Some(Self::EveryDays(7)),
+            "daily" => Some(Self::EveryDays(1)),
+            other => {
+                let (amount, unit) = other.split_at(other.len().checked_sub(1)?);
+                let amount: u32 = amount.parse().ok().filter(|n| *n > 0)?;
+                match unit {
+                    "d" => Some(Self::EveryDays(amount)),
+                    "w" => amount.checked_mul(7).map(Self::EveryDays),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// First occurrence on or after `from`, and how many periods it is past
+    /// `start`.
+    ///
+    /// A start date that is still ahead is its own first occurrence.
+    pub fn next_on_or_after(&self, start: NaiveDate, from: NaiveDate) -> (NaiveDate, u32) {
+        if start >= from {
+            return (start, 0);
+        }
+        match *self {
+            Self::Yearly => {
+                let mut year = from.year();
+                let mut due = clamped_date(year, start.month(), start.day());
+                if due < from {
+                    year += 1;
+                    due = clamped_date(year, start.month(), start.day());
+                }
+                (due, (year - start.year()) as u32)
+            }
+            Self::Monthly => {
+                let months = |date: NaiveDate| date.year() * 12 + date.month0() as i32;
+                let mut index = months(from);
+                let at = |index: i32| {
+                    clamped_date(
+                        index.div_euclid(12),
+                        index.rem_euclid(12) as u32 + 1,
+                        start.day(),
+                    )
+                };
+                if at(index) < from {
+                    index += 1;
+                }
+                (at(index), (index - months(start)) as u32)
+            }
+            Self::EveryDays(days) => {
+                let days = i64::from(days);
+                let periods = ((from - start).num_days() + days - 1) / days;
+                (start + Duration::days(periods * days), periods as u32)
+            }
+        }
+    }
+}
+
+/// The given day, or the last day of the month if it is shorter.
+fn clamped_date(year: i32, month: u32, day: u32) -> NaiveDate {
+    (1..=day)
+        .rev()
+        .find_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+        .unwrap_or(NaiveDate::MIN)
+}
+
+/// An entry whose recurring date field falls due in a queried window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DueEntry {
+    /// The current revision of the entry
+    pub entry: Entry,
+
+    /// Name of the date field that is due
+    pub field: String,
+
+    /// Date stored in the field
+    pub date: NaiveDate,
+
+    /// When it next falls due
+    pub due: NaiveDate,
+
+    /// Periods elapsed since `date` (e.g. 5 for a fifth anniversary)
+    pub occurrence: u32,
+
+    /// How the field recurs
+    pub recurrence: Recurrence,
+
+    /// Whether the field asks for a desktop notification when due
+    #[serde(default = "default_notify")]
+    pub notify: bool,
+}
+
+fn default_notify() -> bool {
+    true
+}
+
+/// One-line summary format from a schema's `summary_template`, such as
+/// `"{weight} kg — {notes}"`.
+///
+/// `{name}` stands for the entry's `name` field; `{{` and `}}` are literal
+/// braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Text(String),
+    Field(String),
+}
+
+impl SummaryTemplate {
+    /// Parse a template; `None` if a brace is unbalanced, a placeholder is
+    /// empty, or there are no placeholders at all.
+    pub fn parse(template: &str) -> Option<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next()? {
+                            '}' => break,
+                            '{' => return None,
+                            c => name.push(c),
+                        }
+                    }
+                    let name = name.trim();
+                    if name.is_empty() {
+                        return None;
+                    }
+                    if !text.is_empty() {
+                        parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(TemplatePart::Field(name.to_string()));
+                }
+                '}' => return None,
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+        let template = Self { parts };
+        template.fields().next()?;
+        Some(template)
+    }
+
+    /// Field names the template refers to, in order.
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().filter_map(|part| match part {
+            TemplatePart::Field(name) => Some(name.as_str()),
+            TemplatePart::Text(_) => None,
+        })
+    }
+
+    /// Fill in fields from entry data.
+    ///
+    /// Missing and null fields render as nothing; `None` if none of the
+    /// fields has a value, so callers can fall back to another summary.
+    pub fn render(&self, data: &serde_json::Value) -> Option<String> {
+        let mut out = String::new();
+        let mut filled = false;
+        for part in &self.parts {
+            match part {
+                TemplatePart::Text(text) => out.push_str(text),
+                TemplatePart::Field(name) => match data.get(name) {
+                    None | Some(serde_json::Value::Null) => {}
+                    Some(value) => {
+                        filled = true;
+                        out.push_str(&template_value(value));
+                    }
+                },
+            }
+        }
+        let out = out.trim();
+        (filled && !out.is_empty()).then(|| out.to_string())
+    }
+}
+
+fn template_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(template_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// How often a habit is meant to be done.
+///
+/// Written as `daily`, `weekdays`, `weekends`, a day list such as
+/// `mon,wed,fri`, or a weekly count such as `3/week`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HabitSchedule {
+    /// Every day
+    Daily,
+    /// Specific days of the week
+    Days(Vec<Weekday>),
+    /// A number of times per ISO week, on any days
+    PerWeek(u32),
+}
+
+const WEEKDAYS: [Weekday; 5] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+];
+const WEEKENDS: [Weekday; 2] = [Weekday::Sat, Weekday::Sun];
+
+impl HabitSchedule {
+    /// Parse a schedule string.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        match value.as_str() {
+            "daily" => return Some(Self::Daily),
+            "weekdays" => return Some(Self::Days(WEEKDAYS.to_vec())),
+            "weekends" => return Some(Self::Days(WEEKENDS.to_vec())),
+            _ => {}
+        }
+        if let Some(count) = value
+            .strip_suffix("/week")
+            .map(|count| count.trim_end_matches('x'))
+        {
+            let count: u32 = count.parse().ok().filter(|n| (1..=7).contains(n))?;
+            return Some(Self::PerWeek(count));
+        }
+        let mut days: Vec<Weekday> = value
+            .split(',')
+            .map(|day| day.trim().parse::<Weekday>().ok())
+            .collect::<Option<_>>()?;
+        days.sort_by_key(|day| day.num_days_from_monday());
+        days.dedup();
+        Some(Self::Days(days))
+    }
+
+    /// Whether the habit is expected on `date`.
+    ///
+    /// Weekly counts can be met on any day, so every day is scheduled.
+    pub fn is_scheduled(&self, date: NaiveDate) -> bool {
+        match self {
+            Self::Daily | Self::PerWeek(_) => true,
+            Self::Days(days) => days.contains(&date.weekday()),
+        }
+    }
+
+    /// Current and longest streak for a set of completion dates.
+    ///
+    /// Streaks count scheduled days, or whole weeks for `PerWeek`. Today (or
+    /// this week) only extends a streak; not having done it yet does not
+    /// break one.
+    pub fn streaks(&self, done: &BTreeSet<NaiveDate>, today: NaiveDate) -> (u32, u32) {
+        let Some(&first) = done.iter().next() else {
+            return (0, 0);
+        };
+        let mut current = 0;
+        let mut longest = 0;
+        let mut record = |met: bool, pending: bool| {
+            if met {
+                current += 1;
+                longest = longest.max(current);
+            } else if !pending {
+                current = 0;
+            }
+        };
+        match self {
+            Self::PerWeek(count) => {
+                let week_start = |date: NaiveDate| {
+                    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+                };
+                let this_week = week_start(today);
+                let mut week = week_start(first);
+                while week <= this_week {
+                    let next = week + Duration::days(7);
+                    let met = done.range(week..next).count() as u32 >= *count;
+                    record(met, week == this_week);
+                    week = next;
+                }
+            }
+            _ => {
+                let mut date = first;
+                while date <= today {
+                    if self.is_scheduled(date) {
+                        record(done.contains(&date), date == today);
+                    }
+                    date += Duration::days(1);
+                }
+            }
+        }
+        (current, longest)
+    }
+}
+
+impl fmt::Display for HabitSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Daily => write!(f, "daily"),
+            Self::Days(days) if days[..] == WEEKDAYS => write!(f, "weekdays"),
+            Self::Days(days) if days[..] == WEEKENDS => write!(f, "weekends"),
+            Self::Days(days) => {
+                let names: Vec<String> = days
+                    .iter()
+                    .map(|day| day.to_string().to_ascii_lowercase())
+                    .collect();
+                write!(f, "{}", names.join(","))
+            }
+            Self::PerWeek(count) => write!(f, "{}/week", count),
+        }
+    }
+}
+
+impl Serialize for HabitSchedule {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for HabitSchedule {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Self::parse(&value)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid habit schedule: {}", value)))
+    }
+}
+
+/// A habit definition.
+///
+/// Completions are ordinary `habit` entries; this only records the name
+/// and how often it is due.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Habit {
+    pub name: String,
+    pub schedule: HabitSchedule,
+    pub created_at: DateTime<Utc>,
+    pub device_id: Uuid,
+}
+
+/// A ledger this one exchanges change bundles with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncPeer {
+    /// Peer name (lowercase letters, digits, '-', '_')
+    pub name: String,
+
+    /// Device fingerprint of the peer's ledger (`xxxx-xxxx-xxxx-xxxx`)
+    pub fingerprint: String,
+
+    /// When the peer was added
+    pub added_at: DateTime<Utc>,
+
+    /// When a change bundle was last exported for the peer
+    pub last_sent_at: Option<DateTime<Utc>>,
+
+    /// When a change bundle from the peer was last imported
+    pub last_received_at: Option<DateTime<Utc>>,
+
+    /// Highest entry `seq` in the ledger when a change bundle was last
+    /// exported for the peer; the next one starts after it
+    pub last_sent_seq: Option<i64>,
+}
+
+/// Which way a change bundle travelled between this ledger and a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDirection {
+    /// Exported for the peer
+    Sent,
+    /// Imported from the peer
+    Received,
+}
+
+/// A pack of entry types and templates installed from a manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledPack {
+    /// Pack name (lowercase letters, digits, '-', '_')
+    pub name: String,
+
+    /// Version from the manifest, as written there
+    pub version: String,
+
+    /// One-line description from the manifest
+    pub description: Option<String>,
+
+    /// Entry types the pack defines
+    pub entry_types: Vec<String>,
+
+    /// Templates the pack defines
+    pub templates: Vec<String>,
+
+    /// When this version was installed
+    pub installed_at: DateTime<Utc>,
+
+    /// Device that installed it
+    pub device_id: Uuid,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -412,6 +1697,97 @@ mod tests {
         assert_eq!(filter.composition_id, Some(comp_id));
     }
 
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_habit_schedule_parse_round_trip() {
+        for value in ["daily", "weekdays", "weekends", "mon,wed,fri", "3/week"] {
+            let schedule = HabitSchedule::parse(value).expect(value);
+            assert_eq!(schedule.to_string(), value);
+        }
+        assert_eq!(
+            HabitSchedule::parse("Fri, mon").unwrap().to_string(),
+            "mon,fri"
+        );
+        assert_eq!(
+            HabitSchedule::parse("2x/week"),
+            Some(HabitSchedule::PerWeek(2))
+        );
+        assert!(HabitSchedule::parse("8/week").is_none());
+        assert!(HabitSchedule::parse("sometimes").is_none());
+    }
+
+    #[test]
+    fn test_daily_streaks_tolerate_pending_today() {
+        let done: BTreeSet<NaiveDate> = [
+            "2024-03-01",
+            "2024-03-02",
+            "2024-03-03",
+            "2024-03-05",
+            "2024-03-06",
+        ]
+        .iter()
+        .map(|d| date(d))
+        .collect();
+        let schedule = HabitSchedule::Daily;
+        assert_eq!(schedule.streaks(&done, date("2024-03-06")), (2, 3));
+        assert_eq!(schedule.streaks(&done, date("2024-03-07")), (2, 3));
+        assert_eq!(schedule.streaks(&done, date("2024-03-08")), (0, 3));
+        assert_eq!(
+            schedule.streaks(&BTreeSet::new(), date("2024-03-08")),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn test_day_list_streaks_skip_unscheduled_days() {
+        // 2024-03-04 is a Monday
+        let done: BTreeSet<NaiveDate> = ["2024-03-04", "2024-03-06", "2024-03-08", "2024-03-11"]
+            .iter()
+            .map(|d| date(d))
+            .collect();
+        let schedule = HabitSchedule::parse("mon,wed,fri").unwrap();
+        assert_eq!(schedule.streaks(&done, date("2024-03-12")), (4, 4));
+        assert_eq!(schedule.streaks(&done, date("2024-03-14")), (0, 4));
+    }
+
+    #[test]
+    fn test_weekly_streaks_count_weeks() {
+        let done: BTreeSet<NaiveDate> = [
+            "2024-03-04",
+            "2024-03-07",
+            "2024-03-12",
+            "2024-03-15",
+            "2024-03-18",
+        ]
+        .iter()
+        .map(|d| date(d))
+        .collect();
+        let schedule = HabitSchedule::PerWeek(2);
+        assert_eq!(schedule.streaks(&done, date("2024-03-19")), (2, 2));
+        assert_eq!(schedule.streaks(&done, date("2024-03-26")), (0, 2));
+    }
+
+    #[test]
+    fn test_aggregate_query_builder() {
+        let type_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        let query = AggregateQuery::new(type_id, "amount")
+            .group_by("currency")
+            .group_by("category")
+            .since(now)
+            .until(now);
+
+        assert_eq!(query.entry_type_id, type_id);
+        assert_eq!(query.sum_field, "amount");
+        assert_eq!(query.group_by, vec!["currency", "category"]);
+        assert_eq!(query.since, Some(now));
+        assert_eq!(query.until, Some(now));
+    }
+
     #[test]
     fn test_new_composition_builder() {
         let device_id = Uuid::new_v4();
@@ -449,10 +1825,163 @@ mod tests {
         assert_eq!(template.template_json, template_json);
     }
 
+    #[test]
+    fn test_table_recovery_merges_adjacent_ranges() {
+        let mut recovery = TableRecovery::new("entries");
+        recovery.add_unreadable(RowRange {
+            start: 3,
+            end: Some(4),
+        });
+        recovery.add_unreadable(RowRange {
+            start: 5,
+            end: Some(8),
+        });
+        recovery.add_unreadable(RowRange {
+            start: 12,
+            end: None,
+        });
+
+        assert_eq!(
+            recovery.unreadable,
+            vec![
+                RowRange {
+                    start: 3,
+                    end: Some(8)
+                },
+                RowRange {
+                    start: 12,
+                    end: None
+                },
+            ]
+        );
+        assert!(recovery.is_lossy());
+        assert!(!TableRecovery::new("meta").is_lossy());
+    }
+
+    #[test]
+    fn test_day_number_parse_round_trip() {
+        let number = DayNumber::parse("2025-02-03#4").unwrap();
+        assert_eq!(number.day, NaiveDate::from_ymd_opt(2025, 2, 3).unwrap());
+        assert_eq!(number.number, 4);
+        assert_eq!(number.to_string(), "2025-02-03#4");
+
+        assert!(DayNumber::parse("2025-02-03#0").is_none());
+        assert!(DayNumber::parse("2025-02-03").is_none());
+        assert!(DayNumber::parse("2025-02-30#1").is_none());
+        assert!(DayNumber::parse("#4").is_none());
+    }
+
+    #[test]
+    fn test_recurrence_parse() {
+        assert_eq!(Recurrence::parse("yearly"), Some(Recurrence::Yearly));
+        assert_eq!(Recurrence::parse("weekly"), Some(Recurrence::EveryDays(7)));
+        assert_eq!(Recurrence::parse("10d"), Some(Recurrence::EveryDays(10)));
+        assert_eq!(Recurrence::parse("2w"), Some(Recurrence::EveryDays(14)));
+        assert_eq!(Recurrence::parse("0d"), None);
+        assert_eq!(Recurrence::parse("fortnightly"), None);
+        assert_eq!(Recurrence::parse(""), None);
+    }
+
+    #[test]
+    fn test_recurrence_next_occurrence() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(
+            Recurrence::Yearly.next_on_or_after(date(2019, 6, 20), date(2024, 6, 10)),
+            (date(2024, 6, 20), 5)
+        );
+        assert_eq!(
+            Recurrence::Yearly.next_on_or_after(date(2019, 6, 1), date(2024, 6, 10)),
+            (date(2025, 6, 1), 6)
+        );
+        assert_eq!(
+            Recurrence::Yearly.next_on_or_after(date(2020, 2, 29), date(2023, 2, 1)),
+            (date(2023, 2, 28), 3)
+        );
+        assert_eq!(
+            Recurrence::Monthly.next_on_or_after(date(2024, 1, 31), date(2024, 2, 10)),
+            (date(2024, 2, 29), 1)
+        );
+        assert_eq!(
+            Recurrence::Monthly.next_on_or_after(date(2024, 11, 15), date(2024, 12, 20)),
+            (date(2025, 1, 15), 2)
+        );
+        assert_eq!(
+            Recurrence::EveryDays(10).next_on_or_after(date(2024, 3, 1), date(2024, 3, 15)),
+            (date(2024, 3, 21), 2)
+        );
+        assert_eq!(
+            Recurrence::EveryDays(10).next_on_or_after(date(2024, 3, 20), date(2024, 3, 15)),
+            (date(2024, 3, 20), 0)
+        );
+    }
+
     #[test]
     fn test_composition_filter_builder() {
         let filter = CompositionFilter::new().limit(10);
 
         assert_eq!(filter.limit, Some(10));
     }
+
+    #[test]
+    fn test_search_tokenizer_parse_round_trips() {
+        for tokenizer in SearchTokenizer::ALL {
+            assert_eq!(SearchTokenizer::parse(tokenizer.as_str()), Some(tokenizer));
+        }
+        assert_eq!(
+            SearchTokenizer::parse(" Trigram "),
+            Some(SearchTokenizer::Trigram)
+        );
+        assert_eq!(SearchTokenizer::parse("icu"), None);
+    }
+
+    #[test]
+    fn test_timestamp_precision_truncate() {
+        let time = DateTime::parse_from_rfc3339("2025-01-02T03:04:05.123456789Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let format = |precision: TimestampPrecision| precision.truncate(time).to_rfc3339();
+        assert_eq!(
+            format(TimestampPrecision::Seconds),
+            "2025-01-02T03:04:05+00:00"
+        );
+        assert_eq!(
+            format(TimestampPrecision::Millis),
+            "2025-01-02T03:04:05.123+00:00"
+        );
+        assert_eq!(
+            format(TimestampPrecision::Micros),
+            "2025-01-02T03:04:05.123456+00:00"
+        );
+        assert_eq!(TimestampPrecision::Nanos.truncate(time), time);
+        assert_eq!(
+            TimestampPrecision::parse("Millis"),
+            Some(TimestampPrecision::Millis)
+        );
+        assert_eq!(TimestampPrecision::parse("minutes"), None);
+    }
+
+    #[test]
+    fn test_summary_template_parse_and_render() {
+        let template = SummaryTemplate::parse("{weight} kg \u{2014} {notes}").unwrap();
+        assert_eq!(template.fields().collect::<Vec<_>>(), ["weight", "notes"]);
+        assert_eq!(
+            template.render(&serde_json::json!({"weight": 73.5, "notes": "after run"})),
+            Some("73.5 kg \u{2014} after run".to_string())
+        );
+        assert_eq!(
+            template.render(&serde_json::json!({"weight": 73.5, "notes": null})),
+            Some("73.5 kg \u{2014}".to_string())
+        );
+        assert_eq!(template.render(&serde_json::json!({"other": 1})), None);
+
+        let braces = SummaryTemplate::parse("{{{ tags }}}").unwrap();
+        assert_eq!(
+            braces.render(&serde_json::json!({"tags": ["a", "b"]})),
+            Some("{a, b}".to_string())
+        );
+        for invalid in ["no fields", "{}", "{open", "close}", "{a{b}}"] {
+            assert_eq!(SummaryTemplate::parse(invalid), None, "{}", invalid);
+        }
+    }
 }
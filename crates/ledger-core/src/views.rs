@@ -0,0 +1,503 @@
+//! JSON shapes printed by Ledger frontends.
+//!
+//! Every frontend that prints entries, compositions, templates or mutation
+//! receipts as JSON serializes these views, so the shapes cannot drift
+//! between binaries. Each view describes itself as a JSON Schema for
+//! consumers of that output.
+//!
+//! Fields are declared in the order they are printed. Entry, composition
+//! and template views list them alphabetically, matching objects built with
+//! `serde_json::json!`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::storage::{
+    Composition, CompositionOverview, CompositionStats, DayNumber, Entry, EntryComposition,
+    Template, TemplateUsage,
+};
+
+/// JSON Schema dialect the view schemas are written in.
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Names of the views with a schema, as accepted by [`view_schema`].
+pub const VIEW_NAMES: &[&str] = &["entry", "composition", "template", "receipt"];
+
+/// An entry, with the name of its type; listings add its day number.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryView {
+    pub created_at: DateTime<Utc>,
+    pub data: Value,
+    pub device_id: Uuid,
+    pub entry_type_id: Uuid,
+    pub entry_type_name: String,
+    pub id: Uuid,
+    /// Day number such as `2025-02-03#4`, accepted wherever an entry id is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_id: Option<String>,
+    pub schema_version: i32,
+    pub seq: i64,
+    pub supersedes: Option<Uuid>,
+    pub tags: Vec<String>,
+}
+
+impl EntryView {
+    pub fn new(entry: &Entry, entry_type_name: &str) -> Self {
+        Self {
+            created_at: entry.created_at,
+            data: entry.data.clone(),
+            device_id: entry.device_id,
+            entry_type_id: entry.entry_type_id,
+            entry_type_name: entry_type_name.to_string(),
+            id: entry.id,
+            log_id: None,
+            schema_version: entry.schema_version,
+            seq: entry.seq,
+            supersedes: entry.supersedes,
+            tags: entry.tags.clone(),
+        }
+    }
+
+    pub fn with_log_id(mut self, number: Option<&DayNumber>) -> Self {
+        self.log_id = number.map(|number| number.to_string());
+        self
+    }
+
+    pub fn json_schema() -> Value {
+        object_schema(
+            "EntryView",
+            "An entry, with the name of its type",
+            &[
+                ("created_at", date_time()),
+                ("data", json!({"type": "object"})),
+                ("device_id", uuid()),
+                ("entry_type_id", uuid()),
+                ("entry_type_name", string()),
+                ("id", uuid()),
+                ("log_id", string()),
+                ("schema_version", json!({"type": "integer"})),
+                ("seq", json!({"type": "integer"})),
+                ("supersedes", nullable(uuid())),
+                ("tags", json!({"type": "array", "items": string()})),
+            ],
+            &["log_id"],
+        )
+    }
+}
+
+/// A composition with its current size; `show` adds the overview and,
+/// when asked, the membership history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompositionView {
+    pub created_at: DateTime<Utc>,
+    pub description: Option<String>,
+    pub device_id: Uuid,
+    pub entry_count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<Vec<MembershipView>>,
+    pub id: Uuid,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub metadata: Option<Value>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overview: Option<OverviewView>,
+}
+
+/// A composition's markdown overview.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OverviewView {
+    pub body: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One membership of an entry in a composition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MembershipView {
+    pub added_at: DateTime<Utc>,
+    pub entry_id: Uuid,
+    pub removed_at: Option<DateTime<Utc>>,
+}
+
+impl CompositionView {
+    pub fn new(composition: &Composition, stats: &CompositionStats) -> Self {
+        Self {
+            created_at: composition.created_at,
+            description: composition.description.clone(),
+            device_id: composition.device_id,
+            entry_count: stats.entry_count,
+            history: None,
+            id: composition.id,
+            last_activity: stats.last_activity,
+            metadata: composition.metadata.clone(),
+            name: composition.name.clone(),
+            overview: None,
+        }
+    }
+
+    pub fn with_overview(mut self, overview: Option<&CompositionOverview>) -> Self {
+        self.overview = overview.map(|overview| OverviewView {
+            body: overview.body.clone(),
+            updated_at: overview.updated_at,
+        });
+        self
+    }
+
+    pub fn with_history(mut self, history: &[EntryComposition]) -> Self {
+        self.history = Some(
+            history
+                .iter()
+                .map(|membership| MembershipView {
+                    added_at: membership.added_at,
+                    entry_id: membership.entry_id,
+                    removed_at: membership.removed_at,
+                })
+                .collect(),
+        );
+        self
+    }
+
+    pub fn json_schema() -> Value {
+        object_schema(
+            "CompositionView",
+            "A composition with its current size",
+            &[
+                ("created_at", date_time()),
+                ("description", nullable(string())),
+                ("device_id", uuid()),
+                ("entry_count", json!({"type": "integer", "minimum": 0})),
+                (
+                    "history",
+                    json!({
+                        "type": "array",
+                        "items": object_schema(
+                            "MembershipView",
+                            "One membership of an entry in the composition",
+                            &[
+                                ("added_at", date_time()),
+                                ("entry_id", uuid()),
+                                ("removed_at", nullable(date_time())),
+                            ],
+                            &[],
+                        ),
+                    }),
+                ),
+                ("id", uuid()),
+                ("last_activity", nullable(date_time())),
+                ("metadata", json!({})),
+                ("name", string()),
+                (
+                    "overview",
+                    object_schema(
+                        "OverviewView",
+                        "The composition's markdown overview",
+                        &[("body", string()), ("updated_at", date_time())],
+                        &[],
+                    ),
+                ),
+            ],
+            &["history", "overview"],
+        )
+    }
+}
+
+/// A template at its latest version; listings add how much it is used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateView {
+    pub created_at: DateTime<Utc>,
+    pub description: Option<String>,
+    pub device_id: Uuid,
+    pub entry_type: String,
+    pub entry_type_id: Uuid,
+    pub id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_default: Option<bool>,
+    pub name: String,
+    pub template_json: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_count: Option<usize>,
+    pub version: i32,
+}
+
+impl TemplateView {
+    pub fn new(template: &Template, entry_type_name: &str) -> Self {
+        Self {
+            created_at: template.created_at,
+            description: template.description.clone(),
+            device_id: template.device_id,
+            entry_type: entry_type_name.to_string(),
+            entry_type_id: template.entry_type_id,
+            id: template.id,
+            is_default: None,
+            name: template.name.clone(),
+            template_json: template.template_json.clone(),
+            usage_count: None,
+            version: template.version,
+        }
+    }
+
+    pub fn with_usage(mut self, usage: &TemplateUsage) -> Self {
+        self.usage_count = Some(usage.uses);
+        self.is_default = Some(usage.is_default);
+        self
+    }
+
+    pub fn json_schema() -> Value {
+        object_schema(
+            "TemplateView",
+            "A template at its latest version",
+            &[
+                ("created_at", date_time()),
+                ("description", nullable(string())),
+                ("device_id", uuid()),
+                ("entry_type", string()),
+                ("entry_type_id", uuid()),
+                ("id", uuid()),
+                ("is_default", json!({"type": "boolean"})),
+                ("name", string()),
+                ("template_json", json!({"type": "object"})),
+                ("usage_count", json!({"type": "integer", "minimum": 0})),
+                ("version", json!({"type": "integer"})),
+            ],
+            &["is_default", "usage_count"],
+        )
+    }
+}
+
+/// What a mutation did, so scripts can capture IDs without parsing text.
+///
+/// `id` is the record the command acted on; `affected` lists every record
+/// it touched, starting with that one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptView {
+    pub operation: String,
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub affected: Vec<AffectedView>,
+    /// Set when an idempotency key matched an earlier run and nothing was
+    /// written
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub replayed: bool,
+}
+
+/// One record touched by a mutation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AffectedView {
+    pub kind: String,
+    pub id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl ReceiptView {
+    pub fn json_schema() -> Value {
+        object_schema(
+            "ReceiptView",
+            "What a mutation did",
+            &[
+                ("operation", string()),
+                ("id", uuid()),
+                ("created_at", date_time()),
+                (
+                    "affected",
+                    json!({
+                        "type": "array",
+                        "items": object_schema(
+                            "AffectedView",
+                            "One record touched by the mutation",
+                            &[("kind", string()), ("id", uuid()), ("name", string())],
+                            &["name"],
+                        ),
+                    }),
+                ),
+                ("replayed", json!({"type": "boolean"})),
+            ],
+            &["replayed"],
+        )
+    }
+}
+
+/// The JSON Schema of the view called `name` (one of [`VIEW_NAMES`]).
+pub fn view_schema(name: &str) -> Option<Value> {
+    let mut schema = match name {
+        "entry" => EntryView::json_schema(),
+        "composition" => CompositionView::json_schema(),
+        "template" => TemplateView::json_schema(),
+        "receipt" => ReceiptView::json_schema(),
+        _ => return None,
+    };
+    schema["$schema"] = json!(JSON_SCHEMA_DIALECT);
+    Some(schema)
+}
+
+/// An object schema whose properties are all required except `optional`.
+fn object_schema(
+    title: &str,
+    description: &str,
+    properties: &[(&str, Value)],
+    optional: &[&str],
+) -> Value {
+    let required: Vec<&str> = properties
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| !optional.contains(name))
+        .collect();
+    json!({
+        "title": title,
+        "description": description,
+        "type": "object",
+        "properties": properties
+            .iter()
+            .map(|(name, schema)| (name.to_string(), schema.clone()))
+            .collect::<serde_json::Map<_, _>>(),
+        "required": required,
+    })
+}
+
+fn string() -> Value {
+    json!({"type": "string"})
+}
+
+fn uuid() -> Value {
+    json!({"type": "string", "format": "uuid"})
+}
+
+fn date_time() -> Value {
+    json!({"type": "string", "format": "date-time"})
+}
+
+fn nullable(schema: Value) -> Value {
+    json!({"anyOf": [schema, {"type": "null"}]})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property_names(schema: &Value) -> Vec<String> {
+        schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    fn assert_fits(value: &Value, schema: &Value) {
+        let keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        let mut properties = property_names(schema);
+        properties.sort();
+        for key in &keys {
+            assert!(properties.contains(key), "{} is not in the schema", key);
+        }
+        for required in schema["required"].as_array().unwrap() {
+            assert!(
+                keys.iter().any(|key| key == required),
+                "{} is required",
+                required
+            );
+        }
+    }
+
+    #[test]
+    fn test_views_match_their_schemas() {
+        let now = Utc::now();
+        let composition = Composition {
+            id: Uuid::new_v4(),
+            name: "trip".to_string(),
+            description: None,
+            created_at: now,
+            device_id: Uuid::new_v4(),
+            metadata: None,
+        };
+        let stats = CompositionStats {
+            entry_count: 2,
+            last_activity: Some(now),
+        };
+        let membership = EntryComposition {
+            entry_id: Uuid::new_v4(),
+            composition_id: composition.id,
+            added_at: now,
+            removed_at: None,
+        };
+        let full = CompositionView::new(&composition, &stats)
+            .with_overview(Some(&CompositionOverview {
+                composition_id: composition.id,
+                body: "# Trip".to_string(),
+                updated_at: now,
+            }))
+            .with_history(&[membership]);
+        let schema = view_schema("composition").unwrap();
+        assert_fits(&serde_json::to_value(&full).unwrap(), &schema);
+        let listed = serde_json::to_value(CompositionView::new(&composition, &stats)).unwrap();
+        assert_fits(&listed, &schema);
+        assert!(listed.get("overview").is_none());
+
+        let entry = Entry {
+            id: Uuid::new_v4(),
+            entry_type_id: Uuid::new_v4(),
+            schema_version: 1,
+            data: json!({"body": "hi"}),
+            tags: vec!["a".to_string()],
+            created_at: now,
+            seq: 1,
+            device_id: Uuid::new_v4(),
+            supersedes: None,
+        };
+        assert_fits(
+            &serde_json::to_value(EntryView::new(&entry, "journal")).unwrap(),
+            &view_schema("entry").unwrap(),
+        );
+        let numbered = EntryView::new(&entry, "journal").with_log_id(Some(&DayNumber {
+            day: now.date_naive(),
+            number: 4,
+        }));
+        let numbered = serde_json::to_value(numbered).unwrap();
+        assert_fits(&numbered, &view_schema("entry").unwrap());
+        assert_eq!(
+            numbered["log_id"],
+            format!("{}#4", now.date_naive().format("%Y-%m-%d"))
+        );
+
+        let receipt = ReceiptView {
+            operation: "add".to_string(),
+            id: entry.id,
+            created_at: now,
+            affected: Vec::new(),
+            replayed: false,
+        };
+        assert_fits(
+            &serde_json::to_value(&receipt).unwrap(),
+            &view_schema("receipt").unwrap(),
+        );
+
+        for name in VIEW_NAMES {
+            let schema = view_schema(name).unwrap();
+            assert_eq!(schema["$schema"], JSON_SCHEMA_DIALECT);
+            assert_eq!(schema["type"], "object");
+        }
+        assert!(view_schema("nope").is_none());
+    }
+
+    #[test]
+    fn test_printed_field_order_matches_json_macro_objects() {
+        let entry = Entry {
+            id: Uuid::new_v4(),
+            entry_type_id: Uuid::new_v4(),
+            schema_version: 1,
+            data: json!({"body": "hi"}),
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            seq: 1,
+            device_id: Uuid::new_v4(),
+            supersedes: None,
+        };
+        let view = EntryView::new(&entry, "journal");
+        // A `Value` object sorts its keys, the struct prints in field order
+        assert_eq!(
+            serde_json::to_string(&view).unwrap(),
+            serde_json::to_value(&view).unwrap().to_string()
+        );
+    }
+}
@@ -1,12 +1,15 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use ledger_core::storage::encryption::decrypt;
 use ledger_core::storage::{
-    AgeSqliteStorage, CompositionFilter, EntryFilter, NewComposition, NewEntry, NewEntryType,
-    NewTemplate, StorageEngine,
+    AgeSqliteStorage, AggregateQuery, CompositionFilter, DayNumber, Entry, EntryFilter,
+    EntryOrigin, FixedClock, HabitSchedule, InstalledPack, IntegritySeverity, NewAttachment,
+    NewComposition, NewEntry, NewEntryType, NewTemplate, RelatedReason, SearchTokenizer,
+    SequentialIds, StorageEngine, SyncDirection, TimestampPrecision,
 };
 use rusqlite::serialize::OwnedData;
 use rusqlite::{Connection, DatabaseName};
@@ -335,7 +338,10 @@ fn test_insert_entry_missing_required_field_fails() {
     let new_entry = NewEntry::new(entry_type_id, 1, serde_json::json!({}), device_id);
 
     let result = storage.insert_entry(&new_entry);
-    assert!(result.is_err());
+    match result {
+        Err(ledger_core::LedgerError::SchemaViolation { field, .. }) => assert_eq!(field, "body"),
+        other => panic!("expected a schema violation, got {:?}", other),
+    }
 }
 
 #[test]
@@ -350,8 +356,17 @@ fn test_insert_entry_type_mismatch_fails() {
     let device_id = Uuid::new_v4();
     let new_entry = NewEntry::new(entry_type_id, 1, serde_json::json!({"body": 42}), device_id);
 
-    let result = storage.insert_entry(&new_entry);
-    assert!(result.is_err());
+    let err = storage
+        .insert_entry(&new_entry)
+        .expect_err("a number should not fit a string field");
+    assert!(matches!(
+        &err,
+        ledger_core::LedgerError::SchemaViolation { field, .. } if field == "body"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Validation error: Field body must be a string"
+    );
 }
 
 #[test]
@@ -445,6 +460,265 @@ fn test_insert_entry_invalid_tag_characters() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_hierarchical_tags_filter_by_prefix_and_roll_up() {
+    let temp = TempFile::new("ledger_entry_tag_hierarchy");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    let mut insert = |tags: &[&str]| {
+        let entry = NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": "ok"}),
+            device_id,
+        )
+        .with_tags(tags.iter().map(|t| t.to_string()).collect());
+        storage.insert_entry(&entry).expect("insert should succeed")
+    };
+    let meeting = insert(&["Work/Project-X/Meetings", "work/project-x/notes"]);
+    let planning = insert(&["work"]);
+    let homework = insert(&["workshop"]);
+    let sibling = insert(&["work-log"]);
+
+    for bad in ["work/", "/work", "work//x"] {
+        let entry = NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": "ok"}),
+            device_id,
+        )
+        .with_tags(vec![bad.to_string()]);
+        assert!(storage.insert_entry(&entry).is_err(), "{} accepted", bad);
+    }
+
+    let ids = |filter: EntryFilter| -> Vec<Uuid> {
+        let mut ids: Vec<Uuid> = storage
+            .list_entries(&filter)
+            .expect("list should succeed")
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        ids.sort();
+        ids
+    };
+    let mut work_tree = vec![meeting, planning];
+    work_tree.sort();
+    assert_eq!(ids(EntryFilter::new().tag("work/")), work_tree);
+    assert_eq!(ids(EntryFilter::new().tag("work")), vec![planning]);
+    assert_eq!(
+        ids(EntryFilter::new().tag("work/project-x/meetings")),
+        vec![meeting]
+    );
+    assert_eq!(
+        ids(EntryFilter::new().tag("Work/Project-X/")),
+        vec![meeting]
+    );
+    assert_eq!(ids(EntryFilter::new().tag("workshop/")), vec![homework]);
+    assert_eq!(ids(EntryFilter::new().tag("work-log/")), vec![sibling]);
+
+    let counts: Vec<(String, usize, usize)> = storage
+        .tag_counts()
+        .expect("tag counts should succeed")
+        .into_iter()
+        .map(|c| (c.tag, c.count, c.total))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("work".to_string(), 1, 2),
+            ("work/project-x".to_string(), 0, 1),
+            ("work/project-x/meetings".to_string(), 1, 1),
+            ("work/project-x/notes".to_string(), 1, 1),
+            ("work-log".to_string(), 1, 1),
+            ("workshop".to_string(), 1, 1),
+        ]
+    );
+}
+
+#[test]
+fn test_exclude_tags_hide_nested_tags_and_earlier_revisions() {
+    let temp = TempFile::new("ledger_entry_exclude_tags");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    let mut insert = |tags: &[&str], supersedes: Option<Uuid>| {
+        let mut entry = NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": "ok"}),
+            device_id,
+        )
+        .with_tags(tags.iter().map(|t| t.to_string()).collect());
+        if let Some(id) = supersedes {
+            entry = entry.with_supersedes(id);
+        }
+        storage.insert_entry(&entry).expect("insert should succeed")
+    };
+    let public = insert(&["blog"], None);
+    insert(&["private"], None);
+    insert(&["private/health", "blog"], None);
+    let draft = insert(&["blog"], None);
+    insert(&["blog", "private"], Some(draft));
+    let untagged = insert(&[], None);
+    let lookalike = insert(&["private-ish"], None);
+
+    let mut ids: Vec<Uuid> = storage
+        .list_entries(&EntryFilter::new().exclude_tag("Private"))
+        .expect("list should succeed")
+        .into_iter()
+        .map(|entry| entry.id)
+        .collect();
+    ids.sort();
+    let mut expected = vec![public, untagged, lookalike];
+    expected.sort();
+    assert_eq!(ids, expected);
+    assert_eq!(
+        storage
+            .count_entries(&EntryFilter::new().tag("blog").exclude_tag("private/"))
+            .expect("count should succeed"),
+        1
+    );
+}
+
+#[test]
+fn test_suggest_tags_from_similar_entries() {
+    let temp = TempFile::new("ledger_entry_suggest_tags");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    let mut insert = |body: &str, tags: &[&str]| {
+        let entry = NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({ "body": body }),
+            device_id,
+        )
+        .with_tags(tags.iter().map(|t| t.to_string()).collect());
+        storage.insert_entry(&entry).expect("insert should succeed");
+    };
+    insert("Sourdough starter fed, loaf rising", &["baking"]);
+    insert("Baked a sourdough loaf with rye", &["baking", "food"]);
+    insert("Quarterly budget review with finance", &["work"]);
+    insert("Went for a run in the rain", &[]);
+
+    let suggested = storage
+        .suggest_tags("Tried a new sourdough loaf recipe", 3)
+        .expect("suggest should succeed");
+    assert_eq!(suggested, vec!["baking", "food"]);
+    assert_eq!(
+        storage
+            .suggest_tags("sourdough", 1)
+            .expect("suggest should succeed"),
+        vec!["baking"]
+    );
+    assert!(storage
+        .suggest_tags("an ox", 3)
+        .expect("short words are ignored")
+        .is_empty());
+    assert!(storage
+        .suggest_tags("Weather was nice", 3)
+        .expect("suggest should succeed")
+        .is_empty());
+}
+
+#[test]
+fn test_related_entries_by_tag_composition_and_text() {
+    let temp = TempFile::new("ledger_entry_related");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    let mut insert = |body: &str, tags: &[&str]| {
+        let entry = NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({ "body": body }),
+            device_id,
+        )
+        .with_tags(tags.iter().map(|t| t.to_string()).collect());
+        storage.insert_entry(&entry).expect("insert should succeed")
+    };
+    let subject = insert(
+        "Kiln firing schedule for glaze tests",
+        &["pottery", "daily"],
+    );
+    let same_tag = insert("Bought clay", &["pottery", "daily"]);
+    let same_text = insert("Glaze tests came out of the kiln cracked", &["daily"]);
+    let grouped = insert("Studio rent paid", &["daily"]);
+    let unrelated = insert("Walked the dog", &["daily"]);
+    // Enough other text for BM25 to tell shared terms from common ones
+    for day in 0..10 {
+        insert(&format!("Day {} of reading on the porch", day), &[]);
+    }
+
+    let studio = storage
+        .create_composition(&NewComposition::new("studio", device_id))
+        .expect("create composition");
+    for id in [subject, grouped] {
+        storage
+            .attach_entry_to_composition(&id, &studio)
+            .expect("attach");
+    }
+    let revised = NewEntry::new(
+        entry_type_id,
+        1,
+        serde_json::json!({ "body": "Kiln firing schedule for glaze tests, revised" }),
+        device_id,
+    )
+    .with_tags(vec!["pottery".to_string()])
+    .with_supersedes(subject);
+    let revision = storage.insert_entry(&revised).expect("insert revision");
+
+    let entry = storage.get_entry(&subject).unwrap().unwrap();
+    let related = storage.related_entries(&entry, 10).expect("related");
+    let ids: Vec<Uuid> = related.iter().map(|r| r.entry.id).collect();
+    assert!(!ids.contains(&subject) && !ids.contains(&revision));
+    assert!(ids.contains(&same_tag));
+    assert!(ids.contains(&same_text));
+    assert!(ids.contains(&grouped));
+    // `daily` is on every entry, so it alone only barely links
+    assert_eq!(ids.last(), Some(&unrelated));
+
+    let reasons = |id: Uuid| {
+        related
+            .iter()
+            .find(|r| r.entry.id == id)
+            .map(|r| r.reasons.clone())
+            .unwrap()
+    };
+    assert!(reasons(same_tag).contains(&RelatedReason::Tag("pottery".to_string())));
+    assert!(reasons(same_text).contains(&RelatedReason::Text));
+    assert!(reasons(grouped).contains(&RelatedReason::Composition("studio".to_string())));
+    assert!(related
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+
+    assert_eq!(
+        storage.related_entries(&entry, 2).expect("related").len(),
+        2
+    );
+    assert!(storage
+        .related_entries(&entry, 0)
+        .expect("related")
+        .is_empty());
+}
+
 #[test]
 fn test_insert_entry_empty_tag_fails() {
     let temp = TempFile::new("ledger_entry_empty_tag");
@@ -595,8 +869,8 @@ fn test_search_entries_basic() {
 }
 
 #[test]
-fn test_check_integrity_ok() {
-    let temp = TempFile::new("ledger_integrity_ok");
+fn test_latest_only_filter_applies_before_limit() {
+    let temp = TempFile::new("ledger_latest_only");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
@@ -604,20 +878,50 @@ fn test_check_integrity_ok() {
 
     let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-    let entry = NewEntry::new(
-        entry_type_id,
-        1,
-        serde_json::json!({"body": "integrity check"}),
-        device_id,
-    );
-    storage.insert_entry(&entry).expect("insert should succeed");
+    let insert = |storage: &mut AgeSqliteStorage, body: &str, supersedes: Option<Uuid>| {
+        let mut entry = NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": body}),
+            device_id,
+        );
+        if let Some(id) = supersedes {
+            entry = entry.with_supersedes(id);
+        }
+        let id = storage.insert_entry(&entry).expect("insert should succeed");
+        std::thread::sleep(Duration::from_millis(5));
+        id
+    };
+    let older = insert(&mut storage, "note alpha", None);
+    let draft = insert(&mut storage, "note beta draft", None);
+    let revised = insert(&mut storage, "note beta", Some(draft));
+
+    let listed = storage
+        .list_entries(&EntryFilter::new().latest_only().limit(2))
+        .expect("list should succeed");
+    let ids: Vec<Uuid> = listed.iter().map(|entry| entry.id).collect();
+    assert_eq!(ids, vec![revised, older]);
+
+    let searched = storage
+        .search_entries_filtered("note", &EntryFilter::new().latest_only())
+        .expect("search should succeed");
+    assert_eq!(searched.len(), 2);
+    assert!(searched.iter().all(|entry| entry.id != draft));
+
+    let limited = storage
+        .search_entries_filtered("note", &EntryFilter::new().limit(1))
+        .expect("search should succeed");
+    assert_eq!(limited.len(), 1);
 
-    storage.check_integrity().expect("integrity should succeed");
+    let other_type = storage
+        .search_entries_filtered("note", &EntryFilter::new().entry_type(Uuid::new_v4()))
+        .expect("search should succeed");
+    assert!(other_type.is_empty());
 }
 
 #[test]
-fn test_check_integrity_fails_on_orphaned_fts() {
-    let temp = TempFile::new("ledger_integrity_fail_fts");
+fn test_batch_commits_or_rolls_back_together() {
+    let temp = TempFile::new("ledger_batch");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
@@ -625,607 +929,758 @@ fn test_check_integrity_fails_on_orphaned_fts() {
 
     let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-    let entry = NewEntry::new(
-        entry_type_id,
-        1,
-        serde_json::json!({"body": "integrity test"}),
-        device_id,
-    );
-    let entry_id = storage.insert_entry(&entry).expect("insert should succeed");
-    storage.close(passphrase).expect("close should succeed");
-
-    let conn = open_sqlite_from_file(&temp.path, passphrase);
-    conn.execute(
-        "DELETE FROM entries_fts WHERE entry_id = ?",
-        [entry_id.to_string()],
-    )
-    .expect("delete fts should succeed");
-
-    let data = conn
-        .serialize(DatabaseName::Main)
-        .expect("serialize should succeed");
-    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
-        .expect("encrypt should succeed");
-    fs::write(&temp.path, encrypted).expect("write should succeed");
+    let entry = |body: &str| {
+        NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": body}),
+            device_id,
+        )
+    };
 
-    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
-    let result = storage.check_integrity();
+    let ids = storage
+        .batch(|storage| {
+            let first = storage.insert_entry(&entry("first"))?;
+            let second = storage.insert_entry(&entry("second"))?;
+            Ok(vec![first, second])
+        })
+        .expect("batch should succeed");
+    assert_eq!(ids.len(), 2);
+
+    let result = storage.batch(|storage| {
+        storage.insert_entry(&entry("third"))?;
+        storage.batch(|storage| storage.insert_entry(&entry("nested")))?;
+        // Missing the required body field
+        storage.insert_entry(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({}),
+            device_id,
+        ))
+    });
     assert!(result.is_err());
+
+    let entries = storage
+        .list_entries(&EntryFilter::new())
+        .expect("list should succeed");
+    assert_eq!(entries.len(), 2);
+    assert!(storage.search_entries("third").unwrap().is_empty());
+    assert!(storage.search_entries("nested").unwrap().is_empty());
 }
 
-#[cfg(unix)]
 #[test]
-fn test_atomic_write_failure_leaves_no_temp_files() {
-    use std::os::unix::fs::PermissionsExt;
-
-    let dir = temp_dir("ledger_atomic_failure");
-    let ledger_path = dir.join("test.ledger");
+fn test_equal_timestamps_keep_insertion_order() {
+    let temp = TempFile::new("ledger_seq_order");
+    let target = TempFile::new("ledger_seq_order_import");
     let passphrase = "test-passphrase-secure-123";
 
-    AgeSqliteStorage::create(&ledger_path, passphrase).expect("create should succeed");
-    let storage = AgeSqliteStorage::open(&ledger_path, passphrase).expect("open should succeed");
-
-    let mut perms = fs::metadata(&dir).expect("metadata").permissions();
-    perms.set_mode(0o500);
-    fs::set_permissions(&dir, perms).expect("set permissions");
-
-    let result = storage.close(passphrase);
-    assert!(result.is_err());
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    storage.set_timestamp_precision(TimestampPrecision::Seconds);
 
-    let mut perms = fs::metadata(&dir).expect("metadata").permissions();
-    perms.set_mode(0o700);
-    fs::set_permissions(&dir, perms).expect("restore permissions");
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    let second = chrono::DateTime::parse_from_rfc3339("2025-01-02T03:04:05Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    // Later bodies get earlier sub-second parts, which truncation drops
+    for (i, body) in ["first", "second", "third"].iter().enumerate() {
+        let created_at = second + chrono::Duration::milliseconds(900 - 100 * i as i64);
+        storage
+            .insert_entry(
+                &NewEntry::new(
+                    entry_type_id,
+                    1,
+                    serde_json::json!({ "body": body }),
+                    device_id,
+                )
+                .with_created_at(created_at),
+            )
+            .expect("insert should succeed");
+    }
 
-    assert_no_temp_files(&ledger_path);
-    let _ = fs::remove_file(&ledger_path);
-    let _ = fs::remove_dir_all(&dir);
+    let bodies = |storage: &AgeSqliteStorage| {
+        storage
+            .list_entries(&EntryFilter::new())
+            .expect("list should succeed")
+            .into_iter()
+            .map(|entry| entry.data["body"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    let entries = storage.list_entries(&EntryFilter::new()).unwrap();
+    assert!(entries.iter().all(|entry| entry.created_at == second));
+    assert!(entries.windows(2).all(|pair| pair[0].seq > pair[1].seq));
+    assert_eq!(bodies(&storage), ["third", "second", "first"]);
+    let found = storage
+        .search_entries_filtered("first OR second OR third", &EntryFilter::new().limit(2))
+        .expect("search should succeed");
+    assert_eq!(found.len(), 2);
+
+    // Bundles list newest first; import restores the original order
+    let mut bundle = storage.export_structure().expect("export should succeed");
+    bundle.entries = storage.list_entries(&EntryFilter::new()).unwrap();
+    AgeSqliteStorage::create(&target.path, passphrase).expect("create should succeed");
+    let mut imported =
+        AgeSqliteStorage::open(&target.path, passphrase).expect("open should succeed");
+    imported
+        .import_bundle(&bundle)
+        .expect("import should succeed");
+    assert_eq!(bodies(&imported), ["third", "second", "first"]);
 }
 
 #[test]
-fn test_entry_type_active_flag_unique() {
-    let temp = TempFile::new("ledger_entry_type_active");
+fn test_iter_entries_streams_across_pages() {
+    let temp = TempFile::new("ledger_export_stream");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
-
+    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-    let schema_v1 =
-        serde_json::json!({"fields": [{"name": "body", "type": "string", "required": true}]});
-    let schema_v2 =
-        serde_json::json!({"fields": [{"name": "body", "type": "string", "required": true}]});
-
-    storage
-        .create_entry_type(&NewEntryType::new("journal", schema_v1, device_id))
-        .expect("create v1 should succeed");
+    let start = chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    // Enough entries to span several pages, with runs of equal timestamps
+    // straddling page boundaries
     storage
-        .create_entry_type(&NewEntryType::new("journal", schema_v2, device_id))
-        .expect("create v2 should succeed");
-
-    storage.close(passphrase).expect("close should succeed");
-
-    let conn = open_sqlite_from_file(&temp.path, passphrase);
-    let entry_type_id: String = conn
-        .query_row(
-            "SELECT id FROM entry_types WHERE name = 'journal'",
-            [],
-            |row| row.get(0),
-        )
-        .expect("entry type should exist");
-
-    let active_count: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM entry_type_versions WHERE entry_type_id = ? AND active = 1",
-            [entry_type_id],
-            |row| row.get(0),
+        .batch(|storage| {
+            for i in 0..600i64 {
+                let entry = NewEntry::new(
+                    entry_type_id,
+                    1,
+                    serde_json::json!({ "body": i.to_string() }),
+                    device_id,
+                )
+                .with_created_at(start + chrono::Duration::hours(i / 7))
+                .with_tags(vec![if i % 2 == 0 { "even" } else { "odd" }.to_string()]);
+                storage.insert_entry(&entry)?;
+            }
+            Ok(())
+        })
+        .expect("batch should succeed");
+
+    let streamed = |filter: &EntryFilter| -> Vec<i64> {
+        storage
+            .iter_entries(filter)
+            .map(|entry| {
+                entry.unwrap().data["body"]
+                    .as_str()
+                    .unwrap()
+                    .parse()
+                    .unwrap()
+            })
+            .collect()
+    };
+    assert_eq!(
+        streamed(&EntryFilter::new().oldest_first()),
+        (0..600).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        streamed(&EntryFilter::new()),
+        (0..600).rev().collect::<Vec<_>>()
+    );
+    let listed: Vec<_> = storage
+        .list_entries(&EntryFilter::new().oldest_first().limit(3))
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.data["body"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(listed, ["0", "1", "2"]);
+
+    let since = start + chrono::Duration::hours(50);
+    let filtered: Vec<Entry> = storage
+        .iter_entries(
+            &EntryFilter::new()
+                .tag("even")
+                .since(since)
+                .oldest_first()
+                .limit(5),
         )
-        .expect("count should succeed");
-
-    assert_eq!(active_count, 1);
+        .collect::<Result<_, _>>()
+        .expect("stream should succeed");
+    let bodies: Vec<&str> = filtered
+        .iter()
+        .map(|entry| entry.data["body"].as_str().unwrap())
+        .collect();
+    assert_eq!(bodies, ["350", "352", "354", "356", "358"]);
+    assert_eq!(
+        storage
+            .count_entries(&EntryFilter::new().tag("even").since(since))
+            .unwrap(),
+        125
+    );
 }
 
 #[test]
-fn test_last_modified_updates_on_entry_type_create() {
-    let temp = TempFile::new("ledger_last_modified");
+fn test_open_assigns_seq_to_older_ledgers() {
+    let temp = TempFile::new("ledger_seq_upgrade");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
-
-    let before = storage
-        .metadata()
-        .expect("metadata should succeed")
-        .last_modified;
-
-    std::thread::sleep(Duration::from_millis(2));
-
+    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-    let schema =
-        serde_json::json!({"fields": [{"name": "body", "type": "string", "required": true}]});
-    storage
-        .create_entry_type(&NewEntryType::new("journal", schema, device_id))
-        .expect("create should succeed");
-
-    let after = storage
-        .metadata()
-        .expect("metadata should succeed")
-        .last_modified;
+    let created_at = chrono::Utc::now();
+    for body in ["first", "second"] {
+        storage
+            .insert_entry(
+                &NewEntry::new(
+                    entry_type_id,
+                    1,
+                    serde_json::json!({ "body": body }),
+                    device_id,
+                )
+                .with_created_at(created_at),
+            )
+            .expect("insert should succeed");
+    }
+    storage.close(passphrase).expect("close should succeed");
 
-    assert!(after >= before);
-    assert!(after > before);
+    // Simulate a ledger written before entries had a sequence number
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    conn.execute_batch("DROP INDEX entries_order; ALTER TABLE entries DROP COLUMN seq;")
+        .expect("drop should succeed");
+    let data = conn.serialize(DatabaseName::Main).expect("serialize");
+    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
+        .expect("encrypt should succeed");
+    fs::write(&temp.path, encrypted).expect("write should succeed");
 
-    storage.close(passphrase).expect("close should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    storage
+        .insert_entry(
+            &NewEntry::new(
+                entry_type_id,
+                1,
+                serde_json::json!({ "body": "third" }),
+                device_id,
+            )
+            .with_created_at(created_at),
+        )
+        .expect("insert should succeed");
+    let entries = storage.list_entries(&EntryFilter::new()).unwrap();
+    let seqs: Vec<i64> = entries.iter().map(|entry| entry.seq).collect();
+    assert_eq!(seqs, [3, 2, 1]);
+    assert_eq!(entries[0].data["body"], "third");
 }
 
-// ============================================================================
-// Composition Tests
-// ============================================================================
-
 #[test]
-fn test_create_and_get_composition() {
-    let temp = TempFile::new("ledger_composition_basic");
+fn test_entries_are_numbered_per_local_day() {
+    use chrono::TimeZone;
+
+    let temp = TempFile::new("ledger_day_numbers");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
-
+    storage
+        .set_day_offset(chrono::FixedOffset::east_opt(2 * 3600).unwrap())
+        .expect("set offset should succeed");
+    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-    let new_comp = NewComposition::new("project_x", device_id)
-        .with_description("My research project")
-        .with_metadata(serde_json::json!({"priority": "high"}));
+    let insert = |storage: &mut AgeSqliteStorage, body: &str, created_at, supersedes| {
+        let mut entry = NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({ "body": body }),
+            device_id,
+        )
+        .with_created_at(created_at);
+        if let Some(id) = supersedes {
+            entry = entry.with_supersedes(id);
+        }
+        storage.insert_entry(&entry).expect("insert should succeed")
+    };
 
-    let comp_id = storage
-        .create_composition(&new_comp)
-        .expect("create_composition should succeed");
-    assert!(!comp_id.is_nil());
+    let first = insert(
+        &mut storage,
+        "first",
+        chrono::Utc.with_ymd_and_hms(2025, 2, 3, 9, 0, 0).unwrap(),
+        None,
+    );
+    // 23:30 UTC is already the next day two hours east
+    let late = insert(
+        &mut storage,
+        "late",
+        chrono::Utc.with_ymd_and_hms(2025, 2, 2, 23, 30, 0).unwrap(),
+        None,
+    );
+    let revised = insert(
+        &mut storage,
+        "first, revised",
+        chrono::Utc.with_ymd_and_hms(2025, 2, 4, 8, 0, 0).unwrap(),
+        Some(first),
+    );
 
-    let retrieved = storage
-        .get_composition("project_x")
-        .expect("get_composition should succeed");
-    assert!(retrieved.is_some());
+    let day = |number: &str| DayNumber::parse(number).unwrap();
+    assert_eq!(
+        storage.day_number(&first).unwrap(),
+        Some(day("2025-02-03#1"))
+    );
+    assert_eq!(
+        storage.day_number(&late).unwrap(),
+        Some(day("2025-02-03#2"))
+    );
+    assert_eq!(
+        storage.day_number(&revised).unwrap(),
+        Some(day("2025-02-03#1"))
+    );
+    assert_eq!(
+        storage.entry_by_day_number(&day("2025-02-03#1")).unwrap(),
+        Some(revised)
+    );
+    assert_eq!(
+        storage.entry_by_day_number(&day("2025-02-04#1")).unwrap(),
+        None
+    );
+    storage.close(passphrase).expect("close should succeed");
 
-    let comp = retrieved.unwrap();
-    assert_eq!(comp.name, "project_x");
-    assert_eq!(comp.description, Some("My research project".to_string()));
-    assert_eq!(comp.id, comp_id);
-    assert_eq!(comp.metadata, Some(serde_json::json!({"priority": "high"})));
+    // Simulate a ledger written before entries were numbered
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    conn.execute_batch("DROP TABLE entry_day_numbers;")
+        .expect("drop should succeed");
+    let data = conn.serialize(DatabaseName::Main).expect("serialize");
+    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
+        .expect("encrypt should succeed");
+    fs::write(&temp.path, encrypted).expect("write should succeed");
 
-    storage.close(passphrase).expect("close should succeed");
+    // Opening numbers them in time order, under the offset the ledger recorded
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let numbers = storage.day_numbers().unwrap();
+    assert_eq!(numbers.len(), 3);
+    assert_eq!(numbers[&late], day("2025-02-03#1"));
+    assert_eq!(numbers[&first], day("2025-02-03#2"));
+    assert_eq!(numbers[&revised], day("2025-02-03#2"));
 }
 
 #[test]
-fn test_get_composition_by_id() {
-    let temp = TempFile::new("ledger_composition_by_id");
+fn test_open_adds_template_version_to_older_ledgers() {
+    let temp = TempFile::new("ledger_template_version_upgrade");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
-
+    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-    let comp_id = storage
-        .create_composition(&NewComposition::new("test_comp", device_id))
-        .expect("create should succeed");
-
-    let by_id = storage
-        .get_composition_by_id(&comp_id)
-        .expect("get_by_id should succeed");
-    assert!(by_id.is_some());
-    assert_eq!(by_id.unwrap().name, "test_comp");
+    let template_id = storage
+        .create_template(&NewTemplate::new(
+            "daily",
+            entry_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create template");
+    let entry_id = storage
+        .insert_entry(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({ "body": "first" }),
+            device_id,
+        ))
+        .expect("insert should succeed");
+    storage
+        .record_template_use(&entry_id, &template_id, 1)
+        .expect("record use");
+    storage.close(passphrase).expect("close should succeed");
 
-    let nonexistent = storage
-        .get_composition_by_id(&Uuid::new_v4())
-        .expect("get_by_id should succeed");
-    assert!(nonexistent.is_none());
+    // Simulate a ledger written before template versions were recorded
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    conn.execute_batch("ALTER TABLE entry_templates DROP COLUMN template_version;")
+        .expect("drop should succeed");
+    let data = conn.serialize(DatabaseName::Main).expect("serialize");
+    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
+        .expect("encrypt should succeed");
+    fs::write(&temp.path, encrypted).expect("write should succeed");
 
-    storage.close(passphrase).expect("close should succeed");
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let from = storage
+        .entry_template(&entry_id)
+        .expect("entry template")
+        .expect("template recorded");
+    assert_eq!(from.version, None);
+    let usage = storage.template_usage().expect("usage");
+    assert_eq!(usage[&template_id].uses, 1);
+    assert!(usage[&template_id].by_version.is_empty());
 }
 
 #[test]
-fn test_composition_duplicate_name_fails() {
-    let temp = TempFile::new("ledger_composition_dup");
+fn test_check_integrity_ok() {
+    let temp = TempFile::new("ledger_integrity_ok");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
+    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-    storage
-        .create_composition(&NewComposition::new("project_x", device_id))
-        .expect("first create should succeed");
-
-    let result = storage.create_composition(&NewComposition::new("project_x", device_id));
-    assert!(result.is_err());
+    let entry = NewEntry::new(
+        entry_type_id,
+        1,
+        serde_json::json!({"body": "integrity check"}),
+        device_id,
+    );
+    storage.insert_entry(&entry).expect("insert should succeed");
 
-    storage.close(passphrase).expect("close should succeed");
+    let issues = storage.check_integrity().expect("integrity should succeed");
+    assert!(issues.is_empty(), "issues: {:?}", issues);
 }
 
 #[test]
-fn test_list_compositions() {
-    let temp = TempFile::new("ledger_composition_list");
+fn test_close_with_verify_after_write() {
+    let temp = TempFile::new("ledger_verify_after_write");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    storage.set_verify_after_write(true);
 
-    let device_id = Uuid::new_v4();
-    storage
-        .create_composition(&NewComposition::new("alpha", device_id))
-        .expect("create alpha should succeed");
-    storage
-        .create_composition(&NewComposition::new("beta", device_id))
-        .expect("create beta should succeed");
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let entry = NewEntry::new(
+        entry_type_id,
+        1,
+        serde_json::json!({"body": "verified write"}),
+        Uuid::new_v4(),
+    );
+    storage.insert_entry(&entry).expect("insert should succeed");
     storage
-        .create_composition(&NewComposition::new("gamma", device_id))
-        .expect("create gamma should succeed");
-
-    let all = storage
-        .list_compositions(&CompositionFilter::new())
-        .expect("list should succeed");
-    assert_eq!(all.len(), 3);
-
-    // Should be ordered by name
-    assert_eq!(all[0].name, "alpha");
-    assert_eq!(all[1].name, "beta");
-    assert_eq!(all[2].name, "gamma");
+        .close(passphrase)
+        .expect("verified close should succeed");
 
-    // Test limit
-    let limited = storage
-        .list_compositions(&CompositionFilter::new().limit(2))
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+    let entries = storage
+        .list_entries(&EntryFilter::new())
         .expect("list should succeed");
-    assert_eq!(limited.len(), 2);
-
-    storage.close(passphrase).expect("close should succeed");
+    assert_eq!(entries.len(), 1);
 }
 
 #[test]
-fn test_rename_composition() {
-    let temp = TempFile::new("ledger_composition_rename");
+fn test_check_integrity_fails_on_orphaned_fts() {
+    let temp = TempFile::new("ledger_integrity_fail_fts");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
+    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-    let comp_id = storage
-        .create_composition(&NewComposition::new("old_name", device_id))
-        .expect("create should succeed");
-
-    storage
-        .rename_composition(&comp_id, "new_name")
-        .expect("rename should succeed");
+    let entry = NewEntry::new(
+        entry_type_id,
+        1,
+        serde_json::json!({"body": "integrity test"}),
+        device_id,
+    );
+    let entry_id = storage.insert_entry(&entry).expect("insert should succeed");
+    storage.close(passphrase).expect("close should succeed");
 
-    let old = storage
-        .get_composition("old_name")
-        .expect("get old should succeed");
-    assert!(old.is_none());
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    conn.execute(
+        "DELETE FROM entries_fts WHERE entry_id = ?",
+        [entry_id.to_string()],
+    )
+    .expect("delete fts should succeed");
 
-    let new = storage
-        .get_composition("new_name")
-        .expect("get new should succeed");
-    assert!(new.is_some());
-    assert_eq!(new.unwrap().id, comp_id);
+    let data = conn
+        .serialize(DatabaseName::Main)
+        .expect("serialize should succeed");
+    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
+        .expect("encrypt should succeed");
+    fs::write(&temp.path, encrypted).expect("write should succeed");
 
-    storage.close(passphrase).expect("close should succeed");
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let issues = storage.check_integrity().expect("checks should run");
+    let issue = issues
+        .iter()
+        .find(|issue| issue.code == "fts_missing")
+        .expect("missing FTS issue");
+    assert!(issue.is_error());
+    assert_eq!(issue.ids, vec![entry_id.to_string()]);
 }
 
 #[test]
-fn test_rename_composition_to_existing_fails() {
-    let temp = TempFile::new("ledger_composition_rename_dup");
+fn test_check_integrity_reports_orphaned_fts_as_warning() {
+    let temp = TempFile::new("ledger_integrity_orphaned_fts");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
-    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    storage.close(passphrase).expect("close should succeed");
 
-    let device_id = Uuid::new_v4();
-    let comp_id = storage
-        .create_composition(&NewComposition::new("first", device_id))
-        .expect("create first should succeed");
-    storage
-        .create_composition(&NewComposition::new("second", device_id))
-        .expect("create second should succeed");
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    conn.execute(
+        "INSERT INTO entries_fts (entry_id, content) VALUES ('gone', 'stale')",
+        [],
+    )
+    .expect("insert fts should succeed");
+    conn.execute("DELETE FROM meta WHERE key = 'last_modified'", [])
+        .expect("delete meta should succeed");
 
-    let result = storage.rename_composition(&comp_id, "second");
-    assert!(result.is_err());
+    let data = conn
+        .serialize(DatabaseName::Main)
+        .expect("serialize should succeed");
+    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
+        .expect("encrypt should succeed");
+    fs::write(&temp.path, encrypted).expect("write should succeed");
 
-    storage.close(passphrase).expect("close should succeed");
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let issues = storage.check_integrity().expect("checks should run");
+    let codes: Vec<(&str, IntegritySeverity)> = issues
+        .iter()
+        .map(|issue| (issue.code.as_str(), issue.severity))
+        .collect();
+    assert_eq!(
+        codes,
+        vec![
+            ("fts_orphaned", IntegritySeverity::Warning),
+            ("metadata_missing_keys", IntegritySeverity::Error),
+        ]
+    );
+    assert_eq!(issues[0].ids, vec!["gone".to_string()]);
+    assert_eq!(issues[1].ids, vec!["last_modified".to_string()]);
 }
 
+#[cfg(unix)]
 #[test]
-fn test_delete_composition() {
-    let temp = TempFile::new("ledger_composition_delete");
+fn test_atomic_write_failure_leaves_no_temp_files() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_dir("ledger_atomic_failure");
+    let ledger_path = dir.join("test.ledger");
     let passphrase = "test-passphrase-secure-123";
 
-    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
-    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    AgeSqliteStorage::create(&ledger_path, passphrase).expect("create should succeed");
+    let storage = AgeSqliteStorage::open(&ledger_path, passphrase).expect("open should succeed");
 
-    let device_id = Uuid::new_v4();
-    let comp_id = storage
-        .create_composition(&NewComposition::new("to_delete", device_id))
-        .expect("create should succeed");
+    let mut perms = fs::metadata(&dir).expect("metadata").permissions();
+    perms.set_mode(0o500);
+    fs::set_permissions(&dir, perms).expect("set permissions");
 
-    storage
-        .delete_composition(&comp_id)
-        .expect("delete should succeed");
+    let result = storage.close(passphrase);
+    assert!(result.is_err());
 
-    let deleted = storage
-        .get_composition("to_delete")
-        .expect("get should succeed");
-    assert!(deleted.is_none());
+    let mut perms = fs::metadata(&dir).expect("metadata").permissions();
+    perms.set_mode(0o700);
+    fs::set_permissions(&dir, perms).expect("restore permissions");
 
-    storage.close(passphrase).expect("close should succeed");
+    assert_no_temp_files(&ledger_path);
+    let _ = fs::remove_file(&ledger_path);
+    let _ = fs::remove_dir_all(&dir);
 }
 
+#[cfg(unix)]
 #[test]
-fn test_attach_detach_entry_to_composition() {
-    let temp = TempFile::new("ledger_composition_attach");
+fn test_close_writes_through_symlink() {
+    let dir = temp_dir("ledger_symlink_write");
+    let real_path = dir.join("real.ledger");
+    let link_path = dir.join("link.ledger");
     let passphrase = "test-passphrase-secure-123";
 
-    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
-    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
-
-    let device_id = Uuid::new_v4();
-    let entry_type_id = create_basic_entry_type(&mut storage);
-    let comp_id = storage
-        .create_composition(&NewComposition::new("project", device_id))
-        .expect("create comp should succeed");
-
-    let entry_id = storage
-        .insert_entry(&NewEntry::new(
-            entry_type_id,
-            1,
-            serde_json::json!({"body": "test"}),
-            device_id,
-        ))
-        .expect("insert entry should succeed");
-
-    // Attach entry to composition
-    storage
-        .attach_entry_to_composition(&entry_id, &comp_id)
-        .expect("attach should succeed");
-
-    // Verify entry is in composition
-    let comps = storage
-        .get_entry_compositions(&entry_id)
-        .expect("get entry comps should succeed");
-    assert_eq!(comps.len(), 1);
-    assert_eq!(comps[0].id, comp_id);
-
-    // Verify composition has entry
-    let entries = storage
-        .get_composition_entries(&comp_id)
-        .expect("get comp entries should succeed");
-    assert_eq!(entries.len(), 1);
-    assert_eq!(entries[0].entry_id, entry_id);
-
-    // Detach entry
-    storage
-        .detach_entry_from_composition(&entry_id, &comp_id)
-        .expect("detach should succeed");
-
-    let comps_after = storage
-        .get_entry_compositions(&entry_id)
-        .expect("get entry comps should succeed");
-    assert!(comps_after.is_empty());
+    AgeSqliteStorage::create(&real_path, passphrase).expect("create should succeed");
+    std::os::unix::fs::symlink(&real_path, &link_path).expect("create symlink");
 
+    let mut storage = AgeSqliteStorage::open(&link_path, passphrase).expect("open should succeed");
+    create_basic_entry_type(&mut storage);
     storage.close(passphrase).expect("close should succeed");
+
+    let meta = fs::symlink_metadata(&link_path).expect("link metadata");
+    assert!(meta.file_type().is_symlink());
+    let storage = AgeSqliteStorage::open(&real_path, passphrase).expect("reopen real");
+    assert!(storage
+        .get_entry_type("journal")
+        .expect("get entry type")
+        .is_some());
+    assert_no_temp_files(&real_path);
+    let _ = fs::remove_dir_all(&dir);
 }
 
 #[test]
-fn test_attach_idempotent() {
-    let temp = TempFile::new("ledger_composition_attach_idem");
+fn test_entry_type_active_flag_unique() {
+    let temp = TempFile::new("ledger_entry_type_active");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
     let device_id = Uuid::new_v4();
-    let entry_type_id = create_basic_entry_type(&mut storage);
-    let comp_id = storage
-        .create_composition(&NewComposition::new("project", device_id))
-        .expect("create comp should succeed");
-
-    let entry_id = storage
-        .insert_entry(&NewEntry::new(
-            entry_type_id,
-            1,
-            serde_json::json!({"body": "test"}),
-            device_id,
-        ))
-        .expect("insert entry should succeed");
+    let schema_v1 =
+        serde_json::json!({"fields": [{"name": "body", "type": "string", "required": true}]});
+    let schema_v2 =
+        serde_json::json!({"fields": [{"name": "body", "type": "string", "required": true}]});
 
-    // Attach twice should be idempotent
     storage
-        .attach_entry_to_composition(&entry_id, &comp_id)
-        .expect("first attach should succeed");
+        .create_entry_type(&NewEntryType::new("journal", schema_v1, device_id))
+        .expect("create v1 should succeed");
     storage
-        .attach_entry_to_composition(&entry_id, &comp_id)
-        .expect("second attach should succeed");
-
-    let comps = storage
-        .get_entry_compositions(&entry_id)
-        .expect("get entry comps should succeed");
-    assert_eq!(comps.len(), 1);
+        .create_entry_type(&NewEntryType::new("journal", schema_v2, device_id))
+        .expect("create v2 should succeed");
 
     storage.close(passphrase).expect("close should succeed");
+
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    let entry_type_id: String = conn
+        .query_row(
+            "SELECT id FROM entry_types WHERE name = 'journal'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("entry type should exist");
+
+    let active_count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM entry_type_versions WHERE entry_type_id = ? AND active = 1",
+            [entry_type_id],
+            |row| row.get(0),
+        )
+        .expect("count should succeed");
+
+    assert_eq!(active_count, 1);
 }
 
 #[test]
-fn test_delete_composition_removes_associations() {
-    let temp = TempFile::new("ledger_composition_delete_assoc");
+fn test_last_modified_updates_on_entry_type_create() {
+    let temp = TempFile::new("ledger_last_modified");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let device_id = Uuid::new_v4();
-    let entry_type_id = create_basic_entry_type(&mut storage);
-    let comp_id = storage
-        .create_composition(&NewComposition::new("project", device_id))
-        .expect("create comp should succeed");
-
-    let entry_id = storage
-        .insert_entry(&NewEntry::new(
-            entry_type_id,
-            1,
-            serde_json::json!({"body": "test"}),
-            device_id,
-        ))
-        .expect("insert entry should succeed");
+    let before = storage
+        .metadata()
+        .expect("metadata should succeed")
+        .last_modified;
 
-    storage
-        .attach_entry_to_composition(&entry_id, &comp_id)
-        .expect("attach should succeed");
+    std::thread::sleep(Duration::from_millis(2));
 
-    // Delete composition
+    let device_id = Uuid::new_v4();
+    let schema =
+        serde_json::json!({"fields": [{"name": "body", "type": "string", "required": true}]});
     storage
-        .delete_composition(&comp_id)
-        .expect("delete should succeed");
+        .create_entry_type(&NewEntryType::new("journal", schema, device_id))
+        .expect("create should succeed");
 
-    // Entry should still exist
-    let entry = storage
-        .get_entry(&entry_id)
-        .expect("get entry should succeed");
-    assert!(entry.is_some());
+    let after = storage
+        .metadata()
+        .expect("metadata should succeed")
+        .last_modified;
 
-    // But have no compositions
-    let comps = storage
-        .get_entry_compositions(&entry_id)
-        .expect("get entry comps should succeed");
-    assert!(comps.is_empty());
+    assert!(after >= before);
+    assert!(after > before);
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_list_entries_with_composition_filter() {
-    let temp = TempFile::new("ledger_entries_comp_filter");
+fn test_injected_clock_and_ids() {
+    let temp = TempFile::new("ledger_fixed_clock");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let start = chrono::DateTime::parse_from_rfc3339("2025-01-02T03:04:05Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let clock = Arc::new(FixedClock::new(start));
+    storage.set_clock(clock.clone());
+    storage.set_id_gen(Arc::new(SequentialIds::new()));
 
-    let device_id = Uuid::new_v4();
     let entry_type_id = create_basic_entry_type(&mut storage);
-    let comp_id = storage
-        .create_composition(&NewComposition::new("project", device_id))
-        .expect("create comp should succeed");
+    assert_eq!(
+        storage.metadata().unwrap().last_modified,
+        start,
+        "writes stamp last_modified from the clock"
+    );
 
-    // Create two entries, attach only one to composition
-    let entry1_id = storage
-        .insert_entry(&NewEntry::new(
-            entry_type_id,
-            1,
-            serde_json::json!({"body": "in project"}),
-            device_id,
-        ))
-        .expect("insert should succeed");
+    let device_id = Uuid::new_v4();
+    let mut ids = Vec::new();
+    for body in ["first", "second"] {
+        clock.advance(chrono::Duration::minutes(5));
+        ids.push(
+            storage
+                .insert_entry(&NewEntry::new(
+                    entry_type_id,
+                    1,
+                    serde_json::json!({ "body": body }),
+                    device_id,
+                ))
+                .expect("insert should succeed"),
+        );
+    }
+    assert_eq!(ids[1].as_u128(), ids[0].as_u128() + 1);
+    assert_eq!(ids[0].get_version_num(), 4);
 
-    let _entry2_id = storage
-        .insert_entry(&NewEntry::new(
-            entry_type_id,
-            1,
-            serde_json::json!({"body": "not in project"}),
-            device_id,
-        ))
-        .expect("insert should succeed");
+    let first = storage.get_entry(&ids[0]).unwrap().expect("entry exists");
+    assert_eq!(first.created_at, start + chrono::Duration::minutes(5));
+    let entries = storage.list_entries(&EntryFilter::new()).unwrap();
+    assert_eq!(entries[0].id, ids[1]);
+    assert_eq!(
+        storage.metadata().unwrap().last_modified,
+        start + chrono::Duration::minutes(10)
+    );
 
     storage
-        .attach_entry_to_composition(&entry1_id, &comp_id)
-        .expect("attach should succeed");
-
-    // List all entries
-    let all = storage
-        .list_entries(&EntryFilter::new())
-        .expect("list should succeed");
-    assert_eq!(all.len(), 2);
-
-    // List only entries in composition
-    let filtered = storage
-        .list_entries(&EntryFilter::new().composition(comp_id))
-        .expect("list should succeed");
-    assert_eq!(filtered.len(), 1);
-    assert_eq!(filtered[0].id, entry1_id);
+        .create_habit("stretch", &HabitSchedule::Daily, device_id)
+        .expect("create habit should succeed");
+    let habit = storage.get_habit("stretch").unwrap().expect("habit exists");
+    assert_eq!(habit.created_at, start + chrono::Duration::minutes(10));
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 // ============================================================================
-// Template Tests
+// Composition Tests
 // ============================================================================
 
 #[test]
-fn test_create_and_get_template() {
-    let temp = TempFile::new("ledger_template_basic");
+fn test_create_and_get_composition() {
+    let temp = TempFile::new("ledger_composition_basic");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-    let template_json = serde_json::json!({
-        "defaults": {"body": "Default text"},
-        "default_tags": ["daily"]
-    });
-
-    let new_template = NewTemplate::new(
-        "daily_journal",
-        entry_type_id,
-        template_json.clone(),
-        device_id,
-    )
-    .with_description("Template for daily journal entries");
+    let new_comp = NewComposition::new("project_x", device_id)
+        .with_description("My research project")
+        .with_metadata(serde_json::json!({"priority": "high"}));
 
-    let template_id = storage
-        .create_template(&new_template)
-        .expect("create_template should succeed");
-    assert!(!template_id.is_nil());
+    let comp_id = storage
+        .create_composition(&new_comp)
+        .expect("create_composition should succeed");
+    assert!(!comp_id.is_nil());
 
     let retrieved = storage
-        .get_template("daily_journal")
-        .expect("get_template should succeed");
+        .get_composition("project_x")
+        .expect("get_composition should succeed");
     assert!(retrieved.is_some());
 
-    let template = retrieved.unwrap();
-    assert_eq!(template.name, "daily_journal");
-    assert_eq!(template.entry_type_id, entry_type_id);
-    assert_eq!(template.version, 1);
-    assert_eq!(
-        template.description,
-        Some("Template for daily journal entries".to_string())
-    );
-    assert_eq!(template.template_json, template_json);
+    let comp = retrieved.unwrap();
+    assert_eq!(comp.name, "project_x");
+    assert_eq!(comp.description, Some("My research project".to_string()));
+    assert_eq!(comp.id, comp_id);
+    assert_eq!(comp.metadata, Some(serde_json::json!({"priority": "high"})));
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_get_template_by_id() {
-    let temp = TempFile::new("ledger_template_by_id");
+fn test_get_composition_by_id() {
+    let temp = TempFile::new("ledger_composition_by_id");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-    let template_id = storage
-        .create_template(&NewTemplate::new(
-            "test_template",
-            entry_type_id,
-            serde_json::json!({}),
-            device_id,
-        ))
+    let comp_id = storage
+        .create_composition(&NewComposition::new("test_comp", device_id))
         .expect("create should succeed");
 
     let by_id = storage
-        .get_template_by_id(&template_id)
+        .get_composition_by_id(&comp_id)
         .expect("get_by_id should succeed");
     assert!(by_id.is_some());
-    assert_eq!(by_id.unwrap().name, "test_template");
+    assert_eq!(by_id.unwrap().name, "test_comp");
 
     let nonexistent = storage
-        .get_template_by_id(&Uuid::new_v4())
+        .get_composition_by_id(&Uuid::new_v4())
         .expect("get_by_id should succeed");
     assert!(nonexistent.is_none());
 
@@ -1233,168 +1688,134 @@ fn test_get_template_by_id() {
 }
 
 #[test]
-fn test_template_duplicate_name_fails() {
-    let temp = TempFile::new("ledger_template_dup");
+fn test_composition_duplicate_name_fails() {
+    let temp = TempFile::new("ledger_composition_dup");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-
     storage
-        .create_template(&NewTemplate::new(
-            "my_template",
-            entry_type_id,
-            serde_json::json!({}),
-            device_id,
-        ))
+        .create_composition(&NewComposition::new("project_x", device_id))
         .expect("first create should succeed");
 
-    let result = storage.create_template(&NewTemplate::new(
-        "my_template",
-        entry_type_id,
-        serde_json::json!({}),
-        device_id,
-    ));
+    let result = storage.create_composition(&NewComposition::new("project_x", device_id));
     assert!(result.is_err());
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_template_invalid_entry_type_fails() {
-    let temp = TempFile::new("ledger_template_invalid_type");
+fn test_list_compositions() {
+    let temp = TempFile::new("ledger_composition_list");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
     let device_id = Uuid::new_v4();
-    let fake_type_id = Uuid::new_v4();
+    storage
+        .create_composition(&NewComposition::new("alpha", device_id))
+        .expect("create alpha should succeed");
+    storage
+        .create_composition(&NewComposition::new("beta", device_id))
+        .expect("create beta should succeed");
+    storage
+        .create_composition(&NewComposition::new("gamma", device_id))
+        .expect("create gamma should succeed");
 
-    let result = storage.create_template(&NewTemplate::new(
-        "bad_template",
-        fake_type_id,
-        serde_json::json!({}),
-        device_id,
-    ));
-    assert!(result.is_err());
+    let all = storage
+        .list_compositions(&CompositionFilter::new())
+        .expect("list should succeed");
+    assert_eq!(all.len(), 3);
+
+    // Should be ordered by name
+    assert_eq!(all[0].name, "alpha");
+    assert_eq!(all[1].name, "beta");
+    assert_eq!(all[2].name, "gamma");
+
+    // Test limit
+    let limited = storage
+        .list_compositions(&CompositionFilter::new().limit(2))
+        .expect("list should succeed");
+    assert_eq!(limited.len(), 2);
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_list_templates() {
-    let temp = TempFile::new("ledger_template_list");
+fn test_rename_composition() {
+    let temp = TempFile::new("ledger_composition_rename");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
+    let comp_id = storage
+        .create_composition(&NewComposition::new("old_name", device_id))
+        .expect("create should succeed");
 
     storage
-        .create_template(&NewTemplate::new(
-            "alpha",
-            entry_type_id,
-            serde_json::json!({}),
-            device_id,
-        ))
-        .expect("create alpha should succeed");
-    storage
-        .create_template(&NewTemplate::new(
-            "beta",
-            entry_type_id,
-            serde_json::json!({}),
-            device_id,
-        ))
-        .expect("create beta should succeed");
+        .rename_composition(&comp_id, "new_name")
+        .expect("rename should succeed");
 
-    let templates = storage.list_templates().expect("list should succeed");
-    assert_eq!(templates.len(), 2);
+    let old = storage
+        .get_composition("old_name")
+        .expect("get old should succeed");
+    assert!(old.is_none());
 
-    let names: Vec<_> = templates.iter().map(|t| t.name.as_str()).collect();
-    assert!(names.contains(&"alpha"));
-    assert!(names.contains(&"beta"));
+    let new = storage
+        .get_composition("new_name")
+        .expect("get new should succeed");
+    assert!(new.is_some());
+    assert_eq!(new.unwrap().id, comp_id);
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_update_template_creates_new_version() {
-    let temp = TempFile::new("ledger_template_update");
+fn test_rename_composition_to_existing_fails() {
+    let temp = TempFile::new("ledger_composition_rename_dup");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
+    let comp_id = storage
+        .create_composition(&NewComposition::new("first", device_id))
+        .expect("create first should succeed");
+    storage
+        .create_composition(&NewComposition::new("second", device_id))
+        .expect("create second should succeed");
 
-    let template_id = storage
-        .create_template(&NewTemplate::new(
-            "evolving",
-            entry_type_id,
-            serde_json::json!({"defaults": {"body": "v1"}}),
-            device_id,
-        ))
-        .expect("create should succeed");
-
-    let v1 = storage
-        .get_template("evolving")
-        .expect("get should succeed")
-        .unwrap();
-    assert_eq!(v1.version, 1);
-    assert_eq!(v1.template_json["defaults"]["body"], "v1");
-
-    // Update template
-    let new_version = storage
-        .update_template(
-            &template_id,
-            serde_json::json!({"defaults": {"body": "v2"}}),
-        )
-        .expect("update should succeed");
-    assert_eq!(new_version, 2);
-
-    let v2 = storage
-        .get_template("evolving")
-        .expect("get should succeed")
-        .unwrap();
-    assert_eq!(v2.version, 2);
-    assert_eq!(v2.template_json["defaults"]["body"], "v2");
+    let result = storage.rename_composition(&comp_id, "second");
+    assert!(result.is_err());
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_delete_template() {
-    let temp = TempFile::new("ledger_template_delete");
+fn test_delete_composition() {
+    let temp = TempFile::new("ledger_composition_delete");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-
-    let template_id = storage
-        .create_template(&NewTemplate::new(
-            "to_delete",
-            entry_type_id,
-            serde_json::json!({}),
-            device_id,
-        ))
+    let comp_id = storage
+        .create_composition(&NewComposition::new("to_delete", device_id))
         .expect("create should succeed");
 
     storage
-        .delete_template(&template_id)
+        .delete_composition(&comp_id)
         .expect("delete should succeed");
 
     let deleted = storage
-        .get_template("to_delete")
+        .get_composition("to_delete")
         .expect("get should succeed");
     assert!(deleted.is_none());
 
@@ -1402,318 +1823,502 @@ fn test_delete_template() {
 }
 
 #[test]
-fn test_set_and_get_default_template() {
-    let temp = TempFile::new("ledger_template_default");
+fn test_attach_detach_entry_to_composition() {
+    let temp = TempFile::new("ledger_composition_attach");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let comp_id = storage
+        .create_composition(&NewComposition::new("project", device_id))
+        .expect("create comp should succeed");
 
-    let template_id = storage
-        .create_template(&NewTemplate::new(
-            "default_tmpl",
+    let entry_id = storage
+        .insert_entry(&NewEntry::new(
             entry_type_id,
-            serde_json::json!({"defaults": {"body": "default body"}}),
+            1,
+            serde_json::json!({"body": "test"}),
             device_id,
         ))
-        .expect("create should succeed");
+        .expect("insert entry should succeed");
 
-    // No default initially
-    let no_default = storage
-        .get_default_template(&entry_type_id)
-        .expect("get default should succeed");
-    assert!(no_default.is_none());
+    // Attach entry to composition
+    storage
+        .attach_entry_to_composition(&entry_id, &comp_id)
+        .expect("attach should succeed");
 
-    // Set default
+    // Verify entry is in composition
+    let comps = storage
+        .get_entry_compositions(&entry_id)
+        .expect("get entry comps should succeed");
+    assert_eq!(comps.len(), 1);
+    assert_eq!(comps[0].id, comp_id);
+
+    // Verify composition has entry
+    let entries = storage
+        .get_composition_entries(&comp_id)
+        .expect("get comp entries should succeed");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].entry_id, entry_id);
+
+    // Detach entry
     storage
-        .set_default_template(&entry_type_id, &template_id)
-        .expect("set default should succeed");
+        .detach_entry_from_composition(&entry_id, &comp_id)
+        .expect("detach should succeed");
 
-    let default = storage
-        .get_default_template(&entry_type_id)
-        .expect("get default should succeed");
-    assert!(default.is_some());
-    assert_eq!(default.unwrap().id, template_id);
+    let comps_after = storage
+        .get_entry_compositions(&entry_id)
+        .expect("get entry comps should succeed");
+    assert!(comps_after.is_empty());
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_set_default_template_replaces_existing() {
-    let temp = TempFile::new("ledger_template_default_replace");
+fn test_attach_idempotent() {
+    let temp = TempFile::new("ledger_composition_attach_idem");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-
-    let template1_id = storage
-        .create_template(&NewTemplate::new(
-            "first",
-            entry_type_id,
-            serde_json::json!({}),
-            device_id,
-        ))
-        .expect("create should succeed");
-    let template2_id = storage
-        .create_template(&NewTemplate::new(
-            "second",
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let comp_id = storage
+        .create_composition(&NewComposition::new("project", device_id))
+        .expect("create comp should succeed");
+
+    let entry_id = storage
+        .insert_entry(&NewEntry::new(
             entry_type_id,
-            serde_json::json!({}),
+            1,
+            serde_json::json!({"body": "test"}),
             device_id,
         ))
-        .expect("create should succeed");
+        .expect("insert entry should succeed");
 
+    // Attach twice should be idempotent
     storage
-        .set_default_template(&entry_type_id, &template1_id)
-        .expect("set first should succeed");
+        .attach_entry_to_composition(&entry_id, &comp_id)
+        .expect("first attach should succeed");
     storage
-        .set_default_template(&entry_type_id, &template2_id)
-        .expect("set second should succeed");
+        .attach_entry_to_composition(&entry_id, &comp_id)
+        .expect("second attach should succeed");
 
-    let default = storage
-        .get_default_template(&entry_type_id)
-        .expect("get default should succeed");
-    assert!(default.is_some());
-    assert_eq!(default.unwrap().id, template2_id);
+    let comps = storage
+        .get_entry_compositions(&entry_id)
+        .expect("get entry comps should succeed");
+    assert_eq!(comps.len(), 1);
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_set_default_template_wrong_entry_type_fails() {
-    let temp = TempFile::new("ledger_template_default_wrong_type");
+fn test_detach_keeps_membership_history() {
+    let temp = TempFile::new("ledger_composition_history");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let journal_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-
-    // Create another entry type
-    let weight_type_id = storage
-        .create_entry_type(&NewEntryType::new(
-            "weight",
-            serde_json::json!({"fields": [{"name": "kg", "type": "number", "required": true}]}),
-            device_id,
-        ))
-        .expect("create weight type should succeed");
-
-    // Create template for journal
-    let template_id = storage
-        .create_template(&NewTemplate::new(
-            "journal_tmpl",
-            journal_type_id,
-            serde_json::json!({}),
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let comp_id = storage
+        .create_composition(&NewComposition::new("project", device_id))
+        .expect("create comp should succeed");
+    let entry_id = storage
+        .insert_entry(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": "test"}),
             device_id,
         ))
-        .expect("create should succeed");
+        .expect("insert entry should succeed");
 
-    // Try to set it as default for weight (should fail)
-    let result = storage.set_default_template(&weight_type_id, &template_id);
-    assert!(result.is_err());
+    storage
+        .attach_entry_to_composition(&entry_id, &comp_id)
+        .expect("attach should succeed");
+    storage
+        .detach_entry_from_composition(&entry_id, &comp_id)
+        .expect("detach should succeed");
+    assert!(storage
+        .get_composition_entries(&comp_id)
+        .expect("get comp entries should succeed")
+        .is_empty());
+    assert!(storage
+        .detach_entry_from_composition(&entry_id, &comp_id)
+        .is_err());
+    storage
+        .attach_entry_to_composition(&entry_id, &comp_id)
+        .expect("reattach should succeed");
+    storage.close(passphrase).expect("close should succeed");
 
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let history = storage
+        .composition_history(&comp_id)
+        .expect("history should load");
+    assert_eq!(history.len(), 2);
+    assert!(history.iter().all(|m| m.entry_id == entry_id));
+    assert!(history[0].removed_at.is_some());
+    assert!(history[1].removed_at.is_none());
+    assert_eq!(
+        storage
+            .get_composition_entries(&comp_id)
+            .expect("get comp entries should succeed")
+            .len(),
+        1
+    );
+    let bundle = storage.export_structure().expect("export should succeed");
+    assert_eq!(bundle.entry_compositions.len(), 2);
+    assert!(storage
+        .check_integrity()
+        .expect("checks should run")
+        .is_empty());
     storage.close(passphrase).expect("close should succeed");
+
+    // A membership that ends before it starts, and one whose entry is gone
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    conn.execute_batch("PRAGMA foreign_keys = OFF;")
+        .expect("pragma should succeed");
+    conn.execute(
+        "UPDATE entry_compositions SET removed_at = '2000-01-01T00:00:00+00:00' \
+         WHERE removed_at IS NOT NULL",
+        [],
+    )
+    .expect("update should succeed");
+    conn.execute(
+        "INSERT INTO entry_compositions (entry_id, composition_id, added_at) \
+         VALUES ('gone', ?, '2024-01-01T00:00:00+00:00')",
+        [comp_id.to_string()],
+    )
+    .expect("insert should succeed");
+    let data = conn
+        .serialize(DatabaseName::Main)
+        .expect("serialize should succeed");
+    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
+        .expect("encrypt should succeed");
+    fs::write(&temp.path, encrypted).expect("write should succeed");
+
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let issues = storage.check_integrity().expect("checks should run");
+    let dangling = issues
+        .iter()
+        .find(|issue| issue.code == "membership_dangling")
+        .expect("dangling membership should be reported");
+    assert_eq!(dangling.ids, vec![format!("gone:{}", comp_id)]);
+    assert!(issues
+        .iter()
+        .any(|issue| issue.code == "membership_removed_before_added"));
 }
 
 #[test]
-fn test_clear_default_template() {
-    let temp = TempFile::new("ledger_template_clear_default");
+fn test_open_migrates_memberships_without_history() {
+    let temp = TempFile::new("ledger_composition_history_migrate");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
-
-    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-
-    let template_id = storage
-        .create_template(&NewTemplate::new(
-            "default_tmpl",
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let comp_id = storage
+        .create_composition(&NewComposition::new("project", device_id))
+        .expect("create comp should succeed");
+    let entry_id = storage
+        .insert_entry(&NewEntry::new(
             entry_type_id,
-            serde_json::json!({}),
+            1,
+            serde_json::json!({"body": "test"}),
             device_id,
         ))
-        .expect("create should succeed");
+        .expect("insert entry should succeed");
+    storage.close(passphrase).expect("close should succeed");
 
-    storage
-        .set_default_template(&entry_type_id, &template_id)
-        .expect("set should succeed");
+    // Recreate the join table as it was before membership history
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    conn.execute_batch(&format!(
+        r#"
+        DROP TABLE entry_compositions;
+        CREATE TABLE entry_compositions (
+            entry_id TEXT NOT NULL,
+            composition_id TEXT NOT NULL,
+            added_at TEXT NOT NULL,
+            PRIMARY KEY (entry_id, composition_id)
+        );
+        INSERT INTO entry_compositions VALUES ('{}', '{}', '2024-01-01T00:00:00+00:00');
+        "#,
+        entry_id, comp_id
+    ))
+    .expect("old schema should apply");
+    let data = conn
+        .serialize(DatabaseName::Main)
+        .expect("serialize should succeed");
+    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
+        .expect("encrypt should succeed");
+    fs::write(&temp.path, encrypted).expect("write should succeed");
 
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    assert_eq!(
+        storage
+            .get_composition_entries(&comp_id)
+            .expect("get comp entries should succeed")
+            .len(),
+        1
+    );
     storage
-        .clear_default_template(&entry_type_id)
-        .expect("clear should succeed");
-
-    let default = storage
-        .get_default_template(&entry_type_id)
-        .expect("get default should succeed");
-    assert!(default.is_none());
-
-    storage.close(passphrase).expect("close should succeed");
+        .detach_entry_from_composition(&entry_id, &comp_id)
+        .expect("detach should succeed");
+    storage
+        .attach_entry_to_composition(&entry_id, &comp_id)
+        .expect("reattach should succeed");
+    let history = storage
+        .composition_history(&comp_id)
+        .expect("history should load");
+    assert_eq!(history.len(), 2);
+    assert!(history[0].removed_at.is_some());
 }
 
 #[test]
-fn test_delete_template_removes_default_mapping() {
-    let temp = TempFile::new("ledger_template_delete_default");
+fn test_delete_composition_removes_associations() {
+    let temp = TempFile::new("ledger_composition_delete_assoc");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
     let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
 
-    let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let comp_id = storage
+        .create_composition(&NewComposition::new("project", device_id))
+        .expect("create comp should succeed");
 
-    let template_id = storage
-        .create_template(&NewTemplate::new(
-            "default_tmpl",
+    let entry_id = storage
+        .insert_entry(&NewEntry::new(
             entry_type_id,
-            serde_json::json!({}),
+            1,
+            serde_json::json!({"body": "test"}),
             device_id,
         ))
-        .expect("create should succeed");
+        .expect("insert entry should succeed");
 
     storage
-        .set_default_template(&entry_type_id, &template_id)
-        .expect("set should succeed");
+        .attach_entry_to_composition(&entry_id, &comp_id)
+        .expect("attach should succeed");
 
+    // Delete composition
     storage
-        .delete_template(&template_id)
+        .delete_composition(&comp_id)
         .expect("delete should succeed");
 
-    let default = storage
-        .get_default_template(&entry_type_id)
-        .expect("get default should succeed");
-    assert!(default.is_none());
+    // Entry should still exist
+    let entry = storage
+        .get_entry(&entry_id)
+        .expect("get entry should succeed");
+    assert!(entry.is_some());
+
+    // But have no compositions
+    let comps = storage
+        .get_entry_compositions(&entry_id)
+        .expect("get entry comps should succeed");
+    assert!(comps.is_empty());
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_template_persistence() {
-    let temp = TempFile::new("ledger_template_persist");
+fn test_drafts_stay_out_of_entries_until_published() {
+    let temp = TempFile::new("ledger_drafts");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let written = chrono::Utc::now() - chrono::Duration::days(3);
 
-    {
-        let mut storage =
-            AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
-
-        let entry_type_id = create_basic_entry_type(&mut storage);
-        let device_id = Uuid::new_v4();
-
-        let template_id = storage
-            .create_template(&NewTemplate::new(
-                "persistent",
+    let draft_id = storage
+        .insert_draft(
+            &NewEntry::new(
                 entry_type_id,
-                serde_json::json!({"defaults": {"body": "hello"}}),
+                1,
+                serde_json::json!({"body": "first pass"}),
                 device_id,
-            ))
-            .expect("create should succeed");
-
-        storage
-            .set_default_template(&entry_type_id, &template_id)
-            .expect("set default should succeed");
-
-        storage.close(passphrase).expect("close should succeed");
-    }
-
-    // Reopen and verify persistence
-    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
-
-    let template = storage
-        .get_template("persistent")
-        .expect("get should succeed");
-    assert!(template.is_some());
-    assert_eq!(template.unwrap().template_json["defaults"]["body"], "hello");
-
-    let entry_type = storage
-        .get_entry_type("journal")
-        .expect("get type should succeed")
-        .expect("type should exist");
-
-    let default = storage
-        .get_default_template(&entry_type.id)
-        .expect("get default should succeed");
-    assert!(default.is_some());
-    assert_eq!(default.unwrap().name, "persistent");
+            )
+            .with_created_at(written),
+        )
+        .expect("insert draft should succeed");
+    let empty = storage
+        .insert_draft(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("drafts need not match the schema");
+    storage
+        .update_draft(
+            &draft_id,
+            &serde_json::json!({"body": "second pass"}),
+            &["Work".to_string()],
+        )
+        .expect("update draft should succeed");
+    assert!(storage
+        .update_draft(&Uuid::new_v4(), &serde_json::json!({}), &[])
+        .is_err());
+    assert!(storage
+        .list_entries(&EntryFilter::new())
+        .expect("list should succeed")
+        .is_empty());
+    assert!(storage
+        .search_entries("second")
+        .expect("search should succeed")
+        .is_empty());
+    storage.close(passphrase).expect("close should succeed");
 
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let drafts = storage.list_drafts().expect("list drafts should succeed");
+    assert_eq!(drafts.len(), 2);
+    assert_eq!(drafts[0].id, draft_id);
+    assert_eq!(drafts[0].tags, vec!["work".to_string()]);
+
+    // Publishing validates against the schema and keeps the draft on failure
+    assert!(storage.publish_draft(&empty, None).is_err());
+    assert!(storage.get_draft(&empty).expect("get draft").is_some());
+    let entry_id = storage
+        .publish_draft(&draft_id, None)
+        .expect("publish should succeed");
+    let entry = storage
+        .get_entry(&entry_id)
+        .expect("get entry should succeed")
+        .expect("entry should exist");
+    assert_eq!(entry.data["body"], "second pass");
+    assert_eq!(entry.created_at.timestamp(), written.timestamp());
+    assert!(storage.get_draft(&draft_id).expect("get draft").is_none());
+    assert!(storage.delete_draft(&empty).expect("delete draft"));
+    assert!(!storage.delete_draft(&empty).expect("delete draft"));
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_composition_persistence() {
-    let temp = TempFile::new("ledger_composition_persist");
+fn test_idempotency_keys_replay_per_operation() {
+    let temp = TempFile::new("ledger_idempotency");
     let passphrase = "test-passphrase-secure-123";
 
-    let comp_id: Uuid;
-    let entry_id: Uuid;
-
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let entry_id = storage
+        .insert_entry(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": "once"}),
+            device_id,
+        ))
+        .expect("insert entry should succeed");
 
-    {
-        let mut storage =
-            AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
-
-        let device_id = Uuid::new_v4();
-        let entry_type_id = create_basic_entry_type(&mut storage);
-
-        comp_id = storage
-            .create_composition(
-                &NewComposition::new("persistent_comp", device_id)
-                    .with_description("Persisted composition"),
-            )
-            .expect("create comp should succeed");
-
-        entry_id = storage
-            .insert_entry(&NewEntry::new(
-                entry_type_id,
-                1,
-                serde_json::json!({"body": "test"}),
-                device_id,
-            ))
-            .expect("insert should succeed");
+    assert!(storage
+        .idempotency_record("job-1", "add")
+        .expect("lookup should succeed")
+        .is_none());
+    storage
+        .record_idempotency_key("job-1", "add", Some(&entry_id), None)
+        .expect("record should succeed");
+    assert!(storage
+        .record_idempotency_key("job-1", "add", Some(&entry_id), None)
+        .is_err());
+    assert!(storage
+        .record_idempotency_key("job-2", "add", Some(&Uuid::new_v4()), None)
+        .is_err());
+    assert!(storage.idempotency_record(" ", "add").is_err());
+    storage
+        .record_idempotency_key(
+            "import-1",
+            "import",
+            None,
+            Some(&serde_json::json!({"entries": 3})),
+        )
+        .expect("record should succeed");
+    storage.close(passphrase).expect("close should succeed");
 
-        storage
-            .attach_entry_to_composition(&entry_id, &comp_id)
-            .expect("attach should succeed");
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let record = storage
+        .idempotency_record("job-1", "add")
+        .expect("lookup should succeed")
+        .expect("key should be recorded");
+    assert_eq!(record.entry_id, Some(entry_id));
+    assert_eq!(record.result, None);
+    let import = storage
+        .idempotency_record("import-1", "import")
+        .expect("lookup should succeed")
+        .expect("key should be recorded");
+    assert_eq!(import.result, Some(serde_json::json!({"entries": 3})));
+    // A key belongs to the operation that used it first
+    assert!(storage.idempotency_record("job-1", "import").is_err());
+}
 
-        storage.close(passphrase).expect("close should succeed");
-    }
+#[test]
+fn test_composition_overview_round_trip_and_export() {
+    let temp = TempFile::new("ledger_composition_overview");
+    let passphrase = "test-passphrase-secure-123";
 
-    // Reopen and verify persistence
-    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let comp_id = storage
+        .create_composition(&NewComposition::new("garden", Uuid::new_v4()))
+        .expect("create comp should succeed");
+    assert!(storage.composition_overview(&comp_id).unwrap().is_none());
+    assert!(storage
+        .set_composition_overview(&Uuid::new_v4(), "# Missing")
+        .is_err());
 
-    let comp = storage
-        .get_composition("persistent_comp")
-        .expect("get should succeed");
-    assert!(comp.is_some());
-    let comp = comp.unwrap();
-    assert_eq!(comp.id, comp_id);
-    assert_eq!(comp.description, Some("Persisted composition".to_string()));
+    storage
+        .set_composition_overview(&comp_id, "# Garden\n\nRaised beds.")
+        .expect("set overview should succeed");
+    storage.close(passphrase).expect("close should succeed");
 
-    let comps = storage
-        .get_entry_compositions(&entry_id)
-        .expect("get comps should succeed");
-    assert_eq!(comps.len(), 1);
-    assert_eq!(comps[0].id, comp_id);
+    let mut storage =
+        AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+    let overview = storage
+        .composition_overview(&comp_id)
+        .unwrap()
+        .expect("overview");
+    assert_eq!(overview.body, "# Garden\n\nRaised beds.");
+
+    // Exports carry the overview into a fresh ledger
+    let bundle = storage.export_structure().expect("export should succeed");
+    assert_eq!(bundle.composition_overviews.len(), 1);
+    let other = TempFile::new("ledger_composition_overview_import");
+    AgeSqliteStorage::create(&other.path, passphrase).expect("create should succeed");
+    let mut imported =
+        AgeSqliteStorage::open(&other.path, passphrase).expect("open should succeed");
+    let summary = imported
+        .import_bundle(&bundle)
+        .expect("import should succeed");
+    assert_eq!(summary.overviews, 1);
+    assert_eq!(
+        imported
+            .composition_overview(&comp_id)
+            .unwrap()
+            .unwrap()
+            .body,
+        overview.body
+    );
 
-    storage.close(passphrase).expect("close should succeed");
+    // Empty text clears it, and deleting the composition removes it
+    storage.set_composition_overview(&comp_id, "").unwrap();
+    assert!(storage.composition_overview(&comp_id).unwrap().is_none());
+    imported
+        .delete_composition(&comp_id)
+        .expect("delete should succeed");
+    assert!(imported.composition_overview(&comp_id).unwrap().is_none());
 }
 
-// ============================================================================
-// Edge Case Tests (M5)
-// ============================================================================
-
 #[test]
-fn test_detach_entry_not_attached_fails() {
-    let temp = TempFile::new("ledger_detach_not_attached");
+fn test_list_entries_with_composition_filter() {
+    let temp = TempFile::new("ledger_entries_comp_filter");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
@@ -1725,25 +2330,52 @@ fn test_detach_entry_not_attached_fails() {
         .create_composition(&NewComposition::new("project", device_id))
         .expect("create comp should succeed");
 
-    let entry_id = storage
+    // Create two entries, attach only one to composition
+    let entry1_id = storage
         .insert_entry(&NewEntry::new(
             entry_type_id,
             1,
-            serde_json::json!({"body": "test"}),
+            serde_json::json!({"body": "in project"}),
             device_id,
         ))
-        .expect("insert entry should succeed");
+        .expect("insert should succeed");
 
-    // Try to detach entry that was never attached
-    let result = storage.detach_entry_from_composition(&entry_id, &comp_id);
-    assert!(result.is_err());
+    let _entry2_id = storage
+        .insert_entry(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": "not in project"}),
+            device_id,
+        ))
+        .expect("insert should succeed");
+
+    storage
+        .attach_entry_to_composition(&entry1_id, &comp_id)
+        .expect("attach should succeed");
+
+    // List all entries
+    let all = storage
+        .list_entries(&EntryFilter::new())
+        .expect("list should succeed");
+    assert_eq!(all.len(), 2);
+
+    // List only entries in composition
+    let filtered = storage
+        .list_entries(&EntryFilter::new().composition(comp_id))
+        .expect("list should succeed");
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(filtered[0].id, entry1_id);
 
     storage.close(passphrase).expect("close should succeed");
 }
 
+// ============================================================================
+// Template Tests
+// ============================================================================
+
 #[test]
-fn test_get_default_template_returns_latest_version() {
-    let temp = TempFile::new("ledger_default_latest_version");
+fn test_create_and_get_template() {
+    let temp = TempFile::new("ledger_template_basic");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
@@ -1751,51 +2383,45 @@ fn test_get_default_template_returns_latest_version() {
 
     let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
+    let template_json = serde_json::json!({
+        "defaults": {"body": "Default text"},
+        "default_tags": ["daily"]
+    });
 
-    let template_id = storage
-        .create_template(&NewTemplate::new(
-            "versioned",
-            entry_type_id,
-            serde_json::json!({"defaults": {"body": "v1"}}),
-            device_id,
-        ))
-        .expect("create should succeed");
-
-    storage
-        .set_default_template(&entry_type_id, &template_id)
-        .expect("set default should succeed");
-
-    // Update template to v2
-    storage
-        .update_template(
-            &template_id,
-            serde_json::json!({"defaults": {"body": "v2"}}),
-        )
-        .expect("update should succeed");
+    let new_template = NewTemplate::new(
+        "daily_journal",
+        entry_type_id,
+        template_json.clone(),
+        device_id,
+    )
+    .with_description("Template for daily journal entries");
 
-    // Update template to v3
-    storage
-        .update_template(
-            &template_id,
-            serde_json::json!({"defaults": {"body": "v3"}}),
-        )
-        .expect("update should succeed");
+    let template_id = storage
+        .create_template(&new_template)
+        .expect("create_template should succeed");
+    assert!(!template_id.is_nil());
 
-    // get_default_template should return v3
-    let default = storage
-        .get_default_template(&entry_type_id)
-        .expect("get default should succeed")
-        .expect("default should exist");
+    let retrieved = storage
+        .get_template("daily_journal")
+        .expect("get_template should succeed");
+    assert!(retrieved.is_some());
 
-    assert_eq!(default.version, 3);
-    assert_eq!(default.template_json["defaults"]["body"], "v3");
+    let template = retrieved.unwrap();
+    assert_eq!(template.name, "daily_journal");
+    assert_eq!(template.entry_type_id, entry_type_id);
+    assert_eq!(template.version, 1);
+    assert_eq!(
+        template.description,
+        Some("Template for daily journal entries".to_string())
+    );
+    assert_eq!(template.template_json, template_json);
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_delete_template_allows_new_default() {
-    let temp = TempFile::new("ledger_delete_allows_new");
+fn test_get_template_by_id() {
+    let temp = TempFile::new("ledger_template_by_id");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
@@ -1803,52 +2429,32 @@ fn test_delete_template_allows_new_default() {
 
     let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
-
-    let template1_id = storage
-        .create_template(&NewTemplate::new(
-            "first",
-            entry_type_id,
-            serde_json::json!({}),
-            device_id,
-        ))
-        .expect("create first should succeed");
-
-    let template2_id = storage
+    let template_id = storage
         .create_template(&NewTemplate::new(
-            "second",
+            "test_template",
             entry_type_id,
             serde_json::json!({}),
             device_id,
         ))
-        .expect("create second should succeed");
-
-    // Set first as default
-    storage
-        .set_default_template(&entry_type_id, &template1_id)
-        .expect("set should succeed");
-
-    // Delete first template
-    storage
-        .delete_template(&template1_id)
-        .expect("delete should succeed");
+        .expect("create should succeed");
 
-    // Should be able to set second as default without issues
-    storage
-        .set_default_template(&entry_type_id, &template2_id)
-        .expect("set new default should succeed");
+    let by_id = storage
+        .get_template_by_id(&template_id)
+        .expect("get_by_id should succeed");
+    assert!(by_id.is_some());
+    assert_eq!(by_id.unwrap().name, "test_template");
 
-    let default = storage
-        .get_default_template(&entry_type_id)
-        .expect("get default should succeed")
-        .expect("default should exist");
-    assert_eq!(default.id, template2_id);
+    let nonexistent = storage
+        .get_template_by_id(&Uuid::new_v4())
+        .expect("get_by_id should succeed");
+    assert!(nonexistent.is_none());
 
     storage.close(passphrase).expect("close should succeed");
 }
 
 #[test]
-fn test_list_templates_returns_latest_versions_only() {
-    let temp = TempFile::new("ledger_list_latest_only");
+fn test_template_duplicate_name_fails() {
+    let temp = TempFile::new("ledger_template_dup");
     let passphrase = "test-passphrase-secure-123";
 
     AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
@@ -1857,34 +2463,2344 @@ fn test_list_templates_returns_latest_versions_only() {
     let entry_type_id = create_basic_entry_type(&mut storage);
     let device_id = Uuid::new_v4();
 
-    let template_id = storage
+    storage
         .create_template(&NewTemplate::new(
-            "multi_version",
+            "my_template",
             entry_type_id,
-            serde_json::json!({"defaults": {"body": "v1"}}),
+            serde_json::json!({}),
             device_id,
         ))
-        .expect("create should succeed");
+        .expect("first create should succeed");
 
-    // Create multiple versions
-    storage
-        .update_template(
-            &template_id,
-            serde_json::json!({"defaults": {"body": "v2"}}),
-        )
-        .expect("update v2 should succeed");
-    storage
-        .update_template(
-            &template_id,
-            serde_json::json!({"defaults": {"body": "v3"}}),
-        )
-        .expect("update v3 should succeed");
+    let result = storage.create_template(&NewTemplate::new(
+        "my_template",
+        entry_type_id,
+        serde_json::json!({}),
+        device_id,
+    ));
+    assert!(result.is_err());
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_template_invalid_entry_type_fails() {
+    let temp = TempFile::new("ledger_template_invalid_type");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let device_id = Uuid::new_v4();
+    let fake_type_id = Uuid::new_v4();
+
+    let result = storage.create_template(&NewTemplate::new(
+        "bad_template",
+        fake_type_id,
+        serde_json::json!({}),
+        device_id,
+    ));
+    assert!(result.is_err());
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_list_templates() {
+    let temp = TempFile::new("ledger_template_list");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    storage
+        .create_template(&NewTemplate::new(
+            "alpha",
+            entry_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create alpha should succeed");
+    storage
+        .create_template(&NewTemplate::new(
+            "beta",
+            entry_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create beta should succeed");
 
-    // list_templates should only return one entry with version 3
     let templates = storage.list_templates().expect("list should succeed");
-    assert_eq!(templates.len(), 1);
-    assert_eq!(templates[0].version, 3);
-    assert_eq!(templates[0].template_json["defaults"]["body"], "v3");
+    assert_eq!(templates.len(), 2);
+
+    let names: Vec<_> = templates.iter().map(|t| t.name.as_str()).collect();
+    assert!(names.contains(&"alpha"));
+    assert!(names.contains(&"beta"));
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_update_template_creates_new_version() {
+    let temp = TempFile::new("ledger_template_update");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    let template_id = storage
+        .create_template(&NewTemplate::new(
+            "evolving",
+            entry_type_id,
+            serde_json::json!({"defaults": {"body": "v1"}}),
+            device_id,
+        ))
+        .expect("create should succeed");
+
+    let v1 = storage
+        .get_template("evolving")
+        .expect("get should succeed")
+        .unwrap();
+    assert_eq!(v1.version, 1);
+    assert_eq!(v1.template_json["defaults"]["body"], "v1");
+
+    // Update template
+    let new_version = storage
+        .update_template(
+            &template_id,
+            serde_json::json!({"defaults": {"body": "v2"}}),
+        )
+        .expect("update should succeed");
+    assert_eq!(new_version, 2);
+
+    let v2 = storage
+        .get_template("evolving")
+        .expect("get should succeed")
+        .unwrap();
+    assert_eq!(v2.version, 2);
+    assert_eq!(v2.template_json["defaults"]["body"], "v2");
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_delete_template() {
+    let temp = TempFile::new("ledger_template_delete");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    let template_id = storage
+        .create_template(&NewTemplate::new(
+            "to_delete",
+            entry_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create should succeed");
+
+    storage
+        .delete_template(&template_id)
+        .expect("delete should succeed");
+
+    let deleted = storage
+        .get_template("to_delete")
+        .expect("get should succeed");
+    assert!(deleted.is_none());
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_set_and_get_default_template() {
+    let temp = TempFile::new("ledger_template_default");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    let template_id = storage
+        .create_template(&NewTemplate::new(
+            "default_tmpl",
+            entry_type_id,
+            serde_json::json!({"defaults": {"body": "default body"}}),
+            device_id,
+        ))
+        .expect("create should succeed");
+
+    // No default initially
+    let no_default = storage
+        .get_default_template(&entry_type_id)
+        .expect("get default should succeed");
+    assert!(no_default.is_none());
+
+    // Set default
+    storage
+        .set_default_template(&entry_type_id, &template_id)
+        .expect("set default should succeed");
+
+    let default = storage
+        .get_default_template(&entry_type_id)
+        .expect("get default should succeed");
+    assert!(default.is_some());
+    assert_eq!(default.unwrap().id, template_id);
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_set_default_template_replaces_existing() {
+    let temp = TempFile::new("ledger_template_default_replace");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    let template1_id = storage
+        .create_template(&NewTemplate::new(
+            "first",
+            entry_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create should succeed");
+    let template2_id = storage
+        .create_template(&NewTemplate::new(
+            "second",
+            entry_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create should succeed");
+
+    storage
+        .set_default_template(&entry_type_id, &template1_id)
+        .expect("set first should succeed");
+    storage
+        .set_default_template(&entry_type_id, &template2_id)
+        .expect("set second should succeed");
+
+    let default = storage
+        .get_default_template(&entry_type_id)
+        .expect("get default should succeed");
+    assert!(default.is_some());
+    assert_eq!(default.unwrap().id, template2_id);
 
     storage.close(passphrase).expect("close should succeed");
 }
+
+#[test]
+fn test_set_default_template_wrong_entry_type_fails() {
+    let temp = TempFile::new("ledger_template_default_wrong_type");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let journal_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    // Create another entry type
+    let weight_type_id = storage
+        .create_entry_type(&NewEntryType::new(
+            "weight",
+            serde_json::json!({"fields": [{"name": "kg", "type": "number", "required": true}]}),
+            device_id,
+        ))
+        .expect("create weight type should succeed");
+
+    // Create template for journal
+    let template_id = storage
+        .create_template(&NewTemplate::new(
+            "journal_tmpl",
+            journal_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create should succeed");
+
+    // Try to set it as default for weight (should fail)
+    let result = storage.set_default_template(&weight_type_id, &template_id);
+    assert!(result.is_err());
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_clear_default_template() {
+    let temp = TempFile::new("ledger_template_clear_default");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    let template_id = storage
+        .create_template(&NewTemplate::new(
+            "default_tmpl",
+            entry_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create should succeed");
+
+    storage
+        .set_default_template(&entry_type_id, &template_id)
+        .expect("set should succeed");
+
+    storage
+        .clear_default_template(&entry_type_id)
+        .expect("clear should succeed");
+
+    let default = storage
+        .get_default_template(&entry_type_id)
+        .expect("get default should succeed");
+    assert!(default.is_none());
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_delete_template_removes_default_mapping() {
+    let temp = TempFile::new("ledger_template_delete_default");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    let template_id = storage
+        .create_template(&NewTemplate::new(
+            "default_tmpl",
+            entry_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create should succeed");
+
+    storage
+        .set_default_template(&entry_type_id, &template_id)
+        .expect("set should succeed");
+
+    storage
+        .delete_template(&template_id)
+        .expect("delete should succeed");
+
+    let default = storage
+        .get_default_template(&entry_type_id)
+        .expect("get default should succeed");
+    assert!(default.is_none());
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_template_persistence() {
+    let temp = TempFile::new("ledger_template_persist");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+
+    {
+        let mut storage =
+            AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+        let entry_type_id = create_basic_entry_type(&mut storage);
+        let device_id = Uuid::new_v4();
+
+        let template_id = storage
+            .create_template(&NewTemplate::new(
+                "persistent",
+                entry_type_id,
+                serde_json::json!({"defaults": {"body": "hello"}}),
+                device_id,
+            ))
+            .expect("create should succeed");
+
+        storage
+            .set_default_template(&entry_type_id, &template_id)
+            .expect("set default should succeed");
+
+        storage.close(passphrase).expect("close should succeed");
+    }
+
+    // Reopen and verify persistence
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+
+    let template = storage
+        .get_template("persistent")
+        .expect("get should succeed");
+    assert!(template.is_some());
+    assert_eq!(template.unwrap().template_json["defaults"]["body"], "hello");
+
+    let entry_type = storage
+        .get_entry_type("journal")
+        .expect("get type should succeed")
+        .expect("type should exist");
+
+    let default = storage
+        .get_default_template(&entry_type.id)
+        .expect("get default should succeed");
+    assert!(default.is_some());
+    assert_eq!(default.unwrap().name, "persistent");
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_composition_persistence() {
+    let temp = TempFile::new("ledger_composition_persist");
+    let passphrase = "test-passphrase-secure-123";
+
+    let comp_id: Uuid;
+    let entry_id: Uuid;
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+
+    {
+        let mut storage =
+            AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+        let device_id = Uuid::new_v4();
+        let entry_type_id = create_basic_entry_type(&mut storage);
+
+        comp_id = storage
+            .create_composition(
+                &NewComposition::new("persistent_comp", device_id)
+                    .with_description("Persisted composition"),
+            )
+            .expect("create comp should succeed");
+
+        entry_id = storage
+            .insert_entry(&NewEntry::new(
+                entry_type_id,
+                1,
+                serde_json::json!({"body": "test"}),
+                device_id,
+            ))
+            .expect("insert should succeed");
+
+        storage
+            .attach_entry_to_composition(&entry_id, &comp_id)
+            .expect("attach should succeed");
+
+        storage.close(passphrase).expect("close should succeed");
+    }
+
+    // Reopen and verify persistence
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+
+    let comp = storage
+        .get_composition("persistent_comp")
+        .expect("get should succeed");
+    assert!(comp.is_some());
+    let comp = comp.unwrap();
+    assert_eq!(comp.id, comp_id);
+    assert_eq!(comp.description, Some("Persisted composition".to_string()));
+
+    let comps = storage
+        .get_entry_compositions(&entry_id)
+        .expect("get comps should succeed");
+    assert_eq!(comps.len(), 1);
+    assert_eq!(comps[0].id, comp_id);
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+// ============================================================================
+// Edge Case Tests (M5)
+// ============================================================================
+
+#[test]
+fn test_detach_entry_not_attached_fails() {
+    let temp = TempFile::new("ledger_detach_not_attached");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let device_id = Uuid::new_v4();
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let comp_id = storage
+        .create_composition(&NewComposition::new("project", device_id))
+        .expect("create comp should succeed");
+
+    let entry_id = storage
+        .insert_entry(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": "test"}),
+            device_id,
+        ))
+        .expect("insert entry should succeed");
+
+    // Try to detach entry that was never attached
+    let result = storage.detach_entry_from_composition(&entry_id, &comp_id);
+    assert!(result.is_err());
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_get_default_template_returns_latest_version() {
+    let temp = TempFile::new("ledger_default_latest_version");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    let template_id = storage
+        .create_template(&NewTemplate::new(
+            "versioned",
+            entry_type_id,
+            serde_json::json!({"defaults": {"body": "v1"}}),
+            device_id,
+        ))
+        .expect("create should succeed");
+
+    storage
+        .set_default_template(&entry_type_id, &template_id)
+        .expect("set default should succeed");
+
+    // Update template to v2
+    storage
+        .update_template(
+            &template_id,
+            serde_json::json!({"defaults": {"body": "v2"}}),
+        )
+        .expect("update should succeed");
+
+    // Update template to v3
+    storage
+        .update_template(
+            &template_id,
+            serde_json::json!({"defaults": {"body": "v3"}}),
+        )
+        .expect("update should succeed");
+
+    // get_default_template should return v3
+    let default = storage
+        .get_default_template(&entry_type_id)
+        .expect("get default should succeed")
+        .expect("default should exist");
+
+    assert_eq!(default.version, 3);
+    assert_eq!(default.template_json["defaults"]["body"], "v3");
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_delete_template_allows_new_default() {
+    let temp = TempFile::new("ledger_delete_allows_new");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    let template1_id = storage
+        .create_template(&NewTemplate::new(
+            "first",
+            entry_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create first should succeed");
+
+    let template2_id = storage
+        .create_template(&NewTemplate::new(
+            "second",
+            entry_type_id,
+            serde_json::json!({}),
+            device_id,
+        ))
+        .expect("create second should succeed");
+
+    // Set first as default
+    storage
+        .set_default_template(&entry_type_id, &template1_id)
+        .expect("set should succeed");
+
+    // Delete first template
+    storage
+        .delete_template(&template1_id)
+        .expect("delete should succeed");
+
+    // Should be able to set second as default without issues
+    storage
+        .set_default_template(&entry_type_id, &template2_id)
+        .expect("set new default should succeed");
+
+    let default = storage
+        .get_default_template(&entry_type_id)
+        .expect("get default should succeed")
+        .expect("default should exist");
+    assert_eq!(default.id, template2_id);
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_list_templates_returns_latest_versions_only() {
+    let temp = TempFile::new("ledger_list_latest_only");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+
+    let template_id = storage
+        .create_template(&NewTemplate::new(
+            "multi_version",
+            entry_type_id,
+            serde_json::json!({"defaults": {"body": "v1"}}),
+            device_id,
+        ))
+        .expect("create should succeed");
+
+    // Create multiple versions
+    storage
+        .update_template(
+            &template_id,
+            serde_json::json!({"defaults": {"body": "v2"}}),
+        )
+        .expect("update v2 should succeed");
+    storage
+        .update_template(
+            &template_id,
+            serde_json::json!({"defaults": {"body": "v3"}}),
+        )
+        .expect("update v3 should succeed");
+
+    // list_templates should only return one entry with version 3
+    let templates = storage.list_templates().expect("list should succeed");
+    assert_eq!(templates.len(), 1);
+    assert_eq!(templates[0].version, 3);
+    assert_eq!(templates[0].template_json["defaults"]["body"], "v3");
+
+    storage.close(passphrase).expect("close should succeed");
+}
+
+// ============================================================================
+// Attachment Tests
+// ============================================================================
+
+#[test]
+fn test_attachment_round_trip_persists_encrypted() {
+    let temp = TempFile::new("ledger_attachment_round_trip");
+    let passphrase = "test-passphrase-secure-123";
+    let payload = b"RIFF fake audio bytes".to_vec();
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+
+    let entry_id: Uuid;
+    let attachment_id: Uuid;
+    {
+        let mut storage =
+            AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+        let device_id = Uuid::new_v4();
+        let entry_type_id = create_basic_entry_type(&mut storage);
+        entry_id = storage
+            .insert_entry(&NewEntry::new(
+                entry_type_id,
+                1,
+                serde_json::json!({"body": "memo"}),
+                device_id,
+            ))
+            .expect("insert should succeed");
+        attachment_id = storage
+            .add_attachment(&NewAttachment::new(
+                entry_id,
+                "memo.m4a",
+                "audio/mp4",
+                payload.clone(),
+                device_id,
+            ))
+            .expect("add attachment should succeed");
+        assert!(storage
+            .check_integrity()
+            .expect("integrity should pass")
+            .is_empty());
+        storage.close(passphrase).expect("close should succeed");
+    }
+
+    let on_disk = fs::read(&temp.path).expect("read should succeed");
+    assert!(!on_disk
+        .windows(payload.len())
+        .any(|window| window == payload.as_slice()));
+
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+    let attachments = storage
+        .list_attachments(&entry_id)
+        .expect("list attachments should succeed");
+    assert_eq!(attachments.len(), 1);
+    assert_eq!(attachments[0].id, attachment_id);
+    assert_eq!(attachments[0].name, "memo.m4a");
+    assert_eq!(attachments[0].mime_type, "audio/mp4");
+    assert_eq!(attachments[0].size, payload.len() as u64);
+
+    let data = storage
+        .get_attachment_data(&attachment_id)
+        .expect("get data should succeed");
+    assert_eq!(data, Some(payload));
+    assert!(storage
+        .get_attachment_data(&Uuid::new_v4())
+        .expect("get data should succeed")
+        .is_none());
+}
+
+#[test]
+fn test_attachment_requires_existing_entry() {
+    let temp = TempFile::new("ledger_attachment_missing_entry");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let result = storage.add_attachment(&NewAttachment::new(
+        Uuid::new_v4(),
+        "memo.m4a",
+        "audio/mp4",
+        vec![1, 2, 3],
+        Uuid::new_v4(),
+    ));
+    assert!(matches!(result, Err(ledger_core::LedgerError::NotFound(_))));
+}
+
+#[test]
+fn test_open_refuses_ledgers_from_a_newer_format() {
+    let temp = TempFile::new("ledger_newer_format");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    conn.execute(
+        "UPDATE meta SET value = '9.0' WHERE key = 'format_version'",
+        [],
+    )
+    .expect("update should succeed");
+    let data = conn.serialize(DatabaseName::Main).expect("serialize");
+    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
+        .expect("encrypt should succeed");
+    fs::write(&temp.path, encrypted).expect("write should succeed");
+
+    match AgeSqliteStorage::open(&temp.path, passphrase) {
+        Err(ledger_core::LedgerError::VersionMismatch { found, supported }) => {
+            assert_eq!(found, "9.0");
+            assert_eq!(supported, "0.1");
+        }
+        Err(other) => panic!("expected a version mismatch, got {:?}", other),
+        Ok(_) => panic!("a newer format should not open"),
+    }
+}
+
+#[test]
+fn test_missing_templates_and_compositions_have_their_own_errors() {
+    let temp = TempFile::new("ledger_typed_not_found");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    assert!(matches!(
+        AgeSqliteStorage::open(&temp.path, "not-the-passphrase-123"),
+        Err(ledger_core::LedgerError::IncorrectPassphrase)
+    ));
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    let missing = Uuid::new_v4();
+    let err = storage
+        .delete_composition(&missing)
+        .expect_err("delete should fail");
+    assert!(matches!(err, ledger_core::LedgerError::CompositionNotFound(id) if id == missing));
+    assert_eq!(
+        err.to_string(),
+        format!("Not found: Composition {} not found", missing)
+    );
+
+    let err = storage
+        .delete_template(&missing)
+        .expect_err("delete should fail");
+    assert!(matches!(err, ledger_core::LedgerError::TemplateNotFound(id) if id == missing));
+    assert_eq!(
+        err.to_string(),
+        format!("Not found: Template {} not found", missing)
+    );
+}
+
+#[test]
+fn test_open_adds_attachments_table_to_older_ledgers() {
+    let temp = TempFile::new("ledger_attachment_upgrade");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+
+    // Simulate a ledger written before attachments existed
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    conn.execute_batch("DROP TABLE attachments;")
+        .expect("drop should succeed");
+    let data = conn.serialize(DatabaseName::Main).expect("serialize");
+    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
+        .expect("encrypt should succeed");
+    fs::write(&temp.path, encrypted).expect("write should succeed");
+
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let attachments = storage
+        .list_attachments(&Uuid::new_v4())
+        .expect("list attachments should succeed");
+    assert!(attachments.is_empty());
+}
+
+#[test]
+fn test_identical_attachments_share_one_blob_until_gc() {
+    let temp = TempFile::new("ledger_attachment_dedup");
+    let passphrase = "test-passphrase-secure-123";
+    let photo = vec![7u8; 4096];
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let mut attachment_ids = Vec::new();
+    for body in ["first", "second"] {
+        let entry_id = storage
+            .insert_entry(&NewEntry::new(
+                entry_type_id,
+                1,
+                serde_json::json!({"body": body}),
+                device_id,
+            ))
+            .expect("insert should succeed");
+        attachment_ids.push(
+            storage
+                .add_attachment(&NewAttachment::new(
+                    entry_id,
+                    "photo.jpg",
+                    "image/jpeg",
+                    photo.clone(),
+                    device_id,
+                ))
+                .expect("add attachment should succeed"),
+        );
+    }
+
+    let usage = storage.attachment_usage().expect("usage should succeed");
+    assert_eq!(usage.attachments, 2);
+    assert_eq!(usage.logical_bytes, 8192);
+    assert_eq!(usage.blobs, 1);
+    assert_eq!(usage.stored_bytes, 4096);
+    assert_eq!(usage.unreferenced_blobs, 0);
+
+    // Dropping one reference keeps the bytes for the other
+    assert!(storage
+        .remove_attachment(&attachment_ids[0])
+        .expect("remove should succeed"));
+    assert!(!storage
+        .remove_attachment(&attachment_ids[0])
+        .expect("remove should succeed"));
+    let gc = storage.gc_attachment_blobs().expect("gc should succeed");
+    assert_eq!(gc.blobs_removed, 0);
+    assert_eq!(
+        storage
+            .get_attachment_data(&attachment_ids[1])
+            .expect("get data should succeed"),
+        Some(photo)
+    );
+
+    // The last reference leaves the blob until gc collects it
+    storage
+        .remove_attachment(&attachment_ids[1])
+        .expect("remove should succeed");
+    let usage = storage.attachment_usage().expect("usage should succeed");
+    assert_eq!(usage.unreferenced_blobs, 1);
+    assert_eq!(usage.unreferenced_bytes, 4096);
+    let gc = storage.gc_attachment_blobs().expect("gc should succeed");
+    assert_eq!(gc.blobs_removed, 1);
+    assert_eq!(gc.bytes_freed, 4096);
+    assert_eq!(
+        storage.attachment_usage().expect("usage should succeed"),
+        Default::default()
+    );
+    assert!(storage
+        .check_integrity()
+        .expect("integrity should pass")
+        .is_empty());
+}
+
+#[test]
+fn test_open_moves_inline_attachment_bytes_into_blobs() {
+    let temp = TempFile::new("ledger_attachment_blob_upgrade");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let entry_id = {
+        let mut storage =
+            AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+        let entry_type_id = create_basic_entry_type(&mut storage);
+        let entry_id = storage
+            .insert_entry(&NewEntry::new(
+                entry_type_id,
+                1,
+                serde_json::json!({"body": "memo"}),
+                Uuid::new_v4(),
+            ))
+            .expect("insert should succeed");
+        storage.close(passphrase).expect("close should succeed");
+        entry_id
+    };
+
+    // Simulate a ledger written before attachment blobs existed
+    let conn = open_sqlite_from_file(&temp.path, passphrase);
+    conn.execute_batch(
+        r#"
+        DROP TABLE attachments;
+        DROP TABLE attachment_blobs;
+        CREATE TABLE attachments (
+            id TEXT PRIMARY KEY,
+            entry_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            data BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            device_id TEXT NOT NULL,
+
+            FOREIGN KEY (entry_id) REFERENCES entries(id)
+        );
+        "#,
+    )
+    .expect("downgrade should succeed");
+    for name in ["a.jpg", "b.jpg"] {
+        conn.execute(
+            "INSERT INTO attachments (id, entry_id, name, mime_type, size, data, created_at, device_id) \
+             VALUES (?, ?, ?, 'image/jpeg', 3, x'010203', '2024-01-01T00:00:00+00:00', ?)",
+            rusqlite::params![
+                Uuid::new_v4().to_string(),
+                entry_id.to_string(),
+                name,
+                Uuid::new_v4().to_string()
+            ],
+        )
+        .expect("insert should succeed");
+    }
+    let data = conn.serialize(DatabaseName::Main).expect("serialize");
+    let encrypted = ledger_core::storage::encryption::encrypt(data.as_ref(), passphrase)
+        .expect("encrypt should succeed");
+    fs::write(&temp.path, encrypted).expect("write should succeed");
+
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let usage = storage.attachment_usage().expect("usage should succeed");
+    assert_eq!(usage.attachments, 2);
+    assert_eq!(usage.blobs, 1);
+    assert_eq!(usage.stored_bytes, 3);
+    for attachment in storage
+        .list_attachments(&entry_id)
+        .expect("list attachments should succeed")
+    {
+        assert_eq!(
+            storage
+                .get_attachment_data(&attachment.id)
+                .expect("get data should succeed"),
+            Some(vec![1, 2, 3])
+        );
+    }
+    assert!(storage
+        .check_integrity()
+        .expect("integrity should pass")
+        .is_empty());
+}
+
+// ============================================================================
+// Secret Tests
+// ============================================================================
+
+#[test]
+fn test_secret_round_trip_persists_encrypted() {
+    let temp = TempFile::new("ledger_secret_round_trip");
+    let passphrase = "test-passphrase-secure-123";
+    let value = "s3cr3t-backup-token";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    {
+        let mut storage =
+            AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+        storage
+            .set_secret("backup.token", "old-value")
+            .expect("set should succeed");
+        storage
+            .set_secret("backup.token", value)
+            .expect("overwrite should succeed");
+        storage
+            .set_secret("api-key", "other")
+            .expect("set should succeed");
+        storage.close(passphrase).expect("close should succeed");
+    }
+
+    let on_disk = fs::read(&temp.path).expect("read should succeed");
+    assert!(!on_disk
+        .windows(value.len())
+        .any(|window| window == value.as_bytes()));
+
+    let mut storage =
+        AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+    assert_eq!(
+        storage
+            .get_secret("backup.token")
+            .expect("get should succeed"),
+        Some(value.to_string())
+    );
+    assert_eq!(
+        storage.list_secret_names().expect("list should succeed"),
+        vec!["api-key", "backup.token"]
+    );
+
+    storage
+        .delete_secret("api-key")
+        .expect("delete should succeed");
+    assert!(storage
+        .get_secret("api-key")
+        .expect("get should succeed")
+        .is_none());
+    assert!(matches!(
+        storage.delete_secret("api-key"),
+        Err(ledger_core::LedgerError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_secret_name_and_value_validation() {
+    let temp = TempFile::new("ledger_secret_validation");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+
+    for name in ["", "Upper", "has space", "slash/name"] {
+        assert!(
+            matches!(
+                storage.set_secret(name, "value"),
+                Err(ledger_core::LedgerError::Validation(_))
+            ),
+            "name {:?} should be rejected",
+            name
+        );
+    }
+    assert!(matches!(
+        storage.set_secret("empty", ""),
+        Err(ledger_core::LedgerError::Validation(_))
+    ));
+}
+
+fn create_ledger_with_entries(temp: &TempFile, passphrase: &str, count: usize) {
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    for index in 0..count {
+        let body = format!("marker-{:04} {}", index, "padding ".repeat(60));
+        storage
+            .insert_entry(&NewEntry::new(
+                entry_type_id,
+                1,
+                serde_json::json!({ "body": body }),
+                device_id,
+            ))
+            .expect("insert should succeed");
+    }
+    storage.close(passphrase).expect("close should succeed");
+}
+
+#[test]
+fn test_recover_healthy_ledger_is_complete() {
+    let source = TempFile::new("ledger_recover_healthy_src");
+    let out = TempFile::new("ledger_recover_healthy_out");
+    let passphrase = "test-passphrase-secure-123";
+    create_ledger_with_entries(&source, passphrase, 3);
+
+    let report = AgeSqliteStorage::recover(&source.path, &out.path, passphrase).expect("recover");
+    assert!(report.is_complete(), "report: {:?}", report);
+    assert!(report.issues.is_empty());
+    let entries = report
+        .tables
+        .iter()
+        .find(|table| table.table == "entries")
+        .expect("entries table");
+    assert_eq!(entries.recovered, 3);
+
+    let storage = AgeSqliteStorage::open(&out.path, passphrase).expect("open recovered");
+    let results = storage.search_entries("marker").expect("search recovered");
+    assert_eq!(results.len(), 3);
+
+    let err = AgeSqliteStorage::recover(&source.path, &out.path, passphrase)
+        .expect_err("existing output should be refused");
+    assert!(err.to_string().contains("already exists"));
+}
+
+#[test]
+fn test_recover_salvages_around_damaged_page() {
+    let source = TempFile::new("ledger_recover_damaged_src");
+    let out = TempFile::new("ledger_recover_damaged_out");
+    let passphrase = "test-passphrase-secure-123";
+    create_ledger_with_entries(&source, passphrase, 120);
+
+    // Smash the header of the table page holding one entry's row
+    let mut plaintext = decrypt(&fs::read(&source.path).unwrap(), passphrase).unwrap();
+    let page_size = u16::from_be_bytes([plaintext[16], plaintext[17]]) as usize;
+    let needle = br#"{"body":"marker-0060"#;
+    let offset = plaintext
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .expect("entry row in file");
+    let page_start = offset / page_size * page_size;
+    plaintext[page_start..page_start + 8].copy_from_slice(&[0xFF; 8]);
+    let damaged = ledger_core::storage::encryption::encrypt(&plaintext, passphrase).unwrap();
+    fs::write(&source.path, &damaged).unwrap();
+
+    let report = AgeSqliteStorage::recover(&source.path, &out.path, passphrase).expect("recover");
+    let entries = report
+        .tables
+        .iter()
+        .find(|table| table.table == "entries")
+        .expect("entries table");
+    assert!(!entries.unreadable.is_empty(), "report: {:?}", entries);
+    assert!(entries.recovered > 0 && entries.recovered < 120);
+    assert!(!report.is_complete());
+    assert!(report.issues.is_empty(), "issues: {:?}", report.issues);
+
+    // The source is untouched and the output is a healthy ledger
+    assert_eq!(fs::read(&source.path).unwrap(), damaged);
+    let storage = AgeSqliteStorage::open(&out.path, passphrase).expect("open recovered");
+    assert!(storage.check_integrity().expect("check").is_empty());
+    let listed = storage
+        .list_entries(&EntryFilter::new())
+        .expect("list recovered");
+    assert_eq!(listed.len(), entries.recovered);
+}
+
+#[test]
+fn test_export_import_round_trip_keeps_structure() {
+    let source = TempFile::new("ledger_bundle_src");
+    let target = TempFile::new("ledger_bundle_dst");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&source.path, passphrase).expect("create should succeed");
+    let mut storage =
+        AgeSqliteStorage::open(&source.path, passphrase).expect("open should succeed");
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    let entry_id = storage
+        .insert_entry(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": "carried over"}),
+            device_id,
+        ))
+        .expect("insert should succeed");
+    let composition_id = storage
+        .create_composition(&NewComposition::new("project-x", device_id))
+        .expect("create composition should succeed");
+    storage
+        .attach_entry_to_composition(&entry_id, &composition_id)
+        .expect("attach should succeed");
+    let template_id = storage
+        .create_template(&NewTemplate::new(
+            "daily",
+            entry_type_id,
+            serde_json::json!({"defaults": {"body": "v1"}}),
+            device_id,
+        ))
+        .expect("create template should succeed");
+    storage
+        .update_template(
+            &template_id,
+            serde_json::json!({"defaults": {"body": "v2"}}),
+        )
+        .expect("update template should succeed");
+    storage
+        .set_default_template(&entry_type_id, &template_id)
+        .expect("set default should succeed");
+
+    let mut bundle = storage.export_structure().expect("export should succeed");
+    bundle.entries = storage
+        .list_entries(&EntryFilter::new())
+        .expect("list should succeed");
+    assert_eq!(bundle.templates[0].versions.len(), 2);
+    assert!(bundle.templates[0].default);
+    assert_eq!(bundle.entry_compositions.len(), 1);
+
+    AgeSqliteStorage::create(&target.path, passphrase).expect("create should succeed");
+    let mut imported =
+        AgeSqliteStorage::open(&target.path, passphrase).expect("open should succeed");
+    let summary = imported
+        .import_bundle(&bundle)
+        .expect("import should succeed");
+    assert_eq!(summary.entry_types, 1);
+    assert_eq!(summary.templates, 1);
+    assert_eq!(summary.compositions, 1);
+    assert_eq!(summary.entries, 1);
+    assert_eq!(summary.memberships, 1);
+    assert_eq!(summary.skipped, 0);
+
+    let members = imported
+        .get_composition_entries(&composition_id)
+        .expect("memberships should load");
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].entry_id, entry_id);
+    let default = imported
+        .get_default_template(&entry_type_id)
+        .expect("default should load")
+        .expect("default should exist");
+    assert_eq!(default.id, template_id);
+    assert_eq!(default.version, 2);
+    assert_eq!(imported.search_entries("carried").unwrap().len(), 1);
+    assert!(imported.check_integrity().unwrap().is_empty());
+
+    // Importing again changes nothing
+    let again = imported
+        .import_bundle(&bundle)
+        .expect("reimport should succeed");
+    assert_eq!(again.entries, 0);
+    assert_eq!(again.skipped, 5);
+}
+
+#[test]
+fn test_due_entries_follow_recurrence_annotations() {
+    let temp = TempFile::new("ledger_due_entries");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+
+    for field in [
+        serde_json::json!({"name": "title", "type": "string", "recurrence": "yearly"}),
+        serde_json::json!({"name": "date", "type": "date", "notify": true}),
+        serde_json::json!({"name": "date", "type": "date", "recurrence": "yearly", "notify": "no"}),
+    ] {
+        let bad = NewEntryType::new(
+            "broken",
+            serde_json::json!({ "fields": [field] }),
+            device_id,
+        );
+        assert!(storage.create_entry_type(&bad).is_err());
+    }
+
+    let anniversary = storage
+        .create_entry_type(&NewEntryType::new(
+            "anniversary",
+            serde_json::json!({"fields": [
+                {"name": "title", "type": "string", "required": true},
+                {"name": "date", "type": "date", "required": true, "recurrence": "yearly"},
+                {"name": "noted", "type": "date"}
+            ]}),
+            device_id,
+        ))
+        .expect("create entry type should succeed");
+    let add = |storage: &mut AgeSqliteStorage, title: &str, date: &str| {
+        storage
+            .insert_entry(&NewEntry::new(
+                anniversary,
+                1,
+                serde_json::json!({"title": title, "date": date, "noted": "2024-06-12"}),
+                device_id,
+            ))
+            .expect("insert should succeed")
+    };
+    let wedding = add(&mut storage, "Wedding", "2019-06-20");
+    add(&mut storage, "Moved in", "2020-09-01");
+    let first_job = add(&mut storage, "First job", "2015-06-11");
+    let revised = storage
+        .insert_entry(
+            &NewEntry::new(
+                anniversary,
+                1,
+                serde_json::json!({"title": "First job", "date": "2015-06-14"}),
+                device_id,
+            )
+            .with_supersedes(first_job),
+        )
+        .expect("insert should succeed");
+
+    let from = chrono::NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    let until = chrono::NaiveDate::from_ymd_opt(2024, 6, 24).unwrap();
+    let due = storage
+        .due_entries(from, until)
+        .expect("due query should succeed");
+
+    let ids: Vec<Uuid> = due.iter().map(|d| d.entry.id).collect();
+    assert_eq!(ids, vec![revised, wedding]);
+    assert_eq!(due[1].field, "date");
+    assert_eq!(
+        due[1].due,
+        chrono::NaiveDate::from_ymd_opt(2024, 6, 20).unwrap()
+    );
+    assert_eq!(due[1].occurrence, 5);
+    assert!(due[1].notify);
+}
+
+#[test]
+fn test_display_annotations_are_validated() {
+    let temp = TempFile::new("ledger_display_annotations");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+    let schema = |field: serde_json::Value| serde_json::json!({ "fields": [field] });
+
+    for bad in [
+        serde_json::json!({"name": "note", "type": "string", "unit": "kg"}),
+        serde_json::json!({"name": "amount", "type": "number", "currency": "EURO"}),
+        serde_json::json!({"name": "amount", "type": "number", "decimals": -1}),
+        serde_json::json!({"name": "amount", "type": "number", "unit": " "}),
+    ] {
+        let entry_type = NewEntryType::new("bad", schema(bad.clone()), device_id);
+        assert!(
+            storage.create_entry_type(&entry_type).is_err(),
+            "{} should be rejected",
+            bad
+        );
+    }
+
+    let good = NewEntryType::new(
+        "expense",
+        schema(serde_json::json!({
+            "name": "amount", "type": "number", "currency": "EUR", "decimals": 2
+        })),
+        device_id,
+    );
+    storage
+        .create_entry_type(&good)
+        .expect("annotated number field should be accepted");
+}
+
+#[test]
+fn test_type_icon_and_color_are_validated() {
+    let temp = TempFile::new("ledger_type_badge");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+    let fields = serde_json::json!([{"name": "body", "type": "text"}]);
+
+    for bad in [
+        serde_json::json!({"fields": fields, "icon": ""}),
+        serde_json::json!({"fields": fields, "icon": "a very long icon"}),
+        serde_json::json!({"fields": fields, "icon": 7}),
+        serde_json::json!({"fields": fields, "color": "teal"}),
+        serde_json::json!({"fields": fields, "summary_template": "no placeholders"}),
+        serde_json::json!({"fields": fields, "summary_template": "{body} {mood}"}),
+        serde_json::json!({"fields": fields, "summary_template": "{body"}),
+    ] {
+        let entry_type = NewEntryType::new("bad", bad.clone(), device_id);
+        assert!(
+            storage.create_entry_type(&entry_type).is_err(),
+            "{} should be rejected",
+            bad
+        );
+    }
+
+    let good = NewEntryType::new(
+        "mood",
+        serde_json::json!({
+            "fields": fields,
+            "icon": "\u{1F642}",
+            "color": "magenta",
+            "summary_template": "Feeling {body}",
+        }),
+        device_id,
+    );
+    storage
+        .create_entry_type(&good)
+        .expect("icon and color should be accepted");
+    let stored = storage.get_entry_type("mood").unwrap().unwrap();
+    assert_eq!(stored.icon(), Some("\u{1F642}"));
+    assert_eq!(stored.color(), Some("magenta"));
+    assert_eq!(
+        stored
+            .summary_template()
+            .and_then(|template| template.render(&serde_json::json!({"body": "fine"}))),
+        Some("Feeling fine".to_string())
+    );
+}
+
+#[test]
+fn test_aggregate_sums_current_entries_by_group() {
+    let temp = TempFile::new("ledger_aggregate");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+
+    let expense = storage
+        .create_entry_type(&NewEntryType::new(
+            "expense",
+            serde_json::json!({"fields": [
+                {"name": "amount", "type": "number", "required": true},
+                {"name": "currency", "type": "string", "required": true},
+                {"name": "category", "type": "string"}
+            ]}),
+            device_id,
+        ))
+        .expect("create entry type should succeed");
+    let mut add = |amount: f64, currency: &str, category: Option<&str>| {
+        let mut data = serde_json::json!({"amount": amount, "currency": currency});
+        if let Some(category) = category {
+            data["category"] = serde_json::json!(category);
+        }
+        storage
+            .insert_entry(&NewEntry::new(expense, 1, data, device_id))
+            .expect("insert should succeed")
+    };
+    add(12.5, "USD", Some("food"));
+    add(7.5, "USD", Some("food"));
+    add(40.0, "USD", None);
+    let typo = add(900.0, "EUR", Some("rent"));
+    storage
+        .insert_entry(
+            &NewEntry::new(
+                expense,
+                1,
+                serde_json::json!({"amount": 800.0, "currency": "EUR", "category": "rent"}),
+                device_id,
+            )
+            .with_supersedes(typo),
+        )
+        .expect("insert should succeed");
+
+    let rows = storage
+        .aggregate(
+            &AggregateQuery::new(expense, "amount")
+                .group_by("currency")
+                .group_by("category"),
+        )
+        .expect("aggregate should succeed");
+    let summary: Vec<(Vec<Option<&str>>, usize, f64)> = rows
+        .iter()
+        .map(|row| {
+            (
+                row.group.iter().map(|g| g.as_deref()).collect(),
+                row.count,
+                row.sum,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (vec![Some("EUR"), Some("rent")], 1, 800.0),
+            (vec![Some("USD"), None], 1, 40.0),
+            (vec![Some("USD"), Some("food")], 2, 20.0),
+        ]
+    );
+
+    let total = storage
+        .aggregate(&AggregateQuery::new(expense, "amount").since(chrono::Utc::now()))
+        .expect("aggregate should succeed");
+    assert!(total.is_empty());
+
+    let err = storage
+        .aggregate(&AggregateQuery::new(expense, "amount').x"))
+        .expect_err("field names are restricted");
+    assert!(err.to_string().contains("Invalid field name"));
+}
+
+#[test]
+fn test_day_counts_bucket_by_shifted_day() {
+    use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+
+    let temp = TempFile::new("ledger_day_counts");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+
+    let journal = storage
+        .create_entry_type(&NewEntryType::new(
+            "journal",
+            serde_json::json!({"fields": [{"name": "body", "type": "text"}]}),
+            device_id,
+        ))
+        .expect("create entry type should succeed");
+    let mut add = |hour: u32, day: u32| {
+        let created_at = Utc.with_ymd_and_hms(2024, 3, day, hour, 30, 0).unwrap();
+        storage
+            .insert_entry(
+                &NewEntry::new(journal, 1, serde_json::json!({"body": "x"}), device_id)
+                    .with_created_at(created_at),
+            )
+            .expect("insert should succeed")
+    };
+    add(10, 4);
+    let revised = add(23, 4);
+    add(12, 5);
+    add(12, 9);
+    storage
+        .insert_entry(
+            &NewEntry::new(journal, 1, serde_json::json!({"body": "y"}), device_id)
+                .with_created_at(Utc.with_ymd_and_hms(2024, 3, 4, 23, 45, 0).unwrap())
+                .with_supersedes(revised),
+        )
+        .expect("insert should succeed");
+
+    let day = |d: u32| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    let counts = |offset: FixedOffset| -> Vec<(NaiveDate, usize)> {
+        storage
+            .day_counts(day(4), day(8), offset)
+            .expect("day counts should succeed")
+            .into_iter()
+            .map(|row| {
+                assert_eq!(row.entry_type_id, journal);
+                (row.date, row.count)
+            })
+            .collect()
+    };
+    assert_eq!(
+        counts(FixedOffset::east_opt(0).unwrap()),
+        vec![(day(4), 2), (day(5), 1)]
+    );
+    // Two hours ahead of UTC, the late revision falls on the 5th
+    assert_eq!(
+        counts(FixedOffset::east_opt(2 * 3600).unwrap()),
+        vec![(day(4), 1), (day(5), 2)]
+    );
+}
+
+#[test]
+fn test_habit_definitions_round_trip() {
+    let temp = TempFile::new("ledger_habits");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+
+    let stretch = HabitSchedule::parse("weekdays").unwrap();
+    storage
+        .create_habit("stretch", &stretch, device_id)
+        .expect("create habit should succeed");
+    storage
+        .create_habit("run", &HabitSchedule::PerWeek(3), device_id)
+        .expect("create habit should succeed");
+    assert!(storage
+        .create_habit("run", &HabitSchedule::Daily, device_id)
+        .is_err());
+    assert!(storage
+        .create_habit("Bad Name", &HabitSchedule::Daily, device_id)
+        .is_err());
+    storage.close(passphrase).expect("close should succeed");
+
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let names: Vec<String> = storage
+        .list_habits()
+        .expect("list should succeed")
+        .into_iter()
+        .map(|habit| habit.name)
+        .collect();
+    assert_eq!(names, vec!["run", "stretch"]);
+    let habit = storage
+        .get_habit("stretch")
+        .expect("get should succeed")
+        .expect("habit should exist");
+    assert_eq!(habit.schedule, stretch);
+
+    storage.delete_habit("run").expect("delete should succeed");
+    assert!(storage
+        .get_habit("run")
+        .expect("get should succeed")
+        .is_none());
+    assert!(storage.delete_habit("run").is_err());
+}
+
+#[test]
+fn test_pack_records_round_trip() {
+    let temp = TempFile::new("ledger_packs");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let pack = InstalledPack {
+        name: "fitness".to_string(),
+        version: "1.0".to_string(),
+        description: Some("Workouts".to_string()),
+        entry_types: vec!["workout".to_string()],
+        templates: vec![],
+        installed_at: chrono::Utc::now(),
+        device_id: Uuid::new_v4(),
+    };
+    storage.record_pack(&pack).expect("record should succeed");
+    assert!(storage
+        .record_pack(&InstalledPack {
+            name: "Fitness Pack".to_string(),
+            ..pack.clone()
+        })
+        .is_err());
+    storage.close(passphrase).expect("close should succeed");
+
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    assert_eq!(
+        storage.list_packs().expect("list should succeed"),
+        vec![pack.clone()]
+    );
+
+    // Reinstalling replaces the record
+    let upgraded = InstalledPack {
+        version: "1.1".to_string(),
+        templates: vec!["leg-day".to_string()],
+        ..pack
+    };
+    storage
+        .record_pack(&upgraded)
+        .expect("record should succeed");
+    assert_eq!(
+        storage.get_pack("fitness").expect("get should succeed"),
+        Some(upgraded)
+    );
+    assert_eq!(storage.list_packs().unwrap().len(), 1);
+    assert!(storage.get_pack("gardening").unwrap().is_none());
+}
+
+#[test]
+fn test_sync_peers_round_trip_and_track_each_direction() {
+    let temp = TempFile::new("ledger_sync_peers");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let (own, backdated) = {
+        let mut storage =
+            AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+        let own = storage.device_fingerprint();
+        assert_eq!(own.len(), 19);
+        let peer = storage
+            .add_sync_peer("work", "1A2B:3C4D 5e6f-7a8b")
+            .expect("add should succeed");
+        assert_eq!(peer.fingerprint, "1a2b-3c4d-5e6f-7a8b");
+        assert!(peer.last_sent_at.is_none());
+        storage
+            .add_sync_peer("laptop", "0000-0000-0000-0001")
+            .expect("add should succeed");
+
+        assert!(storage
+            .add_sync_peer("work", "0000-0000-0000-0002")
+            .is_err());
+        assert!(storage.add_sync_peer("phone", "not-hex").is_err());
+        assert!(storage
+            .add_sync_peer("Phone", "0000-0000-0000-0003")
+            .is_err());
+        assert!(storage.add_sync_peer("self", &own).is_err());
+
+        // A backdated entry still sits after everything inserted before it
+        let entry_type_id = create_basic_entry_type(&mut storage);
+        let backdated = NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({ "body": "from last year" }),
+            Uuid::new_v4(),
+        )
+        .with_created_at(chrono::Utc::now() - chrono::Duration::days(365));
+        let backdated = storage
+            .insert_entry(&backdated)
+            .expect("insert should succeed");
+        let sent = storage.now();
+        storage
+            .mark_peer_synced("work", SyncDirection::Sent, sent)
+            .expect("mark should succeed");
+        assert!(matches!(
+            storage.mark_peer_synced("phone", SyncDirection::Received, sent),
+            Err(ledger_core::LedgerError::NotFound(_))
+        ));
+        storage.close(passphrase).expect("close should succeed");
+        (own, backdated)
+    };
+
+    let mut storage =
+        AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+    assert_eq!(storage.device_fingerprint(), own);
+    let peers = storage.list_sync_peers().expect("list should succeed");
+    let names: Vec<&str> = peers.iter().map(|peer| peer.name.as_str()).collect();
+    assert_eq!(names, vec!["laptop", "work"]);
+    let work = storage
+        .get_sync_peer("work")
+        .expect("get should succeed")
+        .expect("work peer");
+    assert!(work.last_sent_at.is_some());
+    assert!(work.last_received_at.is_none());
+    let backdated = storage
+        .get_entry(&backdated)
+        .expect("get should succeed")
+        .expect("backdated entry");
+    assert_eq!(work.last_sent_seq, Some(backdated.seq));
+    let laptop = storage
+        .get_sync_peer("laptop")
+        .expect("get should succeed")
+        .expect("laptop peer");
+    assert_eq!(laptop.last_sent_seq, None);
+
+    storage
+        .remove_sync_peer("laptop")
+        .expect("remove should succeed");
+    assert!(storage.remove_sync_peer("laptop").is_err());
+    assert!(storage
+        .get_sync_peer("laptop")
+        .expect("get should succeed")
+        .is_none());
+}
+
+#[test]
+fn test_record_access_keeps_latest_view() {
+    let temp = TempFile::new("ledger_access");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    let entry_id = storage
+        .insert_entry(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": "Idea"}),
+            device_id,
+        ))
+        .expect("insert should succeed");
+
+    assert!(storage
+        .last_accessed()
+        .expect("query should succeed")
+        .is_empty());
+    let first = chrono::Utc::now() - chrono::Duration::days(2);
+    let second = chrono::Utc::now();
+    storage
+        .record_access(&entry_id, first)
+        .expect("record should succeed");
+    storage
+        .record_access(&entry_id, second)
+        .expect("record should succeed");
+    assert!(storage.record_access(&Uuid::new_v4(), second).is_err());
+    storage.close(passphrase).expect("close should succeed");
+
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let accessed = storage.last_accessed().expect("query should succeed");
+    assert_eq!(accessed.len(), 1);
+    assert_eq!(accessed[&entry_id], second);
+}
+
+#[test]
+fn test_composition_stats_and_template_usage() {
+    let temp = TempFile::new("ledger_usage_counts");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    let at = |day: u32| {
+        chrono::DateTime::parse_from_rfc3339(&format!("2024-03-{:02}T09:00:00Z", day))
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+    };
+    let mut insert = |day: u32, supersedes: Option<Uuid>| {
+        let mut entry = NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"body": format!("Day {}", day)}),
+            device_id,
+        )
+        .with_created_at(at(day));
+        if let Some(id) = supersedes {
+            entry = entry.with_supersedes(id);
+        }
+        storage.insert_entry(&entry).expect("insert should succeed")
+    };
+    let first = insert(1, None);
+    let second = insert(2, None);
+    let revised = insert(3, Some(second));
+
+    let studio = storage
+        .create_composition(&NewComposition::new("studio", device_id))
+        .expect("create composition");
+    let empty = storage
+        .create_composition(&NewComposition::new("empty", device_id))
+        .expect("create composition");
+    for id in [first, second, revised] {
+        storage
+            .attach_entry_to_composition(&id, &studio)
+            .expect("attach");
+    }
+    let stats = storage.composition_stats().expect("stats");
+    // The superseded revision no longer counts
+    assert_eq!(stats[&studio].entry_count, 2);
+    assert_eq!(stats[&studio].last_activity, Some(at(3)));
+    assert!(!stats.contains_key(&empty));
+
+    let template =
+        |name: &str| NewTemplate::new(name, entry_type_id, serde_json::json!({}), device_id);
+    let daily = storage
+        .create_template(&template("daily"))
+        .expect("create template");
+    let unused = storage
+        .create_template(&template("unused"))
+        .expect("create template");
+    storage
+        .set_default_template(&entry_type_id, &daily)
+        .expect("set default");
+    for (id, version) in [(first, 1), (second, 2)] {
+        storage
+            .record_template_use(&id, &daily, version)
+            .expect("record use");
+    }
+    assert!(storage
+        .record_template_use(&Uuid::new_v4(), &daily, 1)
+        .is_err());
+    assert!(storage
+        .record_template_use(&first, &Uuid::new_v4(), 1)
+        .is_err());
+    storage.close(passphrase).expect("close should succeed");
+
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let usage = storage.template_usage().expect("usage");
+    assert_eq!(usage[&daily].uses, 2);
+    assert_eq!(
+        usage[&daily].by_version.iter().collect::<Vec<_>>(),
+        vec![(&1, &1), (&2, &1)]
+    );
+    assert_eq!(usage[&daily].last_used, Some(at(2)));
+    assert!(usage[&daily].is_default);
+    assert_eq!(usage[&unused].uses, 0);
+    assert_eq!(usage[&unused].last_used, None);
+    assert!(!usage[&unused].is_default);
+
+    // The edited revision reports the template of the entry it replaced
+    let from = storage
+        .entry_template(&revised)
+        .expect("entry template")
+        .expect("template recorded");
+    assert_eq!(
+        (from.id, from.name.as_str(), from.version),
+        (daily, "daily", Some(2))
+    );
+    assert!(storage
+        .entry_template(&Uuid::new_v4())
+        .expect("entry template")
+        .is_none());
+
+    // Entries created from it keep it from being deleted
+    assert_eq!(
+        storage.template_entries(&daily).expect("template entries"),
+        {
+            let mut ids = vec![first, second];
+            ids.sort_by_key(|id| id.to_string());
+            ids
+        }
+    );
+    let err = storage
+        .delete_template(&daily)
+        .expect_err("used template is kept")
+        .to_string();
+    assert!(err.contains("create 2 entries"), "{}", err);
+    assert!(err.contains(&first.to_string()), "{}", err);
+    assert!(storage
+        .template_usage()
+        .expect("usage")
+        .contains_key(&daily));
+    storage
+        .delete_template(&unused)
+        .expect("delete unused template");
+}
+
+#[test]
+fn test_retired_entry_type_refuses_new_entries() {
+    let temp = TempFile::new("ledger_retire_type");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    let entry = |body: &str| {
+        NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({ "body": body }),
+            device_id,
+        )
+    };
+    let kept = storage
+        .insert_entry(&entry("Before"))
+        .expect("insert should succeed");
+
+    let retired_at = chrono::Utc::now();
+    storage
+        .retire_entry_type(&entry_type_id, retired_at)
+        .expect("retire");
+    storage
+        .retire_entry_type(&entry_type_id, retired_at + chrono::Duration::days(1))
+        .expect("retire again");
+    assert!(storage
+        .retire_entry_type(&Uuid::new_v4(), retired_at)
+        .is_err());
+    storage.close(passphrase).expect("close should succeed");
+
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    assert_eq!(
+        storage.retired_entry_types().expect("retired")[&entry_type_id],
+        retired_at
+    );
+    let err = storage
+        .insert_entry(&entry("After"))
+        .expect_err("retired type refuses new entries")
+        .to_string();
+    assert!(err.contains("is retired"), "{}", err);
+    // History stays readable and editable
+    assert!(storage.get_entry(&kept).unwrap().is_some());
+    storage
+        .insert_entry(&entry("Before, revised").with_supersedes(kept))
+        .expect("revision of a retired type");
+
+    assert!(storage
+        .unretire_entry_type(&entry_type_id)
+        .expect("unretire"));
+    assert!(!storage
+        .unretire_entry_type(&entry_type_id)
+        .expect("unretire"));
+    storage
+        .insert_entry(&entry("After"))
+        .expect("insert should succeed");
+}
+
+#[test]
+fn test_rebuild_search_index_switches_tokenizer() {
+    let temp = TempFile::new("ledger_tokenizer");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    for body in ["Went running by the river", "Café crème à Montréal"] {
+        storage
+            .insert_entry(&NewEntry::new(
+                entry_type_id,
+                1,
+                serde_json::json!({ "body": body }),
+                device_id,
+            ))
+            .expect("insert should succeed");
+    }
+    let hits = |storage: &AgeSqliteStorage, query: &str| {
+        storage
+            .search_entries(query)
+            .expect("search should succeed")
+            .len()
+    };
+
+    assert_eq!(storage.search_tokenizer().unwrap(), SearchTokenizer::Porter);
+    assert_eq!(hits(&storage, "run"), 1, "porter stems");
+    assert_eq!(hits(&storage, "montreal"), 1, "diacritics are ignored");
+
+    let indexed = storage
+        .rebuild_search_index(SearchTokenizer::Unicode61)
+        .expect("rebuild should succeed");
+    assert_eq!(indexed, 2);
+    assert_eq!(hits(&storage, "run"), 0);
+    assert_eq!(hits(&storage, "running"), 1);
+    assert_eq!(hits(&storage, "creme"), 1);
+    storage.close(passphrase).expect("close should succeed");
+
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    assert_eq!(
+        storage.search_tokenizer().unwrap(),
+        SearchTokenizer::Unicode61
+    );
+    storage
+        .rebuild_search_index(SearchTokenizer::Trigram)
+        .expect("rebuild should succeed");
+    assert_eq!(hits(&storage, "iver"), 1, "trigram matches substrings");
+    assert_eq!(hits(&storage, "cafe"), 1);
+    assert!(storage
+        .check_integrity()
+        .expect("check should succeed")
+        .is_empty());
+}
+
+#[test]
+fn test_chunked_search_rebuild_resumes_after_reopen() {
+    let temp = TempFile::new("ledger_chunked_rebuild");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    let entry = |body: String| {
+        NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({ "body": body }),
+            device_id,
+        )
+    };
+    for i in 0..5 {
+        storage
+            .insert_entry(&entry(format!("running note {}", i)))
+            .expect("insert should succeed");
+    }
+    assert!(storage.pending_search_rebuild().unwrap().is_none());
+
+    let started = storage
+        .begin_search_rebuild(SearchTokenizer::Unicode61)
+        .expect("begin should succeed");
+    assert_eq!((started.indexed, started.total), (0, 5));
+    let progress = storage
+        .continue_search_rebuild(2)
+        .expect("chunk should succeed");
+    assert_eq!((progress.indexed, progress.total), (2, 5));
+    // Entries added mid-rebuild are indexed on insert, not by the rebuild
+    storage
+        .insert_entry(&entry("running late".to_string()))
+        .expect("insert should succeed");
+    let issues = storage.check_integrity().expect("check should succeed");
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].code, "fts_rebuild_pending");
+    storage.close(passphrase).expect("close should succeed");
+
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let pending = storage
+        .pending_search_rebuild()
+        .unwrap()
+        .expect("rebuild should still be pending");
+    assert_eq!(pending.tokenizer, SearchTokenizer::Unicode61);
+    assert_eq!((pending.indexed, pending.total), (2, 5));
+    let resumed = storage
+        .begin_search_rebuild(SearchTokenizer::Unicode61)
+        .expect("resume should succeed");
+    assert_eq!(resumed, pending);
+    let done = storage
+        .continue_search_rebuild(100)
+        .expect("chunk should succeed");
+    assert!(done.is_complete());
+    assert_eq!((done.indexed, done.total), (5, 5));
+    assert!(storage.pending_search_rebuild().unwrap().is_none());
+    assert_eq!(storage.search_entries("running").unwrap().len(), 6);
+    assert_eq!(storage.search_entries("run").unwrap().len(), 0);
+    assert!(storage
+        .check_integrity()
+        .expect("check should succeed")
+        .is_empty());
+}
+
+#[test]
+fn test_unindexed_fields_stay_out_of_search() {
+    let temp = TempFile::new("ledger_unindexed");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+
+    let invalid = serde_json::json!({
+        "fields": [{"name": "url", "type": "string", "index": "no"}]
+    });
+    let err = storage
+        .create_entry_type(&NewEntryType::new("bad", invalid, device_id))
+        .expect_err("non-boolean index should be rejected");
+    assert!(err.to_string().contains("Invalid index for field url"));
+
+    let schema = serde_json::json!({
+        "fields": [
+            {"name": "title", "type": "string", "required": true},
+            {"name": "url", "type": "string", "index": false},
+            {"name": "notes", "type": "text", "index": true}
+        ]
+    });
+    let entry_type_id = storage
+        .create_entry_type(&NewEntryType::new("link", schema, device_id))
+        .expect("create entry type should succeed");
+    storage
+        .insert_entry(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({
+                "title": "Rust book",
+                "url": "https://example.com/secretpath",
+                "notes": "ownership chapter"
+            }),
+            device_id,
+        ))
+        .expect("insert should succeed");
+    let hits = |storage: &AgeSqliteStorage, query: &str| {
+        storage
+            .search_entries(query)
+            .expect("search should succeed")
+            .len()
+    };
+
+    assert_eq!(hits(&storage, "ownership"), 1);
+    assert_eq!(hits(&storage, "secretpath"), 0);
+    storage
+        .rebuild_search_index(SearchTokenizer::Unicode61)
+        .expect("rebuild should succeed");
+    assert_eq!(hits(&storage, "book"), 1);
+    assert_eq!(
+        hits(&storage, "secretpath"),
+        0,
+        "rebuild keeps the exclusion"
+    );
+}
+
+#[test]
+fn test_bytes_fields_hold_capped_base64() {
+    let temp = TempFile::new("ledger_bytes_field");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let device_id = Uuid::new_v4();
+
+    let misplaced = serde_json::json!({
+        "fields": [{"name": "label", "type": "string", "max_bytes": 16}]
+    });
+    let err = storage
+        .create_entry_type(&NewEntryType::new("bad", misplaced, device_id))
+        .expect_err("max_bytes needs a bytes field");
+    assert!(err.to_string().contains("not a bytes field"));
+
+    let schema = serde_json::json!({
+        "fields": [
+            {"name": "label", "type": "string", "required": true},
+            {"name": "qr", "type": "bytes", "max_bytes": 4}
+        ]
+    });
+    let entry_type_id = storage
+        .create_entry_type(&NewEntryType::new("code", schema, device_id))
+        .expect("create entry type should succeed");
+    let insert = |storage: &mut AgeSqliteStorage, qr: &str| {
+        storage.insert_entry(&NewEntry::new(
+            entry_type_id,
+            1,
+            serde_json::json!({"label": "wifi", "qr": qr}),
+            device_id,
+        ))
+    };
+
+    // "AAEC/w==" decodes to 4 bytes; "AAECAwQ=" to 5
+    insert(&mut storage, "AAEC/w==").expect("4 bytes should fit");
+    let err = insert(&mut storage, "AAECAwQ=").expect_err("5 bytes should not fit");
+    assert!(
+        matches!(err, ledger_core::LedgerError::SchemaViolation { ref field, .. } if field == "qr")
+    );
+    assert!(err.to_string().contains("5 bytes (max 4)"));
+    let err = insert(&mut storage, "not base64!").expect_err("must be base64");
+    assert!(err.to_string().contains("must be base64"));
+
+    let hits = |query: &str| storage.search_entries(query).expect("search").len();
+    assert_eq!(hits("wifi"), 1);
+    assert_eq!(hits("AAEC"), 0, "bytes fields stay out of the index");
+}
+
+#[test]
+fn test_trigram_search_finds_cjk_text() {
+    let temp = TempFile::new("ledger_cjk");
+    let passphrase = "test-passphrase-secure-123";
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    let entry_type_id = create_basic_entry_type(&mut storage);
+    let device_id = Uuid::new_v4();
+    for body in [
+        "今日は東京で寿司を食べました",
+        "北京今天天气很好，我们去了长城",
+        "서울에서 친구를 만났다",
+        "100% done_today",
+    ] {
+        storage
+            .insert_entry(&NewEntry::new(
+                entry_type_id,
+                1,
+                serde_json::json!({ "body": body }),
+                device_id,
+            ))
+            .expect("insert should succeed");
+    }
+    let hits = |storage: &AgeSqliteStorage, query: &str| {
+        storage
+            .search_entries(query)
+            .expect("search should succeed")
+            .len()
+    };
+
+    // Porter sees each run of CJK characters as a single word
+    assert_eq!(hits(&storage, "東京"), 0);
+
+    storage
+        .rebuild_search_index(SearchTokenizer::Trigram)
+        .expect("rebuild should succeed");
+    assert_eq!(hits(&storage, "寿司を"), 1);
+    assert_eq!(
+        hits(&storage, "东京"),
+        0,
+        "simplified and traditional differ"
+    );
+    // Two-character words are below the trigram minimum
+    assert_eq!(hits(&storage, "東京"), 1);
+    assert_eq!(hits(&storage, "天气 长城"), 1);
+    assert_eq!(hits(&storage, "天气 東京"), 0);
+    assert_eq!(hits(&storage, "친구"), 1);
+    assert_eq!(hits(&storage, "0%"), 1, "wildcards are literal");
+    assert_eq!(hits(&storage, "e_"), 1);
+    assert_eq!(hits(&storage, "x%"), 0);
+}
+
+#[test]
+fn test_reencryption_recorded_with_fresh_salt() {
+    let temp = TempFile::new("ledger_reencrypt");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let before = std::fs::read(&temp.path).expect("read ledger");
+    let mut storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+    assert!(storage
+        .last_reencrypted()
+        .expect("read should succeed")
+        .is_none());
+    let modified = storage.metadata().expect("metadata").last_modified;
+    let at = storage
+        .record_reencryption()
+        .expect("record should succeed");
+    storage.close(passphrase).expect("close should succeed");
+
+    let after = std::fs::read(&temp.path).expect("read ledger");
+    assert_ne!(
+        before[..100],
+        after[..100],
+        "header should carry a new salt"
+    );
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+    assert_eq!(
+        storage.last_reencrypted().expect("read should succeed"),
+        Some(at)
+    );
+    assert_eq!(
+        storage.metadata().expect("metadata").last_modified,
+        modified
+    );
+}
+
+#[test]
+fn test_scratchpad_persists_and_clears() {
+    let temp = TempFile::new("ledger_scratchpad");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    {
+        let mut storage =
+            AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+        assert!(storage.scratchpad().expect("read should succeed").is_none());
+        storage
+            .set_scratchpad("half a thought")
+            .expect("set should succeed");
+        storage
+            .set_scratchpad("half a thought\nand another")
+            .expect("overwrite should succeed");
+        storage.close(passphrase).expect("close should succeed");
+    }
+
+    let mut storage =
+        AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+    let scratch = storage
+        .scratchpad()
+        .expect("read should succeed")
+        .expect("scratchpad should be set");
+    assert_eq!(scratch.text, "half a thought\nand another");
+    assert!(storage
+        .check_integrity()
+        .expect("check should succeed")
+        .is_empty());
+
+    assert!(storage.clear_scratchpad().expect("clear should succeed"));
+    assert!(!storage.clear_scratchpad().expect("clear should succeed"));
+    assert!(storage.scratchpad().expect("read should succeed").is_none());
+
+    storage.set_scratchpad("x").expect("set should succeed");
+    storage.set_scratchpad("").expect("empty set should clear");
+    assert!(storage.scratchpad().expect("read should succeed").is_none());
+}
+
+#[test]
+fn test_entry_origin_recorded_and_filterable() {
+    let temp = TempFile::new("ledger_entry_origin");
+    let passphrase = "test-passphrase-secure-123";
+
+    AgeSqliteStorage::create(&temp.path, passphrase).expect("create should succeed");
+    let (plain_id, cli_id, plugin_id) = {
+        let mut storage =
+            AgeSqliteStorage::open(&temp.path, passphrase).expect("open should succeed");
+        let type_id = create_basic_entry_type(&mut storage);
+        let device_id = Uuid::new_v4();
+        let entry =
+            |body: &str| NewEntry::new(type_id, 1, serde_json::json!({ "body": body }), device_id);
+
+        let plain_id = storage
+            .insert_entry(&entry("untracked"))
+            .expect("insert should succeed");
+        storage.set_origin(Some(
+            EntryOrigin::new("ledger-cli")
+                .with_version("1.2.3")
+                .with_command("add")
+                .with_hostname("desk"),
+        ));
+        let cli_id = storage
+            .insert_entry(&entry("from the cli"))
+            .expect("insert should succeed");
+        storage.set_origin(Some(EntryOrigin::new("vim-ledger").with_command("api add")));
+        let plugin_id = storage
+            .insert_entry(&entry("from a plugin"))
+            .expect("insert should succeed");
+        storage.close(passphrase).expect("close should succeed");
+        (plain_id, cli_id, plugin_id)
+    };
+
+    let storage = AgeSqliteStorage::open(&temp.path, passphrase).expect("reopen should succeed");
+    assert!(storage
+        .entry_origin(&plain_id)
+        .expect("read should succeed")
+        .is_none());
+    let origin = storage
+        .entry_origin(&cli_id)
+        .expect("read should succeed")
+        .expect("origin should be recorded");
+    assert_eq!(origin.app, "ledger-cli");
+    assert_eq!(origin.version.as_deref(), Some("1.2.3"));
+    assert_eq!(origin.hostname.as_deref(), Some("desk"));
+
+    let ids = |filter: EntryFilter| -> Vec<Uuid> {
+        storage
+            .list_entries(&filter)
+            .expect("list should succeed")
+            .iter()
+            .map(|entry| entry.id)
+            .collect()
+    };
+    assert_eq!(
+        ids(EntryFilter::new().origin("vim-ledger")),
+        vec![plugin_id]
+    );
+    assert_eq!(ids(EntryFilter::new().origin("desk")), vec![cli_id]);
+    assert_eq!(ids(EntryFilter::new().origin("add")), vec![cli_id]);
+    assert!(ids(EntryFilter::new().origin("nowhere")).is_empty());
+}